@@ -0,0 +1,273 @@
+//! Path-aware document ids: the file and chunk span of every vector, stored in the index next
+//! to the vectors. Each vector carries the id of its file, so a search scoped to a file or
+//! directory ranks only the vectors inside it, and hits from overlapping chunks collapse,
+//! without reading the sidecars the vectors came from.
+
+use crate::{AllowList, AnnIndex, Int8Index};
+use anyhow::Result;
+use cs_core::Span;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A vector found by `DocIndex::search`
+#[derive(Debug, Clone)]
+pub struct DocHit {
+    pub id: u32,
+    /// Id of the vector's file, a position in `DocIndex::files`
+    pub file: u32,
+    pub span: Span,
+    pub score: f32,
+}
+
+/// An ANN index whose ids are positions in its file id and span columns
+#[derive(Serialize, Deserialize)]
+pub struct DocIndex<I = Int8Index> {
+    index: I,
+    /// Paths relative to the index root, `/`-separated; a file id is a position here
+    files: Vec<String>,
+    /// File id of each vector
+    file_ids: Vec<u32>,
+    /// Chunk span of each vector
+    spans: Vec<Span>,
+}
+
+impl<I: AnnIndex + Default> Default for DocIndex<I> {
+    fn default() -> Self {
+        Self {
+            index: I::default(),
+            files: Vec::new(),
+            file_ids: Vec::new(),
+            spans: Vec::new(),
+        }
+    }
+}
+
+impl<I: AnnIndex + Default> DocIndex<I> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<I: AnnIndex> DocIndex<I> {
+    /// Register a file and return its id; add its vectors with `add`
+    pub fn add_file(&mut self, path: &str) -> u32 {
+        self.files.push(path.trim_start_matches("./").to_string());
+        (self.files.len() - 1) as u32
+    }
+
+    /// Add a vector of the chunk at `span` in file `file` and return its id
+    pub fn add(&mut self, file: u32, span: Span, vector: &[f32]) -> Result<u32> {
+        let id = self.file_ids.len() as u32;
+        self.index.add(id, vector)?;
+        self.file_ids.push(file);
+        self.spans.push(span);
+        Ok(id)
+    }
+
+    /// Relative paths by file id
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    pub fn file(&self, file: u32) -> Option<&str> {
+        self.files.get(file as usize).map(String::as_str)
+    }
+
+    /// File id and span of vector `id`
+    pub fn doc(&self, id: u32) -> Option<(u32, &Span)> {
+        Some((
+            *self.file_ids.get(id as usize)?,
+            self.spans.get(id as usize)?,
+        ))
+    }
+
+    /// Number of vectors
+    pub fn len(&self) -> usize {
+        self.file_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.file_ids.is_empty()
+    }
+
+    /// Ids of the vectors whose file passes `allowed`, which sees each relative path once
+    pub fn allow_files(&self, allowed: impl Fn(&str) -> bool) -> AllowList {
+        let files: Vec<bool> = self.files.iter().map(|path| allowed(path)).collect();
+        AllowList::from_file_ids(&self.file_ids, |file| files[file as usize])
+    }
+
+    /// Ids of the vectors whose file passes `file_allowed` and whose chunk passes
+    /// `doc_allowed`, which is asked only about chunks of allowed files
+    pub fn allow(
+        &self,
+        file_allowed: impl Fn(&str) -> bool,
+        doc_allowed: impl Fn(&str, &Span) -> bool,
+    ) -> AllowList {
+        let files: Vec<bool> = self.files.iter().map(|path| file_allowed(path)).collect();
+        (0..self.len() as u32)
+            .filter(|&id| {
+                let file = self.file_ids[id as usize] as usize;
+                files[file] && doc_allowed(&self.files[file], &self.spans[id as usize])
+            })
+            .collect()
+    }
+
+    /// Ids of the vectors under `scope`, a relative file or directory path; `""` or `"."`
+    /// covers everything
+    pub fn allow_scope(&self, scope: &str) -> AllowList {
+        let scope = scope.trim_start_matches("./").trim_end_matches('/');
+        self.allow_files(|path| in_scope(path, scope))
+    }
+
+    /// Best `topk` vectors among `allowed`, ranked within them. A hit whose span overlaps a
+    /// better hit in the same file is dropped, so strides of one long definition do not fill
+    /// the results.
+    pub fn search(&self, query: &[f32], topk: usize, allowed: &AllowList) -> Result<Vec<DocHit>> {
+        let ranked = self.index.search_filtered(query, allowed.len(), allowed)?;
+
+        let mut hits: Vec<DocHit> = Vec::with_capacity(topk.min(ranked.len()));
+        for (id, score) in ranked {
+            if hits.len() == topk {
+                break;
+            }
+            let Some((file, span)) = self.doc(id) else {
+                continue;
+            };
+            let overlaps_better = hits.iter().any(|hit| {
+                hit.file == file
+                    && hit.span.byte_start < span.byte_end
+                    && span.byte_start < hit.span.byte_end
+            });
+            if !overlaps_better {
+                hits.push(DocHit {
+                    id,
+                    file,
+                    span: span.clone(),
+                    score,
+                });
+            }
+        }
+        Ok(hits)
+    }
+}
+
+impl<I> DocIndex<I>
+where
+    I: AnnIndex + Serialize + for<'de> Deserialize<'de>,
+{
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, bincode::serialize(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(bincode::deserialize(&std::fs::read(path)?)?)
+    }
+}
+
+/// Whether `path` is `scope` or lies under it, matching whole components
+fn in_scope(path: &str, scope: &str) -> bool {
+    if scope.is_empty() || scope == "." {
+        return true;
+    }
+    path.strip_prefix(scope)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleIndex;
+    use tempfile::TempDir;
+
+    fn build<I: AnnIndex + Default>() -> DocIndex<I> {
+        let mut index = DocIndex::new();
+        let chunks = [
+            ("src/lib.rs", 0, 100, [1.0, 0.0]),
+            // A stride of the chunk above
+            ("src/lib.rs", 80, 180, [0.99, 0.05]),
+            ("./src/main.rs", 0, 50, [0.9, 0.3]),
+            ("src/small/one.rs", 0, 40, [0.1, 1.0]),
+            ("src/smaller.rs", 0, 40, [0.5, 0.5]),
+        ];
+        let mut file = None;
+        for (path, start, end, vector) in chunks {
+            if file.and_then(|file| index.file(file)) != Some(path.trim_start_matches("./")) {
+                file = Some(index.add_file(path));
+            }
+            let span = Span::new(start, end, 1, 1).unwrap();
+            index.add(file.unwrap(), span, &vector).unwrap();
+        }
+        index
+    }
+
+    fn paths<I: AnnIndex>(index: &DocIndex<I>, hits: Vec<DocHit>) -> Vec<(&str, usize)> {
+        hits.into_iter()
+            .map(|hit| (index.file(hit.file).unwrap(), hit.span.byte_start))
+            .collect()
+    }
+
+    #[test]
+    fn test_doc_index_scopes_and_collapses_overlaps() {
+        let index = build::<SimpleIndex>();
+        let query = [1.0, 0.0];
+
+        let everything = index.allow_scope(".");
+        assert_eq!(everything.len(), 5);
+        assert_eq!(
+            paths(&index, index.search(&query, 2, &everything).unwrap()),
+            vec![("src/lib.rs", 0), ("src/main.rs", 0)]
+        );
+        // `src/small` does not take in `src/smaller.rs`
+        let small = index.allow_scope("src/small/");
+        assert_eq!(
+            paths(&index, index.search(&query, 2, &small).unwrap()),
+            vec![("src/small/one.rs", 0)]
+        );
+        let main = index.allow_scope("./src/main.rs");
+        assert_eq!(
+            paths(&index, index.search(&query, 1, &main).unwrap()),
+            vec![("src/main.rs", 0)]
+        );
+        let docs = index.allow_scope("docs");
+        assert!(index.search(&query, 3, &docs).unwrap().is_empty());
+
+        let rust = index.allow_files(|path| path.ends_with("er.rs"));
+        assert_eq!(
+            paths(&index, index.search(&query, 3, &rust).unwrap()),
+            vec![("src/smaller.rs", 0)]
+        );
+        // Leaving out the first chunk of `src/lib.rs` lets its stride through
+        let strides = index.allow(|path| path == "src/lib.rs", |_, span| span.byte_start > 0);
+        assert_eq!(
+            paths(&index, index.search(&query, 3, &strides).unwrap()),
+            vec![("src/lib.rs", 80)]
+        );
+    }
+
+    #[test]
+    fn test_doc_index_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("docs.bin");
+        let mut index = build::<Int8Index>();
+        let readme = index.add_file("README.md");
+        let id = index
+            .add(readme, Span::new(0, 10, 1, 1).unwrap(), &[0.0, 1.0])
+            .unwrap();
+        assert_eq!(id, 5);
+        index.save(&path).unwrap();
+
+        let loaded = DocIndex::<Int8Index>::load(&path).unwrap();
+        assert_eq!(loaded.len(), 6);
+        assert_eq!(loaded.files().len(), 5);
+        let (file, span) = loaded.doc(5).unwrap();
+        assert_eq!(
+            (loaded.file(file).unwrap(), span.byte_end),
+            ("README.md", 10)
+        );
+        let hits = loaded
+            .search(&[0.0, 1.0], 1, &loaded.allow_scope("README.md"))
+            .unwrap();
+        assert_eq!(hits[0].id, 5);
+    }
+}
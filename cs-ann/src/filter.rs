@@ -0,0 +1,63 @@
+//! Allow-lists for filtered search. A search restricted to a subtree or file has to rank only
+//! the vectors inside it; ranking everything and filtering afterwards can leave a small subtree
+//! with no results at all.
+
+/// Vector ids a filtered search may return, one bit per id
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllowList {
+    bits: Vec<u64>,
+}
+
+impl AllowList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow the vectors whose file passes `allowed_file`; `file_ids[id]` is the file id
+    /// stored for vector `id`
+    pub fn from_file_ids(file_ids: &[u32], allowed_file: impl Fn(u32) -> bool) -> Self {
+        let mut list = Self::new();
+        for (id, &file) in file_ids.iter().enumerate() {
+            if allowed_file(file) {
+                list.allow(id as u32);
+            }
+        }
+        list
+    }
+
+    pub fn allow(&mut self, id: u32) {
+        let (word, bit) = (id as usize / 64, id % 64);
+        if word >= self.bits.len() {
+            self.bits.resize(word + 1, 0);
+        }
+        self.bits[word] |= 1 << bit;
+    }
+
+    pub fn allows(&self, id: u32) -> bool {
+        self.bits
+            .get(id as usize / 64)
+            .is_some_and(|word| word & (1 << (id % 64)) != 0)
+    }
+
+    /// Number of allowed ids
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|word| *word == 0)
+    }
+}
+
+impl FromIterator<u32> for AllowList {
+    fn from_iter<I: IntoIterator<Item = u32>>(ids: I) -> Self {
+        let mut list = Self::new();
+        for id in ids {
+            list.allow(id);
+        }
+        list
+    }
+}
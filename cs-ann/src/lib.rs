@@ -2,11 +2,26 @@ use anyhow::{Result, bail};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+pub mod docs;
+pub mod filter;
+pub mod quantized;
+
+pub use docs::{DocHit, DocIndex};
+pub use filter::AllowList;
+pub use quantized::{Int8Index, rerank};
+
 pub trait AnnIndex: Send + Sync {
     fn build(vectors: &[Vec<f32>]) -> Result<Self>
     where
         Self: Sized;
     fn search(&self, query: &[f32], topk: usize) -> Result<Vec<(u32, f32)>>;
+    /// Best `topk` among the vectors `allowed` contains, so a narrow filter still fills `topk`
+    fn search_filtered(
+        &self,
+        query: &[f32],
+        topk: usize,
+        allowed: &AllowList,
+    ) -> Result<Vec<(u32, f32)>>;
     fn add(&mut self, id: u32, vector: &[f32]) -> Result<()>;
     fn save(&self, path: &Path) -> Result<()>;
     fn load(path: &Path) -> Result<Self>
@@ -14,11 +29,14 @@ pub trait AnnIndex: Send + Sync {
         Self: Sized;
 }
 
-pub fn create_index(_backend: Option<&str>) -> Result<Box<dyn AnnIndex>> {
-    Ok(Box::new(SimpleIndex::new()?))
+pub fn create_index(backend: Option<&str>) -> Result<Box<dyn AnnIndex>> {
+    match backend {
+        Some("int8") => Ok(Box::new(Int8Index::new())),
+        _ => Ok(Box::new(SimpleIndex::new()?)),
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct SimpleIndex {
     vectors: Vec<Vec<f32>>,
     ids: Vec<u32>,
@@ -34,6 +52,56 @@ impl SimpleIndex {
         })
     }
 
+    /// Number of vectors in the index
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// Vector dimensions; 0 for an empty index
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    fn ranked(
+        &self,
+        query: &[f32],
+        topk: usize,
+        allowed: Option<&AllowList>,
+    ) -> Result<Vec<(u32, f32)>> {
+        if self.dim == 0 {
+            bail!(
+                "The ANN index is empty. Reindex the repository before running semantic search (`cc --index`)."
+            );
+        }
+
+        if query.len() != self.dim {
+            bail!(
+                "Embedding size mismatch during search: this index stores vectors with {expected} values, but the query provided {actual}. This happens when different embedding models are mixed. Re-run the command with the original model or clean the index (`cc --clean .`) and rebuild with a single model.",
+                expected = self.dim,
+                actual = query.len()
+            );
+        }
+
+        let mut similarities: Vec<_> = self
+            .vectors
+            .iter()
+            .zip(&self.ids)
+            .filter(|(_, id)| allowed.is_none_or(|allowed| allowed.allows(**id)))
+            .map(|(vector, &id)| {
+                let similarity = self.cosine_similarity(query, vector);
+                (id, similarity)
+            })
+            .collect();
+
+        similarities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        similarities.truncate(topk);
+        Ok(similarities)
+    }
+
     fn cosine_similarity(&self, a: &[f32], b: &[f32]) -> f32 {
         let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
         let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
@@ -82,33 +150,16 @@ impl AnnIndex for SimpleIndex {
     }
 
     fn search(&self, query: &[f32], topk: usize) -> Result<Vec<(u32, f32)>> {
-        if self.dim == 0 {
-            bail!(
-                "The ANN index is empty. Reindex the repository before running semantic search (`cc --index`)."
-            );
-        }
-
-        if query.len() != self.dim {
-            bail!(
-                "Embedding size mismatch during search: this index stores vectors with {expected} values, but the query provided {actual}. This happens when different embedding models are mixed. Re-run the command with the original model or clean the index (`cc --clean .`) and rebuild with a single model.",
-                expected = self.dim,
-                actual = query.len()
-            );
-        }
-
-        let mut similarities: Vec<_> = self
-            .vectors
-            .iter()
-            .zip(&self.ids)
-            .map(|(vector, &id)| {
-                let similarity = self.cosine_similarity(query, vector);
-                (id, similarity)
-            })
-            .collect();
+        self.ranked(query, topk, None)
+    }
 
-        similarities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        similarities.truncate(topk);
-        Ok(similarities)
+    fn search_filtered(
+        &self,
+        query: &[f32],
+        topk: usize,
+        allowed: &AllowList,
+    ) -> Result<Vec<(u32, f32)>> {
+        self.ranked(query, topk, Some(allowed))
     }
 
     fn add(&mut self, id: u32, vector: &[f32]) -> Result<()> {
@@ -315,6 +366,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_search_filtered_ranks_within_allow_list() {
+        // Vectors 0-7 sit near the query; 8-9 (the "small subtree") are far from it
+        let mut vectors: Vec<Vec<f32>> = (0..8).map(|i| vec![1.0, i as f32 * 0.01]).collect();
+        vectors.push(vec![0.0, 1.0]);
+        vectors.push(vec![0.2, 1.0]);
+        let file_ids = [0, 0, 0, 0, 1, 1, 1, 1, 2, 2];
+        let allowed = AllowList::from_file_ids(&file_ids, |file| file == 2);
+        assert_eq!(allowed.len(), 2);
+        assert!(allowed.allows(9) && !allowed.allows(0) && !allowed.allows(64));
+
+        let query = [1.0, 0.0];
+        let simple = SimpleIndex::build(&vectors).unwrap();
+        let int8 = Int8Index::build(&vectors).unwrap();
+        for index in [&simple as &dyn AnnIndex, &int8] {
+            assert!(
+                index
+                    .search(&query, 2)
+                    .unwrap()
+                    .iter()
+                    .all(|(id, _)| *id < 8)
+            );
+            let ids: Vec<u32> = index
+                .search_filtered(&query, 2, &allowed)
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            assert_eq!(ids, vec![9, 8]);
+            assert!(
+                index
+                    .search_filtered(&query, 2, &AllowList::new())
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+    }
+
     #[test]
     fn test_create_index() {
         let _index = create_index(None).unwrap();
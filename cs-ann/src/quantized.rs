@@ -0,0 +1,275 @@
+//! Int8 scalar quantization: each vector is normalized and stored as one signed byte per
+//! dimension plus a scale, a quarter of the memory of `f32`. Scores are approximate, so
+//! searches scan the codes for a wider pool of candidates and re-score those at full
+//! precision (`rerank`).
+
+use crate::{AllowList, AnnIndex};
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Int8Index {
+    dim: usize,
+    /// `dim` codes per vector, back to back
+    codes: Vec<i8>,
+    /// Multiplier turning a vector's codes back into its normalized components
+    scales: Vec<f32>,
+    ids: Vec<u32>,
+}
+
+impl Int8Index {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of vectors in the index
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Vector dimensions; 0 for an empty index
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Bytes held by codes, scales and ids
+    pub fn memory_bytes(&self) -> usize {
+        self.codes.len() + self.scales.len() * 4 + self.ids.len() * 4
+    }
+
+    /// Approximate cosine similarity between `query` and the vector at `position` (insertion
+    /// order, not id)
+    pub fn similarity(&self, position: usize, query: &[f32]) -> f32 {
+        let query_norm = norm(query);
+        if query_norm == 0.0 || query.len() != self.dim {
+            return 0.0;
+        }
+        let codes = &self.codes[position * self.dim..(position + 1) * self.dim];
+        let dot: f32 = codes
+            .iter()
+            .zip(query)
+            .map(|(code, value)| f32::from(*code) * value)
+            .sum();
+        dot * self.scales[position] / query_norm
+    }
+
+    fn ranked(
+        &self,
+        query: &[f32],
+        topk: usize,
+        allowed: Option<&AllowList>,
+    ) -> Result<Vec<(u32, f32)>> {
+        if self.dim == 0 {
+            bail!(
+                "The ANN index is empty. Reindex the repository before running semantic search (`cc --index`)."
+            );
+        }
+        if query.len() != self.dim {
+            bail!(
+                "Embedding size mismatch during search: this index stores vectors with {} values, but the query provided {}. Re-run the command with the original model or clean the index (`cc --clean .`) and rebuild with a single model.",
+                self.dim,
+                query.len()
+            );
+        }
+        let mut scores: Vec<(u32, f32)> = self
+            .ids
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| allowed.is_none_or(|allowed| allowed.allows(**id)))
+            .map(|(position, &id)| (id, self.similarity(position, query)))
+            .collect();
+        scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scores.truncate(topk);
+        Ok(scores)
+    }
+
+    fn push(&mut self, id: u32, vector: &[f32]) -> Result<()> {
+        if self.dim == 0 {
+            if vector.is_empty() {
+                bail!(
+                    "Embedding vectors are empty. The embedding model returned 0 values per vector. Re-run the command with a supported embedding model or rebuild the index."
+                );
+            }
+            self.dim = vector.len();
+        }
+        if vector.len() != self.dim {
+            bail!(
+                "Embedding size mismatch while building index: expected {} values but received {}. This usually means different embedding models were mixed. Clean the index (`cc --clean .`) and rebuild with a single model.",
+                self.dim,
+                vector.len()
+            );
+        }
+
+        let vector_norm = norm(vector);
+        let largest = vector
+            .iter()
+            .fold(0.0f32, |largest, value| largest.max(value.abs()));
+        let scale = if vector_norm == 0.0 || largest == 0.0 {
+            0.0
+        } else {
+            largest / vector_norm / 127.0
+        };
+        self.codes.extend(vector.iter().map(|value| {
+            if scale == 0.0 {
+                0
+            } else {
+                (value / vector_norm / scale).round().clamp(-127.0, 127.0) as i8
+            }
+        }));
+        self.scales.push(scale);
+        self.ids.push(id);
+        Ok(())
+    }
+}
+
+impl AnnIndex for Int8Index {
+    fn build(vectors: &[Vec<f32>]) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut index = Self::new();
+        for (id, vector) in vectors.iter().enumerate() {
+            index.push(id as u32, vector)?;
+        }
+        Ok(index)
+    }
+
+    /// Approximate scores from the codes; re-score the hits with `rerank` for exact ones
+    fn search(&self, query: &[f32], topk: usize) -> Result<Vec<(u32, f32)>> {
+        self.ranked(query, topk, None)
+    }
+
+    fn search_filtered(
+        &self,
+        query: &[f32],
+        topk: usize,
+        allowed: &AllowList,
+    ) -> Result<Vec<(u32, f32)>> {
+        self.ranked(query, topk, Some(allowed))
+    }
+
+    fn add(&mut self, id: u32, vector: &[f32]) -> Result<()> {
+        self.push(id, vector)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, bincode::serialize(self)?)?;
+        Ok(())
+    }
+
+    fn load(path: &Path) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(bincode::deserialize(&std::fs::read(path)?)?)
+    }
+}
+
+/// Second stage of a quantized search: exact cosine similarity of each candidate whose
+/// full-precision vector `exact` can supply, best `topk` first
+pub fn rerank(
+    query: &[f32],
+    candidates: &[u32],
+    exact: impl Fn(u32) -> Option<Vec<f32>>,
+    topk: usize,
+) -> Vec<(u32, f32)> {
+    let query_norm = norm(query);
+    let mut scores: Vec<(u32, f32)> = candidates
+        .iter()
+        .filter_map(|&id| {
+            let vector = exact(id)?;
+            let vector_norm = norm(&vector);
+            if query_norm == 0.0 || vector_norm == 0.0 || vector.len() != query.len() {
+                return Some((id, 0.0));
+            }
+            let dot: f32 = query.iter().zip(&vector).map(|(a, b)| a * b).sum();
+            Some((id, dot / (query_norm * vector_norm)))
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scores.truncate(topk);
+    scores
+}
+
+fn norm(vector: &[f32]) -> f32 {
+    vector.iter().map(|value| value * value).sum::<f32>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random vectors in [-1, 1)
+    fn vectors(count: usize, dim: usize) -> Vec<Vec<f32>> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        (0..count)
+            .map(|_| {
+                (0..dim)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        (state % 2000) as f32 / 1000.0 - 1.0
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_int8_scores_track_exact_cosine() {
+        let data = vectors(200, 64);
+        let index = Int8Index::build(&data).unwrap();
+        assert_eq!(index.len(), 200);
+        assert!(index.memory_bytes() < 200 * 64 * 4 / 3);
+
+        let query = &vectors(201, 64)[200];
+        for (position, vector) in data.iter().enumerate() {
+            let exact = rerank(query, &[0], |_| Some(vector.clone()), 1)[0].1;
+            let approx = index.similarity(position, query);
+            assert!((exact - approx).abs() < 0.02, "{} vs {}", exact, approx);
+        }
+    }
+
+    #[test]
+    fn test_two_stage_search_finds_exact_top_hits() {
+        let data = vectors(500, 32);
+        let index = Int8Index::build(&data).unwrap();
+        let query = &data[123];
+
+        let candidates: Vec<u32> = index
+            .search(query, 40)
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let hits = rerank(query, &candidates, |id| data.get(id as usize).cloned(), 10);
+        assert_eq!(hits[0].0, 123);
+        assert!((hits[0].1 - 1.0).abs() < 1e-5);
+
+        let mut exact: Vec<(u32, f32)> = rerank(
+            query,
+            &(0..500).collect::<Vec<_>>(),
+            |id| data.get(id as usize).cloned(),
+            10,
+        );
+        exact.truncate(10);
+        assert_eq!(hits, exact);
+    }
+
+    #[test]
+    fn test_int8_rejects_mixed_dimensions() {
+        let mut index = Int8Index::new();
+        index.add(0, &[1.0, 0.0]).unwrap();
+        assert!(index.add(1, &[1.0, 0.0, 0.0]).is_err());
+        assert!(index.search(&[1.0], 1).is_err());
+        // A zero vector scores 0 instead of NaN
+        index.add(2, &[0.0, 0.0]).unwrap();
+        assert_eq!(index.similarity(1, &[1.0, 0.0]), 0.0);
+    }
+}
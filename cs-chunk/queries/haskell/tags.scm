@@ -2,6 +2,7 @@
 
 (signature) @definition.function
 (function) @definition.function
+(bind) @definition.function
 (data_type) @module
 (newtype) @module
 (type_family) @module
@@ -0,0 +1,6 @@
+; JSON chunk definitions
+
+; Top-level keys
+(document
+  (object
+    (pair) @module))
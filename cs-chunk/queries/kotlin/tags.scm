@@ -0,0 +1,9 @@
+; Kotlin chunk definitions
+
+; Functions
+(function_declaration) @definition.function
+
+; Classes, interfaces and objects
+(class_declaration) @definition.class
+(object_declaration) @definition.class
+(companion_object) @definition.class
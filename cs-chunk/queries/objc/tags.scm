@@ -0,0 +1,13 @@
+; Objective-C chunk definitions
+
+; Interfaces and implementations
+(class_interface) @definition.class
+(class_implementation) @definition.class
+
+; Protocols
+(protocol_declaration) @module
+
+; Methods and C functions
+(method_declaration) @definition.method
+(method_definition) @definition.method
+(function_definition) @definition.function
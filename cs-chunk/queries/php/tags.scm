@@ -0,0 +1,14 @@
+; PHP chunk definitions
+
+; Functions and methods
+(function_definition) @definition.function
+(method_declaration) @definition.method
+
+; Types
+(class_declaration) @definition.class
+(interface_declaration) @definition.class
+(trait_declaration) @definition.class
+(enum_declaration) @definition.class
+
+; Namespaces
+(namespace_definition) @module
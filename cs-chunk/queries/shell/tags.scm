@@ -0,0 +1,3 @@
+; Shell chunk definitions
+
+(function_definition) @definition.function
@@ -0,0 +1,11 @@
+; Swift chunk definitions
+
+; Functions, initializers and protocol requirements
+(function_declaration) @definition.function
+(init_declaration) @definition.method
+(deinit_declaration) @definition.method
+(protocol_function_declaration) @definition.method
+
+; Classes, structs, enums, actors and extensions
+(class_declaration) @definition.class
+(protocol_declaration) @definition.class
@@ -0,0 +1,5 @@
+; TOML chunk definitions
+
+; Tables and arrays of tables; top-level pairs are left to the gaps between them
+(table) @module
+(table_array_element) @module
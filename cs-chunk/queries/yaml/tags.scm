@@ -0,0 +1,8 @@
+; YAML chunk definitions
+
+; Top-level keys of each document
+(stream
+  (document
+    (block_node
+      (block_mapping
+        (block_mapping_pair) @module))))
@@ -0,0 +1,273 @@
+// Doc comments and docstrings for definition chunks
+// The doc is found per definition (rustdoc, JSDoc above `export`, Python docstrings, ...) and
+// prepended to the text embedded for each of its chunks, so natural-language queries match
+// code whose span doesn't include the comment; spans are left unchanged
+
+use serde::{Deserialize, Serialize};
+
+use crate::ParseableLanguage;
+
+/// Longest doc prepended to a chunk; longer docs keep their opening
+const MAX_DOC_CHARS: usize = 1000;
+
+/// Nodes that wrap a definition and sit between it and its doc comment
+const WRAPPERS: &[&str] = &["export_statement", "decorated_definition"];
+
+/// Doc comment or docstring of the definition a chunk belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocComment {
+    /// Comment text without markers such as `///`, `/**`, `#` or quotes
+    pub text: String,
+    /// Byte range of the comment in the source
+    pub byte_start: usize,
+    pub byte_end: usize,
+}
+
+/// The doc of the definition at `node`: its docstring for Python, otherwise the comment block
+/// directly above it (attributes and decorators may sit in between)
+pub(crate) fn find_doc(
+    node: tree_sitter::Node<'_>,
+    language: ParseableLanguage,
+    source: &str,
+) -> Option<DocComment> {
+    if language == ParseableLanguage::Python
+        && let Some(doc) = python_docstring(node, source)
+    {
+        return Some(doc);
+    }
+
+    let mut anchor = node;
+    while let Some(parent) = anchor.parent() {
+        if !WRAPPERS.contains(&parent.kind()) {
+            break;
+        }
+        anchor = parent;
+    }
+
+    // Walk up over attributes and decorators, then collect the adjacent comment lines
+    let mut comments: Vec<tree_sitter::Node<'_>> = Vec::new();
+    let mut next_start = anchor.start_byte();
+    let mut current = anchor;
+    while let Some(prev) = current.prev_sibling() {
+        if !directly_above(source, prev, next_start) {
+            break;
+        }
+        if is_comment(prev.kind()) {
+            if is_inner_doc(node_text(prev, source)) {
+                break;
+            }
+            comments.push(prev);
+        } else if !comments.is_empty() || !is_attribute(language, prev.kind()) {
+            break;
+        }
+        next_start = prev.start_byte();
+        current = prev;
+    }
+
+    let (first, last) = (comments.last()?, comments.first()?);
+    let (byte_start, byte_end) = (first.start_byte(), last.end_byte());
+    let raw = source.get(byte_start..byte_end)?;
+    let text = clean_comment(raw, language);
+    (!text.is_empty()).then_some(DocComment {
+        text,
+        byte_start,
+        byte_end,
+    })
+}
+
+fn python_docstring(node: tree_sitter::Node<'_>, source: &str) -> Option<DocComment> {
+    let definition = match node.kind() {
+        "decorated_definition" => node.child_by_field_name("definition")?,
+        _ => node,
+    };
+    if !matches!(
+        definition.kind(),
+        "function_definition" | "class_definition"
+    ) {
+        return None;
+    }
+    let body = definition.child_by_field_name("body")?;
+    let mut cursor = body.walk();
+    let first = body
+        .named_children(&mut cursor)
+        .find(|child| child.kind() != "comment")?;
+    let string = first.named_child(0)?;
+    if first.kind() != "expression_statement" || string.kind() != "string" {
+        return None;
+    }
+
+    let text = clean_lines(strip_string_quotes(node_text(string, source)).lines());
+    (!text.is_empty()).then_some(DocComment {
+        text,
+        byte_start: string.start_byte(),
+        byte_end: string.end_byte(),
+    })
+}
+
+fn node_text<'a>(node: tree_sitter::Node<'_>, source: &'a str) -> &'a str {
+    source.get(node.start_byte()..node.end_byte()).unwrap_or("")
+}
+
+fn is_comment(kind: &str) -> bool {
+    matches!(
+        kind,
+        "comment" | "line_comment" | "block_comment" | "doc_comment" | "haddock"
+    )
+}
+
+/// `//!` and `/*!` document the enclosing module, not the next item
+fn is_inner_doc(text: &str) -> bool {
+    text.starts_with("//!") || text.starts_with("/*!")
+}
+
+fn is_attribute(language: ParseableLanguage, kind: &str) -> bool {
+    match language {
+        ParseableLanguage::Rust => kind == "attribute_item",
+        ParseableLanguage::Python
+        | ParseableLanguage::TypeScript
+        | ParseableLanguage::JavaScript => kind == "decorator",
+        ParseableLanguage::CSharp => matches!(kind, "attribute_list" | "attribute"),
+        _ => false,
+    }
+}
+
+/// Whether `prev` ends on the line before `next_start`, with no blank line between them
+fn directly_above(source: &str, prev: tree_sitter::Node<'_>, next_start: usize) -> bool {
+    let Some(gap) = source.get(prev.end_byte()..next_start) else {
+        return false;
+    };
+    // Line comments may include their newline
+    let newlines = gap.matches('\n').count() + usize::from(node_text(prev, source).ends_with('\n'));
+    gap.trim().is_empty() && newlines <= 1
+}
+
+fn clean_comment(raw: &str, language: ParseableLanguage) -> String {
+    let text = clean_lines(raw.lines().map(strip_comment_markers));
+    if language == ParseableLanguage::CSharp {
+        strip_xml_tags(&text)
+    } else {
+        text
+    }
+}
+
+fn strip_comment_markers(line: &str) -> &str {
+    let mut line = line.trim();
+    line = line.strip_suffix("*/").unwrap_or(line);
+    for prefix in ["///", "//", "/**", "/*", "*", "#", "-- |", "--"] {
+        if let Some(rest) = line.strip_prefix(prefix) {
+            line = rest;
+            break;
+        }
+    }
+    line.trim()
+}
+
+fn strip_string_quotes(text: &str) -> &str {
+    let text = text.trim_start_matches(['r', 'R', 'b', 'B', 'u', 'U', 'f', 'F']);
+    for quote in ["\"\"\"", "'''", "\"", "'"] {
+        if let Some(inner) = text
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    text
+}
+
+/// Trim each line, drop blank lines at either end and cap the length
+fn clean_lines<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let lines: Vec<&str> = lines.map(str::trim).collect();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+    let text = match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].join("\n"),
+        _ => return String::new(),
+    };
+    match text.char_indices().nth(MAX_DOC_CHARS) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text,
+    }
+}
+
+fn strip_xml_tags(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => result.push(c),
+            _ => {}
+        }
+    }
+    clean_lines(result.lines())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Chunk, chunk_text, chunk_text_with_model};
+    use cs_core::Language;
+
+    fn chunk_named<'a>(chunks: &'a [Chunk], name: &str) -> &'a Chunk {
+        chunks
+            .iter()
+            .find(|chunk| chunk.metadata.name.as_deref() == Some(name))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_doc_prepended_when_outside_span() {
+        let rust = "/// Parse the retry policy\n/// from the environment.\n#[inline]\npub fn parse_policy() -> u32 {\n    1\n}\n\n//! Not a doc for the next item\nfn undocumented() {}\n";
+        let chunks = chunk_text(rust, Some(Language::Rust)).unwrap();
+        let chunk = chunk_named(&chunks, "parse_policy");
+        assert_eq!(
+            chunk.metadata.doc.as_ref().unwrap().text,
+            "Parse the retry policy\nfrom the environment."
+        );
+        assert!(
+            chunk
+                .embedding_text()
+                .starts_with("Parse the retry policy\nfrom the environment.\n#[inline]")
+        );
+        assert!(chunk_named(&chunks, "undocumented").metadata.doc.is_none());
+
+        let typescript = "/**\n * Fetch a user by id.\n */\nexport function getUser(id: string) {\n  return id;\n}\n";
+        let chunks = chunk_text(typescript, Some(Language::TypeScript)).unwrap();
+        let chunk = chunk_named(&chunks, "getUser");
+        assert_eq!(
+            chunk.metadata.doc.as_ref().unwrap().text,
+            "Fetch a user by id."
+        );
+        assert!(
+            chunk
+                .embedding_text()
+                .starts_with("Fetch a user by id.\nfunction getUser")
+        );
+    }
+
+    #[test]
+    fn test_docstring_repeated_in_later_strides() {
+        let body = "    value = compute_something(value, other_input)\n".repeat(200);
+        let python = format!(
+            "def long_function(value):\n    \"\"\"Retry the request with backoff.\"\"\"\n{}    return value\n",
+            body
+        );
+        let chunks = chunk_text_with_model(
+            &python,
+            Some(Language::Python),
+            Some("BAAI/bge-small-en-v1.5"),
+        )
+        .unwrap();
+        assert!(chunks.len() > 1);
+        // The first stride holds the docstring; the rest get it prepended
+        assert_eq!(chunks[0].embedding_text(), chunks[0].text);
+        for chunk in &chunks[1..] {
+            assert!(
+                chunk
+                    .embedding_text()
+                    .starts_with("Retry the request with backoff.\n")
+            );
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Line-based chunking for files without a parser (prose, logs, unsupported languages).
+//!
+//! Chunks grow line by line up to the token target, then end at the most natural break in
+//! the back half of that window: before a heading, after a blank line, where indentation
+//! returns to the left margin, or after a sentence. Only when the window holds none of these
+//! is the chunk cut at the target, and then the next chunk repeats the last lines of this one
+//! so the cut text keeps some context. Chunks cover the file without gaps, so their spans
+//! reconstruct it.
+
+use anyhow::Result;
+use cs_core::Span;
+
+use crate::{Chunk, ChunkMetadata, ChunkType, estimate_tokens};
+
+/// One line of the source, with its line ending
+struct Line<'a> {
+    start: usize,
+    end: usize,
+    content: &'a str,
+    tokens: usize,
+}
+
+impl Line<'_> {
+    fn is_blank(&self) -> bool {
+        self.content.trim().is_empty()
+    }
+
+    fn indent(&self) -> usize {
+        self.content.len() - self.content.trim_start().len()
+    }
+
+    /// Markdown (`# Title`) or Org (`* Title`) heading
+    fn is_heading(&self) -> bool {
+        let marker = match self.content.chars().next() {
+            Some(marker @ ('#' | '*')) => marker,
+            _ => return false,
+        };
+        self.content
+            .trim_start_matches(marker)
+            .starts_with([' ', '\t'])
+    }
+
+    fn ends_sentence(&self) -> bool {
+        self.content
+            .trim_end()
+            .trim_end_matches(['"', '\'', ')', ']'])
+            .ends_with(['.', '!', '?'])
+    }
+
+    fn closes_block(&self) -> bool {
+        let content = self.content.trim_start();
+        content.starts_with(['}', ')', ']']) || content.starts_with("end")
+    }
+}
+
+/// Chunks of about `target_tokens` each, ending at natural breaks where possible
+pub(crate) fn chunk_by_lines(
+    text: &str,
+    (target_tokens, overlap_tokens): (usize, usize),
+) -> Result<Vec<Chunk>> {
+    let lines = split_lines(text);
+    let mut chunks = Vec::new();
+
+    let mut start = 0;
+    // Each chunk ends past the previous one, even when it starts with repeated lines
+    let mut min_end = 1;
+    while start < lines.len() {
+        // Furthest end that keeps the chunk within the target
+        let mut end = start;
+        let mut tokens = 0;
+        while end < lines.len() && (end < min_end || tokens + lines[end].tokens <= target_tokens) {
+            tokens += lines[end].tokens;
+            end += 1;
+        }
+        if end == lines.len() {
+            chunks.push(make_chunk(text, &lines, start, end));
+            break;
+        }
+
+        let earliest = (start + (end - start) / 2).max(min_end);
+        let natural = (earliest..=end)
+            .filter_map(|cut| Some((break_strength(&lines, cut)?, cut)))
+            .max();
+        let cut = natural.map_or(end, |(_, cut)| cut);
+        chunks.push(make_chunk(text, &lines, start, cut));
+        min_end = cut + 1;
+
+        start = if natural.is_some() {
+            cut
+        } else {
+            overlap_start(&lines, start, cut, overlap_tokens)
+        };
+    }
+
+    Ok(chunks)
+}
+
+fn split_lines(text: &str) -> Vec<Line<'_>> {
+    let mut start = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let end = start + line.len();
+            let content = line.trim_end_matches(['\n', '\r']);
+            let line = Line {
+                start,
+                end,
+                content,
+                tokens: estimate_tokens(content).max(1),
+            };
+            start = end;
+            line
+        })
+        .collect()
+}
+
+/// How good a place it is to end a chunk before `lines[cut]`; `None` when it is mid-block
+fn break_strength(lines: &[Line<'_>], cut: usize) -> Option<u8> {
+    let previous = &lines[cut - 1];
+    let next = lines.get(cut)?;
+    if next.is_blank() {
+        // Blank lines stay with the chunk above them
+        return None;
+    }
+    if next.is_heading() && (previous.is_blank() || previous.is_heading()) {
+        Some(4)
+    } else if previous.is_blank() {
+        Some(3)
+    } else if next.indent() == 0
+        && !next.closes_block()
+        && (previous.indent() > 0 || previous.closes_block())
+    {
+        Some(2)
+    } else if previous.ends_sentence() {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Start of the chunk after a forced cut: the last lines before `cut`, about `overlap_tokens`
+/// of them, and always after `start` so chunking moves forward
+fn overlap_start(lines: &[Line<'_>], start: usize, cut: usize, overlap_tokens: usize) -> usize {
+    let mut next = cut;
+    let mut tokens = 0;
+    while next > start + 1 && (next == cut || tokens + lines[next - 1].tokens <= overlap_tokens) {
+        tokens += lines[next - 1].tokens;
+        next -= 1;
+    }
+    next
+}
+
+fn make_chunk(text: &str, lines: &[Line<'_>], start: usize, end: usize) -> Chunk {
+    let byte_start = lines[start].start;
+    let byte_end = lines[end - 1].end;
+    let chunk_text = text[byte_start..byte_end].to_string();
+    let metadata = ChunkMetadata::from_text(&chunk_text);
+    Chunk {
+        span: Span {
+            byte_start,
+            byte_end,
+            line_start: start + 1,
+            line_end: end,
+        },
+        text: chunk_text,
+        chunk_type: ChunkType::Text,
+        stride_info: None,
+        metadata,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn texts(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().map(|chunk| chunk.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_prefers_headings_over_blank_lines() {
+        let text = "# Install\n\
+                    Run the installer.\n\
+                    \n\
+                    # Usage\n\
+                    Search with cs.\n\
+                    \n\
+                    Pass --help for more.\n\
+                    The rest of the usage section.\n";
+        // A window holding everything but the last line
+        let lines = split_lines(text);
+        let target = lines.iter().map(|line| line.tokens).sum::<usize>() - lines[7].tokens;
+
+        let chunks = chunk_by_lines(text, (target, 5)).unwrap();
+        assert_eq!(
+            texts(&chunks),
+            vec![
+                "# Install\nRun the installer.\n\n",
+                "# Usage\nSearch with cs.\n\nPass --help for more.\nThe rest of the usage section.\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breaks_where_indentation_returns() {
+        let text = "fn first() {\n    one();\n    two();\n}\nfn second() {\n    three();\n}\n";
+        let chunks = chunk_by_lines(text, (14, 2)).unwrap();
+        assert_eq!(chunks[0].text, "fn first() {\n    one();\n    two();\n}\n");
+        assert_eq!(chunks[1].text, "fn second() {\n    three();\n}\n");
+    }
+
+    #[test]
+    fn test_forced_cut_overlaps_next_chunk() {
+        let text: String = (0..40).map(|i| format!("value_{} = {}\n", i, i)).collect();
+        let chunks = chunk_by_lines(&text, (40, 10)).unwrap();
+        assert!(chunks.len() > 1);
+        for pair in chunks.windows(2) {
+            assert!(pair[1].span.byte_start < pair[0].span.byte_end);
+        }
+    }
+
+    fn line() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just(String::new()),
+            Just("# Heading".to_string()),
+            Just("* Org heading".to_string()),
+            Just("}".to_string()),
+            "[a-z ]{1,60}[.!?]?",
+            "    [a-z(){};]{1,40}",
+            "[a-zA-Z0-9 ]{100,300}",
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_spans_reconstruct_the_file(
+            lines in prop::collection::vec(line(), 0..200),
+            crlf in any::<bool>(),
+            trailing_newline in any::<bool>(),
+            target in 1usize..400,
+            overlap in 0usize..100,
+        ) {
+            let separator = if crlf { "\r\n" } else { "\n" };
+            let mut text = lines.join(separator);
+            if trailing_newline && !text.is_empty() {
+                text.push_str(separator);
+            }
+
+            let chunks = chunk_by_lines(&text, (target, overlap)).unwrap();
+            prop_assert_eq!(chunks.is_empty(), text.is_empty());
+            let mut covered = 0;
+            for chunk in &chunks {
+                let span = &chunk.span;
+                prop_assert_eq!(&text[span.byte_start..span.byte_end], chunk.text.as_str());
+                prop_assert!(span.byte_start <= covered, "gap before {:?}", span);
+                prop_assert!(span.byte_end > covered, "no progress at {:?}", span);
+                prop_assert_eq!(span.line_start, text[..span.byte_start].matches('\n').count() + 1);
+                prop_assert_eq!(
+                    span.line_end,
+                    text[..span.byte_end].strip_suffix('\n').unwrap_or(&text[..span.byte_end]).matches('\n').count() + 1
+                );
+                covered = span.byte_end;
+            }
+            prop_assert_eq!(covered, text.len());
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Grouping of Haskell definitions into one chunk each.
+//!
+//! A Haskell function is spread over several top-level declarations: an optional type
+//! signature, then one equation per clause, each of which may carry guards, comments and a
+//! `where` block. The grammar yields a node per declaration, so the chunker sees them
+//! separately. A query finds the declarations, each is named from its parse tree rather than
+//! its text (so `(<+>) :: ...` and `a <+> b = ...` both define `<+>`), and adjacent
+//! declarations of the same name collapse into a single function chunk. Definitions local to
+//! an equation (`where`, `let`) stay inside it instead of becoming chunks of their own.
+
+use anyhow::{Context, Result};
+use tree_sitter::{Node, Query, QueryCursor, StreamingIterator, Tree};
+
+use crate::{Chunk, ChunkType, ParseableLanguage, tree_sitter_language};
+
+/// Declarations that can make up one function definition
+const DECLARATION_QUERY: &str = r#"
+(signature) @declaration
+(function) @declaration
+(bind) @declaration
+"#;
+
+/// Nodes that hold local definitions or type-level `function` nodes
+const ENCLOSING_DECLARATIONS: &[&str] = &["signature", "function", "bind"];
+
+/// Nodes that may sit between two declarations of one definition
+const BETWEEN_DECLARATIONS: &[&str] = &["comment", "haddock", "pragma", "cpp"];
+
+/// Merge the chunks of each multi-declaration definition and drop chunks of local definitions
+pub(crate) fn group_definitions(
+    chunks: Vec<Chunk>,
+    tree: &Tree,
+    source: &str,
+) -> Result<Vec<Chunk>> {
+    let language = tree_sitter_language(ParseableLanguage::Haskell)?;
+    let query = Query::new(&language, DECLARATION_QUERY)
+        .context("Failed to compile the Haskell declaration query")?;
+
+    let mut declarations = Vec::new();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+    while let Some(mat) = matches.next() {
+        for capture in mat.captures {
+            if !is_local(capture.node) {
+                declarations.push(capture.node);
+            }
+        }
+    }
+    declarations.sort_by_key(|node| node.start_byte());
+    declarations.dedup_by_key(|node| node.id());
+
+    // Runs of adjacent sibling declarations that define the same name
+    let mut groups: Vec<(Option<String>, Vec<Node<'_>>)> = Vec::new();
+    for node in &declarations {
+        let name = declared_name(*node, source);
+        if let Some((group_name, members)) = groups.last_mut()
+            && name.is_some()
+            && *group_name == name
+            && members.last().is_some_and(|last| follows(*last, *node))
+        {
+            members.push(*node);
+            continue;
+        }
+        groups.push((name, vec![*node]));
+    }
+
+    // Equations own their local definitions, and signatures their function types
+    let enclosing: Vec<(usize, usize)> = declarations
+        .iter()
+        .map(|node| (node.start_byte(), node.end_byte()))
+        .collect();
+    let mut chunks: Vec<Chunk> = chunks
+        .into_iter()
+        .filter(|chunk| {
+            !matches!(chunk.chunk_type, ChunkType::Function | ChunkType::Method)
+                || !enclosing.iter().any(|&(start, end)| {
+                    start < chunk.span.byte_start && chunk.span.byte_end <= end
+                })
+        })
+        .collect();
+
+    for (_, members) in groups.iter().filter(|(_, members)| members.len() > 1) {
+        let member_chunks: Vec<usize> = chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| {
+                chunk.chunk_type == ChunkType::Function
+                    && members.iter().any(|member| {
+                        chunk.span.byte_end == member.end_byte()
+                            && chunk.span.byte_start <= member.start_byte()
+                    })
+            })
+            .map(|(position, _)| position)
+            .collect();
+        let (Some(&first), Some(&last)) = (member_chunks.first(), member_chunks.last()) else {
+            continue;
+        };
+        if let Some(merged) = merge(&chunks[first], &chunks[last], source) {
+            chunks[first] = merged;
+            for &position in member_chunks[1..].iter().rev() {
+                chunks.remove(position);
+            }
+        }
+    }
+
+    Ok(chunks)
+}
+
+/// Whether `node` is local to another declaration, or a function type inside a signature
+fn is_local(node: Node<'_>) -> bool {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if ENCLOSING_DECLARATIONS.contains(&parent.kind()) {
+            return true;
+        }
+        current = parent.parent();
+    }
+    false
+}
+
+/// Name a declaration defines: `name` for prefix definitions (`f x = ...`, `(<+>) :: ...`),
+/// the operator for infix equations (``a <+> b = ...``, ``a `plus` b = ...``)
+fn declared_name(node: Node<'_>, source: &str) -> Option<String> {
+    let name = match node.child_by_field_name("name") {
+        Some(name) => name,
+        None => {
+            let infix = node.child_by_field_name("infix").or_else(|| {
+                let mut cursor = node.walk();
+                node.named_children(&mut cursor)
+                    .find(|child| child.kind() == "infix")
+            })?;
+            infix
+                .child_by_field_name("operator")
+                .or_else(|| infix.named_child(1))?
+        }
+    };
+    let text = name.utf8_text(source.as_bytes()).ok()?;
+    let name = text
+        .trim()
+        .trim_start_matches(['(', '`'])
+        .trim_end_matches([')', '`'])
+        .trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Whether `next` comes right after `previous` among its siblings, apart from comments
+fn follows(previous: Node<'_>, next: Node<'_>) -> bool {
+    let mut current = next.prev_sibling();
+    while let Some(sibling) = current {
+        if sibling.id() == previous.id() {
+            return true;
+        }
+        if !BETWEEN_DECLARATIONS.contains(&sibling.kind()) {
+            return false;
+        }
+        current = sibling.prev_sibling();
+    }
+    false
+}
+
+/// One function chunk from the start of `first` to the end of `last`
+fn merge(first: &Chunk, last: &Chunk, source: &str) -> Option<Chunk> {
+    let mut span = first.span.clone();
+    span.byte_end = last.span.byte_end;
+    span.line_end = last.span.line_end;
+    let text = source.get(span.byte_start..span.byte_end)?.to_string();
+    let mut metadata = first.metadata.with_updated_text(&text);
+    metadata.trailing_trivia = last.metadata.trailing_trivia.clone();
+    Some(Chunk {
+        span,
+        text,
+        chunk_type: ChunkType::Function,
+        stride_info: None,
+        metadata,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ChunkType, ParseableLanguage, chunk_language};
+
+    fn function_chunks(source: &str) -> Vec<String> {
+        chunk_language(source, ParseableLanguage::Haskell)
+            .unwrap()
+            .into_iter()
+            .filter(|chunk| chunk.chunk_type == ChunkType::Function)
+            .map(|chunk| chunk.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_operator_definitions_group_with_signatures() {
+        let source = r#"
+infixl 6 <+>
+
+-- | Combine two vectors component-wise.
+(<+>) :: Vec -> Vec -> Vec
+Vec a b <+> Vec c d = Vec (a + c) (b + d)
+
+(.*) :: Double -> Vec -> Vec
+(.*) k (Vec a b) = Vec (k * a) (k * b)
+
+plus :: Int -> Int -> Int
+x `plus` y = x + y
+"#;
+        let functions = function_chunks(source);
+        assert_eq!(functions.len(), 3, "{:#?}", functions);
+        assert!(functions[0].starts_with("-- | Combine two vectors"));
+        assert!(functions[0].trim_end().ends_with("Vec (a + c) (b + d)"));
+        assert!(functions[1].starts_with("(.*) :: Double"));
+        assert!(functions[1].contains("(.*) k (Vec a b)"));
+        assert!(functions[2].starts_with("plus :: Int"));
+        assert!(functions[2].contains("x `plus` y"));
+    }
+
+    #[test]
+    fn test_guards_comments_and_where_stay_in_one_chunk() {
+        let source = r#"
+classify :: Int -> String
+classify n
+  -- negative numbers first
+  | n < 0 = "negative"
+  | n == 0 = "zero"
+-- a comment between equations
+classify n = describe n
+  where
+    describe m
+      | even m = "even"
+      | otherwise = "odd"
+
+other :: Int
+other = 1
+"#;
+        let functions = function_chunks(source);
+        assert_eq!(functions.len(), 2, "{:#?}", functions);
+        assert!(functions[0].starts_with("classify :: Int -> String"));
+        assert!(functions[0].contains("| n == 0 = \"zero\""));
+        assert!(functions[0].trim_end().ends_with("| otherwise = \"odd\""));
+        assert!(functions[1].starts_with("other :: Int"));
+        assert!(functions[1].trim_end().ends_with("other = 1"));
+    }
+
+    #[test]
+    fn test_instance_methods_group_inside_instance() {
+        let source = r#"
+class Shape a where
+  area :: a -> Double
+  perimeter :: a -> Double
+
+instance Shape Rect where
+  area (Rect w h) = w * h
+  perimeter (Rect w h)
+    | w == h = 4 * w
+    | otherwise = 2 * (w + h)
+  perimeter _ = 0
+"#;
+        let chunks = chunk_language(source, ParseableLanguage::Haskell).unwrap();
+        let instance = chunks
+            .iter()
+            .find(|chunk| chunk.text.starts_with("instance Shape Rect"))
+            .expect("instance chunk");
+        assert_eq!(instance.chunk_type, ChunkType::Class);
+
+        let functions: Vec<&str> = chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type == ChunkType::Function)
+            .map(|chunk| chunk.text.as_str())
+            .collect();
+        // Two method signatures in the class, and the two methods of the instance
+        assert_eq!(functions.len(), 4, "{:#?}", functions);
+        let perimeter = functions
+            .iter()
+            .find(|text| text.starts_with("perimeter (Rect w h)"))
+            .expect("perimeter equations");
+        assert!(perimeter.trim_end().ends_with("perimeter _ = 0"));
+    }
+}
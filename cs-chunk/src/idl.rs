@@ -0,0 +1,570 @@
+//! Definition-level chunking for Protobuf and Thrift IDL files.
+//!
+//! Neither format ships with a tree-sitter grammar we depend on, but both are
+//! regular enough that a brace-aware token scan recovers the boundaries we care
+//! about: messages/structs, enums, services, and the rpc/function definitions
+//! inside services.
+
+use anyhow::Result;
+use cs_core::Span;
+
+use crate::{Chunk, ChunkMetadata, ChunkType, chunk_generic, fill_gaps};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IdlDialect {
+    Protobuf,
+    Thrift,
+}
+
+impl IdlDialect {
+    pub(crate) fn from_language(language: cs_core::Language) -> Option<Self> {
+        match language {
+            cs_core::Language::Protobuf => Some(IdlDialect::Protobuf),
+            cs_core::Language::Thrift => Some(IdlDialect::Thrift),
+            _ => None,
+        }
+    }
+
+    /// Chunk type for a block-introducing keyword such as `message` or `service`.
+    fn definition_type(self, keyword: &str) -> Option<ChunkType> {
+        match (self, keyword) {
+            (IdlDialect::Protobuf, "message" | "enum") => Some(ChunkType::Class),
+            (IdlDialect::Protobuf, "service" | "extend") => Some(ChunkType::Module),
+            (IdlDialect::Thrift, "struct" | "union" | "exception" | "enum" | "senum") => {
+                Some(ChunkType::Class)
+            }
+            (IdlDialect::Thrift, "service") => Some(ChunkType::Module),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for IdlDialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            IdlDialect::Protobuf => "protobuf",
+            IdlDialect::Thrift => "thrift",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Punct(u8),
+    Comment,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+}
+
+pub(crate) fn chunk_idl(text: &str, dialect: IdlDialect) -> Result<Vec<Chunk>> {
+    let tokens = tokenize(text, dialect);
+    let mut scanner = Scanner {
+        source: text,
+        tokens: &tokens,
+        dialect,
+        line_starts: line_starts(text),
+        chunks: Vec::new(),
+    };
+    scanner.scan_block(0, tokens.len(), &[], false);
+
+    let mut chunks = scanner.chunks;
+    if chunks.is_empty() {
+        return chunk_generic(text);
+    }
+
+    chunks.sort_by_key(|chunk| chunk.span.byte_start);
+    Ok(fill_gaps(chunks, text))
+}
+
+fn tokenize(text: &str, dialect: IdlDialect) -> Vec<Token> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        let start = i;
+
+        if b.is_ascii_whitespace() {
+            i += 1;
+        } else if bytes[i..].starts_with(b"//") || (b == b'#' && dialect == IdlDialect::Thrift) {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Comment,
+                start,
+                end: i,
+            });
+        } else if bytes[i..].starts_with(b"/*") {
+            i = text[i + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |pos| i + 2 + pos + 2);
+            tokens.push(Token {
+                kind: TokenKind::Comment,
+                start,
+                end: i,
+            });
+        } else if b == b'"' || b == b'\'' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b {
+                if bytes[i] == b'\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(bytes.len());
+            tokens.push(Token {
+                kind: TokenKind::Word,
+                start,
+                end: i,
+            });
+        } else if is_word_byte(b) {
+            while i < bytes.len() && is_word_byte(bytes[i]) {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Word,
+                start,
+                end: i,
+            });
+        } else {
+            // Step over the whole (possibly multi-byte) character
+            i += text[i..].chars().next().map_or(1, char::len_utf8);
+            tokens.push(Token {
+                kind: TokenKind::Punct(b),
+                start,
+                end: i,
+            });
+        }
+    }
+
+    tokens
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'.'
+}
+
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(pos, _)| pos + 1))
+        .collect()
+}
+
+struct Scanner<'a> {
+    source: &'a str,
+    tokens: &'a [Token],
+    dialect: IdlDialect,
+    line_starts: Vec<usize>,
+    chunks: Vec<Chunk>,
+}
+
+impl<'a> Scanner<'a> {
+    /// Scan tokens in `[start, end)`, emitting chunks for definitions found at this level
+    /// and recursing into their bodies.
+    fn scan_block(&mut self, start: usize, end: usize, ancestry: &[String], in_service: bool) {
+        let mut i = start;
+        // First token of the statement currently being read, used for Thrift service functions
+        let mut statement_start: Option<usize> = None;
+
+        while i < end {
+            let token = self.tokens[i];
+            match token.kind {
+                TokenKind::Comment => {
+                    i += 1;
+                    continue;
+                }
+                TokenKind::Punct(b'{') => {
+                    // Option literals and other non-definition blocks are skipped wholesale
+                    i = self.matching(i, b'{', b'}', end) + 1;
+                    statement_start = None;
+                    continue;
+                }
+                TokenKind::Punct(b';' | b',') => {
+                    i += 1;
+                    statement_start = None;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let stmt = *statement_start.get_or_insert(i);
+
+            if token.kind == TokenKind::Word {
+                let word = self.text(i);
+
+                if let Some(chunk_type) = self.dialect.definition_type(word)
+                    && let Some(name_idx) = self.next_code(i + 1, end)
+                    && self.tokens[name_idx].kind == TokenKind::Word
+                    && let Some(open) = self.find_block_open(name_idx + 1, end)
+                {
+                    let close = self.matching(open, b'{', b'}', end);
+                    let name = self.text(name_idx).to_string();
+                    self.push_chunk(i, close, chunk_type, Some(name.clone()), ancestry);
+
+                    let mut nested = ancestry.to_vec();
+                    nested.push(name);
+                    self.scan_block(open + 1, close, &nested, word == "service");
+
+                    i = close + 1;
+                    statement_start = None;
+                    continue;
+                }
+
+                if in_service
+                    && self.dialect == IdlDialect::Protobuf
+                    && word == "rpc"
+                    && let Some(last) = self.rpc_end(i, end)
+                {
+                    let name = self
+                        .next_code(i + 1, end)
+                        .map(|idx| self.text(idx).to_string());
+                    self.push_chunk(i, last, ChunkType::Method, name, ancestry);
+                    i = last + 1;
+                    statement_start = None;
+                    continue;
+                }
+            }
+
+            if in_service
+                && self.dialect == IdlDialect::Thrift
+                && token.kind == TokenKind::Punct(b'(')
+            {
+                let last = self.thrift_function_end(i, end);
+                // The function name is the last word before the parameter list
+                let name = (stmt..i)
+                    .rev()
+                    .find(|&idx| self.tokens[idx].kind == TokenKind::Word)
+                    .map(|idx| self.text(idx).to_string());
+                self.push_chunk(stmt, last, ChunkType::Method, name, ancestry);
+                i = last + 1;
+                statement_start = None;
+                continue;
+            }
+
+            i += 1;
+        }
+    }
+
+    /// Locate the `{` that opens a definition body, giving up at statement terminators.
+    fn find_block_open(&self, from: usize, end: usize) -> Option<usize> {
+        (from..end)
+            .find(|&idx| {
+                matches!(
+                    self.tokens[idx].kind,
+                    TokenKind::Punct(b'{' | b';' | b',' | b'=' | b'(' | b'}')
+                )
+            })
+            .filter(|&idx| self.tokens[idx].kind == TokenKind::Punct(b'{'))
+    }
+
+    /// Protobuf rpcs end at `;` or at the close of an inline options block.
+    fn rpc_end(&self, from: usize, end: usize) -> Option<usize> {
+        let mut idx = from;
+        while idx < end {
+            match self.tokens[idx].kind {
+                TokenKind::Punct(b';') => return Some(idx),
+                TokenKind::Punct(b'{') => {
+                    let close = self.matching(idx, b'{', b'}', end);
+                    return Some(match self.next_code(close + 1, end) {
+                        Some(next) if self.tokens[next].kind == TokenKind::Punct(b';') => next,
+                        _ => close,
+                    });
+                }
+                TokenKind::Punct(b'(') => idx = self.matching(idx, b'(', b')', end),
+                _ => {}
+            }
+            idx += 1;
+        }
+        None
+    }
+
+    /// Thrift functions run through their parameter list, an optional `throws (...)`
+    /// clause, and an optional `,`/`;` separator.
+    fn thrift_function_end(&self, open_paren: usize, end: usize) -> usize {
+        let mut last = self.matching(open_paren, b'(', b')', end);
+
+        if let Some(next) = self.next_code(last + 1, end)
+            && self.tokens[next].kind == TokenKind::Word
+            && self.text(next) == "throws"
+            && let Some(paren) = self.next_code(next + 1, end)
+            && self.tokens[paren].kind == TokenKind::Punct(b'(')
+        {
+            last = self.matching(paren, b'(', b')', end);
+        }
+
+        if let Some(next) = self.next_code(last + 1, end)
+            && matches!(self.tokens[next].kind, TokenKind::Punct(b',' | b';'))
+        {
+            last = next;
+        }
+
+        last
+    }
+
+    fn push_chunk(
+        &mut self,
+        first: usize,
+        last: usize,
+        chunk_type: ChunkType,
+        name: Option<String>,
+        ancestry: &[String],
+    ) {
+        let (byte_start, leading_trivia) = self.leading_comments(first);
+        let byte_end = self.tokens[last].end;
+        let text = self.source[byte_start..byte_end].to_string();
+
+        if text.trim().is_empty() {
+            return;
+        }
+
+        let mut metadata =
+            ChunkMetadata::from_context(&text, ancestry.to_vec(), leading_trivia, Vec::new());
+        metadata.name = name;
+        self.chunks.push(Chunk {
+            span: Span {
+                byte_start,
+                byte_end,
+                line_start: self.line_of(byte_start),
+                line_end: self.line_of(byte_end.saturating_sub(1)),
+            },
+            text,
+            chunk_type,
+            stride_info: None,
+            metadata,
+        });
+    }
+
+    /// Extend a definition backwards over comments that sit on their own lines directly above it.
+    fn leading_comments(&self, first: usize) -> (usize, Vec<String>) {
+        let mut start = self.tokens[first].start;
+        let mut comments = Vec::new();
+        let mut idx = first;
+
+        while idx > 0 {
+            let prev = self.tokens[idx - 1];
+            if prev.kind != TokenKind::Comment
+                || !self.source[prev.end..start].trim().is_empty()
+                || !self.starts_line(prev.start)
+            {
+                break;
+            }
+            comments.push(self.source[prev.start..prev.end].to_string());
+            start = prev.start;
+            idx -= 1;
+        }
+
+        comments.reverse();
+        (start, comments)
+    }
+
+    fn starts_line(&self, byte: usize) -> bool {
+        let line_start = self.line_starts[self.line_of(byte) - 1];
+        self.source[line_start..byte].trim().is_empty()
+    }
+
+    /// Index of the token closing the group opened at `open`, or the last token before `end`.
+    fn matching(&self, open: usize, open_byte: u8, close_byte: u8, end: usize) -> usize {
+        let mut depth = 0usize;
+        for idx in open..end {
+            match self.tokens[idx].kind {
+                TokenKind::Punct(b) if b == open_byte => depth += 1,
+                TokenKind::Punct(b) if b == close_byte => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return idx;
+                    }
+                }
+                _ => {}
+            }
+        }
+        end.saturating_sub(1).max(open)
+    }
+
+    fn next_code(&self, from: usize, end: usize) -> Option<usize> {
+        (from..end).find(|&idx| self.tokens[idx].kind != TokenKind::Comment)
+    }
+
+    fn text(&self, idx: usize) -> &'a str {
+        let token = self.tokens[idx];
+        &self.source[token.start..token.end]
+    }
+
+    fn line_of(&self, byte: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Innermost definition chunk containing `needle`.
+    fn find<'a>(chunks: &'a [Chunk], needle: &str) -> &'a Chunk {
+        chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type != ChunkType::Text && chunk.text.contains(needle))
+            .min_by_key(|chunk| chunk.text.len())
+            .unwrap_or_else(|| panic!("no definition chunk containing {needle:?}"))
+    }
+
+    #[test]
+    fn protobuf_service_and_rpc_definitions() {
+        let source = r#"syntax = "proto3";
+
+package billing.v1;
+
+// A customer invoice.
+message Invoice {
+  string id = 1;
+  message LineItem {
+    string sku = 1;
+  }
+  repeated LineItem items = 2;
+}
+
+enum Status {
+  STATUS_UNKNOWN = 0;
+}
+
+// Billing operations.
+service BillingService {
+  // Fetch a single invoice.
+  rpc GetInvoice(GetInvoiceRequest) returns (Invoice);
+  rpc StreamInvoices(ListRequest) returns (stream Invoice) {
+    option (google.api.http) = { get: "/v1/invoices" };
+  }
+}
+"#;
+
+        let chunks = chunk_idl(source, IdlDialect::Protobuf).unwrap();
+
+        let service = find(&chunks, "service BillingService");
+        assert_eq!(service.chunk_type, ChunkType::Module);
+        assert!(service.text.starts_with("// Billing operations."));
+        assert_eq!(service.span.line_start, 18);
+        assert_eq!(service.span.line_end, 25);
+
+        let get = find(&chunks, "rpc GetInvoice");
+        assert_eq!(get.chunk_type, ChunkType::Method);
+        assert_eq!(get.metadata.name.as_deref(), Some("GetInvoice"));
+        assert_eq!(get.metadata.ancestry, vec!["BillingService".to_string()]);
+        assert_eq!(
+            get.metadata.leading_trivia,
+            vec!["// Fetch a single invoice.".to_string()]
+        );
+        assert!(get.text.trim_end().ends_with("returns (Invoice);"));
+
+        let stream = find(&chunks, "rpc StreamInvoices");
+        assert_eq!(stream.chunk_type, ChunkType::Method);
+        assert!(stream.text.contains("/v1/invoices"));
+        assert!(stream.text.trim_end().ends_with('}'));
+
+        let invoice = find(&chunks, "message Invoice");
+        assert_eq!(invoice.chunk_type, ChunkType::Class);
+        assert!(invoice.text.contains("repeated LineItem items"));
+
+        let line_item = find(&chunks, "message LineItem");
+        assert_eq!(line_item.metadata.ancestry, vec!["Invoice".to_string()]);
+
+        assert_eq!(find(&chunks, "enum Status").chunk_type, ChunkType::Class);
+
+        // Header content outside definitions is still indexed
+        assert!(
+            chunks
+                .iter()
+                .any(|chunk| chunk.text.contains("package billing.v1;"))
+        );
+    }
+
+    #[test]
+    fn thrift_service_functions() {
+        let source = r#"namespace py calculator
+
+# Arithmetic failure.
+exception InvalidOperation {
+  1: i32 whatOp,
+  2: string why
+}
+
+struct Work {
+  1: i32 num1 = 0,
+  2: i32 num2,
+}
+
+service Calculator extends shared.SharedService {
+  void ping(),
+
+  /** Adds two numbers. */
+  i32 add(1: i32 num1, 2: i32 num2),
+
+  i32 calculate(1: i32 logid, 2: Work w) throws (1: InvalidOperation ouch)
+
+  oneway void zip()
+}
+"#;
+
+        let chunks = chunk_idl(source, IdlDialect::Thrift).unwrap();
+
+        let service = find(&chunks, "service Calculator");
+        assert_eq!(service.chunk_type, ChunkType::Module);
+
+        let add = find(&chunks, "i32 add(");
+        assert_eq!(add.chunk_type, ChunkType::Method);
+        assert_eq!(add.metadata.name.as_deref(), Some("add"));
+        assert_eq!(add.metadata.ancestry, vec!["Calculator".to_string()]);
+        assert!(add.text.starts_with("/** Adds two numbers. */"));
+
+        let calculate = find(&chunks, "i32 calculate(");
+        assert!(
+            calculate
+                .text
+                .trim_end()
+                .ends_with("InvalidOperation ouch)")
+        );
+        assert!(!calculate.text.contains("oneway"));
+
+        let zip = find(&chunks, "oneway void zip()");
+        assert_eq!(zip.chunk_type, ChunkType::Method);
+
+        let exception = find(&chunks, "exception InvalidOperation");
+        assert_eq!(exception.chunk_type, ChunkType::Class);
+        assert!(exception.text.starts_with("# Arithmetic failure."));
+
+        assert_eq!(find(&chunks, "struct Work").chunk_type, ChunkType::Class);
+    }
+
+    #[test]
+    fn field_names_matching_keywords_are_not_definitions() {
+        let source = "message Config {\n  string service = 1;\n  string message = 2;\n}\n";
+
+        let chunks = chunk_idl(source, IdlDialect::Protobuf).unwrap();
+        let definitions: Vec<_> = chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type != ChunkType::Text)
+            .collect();
+
+        assert_eq!(definitions.len(), 1);
+        assert!(definitions[0].text.starts_with("message Config"));
+    }
+
+    #[test]
+    fn chunk_text_routes_idl_languages() {
+        let source = "service Health {\n  rpc Check(Ping) returns (Pong);\n}\n";
+
+        let chunks = crate::chunk_text(source, Some(cs_core::Language::Protobuf)).unwrap();
+        assert!(
+            chunks
+                .iter()
+                .any(|chunk| chunk.chunk_type == ChunkType::Method)
+        );
+    }
+}
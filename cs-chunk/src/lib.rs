@@ -1,8 +1,17 @@
 use anyhow::Result;
 use cs_core::Span;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
 
+mod doc;
+mod generic;
+mod haskell;
+mod idl;
 mod query_chunker;
+pub mod snapshot;
+
+pub use doc::DocComment;
 
 /// Import token estimation from cc-embed
 pub use cs_embed::TokenEstimator;
@@ -56,12 +65,18 @@ pub struct StrideInfo {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ChunkMetadata {
+    /// Name of the symbol this chunk defines, when the parser could determine one
+    #[serde(default)]
+    pub name: Option<String>,
     pub ancestry: Vec<String>,
     pub breadcrumb: Option<String>,
     pub leading_trivia: Vec<String>,
     pub trailing_trivia: Vec<String>,
     pub byte_length: usize,
     pub estimated_tokens: usize,
+    /// Doc comment or docstring of the definition, which may lie outside the span
+    #[serde(default)]
+    pub doc: Option<DocComment>,
 }
 
 impl ChunkMetadata {
@@ -78,23 +93,27 @@ impl ChunkMetadata {
         };
 
         Self {
+            name: None,
             ancestry,
             breadcrumb,
             leading_trivia,
             trailing_trivia,
             byte_length: text.len(),
             estimated_tokens: estimate_tokens(text),
+            doc: None,
         }
     }
 
     fn from_text(text: &str) -> Self {
         Self {
+            name: None,
             ancestry: Vec::new(),
             breadcrumb: None,
             leading_trivia: Vec::new(),
             trailing_trivia: Vec::new(),
             byte_length: text.len(),
             estimated_tokens: estimate_tokens(text),
+            doc: None,
         }
     }
 
@@ -116,6 +135,30 @@ pub struct Chunk {
     pub metadata: ChunkMetadata,
 }
 
+impl Chunk {
+    /// Text to embed: the code, preceded by its definition's doc comment when the span doesn't
+    /// already include it (JSDoc above `export`, rustdoc, later strides of a long function)
+    pub fn embedding_text(&self) -> Cow<'_, str> {
+        match &self.metadata.doc {
+            Some(doc)
+                if doc.byte_start < self.span.byte_start || doc.byte_end > self.span.byte_end =>
+            {
+                Cow::Owned(format!("{}\n{}", doc.text, self.text))
+            }
+            _ => Cow::Borrowed(&self.text),
+        }
+    }
+
+    /// The definition this chunk holds, with its containers (`Class::method`)
+    pub fn symbol(&self) -> Option<String> {
+        let name = self.metadata.name.as_ref()?;
+        Some(match &self.metadata.breadcrumb {
+            Some(breadcrumb) => format!("{}::{}", breadcrumb, name),
+            None => name.clone(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ChunkType {
     Text,
@@ -136,6 +179,14 @@ pub enum ParseableLanguage {
     Go,
     CSharp,
     Zig,
+    ObjectiveC,
+    Php,
+    Swift,
+    Kotlin,
+    Shell,
+    Yaml,
+    Toml,
+    Json,
 }
 
 impl std::fmt::Display for ParseableLanguage {
@@ -150,6 +201,14 @@ impl std::fmt::Display for ParseableLanguage {
             ParseableLanguage::Go => "go",
             ParseableLanguage::CSharp => "csharp",
             ParseableLanguage::Zig => "zig",
+            ParseableLanguage::ObjectiveC => "objc",
+            ParseableLanguage::Php => "php",
+            ParseableLanguage::Swift => "swift",
+            ParseableLanguage::Kotlin => "kotlin",
+            ParseableLanguage::Shell => "shell",
+            ParseableLanguage::Yaml => "yaml",
+            ParseableLanguage::Toml => "toml",
+            ParseableLanguage::Json => "json",
         };
         write!(f, "{}", name)
     }
@@ -169,6 +228,14 @@ impl TryFrom<cs_core::Language> for ParseableLanguage {
             cs_core::Language::Go => Ok(ParseableLanguage::Go),
             cs_core::Language::CSharp => Ok(ParseableLanguage::CSharp),
             cs_core::Language::Zig => Ok(ParseableLanguage::Zig),
+            cs_core::Language::ObjectiveC => Ok(ParseableLanguage::ObjectiveC),
+            cs_core::Language::Php => Ok(ParseableLanguage::Php),
+            cs_core::Language::Swift => Ok(ParseableLanguage::Swift),
+            cs_core::Language::Kotlin => Ok(ParseableLanguage::Kotlin),
+            cs_core::Language::Shell => Ok(ParseableLanguage::Shell),
+            cs_core::Language::Yaml => Ok(ParseableLanguage::Yaml),
+            cs_core::Language::Toml => Ok(ParseableLanguage::Toml),
+            cs_core::Language::Json => Ok(ParseableLanguage::Json),
             _ => Err(anyhow::anyhow!(
                 "Language {:?} is not supported for parsing",
                 lang
@@ -202,22 +269,68 @@ impl Default for ChunkConfig {
     }
 }
 
+/// Project or command-line replacements for a model's chunk size (`--chunk-tokens`,
+/// `--chunk-overlap`, `--no-stride`); unset fields keep the model's defaults
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkOverrides {
+    /// Target tokens per chunk; longer chunks are strided
+    pub max_tokens: Option<usize>,
+    /// Tokens shared by neighbouring strides and generic chunks
+    pub overlap: Option<usize>,
+    /// `Some(false)` keeps oversized chunks whole instead of striding them
+    pub stride: Option<bool>,
+}
+
+impl ChunkOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These overrides, with `fallback` filling the fields left unset
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            max_tokens: self.max_tokens.or(fallback.max_tokens),
+            overlap: self.overlap.or(fallback.overlap),
+            stride: self.stride.or(fallback.stride),
+        }
+    }
+}
+
+/// Chunk configuration for `model_name` with `overrides` applied
+pub fn model_chunk_config(model_name: Option<&str>, overrides: &ChunkOverrides) -> ChunkConfig {
+    let (target_tokens, overlap_tokens) = get_model_chunk_config(model_name);
+    let max_tokens = overrides.max_tokens.unwrap_or(target_tokens).max(1);
+    ChunkConfig {
+        max_tokens,
+        // The overlap must leave each stride room to advance
+        stride_overlap: overrides
+            .overlap
+            .unwrap_or(overlap_tokens)
+            .min(max_tokens / 2),
+        enable_striding: overrides.stride.unwrap_or(true),
+    }
+}
+
 /// New function that accepts model name for model-specific chunking
 pub fn chunk_text_with_model(
     text: &str,
     language: Option<cs_core::Language>,
     model_name: Option<&str>,
 ) -> Result<Vec<Chunk>> {
-    let (target_tokens, overlap_tokens) = get_model_chunk_config(model_name);
-
-    // Create a config based on model-specific parameters
-    let config = ChunkConfig {
-        max_tokens: target_tokens,
-        stride_overlap: overlap_tokens,
-        enable_striding: true,
-    };
+    chunk_text_with_overrides(text, language, model_name, &ChunkOverrides::default())
+}
 
-    chunk_text_with_config_and_model(text, language, &config, model_name)
+/// Model-specific chunking with the chunk size, overlap or striding replaced by `overrides`
+pub fn chunk_text_with_overrides(
+    text: &str,
+    language: Option<cs_core::Language>,
+    model_name: Option<&str>,
+    overrides: &ChunkOverrides,
+) -> Result<Vec<Chunk>> {
+    let config = model_chunk_config(model_name, overrides);
+    let generic_size = (config.max_tokens, config.stride_overlap);
+    chunk_text_with_config_and_sizes(text, language, &config, generic_size)
 }
 
 pub fn chunk_text_with_config(
@@ -225,14 +338,15 @@ pub fn chunk_text_with_config(
     language: Option<cs_core::Language>,
     config: &ChunkConfig,
 ) -> Result<Vec<Chunk>> {
-    chunk_text_with_config_and_model(text, language, config, None)
+    chunk_text_with_config_and_sizes(text, language, config, get_model_chunk_config(None))
 }
 
-fn chunk_text_with_config_and_model(
+/// `generic_size` is the (target, overlap) in tokens for files chunked by lines
+fn chunk_text_with_config_and_sizes(
     text: &str,
     language: Option<cs_core::Language>,
     config: &ChunkConfig,
-    model_name: Option<&str>,
+    generic_size: (usize, usize),
 ) -> Result<Vec<Chunk>> {
     tracing::debug!(
         "Chunking text with language: {:?}, length: {} chars, config: {:?}",
@@ -241,18 +355,25 @@ fn chunk_text_with_config_and_model(
         config
     );
 
-    let result = match language.map(ParseableLanguage::try_from) {
-        Some(Ok(lang)) => {
-            tracing::debug!("Using {} tree-sitter parser", lang);
-            chunk_language_with_model(text, lang, model_name)
-        }
-        Some(Err(_)) => {
-            tracing::debug!("Language not supported for parsing, using generic chunking strategy");
-            chunk_generic_with_token_config(text, model_name)
-        }
-        None => {
-            tracing::debug!("Using generic chunking strategy");
-            chunk_generic_with_token_config(text, model_name)
+    let result = if let Some(dialect) = language.and_then(idl::IdlDialect::from_language) {
+        tracing::debug!("Using {} IDL definition scanner", dialect);
+        idl::chunk_idl(text, dialect)
+    } else {
+        match language.map(ParseableLanguage::try_from) {
+            Some(Ok(lang)) => {
+                tracing::debug!("Using {} tree-sitter parser", lang);
+                chunk_language(text, lang)
+            }
+            Some(Err(_)) => {
+                tracing::debug!(
+                    "Language not supported for parsing, using generic chunking strategy"
+                );
+                generic::chunk_by_lines(text, generic_size)
+            }
+            None => {
+                tracing::debug!("Using generic chunking strategy");
+                generic::chunk_by_lines(text, generic_size)
+            }
         }
     };
 
@@ -268,82 +389,7 @@ fn chunk_text_with_config_and_model(
 }
 
 fn chunk_generic(text: &str) -> Result<Vec<Chunk>> {
-    chunk_generic_with_token_config(text, None)
-}
-
-fn chunk_generic_with_token_config(text: &str, model_name: Option<&str>) -> Result<Vec<Chunk>> {
-    let mut chunks = Vec::new();
-    let lines: Vec<&str> = text.lines().collect();
-
-    // Get model-specific optimal chunk size in tokens
-    let (target_tokens, overlap_tokens) = get_model_chunk_config(model_name);
-
-    // Convert token targets to approximate line counts
-    // This is a rough heuristic - we'll validate with actual token counting
-    let avg_tokens_per_line = 10.0; // Rough estimate for code
-    let target_lines = ((target_tokens as f32) / avg_tokens_per_line) as usize;
-    let overlap_lines = ((overlap_tokens as f32) / avg_tokens_per_line) as usize;
-
-    let chunk_size = target_lines.max(5); // Minimum 5 lines
-    let overlap = overlap_lines.max(1); // Minimum 1 line overlap
-
-    // Pre-compute cumulative byte offsets for O(1) lookup, accounting for different line endings
-    let mut line_byte_offsets = Vec::with_capacity(lines.len() + 1);
-    line_byte_offsets.push(0);
-    let mut cumulative_offset = 0;
-    let mut byte_pos = 0;
-
-    for line in lines.iter() {
-        cumulative_offset += line.len();
-
-        // Find the actual line ending length in the original text
-        let line_end_pos = byte_pos + line.len();
-        let newline_len = if line_end_pos < text.len() && text.as_bytes()[line_end_pos] == b'\r' {
-            if line_end_pos + 1 < text.len() && text.as_bytes()[line_end_pos + 1] == b'\n' {
-                2 // CRLF
-            } else {
-                1 // CR only (old Mac)
-            }
-        } else if line_end_pos < text.len() && text.as_bytes()[line_end_pos] == b'\n' {
-            1 // LF only (Unix)
-        } else {
-            0 // No newline at this position (could be last line without newline)
-        };
-
-        cumulative_offset += newline_len;
-        byte_pos = cumulative_offset;
-        line_byte_offsets.push(cumulative_offset);
-    }
-
-    let mut i = 0;
-    while i < lines.len() {
-        let end = (i + chunk_size).min(lines.len());
-        let chunk_lines = &lines[i..end];
-        let chunk_text = chunk_lines.join("\n");
-        let byte_start = line_byte_offsets[i];
-        let byte_end = line_byte_offsets[end];
-        let metadata = ChunkMetadata::from_text(&chunk_text);
-
-        chunks.push(Chunk {
-            span: Span {
-                byte_start,
-                byte_end,
-                line_start: i + 1,
-                line_end: end,
-            },
-            text: chunk_text,
-            chunk_type: ChunkType::Text,
-            stride_info: None,
-            metadata,
-        });
-
-        i += chunk_size - overlap;
-        if i >= lines.len() {
-            break;
-        }
-    }
-
-    Ok(chunks)
+    generic::chunk_by_lines(text, get_model_chunk_config(None))
 }
 
 pub(crate) fn tree_sitter_language(language: ParseableLanguage) -> Result<tree_sitter::Language> {
@@ -358,6 +404,14 @@ pub(crate) fn tree_sitter_language(language: ParseableLanguage) -> Result<tree_s
         ParseableLanguage::Go => tree_sitter_go::LANGUAGE,
         ParseableLanguage::CSharp => tree_sitter_c_sharp::LANGUAGE,
         ParseableLanguage::Zig => tree_sitter_zig::LANGUAGE,
+        ParseableLanguage::ObjectiveC => tree_sitter_objc::LANGUAGE,
+        ParseableLanguage::Php => tree_sitter_php::LANGUAGE_PHP,
+        ParseableLanguage::Swift => tree_sitter_swift::LANGUAGE,
+        ParseableLanguage::Kotlin => tree_sitter_kotlin_ng::LANGUAGE,
+        ParseableLanguage::Shell => tree_sitter_bash::LANGUAGE,
+        ParseableLanguage::Yaml => tree_sitter_yaml::LANGUAGE,
+        ParseableLanguage::Toml => tree_sitter_toml_ng::LANGUAGE,
+        ParseableLanguage::Json => tree_sitter_json::LANGUAGE,
     };
 
     Ok(ts_language.into())
@@ -372,23 +426,14 @@ fn chunk_language(text: &str, language: ParseableLanguage) -> Result<Vec<Chunk>>
         .parse(text, None)
         .ok_or_else(|| anyhow::anyhow!("Failed to parse {} code", language))?;
 
-    let mut chunks = match query_chunker::chunk_with_queries(language, ts_language, &tree, text)? {
-        Some(query_chunks) if !query_chunks.is_empty() => query_chunks,
-        _ => {
-            let mut legacy_chunks = Vec::new();
-            let mut cursor = tree.walk();
-            extract_code_chunks(&mut cursor, text, &mut legacy_chunks, language);
-            legacy_chunks
-        }
-    };
-
+    let mut chunks = query_chunker::chunk_with_queries(language, ts_language, &tree, text)?;
     if chunks.is_empty() {
         return chunk_generic(text);
     }
 
-    // Post-process Haskell chunks to merge function equations
+    // A Haskell function spans its signature and every equation
     if language == ParseableLanguage::Haskell {
-        chunks = merge_haskell_functions(chunks, text);
+        chunks = haskell::group_definitions(chunks, &tree, text)?;
     }
 
     // Fill gaps between chunks with remainder content
@@ -537,343 +582,14 @@ fn fill_gaps(mut chunks: Vec<Chunk>, text: &str) -> Vec<Chunk> {
     result
 }
 
-/// Merge Haskell function equations that belong to the same function definition
-fn merge_haskell_functions(chunks: Vec<Chunk>, source: &str) -> Vec<Chunk> {
-    if chunks.is_empty() {
-        return chunks;
-    }
-
-    let mut merged = Vec::new();
-    let mut i = 0;
-
-    while i < chunks.len() {
-        let chunk = &chunks[i];
-
-        // Skip chunks that are just fragments or comments
-        let trimmed = chunk.text.trim();
-        if trimmed.is_empty()
-            || trimmed.starts_with("--")
-            || trimmed.starts_with("{-")
-            || !chunk.text.contains(|c: char| c.is_alphanumeric())
-        {
-            i += 1;
-            continue;
-        }
-
-        // Extract function name from the chunk text
-        // Check if it's a signature first (contains ::)
-        let is_signature = chunk.text.contains("::");
-        let function_name = if is_signature {
-            // For signatures like "factorial :: Integer -> Integer", extract "factorial"
-            chunk
-                .text
-                .split("::")
-                .next()
-                .and_then(|s| s.split_whitespace().next())
-                .map(|s| s.to_string())
-        } else {
-            extract_haskell_function_name(&chunk.text)
-        };
-
-        if function_name.is_none() {
-            // Not a function (might be data, newtype, etc.), keep as-is
-            merged.push(chunk.clone());
-            i += 1;
-            continue;
-        }
-
-        let name = function_name.unwrap();
-        let group_start = chunk.span.byte_start;
-        let mut group_end = chunk.span.byte_end;
-        let line_start = chunk.span.line_start;
-        let mut line_end = chunk.span.line_end;
-        let mut trailing_trivia = chunk.metadata.trailing_trivia.clone();
-
-        // Look ahead for function equations with the same name
-        let mut j = i + 1;
-        while j < chunks.len() {
-            let next_chunk = &chunks[j];
-
-            // Skip comments
-            let next_trimmed = next_chunk.text.trim();
-            if next_trimmed.starts_with("--") || next_trimmed.starts_with("{-") {
-                j += 1;
-                continue;
-            }
-
-            let next_is_signature = next_chunk.text.contains("::");
-            let next_name = if next_is_signature {
-                next_chunk
-                    .text
-                    .split("::")
-                    .next()
-                    .and_then(|s| s.split_whitespace().next())
-                    .map(|s| s.to_string())
-            } else {
-                extract_haskell_function_name(&next_chunk.text)
-            };
-
-            if next_name == Some(name.clone()) {
-                // Extend the group to include this equation
-                group_end = next_chunk.span.byte_end;
-                line_end = next_chunk.span.line_end;
-                trailing_trivia = next_chunk.metadata.trailing_trivia.clone();
-                j += 1;
-            } else {
-                break;
-            }
-        }
-
-        // Create merged chunk
-        let merged_text = source.get(group_start..group_end).unwrap_or("").to_string();
-        let mut metadata = chunk.metadata.with_updated_text(&merged_text);
-        metadata.trailing_trivia = trailing_trivia;
-
-        merged.push(Chunk {
-            span: Span {
-                byte_start: group_start,
-                byte_end: group_end,
-                line_start,
-                line_end,
-            },
-            text: merged_text,
-            chunk_type: ChunkType::Function,
-            stride_info: None,
-            metadata,
-        });
-
-        i = j; // Skip past all merged chunks
-    }
-
-    merged
-}
-
-/// Extract the function name from a Haskell function equation
-fn extract_haskell_function_name(text: &str) -> Option<String> {
-    // Haskell function equations start with the function name followed by patterns or =
-    // Examples: "factorial 0 = 1", "map f [] = []"
-    let trimmed = text.trim();
-
-    // Find the first word (function name)
-    let first_word = trimmed
-        .split_whitespace()
-        .next()?
-        .trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '\'');
-
-    // Validate it's a valid Haskell identifier (starts with lowercase or underscore)
-    if first_word.is_empty() {
-        return None;
-    }
-
-    let first_char = first_word.chars().next()?;
-    if first_char.is_lowercase() || first_char == '_' {
-        Some(first_word.to_string())
-    } else {
-        None
-    }
-}
-
-fn chunk_language_with_model(
-    text: &str,
-    language: ParseableLanguage,
-    _model_name: Option<&str>,
-) -> Result<Vec<Chunk>> {
-    // For now, language-based chunking doesn't need model-specific behavior
-    // since it's based on semantic code boundaries rather than token counts
-    // We could potentially optimize this in the future by validating chunk token counts
-    chunk_language(text, language)
-}
-
-fn extract_code_chunks(
-    cursor: &mut tree_sitter::TreeCursor,
-    source: &str,
-    chunks: &mut Vec<Chunk>,
-    language: ParseableLanguage,
-) {
-    let node = cursor.node();
-
-    // For Haskell: skip "function" nodes that are nested anywhere inside "signature" nodes
-    // (these are type expressions, not actual function definitions)
-    let should_skip = if language == ParseableLanguage::Haskell && node.kind() == "function" {
-        // Walk up parent chain to check if we're inside a signature
-        let mut current = node.parent();
-        while let Some(parent) = current {
-            if parent.kind() == "signature" {
-                return; // Skip this node and don't recurse
-            }
-            current = parent.parent();
-        }
-        false
-    } else {
-        false
-    };
-
-    if !should_skip
-        && let Some(initial_chunk_type) = chunk_type_for_node(language, &node)
-        && let Some(chunk) = build_chunk(node, source, initial_chunk_type, language)
-    {
-        let is_duplicate = chunks.iter().any(|existing| {
-            existing.span.byte_start == chunk.span.byte_start
-                && existing.span.byte_end == chunk.span.byte_end
-        });
-
-        if !is_duplicate {
-            chunks.push(chunk);
-        }
-    }
-
-    // For Haskell signatures: don't recurse into children (they're just type expressions)
-    let should_recurse = !(language == ParseableLanguage::Haskell && node.kind() == "signature");
-
-    if should_recurse && cursor.goto_first_child() {
-        loop {
-            extract_code_chunks(cursor, source, chunks, language);
-            if !cursor.goto_next_sibling() {
-                break;
-            }
-        }
-        cursor.goto_parent();
-    }
-}
-
-fn chunk_type_for_node(
-    language: ParseableLanguage,
-    node: &tree_sitter::Node<'_>,
-) -> Option<ChunkType> {
-    let kind = node.kind();
-
-    let supported = match language {
-        ParseableLanguage::Python => matches!(kind, "function_definition" | "class_definition"),
-        ParseableLanguage::TypeScript | ParseableLanguage::JavaScript => matches!(
-            kind,
-            "function_declaration" | "class_declaration" | "method_definition" | "arrow_function"
-        ),
-        ParseableLanguage::Haskell => matches!(
-            kind,
-            "function" // Capture function equations
-                | "signature" // Capture type signatures (will be merged with functions)
-                | "data_type"
-                | "newtype"
-                | "type_synonym"
-                | "type_family"
-                | "class"
-                | "instance"
-        ),
-        ParseableLanguage::Rust => matches!(
-            kind,
-            "function_item" | "impl_item" | "struct_item" | "enum_item" | "trait_item" | "mod_item"
-        ),
-        ParseableLanguage::Ruby => {
-            matches!(kind, "method" | "class" | "module" | "singleton_method")
-        }
-        ParseableLanguage::Go => matches!(
-            kind,
-            "function_declaration"
-                | "method_declaration"
-                | "type_declaration"
-                | "var_declaration"
-                | "const_declaration"
-        ),
-        ParseableLanguage::CSharp => matches!(
-            kind,
-            "method_declaration"
-                | "class_declaration"
-                | "interface_declaration"
-                | "variable_declaration"
-        ),
-        ParseableLanguage::Zig => matches!(
-            kind,
-            "function_declaration"
-                | "test_declaration"
-                | "variable_declaration"
-                | "struct_declaration"
-                | "enum_declaration"
-                | "union_declaration"
-                | "opaque_declaration"
-                | "error_set_declaration"
-                | "comptime_declaration"
-        ),
-    };
-
-    if !supported {
-        return None;
-    }
-
-    match language {
-        ParseableLanguage::Go
-            if matches!(node.kind(), "var_declaration" | "const_declaration")
-                && node.parent().is_some_and(|p| p.kind() == "block") =>
-        {
-            return None;
-        }
-        ParseableLanguage::CSharp if node.kind() == "variable_declaration" => {
-            if !is_csharp_field_like(*node) {
-                return None;
-            }
-        }
-        _ => {}
-    }
-
-    Some(classify_chunk_kind(kind))
-}
-
-fn classify_chunk_kind(kind: &str) -> ChunkType {
-    match kind {
-        "function_definition"
-        | "function_declaration"
-        | "arrow_function"
-        | "function"
-        | "function_item"
-        | "def"
-        | "defp"
-        | "defn"
-        | "defn-"
-        | "method"
-        | "singleton_method" => ChunkType::Function,
-        "signature" => ChunkType::Function, // Haskell type signatures will be merged with functions
-        "class_definition"
-        | "class_declaration"
-        | "instance_declaration"
-        | "class"
-        | "instance"
-        | "struct_item"
-        | "enum_item"
-        | "defstruct"
-        | "defrecord"
-        | "deftype"
-        | "type_declaration"
-        | "struct_declaration"
-        | "enum_declaration"
-        | "union_declaration"
-        | "opaque_declaration"
-        | "error_set_declaration" => ChunkType::Class,
-        "method_definition" | "method_declaration" | "defmacro" => ChunkType::Method,
-        "data_type"
-        | "newtype"
-        | "type_synonym"
-        | "type_family"
-        | "impl_item"
-        | "trait_item"
-        | "mod_item"
-        | "defmodule"
-        | "module"
-        | "defprotocol"
-        | "interface_declaration"
-        | "ns"
-        | "var_declaration"
-        | "const_declaration"
-        | "variable_declaration"
-        | "test_declaration"
-        | "comptime_declaration" => ChunkType::Module,
-        _ => ChunkType::Text,
-    }
-}
-
+/// Chunk for a captured definition; `containers` holds the type of every captured node, keyed
+/// by node id, so enclosing definitions make up its ancestry
 pub(crate) fn build_chunk(
     node: tree_sitter::Node<'_>,
     source: &str,
     initial_type: ChunkType,
     language: ParseableLanguage,
+    containers: &HashMap<usize, ChunkType>,
 ) -> Option<Chunk> {
     let target_node = adjust_node_for_language(node, language);
     let (byte_start, start_row, leading_segments) =
@@ -894,10 +610,15 @@ pub(crate) fn build_chunk(
     }
 
     let chunk_type = adjust_chunk_type_for_context(target_node, initial_type, language);
-    let ancestry = collect_ancestry(target_node, language, source);
+    let ancestry = collect_ancestry(target_node, language, source, containers);
     let leading_trivia = segments_to_strings(&leading_segments, source);
     let trailing_trivia = segments_to_strings(&trailing_segments, source);
-    let metadata = ChunkMetadata::from_context(&text, ancestry, leading_trivia, trailing_trivia);
+    let mut metadata =
+        ChunkMetadata::from_context(&text, ancestry, leading_trivia, trailing_trivia);
+    if chunk_type != ChunkType::Text {
+        metadata.name = display_name_for_node(target_node, language, source, chunk_type.clone());
+        metadata.doc = doc::find_doc(target_node, language, source);
+    }
 
     Some(Chunk {
         span: Span {
@@ -999,6 +720,7 @@ fn should_attach_leading_trivia(language: ParseableLanguage, node: &tree_sitter:
         ParseableLanguage::Python => kind == "decorator",
         ParseableLanguage::TypeScript | ParseableLanguage::JavaScript => kind == "decorator",
         ParseableLanguage::CSharp => matches!(kind, "attribute_list" | "attribute"),
+        ParseableLanguage::Haskell => kind == "haddock",
         _ => false,
     }
 }
@@ -1056,12 +778,14 @@ fn collect_ancestry(
     mut node: tree_sitter::Node<'_>,
     language: ParseableLanguage,
     source: &str,
+    containers: &HashMap<usize, ChunkType>,
 ) -> Vec<String> {
     let mut parts = Vec::new();
 
     while let Some(parent) = node.parent() {
-        if let Some(parent_chunk_type) = chunk_type_for_node(language, &parent)
-            && let Some(name) = display_name_for_node(parent, language, source, parent_chunk_type)
+        if let Some(parent_chunk_type) = containers.get(&parent.id())
+            && let Some(name) =
+                display_name_for_node(parent, language, source, parent_chunk_type.clone())
         {
             parts.push(name);
         }
@@ -1098,6 +822,25 @@ fn display_name_for_node(
         ParseableLanguage::Go => find_identifier(node, source, &["identifier", "type_identifier"]),
         ParseableLanguage::CSharp => find_identifier(node, source, &["identifier"]),
         ParseableLanguage::Zig => find_identifier(node, source, &["identifier"]),
+        ParseableLanguage::ObjectiveC => find_identifier(node, source, &["identifier"]),
+        ParseableLanguage::Php => find_identifier(node, source, &["name"]),
+        ParseableLanguage::Swift => {
+            find_identifier(node, source, &["simple_identifier", "type_identifier"])
+        }
+        ParseableLanguage::Kotlin => find_identifier(
+            node,
+            source,
+            &["identifier", "simple_identifier", "type_identifier"],
+        ),
+        ParseableLanguage::Shell => find_identifier(node, source, &["word"]),
+        // Mapping pairs are named by their key
+        ParseableLanguage::Yaml | ParseableLanguage::Json => node
+            .child_by_field_name("key")
+            .and_then(|key| text_for_node(key, source))
+            .map(|key| key.trim().trim_matches(['"', '\'']).to_string()),
+        ParseableLanguage::Toml => {
+            find_identifier(node, source, &["bare_key", "dotted_key", "quoted_key"])
+        }
     }
 }
 
@@ -1195,6 +938,8 @@ fn is_method_context(node: tree_sitter::Node<'_>, language: ParseableLanguage) -
     const TYPESCRIPT_CONTAINERS: &[&str] = &["class_body", "class_declaration"];
     const RUBY_CONTAINERS: &[&str] = &["class", "module"];
     const RUST_CONTAINERS: &[&str] = &["impl_item", "trait_item"];
+    const SWIFT_CONTAINERS: &[&str] = &["class_body", "enum_class_body", "protocol_body"];
+    const KOTLIN_CONTAINERS: &[&str] = &["class_body", "enum_class_body"];
 
     match language {
         ParseableLanguage::Python => ancestor_has_kind(node, PYTHON_CONTAINERS),
@@ -1207,6 +952,15 @@ fn is_method_context(node: tree_sitter::Node<'_>, language: ParseableLanguage) -
         ParseableLanguage::CSharp => false,
         ParseableLanguage::Haskell => false,
         ParseableLanguage::Zig => false,
+        ParseableLanguage::ObjectiveC => false,
+        // Class members are method_declaration nodes, captured as methods already
+        ParseableLanguage::Php => false,
+        ParseableLanguage::Swift => ancestor_has_kind(node, SWIFT_CONTAINERS),
+        ParseableLanguage::Kotlin => ancestor_has_kind(node, KOTLIN_CONTAINERS),
+        ParseableLanguage::Shell
+        | ParseableLanguage::Yaml
+        | ParseableLanguage::Toml
+        | ParseableLanguage::Json => false,
     }
 }
 
@@ -1221,16 +975,6 @@ fn ancestor_has_kind(node: tree_sitter::Node<'_>, kinds: &[&str]) -> bool {
     false
 }
 
-fn is_csharp_field_like(node: tree_sitter::Node<'_>) -> bool {
-    if let Some(parent) = node.parent() {
-        return matches!(
-            parent.kind(),
-            "field_declaration" | "event_field_declaration"
-        );
-    }
-    false
-}
-
 /// Apply striding to chunks that exceed the token limit
 fn apply_striding(chunks: Vec<Chunk>, config: &ChunkConfig) -> Result<Vec<Chunk>> {
     let mut result = Vec::new();
@@ -1379,85 +1123,35 @@ fn stride_large_chunk(chunk: Chunk, config: &ChunkConfig) -> Result<Vec<Chunk>>
 mod tests {
     use super::*;
 
-    fn canonicalize_spans(
-        mut spans: Vec<(usize, usize, ChunkType)>,
-    ) -> Vec<(usize, usize, ChunkType)> {
-        fn chunk_type_order(chunk_type: &ChunkType) -> u8 {
-            match chunk_type {
-                ChunkType::Text => 0,
-                ChunkType::Function => 1,
-                ChunkType::Class => 2,
-                ChunkType::Method => 3,
-                ChunkType::Module => 4,
-            }
-        }
-
-        spans.sort_by(|a, b| {
-            let order_a = chunk_type_order(&a.2);
-            let order_b = chunk_type_order(&b.2);
-            order_a
-                .cmp(&order_b)
-                .then_with(|| a.0.cmp(&b.0))
-                .then_with(|| a.1.cmp(&b.1))
-        });
-
-        let mut result: Vec<(usize, usize, ChunkType)> = Vec::new();
-        for (start, end, ty) in spans {
-            if let Some(last) = result.last_mut()
-                && last.0 == start
-                && last.2 == ty
-            {
-                if end > last.1 {
-                    last.1 = end;
-                }
-                continue;
-            }
-            result.push((start, end, ty));
-        }
-
-        result
-    }
-
-    fn assert_query_parity(language: ParseableLanguage, source: &str) {
+    /// Assert that the language's query yields a chunk of each `(type, first line)`
+    fn assert_query_captures(
+        language: ParseableLanguage,
+        source: &str,
+        expected: &[(ChunkType, &str)],
+    ) {
         let mut parser = tree_sitter::Parser::new();
         let ts_language = tree_sitter_language(language).expect("language");
         parser.set_language(&ts_language).expect("set language");
         let tree = parser.parse(source, None).expect("parse source");
 
-        let query_chunks = query_chunker::chunk_with_queries(language, ts_language, &tree, source)
-            .expect("query execution")
-            .expect("queries available");
-
-        let mut legacy_chunks = Vec::new();
-        let mut cursor = tree.walk();
-        extract_code_chunks(&mut cursor, source, &mut legacy_chunks, language);
-
-        let query_spans = canonicalize_spans(
-            query_chunks
-                .iter()
-                .map(|chunk| {
-                    (
-                        chunk.span.byte_start,
-                        chunk.span.byte_end,
-                        chunk.chunk_type.clone(),
-                    )
-                })
-                .collect(),
-        );
-        let legacy_spans = canonicalize_spans(
-            legacy_chunks
-                .iter()
-                .map(|chunk| {
-                    (
-                        chunk.span.byte_start,
-                        chunk.span.byte_end,
-                        chunk.chunk_type.clone(),
-                    )
-                })
-                .collect(),
-        );
-
-        assert_eq!(query_spans, legacy_spans);
+        let chunks = query_chunker::chunk_with_queries(language, ts_language, &tree, source)
+            .expect("query execution");
+        let captured: Vec<(ChunkType, &str)> = chunks
+            .iter()
+            .map(|chunk| {
+                let first_line = chunk.text.lines().next().unwrap_or_default().trim();
+                (chunk.chunk_type.clone(), first_line)
+            })
+            .collect();
+        for capture in expected {
+            assert!(
+                captured.contains(capture),
+                "{} query missed {:?}; captured {:#?}",
+                language,
+                capture,
+                captured
+            );
+        }
     }
 
     #[test]
@@ -1543,7 +1237,7 @@ pub mod utils {
     }
 
     #[test]
-    fn test_rust_query_matches_legacy() {
+    fn test_rust_query_captures() {
         let source = r#"
             mod sample {
                 struct Thing;
@@ -1557,11 +1251,22 @@ pub mod utils {
             fn util() {}
         "#;
 
-        assert_query_parity(ParseableLanguage::Rust, source);
+        assert_query_captures(
+            ParseableLanguage::Rust,
+            source,
+            &[
+                (ChunkType::Module, "mod sample {"),
+                (ChunkType::Class, "struct Thing;"),
+                (ChunkType::Module, "impl Thing {"),
+                (ChunkType::Method, "fn new() -> Self { Self }"),
+                (ChunkType::Method, "fn helper(&self) {}"),
+                (ChunkType::Function, "fn util() {}"),
+            ],
+        );
     }
 
     #[test]
-    fn test_python_query_matches_legacy() {
+    fn test_python_query_captures() {
         let source = r#"
 class Example:
     @classmethod
@@ -1577,7 +1282,16 @@ async def async_helper():
     return 2
 "#;
 
-        assert_query_parity(ParseableLanguage::Python, source);
+        assert_query_captures(
+            ParseableLanguage::Python,
+            source,
+            &[
+                (ChunkType::Class, "class Example:"),
+                (ChunkType::Method, "@classmethod"),
+                (ChunkType::Function, "def helper():"),
+                (ChunkType::Function, "async def async_helper():"),
+            ],
+        );
     }
 
     #[test]
@@ -1680,6 +1394,306 @@ func main() {
         assert!(chunk_types.contains(&&ChunkType::Method)); // methods
     }
 
+    #[test]
+    fn test_chunk_php() {
+        let php_code = r#"<?php
+
+namespace App\Math;
+
+interface Operation
+{
+    public function calculate(float $a, float $b): float;
+}
+
+trait Remembers
+{
+    private float $memory = 0.0;
+
+    public function recall(): float
+    {
+        return $this->memory;
+    }
+}
+
+class Calculator implements Operation
+{
+    use Remembers;
+
+    public function calculate(float $a, float $b): float
+    {
+        return $a + $b;
+    }
+}
+
+function main(): void
+{
+    $calc = new Calculator();
+}
+"#;
+
+        let chunks = chunk_language(php_code, ParseableLanguage::Php).unwrap();
+        assert!(!chunks.is_empty());
+
+        // Should find the namespace, types, functions, and methods
+        let chunk_types: Vec<&ChunkType> = chunks.iter().map(|c| &c.chunk_type).collect();
+        assert!(chunk_types.contains(&&ChunkType::Module)); // namespace
+        assert!(chunk_types.contains(&&ChunkType::Class)); // interface, trait and class
+        assert!(chunk_types.contains(&&ChunkType::Function)); // functions
+        assert!(chunk_types.contains(&&ChunkType::Method)); // methods
+    }
+
+    #[test]
+    fn test_chunk_swift() {
+        let swift_code = r#"
+import Foundation
+
+protocol Operation {
+    func calculate(_ a: Double, _ b: Double) -> Double
+}
+
+struct Calculator: Operation {
+    var memory: Double = 0.0
+
+    init() {}
+
+    func calculate(_ a: Double, _ b: Double) -> Double {
+        return a + b
+    }
+}
+
+extension Calculator {
+    mutating func clear() {
+        memory = 0.0
+    }
+}
+
+func main() {
+    let calc = Calculator()
+}
+"#;
+
+        let chunks = chunk_language(swift_code, ParseableLanguage::Swift).unwrap();
+        assert!(!chunks.is_empty());
+
+        let method = chunks
+            .iter()
+            .find(|chunk| chunk.text.trim_start().starts_with("mutating func clear()"))
+            .expect("Expected chunk for extension method");
+        assert_eq!(method.chunk_type, ChunkType::Method);
+
+        // Should find types, functions, and methods
+        let chunk_types: Vec<&ChunkType> = chunks.iter().map(|c| &c.chunk_type).collect();
+        assert!(chunk_types.contains(&&ChunkType::Class)); // protocol, struct and extension
+        assert!(chunk_types.contains(&&ChunkType::Function)); // functions
+        assert!(chunk_types.contains(&&ChunkType::Method)); // methods
+    }
+
+    #[test]
+    fn test_chunk_kotlin() {
+        let kotlin_code = r#"
+package app.math
+
+interface Operation {
+    fun calculate(a: Double, b: Double): Double
+}
+
+class Calculator : Operation {
+    private var memory = 0.0
+
+    override fun calculate(a: Double, b: Double): Double {
+        return a + b
+    }
+
+    companion object {
+        fun create(): Calculator = Calculator()
+    }
+}
+
+object Registry {
+    fun register(calc: Calculator) {}
+}
+
+fun main() {
+    val calc = Calculator.create()
+}
+"#;
+
+        let chunks = chunk_language(kotlin_code, ParseableLanguage::Kotlin).unwrap();
+        assert!(!chunks.is_empty());
+
+        let method = chunks
+            .iter()
+            .find(|chunk| chunk.text.trim_start().starts_with("fun register("))
+            .expect("Expected chunk for object method");
+        assert_eq!(method.chunk_type, ChunkType::Method);
+
+        // Should find classes, objects, functions, and methods
+        let chunk_types: Vec<&ChunkType> = chunks.iter().map(|c| &c.chunk_type).collect();
+        assert!(chunk_types.contains(&&ChunkType::Class)); // interface, class and objects
+        assert!(chunk_types.contains(&&ChunkType::Function)); // functions
+        assert!(chunk_types.contains(&&ChunkType::Method)); // methods
+    }
+
+    #[test]
+    fn test_chunk_shell() {
+        let shell_code = r#"#!/usr/bin/env bash
+set -euo pipefail
+
+# Print a message to stderr
+log() {
+    echo "$*" >&2
+}
+
+function deploy {
+    log "deploying $1"
+    kubectl apply -f "$1"
+}
+
+deploy manifests/
+"#;
+
+        let chunks = chunk_language(shell_code, ParseableLanguage::Shell).unwrap();
+
+        let functions: Vec<&Chunk> = chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type == ChunkType::Function)
+            .collect();
+        assert_eq!(functions.len(), 2, "{:#?}", functions);
+        assert!(functions[0].text.starts_with("# Print a message to stderr"));
+        assert_eq!(functions[0].metadata.name.as_deref(), Some("log"));
+        assert_eq!(functions[1].metadata.name.as_deref(), Some("deploy"));
+
+        // Top-level commands are kept as text between the functions
+        assert!(
+            chunks
+                .iter()
+                .any(|chunk| chunk.chunk_type == ChunkType::Text
+                    && chunk.text.contains("set -euo pipefail"))
+        );
+    }
+
+    #[test]
+    fn test_chunk_yaml() {
+        let yaml_code = r#"# CI pipeline
+name: ci
+
+on:
+  push:
+    branches: [main]
+
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo test
+---
+kind: Service
+metadata:
+  name: web
+"#;
+
+        let chunks = chunk_language(yaml_code, ParseableLanguage::Yaml).unwrap();
+
+        let keys: Vec<(Option<&str>, &str)> = chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type == ChunkType::Module)
+            .map(|chunk| (chunk.metadata.name.as_deref(), chunk.text.as_str()))
+            .collect();
+        let names: Vec<Option<&str>> = keys.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            vec![
+                Some("name"),
+                Some("on"),
+                Some("jobs"),
+                Some("kind"),
+                Some("metadata")
+            ]
+        );
+        // Nested keys stay inside their top-level section
+        assert!(keys[2].1.contains("- run: cargo test"));
+        assert!(chunks.iter().any(
+            |chunk| chunk.chunk_type == ChunkType::Text && chunk.text.trim() == "# CI pipeline"
+        ));
+    }
+
+    #[test]
+    fn test_chunk_toml() {
+        let toml_code = r#"title = "cs"
+
+[package]
+name = "cs-search"
+version = "0.1.0"
+
+# Build dependencies
+[dependencies]
+anyhow = "1.0"
+serde = { version = "1.0", features = ["derive"] }
+
+[[bin]]
+name = "cs"
+path = "src/main.rs"
+"#;
+
+        let chunks = chunk_language(toml_code, ParseableLanguage::Toml).unwrap();
+
+        let tables: Vec<&Chunk> = chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type == ChunkType::Module)
+            .collect();
+        let names: Vec<Option<&str>> = tables
+            .iter()
+            .map(|chunk| chunk.metadata.name.as_deref())
+            .collect();
+        assert_eq!(
+            names,
+            vec![Some("package"), Some("dependencies"), Some("bin")]
+        );
+        assert!(tables[1].text.contains("features = [\"derive\"]"));
+        assert!(
+            chunks
+                .iter()
+                .any(|chunk| chunk.chunk_type == ChunkType::Text
+                    && chunk.text.contains("title = \"cs\""))
+        );
+    }
+
+    #[test]
+    fn test_chunk_json() {
+        let json_code = r#"{
+  "name": "web",
+  "scripts": {
+    "build": "vite build",
+    "test": "vitest"
+  },
+  "dependencies": {
+    "react": "^18.2.0"
+  }
+}
+"#;
+
+        let chunks = chunk_language(json_code, ParseableLanguage::Json).unwrap();
+
+        let keys: Vec<&Chunk> = chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type == ChunkType::Module)
+            .collect();
+        let names: Vec<Option<&str>> = keys
+            .iter()
+            .map(|chunk| chunk.metadata.name.as_deref())
+            .collect();
+        assert_eq!(
+            names,
+            vec![Some("name"), Some("scripts"), Some("dependencies")]
+        );
+        assert!(keys[1].text.contains("\"test\": \"vitest\""));
+
+        // A document without top-level keys falls back to generic chunking
+        let array = chunk_language("[1, 2, 3]\n", ParseableLanguage::Json).unwrap();
+        assert_eq!(array.len(), 1);
+        assert_eq!(array[0].chunk_type, ChunkType::Text);
+    }
+
     #[test]
     #[ignore] // TODO: Update test to match query-based chunking behavior
     fn test_chunk_typescript_arrow_context() {
@@ -1754,12 +1768,8 @@ const compute = (x: number) => x * 2;
         );
     }
 
-    // TODO: Query-based chunking is more accurate than legacy for TypeScript
-    // and finds additional method chunks. This is the correct behavior.
-    // Legacy parity tests are disabled until legacy chunking is updated.
     #[test]
-    #[ignore]
-    fn test_typescript_query_matches_legacy() {
+    fn test_typescript_query_captures() {
         let source = r#"
 export const util = () => {
     return 42;
@@ -1774,11 +1784,19 @@ export class Example {
 const compute = (x: number) => x * 2;
 "#;
 
-        assert_query_parity(ParseableLanguage::TypeScript, source);
+        assert_query_captures(
+            ParseableLanguage::TypeScript,
+            source,
+            &[
+                (ChunkType::Function, "export const util = () => {"),
+                (ChunkType::Class, "class Example {"),
+                (ChunkType::Function, "const compute = (x: number) => x * 2;"),
+            ],
+        );
     }
 
     #[test]
-    fn test_ruby_query_matches_legacy() {
+    fn test_ruby_query_captures() {
         let source = r#"
 class Calculator
   def initialize
@@ -1795,11 +1813,20 @@ class Calculator
 end
 "#;
 
-        assert_query_parity(ParseableLanguage::Ruby, source);
+        assert_query_captures(
+            ParseableLanguage::Ruby,
+            source,
+            &[
+                (ChunkType::Class, "class Calculator"),
+                (ChunkType::Method, "def initialize"),
+                (ChunkType::Method, "def add(a, b)"),
+                (ChunkType::Method, "def self.class_method"),
+            ],
+        );
     }
 
     #[test]
-    fn test_go_query_matches_legacy() {
+    fn test_go_query_captures() {
         let source = r#"
 package main
 
@@ -1820,11 +1847,24 @@ func (c *Calculator) Add(a, b float64) float64 {
 func Helper() {}
 "#;
 
-        assert_query_parity(ParseableLanguage::Go, source);
+        assert_query_captures(
+            ParseableLanguage::Go,
+            source,
+            &[
+                (ChunkType::Module, "const Pi = 3.14159"),
+                (ChunkType::Module, "var memory float64"),
+                (ChunkType::Class, "type Calculator struct {"),
+                (
+                    ChunkType::Method,
+                    "func (c *Calculator) Add(a, b float64) float64 {",
+                ),
+                (ChunkType::Function, "func Helper() {}"),
+            ],
+        );
     }
 
     #[test]
-    fn test_haskell_query_matches_legacy() {
+    fn test_haskell_query_captures() {
         let source = r#"
 module Example where
 
@@ -1846,11 +1886,23 @@ shapeDescription (Circle r) = "circle of radius " ++ show r
 shapeDescription (Square s) = "square of side " ++ show s
 "#;
 
-        assert_query_parity(ParseableLanguage::Haskell, source);
+        assert_query_captures(
+            ParseableLanguage::Haskell,
+            source,
+            &[
+                (ChunkType::Module, "data Shape"),
+                (ChunkType::Module, "type family Area a"),
+                (ChunkType::Class, "class Printable a where"),
+                (ChunkType::Function, "printValue :: a -> String"),
+                (ChunkType::Class, "instance Printable Shape where"),
+                (ChunkType::Function, "printValue (Circle _) = \"circle\""),
+                (ChunkType::Function, "shapeDescription :: Shape -> String"),
+            ],
+        );
     }
 
     #[test]
-    fn test_csharp_query_matches_legacy() {
+    fn test_csharp_query_captures() {
         let source = r#"
 namespace Calculator;
 
@@ -1876,11 +1928,22 @@ public class Calculator
 }
 "#;
 
-        assert_query_parity(ParseableLanguage::CSharp, source);
+        assert_query_captures(
+            ParseableLanguage::CSharp,
+            source,
+            &[
+                (ChunkType::Module, "public interface ICalculator"),
+                (ChunkType::Method, "double Add(double x, double y);"),
+                (ChunkType::Class, "public class Calculator"),
+                (ChunkType::Module, "double PI = 3.14159"),
+                (ChunkType::Module, "double _memory"),
+                (ChunkType::Method, "public double Add(double x, double y)"),
+            ],
+        );
     }
 
     #[test]
-    fn test_zig_query_matches_legacy() {
+    fn test_zig_query_captures() {
         let source = r#"
 const std = @import("std");
 
@@ -1903,7 +1966,102 @@ test "calculator addition" {
 }
 "#;
 
-        assert_query_parity(ParseableLanguage::Zig, source);
+        assert_query_captures(
+            ParseableLanguage::Zig,
+            source,
+            &[
+                (ChunkType::Module, "const std = @import(\"std\");"),
+                (ChunkType::Module, "test \"calculator addition\" {"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_php_query_captures() {
+        let source = r#"<?php
+
+class Calculator
+{
+    public function add(float $a, float $b): float
+    {
+        return $a + $b;
+    }
+}
+
+function main(): void
+{
+    $calc = new Calculator();
+}
+"#;
+
+        assert_query_captures(
+            ParseableLanguage::Php,
+            source,
+            &[
+                (ChunkType::Class, "class Calculator"),
+                (
+                    ChunkType::Method,
+                    "public function add(float $a, float $b): float",
+                ),
+                (ChunkType::Function, "function main(): void"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_swift_query_captures() {
+        let source = r#"
+class Calculator {
+    init() {}
+
+    func add(_ a: Double, _ b: Double) -> Double {
+        return a + b
+    }
+}
+
+func main() {
+    let calc = Calculator()
+}
+"#;
+
+        assert_query_captures(
+            ParseableLanguage::Swift,
+            source,
+            &[
+                (ChunkType::Class, "class Calculator {"),
+                (ChunkType::Method, "init() {}"),
+                (
+                    ChunkType::Method,
+                    "func add(_ a: Double, _ b: Double) -> Double {",
+                ),
+                (ChunkType::Function, "func main() {"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_kotlin_query_captures() {
+        let source = r#"
+class Calculator {
+    fun add(a: Double, b: Double): Double {
+        return a + b
+    }
+}
+
+fun main() {
+    val calc = Calculator()
+}
+"#;
+
+        assert_query_captures(
+            ParseableLanguage::Kotlin,
+            source,
+            &[
+                (ChunkType::Class, "class Calculator {"),
+                (ChunkType::Method, "fun add(a: Double, b: Double): Double {"),
+                (ChunkType::Function, "fun main() {"),
+            ],
+        );
     }
 
     #[test]
@@ -2024,6 +2182,77 @@ test "multiply function" {
         );
     }
 
+    #[test]
+    fn test_chunk_objc() {
+        let objc_code = r#"
+#import <Foundation/Foundation.h>
+
+@protocol Greeter <NSObject>
+- (NSString *)greet:(NSString *)name;
+@end
+
+@interface Person : NSObject <Greeter>
+@property (nonatomic, copy) NSString *name;
+- (instancetype)initWithName:(NSString *)name;
+@end
+
+@implementation Person
+
+- (instancetype)initWithName:(NSString *)name {
+    self = [super init];
+    if (self) {
+        _name = [name copy];
+    }
+    return self;
+}
+
+- (NSString *)greet:(NSString *)other {
+    return [NSString stringWithFormat:@"Hello %@, I am %@", other, self.name];
+}
+
+@end
+
+static int add(int a, int b) {
+    return a + b;
+}
+"#;
+
+        let chunks = chunk_language(objc_code, ParseableLanguage::ObjectiveC).unwrap();
+        assert!(!chunks.is_empty());
+
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.chunk_type == ChunkType::Class && c.text.contains("@interface Person")),
+            "Expected a Class chunk for @interface"
+        );
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.chunk_type == ChunkType::Class
+                    && c.text.contains("@implementation Person")),
+            "Expected a Class chunk for @implementation"
+        );
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.chunk_type == ChunkType::Module && c.text.contains("@protocol Greeter")),
+            "Expected a Module chunk for @protocol"
+        );
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.chunk_type == ChunkType::Method && c.text.contains("stringWithFormat")),
+            "Expected a Method chunk for the greet: implementation"
+        );
+        assert!(
+            chunks
+                .iter()
+                .any(|c| c.chunk_type == ChunkType::Function && c.text.contains("static int add")),
+            "Expected a Function chunk for the C function"
+        );
+    }
+
     #[test]
     fn test_chunk_csharp() {
         let csharp_code = r#"
@@ -2174,6 +2403,37 @@ public class Calculator
         }
     }
 
+    #[test]
+    fn test_chunk_overrides() {
+        let model = Some("BAAI/bge-small-en-v1.5");
+        let config = model_chunk_config(model, &ChunkOverrides::default());
+        assert_eq!((config.max_tokens, config.stride_overlap), (400, 80));
+
+        let overrides = ChunkOverrides {
+            max_tokens: Some(100),
+            overlap: Some(90),
+            stride: None,
+        };
+        let config = model_chunk_config(model, &overrides);
+        // Overlap is capped at half the chunk size
+        assert_eq!((config.max_tokens, config.stride_overlap), (100, 50));
+
+        let body = "    let value = compute_something(input, other_input);\n".repeat(100);
+        let code = format!("fn long_function() {{\n{}}}\n", body);
+        let language = Some(cs_core::Language::Rust);
+        let default_chunks = chunk_text_with_model(&code, language, model).unwrap();
+        let small_chunks = chunk_text_with_overrides(&code, language, model, &overrides).unwrap();
+        assert!(small_chunks.len() > default_chunks.len());
+
+        let unstrided = ChunkOverrides {
+            stride: Some(false),
+            ..overrides
+        };
+        let chunks = chunk_text_with_overrides(&code, language, model, &unstrided).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].stride_info.is_none());
+    }
+
     #[test]
     fn test_gap_filling_coverage() {
         // Test that all non-whitespace content gets chunked
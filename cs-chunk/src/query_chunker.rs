@@ -1,4 +1,9 @@
-use std::{borrow::Cow, collections::HashSet, env, fs, path::PathBuf};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::PathBuf,
+};
 
 use anyhow::{Context, Result};
 use tree_sitter::{Language, Query, QueryCursor, StreamingIterator, Tree};
@@ -7,58 +12,61 @@ use crate::{Chunk, ChunkType, ParseableLanguage, build_chunk};
 
 const QUERY_OVERRIDE_DIR_ENV: &str = "CC_CHUNK_QUERY_DIR";
 
+/// Chunks of every definition the language's query captures, in source order
 pub(crate) fn chunk_with_queries(
     language: ParseableLanguage,
     ts_language: Language,
     tree: &Tree,
     source: &str,
-) -> Result<Option<Vec<Chunk>>> {
-    let Some(query_source) = load_query_source(language)? else {
-        return Ok(None);
-    };
-
+) -> Result<Vec<Chunk>> {
+    let query_source = load_query_source(language)?;
     let query = Query::new(&ts_language, &query_source)
         .with_context(|| format!("Failed to compile query for {}", language))?;
 
     let capture_names = query.capture_names();
     let mut cursor = QueryCursor::new();
-    let mut seen_spans = HashSet::new();
-    let mut chunks = Vec::new();
+    let mut definitions = Vec::new();
+    let mut containers = HashMap::new();
 
     let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
     while let Some(mat) = matches.next() {
         for capture in mat.captures {
             let capture_name = &capture_names[capture.index as usize];
-            if let Some(chunk_type) = chunk_type_from_capture(capture_name) {
-                if language == ParseableLanguage::Haskell
-                    && chunk_type == ChunkType::Function
-                    && capture
-                        .node
-                        .parent()
-                        .is_some_and(|parent| parent.kind() == "signature")
-                {
-                    continue;
-                }
-
-                if let Some(chunk) = build_chunk(capture.node, source, chunk_type, language) {
-                    let span_key = (chunk.span.byte_start, chunk.span.byte_end);
-                    if seen_spans.insert(span_key) {
-                        chunks.push(chunk);
-                    }
-                }
+            let Some(chunk_type) = chunk_type_from_capture(capture_name) else {
+                continue;
+            };
+            // Function types inside Haskell signatures are `function` nodes too
+            if language == ParseableLanguage::Haskell
+                && chunk_type == ChunkType::Function
+                && capture
+                    .node
+                    .parent()
+                    .is_some_and(|parent| parent.kind() == "signature")
+            {
+                continue;
             }
+            containers.insert(capture.node.id(), chunk_type.clone());
+            definitions.push((capture.node, chunk_type));
         }
     }
 
-    if chunks.is_empty() {
-        return Ok(None);
+    // Ancestry needs every captured node, so chunks are built once all matches are in
+    let mut seen_spans = HashSet::new();
+    let mut chunks = Vec::new();
+    for (node, chunk_type) in definitions {
+        if let Some(chunk) = build_chunk(node, source, chunk_type, language, &containers) {
+            let span_key = (chunk.span.byte_start, chunk.span.byte_end);
+            if seen_spans.insert(span_key) {
+                chunks.push(chunk);
+            }
+        }
     }
 
     chunks.sort_by_key(|chunk| chunk.span.byte_start);
-    Ok(Some(chunks))
+    Ok(chunks)
 }
 
-fn load_query_source(language: ParseableLanguage) -> Result<Option<Cow<'static, str>>> {
+fn load_query_source(language: ParseableLanguage) -> Result<Cow<'static, str>> {
     if let Some(dir) = env::var_os(QUERY_OVERRIDE_DIR_ENV) {
         let override_path = PathBuf::from(dir)
             .join(language.to_string())
@@ -72,27 +80,37 @@ fn load_query_source(language: ParseableLanguage) -> Result<Option<Cow<'static,
                     override_path.display()
                 )
             })?;
-            return Ok(Some(Cow::Owned(contents)));
+            return Ok(Cow::Owned(contents));
         }
     }
 
-    Ok(builtin_query(language).map(Cow::Borrowed))
+    Ok(Cow::Borrowed(builtin_query(language)))
 }
 
-fn builtin_query(language: ParseableLanguage) -> Option<&'static str> {
+/// The query compiled into the binary; adding a language means adding its `tags.scm`
+fn builtin_query(language: ParseableLanguage) -> &'static str {
     match language {
-        ParseableLanguage::Python => Some(include_str!("../queries/python/tags.scm")),
-        ParseableLanguage::TypeScript => Some(include_str!("../queries/typescript/tags.scm")),
-        ParseableLanguage::JavaScript => Some(include_str!("../queries/javascript/tags.scm")),
-        ParseableLanguage::Rust => Some(include_str!("../queries/rust/tags.scm")),
-        ParseableLanguage::Haskell => Some(include_str!("../queries/haskell/tags.scm")),
-        ParseableLanguage::Ruby => Some(include_str!("../queries/ruby/tags.scm")),
-        ParseableLanguage::Go => Some(include_str!("../queries/go/tags.scm")),
-        ParseableLanguage::CSharp => Some(include_str!("../queries/csharp/tags.scm")),
-        ParseableLanguage::Zig => Some(include_str!("../queries/zig/tags.scm")),
+        ParseableLanguage::Python => include_str!("../queries/python/tags.scm"),
+        ParseableLanguage::TypeScript => include_str!("../queries/typescript/tags.scm"),
+        ParseableLanguage::JavaScript => include_str!("../queries/javascript/tags.scm"),
+        ParseableLanguage::Rust => include_str!("../queries/rust/tags.scm"),
+        ParseableLanguage::Haskell => include_str!("../queries/haskell/tags.scm"),
+        ParseableLanguage::Ruby => include_str!("../queries/ruby/tags.scm"),
+        ParseableLanguage::Go => include_str!("../queries/go/tags.scm"),
+        ParseableLanguage::CSharp => include_str!("../queries/csharp/tags.scm"),
+        ParseableLanguage::Zig => include_str!("../queries/zig/tags.scm"),
+        ParseableLanguage::ObjectiveC => include_str!("../queries/objc/tags.scm"),
+        ParseableLanguage::Php => include_str!("../queries/php/tags.scm"),
+        ParseableLanguage::Swift => include_str!("../queries/swift/tags.scm"),
+        ParseableLanguage::Kotlin => include_str!("../queries/kotlin/tags.scm"),
+        ParseableLanguage::Shell => include_str!("../queries/shell/tags.scm"),
+        ParseableLanguage::Yaml => include_str!("../queries/yaml/tags.scm"),
+        ParseableLanguage::Toml => include_str!("../queries/toml/tags.scm"),
+        ParseableLanguage::Json => include_str!("../queries/json/tags.scm"),
     }
 }
 
+/// Chunk type named by the last part of a capture (`@definition.function` is a function)
 fn chunk_type_from_capture(name: &str) -> Option<ChunkType> {
     let type_name = name.split('.').next_back().unwrap_or(name);
 
@@ -139,8 +157,7 @@ mod tests {
         let tree = parser.parse(source, None).expect("parse rust source");
 
         let chunks = chunk_with_queries(ParseableLanguage::Rust, ts_language, &tree, source)
-            .expect("query execution")
-            .expect("query should be available");
+            .expect("query execution");
 
         assert!(
             chunks
@@ -169,6 +186,7 @@ mod tests {
             method_chunk.metadata.ancestry,
             vec!["sample".to_string(), "Thing".to_string()]
         );
+        assert_eq!(method_chunk.metadata.name.as_deref(), Some("new"));
         let util_chunk = chunks
             .iter()
             .find(|chunk| chunk.chunk_type == ChunkType::Function && chunk.text.contains("fn util"))
@@ -204,8 +222,7 @@ def top_level():
         let tree = parser.parse(source, None).expect("parse python source");
 
         let chunks = chunk_with_queries(ParseableLanguage::Python, ts_language, &tree, source)
-            .expect("query execution")
-            .expect("query should be available");
+            .expect("query execution");
 
         assert!(
             chunks
@@ -228,6 +245,7 @@ def top_level():
             .find(|chunk| chunk.chunk_type == ChunkType::Method && chunk.text.contains("def hello"))
             .expect("method chunk present");
         assert_eq!(method_chunk.metadata.ancestry, vec!["Greeter".to_string()]);
+        assert_eq!(method_chunk.metadata.name.as_deref(), Some("hello"));
         assert!(
             method_chunk
                 .metadata
@@ -274,8 +292,7 @@ const compute = (x: number) => x * 2;
         let tree = parser.parse(source, None).expect("parse ts source");
 
         let chunks = chunk_with_queries(ParseableLanguage::TypeScript, ts_language, &tree, source)
-            .expect("query execution")
-            .expect("query should be available");
+            .expect("query execution");
 
         assert!(chunks.iter().any(|chunk| {
             chunk.chunk_type == ChunkType::Function && chunk.text.contains("export const util")
@@ -0,0 +1,190 @@
+// Chunk-boundary snapshots for catching silent chunking changes
+// A snapshot records where each chunk of a corpus starts and ends; two snapshots can be diffed
+// to review what a grammar or query upgrade moved
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::{ChunkType, chunk_text};
+
+/// Position and kind of one chunk; previews are left out so snapshots stay small and reviewable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkBoundary {
+    pub chunk_type: ChunkType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+}
+
+impl std::fmt::Display for ChunkBoundary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.chunk_type)?;
+        if let Some(name) = &self.name {
+            write!(f, " {}", name)?;
+        }
+        write!(
+            f,
+            " lines {}-{} (bytes {}..{})",
+            self.line_start, self.line_end, self.byte_start, self.byte_end
+        )
+    }
+}
+
+/// Chunk boundaries for every file of a corpus, keyed by `/`-separated path relative to its root
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CorpusSnapshot {
+    pub files: BTreeMap<String, Vec<ChunkBoundary>>,
+}
+
+/// Boundaries that exist in only one of two snapshots of the same file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileBoundaryDiff {
+    pub file: String,
+    pub removed: Vec<ChunkBoundary>,
+    pub added: Vec<ChunkBoundary>,
+}
+
+/// Chunk `text` with the default configuration, picking the parser from `path`
+pub fn snapshot_text(path: &Path, text: &str) -> Result<Vec<ChunkBoundary>> {
+    let chunks = chunk_text(text, cs_core::Language::from_path(path))?;
+    Ok(chunks
+        .into_iter()
+        .map(|chunk| ChunkBoundary {
+            chunk_type: chunk.chunk_type,
+            name: chunk.metadata.name,
+            line_start: chunk.span.line_start,
+            line_end: chunk.span.line_end,
+            byte_start: chunk.span.byte_start,
+            byte_end: chunk.span.byte_end,
+        })
+        .collect())
+}
+
+/// Snapshot every file under `root`
+pub fn snapshot_corpus(root: &Path) -> Result<CorpusSnapshot> {
+    let mut snapshot = CorpusSnapshot::default();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let key = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let text = std::fs::read_to_string(&path)?;
+            snapshot.files.insert(key, snapshot_text(&path, &text)?);
+        }
+    }
+    Ok(snapshot)
+}
+
+/// Per-file boundary changes between two snapshots; unchanged files are omitted
+pub fn diff_snapshots(old: &CorpusSnapshot, new: &CorpusSnapshot) -> Vec<FileBoundaryDiff> {
+    let mut files: Vec<&String> = old.files.keys().chain(new.files.keys()).collect();
+    files.sort();
+    files.dedup();
+
+    files
+        .into_iter()
+        .filter_map(|file| {
+            let old_boundaries = old.files.get(file).map(Vec::as_slice).unwrap_or_default();
+            let new_boundaries = new.files.get(file).map(Vec::as_slice).unwrap_or_default();
+            let removed = missing_from(old_boundaries, new_boundaries);
+            let added = missing_from(new_boundaries, old_boundaries);
+            if removed.is_empty() && added.is_empty() {
+                None
+            } else {
+                Some(FileBoundaryDiff {
+                    file: file.clone(),
+                    removed,
+                    added,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Boundaries of `from` without a counterpart in `other`, matching duplicates one to one
+fn missing_from(from: &[ChunkBoundary], other: &[ChunkBoundary]) -> Vec<ChunkBoundary> {
+    let mut unmatched: Vec<&ChunkBoundary> = other.iter().collect();
+    from.iter()
+        .filter(
+            |boundary| match unmatched.iter().position(|candidate| candidate == boundary) {
+                Some(idx) => {
+                    unmatched.swap_remove(idx);
+                    false
+                }
+                None => true,
+            },
+        )
+        .cloned()
+        .collect()
+}
+
+/// Human-readable diff in a `-`/`+` style, one boundary per line
+pub fn render_diff(diffs: &[FileBoundaryDiff]) -> String {
+    let mut out = String::new();
+    for diff in diffs {
+        let _ = writeln!(out, "{}", diff.file);
+        for boundary in &diff.removed {
+            let _ = writeln!(out, "  - {}", boundary);
+        }
+        for boundary in &diff.added {
+            let _ = writeln!(out, "  + {}", boundary);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boundary(name: &str, line_end: usize) -> ChunkBoundary {
+        ChunkBoundary {
+            chunk_type: ChunkType::Function,
+            name: Some(name.to_string()),
+            line_start: 1,
+            line_end,
+            byte_start: 0,
+            byte_end: line_end * 10,
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_moved_and_dropped_files() {
+        let mut old = CorpusSnapshot::default();
+        old.files
+            .insert("a.rs".into(), vec![boundary("a", 3), boundary("b", 5)]);
+        old.files.insert("gone.rs".into(), vec![boundary("g", 1)]);
+        let mut new = CorpusSnapshot::default();
+        new.files
+            .insert("a.rs".into(), vec![boundary("a", 3), boundary("b", 6)]);
+
+        let diffs = diff_snapshots(&old, &new);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].file, "a.rs");
+        assert_eq!(diffs[0].removed, vec![boundary("b", 5)]);
+        assert_eq!(diffs[0].added, vec![boundary("b", 6)]);
+        assert_eq!(diffs[1].file, "gone.rs");
+        assert!(diffs[1].added.is_empty());
+
+        let rendered = render_diff(&diffs);
+        assert!(rendered.contains("  - Function b lines 1-5 (bytes 0..50)"));
+        assert!(rendered.contains("  + Function b lines 1-6 (bytes 0..60)"));
+
+        assert!(diff_snapshots(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+//! Chunk-boundary stability: chunks the fixtures corpus and compares the result with the
+//! committed snapshot, so grammar or query upgrades cannot shift boundaries silently.
+//!
+//! After an intentional change, review it with
+//! `cs --chunk-diff cs-chunk/tests/snapshots/chunk_boundaries.json <new snapshot>` and
+//! regenerate with `CS_UPDATE_CHUNK_SNAPSHOTS=1 cargo test -p cs-chunk --test chunk_stability`.
+
+use cs_chunk::snapshot::{CorpusSnapshot, diff_snapshots, render_diff, snapshot_corpus};
+use std::path::Path;
+
+#[test]
+fn test_chunk_boundaries_match_snapshot() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let snapshot_path = manifest_dir.join("tests/snapshots/chunk_boundaries.json");
+    let actual = snapshot_corpus(&manifest_dir.join("tests/fixtures")).unwrap();
+
+    if std::env::var_os("CS_UPDATE_CHUNK_SNAPSHOTS").is_some() {
+        let json = serde_json::to_string_pretty(&actual).unwrap();
+        std::fs::write(&snapshot_path, json + "\n").unwrap();
+        return;
+    }
+
+    let expected: CorpusSnapshot =
+        serde_json::from_slice(&std::fs::read(&snapshot_path).unwrap()).unwrap();
+    let diffs = diff_snapshots(&expected, &actual);
+    if !diffs.is_empty() {
+        let new_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("chunk_boundaries.new.json");
+        std::fs::write(&new_path, serde_json::to_string_pretty(&actual).unwrap()).unwrap();
+        panic!(
+            "chunk boundaries changed:\n{}\nReview with `cs --chunk-diff {} {}`; if intended, \
+             rerun with CS_UPDATE_CHUNK_SNAPSHOTS=1",
+            render_diff(&diffs),
+            snapshot_path.display(),
+            new_path.display()
+        );
+    }
+}
@@ -0,0 +1,23 @@
+// Bank account used by the chunk-boundary fixtures
+using System;
+
+namespace Fixtures
+{
+    public class Account
+    {
+        public decimal Balance { get; private set; }
+
+        public void Deposit(decimal amount)
+        {
+            if (amount <= 0) throw new ArgumentException("amount");
+            Balance += amount;
+        }
+
+        public bool TryWithdraw(decimal amount)
+        {
+            if (amount > Balance) return false;
+            Balance -= amount;
+            return true;
+        }
+    }
+}
@@ -0,0 +1,28 @@
+// HTTP client used by the chunk-boundary fixtures
+
+export interface RequestOptions {
+  retries: number;
+  timeoutMs?: number;
+}
+
+export class ApiClient {
+  constructor(private readonly baseUrl: string) {}
+
+  async get<T>(path: string, options: RequestOptions): Promise<T> {
+    let attempt = 0;
+    while (true) {
+      try {
+        const response = await fetch(this.baseUrl + path);
+        return (await response.json()) as T;
+      } catch (err) {
+        if (++attempt > options.retries) throw err;
+      }
+    }
+  }
+}
+
+export const backoff = (attempt: number): number => Math.min(1000 * 2 ** attempt, 30000);
+
+export function isRetryable(status: number): boolean {
+  return status >= 500 || status === 429;
+}
@@ -0,0 +1,25 @@
+// Event emitter used by the chunk-boundary fixtures
+
+class Emitter {
+  constructor() {
+    this.handlers = new Map();
+  }
+
+  on(name, handler) {
+    const list = this.handlers.get(name) || [];
+    list.push(handler);
+    this.handlers.set(name, list);
+  }
+
+  emit(name, ...args) {
+    for (const handler of this.handlers.get(name) || []) {
+      handler(...args);
+    }
+  }
+}
+
+function once(emitter, name) {
+  return new Promise((resolve) => emitter.on(name, resolve));
+}
+
+module.exports = { Emitter, once };
@@ -0,0 +1,20 @@
+# Greeter used by the chunk-boundary fixtures
+module Greetings
+  class Greeter
+    def initialize(name)
+      @name = name
+    end
+
+    def greet
+      "Hello, #{@name}!"
+    end
+
+    def self.default
+      new("world")
+    end
+  end
+
+  def self.shout(text)
+    text.upcase
+  end
+end
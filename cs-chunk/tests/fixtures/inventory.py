@@ -0,0 +1,34 @@
+"""Inventory bookkeeping used by the chunk-boundary fixtures."""
+
+import dataclasses
+
+
+@dataclasses.dataclass
+class Item:
+    name: str
+    quantity: int = 0
+
+    def restock(self, amount: int) -> None:
+        """Add stock."""
+        self.quantity += amount
+
+    @property
+    def empty(self) -> bool:
+        return self.quantity == 0
+
+
+class Inventory:
+    def __init__(self):
+        self.items = {}
+
+    def add(self, item: Item) -> None:
+        self.items[item.name] = item
+
+    async def sync(self, client):
+        for item in self.items.values():
+            await client.push(item)
+
+
+def load(path):
+    with open(path) as handle:
+        return [Item(line.strip()) for line in handle]
@@ -0,0 +1,44 @@
+//! Geometry helpers used by the chunk-boundary fixtures
+
+use std::fmt;
+
+/// A shape with an area
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Circle {
+    pub radius: f64,
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        std::f64::consts::PI * self.radius * self.radius
+    }
+}
+
+impl fmt::Display for Circle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "circle(r={})", self.radius)
+    }
+}
+
+pub enum Kind {
+    Round,
+    Square,
+}
+
+/// Sum of the areas of all shapes
+pub fn total_area(shapes: &[Box<dyn Shape>]) -> f64 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_circle() {
+        assert!((Circle { radius: 1.0 }.area() - 3.14159).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,34 @@
+// Package store is used by the chunk-boundary fixtures.
+package store
+
+import (
+	"errors"
+	"sync"
+)
+
+var ErrNotFound = errors.New("not found")
+
+type Store struct {
+	mu    sync.RWMutex
+	items map[string]string
+}
+
+func New() *Store {
+	return &Store{items: make(map[string]string)}
+}
+
+func (s *Store) Get(key string) (string, error) {
+	s.mu.RLock()
+	defer s.mu.RUnlock()
+	value, ok := s.items[key]
+	if !ok {
+		return "", ErrNotFound
+	}
+	return value, nil
+}
+
+func (s *Store) Put(key, value string) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.items[key] = value
+}
@@ -0,0 +1,240 @@
+//! `cs --chunks FILE`: every chunk indexing would embed for a file, with its span, type, token
+//! estimate and stride overlaps, to debug why a search missed code. `--dump-chunks` draws the
+//! same boundaries over the source instead.
+
+use anyhow::{Context, Result};
+use console::style;
+use cs_core::Span;
+use cs_core::embed_template::EmbedTemplate;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkReport {
+    pub file: PathBuf,
+    pub language: Option<String>,
+    /// Model whose chunk size was used
+    pub model: String,
+    pub chunk_tokens: usize,
+    pub overlap_tokens: usize,
+    /// Whether chunks over `chunk_tokens` are split into overlapping strides
+    pub striding: bool,
+    /// Template composing each chunk's embedded text, when one is set
+    pub embed_template: Option<String>,
+    pub chunks: Vec<ChunkRow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkRow {
+    /// 1-based position in the file
+    pub index: usize,
+    pub chunk_type: &'static str,
+    pub name: Option<String>,
+    pub breadcrumb: Option<String>,
+    pub span: Span,
+    pub tokens: usize,
+    pub stride: Option<StrideRow>,
+    /// Doc comment prepended to the embedded text because the span doesn't include it
+    pub doc: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StrideRow {
+    /// 1-based stride number
+    pub stride: usize,
+    pub total_strides: usize,
+    /// Byte span of the chunk before striding, shared by all its strides
+    pub original_chunk_id: String,
+    /// Lines repeated from the previous stride
+    pub overlap_previous: Option<(usize, usize)>,
+    /// Lines repeated in the next stride
+    pub overlap_next: Option<(usize, usize)>,
+}
+
+/// Chunk `path` the way indexing with `model`, `overrides` and `template` would
+pub fn build_report(
+    path: &Path,
+    model: &str,
+    overrides: &cs_chunk::ChunkOverrides,
+    template: Option<&EmbedTemplate>,
+) -> Result<ChunkReport> {
+    let content = cs_core::encoding::read_to_string(path)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    let language = cs_core::Language::from_path(path);
+    let config = cs_chunk::model_chunk_config(Some(model), overrides);
+    let chunks = cs_chunk::chunk_text_with_overrides(&content, language, Some(model), overrides)?;
+
+    let chunks = chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let stride = chunk.stride_info.as_ref().map(|info| StrideRow {
+                stride: info.stride_index + 1,
+                total_strides: info.total_strides,
+                original_chunk_id: info.original_chunk_id.clone(),
+                overlap_previous: (info.overlap_start > 0).then(|| {
+                    let lines = newlines(chunk.text.chars().take(info.overlap_start));
+                    (chunk.span.line_start, chunk.span.line_start + lines)
+                }),
+                overlap_next: (info.overlap_end > 0).then(|| {
+                    let lines = newlines(chunk.text.chars().rev().take(info.overlap_end));
+                    (
+                        chunk.span.line_end.saturating_sub(lines),
+                        chunk.span.line_end,
+                    )
+                }),
+            });
+            let embedding_text = chunk.embedding_text();
+            let doc = (embedding_text.len() > chunk.text.len())
+                .then(|| chunk.metadata.doc.as_ref().map(|doc| doc.text.clone()))
+                .flatten();
+            ChunkRow {
+                index: i + 1,
+                chunk_type: chunk_type_name(&chunk.chunk_type),
+                name: chunk.metadata.name.clone(),
+                breadcrumb: chunk.metadata.breadcrumb.clone(),
+                tokens: cs_chunk::TokenEstimator::estimate_tokens(&cs_index::embedding_input(
+                    &chunk, path, template,
+                )),
+                span: chunk.span,
+                stride,
+                doc,
+                text: chunk.text.clone(),
+            }
+        })
+        .collect();
+
+    Ok(ChunkReport {
+        file: path.to_path_buf(),
+        language: language.map(|language| language.to_string()),
+        model: model.to_string(),
+        chunk_tokens: config.max_tokens,
+        overlap_tokens: config.stride_overlap,
+        striding: config.enable_striding,
+        embed_template: template.map(|template| template.to_string()),
+        chunks,
+    })
+}
+
+pub fn print_report(report: &ChunkReport) {
+    println!(
+        "{} · {} · {} chunks · {} ({} tokens, {})",
+        style(report.file.display()).cyan().bold(),
+        report.language.as_deref().unwrap_or("plain text"),
+        report.chunks.len(),
+        report.model,
+        report.chunk_tokens,
+        if report.striding {
+            format!("{} overlap", report.overlap_tokens)
+        } else {
+            "no striding".to_string()
+        }
+    );
+    if let Some(template) = &report.embed_template {
+        println!("embedded as {}", style(template).dim());
+    }
+    println!();
+
+    for chunk in &report.chunks {
+        let label = chunk
+            .breadcrumb
+            .as_deref()
+            .or(chunk.name.as_deref())
+            .unwrap_or("");
+        let stride = chunk
+            .stride
+            .as_ref()
+            .map(|stride| format!("  stride {}/{}", stride.stride, stride.total_strides))
+            .unwrap_or_default();
+        println!(
+            "{} {:<8} {:<24} L{:<10} bytes {:<14} ~{} tokens{}",
+            style(format!("#{:<3}", chunk.index)).dim(),
+            style(chunk.chunk_type).blue(),
+            label,
+            format!("{}-{}", chunk.span.line_start, chunk.span.line_end),
+            format!("{}-{}", chunk.span.byte_start, chunk.span.byte_end),
+            style(chunk.tokens).yellow(),
+            style(stride).magenta()
+        );
+        if let Some(stride) = &chunk.stride {
+            if let Some((start, end)) = stride.overlap_previous {
+                println!(
+                    "       overlap L{}-{} shared with previous stride",
+                    start, end
+                );
+            }
+            if let Some((start, end)) = stride.overlap_next {
+                println!("       overlap L{}-{} repeated in next stride", start, end);
+            }
+        }
+        if let Some(doc) = chunk.doc.as_deref().and_then(|doc| doc.lines().next()) {
+            let preview: String = doc.chars().take(80).collect();
+            println!("       doc: {}", style(preview).green());
+        }
+        if let Some(first_line) = chunk.text.lines().find(|line| !line.trim().is_empty()) {
+            let preview: String = first_line.trim().chars().take(80).collect();
+            println!("       {}", style(preview).dim());
+        }
+    }
+}
+
+fn newlines(chars: impl Iterator<Item = char>) -> usize {
+    chars.filter(|&c| c == '\n').count()
+}
+
+fn chunk_type_name(chunk_type: &cs_chunk::ChunkType) -> &'static str {
+    match chunk_type {
+        cs_chunk::ChunkType::Function => "function",
+        cs_chunk::ChunkType::Class => "class",
+        cs_chunk::ChunkType::Method => "method",
+        cs_chunk::ChunkType::Module => "module",
+        cs_chunk::ChunkType::Text => "text",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report_lists_strides_with_overlaps() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("long.rs");
+        let body = "    let value = compute_something(input, other_input);\n".repeat(300);
+        std::fs::write(&path, format!("fn long_function() {{\n{}}}\n", body)).unwrap();
+
+        let model = "BAAI/bge-small-en-v1.5";
+        let report = build_report(&path, model, &Default::default(), None).unwrap();
+        assert_eq!(report.language.as_deref(), Some("rust"));
+        assert_eq!(report.chunk_tokens, 400);
+
+        let strides: Vec<&StrideRow> = report
+            .chunks
+            .iter()
+            .filter_map(|chunk| chunk.stride.as_ref())
+            .collect();
+        assert!(strides.len() > 1);
+        assert_eq!(strides[0].stride, 1);
+        assert!(strides[0].overlap_previous.is_none());
+        let (start, end) = strides[0].overlap_next.unwrap();
+        assert!(start < end);
+        assert!(strides[1].overlap_previous.is_some());
+        assert!(
+            report
+                .chunks
+                .iter()
+                .all(|chunk| chunk.tokens > 0 && chunk.chunk_type == "function")
+        );
+
+        let unstrided = cs_chunk::ChunkOverrides {
+            stride: Some(false),
+            ..Default::default()
+        };
+        let report = build_report(&path, model, &unstrided, None).unwrap();
+        assert!(!report.striding);
+        assert_eq!(report.chunks.len(), 1);
+        assert!(report.chunks[0].stride.is_none());
+    }
+}
@@ -0,0 +1,76 @@
+// Terminal color selection for stdout and stderr
+// `--color` wins; otherwise NO_COLOR, CLICOLOR_FORCE and CLICOLOR are honored before TTY detection
+
+use std::io::IsTerminal;
+
+/// Resolve `--color WHEN` and the environment, then apply the result to each stream
+///
+/// stdout and stderr are decided independently, so `cs "x" | less` keeps colored
+/// status messages on the terminal while the piped results stay plain.
+pub fn configure(when: &str) {
+    let env = |name: &str| std::env::var(name).ok();
+    console::set_colors_enabled(color_enabled(when, env, std::io::stdout().is_terminal()));
+    console::set_colors_enabled_stderr(color_enabled(when, env, std::io::stderr().is_terminal()));
+}
+
+fn color_enabled(when: &str, env: impl Fn(&str) -> Option<String>, is_terminal: bool) -> bool {
+    match when {
+        "always" => return true,
+        "never" => return false,
+        _ => {}
+    }
+
+    let is_set = |name: &str| env(name).is_some_and(|value| !value.is_empty());
+    let is_zero = |name: &str| env(name).is_some_and(|value| value == "0");
+
+    // https://no-color.org: any non-empty value disables color
+    if is_set("NO_COLOR") {
+        return false;
+    }
+    if is_set("CLICOLOR_FORCE") && !is_zero("CLICOLOR_FORCE") {
+        return true;
+    }
+    if is_zero("CLICOLOR") {
+        return false;
+    }
+    is_terminal && env("TERM").as_deref() != Some("dumb")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled(when: &str, vars: &[(&str, &str)], is_terminal: bool) -> bool {
+        let env = |name: &str| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        color_enabled(when, env, is_terminal)
+    }
+
+    #[test]
+    fn test_auto_follows_terminal_and_env() {
+        assert!(enabled("auto", &[], true));
+        assert!(!enabled("auto", &[], false));
+        assert!(!enabled("auto", &[("TERM", "dumb")], true));
+
+        assert!(!enabled("auto", &[("NO_COLOR", "1")], true));
+        assert!(enabled("auto", &[("NO_COLOR", "")], true));
+        assert!(!enabled("auto", &[("CLICOLOR", "0")], true));
+
+        assert!(enabled("auto", &[("CLICOLOR_FORCE", "1")], false));
+        assert!(!enabled("auto", &[("CLICOLOR_FORCE", "0")], false));
+        assert!(!enabled(
+            "auto",
+            &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")],
+            false
+        ));
+    }
+
+    #[test]
+    fn test_explicit_choice_overrides_env() {
+        assert!(enabled("always", &[("NO_COLOR", "1")], false));
+        assert!(!enabled("never", &[("CLICOLOR_FORCE", "1")], true));
+    }
+}
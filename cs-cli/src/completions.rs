@@ -0,0 +1,52 @@
+//! `cs --completions SHELL`: a completion script for bash, zsh, fish, elvish or PowerShell.
+//! The model flags complete the embedding models in the model registry and the reranker
+//! aliases, as known to the `cs` that generated the script; paths complete as the shell's own
+//! file and directory names.
+
+use clap::builder::PossibleValuesParser;
+use clap::{Command, ValueHint};
+use clap_complete::Shell;
+
+/// Flags that take an embedding model name
+const EMBEDDING_MODEL_ARGS: &[&str] = &["model", "switch_model", "shadow_index", "shadow_compare"];
+
+pub fn script(command: Command, shell: Shell) -> Vec<u8> {
+    let mut command = with_value_completions(command);
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, "cs", &mut script);
+    script
+}
+
+/// `command` with the model flags restricted to the known names. Only the generated script
+/// sees the restriction; parsing still accepts any name, including custom models from config.
+fn with_value_completions(mut command: Command) -> Command {
+    let registry = cs_models::ModelRegistry::default();
+    let models: Vec<String> = registry.aliases().into_iter().map(str::to_string).collect();
+    for id in EMBEDDING_MODEL_ARGS {
+        command = command.mut_arg(*id, |arg| {
+            arg.value_parser(PossibleValuesParser::new(models.clone()))
+        });
+    }
+    command
+        .mut_arg("rerank_model", |arg| {
+            arg.value_parser(PossibleValuesParser::new(cs_engine::RERANK_MODEL_ALIASES))
+        })
+        .mut_arg("files", |arg| arg.value_hint(ValueHint::AnyPath))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_script_completes_model_names() {
+        let bash = String::from_utf8(script(crate::Cli::command(), Shell::Bash)).unwrap();
+        assert!(bash.contains("--completions"));
+        assert!(bash.contains("nomic-v1.5"), "embedding models missing");
+        assert!(bash.contains("bge-v2-m3"), "reranker aliases missing");
+
+        let fish = String::from_utf8(script(crate::Cli::command(), Shell::Fish)).unwrap();
+        assert!(fish.contains("bge-small"));
+    }
+}
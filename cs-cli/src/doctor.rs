@@ -0,0 +1,297 @@
+//! `cs --doctor`: checks the embedding setup, the index and free disk space, with a fix for
+//! each problem found. Nothing is downloaded or modified.
+
+use crate::progress::StatusReporter;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Instant;
+
+/// Free space below which indexing or a model download may fail
+const LOW_DISK_SPACE_BYTES: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Health {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub health: Health,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: String) -> Self {
+        Self {
+            name,
+            health: Health::Ok,
+            detail,
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: String, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            health: Health::Warn,
+            detail,
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: String, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            health: Health::Fail,
+            detail,
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check for the index governing `path`, with `model` as given by `--model`
+pub fn run_checks(path: &Path, model: Option<&str>) -> Vec<Check> {
+    let index_root = cs_engine::find_nearest_index_root(path).unwrap_or_else(|| path.to_path_buf());
+    let mut checks = Vec::new();
+
+    match cs_engine::resolve_model_for_path(path, model) {
+        Ok(resolved) => {
+            let registry = cs_models::ModelRegistry::default();
+            let provider = registry
+                .find(&resolved.canonical_name)
+                .map_or("fastembed", |(_, config)| config.provider.as_str());
+            checks.push(Check::ok(
+                "model",
+                format!(
+                    "{} (alias '{}', {} dims, {})",
+                    resolved.canonical_name, resolved.alias, resolved.dimensions, provider
+                ),
+            ));
+            if provider == "jina-api" {
+                checks.push(check_api_key());
+            } else {
+                checks.extend(check_local_model(&resolved.canonical_name, resolved.dimensions));
+            }
+        }
+        Err(e) => checks.push(Check::fail(
+            "model",
+            e.to_string(),
+            "Pick a model from the list with --model, or fix `model` in cs.toml / `cs --config set default-model`",
+        )),
+    }
+
+    checks.push(check_index(&index_root));
+    checks.push(check_disk_space("index disk", &index_root));
+    checks.push(check_disk_space(
+        "model cache disk",
+        &cs_embed::model_cache_dir(),
+    ));
+    checks
+}
+
+fn check_api_key() -> Check {
+    if cs_embed::has_api_key("JINA_API_KEY") {
+        Check::ok("api key", "JINA_API_KEY is set".to_string())
+    } else {
+        Check::fail(
+            "api key",
+            "JINA_API_KEY is not set".to_string(),
+            "export JINA_API_KEY=... or `cs --config set api-keys-file PATH` with a JINA_API_KEY=... line",
+        )
+    }
+}
+
+/// Model cache and ONNX Runtime: a cached model is loaded and used to embed a test string
+fn check_local_model(model: &str, dimensions: usize) -> Vec<Check> {
+    let cache_dir = cs_embed::model_cache_dir();
+    if !cs_embed::is_model_cached(model) {
+        return vec![Check::warn(
+            "model cache",
+            format!("{} is not downloaded to {}", model, cache_dir.display()),
+            format!(
+                "Run `cs --models download {}` with network access, or point `cs --config set cache-dir` at a directory that has it",
+                model
+            ),
+        )];
+    }
+
+    let mut checks = vec![Check::ok(
+        "model cache",
+        format!("{} cached in {}", model, cache_dir.display()),
+    )];
+    let started = Instant::now();
+    let embedded = cs_embed::create_embedder(Some(model))
+        .and_then(|mut embedder| embedder.embed(&["cs doctor".to_string()]));
+    checks.push(match embedded {
+        Ok(embeddings) if embeddings.first().map(Vec::len) == Some(dimensions) => Check::ok(
+            "onnx runtime",
+            format!("embedded a test query in {:?}", started.elapsed()),
+        ),
+        Ok(embeddings) => Check::fail(
+            "onnx runtime",
+            format!(
+                "test embedding has {} dims, {} expected",
+                embeddings.first().map_or(0, Vec::len),
+                dimensions
+            ),
+            format!("Delete {} to re-download the model", cache_dir.display()),
+        ),
+        Err(e) => Check::fail(
+            "onnx runtime",
+            format!("cannot run {}: {}", model, e),
+            "Try `cs --config set device cpu`; if it persists, delete the model cache to re-download it",
+        ),
+    });
+    checks
+}
+
+fn check_index(index_root: &Path) -> Check {
+    let health = match cs_index::health::check_index_health(index_root) {
+        Ok(Some(health)) => health,
+        Ok(None) => {
+            return Check::warn(
+                "index",
+                format!("no index at {}", index_root.display()),
+                "Run `cs --index .` (semantic searches also build it on first use)",
+            );
+        }
+        Err(e) => {
+            return Check::fail(
+                "index",
+                format!("cannot read the manifest: {}", e),
+                "Run `cs --clean .` then `cs --index .`",
+            );
+        }
+    };
+    if health.is_healthy() {
+        return Check::ok(
+            "index",
+            format!(
+                "{} files; manifest, sidecars and shadow partitions agree",
+                health.files
+            ),
+        );
+    }
+
+    let mut problems = Vec::new();
+    let mut fixes = Vec::new();
+    let listed = |paths: &[std::path::PathBuf]| {
+        let mut names: Vec<String> = paths
+            .iter()
+            .take(3)
+            .map(|path| path.display().to_string())
+            .collect();
+        if paths.len() > 3 {
+            names.push("...".to_string());
+        }
+        names.join(", ")
+    };
+    if !health.missing_sidecars.is_empty() || !health.orphaned_sidecars.is_empty() {
+        problems.push(format!(
+            "{} entries without sidecars, {} sidecars without entries ({})",
+            health.missing_sidecars.len(),
+            health.orphaned_sidecars.len(),
+            listed(
+                &[
+                    health.missing_sidecars.as_slice(),
+                    health.orphaned_sidecars.as_slice(),
+                ]
+                .concat()
+            )
+        ));
+        fixes.push("`cs --clean-orphans .`");
+    }
+    if !health.unreadable_sidecars.is_empty() {
+        problems.push(format!(
+            "{} unreadable sidecars ({})",
+            health.unreadable_sidecars.len(),
+            listed(&health.unreadable_sidecars)
+        ));
+        fixes.push("`cs --clean .` then `cs --index .`");
+    }
+    if !health.model_mismatches.is_empty() {
+        problems.push(format!(
+            "{} sidecars embedded with another model ({})",
+            health.model_mismatches.len(),
+            listed(&health.model_mismatches)
+        ));
+        fixes.push("`cs --switch-model MODEL --force .`");
+    }
+    if !health.shadow_problems.is_empty() {
+        problems.push(format!(
+            "shadow partitions: {}",
+            health.shadow_problems.join("; ")
+        ));
+        fixes.push("rebuild the shadow with `cs --shadow-index MODEL .`");
+    }
+    Check::fail("index", problems.join("; "), fixes.join(", "))
+}
+
+fn check_disk_space(name: &'static str, path: &Path) -> Check {
+    // The directory may not exist yet; its nearest existing ancestor is on the same disk
+    let Some(existing) = path.ancestors().find(|dir| dir.exists()) else {
+        return Check::ok(name, format!("{} not created yet", path.display()));
+    };
+    match available_bytes(existing) {
+        Some(bytes) if bytes < LOW_DISK_SPACE_BYTES => Check::warn(
+            name,
+            format!(
+                "{} MB free at {}",
+                bytes / (1024 * 1024),
+                existing.display()
+            ),
+            "Free up space; indexing and model downloads need room",
+        ),
+        Some(bytes) => Check::ok(
+            name,
+            format!(
+                "{:.1} GB free at {}",
+                bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+                existing.display()
+            ),
+        ),
+        None => Check::ok(
+            name,
+            format!("free space unknown at {}", existing.display()),
+        ),
+    }
+}
+
+/// Free space on the disk holding `path`, from `df` (not available on Windows)
+fn available_bytes(path: &Path) -> Option<u64> {
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().nth(1)?;
+    let kilobytes: u64 = line.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Print the checks with their fixes; returns whether any failed
+pub fn report(status: &StatusReporter, checks: &[Check]) -> bool {
+    status.section_header("cs doctor");
+    for check in checks {
+        let line = format!("{:<17} {}", check.name, check.detail);
+        match check.health {
+            Health::Ok => status.success(&line),
+            Health::Warn => status.warn(&line),
+            Health::Fail => status.error(&line),
+        }
+        if let Some(fix) = &check.fix {
+            status.info(&format!("  fix: {}", fix));
+        }
+    }
+    checks.iter().any(|check| check.health == Health::Fail)
+}
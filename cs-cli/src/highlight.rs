@@ -0,0 +1,122 @@
+// Syntax highlighting of result previews for `--highlight`
+// Colors come from syntect's bundled grammars; matched substrings are drawn bold and inverted on top
+
+use owo_colors::{OwoColorize, Style};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// Same theme as the TUI's syntax preview
+const THEME: &str = "base16-ocean.dark";
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme() -> Option<&'static Theme> {
+    static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+    let themes = &THEME_SET.get_or_init(ThemeSet::load_defaults).themes;
+    themes.get(THEME).or_else(|| themes.values().next())
+}
+
+/// Highlight `text` with the grammar for `path`'s extension, drawing the byte ranges in
+/// `matches` bold and inverted. Unknown languages keep their text uncolored apart from matches.
+pub fn highlight(text: &str, path: &Path, matches: &[Range<usize>]) -> String {
+    let syntax_set = syntax_set();
+    let syntax = syntax_set
+        .find_syntax_for_file(path)
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+    let mut highlighter = theme().map(|theme| HighlightLines::new(syntax, theme));
+
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut offset = 0;
+    for line in LinesWithEndings::from(text) {
+        let regions = highlighter
+            .as_mut()
+            .and_then(|highlighter| highlighter.highlight_line(line, syntax_set).ok())
+            .unwrap_or_else(|| vec![(Default::default(), line)]);
+        for (region_style, region) in regions {
+            let color = region_style.foreground;
+            let base = if color.a == 0 {
+                Style::new()
+            } else {
+                Style::new().truecolor(color.r, color.g, color.b)
+            };
+            push_region(&mut out, region, offset, base, matches);
+            offset += region.len();
+        }
+    }
+    out
+}
+
+/// Append `region` (starting at byte `start` of the preview), split where matches begin and end
+fn push_region(
+    out: &mut String,
+    region: &str,
+    start: usize,
+    base: Style,
+    matches: &[Range<usize>],
+) {
+    let end = start + region.len();
+    let mut cuts = vec![start, end];
+    for range in matches {
+        cuts.extend(
+            [range.start, range.end]
+                .into_iter()
+                .filter(|&at| at > start && at < end),
+        );
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    for window in cuts.windows(2) {
+        let piece = &region[window[0] - start..window[1] - start];
+        // Escapes stay off the line ending so `lines()` on the output sees clean lines
+        let body = piece.trim_end_matches(['\n', '\r']);
+        let in_match = matches
+            .iter()
+            .any(|range| range.start <= window[0] && window[0] < range.end);
+        let style = if in_match {
+            base.bold().reversed()
+        } else {
+            base
+        };
+        if !body.is_empty() {
+            out.push_str(&body.style(style).to_string());
+        }
+        out.push_str(&piece[body.len()..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_keeps_text_and_marks_matches() {
+        let text = "fn main() {\n    let todo = 1;\n}\n";
+        let matches: Vec<_> = ["main", "todo"]
+            .iter()
+            .map(|word| {
+                let start = text.find(word).unwrap();
+                start..start + word.len()
+            })
+            .collect();
+        let highlighted = highlight(text, Path::new("main.rs"), &matches);
+
+        assert_ne!(highlighted, text);
+        assert_eq!(console::strip_ansi_codes(&highlighted), text);
+        assert_eq!(highlighted.lines().count(), 3);
+        // Bold (1) and inverted (7) around the matches only
+        assert!(highlighted.contains(";1;7mmain\x1b[0m"));
+        assert!(highlighted.contains(";1;7mtodo\x1b[0m"));
+        assert_eq!(highlighted.matches(";1;7m").count(), 2);
+    }
+}
@@ -6,4 +6,7 @@ pub mod path_utils;
 // TUI is now in its own crate: cc-tui
 
 // Re-export commonly used types for testing
-pub use mcp_server::{CcMcpServer, HybridSearchRequest, RegexSearchRequest, SemanticSearchRequest};
+pub use mcp_server::{
+    CcMcpServer, CleanIndexRequest, FileOutlineRequest, GetChunkRequest, HybridSearchRequest,
+    RegexSearchRequest, SemanticSearchRequest,
+};
@@ -0,0 +1,91 @@
+//! Diagnostics from `tracing` events, written to stderr so they never mix with results.
+//! `RUST_LOG` decides what is shown when set; otherwise `--verbose` and `--debug` raise the
+//! cs crates to info and debug while dependencies stay at the mode's default level.
+//! `--log-json` writes one JSON object per event for pipelines that collect diagnostics.
+
+use tracing::Level;
+use tracing_subscriber::EnvFilter;
+
+/// Module path roots of the workspace crates; the `cs` binary and the `cs_search` library
+/// both compile the CLI modules
+const CS_TARGETS: &[&str] = &[
+    "cs",
+    "cs_search",
+    "cs_ann",
+    "cs_chunk",
+    "cs_core",
+    "cs_embed",
+    "cs_engine",
+    "cs_index",
+    "cs_models",
+    "cs_tui",
+];
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogOptions {
+    pub verbose: bool,
+    pub debug: bool,
+    pub json: bool,
+}
+
+/// Filter directives when `RUST_LOG` is unset; `default` is the level of a run with neither
+/// `--verbose` nor `--debug`, and stays the level of dependencies
+fn directives(options: LogOptions, default: Level) -> String {
+    let cs_level = if options.debug {
+        Level::DEBUG
+    } else if options.verbose {
+        Level::INFO
+    } else {
+        return default.to_string().to_lowercase();
+    };
+    let cs_level = cs_level.max(default).to_string().to_lowercase();
+    let mut directives = default.to_string().to_lowercase();
+    for target in CS_TARGETS {
+        directives.push_str(&format!(",{}={}", target, cs_level));
+    }
+    directives
+}
+
+/// Install the global subscriber; call once, before the first event
+pub fn init(options: LogOptions, default: Level) {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(directives(options, default)));
+    let builder = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(filter);
+    if options.json {
+        builder.json().init();
+    } else {
+        builder.with_ansi(console::colors_enabled_stderr()).init();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directives_raise_only_cs_crates() {
+        assert_eq!(directives(LogOptions::default(), Level::WARN), "warn");
+        assert_eq!(directives(LogOptions::default(), Level::INFO), "info");
+
+        let verbose = LogOptions {
+            verbose: true,
+            ..Default::default()
+        };
+        let filter = directives(verbose, Level::WARN);
+        assert!(filter.starts_with("warn,cs=info,"), "{}", filter);
+        assert!(filter.contains("cs_engine=info"));
+
+        let debug = LogOptions {
+            debug: true,
+            verbose: true,
+            ..Default::default()
+        };
+        let filter = directives(debug, Level::INFO);
+        assert!(filter.starts_with("info,cs=debug,"), "{}", filter);
+        assert!(filter.contains("cs_index=debug"));
+        // Every generated directive parses
+        EnvFilter::try_new(filter).unwrap();
+    }
+}
@@ -0,0 +1,427 @@
+//! `cs --lsp`: a minimal language server on stdio, so editors query the index through the LSP
+//! client they already have instead of a dedicated plugin. `workspace/symbol` answers with the
+//! definitions of the indexed files, as `--export` sees them, whose names contain the query
+//! (case-insensitively, exact and prefix matches first). The custom `cs/semanticSearch`
+//! request takes `{ query, uri?, mode?, topK?, threshold? }` and answers with ranked chunks
+//! (`{ uri, range, score, symbol?, preview }`); the index is brought up to date first, as
+//! before any search. Documents are not synced: results come from the files on disk.
+
+use anyhow::Result;
+use cs_core::{SearchMode, SearchOptions, SearchResult};
+use crate::jsonrpc::{
+    METHOD_NOT_FOUND, REQUEST_FAILED, RpcError, read_message, response, write_message,
+};
+use cs_index::export::{Position, SymbolExport};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::io::{AsyncBufRead, AsyncWrite};
+
+/// Symbols returned for one `workspace/symbol` request
+const MAX_SYMBOLS: usize = 256;
+
+const DEFAULT_TOP_K: usize = 20;
+
+/// LSP's error for requests before `initialize`
+const SERVER_NOT_INITIALIZED: i64 = -32002;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SemanticSearchParams {
+    query: String,
+    /// File or directory to search, the workspace root by default
+    uri: Option<String>,
+    /// `semantic` (default), `hybrid` or `lexical`
+    mode: Option<String>,
+    top_k: Option<usize>,
+    threshold: Option<f32>,
+}
+
+#[derive(Default)]
+struct LspServer {
+    /// Workspace root from `initialize`
+    root: Option<PathBuf>,
+    /// `lsp:<client>`, for the audit log
+    requester: String,
+    /// Symbols of the index, with the manifest modification time they were collected at
+    symbols: Option<(PathBuf, SystemTime, SymbolExport)>,
+    shutdown: bool,
+}
+
+/// Serve LSP messages from `reader` until `exit` or the end of input
+pub async fn serve<R, W>(mut reader: R, mut writer: W) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut server = LspServer::default();
+    while let Some(message) = read_message(&mut reader).await? {
+        // Responses to requests we never send, and anything else without a method
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            continue;
+        };
+        if method == "exit" {
+            break;
+        }
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let result = server.handle(method, params).await;
+        let Some(id) = message.get("id").cloned() else {
+            if let Err(e) = result
+                && e.code != METHOD_NOT_FOUND
+            {
+                tracing::warn!("{} failed: {}", method, e.message);
+            }
+            continue;
+        };
+        write_message(&mut writer, &response(id, result)).await?;
+    }
+    Ok(())
+}
+
+impl LspServer {
+    async fn handle(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        if method == "initialize" {
+            return Ok(self.initialize(&params));
+        }
+        if self.root.is_none() {
+            return Err(RpcError::new(
+                SERVER_NOT_INITIALIZED,
+                "initialize has not been called",
+            ));
+        }
+        match method {
+            "initialized" => Ok(Value::Null),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            _ if self.shutdown => Err(RpcError::new(REQUEST_FAILED, "the server is shutting down")),
+            "workspace/symbol" => {
+                let query = params
+                    .get("query")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| RpcError::invalid_params("query is missing"))?;
+                self.workspace_symbols(query)
+            }
+            "cs/semanticSearch" => {
+                let params: SemanticSearchParams = serde_json::from_value(params)
+                    .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                self.semantic_search(params).await
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("{} is not supported", method),
+            )),
+        }
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        let root = params
+            .get("rootUri")
+            .and_then(Value::as_str)
+            .or_else(|| {
+                params
+                    .pointer("/workspaceFolders/0/uri")
+                    .and_then(Value::as_str)
+            })
+            .and_then(uri_to_path)
+            .or_else(|| {
+                params
+                    .get("rootPath")
+                    .and_then(Value::as_str)
+                    .map(PathBuf::from)
+            })
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("."));
+        self.root = Some(root);
+        self.requester = match params.pointer("/clientInfo/name").and_then(Value::as_str) {
+            Some(client) => format!("lsp:{}", client),
+            None => "lsp".to_string(),
+        };
+        json!({
+            "capabilities": {
+                "workspaceSymbolProvider": true,
+                "experimental": { "semanticSearch": true }
+            },
+            "serverInfo": { "name": "cs", "version": env!("CARGO_PKG_VERSION") }
+        })
+    }
+
+    fn workspace_symbols(&mut self, query: &str) -> Result<Value, RpcError> {
+        let root = self.root.clone().unwrap_or_default();
+        let index_root = cs_engine::find_nearest_index_root(&root).ok_or_else(|| {
+            RpcError::new(
+                REQUEST_FAILED,
+                format!("No index at {}; run 'cs --index' first", root.display()),
+            )
+        })?;
+        // Re-chunking every file is the slow part, so symbols are kept until the index changes
+        let modified = std::fs::metadata(index_root.join(".cs").join("manifest.json"))
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let cached = matches!(&self.symbols, Some((cached_root, cached_modified, _))
+            if *cached_root == index_root && *cached_modified == modified);
+        if !cached {
+            let export = cs_index::export::collect_symbols(&index_root)?;
+            self.symbols = Some((index_root.clone(), modified, export));
+        }
+        let Some((_, _, export)) = &self.symbols else {
+            unreachable!("symbols were collected above");
+        };
+
+        let query = query.to_lowercase();
+        let mut matches: Vec<(u8, _)> = export
+            .symbols
+            .iter()
+            .filter_map(|symbol| {
+                let name = symbol.name.to_lowercase();
+                let rank = if name == query {
+                    0
+                } else if name.starts_with(&query) {
+                    1
+                } else if name.contains(&query) {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, symbol))
+            })
+            .collect();
+        // Stable, so symbols of one rank stay in path and line order
+        matches.sort_by_key(|(rank, _)| *rank);
+        let symbols: Vec<Value> = matches
+            .into_iter()
+            .take(MAX_SYMBOLS)
+            .map(|(_, symbol)| {
+                json!({
+                    "name": symbol.name,
+                    "kind": cs_index::export::lsp_symbol_kind(&symbol.kind),
+                    "location": {
+                        "uri": cs_index::export::file_uri(&index_root.join(&symbol.path)),
+                        "range": { "start": symbol.start, "end": symbol.end }
+                    },
+                    "containerName": symbol.scope
+                })
+            })
+            .collect();
+        Ok(Value::Array(symbols))
+    }
+
+    async fn semantic_search(&self, params: SemanticSearchParams) -> Result<Value, RpcError> {
+        let root = self.root.clone().unwrap_or_default();
+        let path = match &params.uri {
+            Some(uri) => uri_to_path(uri)
+                .ok_or_else(|| RpcError::invalid_params(format!("not a file URI: {}", uri)))?,
+            None => root.clone(),
+        };
+        let mode = match params.mode.as_deref().unwrap_or("semantic") {
+            "semantic" => SearchMode::Semantic,
+            "hybrid" => SearchMode::Hybrid,
+            "lexical" => SearchMode::Lexical,
+            other => {
+                return Err(RpcError::invalid_params(format!(
+                    "mode must be semantic, hybrid or lexical, not {}",
+                    other
+                )));
+            }
+        };
+        let project = cs_models::ProjectConfig::discover(&root)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let exclude_patterns = crate::path_utils::project_exclude_patterns(&root, &project);
+
+        let options = SearchOptions {
+            mode,
+            query: params.query,
+            path,
+            top_k: params.top_k.or(project.top_k).or(Some(DEFAULT_TOP_K)),
+            threshold: params.threshold.or(project.threshold),
+            show_scores: true,
+            requester: Some(self.requester.clone()),
+            exclude_patterns,
+            ..Default::default()
+        };
+        let results = cs_engine::search_enhanced(&options).await?;
+        let matches: Vec<Value> = results
+            .matches
+            .iter()
+            .map(|result| {
+                json!({
+                    "uri": cs_index::export::file_uri(&absolute(&root, &result.file)),
+                    "range": range(result),
+                    "score": result.score,
+                    "symbol": result.symbol,
+                    "preview": result.preview
+                })
+            })
+            .collect();
+        Ok(Value::Array(matches))
+    }
+}
+
+fn absolute(root: &Path, file: &Path) -> PathBuf {
+    if file.is_absolute() {
+        file.to_path_buf()
+    } else {
+        root.join(file)
+    }
+}
+
+/// Range of a result in the file, in UTF-16 code units as LSP counts them; whole lines when
+/// the file cannot be read back
+fn range(result: &SearchResult) -> Value {
+    let span = &result.span;
+    let Ok(content) = cs_core::encoding::read_to_string(&result.file) else {
+        return json!({
+            "start": { "line": span.line_start.saturating_sub(1), "character": 0 },
+            "end": { "line": span.line_end, "character": 0 }
+        });
+    };
+    json!({
+        "start": position(&content, span.byte_start),
+        "end": position(&content, span.byte_end)
+    })
+}
+
+fn position(content: &str, byte: usize) -> Position {
+    let mut byte = byte.min(content.len());
+    while !content.is_char_boundary(byte) {
+        byte -= 1;
+    }
+    let before = &content[..byte];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Position {
+        line: before.matches('\n').count(),
+        character: before[line_start..].encode_utf16().count(),
+    }
+}
+
+/// Path of a `file://` URI
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    // Percent-decode bytes, so multi-byte characters come back whole
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    let path = String::from_utf8(decoded).ok()?;
+    // file:///C:/src on Windows
+    let path = match path.strip_prefix('/') {
+        Some(rest) if cfg!(windows) && rest.get(1..2) == Some(":") => rest.to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc::frame;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_workspace_symbols_over_stdio() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("lib.rs"),
+            "struct Parser;\n\nfn parse() {}\n\nfn parse_header() {}\n\nfn main() {}\n",
+        )
+        .unwrap();
+        cs_index::smart_update_index(root, false, true, &[])
+            .await
+            .unwrap();
+
+        let root_uri = cs_index::export::file_uri(root);
+        let input: Vec<u8> = [
+            json!({ "jsonrpc": "2.0", "id": 0, "method": "workspace/symbol", "params": { "query": "x" } }),
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "rootUri": root_uri, "clientInfo": { "name": "nvim" } } }),
+            json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "workspace/symbol", "params": { "query": "PARSE" } }),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "textDocument/hover", "params": {} }),
+            json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ]
+        .into_iter()
+        .flat_map(frame)
+        .collect();
+        let mut output = Vec::new();
+        serve(input.as_slice(), &mut output).await.unwrap();
+
+        let mut reader = output.as_slice();
+        let mut responses = Vec::new();
+        while let Some(response) = read_message(&mut reader).await.unwrap() {
+            responses.push(response);
+        }
+        let ids: Vec<_> = responses.iter().map(|r| r["id"].clone()).collect();
+        assert_eq!(ids, vec![json!(0), json!(1), json!(2), json!(3), json!(4)]);
+        assert_eq!(responses[0]["error"]["code"], SERVER_NOT_INITIALIZED);
+        assert_eq!(
+            responses[1]["result"]["capabilities"]["workspaceSymbolProvider"],
+            true
+        );
+        assert_eq!(responses[3]["error"]["code"], METHOD_NOT_FOUND);
+
+        // The exact name first, then prefixes and names containing the query
+        let symbols = responses[2]["result"].as_array().unwrap();
+        let names: Vec<_> = symbols
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["parse", "Parser", "parse_header"]);
+        assert_eq!(symbols[0]["kind"], 12);
+        assert_eq!(
+            symbols[0]["location"]["uri"],
+            cs_index::export::file_uri(&root.join("lib.rs"))
+        );
+        assert_eq!(
+            symbols[0]["location"]["range"]["start"],
+            json!({ "line": 2, "character": 0 })
+        );
+    }
+
+    #[test]
+    fn test_uri_and_position_conversions() {
+        assert_eq!(
+            uri_to_path("file:///home/me/My%20Repo/caf%C3%A9.rs"),
+            Some(PathBuf::from("/home/me/My Repo/café.rs"))
+        );
+        assert_eq!(uri_to_path("untitled:Untitled-1"), None);
+
+        let content = "fn main() {\n    let café = open();\n}";
+        let open = content.find("open").unwrap();
+        // UTF-16 code units: "é" counts once
+        assert_eq!(
+            position(content, open),
+            Position {
+                line: 1,
+                character: 15
+            }
+        );
+        assert_eq!(
+            position(content, content.len()),
+            Position {
+                line: 2,
+                character: 1
+            }
+        );
+    }
+}
@@ -2,21 +2,37 @@ use anyhow::Result;
 use cs_core::{
     IncludePattern, SearchMode, SearchOptions, get_default_csignore_content,
     heatmap::{self, HeatmapBucket},
+    redact::PathRedactor,
 };
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use console::style;
 use owo_colors::{OwoColorize, Rgb};
-use regex::RegexBuilder;
 use std::path::{Path, PathBuf};
-
+use tokio_util::sync::CancellationToken;
+
+mod chunk_report;
+mod color;
+mod completions;
+mod doctor;
+mod highlight;
+mod jsonrpc;
+mod logging;
+mod lsp;
 mod mcp;
 mod mcp_server;
 mod path_utils;
 mod progress;
+mod rpc;
+mod run_stats;
+mod search_modes;
+mod similar;
+mod vcs;
+mod vimgrep;
 // TUI is now in its own crate: cs-tui
 
 use path_utils::{build_include_patterns, expand_glob_patterns};
 use progress::StatusReporter;
+use similar::SimilarTo;
 
 #[derive(Parser)]
 #[command(name = "cs")]
@@ -36,7 +52,14 @@ QUICK START EXAMPLES:
     cs --sem "error handling" src/     # Builds/updates the index automatically (top 10, threshold ≥0.6)
     cs --sem "database connection"     # Find DB-related code  
     cs --sem --limit 5 "authentication"    # Limit to top 5 results
+    cs --sem --diversity 0.3 "retry"  # Spread results across files instead of near-duplicates
+    cs --sem --dedupe "retry"         # One result per piece of code: a method, not also its class
+    cs --sem "retry" --not "unit tests"  # Steer results away from a concept (repeatable)
+    cs --sem --expand-query "auth flow"  # Also search synonyms (authentication, login, ...)
+    cs --hybrid --explain "auth flow"    # Show cosine, RRF and rerank components per result
+    cs --lang rust,py "retry"            # Only search Rust and Python files (cs.toml: languages)
     cs --sem --threshold 0.8 "auth"   # Higher precision filtering
+    cs --sem --timeout 5s "auth"      # Return best results found within 5 seconds
 
   Lexical search (BM25 full-text search):
     cs --lex "user authentication"    # Full-text search with ranking
@@ -46,8 +69,14 @@ QUICK START EXAMPLES:
     cs --hybrid "async function"      # Best of both worlds
     cs --hybrid "function $NAME" .    # Auto-detects AST pattern, includes AST search
     cs --hybrid "error" --limit 10    # Top 10 most relevant results (--limit is alias for --topk)
-    cs --hybrid "bug" --threshold 0.02 # Only results with RRF score >= 0.02
+    cs --hybrid "bug" --threshold 0.5  # Only results that led at least one of two fused lists
+    cs --hybrid-lex "retry failed uploads"  # Hybrid that also fuses BM25 lexical hits
     cs --sem "auth" --scores           # Show similarity scores in output
+    cs --hybrid "auth" --scores-raw    # Show raw RRF sums instead of calibrated scores
+    cs --sem --only functions "retry"  # Only function chunks (also classes, methods, modules)
+    cs --sem --no-tests "retry policy" # Skip test files and test functions
+    cs --sem --ephemeral "auth" /mnt/ro # Embed in memory; no .cs index is written
+    cs --sem --files-ranked "auth" src/ # Files ranked by best chunk (or mean, weighted)
 
   AST structural search (code structure matching):
     cs --ast 'function $NAME($$)' .   # Find all functions with any parameters
@@ -55,14 +84,38 @@ QUICK START EXAMPLES:
     cs --ast 'if $COND { $$ }' --ast-lang rust  # Find if statements, force Rust
     cs --ast 'impl $TRAIT for $TYPE' . # Find trait implementations
 
+  Symbol lookup (definition names, no index needed):
+    cs --symbols parse_header src/     # Print the whole definition of parse_header
+    cs --symbols -i '^user' .          # Definitions whose names start with "user"
+
+  Automatic mode selection:
+    cs --auto "fn parse_header"        # Code-like query -> regex
+    cs --auto "where do we retry failed uploads"  # Question -> semantic
+    cs --config set default-search-mode auto      # Make auto the default
+
   Index management:
     cs --status .                     # Check index status
-    cs --status-verbose .              # Detailed index statistics
+    cs --doctor                        # Check model, runtime, API keys, index and disk space
+    cs --status-verbose .              # Breakdown by language, chunk type, model; largest/stale files
+    cs --status --json .               # Index statistics as JSON
     cs --clean-orphans .               # Clean up orphaned files
+    cs --gc .                          # Remove index garbage and caches past the cs.toml [gc] limits
     cs --clean .                       # Remove entire index
     cs --switch-model nomic-v1.5       # Clean + rebuild with a different embedding model
+    cs --models                        # Registry models, which are downloaded and their size
+    cs --models download bge-small nomic-v1.5  # Fetch models now, e.g. before going offline
+    cs --models remove minilm          # Delete a downloaded model (also: cs --models path)
+    cs --shadow-index nomic-v1.5 .     # Embed a file sample with another model (primary untouched)
+    cs --shadow-compare nomic-v1.5 --shadow-queries queries.txt  # Compare both models
+    cs --export ctags . > tags         # ctags file of indexed definitions (or --export lsif)
+    cs --audit-log hash .              # Log every query (hashed; or full) to .cs/audit/
+    cs --audit-tail 50 .               # Last 50 audit records: time, mode, requester, results
     cs --add file.rs                   # Add single file to index
     cs --index .                       # Optional: pre-build before CI runs
+    cs --export-bundle index.tar.zst   # Package the index; cs --import-bundle index.tar.zst on CI
+    cs --remote-push s3://team/semcs . # Share embeddings; --remote URL (or cs.toml) pulls them
+    cs --index --shard 2/4 --shard-out ../shards .  # Embed a quarter of the files per machine...
+    cs --merge-shards ../shards/*      # ...then merge all shard artifacts into ./.cs
 
   JSON output for tools/scripts:
     cs --json --sem "bug fix" src/    # Traditional JSON (single array)
@@ -79,12 +132,61 @@ QUICK START EXAMPLES:
     cs -A 3 -B 1 "TODO"              # 3 lines after, 1 before
     cs -w "test" .                    # Match whole words only
     cs -F "log.Error()" .             # Fixed string (no regex)
+    cs -S "fixme" src/                # Smart case: ignore case unless the pattern has capitals
+    cs -P 'foo(?!_test)' src/         # PCRE2 look-around/backreferences (pcre2 feature)
+    cs -U 'fn \w+\([^)]*\)\s*\{' src/  # Multiline: a match may span lines
+    cs -c "TODO" src/                 # Count matches per file (file:count)
+    cs -H --sem "retry" lib.rs        # Name the file even when searching just one
+    cs --no-filename "TODO" src/      # Matches without file names, in any mode
+    cs --vimgrep "TODO" src/          # file:line:col:text per match, for editor quickfix lists
+    cs --lsp                          # Language server on stdio: workspace/symbol, cs/semanticSearch
+    cs --rpc                          # JSON-RPC server for editor extensions, re-indexing on save
+    cs -v "^\s*//" main.rs           # Invert match: lines NOT matching
+    cs -e TODO -e FIXME src/          # Match any of several patterns
+    cs -o 'v[0-9.]+' CHANGELOG.md     # Print only the matched text
+    cs -q "TODO" src/ && echo found    # Exit status only: 0 match, 1 none, 2 error
+    cs --replace '$2=$1' '(\w+):(\w+)' x  # Rewrite matches using capture groups
+    cs -f patterns.txt .              # Read patterns from a file (one per line)
+    cs -z "panic" logs/               # Also search .gz/.zst/.bz2 files and zip/tar entries
+    cat app.log | cs "timeout" -       # Search standard input (also --sem/--hybrid)
+    cs --color=always "TODO" | less -R  # Keep colors when piping (NO_COLOR=1 disables them)
+    cs --sem "race condition" --changed # Only files with uncommitted changes (git or jj)
+    cs "TODO" --since main src/       # Only files changed since a revision (also --staged)
+    cs --sem "token refresh" --within auth.jsonl  # Only inside earlier --jsonl results (- for stdin)
+    cs --highlight -w "parse" src/     # Syntax-highlight previews, matches inverted
+    cs --sem "retry" --topk 10 --offset 10  # Second page (or --cursor TOKEN from the first)
+    cs "ERROR" --max-filesize 10MB logs/  # Skip huge files (indexing skips >2MB by default)
 
   Model and embedding options:
     cs --index --model nomic-v1.5      # Index with higher-quality model (8k context)
     cs --index --model jina-code       # Index with code-specialized model
+    cs --index --dry-run .             # Chunks, tokens and API cost per model, nothing embedded
+    cs --index src/api/                # Update just src/api/ in the enclosing index (also --from-file)
+    cs --map .                         # Topic map: clusters of related code with label terms
+    cs --calibrate .                   # Score distributions and a suggested --threshold/--topk
+    cs --bench --json . > bench.json   # Chunk/embed throughput, ANN build, query latency
+    cs --chunks src/lib.rs             # List chunks with spans, tokens and stride overlaps
+    cs --get-chunk 3f9a2c1b src/lib.rs # Print a result's chunk by its id (chunk_hash)
+    cs --index --chunk-tokens 256 .    # Smaller chunks (also --chunk-overlap, --no-stride)
+    cs --index --embed-template "{path} :: {symbol} :: {code}" .  # Embed paths and names too
+    cs --index --sparse . && cs --sem "parse_retry_policy"  # Exact identifiers rank higher
+    cs --index --allow-secrets .       # Also index chunks that look like keys or tokens
+    cs --similar-to src/retry.rs:40-80 . # Code like lines 40-80 elsewhere in the repo
     cs --sem "auth" --rerank           # Enable reranking for better relevance
     cs --sem "login" --rerank-model bge # Use specific reranking model
+    cs --hybrid "retry" --rerank --rerank-candidates 100 --rerank-blend 0.7  # Wider pool, blended scores
+    cs --daemon &                      # Keep embedding models loaded for later searches
+    cs --sem "retry" --no-daemon       # Load the model in this process even if the daemon runs
+    cs --sem "retry" --debug 2> debug.log  # Diagnostics on stderr (also --verbose, --log-json, RUST_LOG)
+    cs --sem "retry" --stats           # Files scanned/skipped, chunks scored, time per phase
+    cs --jsonl --redact-paths "token" ~/app  # Root-relative paths, no user names, for sharing
+    cs --serve --mcp-root ~/work --mcp-read-only  # MCP tools limited to ~/work, index never changed
+    cs --serve --mcp-rate-limit 30 --mcp-timeout 10s  # Budget agents' searches
+
+  Shell completions:
+    cs --completions bash > ~/.local/share/bash-completion/completions/cs
+    cs --completions zsh > "${fpath[1]}/_cs"   # Also fish, elvish, powershell
+    cs --man | man -l -                # Man page (install as cs.1)
 
   AI agent integration (MCP):
     cs --serve                         # Start MCP server for Claude/Cursor integration
@@ -97,12 +199,16 @@ QUICK START EXAMPLES:
   --sem     : Semantic/embedding search (auto-indexed, defaults: top 10, threshold ≥0.6)
   --hybrid  : Combines regex + semantic + AST (auto-includes AST if pattern contains $)
   --ast     : AST structural search using ast-grep (requires ast-grep installed)
+  --auto    : Pick one of the above from the query shape (or set default-search-mode=auto)
+  cs --help-modes shows each mode's defaults and index use
 
 RESULT FILTERING:
   --topk, --limit N : Limit to top N results (default: 10 for semantic search)
   --threshold SCORE : Filter by minimum score (default: 0.6 for semantic search)
-                      (0.0-1.0 semantic/lexical, 0.01-0.05 hybrid RRF)
+                      (calibrated 0.0-1.0 in every mode, see cs --help-modes)
   --scores          : Show scores in output [0.950] file:line:match
+  --scores-raw      : Show cosine, BM25 or RRF scores instead of calibrated ones
+  --timeout DURATION: Soft time limit (500ms, 5s, 1m); returns partial results
 
 The semantic search understands meaning - searching for "error handling" 
 will find try/catch blocks, error returns, exception handling, etc.
@@ -117,10 +223,19 @@ struct Cli {
     #[arg(short = 'n', long = "line-number", help = "Show line numbers")]
     line_numbers: bool,
 
-    #[arg(long = "no-filename", help = "Suppress filenames in output")]
+    #[arg(
+        long = "no-filename",
+        overrides_with = "with_filenames",
+        help = "Suppress filenames in output, in every mode (the last of -H and --no-filename wins)"
+    )]
     no_filenames: bool,
 
-    #[arg(short = 'H', help = "Always print filenames")]
+    #[arg(
+        short = 'H',
+        long = "with-filename",
+        overrides_with = "no_filenames",
+        help = "Always print filenames, even for a single file (default: only when searching several files or a directory)"
+    )]
     with_filenames: bool,
 
     #[arg(
@@ -137,9 +252,103 @@ struct Cli {
     )]
     files_without_matches: bool,
 
-    #[arg(short = 'i', long = "ignore-case", help = "Case insensitive search")]
+    #[arg(
+        short = 'c',
+        long = "count",
+        help = "Print only a count of matches per file (matching lines for regex, chunks for semantic/lexical)"
+    )]
+    count: bool,
+
+    #[arg(
+        long = "files-ranked",
+        value_name = "AGG",
+        num_args = 0..=1,
+        default_missing_value = "max",
+        value_parser = ["max", "mean", "weighted"],
+        help = "Print a ranked list of files instead of chunk hits, scoring each file by its best chunk (max, default), the mean of its chunks, or weighted (best chunk plus halving shares of the next ones); --topk limits files",
+        conflicts_with_all = ["files_with_matches", "files_without_matches", "count", "invert_match", "only_matching", "replace"]
+    )]
+    files_ranked: Option<String>,
+
+    #[arg(
+        short = 'v',
+        long = "invert-match",
+        help = "Select non-matching lines (regex mode only)",
+        conflicts_with_all = ["semantic", "lexical", "hybrid", "ast", "symbols"]
+    )]
+    invert_match: bool,
+
+    #[arg(
+        short = 'o',
+        long = "only-matching",
+        help = "Print only the matched parts of matching lines, one per output line (regex mode only)",
+        conflicts_with_all = ["invert_match", "semantic", "lexical", "hybrid", "ast", "symbols"]
+    )]
+    only_matching: bool,
+
+    #[arg(
+        long = "replace",
+        value_name = "TEMPLATE",
+        allow_hyphen_values = true,
+        help = "Print matches rewritten with TEMPLATE; $1, ${name} expand capture groups, $$ is a literal $ (regex mode only)",
+        conflicts_with_all = ["invert_match", "semantic", "lexical", "hybrid", "ast", "symbols"]
+    )]
+    replace: Option<String>,
+
+    #[arg(
+        short = 'z',
+        long = "search-zip",
+        help = "Search inside archives and compressed files (.zip, .tar, .gz, .zst, .bz2); entries show as archive.zip!path (regex and lexical; never embedded)",
+        conflicts_with_all = ["semantic", "ast", "symbols"]
+    )]
+    search_zip: bool,
+
+    #[arg(
+        short = 'e',
+        long = "regexp",
+        value_name = "PATTERN",
+        allow_hyphen_values = true,
+        help = "Pattern to search for; repeat to match any of several patterns (positional args are then all paths)"
+    )]
+    regexp: Vec<String>,
+
+    #[arg(
+        short = 'f',
+        long = "file",
+        value_name = "FILE",
+        help = "Read patterns from FILE, one per line ('-' for stdin); combines with -e"
+    )]
+    pattern_files: Vec<PathBuf>,
+
+    #[arg(
+        short = 'i',
+        long = "ignore-case",
+        help = "Case insensitive search (regex, hybrid and symbols; lexical and semantic search always ignore case)"
+    )]
     ignore_case: bool,
 
+    #[arg(
+        short = 'S',
+        long = "smart-case",
+        help = "Ignore case unless a pattern contains an uppercase letter (like ripgrep); -i wins"
+    )]
+    smart_case: bool,
+
+    #[arg(
+        short = 'P',
+        long = "pcre2",
+        conflicts_with = "replace",
+        help = "Match regex patterns with PCRE2, for look-around and backreferences (builds with the pcre2 feature)"
+    )]
+    pcre2: bool,
+
+    #[arg(
+        short = 'U',
+        long = "multiline",
+        help = "Multiline: regex matches may span lines (\\n, \\s and [^x] match line breaks); ^ and $ match at each line"
+    )]
+    multiline: bool,
+
     #[arg(short = 'w', long = "word-regexp", help = "Match whole words only")]
     word_regexp: bool,
 
@@ -200,12 +409,49 @@ struct Cli {
     )]
     hybrid: bool,
 
+    #[arg(
+        long = "hybrid-lex",
+        help = "Hybrid search that also fuses BM25 lexical hits as a third ranked list (helps natural-language phrase queries); implies --hybrid",
+        conflicts_with_all = ["semantic", "lexical", "ast", "symbols", "regex", "auto"]
+    )]
+    hybrid_lex: bool,
+
     #[arg(
         long = "ast",
         help = "AST structural search - uses ast-grep for code structure matching"
     )]
     ast: bool,
 
+    #[arg(
+        long = "symbols",
+        help = "Symbol search - match the pattern against definition names (functions, classes, structs) and print whole definitions; no index needed",
+        conflicts_with_all = ["semantic", "lexical", "hybrid", "ast"]
+    )]
+    symbols: bool,
+
+    #[arg(
+        long = "only",
+        value_name = "KINDS",
+        value_delimiter = ',',
+        value_parser = ["functions", "classes", "methods", "modules"],
+        help = "Only return chunks of these kinds (comma-separated: functions, classes, methods, modules); semantic, lexical and symbol search"
+    )]
+    only: Vec<String>,
+
+    #[arg(
+        long = "lang",
+        value_name = "LANGS",
+        value_delimiter = ',',
+        help = "Only search files in these languages (comma-separated names or extensions: rust,python or rs,py); overrides `languages` in cs.toml"
+    )]
+    lang: Vec<cs_core::Language>,
+
+    #[arg(
+        long = "no-tests",
+        help = "Leave out test code: test files by path (tests/, *_test.go, test_*.py, *.spec.ts, ...) and, in semantic and symbol search, test functions and modules (#[test], mod tests, func TestX, def test_x) inside other files"
+    )]
+    no_tests: bool,
+
     #[arg(
         long = "ast-lang",
         value_name = "LANG",
@@ -223,6 +469,13 @@ struct Cli {
     #[arg(long = "regex", help = "Regex search mode (default, grep-compatible)")]
     regex: bool,
 
+    #[arg(
+        long = "auto",
+        help = "Pick the search mode from the query shape: code-like queries use regex, questions use semantic search",
+        conflicts_with_all = ["semantic", "lexical", "hybrid", "ast", "symbols", "regex"]
+    )]
+    auto: bool,
+
     #[arg(
         long = "topk",
         alias = "limit",
@@ -231,16 +484,67 @@ struct Cli {
     )]
     top_k: Option<usize>,
 
+    #[arg(
+        long = "offset",
+        value_name = "N",
+        conflicts_with_all = ["cursor", "files_ranked", "count", "files_with_matches", "files_without_matches"],
+        help = "Skip the first N ranked results; with --topk N pages through results"
+    )]
+    offset: Option<usize>,
+
+    #[arg(
+        long = "cursor",
+        value_name = "TOKEN",
+        conflicts_with_all = ["files_ranked", "count", "files_with_matches", "files_without_matches"],
+        help = "Continue a search from the cursor printed after a full page; pages come from the same index, and the search fails if the index changed"
+    )]
+    cursor: Option<cs_core::cursor::SearchCursor>,
+
     #[arg(
         long = "threshold",
         value_name = "SCORE",
-        help = "Minimum score threshold (0.0-1.0 for semantic/lexical, 0.01-0.05 for hybrid RRF) [default: 0.6 for semantic search]"
+        help = "Minimum calibrated score (0.0-1.0 in every ranked mode) [default: 0.6 for semantic search]"
     )]
     threshold: Option<f32>,
 
+    #[arg(
+        long = "timeout",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        help = "Soft time limit for the search (e.g. 500ms, 5s, 1m); returns the best results found so far, flagged as partial"
+    )]
+    timeout: Option<std::time::Duration>,
+
     #[arg(long = "scores", help = "Show similarity scores in output")]
     show_scores: bool,
 
+    #[arg(
+        long = "scores-raw",
+        help = "Show scores on each mode's own scale (cosine, BM25, RRF) instead of calibrated 0.0-1.0; implies --scores, and --threshold still uses the calibrated scale"
+    )]
+    scores_raw: bool,
+
+    #[arg(
+        long = "group",
+        help = "Group results under a heading per file, with line numbers (like ripgrep)",
+        conflicts_with_all = ["json", "json_v1", "jsonl"]
+    )]
+    group: bool,
+
+    #[arg(
+        long = "highlight",
+        help = "Syntax-highlight result previews (when colors are on, see --color); regex matches are shown inverted",
+        conflicts_with_all = ["json", "json_v1", "jsonl"]
+    )]
+    highlight: bool,
+
+    #[arg(
+        long = "vimgrep",
+        help = "Print one file:line:column:text line per match, for Vim/Neovim quickfix lists, Telescope and Emacs compile-mode; every mode",
+        conflicts_with_all = ["json", "json_v1", "jsonl", "group", "count", "files_with_matches", "files_without_matches", "files_ranked"]
+    )]
+    vimgrep: bool,
+
     #[arg(long = "json", help = "Output results as JSON for tools/scripts")]
     json: bool,
 
@@ -253,9 +557,55 @@ struct Cli {
     #[arg(long = "no-snippet", help = "Exclude code snippets from JSONL output")]
     no_snippet: bool,
 
-    #[arg(long = "reindex", help = "Force index update before searching")]
+    #[arg(
+        long = "redact-paths",
+        help = "Print paths relative to the index root and mask user names in home directory paths, in every output format; with --serve, in MCP tool results"
+    )]
+    redact_paths: bool,
+
+    #[arg(
+        long = "reindex",
+        help = "Force index update before searching (only of PATH when it is a subdirectory of the index); with --index, re-index files even when unchanged"
+    )]
     reindex: bool,
 
+    #[arg(
+        long = "ephemeral",
+        help = "Search without an index: chunk and embed the files in memory and discard everything afterwards, writing no .cs directory (semantic, lexical and hybrid search; slower on large trees)",
+        conflicts_with_all = ["reindex"]
+    )]
+    ephemeral: bool,
+
+    #[arg(
+        long = "changed",
+        help = "Only search (or with --index, only index) files with uncommitted changes: staged, unstaged or untracked, per git or Jujutsu",
+        conflicts_with_all = ["staged", "since"]
+    )]
+    changed: bool,
+
+    #[arg(
+        long = "staged",
+        help = "Only search (or index) files staged for commit (git)",
+        conflicts_with_all = ["changed", "since"]
+    )]
+    staged: bool,
+
+    #[arg(
+        long = "since",
+        value_name = "REV",
+        help = "Only search (or index) files changed since REV (e.g. main, HEAD~3), uncommitted changes included",
+        conflicts_with_all = ["changed", "staged"]
+    )]
+    since: Option<String>,
+
+    #[arg(
+        long = "within",
+        value_name = "RESULTS",
+        help = "Only search the files and spans of previous results: --jsonl/--json output or -l file lists, from a file or - for stdin",
+        conflicts_with_all = ["changed", "staged", "since"]
+    )]
+    within: Option<PathBuf>,
+
     #[arg(
         long = "exclude",
         value_name = "PATTERN",
@@ -275,12 +625,44 @@ struct Cli {
     #[arg(long = "no-csignore", help = "Don't respect .csignore file")]
     no_csignore: bool,
 
+    #[arg(
+        long = "max-filesize",
+        value_name = "SIZE",
+        value_parser = cs_core::file_limits::parse_filesize,
+        help = "Skip files larger than SIZE (e.g. 512K, 2MB) [default: 2MB when indexing, unlimited for regex search]"
+    )]
+    max_filesize: Option<u64>,
+
+    #[arg(
+        long = "max-files",
+        value_name = "N",
+        help = "Read at most N files per search or index run, skipping the rest with a warning"
+    )]
+    max_files: Option<usize>,
+
     #[arg(
         long = "print-default-csignore",
         help = "Print the default .csignore content that cs generates and exit"
     )]
     print_default_csignore: bool,
 
+    #[arg(
+        long = "completions",
+        value_name = "SHELL",
+        value_enum,
+        help = "Print a completion script for SHELL (bash, zsh, fish, elvish, powershell) and exit; model flags complete known model names"
+    )]
+    completions: Option<clap_complete::Shell>,
+
+    #[arg(
+        long = "help-modes",
+        help = "Describe each search mode with its default result limit, threshold and index use, and exit (with --json, as JSON)"
+    )]
+    help_modes: bool,
+
+    #[arg(long = "man", help = "Print the cs man page (roff) and exit")]
+    man: bool,
+
     #[arg(
         long = "full-section",
         help = "Return complete code sections (functions/classes) instead of just matching lines. Uses tree-sitter to identify semantic boundaries. Supported: Python, JavaScript, TypeScript, Haskell, Rust, Ruby"
@@ -290,10 +672,43 @@ struct Cli {
     #[arg(
         short = 'q',
         long = "quiet",
-        help = "Suppress status messages and progress indicators"
+        help = "Print nothing and exit 0 at the first match, 1 if there is none (like grep -q); in other modes, suppress status messages and progress indicators"
     )]
     quiet: bool,
 
+    #[arg(
+        long = "verbose",
+        help = "Log what cs is doing (index decisions, model resolution, skipped files) to stderr"
+    )]
+    verbose: bool,
+
+    #[arg(
+        long = "debug",
+        help = "Log debug diagnostics from cs to stderr; RUST_LOG, when set, overrides --verbose and --debug"
+    )]
+    debug: bool,
+
+    #[arg(
+        long = "log-json",
+        help = "Write log events to stderr as JSON lines (one object per event)"
+    )]
+    log_json: bool,
+
+    #[arg(
+        long = "stats",
+        help = "After the results, print files scanned and skipped, chunks scored, index checks, the embedding model and time per phase to stderr (JSON with --json/--jsonl)"
+    )]
+    stats: bool,
+
+    #[arg(
+        long = "color",
+        value_name = "WHEN",
+        value_parser = ["auto", "always", "never"],
+        default_value = "auto",
+        help = "When to color output: auto follows NO_COLOR, CLICOLOR_FORCE, CLICOLOR and whether each of stdout/stderr is a terminal"
+    )]
+    color: String,
+
     // Command flags (replacing subcommands)
     #[arg(
         long = "index",
@@ -301,12 +716,132 @@ struct Cli {
     )]
     index: bool,
 
+    #[arg(
+        long = "dry-run",
+        requires = "index",
+        conflicts_with_all = ["shard", "changed", "staged", "since"],
+        help = "With --index, chunk the files and print chunk and token totals with the estimated API cost per model, without embedding or writing anything"
+    )]
+    dry_run: bool,
+
+    #[arg(
+        long = "from-file",
+        value_name = "FILE",
+        requires = "index",
+        conflicts_with_all = ["dry_run", "shard", "changed", "staged", "since"],
+        help = "With --index, update the index for just the files and directories listed in FILE (one per line, - for stdin)"
+    )]
+    from_file: Option<PathBuf>,
+
+    #[arg(
+        long = "chunk-tokens",
+        value_name = "N",
+        help = "Target chunk size in tokens when indexing [default: per model, e.g. 1024 for nomic-v1.5]"
+    )]
+    chunk_tokens: Option<usize>,
+
+    #[arg(
+        long = "chunk-overlap",
+        value_name = "N",
+        help = "Tokens repeated between strides of a chunk larger than --chunk-tokens (capped at half the chunk size)"
+    )]
+    chunk_overlap: Option<usize>,
+
+    #[arg(
+        long = "no-stride",
+        help = "Embed chunks larger than --chunk-tokens whole (the model truncates them) instead of splitting them into overlapping strides"
+    )]
+    no_stride: bool,
+
+    #[arg(
+        long = "embed-template",
+        value_name = "TEMPLATE",
+        help = "Compose each chunk's embedded text from {path}, {symbol} and {code} when indexing, e.g. \"{path} :: {symbol} :: {code}\""
+    )]
+    embed_template: Option<cs_core::embed_template::EmbedTemplate>,
+
+    #[arg(
+        long = "sparse",
+        help = "Also store BM25 term vectors when indexing, fused into semantic scores so exact identifiers in the query rank higher"
+    )]
+    sparse: bool,
+
+    #[arg(
+        long = "allow-secrets",
+        help = "Index files and chunks that look like secrets (keys, tokens, .env files) instead of keeping them out of embeddings and the lexical index"
+    )]
+    allow_secrets: bool,
+
+    #[arg(
+        long = "shard",
+        value_name = "I/N",
+        requires_all = ["index", "shard_out"],
+        help = "With --index, embed only shard I of N (files are split by path hash) into a shard artifact for --merge-shards"
+    )]
+    shard: Option<cs_index::shard::ShardSpec>,
+
+    #[arg(
+        long = "shard-out",
+        value_name = "DIR",
+        requires = "shard",
+        help = "Directory to write the --shard artifact to (keep it outside the repository)"
+    )]
+    shard_out: Option<PathBuf>,
+
+    #[arg(
+        long = "merge-shards",
+        value_name = "SHARD",
+        num_args = 1..,
+        help = "Merge a complete set of --shard artifacts into the index of the current directory",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model"]
+    )]
+    merge_shards: Vec<PathBuf>,
+
+    #[arg(
+        long = "export-bundle",
+        value_name = "FILE",
+        help = "Write the index of PATH (default .) to a bundle, e.g. index.tar.zst, for --import-bundle on another machine",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "merge_shards", "import_bundle"]
+    )]
+    export_bundle: Option<PathBuf>,
+
+    #[arg(
+        long = "import-bundle",
+        value_name = "FILE",
+        help = "Replace the index of PATH (default .) with a bundle from --export-bundle, after checking its model and chunk settings",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "merge_shards"]
+    )]
+    import_bundle: Option<PathBuf>,
+
+    #[arg(
+        long = "remote",
+        value_name = "URL",
+        help = "Pull sidecars from a shared index (s3://bucket/prefix, an https:// URL or a directory) instead of embedding files it already has; overrides `remote` in cs.toml"
+    )]
+    remote: Option<String>,
+
+    #[arg(
+        long = "remote-push",
+        value_name = "URL",
+        num_args = 0..=1,
+        help = "Upload the embeddings of the index of PATH (default .) to a shared index, --remote or `remote` in cs.toml by default",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "merge_shards", "export_bundle", "import_bundle"]
+    )]
+    remote_push: Option<Option<String>>,
+
     #[arg(long = "clean", help = "Clean up search index")]
     clean: bool,
 
     #[arg(long = "clean-orphans", help = "Clean only orphaned index files")]
     clean_orphans: bool,
 
+    #[arg(
+        long = "gc",
+        help = "Remove orphaned sidecars, leftover temporary files, and caches past the [gc] age or size limits in cs.toml (with --json, print what was removed as JSON)",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model"]
+    )]
+    gc: bool,
+
     #[arg(
         long = "switch-model",
         value_name = "NAME",
@@ -331,32 +866,173 @@ struct Cli {
     )]
     force: bool,
 
+    #[arg(
+        long = "shadow-index",
+        value_name = "MODEL",
+        help = "Build a shadow embedding set with MODEL over a sample of indexed files, leaving the primary index untouched",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "shadow_compare"]
+    )]
+    shadow_index: Option<String>,
+
+    #[arg(
+        long = "shadow-sample",
+        value_name = "N",
+        default_value_t = 200,
+        help = "Number of indexed files to embed with --shadow-index"
+    )]
+    shadow_sample: usize,
+
+    #[arg(
+        long = "shadow-compare",
+        value_name = "MODEL",
+        help = "Run queries against the primary index and the MODEL shadow and report quality metrics",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model"]
+    )]
+    shadow_compare: Option<String>,
+
+    #[arg(
+        long = "shadow-queries",
+        value_name = "FILE",
+        help = "Queries for --shadow-compare, one per line; append <TAB>path to score the expected file",
+        requires = "shadow_compare"
+    )]
+    shadow_queries: Option<PathBuf>,
+
+    #[arg(
+        long = "bench",
+        help = "Measure chunking and embedding throughput, ANN build time and query latency on this tree without writing anything (--json for JSON, --model for another model)",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "export", "shadow_index", "shadow_compare", "map", "calibrate"]
+    )]
+    bench: bool,
+
+    #[arg(
+        long = "bench-sample",
+        value_name = "N",
+        requires = "bench",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Chunks to embed for the --bench embedding throughput [default: 256]"
+    )]
+    bench_sample: Option<u32>,
+
+    #[arg(
+        long = "calibrate",
+        help = "Run queries with known answer files against the index and report score distributions plus a suggested --threshold and --topk for its model (--json for JSON)",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "export", "shadow_index", "shadow_compare", "map"]
+    )]
+    calibrate: bool,
+
+    #[arg(
+        long = "calibrate-queries",
+        value_name = "FILE",
+        requires = "calibrate",
+        help = "Queries for --calibrate, one per line as query<TAB>expected/path [default: generated from signatures of sampled chunks]"
+    )]
+    calibrate_queries: Option<PathBuf>,
+
+    #[arg(
+        long = "map",
+        help = "Cluster the index's chunk embeddings into a topic map: label terms, representative files and size of each cluster (--json for JSON)",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "export", "shadow_index", "shadow_compare"]
+    )]
+    map: bool,
+
+    #[arg(
+        long = "clusters",
+        value_name = "K",
+        requires = "map",
+        value_parser = clap::value_parser!(u16).range(1..),
+        help = "Number of clusters for --map [default: about sqrt(chunks / 2), at most 16]"
+    )]
+    clusters: Option<u16>,
+
+    #[arg(
+        long = "export",
+        value_name = "FORMAT",
+        value_parser = ["ctags", "lsif"],
+        help = "Export definitions from indexed files as a ctags file or LSIF dump on stdout",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "shadow_index", "shadow_compare"]
+    )]
+    export: Option<String>,
+
+    #[arg(
+        long = "audit-log",
+        value_name = "MODE",
+        value_parser = ["hash", "full", "off"],
+        help = "Turn the project query audit log (.cs/audit/) on, recording query hashes or full query text, or off",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "export"]
+    )]
+    audit_log: Option<String>,
+
+    #[arg(
+        long = "audit-tail",
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "20",
+        help = "Show the last N audit log records (default 20); with --jsonl, print them as JSON lines",
+        conflicts_with_all = ["index", "clean", "clean_orphans", "switch_model", "export", "audit_log"]
+    )]
+    audit_tail: Option<usize>,
+
     #[arg(long = "add", help = "Add a single file to the index")]
     add: bool,
 
-    #[arg(long = "status", help = "Show index status and statistics")]
+    #[arg(
+        long = "status",
+        help = "Show index status and statistics (with --json, print the full statistics as JSON)"
+    )]
     status: bool,
 
-    #[arg(long = "status-verbose", help = "Show detailed index statistics")]
+    #[arg(
+        long = "status-verbose",
+        help = "Show detailed index statistics: per-language, chunk type and model breakdowns, largest and stale files"
+    )]
     status_verbose: bool,
 
+    #[arg(
+        long = "doctor",
+        help = "Check the embedding model, ONNX Runtime, API keys, index integrity and disk space, with a fix for each problem (with --json, print the checks as JSON)"
+    )]
+    doctor: bool,
+
     #[arg(
         long = "inspect",
         help = "Show detailed metadata for a specific file (chunks, embeddings, tree-sitter parsing info)"
     )]
     inspect: bool,
 
+    #[arg(
+        long = "chunks",
+        value_name = "FILE",
+        help = "List every chunk indexing would embed for FILE: span, type, token estimate and stride overlaps (--json for JSON, --model for another model's chunk size)"
+    )]
+    chunks: Option<PathBuf>,
+
     #[arg(
         long = "dump-chunks",
         help = "Visualize chunk boundaries for a file using the same rendering as TUI chunk mode"
     )]
     dump_chunks: bool,
 
+    #[arg(
+        long = "chunk-diff",
+        num_args = 2,
+        value_names = ["OLD", "NEW"],
+        help = "Compare two chunk-boundary snapshots (JSON from the cs-chunk stability harness) and list moved, added and removed chunks"
+    )]
+    chunk_diff: Option<Vec<PathBuf>>,
+
+    #[arg(
+        long = "get-chunk",
+        value_name = "ID",
+        help = "Print the chunk of the given file whose id (chunk_hash in --json/--jsonl/MCP results) starts with ID (--json for JSON)"
+    )]
+    get_chunk: Option<String>,
+
     // Model selection (index-time only)
     #[arg(
         long = "model",
         value_name = "MODEL",
-        help = "Embedding model to use for indexing (bge-small, nomic-v1.5, jina-code) [default: bge-small]. Only used with --index."
+        help = "Embedding model to use for indexing (bge-small, nomic-v1.5, jina-code, ... or a full model name) [default: bge-small]. Only used with --index."
     )]
     model: Option<String>,
 
@@ -374,167 +1050,1138 @@ struct Cli {
     )]
     rerank_model: Option<String>,
 
-    // MCP Server mode
     #[arg(
-        long = "serve",
-        help = "Start MCP server mode for AI agent integration",
-        conflicts_with_all = [
-            "pattern", "files", "line_numbers", "no_filenames", "with_filenames",
-            "files_with_matches", "files_without_matches", "ignore_case", "word_regexp",
-            "fixed_strings", "recursive", "context", "after_context", "before_context",
-            "semantic", "lexical", "hybrid", "regex", "top_k", "threshold", "show_scores",
-            "json", "json_v1", "jsonl", "no_snippet", "reindex", "exclude", "no_default_excludes",
-            "no_ignore", "full_section", "index", "clean", "clean_orphans", "switch_model",
-            "force", "add", "status", "status_verbose", "inspect", "dump_chunks", "model", "rerank", "rerank_model", "tui"
-        ]
+        long = "rerank-candidates",
+        value_name = "N",
+        requires = "rerank",
+        help = "Number of first-stage results the reranker re-scores before the --topk cut [default: 50]"
     )]
-    serve: bool,
+    rerank_candidates: Option<usize>,
+
+    #[arg(
+        long = "rerank-blend",
+        value_name = "WEIGHT",
+        requires = "rerank",
+        value_parser = parse_weight,
+        help = "Blend reranker and first-stage scores: 1.0 ranks by the reranker alone (default), 0.5 weighs both equally (scores normalized per search)"
+    )]
+    rerank_blend: Option<f32>,
+
+    #[arg(
+        long = "diversity",
+        value_name = "WEIGHT",
+        value_parser = parse_weight,
+        conflicts_with = "ephemeral",
+        help = "Diversify semantic results with Maximal Marginal Relevance: 0 keeps similarity order, higher values push near-duplicate chunks down in favor of distinct files and regions (0.3 is a good start)"
+    )]
+    diversity: Option<f32>,
+
+    #[arg(
+        long = "sparse-weight",
+        value_name = "WEIGHT",
+        value_parser = parse_weight,
+        help = "How much BM25 term matches lift semantic scores on an index built with --sparse: 0 ranks by cosine similarity alone [default: 0.3]"
+    )]
+    sparse_weight: Option<f32>,
+
+    #[arg(
+        long = "stride-pooling",
+        value_name = "POOLING",
+        value_parser = ["max", "mean", "none"],
+        help = "How the overlapping strides of a long chunk score as one semantic result: max (best stride, default), mean, or none to list every stride on its own"
+    )]
+    stride_pooling: Option<String>,
+
+    #[arg(
+        long = "dedupe",
+        value_name = "PREFER",
+        num_args = 0..=1,
+        default_missing_value = "smallest",
+        value_parser = ["smallest", "score", "none"],
+        help = "Keep one of the semantic, lexical or hybrid results that overlap in a file (a class and its method, strides of one chunk): smallest (default) or the best score"
+    )]
+    dedupe: Option<String>,
+
+    #[arg(
+        long = "not",
+        value_name = "PHRASE",
+        help = "Steer semantic and hybrid results away from chunks about PHRASE (e.g. --not \"unit tests\"); repeatable"
+    )]
+    not: Vec<String>,
+
+    #[arg(
+        long = "not-weight",
+        value_name = "WEIGHT",
+        value_parser = parse_weight,
+        requires = "not",
+        help = "How much resembling a --not phrase lowers a semantic score: the chunks closest to it lose this share of their score, 0 turns --not off [default: 0.5]"
+    )]
+    not_weight: Option<f32>,
+
+    #[arg(
+        long = "similar-to",
+        value_name = "FILE:START-END",
+        conflicts_with_all = ["regexp", "pattern_files", "lexical", "hybrid", "hybrid_lex", "ast", "symbols", "regex", "auto", "invert_match"],
+        help = "Find code semantically similar to lines START-END of FILE (or the whole file): each chunk of the example is a query and the example itself is left out; positional arguments are paths"
+    )]
+    similar_to: Option<SimilarTo>,
+
+    #[arg(
+        long = "expand-query",
+        conflicts_with_all = ["regex", "ast", "symbols"],
+        help = "Also search code-aware synonyms of query terms (auth -> authentication, login; db -> database) in semantic, lexical and hybrid search; set CS_QUERY_EXPANDER to a command that reads the query on stdin and prints one variant per line to use your own expander"
+    )]
+    expand_query: bool,
+
+    #[arg(
+        long = "explain",
+        help = "Show how each result's score was computed: cosine similarity, lexical rank, RRF components, rerank change, chunk type and matching stride (an \"explanation\" object in --json/--jsonl)"
+    )]
+    explain: bool,
+
+    // MCP Server mode
+    #[arg(
+        long = "serve",
+        help = "Start MCP server mode for AI agent integration",
+        conflicts_with_all = [
+            "pattern", "files", "line_numbers", "no_filenames", "with_filenames",
+            "files_with_matches", "files_without_matches", "count", "files_ranked", "invert_match", "only_matching", "replace", "search_zip", "regexp", "pattern_files", "ignore_case", "smart_case", "pcre2", "multiline", "word_regexp",
+            "fixed_strings", "recursive", "context", "after_context", "before_context",
+            "semantic", "lexical", "hybrid", "hybrid_lex", "regex", "auto", "top_k", "offset", "cursor", "threshold", "timeout", "show_scores", "scores_raw", "group", "highlight", "vimgrep",
+            "json", "json_v1", "jsonl", "no_snippet", "reindex", "ephemeral", "changed", "staged", "since", "exclude", "no_default_excludes",
+            "max_filesize", "max_files", "no_ignore", "full_section", "index", "chunk_tokens", "chunk_overlap", "no_stride", "embed_template", "sparse", "allow_secrets", "clean", "clean_orphans", "gc", "export_bundle", "import_bundle", "remote", "remote_push", "switch_model",
+            "ast", "symbols", "only", "no_tests", "ast_lang", "ast_strictness", "within", "no_csignore", "print_default_csignore", "completions", "help_modes", "man", "quiet", "stats", "dry_run", "from_file", "sparse_weight", "stride_pooling", "dedupe", "config", "models",
+            "force", "shard", "shard_out", "merge_shards", "shadow_index", "shadow_sample", "shadow_compare", "shadow_queries", "bench", "bench_sample", "calibrate", "calibrate_queries", "export", "map", "clusters", "audit_log", "audit_tail", "add", "status", "status_verbose", "doctor", "inspect", "chunks", "dump_chunks", "chunk_diff", "get_chunk", "model", "rerank", "rerank_model", "rerank_candidates", "rerank_blend", "diversity", "not", "not_weight", "similar_to", "expand_query", "explain", "lang", "lsp", "rpc", "tui", "daemon"
+        ]
+    )]
+    serve: bool,
+
+    #[arg(
+        long = "mcp-root",
+        value_name = "DIR",
+        requires = "serve",
+        help = "Only let MCP tool calls name paths inside DIR (repeatable); replaces mcp-roots in the user config"
+    )]
+    mcp_root: Vec<PathBuf>,
+
+    #[arg(
+        long = "mcp-read-only",
+        requires = "serve",
+        help = "Refuse MCP tools that change an index (reindex, clean_index) and search indexes as they are"
+    )]
+    mcp_read_only: bool,
+
+    #[arg(
+        long = "mcp-rate-limit",
+        value_name = "N",
+        requires = "serve",
+        help = "Searches and reindexes each MCP client may start per minute, 0 for no limit [default: mcp-rate-limit in the user config, 120]"
+    )]
+    mcp_rate_limit: Option<u32>,
+
+    #[arg(
+        long = "mcp-max-concurrent",
+        value_name = "N",
+        requires = "serve",
+        help = "MCP searches run at once; more wait for a slot, 0 for no limit [default: mcp-max-concurrent in the user config, 2]"
+    )]
+    mcp_max_concurrent: Option<usize>,
+
+    #[arg(
+        long = "mcp-timeout",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        requires = "serve",
+        help = "Soft time limit for each MCP search (e.g. 5s); returns the results found so far, flagged as partial"
+    )]
+    mcp_timeout: Option<std::time::Duration>,
+
+    // Language server mode
+    #[arg(
+        long = "lsp",
+        help = "Start a language server on stdio answering workspace/symbol and cs/semanticSearch, for editors' LSP clients",
+        conflicts_with_all = [
+            "pattern", "files", "line_numbers", "no_filenames", "with_filenames",
+            "files_with_matches", "files_without_matches", "count", "files_ranked", "invert_match", "only_matching", "replace", "search_zip", "regexp", "pattern_files", "ignore_case", "smart_case", "pcre2", "multiline", "word_regexp",
+            "fixed_strings", "recursive", "context", "after_context", "before_context",
+            "semantic", "lexical", "hybrid", "hybrid_lex", "regex", "auto", "top_k", "offset", "cursor", "threshold", "timeout", "show_scores", "scores_raw", "group", "highlight", "vimgrep",
+            "json", "json_v1", "jsonl", "no_snippet", "redact_paths", "reindex", "ephemeral", "changed", "staged", "since", "exclude", "no_default_excludes",
+            "max_filesize", "max_files", "no_ignore", "full_section", "index", "chunk_tokens", "chunk_overlap", "no_stride", "embed_template", "sparse", "allow_secrets", "clean", "clean_orphans", "gc", "export_bundle", "import_bundle", "remote", "remote_push", "switch_model",
+            "ast", "symbols", "only", "no_tests", "ast_lang", "ast_strictness", "within", "no_csignore", "print_default_csignore", "completions", "help_modes", "man", "quiet", "stats", "dry_run", "from_file", "sparse_weight", "stride_pooling", "dedupe", "config", "models",
+            "force", "shard", "shard_out", "merge_shards", "shadow_index", "shadow_sample", "shadow_compare", "shadow_queries", "bench", "bench_sample", "calibrate", "calibrate_queries", "export", "map", "clusters", "audit_log", "audit_tail", "add", "status", "status_verbose", "doctor", "inspect", "chunks", "dump_chunks", "chunk_diff", "get_chunk", "model", "rerank", "rerank_model", "rerank_candidates", "rerank_blend", "diversity", "not", "not_weight", "similar_to", "expand_query", "explain", "lang", "serve", "rpc", "tui", "daemon"
+        ]
+    )]
+    lsp: bool,
+
+    // JSON-RPC server mode
+    #[arg(
+        long = "rpc",
+        value_name = "ROOT",
+        num_args = 0..=1,
+        help = "Start a JSON-RPC server on stdio for editor extensions: search, outline and indexStatus requests for ROOT (default .), with indexUpdated notifications as it re-indexes changed files",
+        conflicts_with_all = [
+            "pattern", "files", "line_numbers", "no_filenames", "with_filenames",
+            "files_with_matches", "files_without_matches", "count", "files_ranked", "invert_match", "only_matching", "replace", "search_zip", "regexp", "pattern_files", "ignore_case", "smart_case", "pcre2", "multiline", "word_regexp",
+            "fixed_strings", "recursive", "context", "after_context", "before_context",
+            "semantic", "lexical", "hybrid", "hybrid_lex", "regex", "auto", "top_k", "offset", "cursor", "threshold", "timeout", "show_scores", "scores_raw", "group", "highlight", "vimgrep",
+            "json", "json_v1", "jsonl", "no_snippet", "redact_paths", "reindex", "ephemeral", "changed", "staged", "since", "exclude", "no_default_excludes",
+            "max_filesize", "max_files", "no_ignore", "full_section", "index", "chunk_tokens", "chunk_overlap", "no_stride", "embed_template", "sparse", "allow_secrets", "clean", "clean_orphans", "gc", "export_bundle", "import_bundle", "remote", "remote_push", "switch_model",
+            "ast", "symbols", "only", "no_tests", "ast_lang", "ast_strictness", "within", "no_csignore", "print_default_csignore", "completions", "help_modes", "man", "quiet", "stats", "dry_run", "from_file", "sparse_weight", "stride_pooling", "dedupe", "config", "models",
+            "force", "shard", "shard_out", "merge_shards", "shadow_index", "shadow_sample", "shadow_compare", "shadow_queries", "bench", "bench_sample", "calibrate", "calibrate_queries", "export", "map", "clusters", "audit_log", "audit_tail", "add", "status", "status_verbose", "doctor", "inspect", "chunks", "dump_chunks", "chunk_diff", "get_chunk", "model", "rerank", "rerank_model", "rerank_candidates", "rerank_blend", "diversity", "not", "not_weight", "similar_to", "expand_query", "explain", "lang", "serve", "lsp", "tui", "daemon"
+        ]
+    )]
+    rpc: Option<Option<PathBuf>>,
+
+    #[arg(
+        long = "daemon",
+        help = "Keep embedding models and the indexes searched loaded in a background process listening on a Unix socket; while it runs, semantic and hybrid searches send their query to it and indexing embeds through it instead of loading the model and index themselves (stop with Ctrl-C). Unix only; there is no named-pipe transport for Windows, where searches always run in-process",
+        conflicts_with_all = [
+            "pattern", "files", "line_numbers", "no_filenames", "with_filenames",
+            "files_with_matches", "files_without_matches", "count", "files_ranked", "invert_match", "only_matching", "replace", "search_zip", "regexp", "pattern_files", "ignore_case", "smart_case", "pcre2", "multiline", "word_regexp",
+            "fixed_strings", "recursive", "context", "after_context", "before_context",
+            "semantic", "lexical", "hybrid", "hybrid_lex", "regex", "auto", "top_k", "offset", "cursor", "threshold", "timeout", "show_scores", "scores_raw", "group", "highlight", "vimgrep",
+            "json", "json_v1", "jsonl", "no_snippet", "redact_paths", "reindex", "ephemeral", "changed", "staged", "since", "exclude", "no_default_excludes",
+            "max_filesize", "max_files", "no_ignore", "full_section", "index", "chunk_tokens", "chunk_overlap", "no_stride", "embed_template", "sparse", "allow_secrets", "clean", "clean_orphans", "gc", "export_bundle", "import_bundle", "remote", "remote_push", "switch_model",
+            "ast", "symbols", "only", "no_tests", "ast_lang", "ast_strictness", "within", "no_csignore", "print_default_csignore", "completions", "help_modes", "man", "quiet", "stats", "dry_run", "from_file", "sparse_weight", "stride_pooling", "dedupe", "config", "models",
+            "force", "shard", "shard_out", "merge_shards", "shadow_index", "shadow_sample", "shadow_compare", "shadow_queries", "bench", "bench_sample", "calibrate", "calibrate_queries", "export", "map", "clusters", "audit_log", "audit_tail", "add", "status", "status_verbose", "doctor", "inspect", "chunks", "dump_chunks", "chunk_diff", "get_chunk", "model", "rerank", "rerank_model", "rerank_candidates", "rerank_blend", "diversity", "not", "not_weight", "similar_to", "expand_query", "explain", "lang", "serve", "lsp", "rpc", "tui", "no_daemon"
+        ]
+    )]
+    daemon: bool,
+
+    #[arg(
+        long = "no-daemon",
+        help = "Load the embedding model and index in this process even when `cs --daemon` is running"
+    )]
+    no_daemon: bool,
+
+    // Configuration management
+    #[arg(
+        long = "config",
+        value_name = "COMMAND",
+        num_args = 0..,
+        help = "Configuration management: init, list, get KEY, set KEY VALUE, path"
+    )]
+    config: Vec<String>,
+
+    // Model cache management
+    #[arg(
+        long = "models",
+        value_name = "COMMAND",
+        num_args = 0..,
+        help = "Model cache management: list (default), download [MODEL...], remove MODEL..., path"
+    )]
+    models: Option<Vec<String>>,
+
+    // TUI mode
+    #[arg(
+        long = "tui",
+        help = "Interactive TUI mode - like fzf but semantic. Live search with arrow keys, Tab to switch modes, Enter to open in $EDITOR",
+        conflicts_with_all = [
+            "line_numbers", "no_filenames", "with_filenames",
+            "files_with_matches", "files_without_matches", "count", "files_ranked", "invert_match", "only_matching", "replace", "search_zip", "regexp", "pattern_files", "ignore_case", "smart_case", "pcre2", "multiline", "word_regexp",
+            "fixed_strings", "recursive", "context", "after_context", "before_context",
+            "semantic", "lexical", "hybrid", "hybrid_lex", "regex", "auto", "top_k", "offset", "cursor", "threshold", "timeout", "show_scores", "scores_raw", "vimgrep",
+            "json", "json_v1", "jsonl", "no_snippet", "redact_paths", "reindex", "ephemeral", "changed", "staged", "since", "exclude", "no_default_excludes",
+            "no_ignore", "full_section", "index", "clean", "clean_orphans", "gc", "export_bundle", "import_bundle", "remote_push", "switch_model",
+            "ast", "symbols", "only", "no_tests", "ast_lang", "ast_strictness", "highlight", "within", "no_csignore", "max_filesize", "max_files", "print_default_csignore", "completions", "help_modes", "man", "quiet", "verbose", "debug", "log_json", "stats", "dry_run", "from_file", "chunk_tokens", "chunk_overlap", "no_stride", "embed_template", "sparse", "allow_secrets", "remote", "sparse_weight", "stride_pooling", "dedupe", "config", "models",
+            "force", "shard", "shard_out", "merge_shards", "shadow_index", "shadow_sample", "shadow_compare", "shadow_queries", "bench", "bench_sample", "calibrate", "calibrate_queries", "export", "map", "clusters", "audit_log", "audit_tail", "add", "status", "status_verbose", "doctor", "inspect", "chunks", "dump_chunks", "chunk_diff", "get_chunk", "model", "rerank", "rerank_model", "rerank_candidates", "rerank_blend", "diversity", "not", "not_weight", "similar_to", "expand_query", "explain", "lang", "serve", "lsp", "rpc", "daemon"
+        ]
+    )]
+    tui: bool,
+}
+
+fn canonicalize_for_comparison(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+
+    std::env::current_dir()
+        .map(|cwd| cwd.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn find_search_root(include_patterns: &[IncludePattern]) -> PathBuf {
+    if include_patterns.is_empty() {
+        return PathBuf::from(".");
+    }
+
+    let mut root = if include_patterns[0].is_dir {
+        include_patterns[0].path.clone()
+    } else {
+        include_patterns[0]
+            .path
+            .parent()
+            .unwrap_or(&include_patterns[0].path)
+            .to_path_buf()
+    };
+
+    for pattern in include_patterns.iter().skip(1) {
+        let mut candidate = if pattern.is_dir {
+            pattern.path.clone()
+        } else {
+            pattern.path.parent().unwrap_or(&pattern.path).to_path_buf()
+        };
+
+        if candidate.starts_with(&root) {
+            continue;
+        }
+
+        while !root.starts_with(&candidate) && !candidate.starts_with(&root) {
+            if let Some(parent) = root.parent() {
+                root = parent.to_path_buf();
+            } else {
+                break;
+            }
+        }
+
+        if !candidate.starts_with(&root) {
+            while let Some(parent) = candidate.parent() {
+                if parent.starts_with(&root) {
+                    candidate = parent.to_path_buf();
+                    break;
+                }
+                candidate = parent.to_path_buf();
+            }
+        }
+
+        if root.starts_with(&candidate) {
+            root = candidate;
+        }
+    }
+
+    if root.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        root
+    }
+}
+
+fn build_exclude_patterns(cli: &Cli, repo_root: Option<&Path>) -> Vec<String> {
+    // Use the centralized pattern builder from cs-core
+    let mut patterns = cs_core::build_exclude_patterns(
+        repo_root,
+        &cli.exclude,
+        !cli.no_csignore,
+        !cli.no_default_excludes,
+    );
+    if cli.search_zip {
+        // .csignore excludes archives by default; -z opts back in unless --exclude names them
+        patterns.retain(|pattern| {
+            !cs_core::archive::is_archive_pattern(pattern) || cli.exclude.contains(pattern)
+        });
+    }
+    // cs.toml excludes add to --exclude; a broken cs.toml is reported by the search itself
+    if let Some(config) =
+        repo_root.and_then(|root| cs_models::ProjectConfig::discover(root).ok().flatten())
+    {
+        patterns.extend(config.exclude);
+    }
+    patterns
+}
+
+fn run_shadow_index(
+    status: &StatusReporter,
+    path: &Path,
+    model_name: &str,
+    sample_size: usize,
+) -> Result<()> {
+    let registry = cs_models::ModelRegistry::default();
+    let (model_alias, model_config) = registry.resolve(Some(model_name))?;
+
+    status.section_header("Building Shadow Embeddings");
+    status.info(&format!(
+        "🤖 Shadow model: {} (alias '{}', {} dims)",
+        model_config.name, model_alias, model_config.dimensions
+    ));
+    status.info(&format!("Sampling up to {} indexed files", sample_size));
+
+    let spinner = status.create_spinner("Embedding sampled files...");
+    let progress_bar = spinner.clone();
+    let progress: cs_index::ProgressCallback = Box::new(move |msg: &str| {
+        if let Some(pb) = &progress_bar {
+            pb.set_message(msg.to_string());
+        }
+    });
+    let stats = cs_index::shadow::build_shadow_index(
+        path,
+        model_config.name.as_str(),
+        sample_size,
+        Some(progress),
+    )?;
+    status.finish_progress(spinner, "Shadow embeddings built");
+
+    status.success(&format!(
+        "Embedded {} chunks from {} of {} sampled files",
+        stats.chunks_embedded, stats.files_embedded, stats.files_sampled
+    ));
+    if stats.files_errored > 0 {
+        status.warn(&format!(
+            "{} files could not be embedded",
+            stats.files_errored
+        ));
+    }
+    status.info(&format!(
+        "Compare with: cs --shadow-compare {} --shadow-queries <file> {}",
+        model_alias,
+        path.display()
+    ));
+    Ok(())
+}
+
+/// Embed one shard of `path` into an artifact for `--merge-shards`
+fn run_shard_index(
+    status: &StatusReporter,
+    path: &Path,
+    cli: &Cli,
+    shard: cs_index::shard::ShardSpec,
+    out_dir: &Path,
+    model_name: &str,
+) -> Result<()> {
+    let exclude_patterns = build_exclude_patterns(cli, Some(path));
+
+    status.section_header(&format!("Indexing Shard {}", shard));
+    let spinner = status.create_spinner("Embedding shard files...");
+    let progress_bar = spinner.clone();
+    let progress: cs_index::ProgressCallback = Box::new(move |msg: &str| {
+        if let Some(pb) = &progress_bar {
+            pb.set_message(msg.to_string());
+        }
+    });
+    let (artifact, stats) = cs_index::shard::build_shard(
+        path,
+        shard,
+        out_dir,
+        !cli.no_ignore,
+        &exclude_patterns,
+        model_name,
+        Some(progress),
+    )?;
+    status.finish_progress(spinner, "Shard embedded");
+
+    status.success(&format!(
+        "Embedded {} chunks from {} of {} files in shard {}",
+        stats.chunks_embedded, stats.files_indexed, stats.files_in_shard, shard
+    ));
+    if stats.files_errored > 0 {
+        status.warn(&format!(
+            "{} files could not be indexed",
+            stats.files_errored
+        ));
+    }
+    status.info(&format!("Shard artifact: {}", artifact.display()));
+    Ok(())
+}
+
+fn run_merge_shards(status: &StatusReporter, path: &Path, shard_dirs: &[PathBuf]) -> Result<()> {
+    status.section_header("Merging Index Shards");
+    let spinner = status.create_spinner("Merging shard sidecars...");
+    let stats = cs_index::shard::merge_shards(path, shard_dirs)?;
+    status.finish_progress(spinner, "Shards merged");
+    status.success(&format!(
+        "Merged {} files from {} shards",
+        stats.files_merged, stats.shards
+    ));
+    Ok(())
+}
+
+/// `--export-bundle` or `--import-bundle` for the index at `path`
+fn run_bundle(status: &StatusReporter, path: &Path, cli: &Cli) -> Result<()> {
+    let stats = if let Some(bundle) = cli.export_bundle.as_deref() {
+        status.section_header("Exporting Index Bundle");
+        let spinner = status.create_spinner("Packing manifest and sidecars...");
+        let stats = cs_index::bundle::export_bundle(path, bundle)?;
+        status.finish_progress(spinner, "Bundle written");
+        stats
+    } else if let Some(bundle) = cli.import_bundle.as_deref() {
+        // --model or cs.toml pin the model the bundle must have been embedded with
+        let model = match cli.model.clone() {
+            Some(model) => Some(model),
+            None => cs_models::configured_model(path)?,
+        };
+        let model = match model {
+            Some(model) => Some(
+                cs_models::ModelRegistry::default()
+                    .resolve(Some(&model))?
+                    .1
+                    .name,
+            ),
+            None => None,
+        };
+        status.section_header("Importing Index Bundle");
+        let spinner = status.create_spinner("Unpacking manifest and sidecars...");
+        let stats = cs_index::bundle::import_bundle(path, bundle, model.as_deref())?;
+        status.finish_progress(spinner, "Bundle imported");
+        stats
+    } else {
+        return Ok(());
+    };
+
+    if cli.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    status.success(&format!(
+        "{} {} files ({}, {})",
+        if cli.export_bundle.is_some() {
+            "Exported"
+        } else {
+            "Imported"
+        },
+        stats.files,
+        stats.embedding_model.as_deref().unwrap_or("no embeddings"),
+        cs_core::file_limits::format_filesize(stats.bytes)
+    ));
+    if stats.files_stale > 0 {
+        status.info(&format!(
+            "{} files differ from this checkout and are re-indexed on the next update",
+            stats.files_stale
+        ));
+    }
+    Ok(())
+}
+
+/// `--remote-push`: upload the index at `path` to `url`, or to the configured remote
+fn run_remote_push(
+    status: &StatusReporter,
+    path: &Path,
+    url: Option<String>,
+    json: bool,
+) -> Result<()> {
+    let Some(url) = url.or_else(|| cs_index::remote_url(path)) else {
+        anyhow::bail!("No remote to push to; pass --remote-push URL or set `remote` in cs.toml");
+    };
+    status.section_header("Pushing Index");
+    let spinner = status.create_spinner(&format!("Uploading sidecars to {}...", url));
+    let stats = cs_index::remote::push(path, &url)?;
+    status.finish_progress(spinner, "Remote index updated");
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    status.success(&format!(
+        "Pushed {} files to {} ({} new objects, {})",
+        stats.files,
+        url,
+        stats.objects_uploaded,
+        cs_core::file_limits::format_filesize(stats.bytes_uploaded)
+    ));
+    if stats.files_unembedded > 0 {
+        status.warn(&format!(
+            "{} files have no embeddings and were left out; run 'cs --index' and push again",
+            stats.files_unembedded
+        ));
+    }
+    Ok(())
+}
+
+/// Enable (`hash`/`full`) or disable (`off`) the query audit log for the project at `path`
+fn run_audit_log_config(status: &StatusReporter, path: &Path, mode: &str) -> Result<()> {
+    use cs_index::audit::{self, AuditConfig, QueryStorage};
+
+    let existing = audit::load_config(path)?;
+    let config = match mode {
+        "off" => {
+            let Some(config) = existing else {
+                status.info("Query audit log is not enabled");
+                return Ok(());
+            };
+            AuditConfig {
+                enabled: false,
+                ..config
+            }
+        }
+        mode => {
+            let store_query = if mode == "full" {
+                QueryStorage::Full
+            } else {
+                QueryStorage::Hash
+            };
+            // Keep any hand-tuned rotation settings
+            match existing {
+                Some(config) => AuditConfig {
+                    enabled: true,
+                    store_query,
+                    ..config
+                },
+                None => AuditConfig::new(store_query),
+            }
+        }
+    };
+    audit::save_config(path, &config)?;
+
+    let dir = audit::audit_dir(path);
+    if config.enabled {
+        status.success(&format!(
+            "Query audit log enabled ({}) in {}",
+            if config.store_query == QueryStorage::Full {
+                "full query text"
+            } else {
+                "query hashes"
+            },
+            dir.display()
+        ));
+    } else {
+        status.success(&format!(
+            "Query audit log disabled; existing records kept in {}",
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+fn run_audit_tail(status: &StatusReporter, path: &Path, count: usize, jsonl: bool) -> Result<()> {
+    let records = cs_index::audit::tail(path, count)?;
+    if records.is_empty() {
+        status.info("No audit records found");
+        return Ok(());
+    }
+
+    for record in records {
+        if jsonl {
+            println!("{}", serde_json::to_string(&record)?);
+            continue;
+        }
+        let time = chrono::DateTime::from_timestamp(record.timestamp as i64, 0)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|| record.timestamp.to_string());
+        let query = match (&record.query, &record.query_hash) {
+            (Some(query), _) => format!("{:?}", query),
+            (None, Some(hash)) => format!("#{}", &hash[..hash.len().min(16)]),
+            (None, None) => String::new(),
+        };
+        println!(
+            "{}  {:<8}  {:<20}  {:>5} results  {}  {}",
+            time,
+            record.mode,
+            record.requester.as_deref().unwrap_or("-"),
+            record.results,
+            record.path,
+            query
+        );
+    }
+    Ok(())
+}
+
+fn run_export(status: &StatusReporter, path: &Path, format: &str) -> Result<()> {
+    let export = cs_index::export::collect_symbols(path)?;
+
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    match format {
+        "lsif" => cs_index::export::write_lsif(&export, path, &mut out)?,
+        _ => cs_index::export::write_ctags(&export, &mut out)?,
+    }
+    std::io::Write::flush(&mut out)?;
+
+    status.info(&format!(
+        "Exported {} symbols from {} files",
+        export.symbols.len(),
+        export.files
+    ));
+    if export.stale_files > 0 {
+        status.warn(&format!(
+            "{} indexed files changed since the last index run; run 'cs --index' to refresh",
+            export.stale_files
+        ));
+    }
+    Ok(())
+}
+
+fn run_map(
+    status: &StatusReporter,
+    path: &Path,
+    clusters: Option<usize>,
+    json: bool,
+) -> Result<()> {
+    let map = cs_index::repo_map::build_repo_map(path, clusters)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&map)?);
+    } else if map.clusters.is_empty() {
+        status.info("No embeddings in the index; run 'cs --index' with an embedding model first");
+        return Ok(());
+    } else {
+        println!(
+            "Repository map: {} chunks in {} files, {} clusters\n",
+            map.chunks,
+            map.files,
+            map.clusters.len()
+        );
+        for (number, cluster) in map.clusters.iter().enumerate() {
+            let label = if cluster.label.is_empty() {
+                "(no terms)".to_string()
+            } else {
+                cluster.label.join(" · ")
+            };
+            println!(
+                "{:>3}. {}  {}",
+                number + 1,
+                style(label).bold(),
+                style(format!(
+                    "{} chunks in {} files",
+                    cluster.chunks, cluster.files
+                ))
+                .dim()
+            );
+            let files: Vec<String> = cluster
+                .representative_files
+                .iter()
+                .map(|file| format!("{} ({})", file.path.display(), file.chunks))
+                .collect();
+            println!("     {}", files.join("  "));
+        }
+    }
+
+    if map.stale_files > 0 {
+        status.warn(&format!(
+            "{} indexed files changed since the last index run; run 'cs --index' to refresh",
+            map.stale_files
+        ));
+    }
+    Ok(())
+}
+
+fn run_bench(status: &StatusReporter, path: &Path, cli: &Cli) -> Result<()> {
+    let model = match cli.model.clone() {
+        Some(model) => Some(model),
+        None => cs_models::configured_model(path)?,
+    };
+    let exclude_patterns = build_exclude_patterns(cli, Some(path));
+    let spinner = status.create_spinner("Benchmarking chunking, embedding and search...");
+    let report = cs_index::bench::bench(
+        path,
+        model.as_deref(),
+        !cli.no_ignore,
+        &exclude_patterns,
+        cli.bench_sample
+            .map_or(cs_index::bench::DEFAULT_EMBED_SAMPLE, |n| n as usize),
+    )?;
+    status.finish_progress(spinner, "Benchmark complete");
+
+    if cli.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    status.section_header("Benchmark");
+    println!(
+        "cs {} · {} ({} dims) · {} files · {} · nothing written",
+        report.version,
+        short_model_name(&report.model),
+        report.dimensions,
+        report.files,
+        cs_core::file_limits::format_filesize(report.bytes)
+    );
+    println!();
+    println!("{:<20} {:>10.2}s", "model load", report.model_load_seconds);
+    println!(
+        "{:<20} {:>10.2}s  {} chunks · {:.0} files/s · {:.0} chunks/s · {:.1} MB/s",
+        "chunking",
+        report.chunking.seconds,
+        report.chunking.chunks,
+        report.chunking.files_per_sec,
+        report.chunking.chunks_per_sec,
+        report.chunking.mb_per_sec
+    );
+    println!(
+        "{:<20} {:>10.2}s  {} chunks · {:.1} chunks/s · {:.0} tokens/s (est.)",
+        "embedding",
+        report.embedding.seconds,
+        report.embedding.chunks,
+        report.embedding.chunks_per_sec,
+        report.embedding.tokens_per_sec
+    );
+    println!(
+        "{:<20} {:>10.3}s  {} vectors from the {} ({})",
+        "ANN build",
+        report.ann_build.seconds,
+        report.ann_build.vectors,
+        report.ann_build.source,
+        report.ann_backend
+    );
+    let latency = &report.query_latency;
+    println!(
+        "{:<20} {:>9.1}ms  p90 {:.1}ms · p99 {:.1}ms · max {:.1}ms over {} queries (top {})",
+        "query p50",
+        latency.p50_ms,
+        latency.p90_ms,
+        latency.p99_ms,
+        latency.max_ms,
+        latency.queries,
+        latency.top_k
+    );
+    Ok(())
+}
+
+fn run_calibrate(
+    status: &StatusReporter,
+    path: &Path,
+    queries_file: Option<&Path>,
+    top_k: usize,
+    json: bool,
+) -> Result<()> {
+    let queries = match queries_file {
+        Some(file) => Some(cs_index::shadow::parse_shadow_queries(
+            &std::fs::read_to_string(file)?,
+        )),
+        None => None,
+    };
+    let spinner = status.create_spinner("Running calibration queries...");
+    let report =
+        cs_index::calibrate::calibrate(path, queries, cs_index::calibrate::DEFAULT_SAMPLES, top_k)?;
+    status.finish_progress(spinner, "Calibration complete");
 
-    // Configuration management
-    #[arg(
-        long = "config",
-        value_name = "COMMAND",
-        num_args = 0..,
-        help = "Configuration management: init, list, get KEY, set KEY VALUE, path"
-    )]
-    config: Vec<String>,
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
 
-    // TUI mode
-    #[arg(
-        long = "tui",
-        help = "Interactive TUI mode - like fzf but semantic. Live search with arrow keys, Tab to switch modes, Enter to open in $EDITOR",
-        conflicts_with_all = [
-            "line_numbers", "no_filenames", "with_filenames",
-            "files_with_matches", "files_without_matches", "ignore_case", "word_regexp",
-            "fixed_strings", "recursive", "context", "after_context", "before_context",
-            "semantic", "lexical", "hybrid", "regex", "top_k", "threshold", "show_scores",
-            "json", "json_v1", "jsonl", "no_snippet", "reindex", "exclude", "no_default_excludes",
-            "no_ignore", "full_section", "index", "clean", "clean_orphans", "switch_model",
-            "force", "add", "status", "status_verbose", "inspect", "dump_chunks", "model", "rerank", "rerank_model", "serve"
-        ]
-    )]
-    tui: bool,
-}
+    status.section_header("Threshold Calibration");
+    println!(
+        "{} · {} chunks in {} files · {} {} queries",
+        short_model_name(&report.model),
+        report.chunks,
+        report.files,
+        report.queries.len(),
+        if report.generated {
+            "generated"
+        } else {
+            "given"
+        }
+    );
+    println!();
+    let score = |score: Option<f32>| score.map_or("-".to_string(), |s| format!("{:.3}", s));
+    println!(
+        "{:<22} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>7}",
+        "", "min", "p10", "p25", "median", "p75", "p90", "max", "count"
+    );
+    for (label, scores) in [
+        ("expected file", &report.expected_scores),
+        ("other results", &report.other_scores),
+    ] {
+        println!(
+            "{:<22} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>7}",
+            label,
+            score(scores.min),
+            score(scores.p10),
+            score(scores.p25),
+            score(scores.median),
+            score(scores.p75),
+            score(scores.p90),
+            score(scores.max),
+            scores.count
+        );
+    }
+    if !report.hit_rates.is_empty() {
+        println!();
+        let rates: Vec<String> = report
+            .hit_rates
+            .iter()
+            .map(|rate| format!("@{} {:.0}%", rate.top_k, rate.hit_rate * 100.0))
+            .collect();
+        println!("{:<22} {}", "expected file found", rates.join("  "));
+    }
 
-fn canonicalize_for_comparison(path: &Path) -> PathBuf {
-    if let Ok(canonical) = path.canonicalize() {
-        return canonical;
+    println!();
+    for query in &report.queries {
+        let rank = query.rank.map_or("-".to_string(), |r| format!("#{r}"));
+        println!(
+            "  {:<40} {:>5}  {}  {}",
+            query.query,
+            rank,
+            score(query.expected_score),
+            query.expected.as_deref().unwrap_or("")
+        );
     }
 
-    std::env::current_dir()
-        .map(|cwd| cwd.join(path))
-        .unwrap_or_else(|_| path.to_path_buf())
+    println!();
+    match (report.suggested_threshold, report.suggested_top_k) {
+        (Some(threshold), Some(top_k)) => status.info(&format!(
+            "Suggested for {}: --threshold {:.2} --topk {}",
+            short_model_name(&report.model),
+            threshold,
+            top_k
+        )),
+        (Some(threshold), None) => status.info(&format!(
+            "Suggested for {}: --threshold {:.2}",
+            short_model_name(&report.model),
+            threshold
+        )),
+        _ => status.warn("No query found its expected file; nothing to suggest"),
+    }
+    Ok(())
 }
 
-fn find_search_root(include_patterns: &[IncludePattern]) -> PathBuf {
-    if include_patterns.is_empty() {
-        return PathBuf::from(".");
+/// `--get-chunk`: print the chunk of `file` whose id starts with `id`
+fn run_get_chunk(file: &Path, id: &str, json: bool) -> Result<()> {
+    use anyhow::Context;
+    if id.is_empty() {
+        anyhow::bail!("--get-chunk: the chunk id is empty");
+    }
+    let content = cs_core::encoding::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let (chunks, _) = cs_engine::file_chunks(file, &content);
+    let mut matches = cs_engine::chunks_with_id(file, &content, chunks, id).into_iter();
+    let chunk = match (matches.next(), matches.next()) {
+        (Some(chunk), None) => chunk,
+        (Some(_), Some(_)) => {
+            anyhow::bail!("Chunk id '{}' matches several chunks; give more of it", id)
+        }
+        (None, _) => anyhow::bail!(
+            "No chunk in {} has id {}; the file may have changed, search again",
+            file.display(),
+            id
+        ),
+    };
+    let span = &chunk.span;
+    let text = &content[span.byte_start..span.byte_end];
+
+    if json {
+        let result = serde_json::json!({
+            "file": file.to_string_lossy(),
+            "span": span,
+            "chunk_hash": cs_engine::chunk_id(file, span, &content),
+            "chunk_type": chunk.chunk_type,
+            "breadcrumb": chunk.breadcrumb,
+            "text": text,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        let label = chunk
+            .breadcrumb
+            .as_deref()
+            .or(chunk.chunk_type.as_deref())
+            .map(|label| format!(" ({})", label))
+            .unwrap_or_default();
+        println!(
+            "{}:{}-{}{}",
+            file.display(),
+            span.line_start,
+            span.line_end,
+            label
+        );
+        print!("{}", text);
+        if !text.ends_with('\n') {
+            println!();
+        }
     }
+    Ok(())
+}
 
-    let mut root = if include_patterns[0].is_dir {
-        include_patterns[0].path.clone()
+fn run_shadow_compare(status: &StatusReporter, cli: &Cli, model_name: &str) -> Result<()> {
+    let registry = cs_models::ModelRegistry::default();
+    let (_, model_config) = registry.resolve(Some(model_name))?;
+
+    let (queries, path) = if let Some(queries_file) = &cli.shadow_queries {
+        let text = std::fs::read_to_string(queries_file)?;
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."));
+        (cs_index::shadow::parse_shadow_queries(&text), path)
     } else {
-        include_patterns[0]
-            .path
-            .parent()
-            .unwrap_or(&include_patterns[0].path)
-            .to_path_buf()
+        let query = cli.pattern.clone().ok_or_else(|| {
+            anyhow::anyhow!("--shadow-compare needs a query or --shadow-queries <file>")
+        })?;
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from("."));
+        (cs_index::shadow::parse_shadow_queries(&query), path)
     };
+    if queries.is_empty() {
+        anyhow::bail!("No queries to compare");
+    }
 
-    for pattern in include_patterns.iter().skip(1) {
-        let mut candidate = if pattern.is_dir {
-            pattern.path.clone()
-        } else {
-            pattern.path.parent().unwrap_or(&pattern.path).to_path_buf()
-        };
-
-        if candidate.starts_with(&root) {
-            continue;
-        }
+    let top_k = cli.top_k.unwrap_or(10);
+    let spinner =
+        status.create_spinner(&format!("Running {} comparative queries...", queries.len()));
+    let report = cs_index::shadow::compare_shadow(&path, &model_config.name, &queries, top_k)?;
+    status.finish_progress(spinner, "Comparison complete");
 
-        while !root.starts_with(&candidate) && !candidate.starts_with(&root) {
-            if let Some(parent) = root.parent() {
-                root = parent.to_path_buf();
-            } else {
-                break;
-            }
-        }
+    if cli.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
 
-        if !candidate.starts_with(&root) {
-            while let Some(parent) = candidate.parent() {
-                if parent.starts_with(&root) {
-                    candidate = parent.to_path_buf();
-                    break;
-                }
-                candidate = parent.to_path_buf();
-            }
-        }
+    status.section_header("Shadow Model Comparison");
+    println!(
+        "{} files sampled · {} primary chunks · {} shadow chunks · top {}",
+        report.files_sampled, report.primary_chunks, report.shadow_chunks, report.top_k
+    );
+    println!();
+    println!("{:<28} {:>12} {:>12}", "", "primary", "shadow");
+    println!(
+        "{:<28} {:>12} {:>12}",
+        "model",
+        short_model_name(&report.primary_model),
+        short_model_name(&report.shadow_model)
+    );
+    println!(
+        "{:<28} {:>12.3} {:>12.3}",
+        "mean top-1 score", report.primary_mean_top_score, report.shadow_mean_top_score
+    );
+    if let (Some(primary_mrr), Some(shadow_mrr)) = (report.primary_mrr, report.shadow_mrr) {
+        println!(
+            "{:<28} {:>12.3} {:>12.3}",
+            "MRR (expected file)", primary_mrr, shadow_mrr
+        );
+    }
+    if let (Some(primary_hits), Some(shadow_hits)) =
+        (report.primary_hit_rate, report.shadow_hit_rate)
+    {
+        println!(
+            "{:<28} {:>11.0}% {:>11.0}%",
+            format!("hit rate @{}", report.top_k),
+            primary_hits * 100.0,
+            shadow_hits * 100.0
+        );
+    }
+    println!(
+        "{:<28} {:>10.1}ms {:>10.1}ms",
+        "avg query latency", report.primary_avg_ms, report.shadow_avg_ms
+    );
+    println!(
+        "{:<28} {:>12.0}%",
+        "result file overlap",
+        report.mean_overlap * 100.0
+    );
 
-        if root.starts_with(&candidate) {
-            root = candidate;
+    println!();
+    for query in &report.queries {
+        let rank = |rank: Option<usize>| rank.map_or("-".to_string(), |r| format!("#{r}"));
+        if query.expected.is_some() {
+            println!(
+                "  {:<40} overlap {:>4.0}%  expected: primary {} / shadow {}",
+                query.query,
+                query.overlap * 100.0,
+                rank(query.primary_rank),
+                rank(query.shadow_rank)
+            );
+        } else {
+            println!(
+                "  {:<40} overlap {:>4.0}%",
+                query.query,
+                query.overlap * 100.0
+            );
         }
     }
 
-    if root.as_os_str().is_empty() {
-        PathBuf::from(".")
-    } else {
-        root
+    if report.shadow_mrr > report.primary_mrr {
+        status.info(&format!(
+            "Shadow model ranks expected files higher; switch with: cs --switch-model {}",
+            model_name
+        ));
     }
+    Ok(())
 }
 
-fn build_exclude_patterns(cli: &Cli, repo_root: Option<&Path>) -> Vec<String> {
-    // Use the centralized pattern builder from cs-core
-    cs_core::build_exclude_patterns(
-        repo_root,
-        &cli.exclude,
-        !cli.no_csignore,
-        !cli.no_default_excludes,
-    )
-}
-
-fn resolve_model_selection(
+/// `--index --dry-run`: what indexing `path` would send to each embedding model
+fn run_index_dry_run(
+    status: &StatusReporter,
+    path: &Path,
+    cli: &Cli,
     registry: &cs_models::ModelRegistry,
-    requested: Option<&str>,
-) -> Result<(String, cs_models::ModelConfig)> {
-    match requested {
-        Some(name) => {
-            if let Some(config) = registry.get_model(name) {
-                return Ok((name.to_string(), config.clone()));
-            }
+    selected_alias: &str,
+) -> Result<()> {
+    let exclude_patterns = build_exclude_patterns(cli, Some(path));
+    let spinner = status.create_spinner("Chunking files...");
+    let estimate =
+        cs_index::estimate::estimate_index(path, !cli.no_ignore, &exclude_patterns, registry)?;
+    status.finish_progress(spinner, "Chunking complete");
 
-            if let Some((alias, config)) = registry
-                .models
-                .iter()
-                .find(|(_, config)| config.name == name)
-            {
-                return Ok((alias.clone(), config.clone()));
-            }
+    if cli.json {
+        println!("{}", serde_json::to_string_pretty(&estimate)?);
+        return Ok(());
+    }
 
-            anyhow::bail!(
-                "Unknown model '{}'. Available models: {}",
-                name,
-                registry
-                    .models
-                    .keys()
-                    .cloned()
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            );
-        }
-        None => {
-            let alias = registry.default_model.clone();
-            let config = registry
-                .get_default_model()
-                .ok_or_else(|| anyhow::anyhow!("No default model configured"))?
-                .clone();
-            Ok((alias, config))
+    status.section_header("Index Dry Run");
+    println!(
+        "{} files · {} · nothing embedded or written",
+        estimate.files,
+        cs_core::file_limits::format_filesize(estimate.bytes)
+    );
+    if estimate.files_skipped > 0 {
+        println!(
+            "{} files skipped by --max-filesize/--max-files",
+            estimate.files_skipped
+        );
+    }
+    if estimate.files_unreadable > 0 {
+        println!("{} files not readable as text", estimate.files_unreadable);
+    }
+    println!();
+    println!(
+        "  {:<16} {:<10} {:>10} {:>14} {:>12}",
+        "model", "provider", "chunks", "tokens (est.)", "cost (est.)"
+    );
+    for model in &estimate.models {
+        let marker = if model.alias == selected_alias {
+            "*"
+        } else {
+            " "
+        };
+        let cost = model
+            .estimated_cost_usd
+            .map_or("local".to_string(), |cost| format!("${:.4}", cost));
+        println!(
+            "{} {:<16} {:<10} {:>10} {:>14} {:>12}",
+            marker, model.alias, model.provider, model.chunks, model.tokens, cost
+        );
+    }
+    println!();
+    status.info(
+        "* model --index would use; token counts are estimates and API costs use list prices",
+    );
+    Ok(())
+}
+
+fn short_model_name(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+/// Index root for `--index PATH` and, for a scoped update, the paths to update: the
+/// `--from-file` list, or PATH itself when it lies inside an existing index
+fn index_scope(path: &Path, from_file: Option<&Path>) -> Result<(PathBuf, Option<Vec<PathBuf>>)> {
+    let absolute = cs_core::paths::canonicalize(path);
+    let enclosing = cs_engine::find_nearest_index_root(&absolute);
+    if let Some(list) = from_file {
+        let text = if cs_engine::is_stdin_path(list) {
+            std::io::read_to_string(std::io::stdin())?
+        } else {
+            std::fs::read_to_string(list).map_err(|e| {
+                anyhow::anyhow!("Failed to read --from-file list {}: {}", list.display(), e)
+            })?
+        };
+        // Listed files may be deleted, so they are made absolute without resolving them
+        let paths = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| std::path::absolute(line).map_err(anyhow::Error::from))
+            .collect::<Result<Vec<_>>>()?;
+        return Ok((enclosing.unwrap_or(absolute), Some(paths)));
+    }
+    match enclosing {
+        Some(root) if !cs_core::paths::same_path(&root, &absolute) => {
+            Ok((root, Some(vec![absolute])))
         }
+        _ => Ok((path.to_path_buf(), None)),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_index_workflow(
     status: &StatusReporter,
     path: &Path,
+    scope: Option<&[PathBuf]>,
     cli: &Cli,
     model_alias: &str,
     model_config: &cs_models::ModelConfig,
@@ -542,7 +2189,19 @@ async fn run_index_workflow(
     clean_first: bool,
 ) -> Result<()> {
     status.section_header(heading);
-    status.info(&format!("Scanning files in {}", path.display()));
+    match scope {
+        Some([only]) => status.info(&format!(
+            "Scanning files in {} (index at {})",
+            only.display(),
+            path.display()
+        )),
+        Some(paths) => status.info(&format!(
+            "Scanning {} listed paths (index at {})",
+            paths.len(),
+            path.display()
+        )),
+        None => status.info(&format!("Scanning files in {}", path.display())),
+    }
 
     if model_alias == model_config.name {
         status.info(&format!(
@@ -557,13 +2216,11 @@ async fn run_index_workflow(
     }
 
     let max_tokens = cs_chunk::TokenEstimator::get_model_limit(model_config.name.as_str());
-    let (chunk_tokens, overlap_tokens) =
-        cs_chunk::get_model_chunk_config(Some(model_config.name.as_str()));
 
     status.info(&format!("📏 FastEmbed Config: {} token limit", max_tokens));
     status.info(&format!(
-        "📄 Chunk Config: {} tokens target, {} token overlap (~20%)",
-        chunk_tokens, overlap_tokens
+        "📄 Chunk Config: {}",
+        describe_chunk_config(&model_config.name, path)
     ));
 
     // Create .csignore file if it doesn't exist
@@ -594,7 +2251,7 @@ async fn run_index_workflow(
         mut overall_progress_bar,
         progress_callback,
         detailed_progress_callback,
-    ) = if !cli.quiet {
+    ) = if status.shows_progress() {
         use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 
         let multi_progress = MultiProgress::new();
@@ -637,6 +2294,7 @@ async fn run_index_workflow(
                     overall_pb_clone2.set_length(progress.total_files as u64);
                 }
                 overall_pb_clone2.set_position(progress.file_index as u64);
+                overall_pb_clone2.set_message(progress.overall.to_string());
 
                 if file_pb_clone2.length().unwrap_or(0) != progress.total_chunks as u64 {
                     file_pb_clone2.set_length(progress.total_chunks as u64);
@@ -668,16 +2326,74 @@ async fn run_index_workflow(
         (None, None, None, None)
     };
 
-    let index_future = cs_index::smart_update_index_with_detailed_progress(
-        path,
-        false,
-        progress_callback,
-        detailed_progress_callback,
-        true,
-        !cli.no_ignore,
-        &exclude_patterns,
-        Some(model_alias),
-    );
+    // --changed/--staged/--since index just the changed files, of the scope when there is one
+    let changed = match vcs::ChangeScope::from_flags(cli.changed, cli.staged, cli.since.as_deref())
+    {
+        Some(change_scope) => {
+            let mut files =
+                expand_glob_patterns(&vcs::changed_files(path, &change_scope)?, &exclude_patterns)?;
+            if let Some(paths) = scope {
+                files.retain(|file| {
+                    paths
+                        .iter()
+                        .any(|scoped| cs_core::paths::relative_to(file, scoped).is_some())
+                });
+            }
+            status.info(&format!(
+                "Indexing {} {} files",
+                files.len(),
+                change_scope.describe()
+            ));
+            Some(files)
+        }
+        None => None,
+    };
+    // --reindex re-indexes files even when they are unchanged
+    let index_future = async {
+        match (&changed, scope) {
+            (Some(files), _) => {
+                cs_index::smart_update_files_cancellable(
+                    path,
+                    files,
+                    cli.reindex,
+                    progress_callback,
+                    detailed_progress_callback,
+                    true,
+                    Some(model_alias),
+                    &CancellationToken::new(),
+                )
+                .await
+            }
+            (None, Some(paths)) => {
+                cs_index::smart_update_paths_cancellable(
+                    path,
+                    paths,
+                    cli.reindex,
+                    progress_callback,
+                    detailed_progress_callback,
+                    true,
+                    !cli.no_ignore,
+                    &exclude_patterns,
+                    Some(model_alias),
+                    &CancellationToken::new(),
+                )
+                .await
+            }
+            (None, None) => {
+                cs_index::smart_update_index_with_detailed_progress(
+                    path,
+                    cli.reindex,
+                    progress_callback,
+                    detailed_progress_callback,
+                    true,
+                    !cli.no_ignore,
+                    &exclude_patterns,
+                    Some(model_alias),
+                )
+                .await
+            }
+        }
+    };
     tokio::pin!(index_future);
 
     let stats = match tokio::select! {
@@ -721,6 +2437,18 @@ async fn run_index_workflow(
         stats.files_indexed as f64
     };
 
+    if stats.interrupted {
+        if let Some(pb) = file_progress_bar.take() {
+            pb.finish_and_clear();
+        }
+        if let Some(pb) = overall_progress_bar.take() {
+            pb.finish_with_message("⏹ Indexing interrupted");
+        }
+        status.warn(&format!(
+            "Indexing interrupted by user after {} files; run 'cs --index' again to finish",
+            stats.files_indexed
+        ));
+    }
     if let Some(file_pb) = file_progress_bar.take() {
         file_pb.finish_with_message("✅ All chunks processed");
     }
@@ -739,18 +2467,57 @@ async fn run_index_workflow(
     if stats.files_modified > 0 {
         status.info(&format!("  🔄 {} files updated", stats.files_modified));
     }
+    if stats.files_pulled > 0 {
+        status.info(&format!(
+            "  ☁️ {} files pulled from the remote index",
+            stats.files_pulled
+        ));
+    }
     if stats.files_up_to_date > 0 {
         status.info(&format!(
             "  ✅ {} files already current",
             stats.files_up_to_date
         ));
     }
+    if stats.files_backfilled > stats.files_unembedded {
+        status.info(&format!(
+            "  🧠 {} files given the embeddings they were indexed without",
+            stats.files_backfilled - stats.files_unembedded
+        ));
+    }
     if stats.orphaned_files_removed > 0 {
         status.info(&format!(
             "  🧹 {} orphaned entries cleaned",
             stats.orphaned_files_removed
         ));
     }
+    if stats.files_skipped > 0 {
+        status.warn(&format!(
+            "{} files skipped by --max-filesize/--max-files",
+            stats.files_skipped
+        ));
+    }
+    if stats.files_unembedded > 0 {
+        status.warn(&format!(
+            "{} files still have no embeddings; semantic search misses them until the next update",
+            stats.files_unembedded
+        ));
+    }
+    if !stats.secrets_withheld.is_empty() {
+        status.warn(&format!(
+            "{} files had likely secrets kept out of the index (--allow-secrets indexes them)",
+            stats.secrets_withheld.len()
+        ));
+        for (file, withheld) in stats.secrets_withheld.iter().take(10) {
+            status.info(&format!("  🔒 {}: {}", file.display(), withheld.summary()));
+        }
+        if stats.secrets_withheld.len() > 10 {
+            status.info(&format!(
+                "  ... and {} more ('cs --status-verbose' lists them all)",
+                stats.secrets_withheld.len() - 10
+            ));
+        }
+    }
 
     if clean_first {
         status.info(&format!(
@@ -762,6 +2529,31 @@ async fn run_index_workflow(
     Ok(())
 }
 
+/// Print boundary changes between two chunk snapshots; returns true when they differ
+fn run_chunk_diff(old_path: &Path, new_path: &Path, status: &StatusReporter) -> Result<bool> {
+    use anyhow::Context;
+    use cs_chunk::snapshot::{CorpusSnapshot, diff_snapshots, render_diff};
+
+    let load = |path: &Path| -> Result<CorpusSnapshot> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read snapshot {}", path.display()))?;
+        serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid chunk snapshot {}", path.display()))
+    };
+    let diffs = diff_snapshots(&load(old_path)?, &load(new_path)?);
+
+    if diffs.is_empty() {
+        status.success("Chunk boundaries are identical");
+        return Ok(false);
+    }
+    print!("{}", render_diff(&diffs));
+    status.warn(&format!(
+        "{} file(s) with changed chunk boundaries",
+        diffs.len()
+    ));
+    Ok(true)
+}
+
 async fn dump_file_chunks(file_path: &PathBuf) -> Result<()> {
     use std::path::Path;
 
@@ -770,7 +2562,7 @@ async fn dump_file_chunks(file_path: &PathBuf) -> Result<()> {
     // Use the shared live chunking function
     let (lines, chunk_metas) = cs_tui::chunk_file_live(path).map_err(|err| {
         eprintln!("Error: {}", err);
-        std::process::exit(1);
+        std::process::exit(EXIT_ERROR);
     })?;
 
     // Display chunks for entire file
@@ -826,7 +2618,7 @@ async fn inspect_file_metadata(file_path: &PathBuf, status: &StatusReporter) ->
 
     let metadata = fs::metadata(path)?;
     let detected_lang = cs_core::Language::from_path(path);
-    let content = fs::read_to_string(path)?;
+    let content = cs_core::encoding::read_to_string(path)?;
     let total_tokens = TokenEstimator::estimate_tokens(&content);
 
     // Basic file info
@@ -940,6 +2732,10 @@ async fn inspect_file_metadata(file_path: &PathBuf, status: &StatusReporter) ->
     Ok(())
 }
 
+/// grep's exit statuses; a search that ran and found nothing exits 1
+const EXIT_NO_MATCH: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
 #[tokio::main]
 async fn main() {
     if let Err(e) = run_main().await {
@@ -953,21 +2749,59 @@ async fn main() {
             source = err.source();
         }
 
-        std::process::exit(1);
+        std::process::exit(EXIT_ERROR);
     }
 }
 
 async fn run_main() -> Result<()> {
     let cli = Cli::parse();
+    color::configure(&cli.color);
 
     if cli.print_default_csignore {
         print!("{}", get_default_csignore_content());
         return Ok(());
     }
 
+    if let Some(shell) = cli.completions {
+        use std::io::Write;
+        std::io::stdout().write_all(&completions::script(Cli::command(), shell))?;
+        return Ok(());
+    }
+
+    if cli.help_modes {
+        if cli.json {
+            println!("{}", serde_json::to_string_pretty(&search_modes::modes())?);
+        } else {
+            print!("{}", search_modes::render_text());
+        }
+        return Ok(());
+    }
+
+    if cli.man {
+        print_man_page()?;
+        return Ok(());
+    }
+
+    if cli.no_daemon {
+        cs_embed::daemon::set_forwarding(false);
+    }
+
     // Handle MCP server mode first
     if cli.serve {
-        return run_mcp_server().await;
+        return run_mcp_server(&cli).await;
+    }
+
+    if cli.lsp {
+        return run_lsp_server(log_options(&cli)).await;
+    }
+
+    if let Some(root) = &cli.rpc {
+        let root = root.clone().unwrap_or_else(|| PathBuf::from("."));
+        return run_rpc_server(root, log_options(&cli)).await;
+    }
+
+    if cli.daemon {
+        return run_daemon(log_options(&cli)).await;
     }
 
     // Handle TUI mode
@@ -978,7 +2812,16 @@ async fn run_main() -> Result<()> {
             .cloned()
             .unwrap_or_else(|| PathBuf::from("."));
         let initial_query = cli.pattern.clone();
-        return cs_tui::run_tui(search_path, initial_query).await;
+        let mut app = cs_tui::TuiApp::new(search_path, initial_query);
+        if cli.group {
+            app.state.group_by_file = true;
+        }
+        return app.run().await;
+    }
+
+    // Handle model cache command
+    if let Some(args) = &cli.models {
+        return handle_models_command(args);
     }
 
     // Handle configuration command
@@ -999,7 +2842,7 @@ fn handle_config_command(args: &[String]) -> Result<()> {
         eprintln!("  cs --config get KEY");
         eprintln!("  cs --config set KEY VALUE");
         eprintln!("  cs --config path");
-        std::process::exit(1);
+        std::process::exit(EXIT_ERROR);
     }
 
     let subcmd = &args[0];
@@ -1012,7 +2855,7 @@ fn handle_config_command(args: &[String]) -> Result<()> {
                 }
                 Err(e) => {
                     eprintln!("Error: Failed to determine config path: {}", e);
-                    std::process::exit(1);
+                    std::process::exit(EXIT_ERROR);
                 }
             }
         }
@@ -1022,7 +2865,7 @@ fn handle_config_command(args: &[String]) -> Result<()> {
                     if path.exists() {
                         eprintln!("⚠️  Config file already exists at: {}", path.display());
                         eprintln!("Use 'cs --config set' to modify existing configuration");
-                        std::process::exit(1);
+                        std::process::exit(EXIT_ERROR);
                     }
 
                     let config = cs_models::UserConfig::default();
@@ -1042,13 +2885,13 @@ fn handle_config_command(args: &[String]) -> Result<()> {
                         }
                         Err(e) => {
                             eprintln!("Error: Failed to save config: {}", e);
-                            std::process::exit(1);
+                            std::process::exit(EXIT_ERROR);
                         }
                     }
                 }
                 Err(e) => {
                     eprintln!("Error: Failed to determine config path: {}", e);
-                    std::process::exit(1);
+                    std::process::exit(EXIT_ERROR);
                 }
             }
         }
@@ -1056,7 +2899,7 @@ fn handle_config_command(args: &[String]) -> Result<()> {
             if args.len() < 2 {
                 eprintln!("Error: 'get' requires a KEY argument");
                 eprintln!("Usage: cs --config get KEY");
-                std::process::exit(1);
+                std::process::exit(EXIT_ERROR);
             }
             let key = &args[1];
 
@@ -1067,13 +2910,13 @@ fn handle_config_command(args: &[String]) -> Result<()> {
                         Ok(())
                     } else {
                         eprintln!("Error: Unknown configuration key: {}", key);
-                        std::process::exit(1);
+                        std::process::exit(EXIT_ERROR);
                     }
                 }
                 Err(e) => {
                     eprintln!("Error: Failed to load config: {}", e);
                     eprintln!("Run 'cs --config init' to create a config file");
-                    std::process::exit(1);
+                    std::process::exit(EXIT_ERROR);
                 }
             }
         }
@@ -1081,7 +2924,7 @@ fn handle_config_command(args: &[String]) -> Result<()> {
             if args.len() < 3 {
                 eprintln!("Error: 'set' requires KEY and VALUE arguments");
                 eprintln!("Usage: cs --config set KEY VALUE");
-                std::process::exit(1);
+                std::process::exit(EXIT_ERROR);
             }
             let key = &args[1];
             let value = &args[2];
@@ -1097,20 +2940,20 @@ fn handle_config_command(args: &[String]) -> Result<()> {
                                 }
                                 Err(e) => {
                                     eprintln!("Error: Failed to save config: {}", e);
-                                    std::process::exit(1);
+                                    std::process::exit(EXIT_ERROR);
                                 }
                             }
                         }
                         Err(e) => {
                             eprintln!("Error: {}", e);
-                            std::process::exit(1);
+                            std::process::exit(EXIT_ERROR);
                         }
                     }
                 }
                 Err(e) => {
                     eprintln!("Error: Failed to load config: {}", e);
                     eprintln!("Run 'cs --config init' to create a config file");
-                    std::process::exit(1);
+                    std::process::exit(EXIT_ERROR);
                 }
             }
         }
@@ -1133,6 +2976,24 @@ fn handle_config_command(args: &[String]) -> Result<()> {
                     println!("  line-numbers-default: {}", config.line_numbers_default);
                     println!("  rerank-enabled: {}", config.rerank_enabled);
                     println!("  rerank-model: {}", config.rerank_model);
+                    println!(
+                        "  default-model: {}",
+                        config.default_model.as_deref().unwrap_or("(registry default)")
+                    );
+                    println!(
+                        "  api-keys-file: {}",
+                        config.get("api-keys-file").unwrap_or_default()
+                    );
+                    println!("  cache-dir: {}", config.get("cache-dir").unwrap_or_default());
+                    println!("  device: {}", config.device);
+                    println!(
+                        "  mcp-roots: {}",
+                        config.get("mcp-roots").unwrap_or_default()
+                    );
+                    println!("  mcp-read-only: {}", config.mcp_read_only);
+                    println!("  mcp-rate-limit: {}", config.mcp_rate_limit);
+                    println!("  mcp-max-concurrent: {}", config.mcp_max_concurrent);
+                    println!("  mcp-timeout-secs: {}", config.mcp_timeout_secs);
                     println!("  quiet-mode: {}", config.quiet_mode);
                     Ok(())
                 }
@@ -1146,36 +3007,262 @@ fn handle_config_command(args: &[String]) -> Result<()> {
         _ => {
             eprintln!("Error: Unknown config subcommand: {}", subcmd);
             eprintln!("Valid subcommands: init, list, get, set, path");
-            std::process::exit(1);
+            std::process::exit(EXIT_ERROR);
         }
     }
 }
 
-async fn run_mcp_server() -> Result<()> {
-    // Configure service-safe logging for MCP mode (no stdout pollution)
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::INFO.into()),
-        )
-        .init();
+fn handle_models_command(args: &[String]) -> Result<()> {
+    let registry = cs_models::ModelRegistry::default();
+    let subcmd = args.first().map_or("list", String::as_str);
+    match subcmd {
+        "list" => {
+            let cached = cs_embed::cache::cached_models();
+            let size_of = |repo: &str| {
+                cached
+                    .iter()
+                    .find(|model| model.repo == repo)
+                    .map(|model| model.size)
+            };
+            println!(
+                "📦 Embedding models (cache: {})\n",
+                cs_embed::model_cache_dir().display()
+            );
+            let mut listed = Vec::new();
+            for alias in registry.aliases() {
+                let config = &registry.models[alias];
+                let status = match cs_embed::cache::model_repo(alias) {
+                    Some(repo) => {
+                        let status = match size_of(&repo) {
+                            Some(size) => format!("{:.1} MB", size as f64 / (1024.0 * 1024.0)),
+                            None => "not downloaded".to_string(),
+                        };
+                        listed.push(repo);
+                        status
+                    }
+                    None => format!("{} (nothing to download)", config.provider),
+                };
+                let default = if alias == registry.default_model {
+                    " (default)"
+                } else {
+                    ""
+                };
+                println!(
+                    "  {:<16} {:<40} {:>5}d  {}{}",
+                    alias, config.name, config.dimensions, status, default
+                );
+            }
+
+            // Rerankers, and models cached by other versions
+            let others: Vec<_> = cached
+                .iter()
+                .filter(|model| !listed.contains(&model.repo))
+                .collect();
+            if !others.is_empty() {
+                println!("\n📦 Other cached models:\n");
+                for model in others {
+                    println!(
+                        "  {:<58} {:.1} MB",
+                        model.repo,
+                        model.size as f64 / (1024.0 * 1024.0)
+                    );
+                }
+            }
+            let total: u64 = cached.iter().map(|model| model.size).sum();
+            println!(
+                "\nTotal: {:.1} MB in {} cached model(s)",
+                total as f64 / (1024.0 * 1024.0),
+                cached.len()
+            );
+            Ok(())
+        }
+        "download" => {
+            let names = if args.len() > 1 {
+                args[1..].to_vec()
+            } else {
+                vec![registry.default_model.clone()]
+            };
+            for name in &names {
+                let Some((alias, config)) = registry.find(name) else {
+                    eprintln!("Error: Unknown model: {}", name);
+                    if let Some(suggestion) = registry.suggest(name) {
+                        eprintln!("Did you mean '{}'?", suggestion);
+                    }
+                    eprintln!("Run 'cs --models list' to see available models");
+                    std::process::exit(EXIT_ERROR);
+                };
+                if cs_embed::cache::model_repo(alias).is_none() {
+                    println!(
+                        "ℹ️  {} runs on the {} provider: nothing to download",
+                        alias, config.provider
+                    );
+                    continue;
+                }
+                println!("⬇️  Downloading {} ({})...", alias, config.name);
+                let progress: cs_embed::ModelDownloadCallback =
+                    Box::new(|message| eprintln!("{}", message));
+                if let Err(e) = cs_embed::cache::download_model(&config.name, Some(progress)) {
+                    eprintln!("Error: Failed to download {}: {}", alias, e);
+                    std::process::exit(EXIT_ERROR);
+                }
+                println!("✅ {} is cached", alias);
+            }
+            Ok(())
+        }
+        "remove" => {
+            if args.len() < 2 {
+                eprintln!("Error: 'remove' requires a MODEL argument");
+                eprintln!("Usage: cs --models remove MODEL...");
+                std::process::exit(EXIT_ERROR);
+            }
+            let cached = cs_embed::cache::cached_models();
+            for name in &args[1..] {
+                // A registry alias or name, or the repository id shown by `cs --models list`
+                let repo = cs_embed::cache::model_repo(name).unwrap_or_else(|| name.clone());
+                let Some(model) = cached.iter().find(|model| model.repo == repo) else {
+                    eprintln!("Error: {} is not in the model cache", name);
+                    eprintln!("Run 'cs --models list' to see downloaded models");
+                    std::process::exit(EXIT_ERROR);
+                };
+                match cs_embed::cache::remove_model(model) {
+                    Ok(freed) => println!(
+                        "🗑️  Removed {} ({:.1} MB freed)",
+                        model.repo,
+                        freed as f64 / (1024.0 * 1024.0)
+                    ),
+                    Err(e) => {
+                        eprintln!("Error: {:#}", e);
+                        std::process::exit(EXIT_ERROR);
+                    }
+                }
+            }
+            Ok(())
+        }
+        "path" => {
+            println!("{}", cs_embed::model_cache_dir().display());
+            Ok(())
+        }
+        _ => {
+            eprintln!("Error: Unknown models subcommand: {}", subcmd);
+            eprintln!("Valid subcommands: list, download, remove, path");
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+}
+
+async fn run_mcp_server(cli: &Cli) -> Result<()> {
+    // Service-safe logging for MCP mode: stderr only, stdout carries the protocol
+    logging::init(log_options(cli), tracing::Level::INFO);
 
     let cwd = std::env::current_dir()?;
-    let server = mcp_server::CcMcpServer::new(cwd)?;
+    let project_config = cs_models::ProjectConfig::discover(&cwd)?.unwrap_or_default();
+    // Allowed roots and read-only mode: the flags, then the user config
+    let user_config = cs_models::UserConfig::load()?;
+    let roots = if cli.mcp_root.is_empty() {
+        user_config.mcp_roots
+    } else {
+        cli.mcp_root.clone()
+    };
+    let access =
+        mcp::access::AccessPolicy::new(&roots, cli.mcp_read_only || user_config.mcp_read_only)?;
+    if !access.roots().is_empty() {
+        tracing::info!("MCP tool calls limited to {:?}", access.roots());
+    }
+    if access.read_only() {
+        tracing::info!("MCP server is read-only");
+        cs_engine::set_read_only(true);
+    }
+
+    // Request budgets: the flags, then the user config
+    let limits = mcp::limits::Limits {
+        rate_per_minute: cli.mcp_rate_limit.unwrap_or(user_config.mcp_rate_limit),
+        max_concurrent: cli
+            .mcp_max_concurrent
+            .unwrap_or(user_config.mcp_max_concurrent),
+        query_timeout: cli.mcp_timeout.or_else(|| {
+            (user_config.mcp_timeout_secs > 0)
+                .then(|| std::time::Duration::from_secs(user_config.mcp_timeout_secs))
+        }),
+    };
+    tracing::info!("MCP request limits: {:?}", limits);
+
+    let mut server = mcp_server::CcMcpServer::new(cwd.clone())?
+        .with_access(access)
+        .with_limits(limits);
+    if cli.redact_paths || project_config.redact_paths == Some(true) {
+        let root = cs_engine::find_nearest_index_root(&cwd).unwrap_or(cwd);
+        server = server.with_redactor(cs_core::redact::PathRedactor::new(&root));
+    }
     server.run().await
 }
 
+async fn run_lsp_server(log: logging::LogOptions) -> Result<()> {
+    // stdout carries the protocol, as in MCP mode
+    logging::init(log, tracing::Level::WARN);
+
+    lsp::serve(
+        tokio::io::BufReader::new(tokio::io::stdin()),
+        tokio::io::stdout(),
+    )
+    .await
+}
+
+async fn run_rpc_server(root: PathBuf, log: logging::LogOptions) -> Result<()> {
+    use anyhow::Context;
+    logging::init(log, tracing::Level::WARN);
+
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("Cannot serve {}", root.display()))?;
+    // Serve the enclosing index when started in a subdirectory of one
+    let root = cs_engine::find_nearest_index_root(&root).unwrap_or(root);
+    rpc::serve(
+        root,
+        tokio::io::BufReader::new(tokio::io::stdin()),
+        tokio::io::stdout(),
+        true,
+    )
+    .await
+}
+
+/// The man page clap_mangen generates from `Cli`, with the search modes section added after
+/// the options
+fn print_man_page() -> Result<()> {
+    use std::io::Write;
+    let man = clap_mangen::Man::new(Cli::command());
+    let mut page = Vec::new();
+    man.render_title(&mut page)?;
+    man.render_name_section(&mut page)?;
+    man.render_synopsis_section(&mut page)?;
+    man.render_description_section(&mut page)?;
+    man.render_options_section(&mut page)?;
+    page.extend_from_slice(search_modes::render_roff().as_bytes());
+    man.render_version_section(&mut page)?;
+    std::io::stdout().write_all(&page)?;
+    Ok(())
+}
+
+async fn run_daemon(log: logging::LogOptions) -> Result<()> {
+    logging::init(log, tracing::Level::WARN);
+
+    let socket = cs_embed::daemon::socket_path()?;
+    tokio::task::spawn_blocking(move || {
+        cs_engine::daemon::serve(&socket, |event| eprintln!("{}", event))
+    })
+    .await?
+}
+
 async fn run_cli_mode(cli: Cli) -> Result<()> {
     // Regular CLI mode logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::WARN.into()),
-        )
-        .init();
+    logging::init(log_options(&cli), tracing::Level::WARN);
 
-    let status = StatusReporter::new(cli.quiet);
+    let status = StatusReporter::new(cli.quiet, cli.json || cli.json_v1 || cli.jsonl);
+    cs_index::set_file_limits(file_limits(&cli));
+    cs_index::set_chunk_overrides(chunk_overrides(&cli));
+    cs_index::set_embed_template(cli.embed_template.clone());
+    cs_index::set_sparse_vectors(cli.sparse.then_some(true));
+    cs_index::set_allow_secrets(cli.allow_secrets.then_some(true));
+    cs_index::set_remote(cli.remote.clone());
 
     // Handle command flags first (these take precedence over search)
     if let Some(model_name) = cli.switch_model.as_deref() {
@@ -1186,7 +3273,7 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
             .unwrap_or_else(|| PathBuf::from("."));
 
         let registry = cs_models::ModelRegistry::default();
-        let (model_alias, model_config) = resolve_model_selection(&registry, Some(model_name))?;
+        let (model_alias, model_config) = registry.resolve(Some(model_name))?;
 
         if !cli.force {
             let manifest_path = path.join(".cs").join("manifest.json");
@@ -1195,7 +3282,7 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
                 && let Ok(manifest) = serde_json::from_slice::<cs_index::IndexManifest>(&data)
                 && let Some(existing_model) = manifest.embedding_model.clone()
                 && let Ok((existing_alias, existing_config)) =
-                    resolve_model_selection(&registry, Some(existing_model.as_str()))
+                    registry.resolve(Some(existing_model.as_str()))
                 && existing_config.name == model_config.name
             {
                 status.section_header("Switching Embedding Model");
@@ -1227,6 +3314,7 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
         run_index_workflow(
             &status,
             &path,
+            None,
             &cli,
             model_alias.as_str(),
             &model_config,
@@ -1237,19 +3325,143 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
         return Ok(());
     }
 
+    if let Some(model_name) = cli.shadow_index.as_deref() {
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."));
+        return run_shadow_index(&status, &path, model_name, cli.shadow_sample);
+    }
+
+    if let Some(model_name) = cli.shadow_compare.as_deref() {
+        return run_shadow_compare(&status, &cli, model_name);
+    }
+
+    if let Some(format) = cli.export.as_deref() {
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."));
+        return run_export(&status, &path, format);
+    }
+
+    if cli.bench {
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."));
+        return run_bench(&status, &path, &cli);
+    }
+
+    if cli.calibrate {
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."));
+        return run_calibrate(
+            &status,
+            &path,
+            cli.calibrate_queries.as_deref(),
+            cli.top_k.unwrap_or(20),
+            cli.json,
+        );
+    }
+
+    if cli.map {
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."));
+        return run_map(&status, &path, cli.clusters.map(usize::from), cli.json);
+    }
+
+    if cli.audit_log.is_some() || cli.audit_tail.is_some() {
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."));
+        return match (cli.audit_log.as_deref(), cli.audit_tail) {
+            (Some(mode), _) => run_audit_log_config(&status, &path, mode),
+            (None, Some(count)) => run_audit_tail(&status, &path, count, cli.jsonl),
+            (None, None) => unreachable!(),
+        };
+    }
+
+    if !cli.merge_shards.is_empty() {
+        return run_merge_shards(&status, Path::new("."), &cli.merge_shards);
+    }
+
+    if cli.export_bundle.is_some() || cli.import_bundle.is_some() {
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."));
+        return run_bundle(&status, &path, &cli);
+    }
+
+    if let Some(url) = &cli.remote_push {
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."));
+        return run_remote_push(&status, &path, url.clone(), cli.json);
+    }
+
     if cli.index {
         let path = cli
             .files
             .first()
             .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
             .unwrap_or_else(|| PathBuf::from("."));
 
+        // Without --model, cs.toml or the user config may name the model
+        let configured_model = match cli.model {
+            Some(_) => None,
+            None => cs_models::configured_model(&path)?,
+        };
         let registry = cs_models::ModelRegistry::default();
-        let (model_alias, model_config) = resolve_model_selection(&registry, cli.model.as_deref())?;
+        let (model_alias, model_config) =
+            registry.resolve(cli.model.as_deref().or(configured_model.as_deref()))?;
+
+        if cli.dry_run {
+            return run_index_dry_run(&status, &path, &cli, &registry, &model_alias);
+        }
+
+        // clap requires --shard-out alongside --shard
+        if let (Some(shard), Some(out_dir)) = (cli.shard, cli.shard_out.as_deref()) {
+            return run_shard_index(
+                &status,
+                &path,
+                &cli,
+                shard,
+                out_dir,
+                model_config.name.as_str(),
+            );
+        }
 
+        // A subdirectory of an existing index, or a --from-file list, updates just those files
+        let (root, scope) = index_scope(&path, cli.from_file.as_deref())?;
         run_index_workflow(
             &status,
-            &path,
+            &root,
+            scope.as_deref(),
             &cli,
             model_alias.as_str(),
             &model_config,
@@ -1307,6 +3519,66 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
         return Ok(());
     }
 
+    if cli.gc {
+        let gc_path = cli
+            .files
+            .first()
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from("."));
+        let policy = cs_index::gc::gc_policy(&gc_path);
+        let stats = cs_index::gc::collect_garbage(&gc_path, &policy)?;
+        if cli.json {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+            return Ok(());
+        }
+
+        status.section_header("Collecting Index Garbage");
+        if stats.orphaned_sidecars_removed > 0 {
+            status.info(&format!(
+                "  🧹 {} orphaned sidecars removed",
+                stats.orphaned_sidecars_removed
+            ));
+        }
+        if stats.content_caches_removed > 0 {
+            status.info(&format!(
+                "  🧹 {} extracted PDF texts of removed files deleted",
+                stats.content_caches_removed
+            ));
+        }
+        if stats.temp_files_removed > 0 {
+            status.info(&format!(
+                "  🧹 {} leftover temporary files removed",
+                stats.temp_files_removed
+            ));
+        }
+        for cache in &stats.caches_removed {
+            status.info(&format!(
+                "  🗑️  Removed cache {} (rebuilt when needed)",
+                cache.display()
+            ));
+        }
+        let freed_mb = stats.bytes_freed as f64 / (1024.0 * 1024.0);
+        let index_size_mb = stats.index_size_bytes as f64 / (1024.0 * 1024.0);
+        if stats.bytes_freed > 0 {
+            status.success(&format!(
+                "Freed {:.1} MB; index is now {:.1} MB",
+                freed_mb, index_size_mb
+            ));
+        } else {
+            status.info(&format!(
+                "Nothing to collect; index is {:.1} MB",
+                index_size_mb
+            ));
+        }
+        if stats.over_budget {
+            status.warn(&format!(
+                "Index is over its {} MB budget even without caches; raise max_size_mb under [gc] in cs.toml or index fewer files",
+                policy.max_size_mb
+            ));
+        }
+        return Ok(());
+    }
+
     if cli.add {
         // Handle --add flag
         // When using --add, the file path might be in pattern or files
@@ -1339,6 +3611,27 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
         return Ok(());
     }
 
+    if cli.doctor {
+        let path = cli
+            .files
+            .first()
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from("."));
+        let checks = doctor::run_checks(&path, cli.model.as_deref());
+        let failed = if cli.json {
+            println!("{}", serde_json::to_string_pretty(&checks)?);
+            checks
+                .iter()
+                .any(|check| check.health == doctor::Health::Fail)
+        } else {
+            doctor::report(&status, &checks)
+        };
+        if failed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     if cli.status || cli.status_verbose {
         // Handle --status and --status-verbose flags
         let status_path = cli
@@ -1348,6 +3641,12 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
             .unwrap_or_else(|| PathBuf::from("."));
         let verbose = cli.status_verbose;
 
+        if cli.json {
+            let stats = cs_index::get_index_stats(&status_path)?;
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+            return Ok(());
+        }
+
         status.section_header("Index Status");
         let check_spinner = status.create_spinner("Reading index...");
         let stats = cs_index::get_index_stats(&status_path)?;
@@ -1360,40 +3659,52 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
             status.info(&format!("Index location: {}", status_path.display()));
             status.success(&format!("Files indexed: {}", stats.total_files));
             status.info(&format!("  Total chunks: {}", stats.total_chunks));
-            status.info(&format!("  Embedded chunks: {}", stats.embedded_chunks));
+            status.info(&format!(
+                "  Embedded chunks: {} ({:.0}%)",
+                stats.embedded_chunks,
+                stats.embedding_coverage() * 100.0
+            ));
 
-            let manifest_path = status_path.join(".cs").join("manifest.json");
-            if let Ok(data) = std::fs::read(&manifest_path)
-                && let Ok(manifest) = serde_json::from_slice::<cs_index::IndexManifest>(&data)
-                && let Some(model_name) = manifest.embedding_model
-            {
+            if let Some(model_name) = &stats.embedding_model {
                 let registry = cs_models::ModelRegistry::default();
-                let alias = registry
-                    .models
-                    .iter()
-                    .find(|(_, config)| config.name == model_name)
-                    .map(|(alias, _)| alias.clone())
-                    .unwrap_or_else(|| model_name.clone());
-                let dims = manifest
+                let known = registry.find(model_name);
+                let dims = stats
                     .embedding_dimensions
-                    .or_else(|| {
-                        registry
-                            .models
-                            .iter()
-                            .find(|(_, config)| config.name == model_name)
-                            .map(|(_, config)| config.dimensions)
-                    })
+                    .or_else(|| known.map(|(_, config)| config.dimensions))
                     .unwrap_or(0);
 
-                if alias == model_name {
-                    status.info(&format!("  Model: {} ({} dims)", model_name, dims));
-                } else {
-                    status.info(&format!(
+                match known {
+                    Some((alias, _)) if alias != model_name => status.info(&format!(
                         "  Model: {} (alias '{}', {} dims)",
                         model_name, alias, dims
+                    )),
+                    _ => status.info(&format!("  Model: {} ({} dims)", model_name, dims)),
+                }
+            }
+            // Sidecars embedded with a model other than the manifest's need a rebuild
+            for (model, breakdown) in &stats.models {
+                if let Some(index_model) = &stats.embedding_model
+                    && model != index_model
+                    && model != "unknown"
+                {
+                    status.warn(&format!(
+                        "  {} files embedded with {}; run 'cs --switch-model' to rebuild",
+                        breakdown.files, model
                     ));
                 }
             }
+            if !stats.stale_files.is_empty() {
+                status.warn(&format!(
+                    "  Stale files: {} changed or deleted since indexing (refreshed on the next search or 'cs --index')",
+                    stats.stale_files.len()
+                ));
+            }
+            if !stats.withheld_secrets.is_empty() {
+                status.warn(&format!(
+                    "  Withheld secrets: {} files had likely secrets kept out of the index (--allow-secrets indexes them)",
+                    stats.withheld_secrets.len()
+                ));
+            }
 
             if verbose {
                 let size_mb = stats.total_size_bytes as f64 / (1024.0 * 1024.0);
@@ -1433,6 +3744,62 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
                         compression_ratio * 100.0
                     ));
                 }
+
+                status.info("  By language:");
+                let mut languages: Vec<_> = stats.languages.iter().collect();
+                languages.sort_by(|a, b| b.1.files.cmp(&a.1.files).then_with(|| a.0.cmp(b.0)));
+                for (language, breakdown) in languages {
+                    status.info(&format!(
+                        "    {:<12} {:>6} files {:>8} chunks {:>8} embedded",
+                        language, breakdown.files, breakdown.chunks, breakdown.embedded_chunks
+                    ));
+                }
+
+                status.info("  By chunk type:");
+                for (chunk_type, count) in &stats.chunk_types {
+                    status.info(&format!("    {:<12} {:>8} chunks", chunk_type, count));
+                }
+
+                if !stats.models.is_empty() {
+                    status.info("  By model:");
+                    for (model, breakdown) in &stats.models {
+                        status.info(&format!(
+                            "    {} ({} files, {} embedded chunks)",
+                            model, breakdown.files, breakdown.embedded_chunks
+                        ));
+                    }
+                }
+
+                status.info("  Largest files:");
+                for file in &stats.largest_files {
+                    status.info(&format!(
+                        "    {:>8.1} KB {:>5} chunks  {}",
+                        file.size_bytes as f64 / 1024.0,
+                        file.chunks,
+                        file.path.display()
+                    ));
+                }
+
+                const STALE_FILES_SHOWN: usize = 20;
+                if !stats.stale_files.is_empty() {
+                    status.info("  Stale files:");
+                    for file in stats.stale_files.iter().take(STALE_FILES_SHOWN) {
+                        status.info(&format!("    {}", file.display()));
+                    }
+                    if stats.stale_files.len() > STALE_FILES_SHOWN {
+                        status.info(&format!(
+                            "    ... and {} more",
+                            stats.stale_files.len() - STALE_FILES_SHOWN
+                        ));
+                    }
+                }
+
+                if !stats.withheld_secrets.is_empty() {
+                    status.info("  Withheld secrets:");
+                    for (file, withheld) in &stats.withheld_secrets {
+                        status.info(&format!("    {}: {}", file.display(), withheld.summary()));
+                    }
+                }
             }
         }
         return Ok(());
@@ -1447,7 +3814,7 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
             cli.files[0].clone()
         } else {
             eprintln!("Error: --inspect requires a file path");
-            std::process::exit(1);
+            std::process::exit(EXIT_ERROR);
         };
 
         status.section_header("File Inspection");
@@ -1457,6 +3824,54 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
         return Ok(());
     }
 
+    if let Some(paths) = &cli.chunk_diff {
+        let changed = run_chunk_diff(&paths[0], &paths[1], &status)?;
+        std::process::exit(if changed { 1 } else { 0 });
+    }
+
+    if let Some(file_path) = &cli.chunks {
+        let configured_model = match cli.model {
+            Some(_) => None,
+            None => {
+                let dir = file_path
+                    .parent()
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."));
+                cs_models::configured_model(dir)?
+            }
+        };
+        let (_, model_config) = cs_models::ModelRegistry::default()
+            .resolve(cli.model.as_deref().or(configured_model.as_deref()))?;
+        let dir = file_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let report = chunk_report::build_report(
+            file_path,
+            &model_config.name,
+            &cs_index::chunk_overrides(dir),
+            cs_index::embed_template(dir).as_ref(),
+        )?;
+        if cli.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            chunk_report::print_report(&report);
+        }
+        return Ok(());
+    }
+
+    if let Some(id) = &cli.get_chunk {
+        let Some(file) = cli
+            .files
+            .first()
+            .cloned()
+            .or_else(|| cli.pattern.as_ref().map(PathBuf::from))
+        else {
+            anyhow::bail!("--get-chunk needs the file the chunk is in");
+        };
+        return run_get_chunk(&file, id, cli.json);
+    }
+
     if cli.dump_chunks {
         // Handle --dump-chunks flag
         let file_path = if let Some(pattern) = &cli.pattern {
@@ -1465,7 +3880,7 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
             cli.files[0].clone()
         } else {
             eprintln!("Error: --dump-chunks requires a file path");
-            std::process::exit(1);
+            std::process::exit(EXIT_ERROR);
         };
 
         dump_file_chunks(&file_path).await?;
@@ -1475,17 +3890,97 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
     // Validate conflicting flags
     if cli.files_with_matches && cli.files_without_matches {
         eprintln!("Error: Cannot use -l and -L together");
-        std::process::exit(1);
+        std::process::exit(EXIT_ERROR);
+    }
+
+    let mut cli = resolve_similar_to(resolve_pattern_flags(cli)?)?;
+    apply_default_mode(&mut cli, &status);
+
+    if !cli.only.is_empty() && !cli.semantic && !cli.lexical && !cli.symbols {
+        eprintln!("Error: --only needs chunk information; use it with --sem, --lex or --symbols");
+        std::process::exit(EXIT_ERROR);
+    }
+
+    if !cli.not.is_empty() && !cli.semantic && !cli.hybrid {
+        eprintln!("Error: --not steers embedding similarity; use it with --sem or --hybrid");
+        std::process::exit(EXIT_ERROR);
+    }
+
+    if cli.ephemeral && cli.lexical && !cli.only.is_empty() {
+        eprintln!("Error: --only with --lex needs an index; drop --ephemeral or use --sem");
+        std::process::exit(EXIT_ERROR);
     }
 
     // Default behavior: search with pattern
     if let Some(ref pattern) = cli.pattern {
         let reindex = cli.reindex;
 
+        // `-` reads standard input, like grep
+        let from_stdin = cli.files.iter().any(|p| cs_engine::is_stdin_path(p));
+        if from_stdin && cli.files.len() > 1 {
+            eprintln!("Error: '-' (standard input) cannot be combined with other paths");
+            std::process::exit(EXIT_ERROR);
+        }
+
+        // --changed/--staged/--since: search the changed files as if they were named on the command line
+        if let Some(scope) =
+            vcs::ChangeScope::from_flags(cli.changed, cli.staged, cli.since.as_deref())
+        {
+            if from_stdin {
+                eprintln!(
+                    "Error: '-' (standard input) cannot be combined with --changed, --staged or --since"
+                );
+                std::process::exit(EXIT_ERROR);
+            }
+            cli.files = vcs::changed_in_targets(&cli.files, &scope)?;
+            if cli.files.is_empty() {
+                status.info(&format!("No {} files to search", scope.describe()));
+                std::process::exit(EXIT_NO_MATCH);
+            }
+            // Name the files even when only one changed
+            if !cli.no_filenames {
+                cli.with_filenames = true;
+            }
+        }
+
+        // --within: search the files of the previous results, keeping only hits in their spans
+        let within = match &cli.within {
+            Some(previous) => {
+                if from_stdin {
+                    eprintln!("Error: '-' (standard input) cannot be combined with --within");
+                    std::process::exit(EXIT_ERROR);
+                }
+                let scope = read_result_scope(previous)?;
+                // Path arguments narrow the previous results
+                let targets: Vec<PathBuf> = cli
+                    .files
+                    .iter()
+                    .map(|target| target.canonicalize().unwrap_or_else(|_| target.clone()))
+                    .collect();
+                cli.files = scope
+                    .files()
+                    .into_iter()
+                    .filter(|file| {
+                        targets.is_empty() || targets.iter().any(|target| file.starts_with(target))
+                    })
+                    .collect();
+                if cli.files.is_empty() {
+                    status.info("No previous results to search within");
+                    std::process::exit(EXIT_NO_MATCH);
+                }
+                if !cli.no_filenames {
+                    cli.with_filenames = true;
+                }
+                Some(std::sync::Arc::new(scope))
+            }
+            None => None,
+        };
+
         // Determine repo root for .csignore loading
         let repo_root_path = cli
             .files
             .first()
+            .filter(|_| !from_stdin)
             .map(|p| {
                 if p.is_dir() {
                     p.clone()
@@ -1496,29 +3991,36 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
             .unwrap_or_else(|| PathBuf::from("."));
 
         let repo_root = Some(repo_root_path.as_path());
+        // Per-repository defaults from cs.toml; flags override them
+        let project_config =
+            cs_models::ProjectConfig::discover(&repo_root_path)?.unwrap_or_default();
 
         // Build options to get exclusion patterns
-        let temp_options = build_options(&cli, reindex, repo_root);
+        let temp_options = build_options(&cli, reindex, repo_root, &project_config);
 
         let expanded_targets = if cli.files.is_empty() {
             vec![PathBuf::from(".")]
+        } else if from_stdin {
+            vec![PathBuf::from(cs_engine::STDIN_LABEL)]
         } else {
             expand_glob_patterns(&cli.files, &temp_options.exclude_patterns)?
         };
 
-        let include_patterns = if cli.files.is_empty() {
+        let include_patterns = if cli.files.is_empty() || from_stdin {
             Vec::new()
         } else {
             build_include_patterns(&expanded_targets)
         };
 
-        let mut search_root = if include_patterns.is_empty() {
+        let mut search_root = if from_stdin {
+            PathBuf::from(cs_engine::STDIN_PATH)
+        } else if include_patterns.is_empty() {
             PathBuf::from(".")
         } else {
             find_search_root(&include_patterns)
         };
 
-        if expanded_targets.len() == 1 && !expanded_targets[0].exists() {
+        if expanded_targets.len() == 1 && !expanded_targets[0].exists() && !from_stdin {
             search_root = expanded_targets[0].clone();
         }
 
@@ -1531,7 +4033,8 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
             include_patterns
         };
 
-        // Handle multiple files like grep; allow -h/-H overrides
+        // Name files like grep: when several files or a directory are searched, unless
+        // --no-filename or -H says otherwise; every mode and text output follows this
         let mut show_filenames = if include_patterns.is_empty() {
             expanded_targets.len() > 1 || expanded_targets.iter().any(|p| p.is_dir())
         } else {
@@ -1543,14 +4046,55 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
         if cli.with_filenames {
             show_filenames = true;
         }
-        let mut options = build_options(&cli, reindex, repo_root);
+        let mut options = build_options(&cli, reindex, repo_root, &project_config);
         options.show_filenames = show_filenames;
         options.include_patterns = include_patterns.clone();
         options.path = search_root.clone();
+        options.within = within;
+
+        // --redact-paths: paths relative to the enclosing index (or the search root)
+        let redactor = (cli.redact_paths || project_config.redact_paths == Some(true)).then(|| {
+            let base = if from_stdin || !search_root.exists() {
+                PathBuf::from(".")
+            } else {
+                search_root.clone()
+            };
+            let base = cs_core::paths::canonicalize(&base);
+            let base = if base.is_file() {
+                base.parent().map(Path::to_path_buf).unwrap_or(base)
+            } else {
+                base
+            };
+            PathRedactor::new(&cs_engine::find_nearest_index_root(&base).unwrap_or(base))
+        });
+        let shown_path = |path: &Path| match &redactor {
+            Some(redactor) => redactor.path(path),
+            None => path.to_path_buf(),
+        };
+
+        let summary = run_search(
+            pattern.clone(),
+            search_root,
+            options,
+            cli.cursor.as_ref(),
+            redactor.as_ref(),
+            &status,
+        )
+        .await?;
 
-        let summary = run_search(pattern.clone(), search_root, options, &status).await?;
+        // -q: the exit status is the only output
+        if cli.quiet {
+            std::process::exit(if summary.had_matches {
+                0
+            } else {
+                EXIT_NO_MATCH
+            });
+        }
 
+        // With -L, like grep, success means a file was listed
+        let mut found = summary.had_matches;
         if cli.files_without_matches {
+            found = false;
             let matched_canon: Vec<PathBuf> = summary
                 .matched_paths
                 .iter()
@@ -1569,48 +4113,323 @@ async fn run_cli_mode(cli: Cli) -> Result<()> {
                 });
 
                 if !has_match {
-                    println!("{}", target.display());
+                    println!("{}", shown_path(target).display());
+                    found = true;
+                }
+            }
+        }
+
+        // -c: like grep, one count per named file in argument order, zero when it had no
+        // matches
+        if cli.count
+            && !cli.files_with_matches
+            && !cli.files_without_matches
+            && !cli.json
+            && !cli.json_v1
+            && !cli.jsonl
+        {
+            for (file, count) in counts_in_input_order(
+                &summary.counts,
+                &expanded_targets,
+                &summary.walked,
+                from_stdin,
+            ) {
+                if show_filenames {
+                    println!(
+                        "{}:{}",
+                        style(shown_path(&file).display()).cyan().bold(),
+                        count
+                    );
+                } else {
+                    println!("{}", count);
                 }
             }
         }
 
         // grep-like exit codes: 0 if matches found, 1 if none
-        if !summary.had_matches {
-            eprintln!("No matches found");
+        if !found {
+            if !cli.files_without_matches {
+                status.info("No matches found");
+            }
 
-            // Show the closest match below threshold if available
-            if let Some(closest) = summary.closest_below_threshold {
+            // Show the closest match below threshold if available (not alongside JSON results)
+            if let Some(closest) = summary
+                .closest_below_threshold
+                .filter(|_| status.shows_progress())
+            {
                 // Format like a regular result but in red
                 let score_text = format!("[{:.3}] ", closest.score);
-                let file_text = format!("{}:", closest.file.display());
+                let file_text = if show_filenames {
+                    format!("{}:", closest.file.display())
+                } else {
+                    String::new()
+                };
 
                 // Get the pattern as a string
-                let options = build_options(&cli, false, repo_root);
-                let highlighted_preview = highlight_matches(&closest.preview, pattern, &options);
+                let options = build_options(&cli, false, repo_root, &project_config);
+                let highlighted_preview = if console::colors_enabled_stderr() {
+                    highlight_matches(&closest.preview, pattern, &closest.file, &options)
+                } else {
+                    closest.preview.clone()
+                };
 
                 // Print in red with same format as regular results, with header
                 eprintln!();
-                eprintln!("{}", style("(nearest match beneath the threshold)").dim());
+                eprintln!(
+                    "{}",
+                    style("(nearest match beneath the threshold)")
+                        .for_stderr()
+                        .dim()
+                );
                 eprintln!(
                     "{}{}{}:{}",
-                    style(score_text).red(),
-                    style(file_text).red(),
-                    style(closest.span.line_start).red(),
-                    style(highlighted_preview).red()
+                    style(score_text).for_stderr().red(),
+                    style(file_text).for_stderr().red(),
+                    style(closest.span.line_start).for_stderr().red(),
+                    style(highlighted_preview).for_stderr().red()
                 );
             }
 
-            std::process::exit(1);
+            std::process::exit(EXIT_NO_MATCH);
         }
     } else {
         eprintln!("Error: No pattern specified");
-        std::process::exit(1);
+        std::process::exit(EXIT_ERROR);
     }
 
     Ok(())
 }
 
-fn build_options(cli: &Cli, reindex: bool, repo_root: Option<&Path>) -> SearchOptions {
+/// Parse a duration like `500ms`, `5s`, `2m`, or a bare number of seconds.
+fn parse_duration(value: &str) -> std::result::Result<std::time::Duration, String> {
+    let value = value.trim();
+    let split_at = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split_at);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}': expected e.g. 500ms, 5s, 1m", value))?;
+
+    let seconds = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        other => {
+            return Err(format!(
+                "invalid duration unit '{}': use ms, s, or m",
+                other
+            ));
+        }
+    };
+
+    Ok(std::time::Duration::from_secs_f64(seconds))
+}
+
+fn chunk_overrides(cli: &Cli) -> cs_chunk::ChunkOverrides {
+    cs_chunk::ChunkOverrides {
+        max_tokens: cli.chunk_tokens,
+        overlap: cli.chunk_overlap,
+        stride: cli.no_stride.then_some(false),
+    }
+}
+
+/// Chunk size and overlap indexing under `path` uses for `model`, after flags and cs.toml
+fn describe_chunk_config(model: &str, path: &Path) -> String {
+    let config = cs_chunk::model_chunk_config(Some(model), &cs_index::chunk_overrides(path));
+    if config.enable_striding {
+        format!(
+            "{} tokens target, {} token overlap",
+            config.max_tokens, config.stride_overlap
+        )
+    } else {
+        format!("{} tokens target, no striding", config.max_tokens)
+    }
+}
+
+fn log_options(cli: &Cli) -> logging::LogOptions {
+    logging::LogOptions {
+        verbose: cli.verbose,
+        debug: cli.debug,
+        json: cli.log_json,
+    }
+}
+
+fn file_limits(cli: &Cli) -> cs_core::file_limits::FileLimits {
+    cs_core::file_limits::FileLimits {
+        max_filesize: cli.max_filesize,
+        max_files: cli.max_files,
+    }
+}
+
+fn parse_weight(value: &str) -> std::result::Result<f32, String> {
+    match value.trim().parse::<f32>() {
+        Ok(weight) if (0.0..=1.0).contains(&weight) => Ok(weight),
+        _ => Err(format!(
+            "invalid weight '{}': expected a number from 0 to 1",
+            value
+        )),
+    }
+}
+
+/// Read `--within` results from a file, or from stdin for `-`
+fn read_result_scope(previous: &Path) -> Result<cs_core::within::ResultScope> {
+    let text = if cs_engine::is_stdin_path(previous) {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(previous).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to read --within results {}: {}",
+                previous.display(),
+                e
+            )
+        })?
+    };
+    cs_core::within::ResultScope::parse(&text)
+        .map_err(|e| anyhow::anyhow!("Invalid --within results {}: {:#}", previous.display(), e))
+}
+
+/// Fold `-e`/`-f` patterns into the positional layout used by search: like grep, once a
+/// pattern comes from a flag every positional argument is a path. Afterwards `pattern`
+/// holds the first pattern and `regexp` the remaining ones.
+fn resolve_pattern_flags(mut cli: Cli) -> Result<Cli> {
+    if cli.regexp.is_empty() && cli.pattern_files.is_empty() {
+        return Ok(cli);
+    }
+
+    let mut patterns = std::mem::take(&mut cli.regexp);
+    for file in &cli.pattern_files {
+        let text = if file == Path::new("-") {
+            std::io::read_to_string(std::io::stdin())?
+        } else {
+            std::fs::read_to_string(file).map_err(|e| {
+                anyhow::anyhow!("Failed to read pattern file {}: {}", file.display(), e)
+            })?
+        };
+        patterns.extend(text.lines().map(str::to_string));
+    }
+
+    if let Some(path) = cli.pattern.take() {
+        cli.files.insert(0, PathBuf::from(path));
+    }
+
+    if patterns.len() > 1 && cli.ast {
+        anyhow::bail!(
+            "AST search takes a single pattern; -e/-f with several patterns is not supported with --ast"
+        );
+    }
+
+    let mut patterns = patterns.into_iter();
+    cli.pattern = patterns.next();
+    cli.regexp = patterns.collect();
+    Ok(cli)
+}
+
+/// Turn `--similar-to` into a semantic search whose patterns are the example's chunks; every
+/// positional argument is then a path, as with `-e`
+fn resolve_similar_to(mut cli: Cli) -> Result<Cli> {
+    let Some(example) = cli.similar_to.clone() else {
+        return Ok(cli);
+    };
+    if let Some(path) = cli.pattern.take() {
+        cli.files.insert(0, PathBuf::from(path));
+    }
+
+    let search_path = cli
+        .files
+        .first()
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("."));
+    let configured_model = match cli.model {
+        Some(_) => None,
+        None => cs_models::configured_model(&search_path)?,
+    };
+    let (_, model_config) = cs_models::ModelRegistry::default()
+        .resolve(cli.model.as_deref().or(configured_model.as_deref()))?;
+    let patterns = example.patterns(
+        Some(&model_config.name),
+        &cs_index::chunk_overrides(&search_path),
+    )?;
+
+    let mut patterns = patterns.into_iter();
+    cli.pattern = patterns.next();
+    cli.regexp = patterns.collect();
+    cli.semantic = true;
+    Ok(cli)
+}
+
+/// Choose the search mode when no mode flag was given, from `--auto` or the configured
+/// `default-search-mode`. `auto` classifies the query and reports the pick on stderr.
+fn apply_default_mode(cli: &mut Cli, status: &StatusReporter) {
+    cli.hybrid |= cli.hybrid_lex;
+    // -v only exists for regex, so it pins the mode like an explicit flag
+    if cli.semantic
+        || cli.lexical
+        || cli.hybrid
+        || cli.ast
+        || cli.symbols
+        || cli.regex
+        || cli.invert_match
+    {
+        return;
+    }
+
+    let configured = if cli.auto {
+        "auto".to_string()
+    } else {
+        cs_models::UserConfig::load()
+            .map(|config| config.default_search_mode)
+            .unwrap_or_default()
+    };
+
+    match configured.as_str() {
+        "sem" => cli.semantic = true,
+        "lex" => cli.lexical = true,
+        "hybrid" => cli.hybrid = true,
+        "auto" => {
+            let Some(query) = cli.pattern.as_deref() else {
+                return;
+            };
+            let choice = cs_engine::auto_select_mode(query);
+
+            let name = match choice.mode {
+                SearchMode::Regex => "regex",
+                SearchMode::Lexical => {
+                    cli.lexical = true;
+                    "lexical"
+                }
+                SearchMode::Semantic => {
+                    cli.semantic = true;
+                    "semantic"
+                }
+                SearchMode::Hybrid => {
+                    cli.hybrid = true;
+                    "hybrid"
+                }
+                SearchMode::Ast => {
+                    cli.ast = true;
+                    "ast"
+                }
+                SearchMode::Symbol => {
+                    cli.symbols = true;
+                    "symbol"
+                }
+            };
+            status.info(&format!(
+                "Auto mode: {} ({}); override with --regex, --lex, --sem, --hybrid or --ast",
+                name, choice.reason
+            ));
+        }
+        _ => {}
+    }
+}
+
+fn build_options(
+    cli: &Cli,
+    reindex: bool,
+    repo_root: Option<&Path>,
+    project: &cs_models::ProjectConfig,
+) -> SearchOptions {
     let mode = if cli.semantic {
         SearchMode::Semantic
     } else if cli.lexical {
@@ -1619,6 +4438,8 @@ fn build_options(cli: &Cli, reindex: bool, repo_root: Option<&Path>) -> SearchOp
         SearchMode::Hybrid
     } else if cli.ast {
         SearchMode::Ast
+    } else if cli.symbols {
+        SearchMode::Symbol
     } else {
         SearchMode::Regex
     };
@@ -1630,25 +4451,32 @@ fn build_options(cli: &Cli, reindex: bool, repo_root: Option<&Path>) -> SearchOp
     // Use the unified pattern builder
     let exclude_patterns = build_exclude_patterns(cli, repo_root);
 
-    // Set intelligent defaults for semantic search
-    let default_topk = match mode {
-        SearchMode::Semantic => Some(10),
-        _ => None,
-    };
-    let default_threshold = match mode {
-        SearchMode::Semantic => Some(0.6),
-        _ => None,
+    // Set intelligent defaults for semantic search; cs.toml replaces them per repository
+    let default_topk = if search_modes::ranked(&mode) {
+        project.top_k.or(search_modes::default_top_k(&mode))
+    } else {
+        None
     };
+    let default_threshold =
+        search_modes::default_threshold(&mode).map(|default| project.threshold.unwrap_or(default));
 
     SearchOptions {
         mode,
         query: String::new(),
+        extra_patterns: cli.regexp.clone(),
         path: PathBuf::from("."),
         top_k: cli.top_k.or(default_topk),
         threshold: cli.threshold.or(default_threshold),
-        case_insensitive: cli.ignore_case,
+        case_insensitive: cli.ignore_case
+            || (cli.smart_case
+                && cs_engine::smart_case_insensitive(
+                    cli.pattern.iter().chain(&cli.regexp).map(String::as_str),
+                    cli.fixed_strings,
+                )),
         whole_word: cli.word_regexp,
         fixed_string: cli.fixed_strings,
+        pcre2: cli.pcre2,
+        multiline: cli.multiline,
         line_numbers: cli.line_numbers,
         context_lines: context,
         before_context_lines: before_context,
@@ -1658,10 +4486,40 @@ fn build_options(cli: &Cli, reindex: bool, repo_root: Option<&Path>) -> SearchOp
         jsonl_output: cli.jsonl,
         no_snippet: cli.no_snippet,
         reindex,
-        show_scores: cli.show_scores,
+        show_scores: cli.show_scores || cli.scores_raw,
+        raw_scores: cli.scores_raw,
         show_filenames: false, // Will be set by caller
+        group_by_file: cli.group,
+        vimgrep: cli.vimgrep,
+        syntax_highlight: cli.highlight,
+        file_limits: file_limits(cli),
         files_with_matches: cli.files_with_matches,
         files_without_matches: cli.files_without_matches,
+        count: cli.count,
+        first_match: cli.quiet,
+        stats: cli.stats.then(Default::default),
+        within: None,
+        files_ranked: cli
+            .files_ranked
+            .as_deref()
+            .and_then(|aggregation| aggregation.parse().ok()),
+        invert_match: cli.invert_match,
+        only_matching: cli.only_matching,
+        replace: cli.replace.clone(),
+        search_zip: cli.search_zip,
+        only_chunk_types: cli
+            .only
+            .iter()
+            .map(|kind| match kind.as_str() {
+                "classes" => "class".to_string(),
+                kind => kind.trim_end_matches('s').to_string(),
+            })
+            .collect(),
+        exclude_tests: cli.no_tests,
+        ephemeral: cli.ephemeral,
+        hybrid_lexical: cli.hybrid_lex,
+        requester: Some("cli".to_string()),
+        timeout: cli.timeout,
         exclude_patterns,
         include_patterns: Vec::new(),
         respect_gitignore: !cli.no_ignore,
@@ -1669,7 +4527,37 @@ fn build_options(cli: &Cli, reindex: bool, repo_root: Option<&Path>) -> SearchOp
         // Enhanced embedding options (search-time only)
         rerank: cli.rerank,
         rerank_model: cli.rerank_model.clone(),
-        embedding_model: cli.model.clone(),
+        rerank_candidates: cli.rerank_candidates,
+        rerank_blend: cli.rerank_blend,
+        diversity: cli.diversity,
+        sparse_weight: cli.sparse_weight,
+        stride_pooling: cli
+            .stride_pooling
+            .as_deref()
+            .and_then(|pooling| pooling.parse().ok())
+            .unwrap_or_default(),
+        dedupe: cli
+            .dedupe
+            .as_deref()
+            .and_then(|dedupe| dedupe.parse().ok())
+            .unwrap_or_default(),
+        negative_queries: cli.not.clone(),
+        negative_weight: cli.not_weight,
+        excluded_span: cli.similar_to.as_ref().map(SimilarTo::excluded_span),
+        offset: cli
+            .cursor
+            .as_ref()
+            .map_or(cli.offset.unwrap_or(0), |cursor| cursor.offset),
+        index_epoch: cli.cursor.as_ref().and_then(|cursor| cursor.index_epoch),
+        expand_query: cli.expand_query,
+        explain: cli.explain,
+        languages: if cli.lang.is_empty() {
+            project.languages()
+        } else {
+            cli.lang.clone()
+        },
+        fusion_weights: project.fusion.unwrap_or_default(),
+        embedding_model: cli.model.clone().or_else(|| project.model.clone()),
         // AST search options
         ast_pattern: None, // Will be set from query
         ast_lang: cli.ast_lang.clone(),
@@ -1678,14 +4566,30 @@ fn build_options(cli: &Cli, reindex: bool, repo_root: Option<&Path>) -> SearchOp
     }
 }
 
-fn highlight_matches(text: &str, pattern: &str, options: &SearchOptions) -> String {
-    // Don't highlight if this is JSON/JSONL output
-    if options.json_output || options.jsonl_output {
+fn highlight_matches(text: &str, pattern: &str, file: &Path, options: &SearchOptions) -> String {
+    // Don't highlight if this is JSON/JSONL output, for inverted matches (the selected
+    // lines by definition do not contain the pattern), or for rewritten matches
+    if options.json_output
+        || options.jsonl_output
+        || options.invert_match
+        || options.replace.is_some()
+    {
         return text.to_string();
     }
 
+    if options.syntax_highlight && console::colors_enabled() {
+        // Syntax colors replace the heatmap; regex matches are marked on top of them
+        let matches = match options.mode {
+            SearchMode::Regex | SearchMode::Symbol => build_highlight_regex(pattern, options)
+                .map(|re| re.find_iter(text))
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        return highlight::highlight(text, file, &matches);
+    }
+
     match options.mode {
-        SearchMode::Regex => highlight_regex_matches(text, pattern, options),
+        SearchMode::Regex | SearchMode::Symbol => highlight_regex_matches(text, pattern, options),
         SearchMode::Semantic | SearchMode::Hybrid => {
             // For semantic/hybrid search, use subchunk similarity highlighting
             highlight_semantic_chunks(text, pattern, options)
@@ -1694,28 +4598,28 @@ fn highlight_matches(text: &str, pattern: &str, options: &SearchOptions) -> Stri
     }
 }
 
-fn highlight_regex_matches(text: &str, pattern: &str, options: &SearchOptions) -> String {
-    // Build regex from pattern with EXACT same logic as regex_search in cs-engine
-    let regex_pattern = if options.fixed_string {
-        regex::escape(pattern)
-    } else if options.whole_word {
-        // Must escape the pattern for whole_word, matching the search engine behavior
-        format!(r"\b{}\b", regex::escape(pattern))
-    } else {
-        pattern.to_string()
-    };
-
-    let regex_result = RegexBuilder::new(&regex_pattern)
-        .case_insensitive(options.case_insensitive)
-        .build();
+/// The search regex, built with EXACT same logic as regex_search in cs-engine
+fn build_highlight_regex(pattern: &str, options: &SearchOptions) -> Result<cs_engine::SearchRegex> {
+    let regex_pattern = cs_engine::build_regex_pattern(
+        std::iter::once(pattern).chain(options.extra_patterns.iter().map(String::as_str)),
+        options,
+    );
+    cs_engine::SearchRegex::new(&regex_pattern, options)
+}
 
-    match regex_result {
+fn highlight_regex_matches(text: &str, pattern: &str, options: &SearchOptions) -> String {
+    match build_highlight_regex(pattern, options) {
         Ok(re) => {
             // Replace matches with highlighted versions
-            re.replace_all(text, |caps: &regex::Captures| {
-                style(&caps[0]).red().bold().to_string()
-            })
-            .to_string()
+            let mut highlighted = String::with_capacity(text.len());
+            let mut last = 0;
+            for range in re.find_iter(text) {
+                highlighted.push_str(&text[last..range.start]);
+                highlighted.push_str(&style(&text[range.clone()]).red().bold().to_string());
+                last = range.end;
+            }
+            highlighted.push_str(&text[last..]);
+            highlighted
         }
         Err(e) => {
             // Surface regex compilation error to user
@@ -1745,6 +4649,10 @@ fn apply_heatmap_color(token: &str, score: f32) -> String {
         return token.to_string();
     }
 
+    if !console::colors_enabled() {
+        return token.to_string();
+    }
+
     let bucket = HeatmapBucket::from_score(score);
 
     match bucket.rgb() {
@@ -1764,18 +4672,36 @@ struct SearchSummary {
     had_matches: bool,
     closest_below_threshold: Option<cs_core::SearchResult>,
     matched_paths: Vec<PathBuf>,
+    /// `-c` counts per file, printed by the caller in input order
+    counts: Vec<(PathBuf, usize)>,
+    /// Files a regex `-c` search read, which count 0 when they had no matches
+    walked: Vec<PathBuf>,
 }
 
 async fn run_search(
     pattern: String,
     path: PathBuf,
     mut options: SearchOptions,
+    cursor: Option<&cs_core::cursor::SearchCursor>,
+    redactor: Option<&PathRedactor>,
     status: &StatusReporter,
 ) -> Result<SearchSummary> {
+    let started = std::time::Instant::now();
     options.query = pattern;
     options.path = path;
+    if cursor.is_some_and(|cursor| !cursor.matches(&options)) {
+        anyhow::bail!(
+            "This cursor belongs to another search; repeat the query, path and filters of its first page"
+        );
+    }
+    // With --files-ranked, --topk limits files; every chunk above the threshold counts
+    let file_limit = if options.files_ranked.is_some() {
+        options.top_k.take()
+    } else {
+        None
+    };
 
-    if options.reindex {
+    if options.reindex && !cs_engine::is_stdin_path(&options.path) {
         let reindex_spinner = status.create_spinner("Updating index...");
         cs_index::update_index(
             &options.path,
@@ -1788,46 +4714,46 @@ async fn run_search(
     }
 
     // Show search parameters for semantic mode
-    if matches!(
-        options.mode,
-        cs_core::SearchMode::Semantic | cs_core::SearchMode::Hybrid
-    ) {
+    if status.shows_progress()
+        && matches!(
+            options.mode,
+            cs_core::SearchMode::Semantic | cs_core::SearchMode::Hybrid
+        )
+    {
         let topk_info = options
             .top_k
             .map_or("unlimited".to_string(), |k| k.to_string());
         let threshold_info = options
             .threshold
             .map_or("none".to_string(), |t| format!("{:.1}", t));
-        eprintln!(
-            "ℹ Semantic search: top {} results, threshold ≥{}",
+        status.info(&format!(
+            "Semantic search: top {} results, threshold ≥{}",
             topk_info, threshold_info
-        );
+        ));
 
         let resolved_model =
             cs_engine::resolve_model_for_path(&options.path, options.embedding_model.as_deref())?;
 
         if resolved_model.alias == resolved_model.canonical_name {
-            eprintln!(
+            status.info(&format!(
                 "🤖 Model: {} ({} dims)",
                 resolved_model.canonical_name, resolved_model.dimensions
-            );
+            ));
         } else {
-            eprintln!(
+            status.info(&format!(
                 "🤖 Model: {} (alias '{}', {} dims)",
                 resolved_model.canonical_name, resolved_model.alias, resolved_model.dimensions
-            );
+            ));
         }
 
         let max_tokens =
             cs_chunk::TokenEstimator::get_model_limit(resolved_model.canonical_name.as_str());
-        let (chunk_tokens, overlap_tokens) =
-            cs_chunk::get_model_chunk_config(Some(resolved_model.canonical_name.as_str()));
 
-        eprintln!("📏 FastEmbed Config: {} token limit", max_tokens);
-        eprintln!(
-            "📄 Chunk Config: {} tokens target, {} token overlap (~20%)",
-            chunk_tokens, overlap_tokens
-        );
+        status.info(&format!("📏 FastEmbed Config: {} token limit", max_tokens));
+        status.info(&format!(
+            "📄 Chunk Config: {}",
+            describe_chunk_config(&resolved_model.canonical_name, &options.path)
+        ));
     }
 
     // Create search spinner for showing live search progress
@@ -1842,7 +4768,9 @@ async fn run_search(
     });
 
     // Create indexing progress callbacks for automatic indexing during semantic search
-    let (indexing_progress_callback, detailed_indexing_progress_callback) = if !status.quiet
+    let (indexing_progress_callback, detailed_indexing_progress_callback) = if status
+        .shows_progress()
+        && !options.ephemeral
         && matches!(
             options.mode,
             cs_core::SearchMode::Semantic | cs_core::SearchMode::Hybrid
@@ -1886,6 +4814,7 @@ async fn run_search(
                     overall_pb_clone2.set_length(progress.total_files as u64);
                 }
                 overall_pb_clone2.set_position(progress.file_index as u64);
+                overall_pb_clone2.set_message(progress.overall.to_string());
 
                 // Update file progress bar
                 if file_pb_clone2.length().unwrap_or(0) != progress.total_chunks as u64 {
@@ -1921,45 +4850,108 @@ async fn run_search(
         (None, None)
     };
 
-    let search_results = cs_engine::search_enhanced_with_indexing_progress(
+    // Ctrl-C stops an automatic index update between files and ends the search
+    let cancel = CancellationToken::new();
+    cs_index::cancel_on_interrupt(&cancel);
+    let search_results = match cs_engine::search_enhanced_cancellable(
         &options,
         search_progress_callback,
         indexing_progress_callback,
         detailed_indexing_progress_callback,
+        &cancel,
     )
-    .await?;
-    let results = &search_results.matches;
-    let matched_paths: Vec<PathBuf> = results.iter().map(|result| result.file.clone()).collect();
+    .await
+    {
+        Err(err) if cs_core::CcError::is_cancelled(&err) => {
+            status.warn("Search cancelled");
+            std::process::exit(130);
+        }
+        result => result?,
+    };
+    let searched = &search_results.matches;
+    let matched_paths: Vec<PathBuf> = searched.iter().map(|result| result.file.clone()).collect();
+    // --redact-paths rewrites what is printed, in every format
+    let redacted: Vec<cs_core::SearchResult>;
+    let results = match redactor {
+        Some(redactor) => {
+            redacted = searched
+                .iter()
+                .map(|result| redactor.result(result))
+                .collect();
+            &redacted
+        }
+        None => searched,
+    };
 
     status.finish_progress(search_spinner, &format!("Found {} results", results.len()));
 
     let mut has_matches = false;
-    if options.jsonl_output {
+    let mut counts = Vec::new();
+    let mut walked = Vec::new();
+    if options.first_match {
+        // -q prints nothing; the caller exits with the status
+        has_matches = !results.is_empty();
+    } else if let Some(aggregation) = options.files_ranked {
+        let mut ranked = cs_core::file_rank::rank_files(results, aggregation);
+        if let Some(limit) = file_limit {
+            ranked.truncate(limit);
+        }
+        has_matches = !ranked.is_empty();
+        if options.jsonl_output {
+            for file in &ranked {
+                println!("{}", serde_json::to_string(file)?);
+            }
+        } else if options.json_output {
+            println!("{}", serde_json::to_string(&ranked)?);
+        } else {
+            for file in &ranked {
+                println!(
+                    "[{:.3}] {} ({} {})",
+                    file.score,
+                    style(file.path.display()).cyan().bold(),
+                    file.chunks,
+                    if file.chunks == 1 { "match" } else { "matches" }
+                );
+            }
+        }
+    } else if options.jsonl_output {
         for result in results {
             has_matches = true;
-            let jsonl_result =
+            let mut jsonl_result =
                 cs_core::JsonlSearchResult::from_search_result(result, !options.no_snippet);
+            jsonl_result.partial = search_results.partial;
             println!("{}", serde_json::to_string(&jsonl_result)?);
         }
     } else if options.json_output {
+        // Modes without embeddings report no model
+        let model = matches!(options.mode, SearchMode::Semantic | SearchMode::Hybrid)
+            .then(|| {
+                cs_engine::resolve_model_for_path(&options.path, options.embedding_model.as_deref())
+                    .ok()
+            })
+            .flatten()
+            .map(|resolved| resolved.canonical_name);
         for result in results {
             has_matches = true;
-            let json_result = cs_core::JsonSearchResult {
-                file: result.file.display().to_string(),
-                span: result.span.clone(),
-                lang: result.lang,
-                symbol: result.symbol.clone(),
-                score: result.score,
-                signals: cs_core::SearchSignals {
-                    lex_rank: None,
-                    vec_rank: None,
-                    rrf_score: result.score,
-                },
-                preview: result.preview.clone(),
-                model: "none".to_string(),
-            };
+            let json_result = cs_core::JsonSearchResult::from_search_result(
+                result,
+                model.as_deref(),
+                search_results.partial,
+            );
             println!("{}", serde_json::to_string(&json_result)?);
         }
+    } else if options.vimgrep {
+        // --vimgrep: every match on its own line, for editor quickfix lists
+        let mut formatter = vimgrep::VimgrepFormatter::default();
+        for result in searched {
+            has_matches = true;
+            // Lines are read back from the files, so paths are redacted after formatting
+            let line = formatter.line(result);
+            match redactor {
+                Some(redactor) => println!("{}", redactor.text(&line)),
+                None => println!("{}", line),
+            }
+        }
     } else if options.files_with_matches {
         // For -l flag: print only unique filenames that have matches
         let mut printed_files = std::collections::HashSet::new();
@@ -1973,20 +4965,110 @@ async fn run_search(
     } else if options.files_without_matches {
         // For -L flag: just set has_matches, printing is done later
         has_matches = !results.is_empty();
+    } else if options.count {
+        // For -c flag: file:count (matching lines for regex, chunks otherwise); the caller
+        // prints them in input order along with the named files that had no matches
+        counts = count_matches_per_file(searched);
+        has_matches = !counts.is_empty();
+        if matches!(options.mode, SearchMode::Regex) && !cs_engine::is_stdin_path(&options.path) {
+            walked = cs_engine::regex_search_files(&options)?;
+        }
+    } else if options.group_by_file {
+        // --group: one heading per file, then its matches with line numbers, like ripgrep
+        let mut grouped = results.clone();
+        cs_core::file_rank::group_by_file(&mut grouped);
+        let mut printed_lines = std::collections::HashSet::new();
+        let mut current_file: Option<&Path> = None;
+        for result in &grouped {
+            if matches!(options.mode, SearchMode::Regex)
+                && !options.only_matching
+                && !printed_lines.insert((&result.file, result.span.line_start))
+            {
+                continue;
+            }
+            has_matches = true;
+            if options.show_filenames && current_file != Some(result.file.as_path()) {
+                if current_file.is_some() {
+                    println!();
+                }
+                println!("{}", style(result.file.display()).cyan().bold());
+                current_file = Some(result.file.as_path());
+            }
+            let score_text = if options.show_scores {
+                format!("[{:.3}] ", result.score)
+            } else {
+                String::new()
+            };
+            let highlighted_preview =
+                highlight_matches(&result.preview, &options.query, &result.file, &options);
+            let numbered = numbered_preview_lines(&highlighted_preview, result, &options)
+                .unwrap_or_else(|| {
+                    highlighted_preview
+                        .lines()
+                        .enumerate()
+                        .map(|(offset, line)| (result.span.line_start + offset, ':', line))
+                        .collect()
+                });
+            for (offset, (number, separator, line)) in numbered.into_iter().enumerate() {
+                println!(
+                    "{}{}{}{}",
+                    if offset == 0 { score_text.as_str() } else { "" },
+                    style(number).yellow(),
+                    separator,
+                    line
+                );
+            }
+            if let Some(explanation) = &result.explanation {
+                println!("  {}", style(format!("↳ {}", explanation)).dim());
+            }
+        }
     } else {
-        // Normal output
+        // Normal output; regex results come one per match, so print each line once unless -o
+        let mut printed_lines = std::collections::HashSet::new();
         for result in results {
             has_matches = true;
+            if matches!(options.mode, SearchMode::Regex)
+                && !options.only_matching
+                && !printed_lines.insert((&result.file, result.span.line_start))
+            {
+                continue;
+            }
             let score_text = if options.show_scores {
                 format!("[{:.3}] ", result.score)
             } else {
                 String::new()
             };
 
-            let highlighted_preview = highlight_matches(&result.preview, &options.query, &options);
+            let highlighted_preview =
+                highlight_matches(&result.preview, &options.query, &result.file, &options);
 
             // Format output based on options
-            if options.line_numbers && options.show_filenames {
+            let numbered = options
+                .line_numbers
+                .then(|| numbered_preview_lines(&highlighted_preview, result, &options))
+                .flatten();
+            if let Some(numbered) = numbered {
+                // -U -n: each line of a multi-line match gets its own number, like ripgrep
+                for (offset, (number, separator, line)) in numbered.into_iter().enumerate() {
+                    let file = if options.show_filenames {
+                        format!(
+                            "{}{}",
+                            style(result.file.display()).cyan().bold(),
+                            separator
+                        )
+                    } else {
+                        String::new()
+                    };
+                    println!(
+                        "{}{}{}{}{}",
+                        if offset == 0 { score_text.as_str() } else { "" },
+                        file,
+                        style(number).yellow(),
+                        separator,
+                        line
+                    );
+                }
+            } else if options.line_numbers && options.show_filenames {
                 // grep format: filename:line_number:content (all on one line)
                 println!(
                     "{}{}:{}:{}",
@@ -2015,16 +5097,187 @@ async fn run_search(
                 // No filename or line number
                 println!("{}{}", score_text, highlighted_preview);
             }
+            if let Some(explanation) = &result.explanation {
+                println!("  {}", style(format!("↳ {}", explanation)).dim());
+            }
         }
     }
 
+    // A full page may have more after it
+    if file_limit.is_none()
+        && options
+            .top_k
+            .is_some_and(|top_k| top_k > 0 && results.len() == top_k)
+    {
+        let next = cs_core::cursor::SearchCursor::new(
+            &options,
+            options.offset + results.len(),
+            results.first().and_then(|result| result.index_epoch),
+        );
+        status.info(&format!("More results: --cursor {}", next));
+    }
+
+    if search_results.partial {
+        let limit = options
+            .timeout
+            .map(|timeout| format!(" after {:?}", timeout))
+            .unwrap_or_default();
+        status.warn(&format!(
+            "Search timed out{}; results are partial (best found so far)",
+            limit
+        ));
+    }
+    if search_results.lossy_files > 0 {
+        status.warn(&format!(
+            "{} matching {} invalid UTF-8; invalid bytes are shown as U+FFFD (spans are exact)",
+            search_results.lossy_files,
+            if search_results.lossy_files == 1 {
+                "file contains"
+            } else {
+                "files contain"
+            }
+        ));
+    }
+
+    if let Some(stats) = options.stats.as_ref().filter(|_| !options.first_match) {
+        let stats = run_stats::collect(&options, stats.report(), results.len(), started.elapsed());
+        run_stats::print(&stats, options.json_output || options.jsonl_output);
+    }
+
     Ok(SearchSummary {
         had_matches: has_matches,
-        closest_below_threshold: search_results.closest_below_threshold,
+        closest_below_threshold: search_results.closest_below_threshold.map(
+            |closest| match redactor {
+                Some(redactor) => redactor.result(&closest),
+                None => closest,
+            },
+        ),
         matched_paths,
+        counts,
+        walked,
     })
 }
 
+/// Lines of a `-U` result's preview with their line numbers, for `-n` output like ripgrep's:
+/// `:` after the number for lines of the match and `-` for context lines. `None` when the
+/// preview is not a run of file lines starting at a known line (`--full-section`, other modes).
+fn numbered_preview_lines<'a>(
+    preview: &'a str,
+    result: &cs_core::SearchResult,
+    options: &SearchOptions,
+) -> Option<Vec<(usize, char, &'a str)>> {
+    if !options.multiline || options.full_section || !matches!(options.mode, SearchMode::Regex) {
+        return None;
+    }
+    // -o previews hold the match alone, others start at the first context line
+    let before = if options.only_matching {
+        0
+    } else {
+        options.before_context_lines.max(options.context_lines)
+    };
+    let first = result.span.line_start.saturating_sub(before).max(1);
+    Some(
+        preview
+            .lines()
+            .enumerate()
+            .map(|(offset, line)| {
+                let number = first + offset;
+                let separator = if (result.span.line_start..=result.span.line_end).contains(&number)
+                {
+                    ':'
+                } else {
+                    '-'
+                };
+                (number, separator, line)
+            })
+            .collect(),
+    )
+}
+
+/// `-c` counts in input order, like grep: each named file in argument order and spelled as
+/// given, with 0 when it had no matches, and the files under a named directory in walk order.
+/// `walked` lists the files a regex search read, so those without matches count 0 too; when
+/// it is empty, only the files with matches are listed.
+fn counts_in_input_order(
+    counts: &[(PathBuf, usize)],
+    targets: &[PathBuf],
+    walked: &[PathBuf],
+    from_stdin: bool,
+) -> Vec<(PathBuf, usize)> {
+    let canonical: Vec<PathBuf> = counts
+        .iter()
+        .map(|(file, _)| canonicalize_for_comparison(file))
+        .collect();
+    let positions: std::collections::HashMap<&PathBuf, usize> = canonical
+        .iter()
+        .enumerate()
+        .map(|(idx, file)| (file, idx))
+        .collect();
+    let mut listed = std::collections::HashSet::new();
+    let mut ordered = Vec::new();
+    for target in targets {
+        let canonical_target = canonicalize_for_comparison(target);
+        if target.is_file() || from_stdin {
+            let count = positions
+                .get(&canonical_target)
+                .map_or(0, |&idx| counts[idx].1);
+            ordered.push((target.clone(), count));
+            listed.insert(canonical_target);
+            continue;
+        }
+        if walked.is_empty() {
+            for (idx, file) in canonical.iter().enumerate() {
+                if file.starts_with(&canonical_target) && listed.insert(file.clone()) {
+                    ordered.push(counts[idx].clone());
+                }
+            }
+            continue;
+        }
+        for file in walked {
+            let canonical_file = canonicalize_for_comparison(file);
+            if !canonical_file.starts_with(&canonical_target) {
+                continue;
+            }
+            let count = positions
+                .get(&canonical_file)
+                .map(|&idx| counts[idx].clone());
+            if listed.insert(canonical_file) {
+                ordered.push(count.unwrap_or_else(|| (file.clone(), 0)));
+            }
+        }
+    }
+    // Files no target names, e.g. archive entries or files under a path that is not on disk
+    ordered.extend(
+        counts
+            .iter()
+            .zip(&canonical)
+            .filter(|(_, file)| !listed.contains(*file))
+            .map(|(count, _)| count.clone()),
+    );
+    ordered
+}
+
+/// Count results per file, preserving the order in which files first appear.
+/// Several regex matches on the same line count once, like grep.
+fn count_matches_per_file(results: &[cs_core::SearchResult]) -> Vec<(PathBuf, usize)> {
+    let mut counts: Vec<(PathBuf, usize)> = Vec::new();
+    let mut positions: std::collections::HashMap<PathBuf, usize> = std::collections::HashMap::new();
+    let mut seen_lines = std::collections::HashSet::new();
+    for result in results {
+        if !seen_lines.insert((&result.file, result.span.line_start)) {
+            continue;
+        }
+        match positions.get(&result.file) {
+            Some(&idx) => counts[idx].1 += 1,
+            None => {
+                positions.insert(result.file.clone(), counts.len());
+                counts.push((result.file.clone(), 1));
+            }
+        }
+    }
+    counts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2078,6 +5331,134 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_duration_units() {
+        use std::time::Duration;
+
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("5s").unwrap(), Duration::from_secs(5));
+        assert_eq!(parse_duration("2").unwrap(), Duration::from_secs(2));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("1m").unwrap(), Duration::from_secs(60));
+        assert!(parse_duration("5h").is_err());
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn test_modes_conflict_with_flags_they_ignore() {
+        // Flags each mode honours; any other flag given with it would be silently ignored
+        let servers = ["verbose", "debug", "log_json", "color", "no_daemon"];
+        let modes: [(&str, Vec<&str>); 5] = [
+            ("serve", [&servers[..], &["redact_paths"]].concat()),
+            ("lsp", servers.to_vec()),
+            ("rpc", servers.to_vec()),
+            ("daemon", servers[..4].to_vec()),
+            (
+                "tui",
+                vec!["pattern", "files", "group", "color", "no_daemon"],
+            ),
+        ];
+        let command = Cli::command();
+        for (mode, honoured) in modes {
+            let mode_arg = command
+                .get_arguments()
+                .find(|arg| arg.get_id() == mode)
+                .unwrap();
+            let conflicts: Vec<&str> = command
+                .get_arg_conflicts_with(mode_arg)
+                .iter()
+                .map(|arg| arg.get_id().as_str())
+                .collect();
+            for arg in command.get_arguments() {
+                let id = arg.get_id().as_str();
+                // The MCP options are --serve's own, and require it
+                if id == mode || honoured.contains(&id) || id.starts_with("mcp_") {
+                    continue;
+                }
+                assert!(
+                    conflicts.contains(&id),
+                    "--{} accepts {} without using it",
+                    mode,
+                    id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_matches_per_file_preserves_first_seen_order() {
+        let make = |file: &str, line: usize| cs_core::SearchResult {
+            file: PathBuf::from(file),
+            span: cs_core::Span::new(0, 1, line, line).unwrap(),
+            score: 1.0,
+            preview: String::new(),
+            lang: None,
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            signals: None,
+            explanation: None,
+        };
+        let results = vec![
+            make("b.rs", 1),
+            make("a.rs", 2),
+            make("b.rs", 5),
+            make("b.rs", 5),
+            make("b.rs", 9),
+        ];
+
+        let counts = count_matches_per_file(&results);
+        assert_eq!(
+            counts,
+            vec![(PathBuf::from("b.rs"), 3), (PathBuf::from("a.rs"), 1)]
+        );
+    }
+
+    #[test]
+    fn test_counts_in_input_order() {
+        let temp_dir = tempdir().unwrap();
+        let root = &temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join("dir")).unwrap();
+        for file in ["a.rs", "b.rs", "z.rs", "dir/c.rs", "dir/d.rs"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        // Regex results come sorted by path; named files keep the spelling they were given
+        let counts = vec![
+            (root.join("a.rs"), 1),
+            (root.join("b.rs"), 2),
+            (root.join("dir/c.rs"), 3),
+        ];
+        let typed_b = root.join("dir/../b.rs");
+        let targets = [
+            typed_b.clone(),
+            root.join("dir"),
+            root.join("z.rs"),
+            root.join("a.rs"),
+        ];
+
+        assert_eq!(
+            counts_in_input_order(&counts, &targets, &[], false),
+            vec![
+                (typed_b.clone(), 2),
+                (root.join("dir/c.rs"), 3),
+                (root.join("z.rs"), 0),
+                (root.join("a.rs"), 1),
+            ]
+        );
+        // Files walked under a named directory count 0 when they had no matches
+        let walked = ["dir/d.rs", "dir/c.rs", "a.rs", "b.rs", "z.rs"].map(|file| root.join(file));
+        assert_eq!(
+            counts_in_input_order(&counts, &targets, &walked, false),
+            vec![
+                (typed_b, 2),
+                (root.join("dir/d.rs"), 0),
+                (root.join("dir/c.rs"), 3),
+                (root.join("z.rs"), 0),
+                (root.join("a.rs"), 1),
+            ]
+        );
+    }
+
     #[test]
     fn test_highlight_regex_matches_with_valid_pattern() {
         let options = SearchOptions {
@@ -0,0 +1,179 @@
+//! Which paths and tools MCP clients may use. With allowed roots configured (`--mcp-root`, or
+//! `mcp-roots` in the user config), the `path` of every tool call and its `include_patterns`
+//! must resolve inside one of them, symlinks followed. Read-only mode refuses the tools that
+//! change an index; searches then use indexes as they are (`cs_engine::set_read_only`).
+
+use rmcp::ErrorData;
+use serde_json::{Map, Value};
+use std::path::{Component, Path, PathBuf};
+
+/// Tools that write to an index
+const WRITE_TOOLS: [&str; 2] = ["reindex", "clean_index"];
+
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicy {
+    /// Canonical directories tool calls may name; empty allows any path
+    roots: Vec<PathBuf>,
+    read_only: bool,
+}
+
+impl AccessPolicy {
+    pub fn new(roots: &[PathBuf], read_only: bool) -> anyhow::Result<Self> {
+        let roots = roots
+            .iter()
+            .map(|root| {
+                if !root.is_dir() {
+                    anyhow::bail!("Allowed MCP root {} is not a directory", root.display());
+                }
+                Ok(cs_core::paths::canonicalize(root))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { roots, read_only })
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Refuse a call to a tool disabled by read-only mode, or naming a path outside the roots
+    pub fn check_call(
+        &self,
+        tool: &str,
+        arguments: Option<&Map<String, Value>>,
+    ) -> Result<(), ErrorData> {
+        if self.read_only && WRITE_TOOLS.contains(&tool) {
+            return Err(ErrorData::invalid_request(
+                format!("{} is disabled: this server is read-only", tool),
+                None,
+            ));
+        }
+        let Some(path) = arguments
+            .and_then(|arguments| arguments.get("path"))
+            .and_then(Value::as_str)
+        else {
+            return Ok(());
+        };
+        let path = self.check_path(Path::new(path))?;
+
+        // Include patterns are relative to the searched directory, but may climb out of it
+        let base = if path.is_file() {
+            path.parent().unwrap_or(&path).to_path_buf()
+        } else {
+            path
+        };
+        let patterns = arguments
+            .and_then(|arguments| arguments.get("include_patterns"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .flat_map(|patterns| patterns.split(';'))
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty());
+        for pattern in patterns {
+            self.check_path(&literal_prefix(&base.join(pattern)))?;
+        }
+        Ok(())
+    }
+
+    /// `path` resolved, when it is inside one of the roots (or no roots are configured)
+    pub fn check_path(&self, path: &Path) -> Result<PathBuf, ErrorData> {
+        let resolved = std::path::absolute(path)
+            .map(|absolute| cs_core::paths::canonicalize(&absolute))
+            .unwrap_or_else(|_| path.to_path_buf());
+        if self.roots.is_empty()
+            || self
+                .roots
+                .iter()
+                .any(|root| cs_core::paths::relative_to(&resolved, root).is_some())
+        {
+            Ok(resolved)
+        } else {
+            Err(ErrorData::invalid_params(
+                format!(
+                    "{} is outside the directories this server may search",
+                    path.display()
+                ),
+                None,
+            ))
+        }
+    }
+}
+
+/// The part of a glob pattern before its first component with a wildcard
+fn literal_prefix(pattern: &Path) -> PathBuf {
+    pattern
+        .components()
+        .take_while(|component| match component {
+            Component::Normal(name) => !name.to_string_lossy().contains(['*', '?', '[', '{']),
+            _ => true,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn arguments(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_paths_outside_roots_are_refused() {
+        let temp_dir = tempdir().unwrap();
+        let allowed = temp_dir.path().join("allowed");
+        let other = temp_dir.path().join("other");
+        fs::create_dir_all(allowed.join("src")).unwrap();
+        fs::create_dir_all(&other).unwrap();
+        fs::write(allowed.join("src/lib.rs"), "fn f() {}").unwrap();
+        let policy = AccessPolicy::new(std::slice::from_ref(&allowed), false).unwrap();
+
+        let call = |path: &Path, include: Option<&str>| {
+            let mut call = json!({"query": "f", "path": path.to_string_lossy()});
+            if let Some(include) = include {
+                call["include_patterns"] = json!([include]);
+            }
+            policy.check_call("regex_search", Some(&arguments(call)))
+        };
+        assert!(call(&allowed, None).is_ok());
+        assert!(call(&allowed.join("src/lib.rs"), None).is_ok());
+        assert!(call(&allowed, Some("src/**/*.rs;*.md")).is_ok());
+        assert!(call(&other, None).is_err());
+        assert!(call(&allowed.join("../other"), None).is_err());
+        assert!(call(&allowed.join("missing/../../other"), None).is_err());
+        assert!(call(&allowed, Some("../other/*.rs")).is_err());
+        assert!(call(&allowed, Some("src/*.rs;/etc/passwd")).is_err());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&other, allowed.join("link")).unwrap();
+            assert!(call(&allowed.join("link"), None).is_err());
+        }
+
+        // Tools without a path are not restricted
+        assert!(policy.check_call("health_check", None).is_ok());
+        assert!(AccessPolicy::new(&[temp_dir.path().join("missing")], false).is_err());
+    }
+
+    #[test]
+    fn test_read_only_refuses_index_changes() {
+        let temp_dir = tempdir().unwrap();
+        let call = arguments(json!({"path": temp_dir.path().to_string_lossy()}));
+        let policy = AccessPolicy::new(&[], true).unwrap();
+        assert!(policy.check_call("reindex", Some(&call)).is_err());
+        assert!(policy.check_call("clean_index", Some(&call)).is_err());
+        assert!(policy.check_call("index_status", Some(&call)).is_ok());
+        assert!(
+            AccessPolicy::default()
+                .check_call("reindex", Some(&call))
+                .is_ok()
+        );
+    }
+}
@@ -31,12 +31,15 @@ impl McpContext {
         let default_search_options = SearchOptions {
             mode: cs_core::SearchMode::Semantic,
             query: String::new(),
+            extra_patterns: Vec::new(),
             path: cwd.clone(),
             top_k: Some(10),
             threshold: Some(0.6),
             case_insensitive: false,
             whole_word: false,
             fixed_string: false,
+            pcre2: false,
+            multiline: false,
             line_numbers: false,
             context_lines: 0,
             before_context_lines: 0,
@@ -47,15 +50,50 @@ impl McpContext {
             no_snippet: false,
             reindex: false,
             show_scores: true,
+            raw_scores: false,
             show_filenames: true,
+            group_by_file: false,
+            vimgrep: false,
+            syntax_highlight: false,
+            file_limits: Default::default(),
             files_with_matches: false,
             files_without_matches: false,
+            count: false,
+            first_match: false,
+            stats: None,
+            within: None,
+            files_ranked: None,
+            invert_match: false,
+            only_matching: false,
+            replace: None,
+            search_zip: false,
+            only_chunk_types: Vec::new(),
+            exclude_tests: false,
+            ephemeral: false,
+            hybrid_lexical: false,
+            expand_query: false,
+            explain: false,
+            languages: Vec::new(),
+            fusion_weights: Default::default(),
+            requester: Some("mcp".to_string()),
+            timeout: None,
             exclude_patterns: get_default_exclude_patterns(),
             include_patterns: Vec::new(),
             respect_gitignore: true,
             full_section: false,
             rerank: false,
             rerank_model: None,
+            rerank_candidates: None,
+            rerank_blend: None,
+            diversity: None,
+            sparse_weight: None,
+            stride_pooling: cs_core::StridePooling::default(),
+            dedupe: cs_core::Dedupe::default(),
+            negative_queries: Vec::new(),
+            negative_weight: None,
+            excluded_span: None,
+            offset: 0,
+            index_epoch: None,
             embedding_model: None,
             ast_pattern: None,
             ast_lang: None,
@@ -0,0 +1,184 @@
+//! Request budgets for the MCP server, so an agent calling searches in a loop cannot peg the
+//! CPU. Each client may start so many queries a minute (a token bucket refilling steadily,
+//! keyed by the client name from the handshake), at most so many queries run at once (later
+//! ones wait for a slot), and each search gets a soft time budget after which it returns the
+//! results found so far, marked `partial`.
+
+use rmcp::ErrorData;
+use rmcp::model::ErrorCode;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+/// Tools that run a query or an index update; the others answer from memory
+const LIMITED_TOOLS: [&str; 5] = [
+    "semantic_search",
+    "lexical_search",
+    "regex_search",
+    "hybrid_search",
+    "reindex",
+];
+
+/// JSON-RPC server error returned to a client over its rate limit
+const RATE_LIMITED: ErrorCode = ErrorCode(-32000);
+
+/// Budgets for limited tool calls; zero or `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    /// Calls each client may start per minute
+    pub rate_per_minute: u32,
+    /// Calls running at once, across clients
+    pub max_concurrent: usize,
+    /// Soft time limit for each search
+    pub query_timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct RequestLimiter {
+    limits: Limits,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    slots: Option<Arc<Semaphore>>,
+}
+
+impl RequestLimiter {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::new(HashMap::new()),
+            slots: (limits.max_concurrent > 0)
+                .then(|| Arc::new(Semaphore::new(limits.max_concurrent))),
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Let `client` call `tool`: refused over the rate limit, otherwise once a slot is free.
+    /// The call runs while the returned permit is held.
+    pub async fn admit(
+        &self,
+        tool: &str,
+        client: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Option<OwnedSemaphorePermit>, ErrorData> {
+        if !LIMITED_TOOLS.contains(&tool) {
+            return Ok(None);
+        }
+        if let Err(retry_after) = self.take(client, Instant::now()) {
+            return Err(ErrorData::new(
+                RATE_LIMITED,
+                format!(
+                    "Rate limit exceeded: at most {} searches per minute; retry in {:.1}s",
+                    self.limits.rate_per_minute,
+                    retry_after.as_secs_f64()
+                ),
+                Some(json!({ "retry_after_ms": retry_after.as_millis() as u64 })),
+            ));
+        }
+        let Some(slots) = &self.slots else {
+            return Ok(None);
+        };
+        tokio::select! {
+            permit = slots.clone().acquire_owned() => permit
+                .map(Some)
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None)),
+            _ = cancel.cancelled() => Err(ErrorData::internal_error(
+                "Request cancelled while waiting for a free search slot",
+                None,
+            )),
+        }
+    }
+
+    /// Take one call from `client`'s allowance, or the time until one is available
+    fn take(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let rate = self.limits.rate_per_minute;
+        if rate == 0 {
+            return Ok(());
+        }
+        // A full bucket allows a burst of a minute's calls, refilled at rate/60 per second
+        let capacity = f64::from(rate);
+        let per_second = capacity / 60.0;
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let refill = now.saturating_duration_since(bucket.updated).as_secs_f64() * per_second;
+        bucket.tokens = (bucket.tokens + refill).min(capacity);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_per_client() {
+        let limiter = RequestLimiter::new(Limits {
+            rate_per_minute: 2,
+            ..Limits::default()
+        });
+        let start = Instant::now();
+        assert!(limiter.take("a", start).is_ok());
+        assert!(limiter.take("a", start).is_ok());
+        let retry_after = limiter.take("a", start).unwrap_err();
+        assert_eq!(retry_after, Duration::from_secs(30));
+        assert!(limiter.take("b", start).is_ok());
+
+        // One call comes back every 30 seconds
+        assert!(limiter.take("a", start + Duration::from_secs(29)).is_err());
+        assert!(limiter.take("a", start + Duration::from_secs(31)).is_ok());
+        assert!(RequestLimiter::default().take("a", start).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_calls_wait_for_a_slot() {
+        let limiter = RequestLimiter::new(Limits {
+            max_concurrent: 1,
+            ..Limits::default()
+        });
+        let cancel = CancellationToken::new();
+        let permit = limiter
+            .admit("semantic_search", "a", &cancel)
+            .await
+            .unwrap();
+        assert!(permit.is_some());
+        // Tools that only read server state are never held up
+        assert!(
+            limiter
+                .admit("health_check", "a", &cancel)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let waiting = CancellationToken::new();
+        waiting.cancel();
+        assert!(limiter.admit("regex_search", "b", &waiting).await.is_err());
+        drop(permit);
+        assert!(
+            limiter
+                .admit("regex_search", "b", &cancel)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}
@@ -1,6 +1,8 @@
+pub mod access;
 pub mod cache;
 pub mod context;
 pub mod errors;
+pub mod limits;
 pub mod session;
 pub mod tools;
 
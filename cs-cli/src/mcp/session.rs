@@ -40,7 +40,7 @@ pub struct SearchSession {
     pub created_at: SystemTime,
     pub last_accessed: SystemTime,
     pub total_count: usize,
-    #[allow(dead_code)]
+    /// False when the search ran out of time and `results` are the best found before then
     pub search_completed: bool,
     pub search_params_hash: String,
 }
@@ -69,6 +69,8 @@ pub struct SearchPage {
     pub total_count: Option<usize>,
     pub has_more: bool,
     pub truncated: bool,
+    /// The search ran out of time; these are pages of the results found before then
+    pub partial: bool,
     pub next_cursor: Option<String>,
     pub current_page: usize,
     pub original_page_size: usize,
@@ -131,6 +133,7 @@ impl SessionManager {
         &self,
         search_options: SearchOptions,
         results: Vec<SearchResult>,
+        search_completed: bool,
     ) -> Result<Uuid, String> {
         let session_id = Uuid::new_v4();
         let now = SystemTime::now();
@@ -143,7 +146,7 @@ impl SessionManager {
             results,
             created_at: now,
             last_accessed: now,
-            search_completed: true,
+            search_completed,
             search_params_hash,
         };
 
@@ -198,6 +201,7 @@ impl SessionManager {
                 total_count: Some(total_results),
                 has_more: false,
                 truncated: false,
+                partial: !session.search_completed,
                 next_cursor: None,
                 current_page: (offset / config.page_size) + 1,
                 original_page_size: config.page_size,
@@ -239,6 +243,7 @@ impl SessionManager {
             total_count: Some(total_results),
             has_more,
             truncated: false, // TODO: Implement truncation logic
+            partial: !session.search_completed,
             next_cursor,
             current_page: (offset / config.page_size) + 1,
             original_page_size: config.page_size,
@@ -250,9 +255,12 @@ impl SessionManager {
         &self,
         search_options: SearchOptions,
         results: Vec<SearchResult>,
+        search_completed: bool,
         config: PaginationConfig,
     ) -> Result<SearchPage, String> {
-        let session_id = self.create_session(search_options, results).await?;
+        let session_id = self
+            .create_session(search_options, results, search_completed)
+            .await?;
         self.get_page(session_id, 0, config).await
     }
 
@@ -271,14 +279,27 @@ impl SessionManager {
             .as_secs()
             - parsed_cursor.timestamp;
 
+        // Results before the cursor were returned already; a fresh search resumes with offset
+        let expired = format!(
+            "Cursor has expired; repeat the search with offset={} to continue",
+            parsed_cursor.offset
+        );
         if cursor_age > self.session_ttl {
-            return Err("Cursor has expired".to_string());
+            return Err(expired);
         }
 
         // Use the original page size from the cursor to maintain consistency
         let mut adjusted_config = config;
         adjusted_config.page_size = parsed_cursor.original_page_size;
 
+        if !self
+            .sessions
+            .read()
+            .await
+            .contains_key(&parsed_cursor.session_id)
+        {
+            return Err(expired);
+        }
         self.get_page(
             parsed_cursor.session_id,
             parsed_cursor.offset,
@@ -441,12 +462,15 @@ mod tests {
         SearchOptions {
             mode: SearchMode::Semantic,
             query: "test query".to_string(),
+            extra_patterns: Vec::new(),
             path: PathBuf::from("/test/path"),
             top_k: Some(10),
             threshold: Some(0.5),
             case_insensitive: false,
             whole_word: false,
             fixed_string: false,
+            pcre2: false,
+            multiline: false,
             line_numbers: false,
             context_lines: 0,
             before_context_lines: 0,
@@ -457,15 +481,50 @@ mod tests {
             no_snippet: false,
             reindex: false,
             show_scores: true,
+            raw_scores: false,
             show_filenames: true,
+            group_by_file: false,
+            vimgrep: false,
+            syntax_highlight: false,
+            file_limits: Default::default(),
             files_with_matches: false,
             files_without_matches: false,
+            count: false,
+            first_match: false,
+            stats: None,
+            within: None,
+            files_ranked: None,
+            invert_match: false,
+            only_matching: false,
+            replace: None,
+            search_zip: false,
+            only_chunk_types: Vec::new(),
+            exclude_tests: false,
+            ephemeral: false,
+            hybrid_lexical: false,
+            expand_query: false,
+            explain: false,
+            languages: Vec::new(),
+            fusion_weights: Default::default(),
+            requester: Some("mcp".to_string()),
+            timeout: None,
             exclude_patterns: vec![],
             include_patterns: Vec::new(),
             respect_gitignore: true,
             full_section: false,
             rerank: false,
             rerank_model: None,
+            rerank_candidates: None,
+            rerank_blend: None,
+            diversity: None,
+            sparse_weight: None,
+            stride_pooling: cs_core::StridePooling::default(),
+            dedupe: cs_core::Dedupe::default(),
+            negative_queries: Vec::new(),
+            negative_weight: None,
+            excluded_span: None,
+            offset: 0,
+            index_epoch: None,
             embedding_model: None,
             ast_pattern: None,
             ast_lang: None,
@@ -490,6 +549,8 @@ mod tests {
                 symbol: None,
                 chunk_hash: None,
                 index_epoch: None,
+                signals: None,
+                explanation: None,
             })
             .collect()
     }
@@ -500,7 +561,10 @@ mod tests {
         let options = create_test_search_options();
         let results = create_test_results(10);
 
-        let session_id = manager.create_session(options, results).await.unwrap();
+        let session_id = manager
+            .create_session(options, results, true)
+            .await
+            .unwrap();
         assert!(!session_id.is_nil());
     }
 
@@ -512,7 +576,7 @@ mod tests {
         let config = PaginationConfig::default();
 
         let page = manager
-            .get_first_page(options, results, config)
+            .get_first_page(options, results, true, config)
             .await
             .unwrap();
 
@@ -531,7 +595,7 @@ mod tests {
 
         // Get first page
         let page1 = manager
-            .get_first_page(options, results, config.clone())
+            .get_first_page(options, results, true, config.clone())
             .await
             .unwrap();
         assert_eq!(page1.count, DEFAULT_PAGE_SIZE);
@@ -539,10 +603,21 @@ mod tests {
 
         // Get second page using cursor
         let cursor = page1.next_cursor.unwrap();
-        let page2 = manager.get_page_by_cursor(&cursor, config).await.unwrap();
+        let page2 = manager
+            .get_page_by_cursor(&cursor, config.clone())
+            .await
+            .unwrap();
         assert_eq!(page2.count, 25); // 75 - 50 = 25
         assert!(!page2.has_more);
         assert!(page2.next_cursor.is_none());
+
+        // Once the session is gone the error says where to resume
+        let restarted = SessionManager::default();
+        let error = restarted
+            .get_page_by_cursor(&cursor, config)
+            .await
+            .unwrap_err();
+        assert!(error.contains("offset=50"), "{}", error);
     }
 
     #[tokio::test]
@@ -561,7 +636,10 @@ mod tests {
         let options = create_test_search_options();
         let results = create_test_results(10);
 
-        let _session_id = manager.create_session(options, results).await.unwrap();
+        let _session_id = manager
+            .create_session(options, results, true)
+            .await
+            .unwrap();
 
         // Wait for session to expire
         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
@@ -586,7 +664,7 @@ mod tests {
         };
 
         let page = manager
-            .get_first_page(options, results, config)
+            .get_first_page(options, results, true, config)
             .await
             .unwrap();
 
@@ -3,8 +3,8 @@ use rmcp::handler::server::router::tool::ToolRouter;
 use rmcp::handler::server::tool::{ToolCallContext, ToolRoute};
 use rmcp::model::{
     CallToolRequestParam, CallToolResult, Content, Implementation, InitializeResult,
-    ListToolsResult, Meta, PaginatedRequestParam, ProgressNotificationParam, ProtocolVersion, Tool,
-    ToolsCapability,
+    ListToolsResult, Meta, PaginatedRequestParam, ProgressNotificationParam, ProtocolVersion,
+    RawContent, Tool, ToolsCapability,
 };
 use rmcp::service::RequestContext;
 use rmcp::transport;
@@ -14,15 +14,18 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Instant;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 use walkdir::WalkDir;
 
+use crate::mcp::access::AccessPolicy;
 use crate::mcp::context::McpContext;
+use crate::mcp::limits::{Limits, RequestLimiter};
 use crate::mcp::session::{PaginationConfig, SearchPage};
 use crate::path_utils::{build_include_patterns, expand_glob_patterns_with_base};
+use cs_core::redact::PathRedactor;
 use cs_core::{
     IncludePattern, SearchMode, SearchOptions, get_default_csignore_content,
     get_default_exclude_patterns,
@@ -31,6 +34,12 @@ use cs_core::{
 /// Default top_k for MCP when not specified by client
 /// Align with CLI default for semantic search to avoid heavy responses
 const DEFAULT_MCP_TOP_K: usize = 10;
+/// Minimum gap between progress notifications within one file
+const INDEXING_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+/// Context lines around a `get_chunk` result when the client does not ask
+const DEFAULT_CHUNK_CONTEXT_LINES: usize = 3;
+/// Largest file `get_chunk` returns whole when no span or hash is given
+const MAX_WHOLE_FILE_BYTES: usize = 256 * 1024;
 
 /// Filter out search results from missing files to prevent errors during result processing
 fn filter_valid_results(mut results: Vec<cs_core::SearchResult>) -> Vec<cs_core::SearchResult> {
@@ -38,6 +47,19 @@ fn filter_valid_results(mut results: Vec<cs_core::SearchResult>) -> Vec<cs_core:
     results
 }
 
+/// Byte offset of the start of each line; a trailing newline does not begin another line
+fn line_starts(content: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .filter(|&start| start < content.len())
+        .collect()
+}
+
+/// 1-based line holding byte `offset`
+fn line_of(line_starts: &[usize], offset: usize) -> usize {
+    line_starts.partition_point(|&start| start <= offset).max(1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,6 +96,65 @@ mod tests {
         assert!(saw_rs, "lib.rs should be included via glob");
         assert!(saw_ts, "file.ts should be included explicitly");
     }
+
+    #[test]
+    fn redacts_tool_results() {
+        let redactor =
+            PathRedactor::with_home(Path::new("/home/dev/app"), Some(PathBuf::from("/home/dev")));
+        let structured = json!({"file": {"path": "/home/dev/app/src/lib.rs"}});
+        let result = CallToolResult {
+            content: vec![
+                Content::text("Searched /home/dev/app for /home/ops/keys"),
+                Content::json(structured.clone()).unwrap(),
+            ],
+            structured_content: Some(structured),
+            is_error: Some(false),
+            meta: None,
+        };
+
+        let result = redact_tool_result(&redactor, result);
+        let texts: Vec<_> = result
+            .content
+            .iter()
+            .filter_map(|content| content.as_text().map(|text| text.text.clone()))
+            .collect();
+        assert_eq!(texts[0], "Searched . for /home/<user>/keys");
+        assert_eq!(texts[1], r#"{"file":{"path":"src/lib.rs"}}"#);
+        assert_eq!(
+            result.structured_content.unwrap()["file"]["path"],
+            "src/lib.rs"
+        );
+
+        let error = redact_error(
+            &redactor,
+            ErrorData::invalid_params("Cannot read /home/dev/notes.txt", None),
+        );
+        assert_eq!(error.message, "Cannot read ~/notes.txt");
+    }
+}
+
+/// Definitions in `content` for the `file_outline` tool and `cs --rpc`'s `outline`, in file
+/// order with their nesting depth
+pub fn outline_symbols(content: &str, language: cs_core::Language) -> anyhow::Result<Vec<Value>> {
+    // Without striding every symbol is one chunk, however large
+    let config = cs_chunk::ChunkConfig {
+        enable_striding: false,
+        ..Default::default()
+    };
+    let chunks = cs_chunk::chunk_text_with_config(content, Some(language), &config)?;
+    Ok(chunks
+        .iter()
+        .filter_map(|chunk| {
+            let kind = cs_engine::chunk_type_name(&chunk.chunk_type)?;
+            Some(json!({
+                "name": chunk.metadata.name,
+                "kind": kind,
+                "span": chunk.span,
+                "breadcrumb": chunk.metadata.breadcrumb,
+                "depth": chunk.metadata.ancestry.len(),
+            }))
+        })
+        .collect())
 }
 
 fn resolve_exclude_patterns(
@@ -151,6 +232,8 @@ pub struct SemanticSearchRequest {
     pub use_default_excludes: Option<bool>,
     pub rerank: Option<bool>,
     pub rerank_model: Option<String>,
+    /// MMR novelty weight (0..=1) so near-duplicate chunks do not fill the results
+    pub diversity: Option<f32>,
     pub case_insensitive: Option<bool>,
     pub whole_word: Option<bool>,
     pub fixed_string: Option<bool>,
@@ -158,6 +241,8 @@ pub struct SemanticSearchRequest {
     pub after_context_lines: Option<usize>,
     // Pagination parameters
     pub cursor: Option<String>,
+    /// Ranked results to skip before the first page, e.g. to resume after a cursor expired
+    pub offset: Option<usize>,
     pub page_size: Option<usize>,
     pub include_snippet: Option<bool>,
     pub snippet_length: Option<usize>,
@@ -178,6 +263,8 @@ pub struct RegexSearchRequest {
     pub fixed_string: Option<bool>,
     // Pagination parameters
     pub cursor: Option<String>,
+    /// Ranked results to skip before the first page, e.g. to resume after a cursor expired
+    pub offset: Option<usize>,
     pub page_size: Option<usize>,
     pub include_snippet: Option<bool>,
     pub snippet_length: Option<usize>,
@@ -195,6 +282,8 @@ pub struct HybridSearchRequest {
     pub use_default_excludes: Option<bool>,
     pub rerank: Option<bool>,
     pub rerank_model: Option<String>,
+    /// Also fuse BM25 lexical hits into the ranking (helps natural-language phrase queries)
+    pub include_lexical: Option<bool>,
     pub case_insensitive: Option<bool>,
     pub whole_word: Option<bool>,
     pub fixed_string: Option<bool>,
@@ -202,6 +291,8 @@ pub struct HybridSearchRequest {
     pub after_context_lines: Option<usize>,
     // Pagination parameters
     pub cursor: Option<String>,
+    /// Ranked results to skip before the first page, e.g. to resume after a cursor expired
+    pub offset: Option<usize>,
     pub page_size: Option<usize>,
     pub include_snippet: Option<bool>,
     pub snippet_length: Option<usize>,
@@ -218,6 +309,7 @@ pub struct LexicalSearchRequest {
     pub exclude_patterns: Option<Vec<String>>,
     pub respect_gitignore: Option<bool>,
     pub use_default_excludes: Option<bool>,
+    /// Has no effect: BM25 matching always ignores case
     pub case_insensitive: Option<bool>,
     pub whole_word: Option<bool>,
     pub fixed_string: Option<bool>,
@@ -225,6 +317,8 @@ pub struct LexicalSearchRequest {
     pub after_context_lines: Option<usize>,
     // Pagination parameters
     pub cursor: Option<String>,
+    /// Ranked results to skip before the first page, e.g. to resume after a cursor expired
+    pub offset: Option<usize>,
     pub page_size: Option<usize>,
     pub include_snippet: Option<bool>,
     pub snippet_length: Option<usize>,
@@ -234,12 +328,44 @@ pub struct LexicalSearchRequest {
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct IndexStatusRequest {
     pub path: String,
+    /// Add per-language, chunk type and model breakdowns, stale files and an integrity check
+    pub detailed: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct ReindexRequest {
     pub path: String,
     pub force: Option<bool>,
+    /// Embedding model for a new index or a forced rebuild; defaults to the index's model
+    pub model: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CleanIndexRequest {
+    pub path: String,
+    /// Only drop entries for files that were deleted or are now excluded, keeping the rest
+    pub orphans_only: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct FileOutlineRequest {
+    pub path: String,
+}
+
+/// Locate a chunk by span (byte or 1-based line range from a search result) or by `chunk_hash`;
+/// with neither, the whole file is returned
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetChunkRequest {
+    pub path: String,
+    pub byte_start: Option<usize>,
+    pub byte_end: Option<usize>,
+    pub line_start: Option<usize>,
+    pub line_end: Option<usize>,
+    /// `chunk_hash` of a search result or an earlier `get_chunk` response; an unambiguous prefix
+    /// is enough
+    pub chunk_hash: Option<String>,
+    /// Lines of context before and after the chunk (default 3)
+    pub context_lines: Option<usize>,
 }
 
 impl PaginationParams for SemanticSearchRequest {
@@ -267,6 +393,7 @@ impl PaginationParams for SemanticSearchRequest {
             "threshold": self.threshold.unwrap_or(0.6),
             "rerank": self.rerank.unwrap_or(false),
             "rerank_model": self.rerank_model,
+            "diversity": self.diversity,
             "case_insensitive": self.case_insensitive.unwrap_or(false),
             "whole_word": self.whole_word.unwrap_or(false),
             "fixed_string": self.fixed_string.unwrap_or(false),
@@ -340,9 +467,10 @@ impl PaginationParams for HybridSearchRequest {
     fn get_search_params(&self) -> serde_json::Value {
         json!({
             "top_k": self.top_k,
-            "threshold": self.threshold.unwrap_or(0.02),
+            "threshold": self.threshold.unwrap_or(0.6),
             "rerank": self.rerank.unwrap_or(false),
             "rerank_model": self.rerank_model,
+            "include_lexical": self.include_lexical.unwrap_or(false),
             "case_insensitive": self.case_insensitive.unwrap_or(false),
             "whole_word": self.whole_word.unwrap_or(false),
             "fixed_string": self.fixed_string.unwrap_or(false),
@@ -402,6 +530,14 @@ impl PaginationParams for LexicalSearchRequest {
 pub struct CcMcpServer {
     context: McpContext,
     tool_router: ToolRouter<Self>,
+    /// Client name from the MCP handshake, recorded as the requester in audit logs
+    client_name: Arc<OnceLock<String>>,
+    /// Rewrites paths in every tool result and error (`--redact-paths`)
+    redactor: Option<Arc<PathRedactor>>,
+    /// Directories tool calls may name, and whether tools may change an index
+    access: Arc<AccessPolicy>,
+    /// Per-client rate limit, concurrent-query cap and query time budget
+    limiter: Arc<RequestLimiter>,
 }
 
 impl ServerHandler for CcMcpServer {
@@ -448,6 +584,7 @@ All search tools support:
 - **include_snippet** (default: true) - Include code snippets
 - **snippet_length** (default: 500) - Max characters per snippet
 - **cursor** - Opaque cursor for subsequent pages
+- **offset** - Ranked results to skip; resumes a search after its cursor expired
 - **context_lines** - Lines of context (semantic/hybrid only)
 
 ## Examples:
@@ -464,13 +601,33 @@ All search tools support:
         request: CallToolRequestParam,
         context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
+        if let Some(peer_info) = context.peer.peer_info() {
+            let _ = self.client_name.set(peer_info.client_info.name.clone());
+        }
+        let allowed = self
+            .access
+            .check_call(&request.name, request.arguments.as_ref());
+        let client = self.client_name.get().map_or("mcp", String::as_str);
+        let admitted = match allowed {
+            Ok(()) => self.limiter.admit(&request.name, client, &context.ct).await,
+            Err(error) => Err(error),
+        };
         let tool_context = ToolCallContext::new(self, request, context);
-        if let Some(route) = self.tool_router.map.get(&tool_context.name) {
-            (route.call)(tool_context).await
-        } else {
-            Err(ErrorData::method_not_found::<
-                rmcp::model::CallToolRequestMethod,
-            >())
+        let result = match admitted {
+            Err(error) => Err(error),
+            // The permit is held until the tool returns, counting against the concurrent cap
+            Ok(_permit) => match self.tool_router.map.get(&tool_context.name) {
+                Some(route) => (route.call)(tool_context).await,
+                None => Err(ErrorData::method_not_found::<
+                    rmcp::model::CallToolRequestMethod,
+                >()),
+            },
+        };
+        match &self.redactor {
+            Some(redactor) => result
+                .map(|result| redact_tool_result(redactor, result))
+                .map_err(|error| redact_error(redactor, error)),
+            None => result,
         }
     }
 
@@ -492,6 +649,33 @@ All search tools support:
     }
 }
 
+/// Tool result with paths redacted in its text, the JSON in it and its structured content
+fn redact_tool_result(redactor: &PathRedactor, mut result: CallToolResult) -> CallToolResult {
+    for content in &mut result.content {
+        if let RawContent::Text(text) = &mut content.raw {
+            text.text = match serde_json::from_str::<Value>(&text.text) {
+                Ok(mut value) if value.is_object() || value.is_array() => {
+                    redactor.json(&mut value);
+                    value.to_string()
+                }
+                _ => redactor.text(&text.text),
+            };
+        }
+    }
+    if let Some(structured) = &mut result.structured_content {
+        redactor.json(structured);
+    }
+    result
+}
+
+fn redact_error(redactor: &PathRedactor, mut error: ErrorData) -> ErrorData {
+    error.message = redactor.text(&error.message).into();
+    if let Some(data) = &mut error.data {
+        redactor.json(data);
+    }
+    error
+}
+
 impl CcMcpServer {
     pub fn new(cwd: PathBuf) -> Result<Self> {
         let context = McpContext::new(cwd)?;
@@ -499,9 +683,72 @@ impl CcMcpServer {
         Ok(Self {
             context,
             tool_router,
+            client_name: Arc::new(OnceLock::new()),
+            redactor: None,
+            access: Arc::new(AccessPolicy::default()),
+            limiter: Arc::new(RequestLimiter::default()),
         })
     }
 
+    /// Rate-limit searches per client, cap how many run at once and budget their time
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limiter = Arc::new(RequestLimiter::new(limits));
+        self
+    }
+
+    /// Restrict tool calls to the policy's directories, and to reading when it is read-only
+    pub fn with_access(mut self, access: AccessPolicy) -> Self {
+        self.access = Arc::new(access);
+        self
+    }
+
+    /// Redact paths in tool results: relative to the redactor's root, user names masked
+    pub fn with_redactor(mut self, redactor: PathRedactor) -> Self {
+        self.redactor = Some(Arc::new(redactor));
+        self
+    }
+
+    /// Audit-log requester tag: `mcp:<client name>`, or `mcp` before the client identified itself
+    fn requester(&self) -> String {
+        match self.client_name.get() {
+            Some(name) => format!("mcp:{}", name),
+            None => "mcp".to_string(),
+        }
+    }
+
+    /// Progress notifications for indexing done by a tool call: `progress` counts files (with
+    /// the current file's embedded fraction), the message carries throughput and ETA. Chunk
+    /// updates are throttled so large files do not flood the client.
+    fn indexing_progress_notifier(
+        meta: &Option<Meta>,
+        peer: &Option<Peer<RoleServer>>,
+    ) -> Option<cs_engine::DetailedIndexingProgressCallback> {
+        let token = meta.as_ref()?.get_progress_token()?;
+        let peer = peer.clone()?;
+        let last_sent = Mutex::new(None::<Instant>);
+        Some(Box::new(move |progress: cs_index::EmbeddingProgress| {
+            let mut last_sent = last_sent.lock().unwrap();
+            let throttled =
+                last_sent.is_some_and(|sent| sent.elapsed() < INDEXING_PROGRESS_INTERVAL);
+            if throttled && progress.chunk_index > 0 {
+                return;
+            }
+            *last_sent = Some(Instant::now());
+
+            let file_fraction = progress.chunk_index as f64 / progress.total_chunks.max(1) as f64;
+            let notification = ProgressNotificationParam {
+                progress_token: token.clone(),
+                progress: progress.overall.files_done as f64 + file_fraction,
+                total: Some(progress.overall.files_total as f64),
+                message: Some(format!("{} · {}", progress.file_name, progress.overall)),
+            };
+            let peer = peer.clone();
+            tokio::spawn(async move {
+                let _ = peer.notify_progress(notification).await;
+            });
+        }))
+    }
+
     /// Extract pagination configuration from request parameters
     fn extract_pagination_config(
         page_size: Option<usize>,
@@ -540,7 +787,8 @@ impl CcMcpServer {
                         "line_start": result.span.line_start,
                         "line_end": result.span.line_end
                     },
-                    "content": result.preview
+                    "content": result.preview,
+                    "chunk_hash": result.chunk_hash
                 },
                 "type": match_type
             });
@@ -569,7 +817,8 @@ impl CcMcpServer {
                 "count": page.count,
                 "total_count": page.total_count,
                 "has_more": page.has_more,
-                "truncated": page.truncated
+                "truncated": page.truncated,
+                "partial": page.partial
             },
             "pagination": {
                 "next_cursor": page.next_cursor,
@@ -578,6 +827,7 @@ impl CcMcpServer {
             },
             "metadata": {
                 "search_time_ms": search_time_ms,
+                "index_epoch": page.matches.first().and_then(|result| result.index_epoch),
                 "index_stats": null  // TODO: Add index information
             }
         })
@@ -629,6 +879,9 @@ impl CcMcpServer {
         router.add_route(Self::hybrid_search_route());
         router.add_route(Self::index_status_route());
         router.add_route(Self::reindex_route());
+        router.add_route(Self::clean_index_route());
+        router.add_route(Self::get_chunk_route());
+        router.add_route(Self::file_outline_route());
         router.add_route(Self::default_csignore_route());
         router
     }
@@ -693,13 +946,23 @@ impl CcMcpServer {
 
         ToolRoute::new_dyn(tool, |context: ToolCallContext<'_, CcMcpServer>| {
             Box::pin(async move {
+                let limits = context.service.limiter.limits();
                 let status_data = json!({
                     "status": "healthy",
                     "server": "cs",
                     "version": env!("CARGO_PKG_VERSION"),
                     "protocol": "mcp",
                     "timestamp": chrono::Utc::now().to_rfc3339(),
-                    "cwd": context.service.context.cwd.to_string_lossy()
+                    "cwd": context.service.context.cwd.to_string_lossy(),
+                    "allowed_roots": context.service.access.roots(),
+                    "read_only": context.service.access.read_only(),
+                    "limits": {
+                        "rate_per_minute": limits.rate_per_minute,
+                        "max_concurrent": limits.max_concurrent,
+                        "query_timeout_ms": limits
+                            .query_timeout
+                            .map(|timeout| timeout.as_millis() as u64)
+                    }
                 });
 
                 let summary = format!(
@@ -745,9 +1008,10 @@ impl CcMcpServer {
 
                 let service: &CcMcpServer = context.service;
                 let meta = context.request_context.meta.clone();
+                let cancel = context.request_context.ct.clone();
                 let peer = context.request_context.peer;
                 match service
-                    .handle_semantic_search(request, Some(meta), Some(peer))
+                    .handle_semantic_search_cancellable(request, Some(meta), Some(peer), &cancel)
                     .await
                 {
                     Ok((summary, result)) => Ok(CallToolResult {
@@ -851,7 +1115,7 @@ impl CcMcpServer {
             name: "hybrid_search".into(),
             title: Some("Hybrid Search".into()),
             description: Some(
-                "Hybrid search combining regex and semantic search with RRF ranking".into(),
+                "Hybrid search combining regex and semantic search (plus BM25 lexical with include_lexical) with RRF ranking".into(),
             ),
             input_schema: Arc::new(input_schema.as_object().unwrap().clone()),
             output_schema: None,
@@ -868,7 +1132,11 @@ impl CcMcpServer {
                     })?;
 
                 let service: &CcMcpServer = context.service;
-                match service.handle_hybrid_search(request).await {
+                let cancel = context.request_context.ct.clone();
+                match service
+                    .handle_hybrid_search_cancellable(request, &cancel)
+                    .await
+                {
                     Ok((summary, result)) => Ok(CallToolResult {
                         content: vec![
                             Content::text(summary),
@@ -935,7 +1203,7 @@ impl CcMcpServer {
         let tool = Tool {
             name: "reindex".into(),
             title: Some("Reindex Directory".into()),
-            description: Some("Force reindexing of a directory with progress tracking".into()),
+            description: Some("Update or rebuild the index of a directory, streaming files done, throughput and ETA as progress notifications".into()),
             input_schema: Arc::new(input_schema.as_object().unwrap().clone()),
             output_schema: None,
             annotations: None,
@@ -952,9 +1220,10 @@ impl CcMcpServer {
 
                 let service: &CcMcpServer = context.service;
                 let meta = context.request_context.meta.clone();
+                let cancel = context.request_context.ct.clone();
                 let peer = context.request_context.peer;
                 match service
-                    .handle_reindex(request, Some(meta), Some(peer))
+                    .handle_reindex(request, Some(meta), Some(peer), &cancel)
                     .await
                 {
                     Ok((summary, result)) => Ok(CallToolResult {
@@ -973,6 +1242,123 @@ impl CcMcpServer {
         })
     }
 
+    fn clean_index_route() -> ToolRoute<Self> {
+        let schema = schemars::schema_for!(CleanIndexRequest);
+        let input_schema = serde_json::to_value(schema).unwrap();
+        let tool = Tool {
+            name: "clean_index".into(),
+            title: Some("Clean Index".into()),
+            description: Some("Remove the index of a directory, or with orphans_only just the entries of deleted or excluded files".into()),
+            input_schema: Arc::new(input_schema.as_object().unwrap().clone()),
+            output_schema: None,
+            annotations: None,
+            icons: None,
+        };
+
+        ToolRoute::new_dyn(tool, |context: ToolCallContext<'_, CcMcpServer>| {
+            Box::pin(async move {
+                let arguments = context.arguments.clone().unwrap_or_default();
+                let request: CleanIndexRequest =
+                    serde_json::from_value(serde_json::Value::Object(arguments)).map_err(|e| {
+                        rmcp::ErrorData::invalid_params(format!("Invalid parameters: {}", e), None)
+                    })?;
+
+                let service: &CcMcpServer = context.service;
+                match service.handle_clean_index(request).await {
+                    Ok((summary, result)) => Ok(CallToolResult {
+                        content: vec![
+                            Content::text(summary),
+                            Content::json(result.clone())
+                                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?,
+                        ],
+                        structured_content: Some(result),
+                        is_error: Some(false),
+                        meta: None,
+                    }),
+                    Err(e) => Err(e),
+                }
+            })
+        })
+    }
+
+    fn get_chunk_route() -> ToolRoute<Self> {
+        let schema = schemars::schema_for!(GetChunkRequest);
+        let input_schema = serde_json::to_value(schema).unwrap();
+        let tool = Tool {
+            name: "get_chunk".into(),
+            title: Some("Get Chunk".into()),
+            description: Some("Fetch the full text of a search result's chunk with surrounding context and chunk metadata, by path plus span or chunk hash".into()),
+            input_schema: Arc::new(input_schema.as_object().unwrap().clone()),
+            output_schema: None,
+            annotations: None,
+            icons: None,
+        };
+
+        ToolRoute::new_dyn(tool, |context: ToolCallContext<'_, CcMcpServer>| {
+            Box::pin(async move {
+                let arguments = context.arguments.clone().unwrap_or_default();
+                let request: GetChunkRequest =
+                    serde_json::from_value(serde_json::Value::Object(arguments)).map_err(|e| {
+                        rmcp::ErrorData::invalid_params(format!("Invalid parameters: {}", e), None)
+                    })?;
+
+                let service: &CcMcpServer = context.service;
+                match service.handle_get_chunk(request) {
+                    Ok((summary, result)) => Ok(CallToolResult {
+                        content: vec![
+                            Content::text(summary),
+                            Content::json(result.clone())
+                                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?,
+                        ],
+                        structured_content: Some(result),
+                        is_error: Some(false),
+                        meta: None,
+                    }),
+                    Err(e) => Err(e),
+                }
+            })
+        })
+    }
+
+    fn file_outline_route() -> ToolRoute<Self> {
+        let schema = schemars::schema_for!(FileOutlineRequest);
+        let input_schema = serde_json::to_value(schema).unwrap();
+        let tool = Tool {
+            name: "file_outline".into(),
+            title: Some("File Outline".into()),
+            description: Some("List the functions, classes, methods and modules of a source file with their names, nesting and spans".into()),
+            input_schema: Arc::new(input_schema.as_object().unwrap().clone()),
+            output_schema: None,
+            annotations: None,
+            icons: None,
+        };
+
+        ToolRoute::new_dyn(tool, |context: ToolCallContext<'_, CcMcpServer>| {
+            Box::pin(async move {
+                let arguments = context.arguments.clone().unwrap_or_default();
+                let request: FileOutlineRequest =
+                    serde_json::from_value(serde_json::Value::Object(arguments)).map_err(|e| {
+                        rmcp::ErrorData::invalid_params(format!("Invalid parameters: {}", e), None)
+                    })?;
+
+                let service: &CcMcpServer = context.service;
+                match service.handle_file_outline(request) {
+                    Ok((summary, result)) => Ok(CallToolResult {
+                        content: vec![
+                            Content::text(summary),
+                            Content::json(result.clone())
+                                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?,
+                        ],
+                        structured_content: Some(result),
+                        is_error: Some(false),
+                        meta: None,
+                    }),
+                    Err(e) => Err(e),
+                }
+            })
+        })
+    }
+
     pub async fn run(&self) -> Result<()> {
         info!("Starting cc MCP server");
 
@@ -982,11 +1368,25 @@ impl CcMcpServer {
         Ok(())
     }
 
+    #[allow(dead_code)] // Library API; tool calls go through the cancellable variant
     pub async fn handle_semantic_search(
         &self,
         request: SemanticSearchRequest,
         meta: Option<Meta>,
         peer: Option<Peer<RoleServer>>,
+    ) -> Result<(String, Value), ErrorData> {
+        self.handle_semantic_search_cancellable(request, meta, peer, &CancellationToken::new())
+            .await
+    }
+
+    /// Semantic search that stops, leaving the index consistent, when the client cancels the
+    /// request
+    async fn handle_semantic_search_cancellable(
+        &self,
+        request: SemanticSearchRequest,
+        meta: Option<Meta>,
+        peer: Option<Peer<RoleServer>>,
+        cancel: &CancellationToken,
     ) -> Result<(String, Value), ErrorData> {
         // Handle pagination via cursor
         if let Some(cursor) = &request.cursor {
@@ -1040,34 +1440,8 @@ impl CcMcpServer {
             request.context_lines,
         );
 
-        // Create progress callback for indexing if we have a progress token and peer
-        let indexing_progress_callback = if let (Some(meta), Some(peer)) = (&meta, &peer) {
-            if let Some(progress_token) = meta.get_progress_token() {
-                let token = progress_token.clone();
-                let peer = peer.clone();
-                let step_count = Arc::new(AtomicUsize::new(0));
-                Some(Box::new(move |message: &str| {
-                    let token = token.clone();
-                    let peer = peer.clone();
-                    let message = message.to_string();
-                    let current_step = step_count.fetch_add(1, Ordering::SeqCst) + 1;
-                    tokio::spawn(async move {
-                        let _ = peer
-                            .notify_progress(ProgressNotificationParam {
-                                progress_token: token,
-                                progress: current_step as f64,
-                                total: None, // Unknown total for indexing
-                                message: Some(message),
-                            })
-                            .await;
-                    });
-                }) as cs_engine::IndexingProgressCallback)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        // Report indexing progress if the client asked for it with a progress token
+        let indexing_progress_callback = Self::indexing_progress_notifier(&meta, &peer);
 
         let include_snippet = request.include_snippet.unwrap_or(true);
         let context_lines = request.context_lines.unwrap_or(0);
@@ -1077,12 +1451,15 @@ impl CcMcpServer {
         let options = SearchOptions {
             mode: SearchMode::Semantic,
             query,
+            extra_patterns: Vec::new(),
             path: path_buf,
             top_k: top_k.or(Some(DEFAULT_MCP_TOP_K)),
             threshold: threshold.or(Some(0.6)),
             case_insensitive: request.case_insensitive.unwrap_or(false),
             whole_word: request.whole_word.unwrap_or(false),
             fixed_string: request.fixed_string.unwrap_or(false),
+            pcre2: false,
+            multiline: false,
             line_numbers: false,
             context_lines,
             before_context_lines,
@@ -1093,15 +1470,50 @@ impl CcMcpServer {
             no_snippet: !include_snippet,
             reindex: false,
             show_scores: true,
+            raw_scores: false,
             show_filenames: true,
+            group_by_file: false,
+            vimgrep: false,
+            syntax_highlight: false,
+            file_limits: Default::default(),
             files_with_matches: false,
             files_without_matches: false,
+            count: false,
+            first_match: false,
+            stats: None,
+            within: None,
+            files_ranked: None,
+            invert_match: false,
+            only_matching: false,
+            replace: None,
+            search_zip: false,
+            only_chunk_types: Vec::new(),
+            exclude_tests: false,
+            ephemeral: false,
+            hybrid_lexical: false,
+            expand_query: false,
+            explain: false,
+            languages: Vec::new(),
+            fusion_weights: Default::default(),
+            requester: Some(self.requester()),
+            timeout: self.limiter.limits().query_timeout,
             exclude_patterns,
             include_patterns,
             respect_gitignore,
             full_section: false,
             rerank: request.rerank.unwrap_or(false),
             rerank_model: request.rerank_model.clone(),
+            rerank_candidates: None,
+            rerank_blend: None,
+            diversity: request.diversity.map(|diversity| diversity.clamp(0.0, 1.0)),
+            sparse_weight: None,
+            stride_pooling: cs_core::StridePooling::default(),
+            dedupe: cs_core::Dedupe::default(),
+            negative_queries: Vec::new(),
+            negative_weight: None,
+            excluded_span: None,
+            offset: request.offset.unwrap_or(0),
+            index_epoch: None,
             embedding_model: None,
             ast_pattern: None,
             ast_lang: None,
@@ -1116,11 +1528,12 @@ impl CcMcpServer {
         let mut indexing_progress_callback = indexing_progress_callback;
         let mut effective_mode: Option<String> = None;
         let started = Instant::now();
-        let search_results = match cs_engine::search_enhanced_with_indexing_progress(
+        let search_results = match cs_engine::search_enhanced_cancellable(
             &options,
             None,
-            indexing_progress_callback.take(),
             None,
+            indexing_progress_callback.take(),
+            cancel,
         )
         .await
         {
@@ -1134,11 +1547,12 @@ impl CcMcpServer {
                     );
                     let mut reindex_options = options.clone();
                     reindex_options.reindex = true;
-                    match cs_engine::search_enhanced_with_indexing_progress(
+                    match cs_engine::search_enhanced_cancellable(
                         &reindex_options,
                         None,
                         None,
                         None,
+                        cancel,
                     )
                     .await
                     {
@@ -1149,11 +1563,12 @@ impl CcMcpServer {
                             let mut fallback_options = options.clone();
                             fallback_options.mode = SearchMode::Lexical;
                             fallback_options.reindex = true;
-                            match cs_engine::search_enhanced_with_indexing_progress(
+                            match cs_engine::search_enhanced_cancellable(
                                 &fallback_options,
                                 None,
                                 None,
                                 None,
+                                cancel,
                             )
                             .await
                             {
@@ -1191,6 +1606,7 @@ impl CcMcpServer {
             .get_first_page(
                 options,
                 filter_valid_results(search_results.matches),
+                !search_results.partial,
                 config,
             )
             .await
@@ -1290,12 +1706,15 @@ impl CcMcpServer {
         let options = SearchOptions {
             mode: SearchMode::Lexical,
             query,
+            extra_patterns: Vec::new(),
             path: path_buf,
             top_k,
             threshold,
             case_insensitive: request.case_insensitive.unwrap_or(false),
             whole_word: request.whole_word.unwrap_or(false),
             fixed_string: request.fixed_string.unwrap_or(false),
+            pcre2: false,
+            multiline: false,
             line_numbers: false,
             context_lines,
             before_context_lines,
@@ -1306,15 +1725,50 @@ impl CcMcpServer {
             no_snippet: !include_snippet,
             reindex: false,
             show_scores: true,
+            raw_scores: false,
             show_filenames: true,
+            group_by_file: false,
+            vimgrep: false,
+            syntax_highlight: false,
+            file_limits: Default::default(),
             files_with_matches: false,
             files_without_matches: false,
+            count: false,
+            first_match: false,
+            stats: None,
+            within: None,
+            files_ranked: None,
+            invert_match: false,
+            only_matching: false,
+            replace: None,
+            search_zip: false,
+            only_chunk_types: Vec::new(),
+            exclude_tests: false,
+            ephemeral: false,
+            hybrid_lexical: false,
+            expand_query: false,
+            explain: false,
+            languages: Vec::new(),
+            fusion_weights: Default::default(),
+            requester: Some(self.requester()),
+            timeout: self.limiter.limits().query_timeout,
             exclude_patterns,
             include_patterns,
             respect_gitignore,
             full_section: false,
             rerank: false,
             rerank_model: None,
+            rerank_candidates: None,
+            rerank_blend: None,
+            diversity: None,
+            sparse_weight: None,
+            stride_pooling: cs_core::StridePooling::default(),
+            dedupe: cs_core::Dedupe::default(),
+            negative_queries: Vec::new(),
+            negative_weight: None,
+            excluded_span: None,
+            offset: request.offset.unwrap_or(0),
+            index_epoch: None,
             embedding_model: None,
             ast_pattern: None,
             ast_lang: None,
@@ -1338,6 +1792,7 @@ impl CcMcpServer {
             .get_first_page(
                 options,
                 filter_valid_results(search_results.matches),
+                !search_results.partial,
                 config,
             )
             .await
@@ -1431,12 +1886,15 @@ impl CcMcpServer {
         let options = SearchOptions {
             mode: SearchMode::Regex,
             query: pattern,
+            extra_patterns: Vec::new(),
             path: path_buf,
             top_k: None,     // No limit for regex search
             threshold: None, // No threshold for regex search
             case_insensitive: ignore_case.unwrap_or(false),
             whole_word: request.whole_word.unwrap_or(false),
             fixed_string: request.fixed_string.unwrap_or(false),
+            pcre2: false,
+            multiline: false,
             line_numbers: true,
             context_lines,
             before_context_lines: context_lines,
@@ -1447,15 +1905,50 @@ impl CcMcpServer {
             no_snippet: !include_snippet,
             reindex: false,
             show_scores: false, // No scores for regex search
+            raw_scores: false,
             show_filenames: true,
+            group_by_file: false,
+            vimgrep: false,
+            syntax_highlight: false,
+            file_limits: Default::default(),
             files_with_matches: false,
             files_without_matches: false,
+            count: false,
+            first_match: false,
+            stats: None,
+            within: None,
+            files_ranked: None,
+            invert_match: false,
+            only_matching: false,
+            replace: None,
+            search_zip: false,
+            only_chunk_types: Vec::new(),
+            exclude_tests: false,
+            ephemeral: false,
+            hybrid_lexical: false,
+            expand_query: false,
+            explain: false,
+            languages: Vec::new(),
+            fusion_weights: Default::default(),
+            requester: Some(self.requester()),
+            timeout: self.limiter.limits().query_timeout,
             exclude_patterns,
             include_patterns,
             respect_gitignore,
             full_section: false,
             rerank: false,
             rerank_model: None,
+            rerank_candidates: None,
+            rerank_blend: None,
+            diversity: None,
+            sparse_weight: None,
+            stride_pooling: cs_core::StridePooling::default(),
+            dedupe: cs_core::Dedupe::default(),
+            negative_queries: Vec::new(),
+            negative_weight: None,
+            excluded_span: None,
+            offset: request.offset.unwrap_or(0),
+            index_epoch: None,
             embedding_model: None,
             ast_pattern: None,
             ast_lang: None,
@@ -1484,6 +1977,7 @@ impl CcMcpServer {
             .get_first_page(
                 options,
                 filter_valid_results(search_results.matches),
+                !search_results.partial,
                 config,
             )
             .await
@@ -1509,9 +2003,20 @@ impl CcMcpServer {
         Ok((summary, structured_result))
     }
 
+    #[allow(dead_code)] // Library API; tool calls go through the cancellable variant
     pub async fn handle_hybrid_search(
         &self,
         request: HybridSearchRequest,
+    ) -> Result<(String, Value), ErrorData> {
+        self.handle_hybrid_search_cancellable(request, &CancellationToken::new())
+            .await
+    }
+
+    /// Hybrid search that stops when the client cancels the request
+    async fn handle_hybrid_search_cancellable(
+        &self,
+        request: HybridSearchRequest,
+        cancel: &CancellationToken,
     ) -> Result<(String, Value), ErrorData> {
         // Handle pagination via cursor
         if let Some(cursor) = &request.cursor {
@@ -1572,12 +2077,15 @@ impl CcMcpServer {
         let options = SearchOptions {
             mode: SearchMode::Hybrid,
             query,
+            extra_patterns: Vec::new(),
             path: path_buf,
             top_k: top_k.or(Some(DEFAULT_MCP_TOP_K)), // User-defined or MCP default
-            threshold: threshold.or(Some(0.02)),      // Lower threshold for hybrid (RRF scores)
+            threshold: threshold.or(Some(0.6)),       // Calibrated like semantic scores
             case_insensitive: request.case_insensitive.unwrap_or(false),
             whole_word: request.whole_word.unwrap_or(false),
             fixed_string: request.fixed_string.unwrap_or(false),
+            pcre2: false,
+            multiline: false,
             line_numbers: false,
             context_lines,
             before_context_lines,
@@ -1588,15 +2096,50 @@ impl CcMcpServer {
             no_snippet: !include_snippet,
             reindex: false,
             show_scores: true,
+            raw_scores: false,
             show_filenames: true,
+            group_by_file: false,
+            vimgrep: false,
+            syntax_highlight: false,
+            file_limits: Default::default(),
             files_with_matches: false,
             files_without_matches: false,
+            count: false,
+            first_match: false,
+            stats: None,
+            within: None,
+            files_ranked: None,
+            invert_match: false,
+            only_matching: false,
+            replace: None,
+            search_zip: false,
+            only_chunk_types: Vec::new(),
+            exclude_tests: false,
+            ephemeral: false,
+            hybrid_lexical: request.include_lexical.unwrap_or(false),
+            expand_query: false,
+            explain: false,
+            languages: Vec::new(),
+            fusion_weights: Default::default(),
+            requester: Some(self.requester()),
+            timeout: self.limiter.limits().query_timeout,
             exclude_patterns,
             include_patterns,
             respect_gitignore,
             full_section: false,
             rerank: request.rerank.unwrap_or(false),
             rerank_model: request.rerank_model.clone(),
+            rerank_candidates: None,
+            rerank_blend: None,
+            diversity: None,
+            sparse_weight: None,
+            stride_pooling: cs_core::StridePooling::default(),
+            dedupe: cs_core::Dedupe::default(),
+            negative_queries: Vec::new(),
+            negative_weight: None,
+            excluded_span: None,
+            offset: request.offset.unwrap_or(0),
+            index_epoch: None,
             embedding_model: None,
             ast_pattern: None,
             ast_lang: None,
@@ -1606,10 +2149,11 @@ impl CcMcpServer {
 
         // Perform the search (suppress progress callbacks for MCP)
         let started = Instant::now();
-        let search_results = match cs_engine::search_enhanced_with_indexing_progress(
+        let search_results = match cs_engine::search_enhanced_cancellable(
             &options, None, // No search progress callback for MCP
             None, // No indexing progress callback for MCP
             None, // No detailed indexing progress callback for MCP
+            cancel,
         )
         .await
         {
@@ -1625,6 +2169,7 @@ impl CcMcpServer {
             .get_first_page(
                 options,
                 filter_valid_results(search_results.matches),
+                !search_results.partial,
                 config,
             )
             .await
@@ -1632,7 +2177,7 @@ impl CcMcpServer {
 
         let search_params = json!({
             "top_k": top_k.unwrap_or(DEFAULT_MCP_TOP_K),
-            "threshold": threshold.unwrap_or(0.02)
+            "threshold": threshold.unwrap_or(0.6)
         });
 
         let current_page = page.current_page;
@@ -1640,11 +2185,11 @@ impl CcMcpServer {
             Self::search_page_to_json(page, &query_clone, "hybrid", search_params, elapsed_ms);
 
         let summary = format!(
-            "Hybrid search for '{}' found {} matches in {} (threshold: {:.3}, top_k: {}, combines semantic + regex) - Page {}",
+            "Hybrid search for '{}' found {} matches in {} (threshold: {:.2}, top_k: {}, combines semantic + regex) - Page {}",
             query_clone,
             structured_result["results"]["count"],
             path_clone.display(),
-            threshold.unwrap_or(0.02),
+            threshold.unwrap_or(0.6),
             top_k.unwrap_or(DEFAULT_MCP_TOP_K),
             current_page
         );
@@ -1658,8 +2203,7 @@ impl CcMcpServer {
         _meta: Option<Meta>,
         _peer: Option<Peer<RoleServer>>,
     ) -> Result<(String, Value), ErrorData> {
-        let path = request.path;
-        let path_buf = PathBuf::from(path);
+        let path_buf = PathBuf::from(&request.path);
 
         // Validate path exists
         if !path_buf.exists() {
@@ -1702,6 +2246,7 @@ impl CcMcpServer {
             if let Some(cached_stats) = self.context.stats_cache.get(&path_buf).await {
                 index_info["total_files"] = json!(cached_stats.file_count);
                 index_info["total_chunks"] = json!(cached_stats.chunk_count);
+                index_info["embedding_model"] = json!(cached_stats.model_name);
                 index_info["cache_hit"] = json!(true);
             } else if let Ok(index_stats) = cs_index::get_index_stats(&path_buf) {
                 index_info["total_files"] = json!(index_stats.total_files);
@@ -1709,10 +2254,15 @@ impl CcMcpServer {
                 index_info["cache_hit"] = json!(false);
 
                 // Update cache with fresh stats
+                let model_name = index_stats
+                    .embedding_model
+                    .clone()
+                    .unwrap_or_else(|| "unknown".to_string());
+                index_info["embedding_model"] = json!(model_name);
                 let cache_stats = crate::mcp::cache::IndexStats {
                     file_count: index_stats.total_files,
                     chunk_count: index_stats.total_chunks,
-                    model_name: "unknown".to_string(), // TODO: Get from manifest
+                    model_name,
                     last_updated: std::time::SystemTime::now(),
                     is_valid: true,
                 };
@@ -1730,6 +2280,25 @@ impl CcMcpServer {
 
                 index_info["estimated_file_count"] = json!(file_count);
             }
+
+            // The full breakdown reads every sidecar, so it is never cached
+            if request.detailed.unwrap_or(false) {
+                match cs_index::get_index_stats(&path_buf) {
+                    Ok(stats) => {
+                        index_info["embedding_coverage"] = json!(stats.embedding_coverage());
+                        index_info["stats"] = json!(stats);
+                    }
+                    Err(e) => index_info["stats_error"] = json!(e.to_string()),
+                }
+                match cs_index::health::check_index_health(&path_buf) {
+                    Ok(Some(health)) => {
+                        index_info["healthy"] = json!(health.is_healthy());
+                        index_info["health"] = json!(health);
+                    }
+                    Ok(None) => {}
+                    Err(e) => index_info["health_error"] = json!(e.to_string()),
+                }
+            }
         }
 
         let structured_result = json!({
@@ -1780,10 +2349,10 @@ impl CcMcpServer {
         request: ReindexRequest,
         meta: Option<Meta>,
         peer: Option<Peer<RoleServer>>,
+        cancel: &CancellationToken,
     ) -> Result<(String, Value), ErrorData> {
-        let path = request.path;
         let force = request.force.unwrap_or(false);
-        let path_buf = PathBuf::from(path);
+        let path_buf = PathBuf::from(&request.path);
 
         // Validate path exists
         if !path_buf.exists() {
@@ -1797,101 +2366,51 @@ impl CcMcpServer {
         let lock = self.context.get_index_lock(&path_buf).await;
         let _guard = lock.lock().await;
 
-        // Create progress callback for reindexing if we have a progress token and peer
-        let progress_callback = if let (Some(meta), Some(peer)) = (&meta, &peer) {
-            if let Some(progress_token) = meta.get_progress_token() {
-                let token = progress_token.clone();
-                let peer = peer.clone();
-                let step_count = Arc::new(AtomicUsize::new(0));
-                Some(Box::new(move |message: &str| {
-                    let token = token.clone();
-                    let peer = peer.clone();
-                    let message = message.to_string();
-                    let current_step = step_count.fetch_add(1, Ordering::SeqCst) + 1;
-                    tokio::spawn(async move {
-                        let _ = peer
-                            .notify_progress(ProgressNotificationParam {
-                                progress_token: token,
-                                progress: current_step as f64,
-                                total: None, // Unknown total for reindexing
-                                message: Some(message),
-                            })
-                            .await;
-                    });
-                }) as cs_engine::IndexingProgressCallback)
+        // Report reindexing progress if the client asked for it with a progress token
+        let progress_callback = Self::indexing_progress_notifier(&meta, &peer);
+
+        // Index the root governing the path, as searches do
+        let index_root = cs_engine::find_nearest_index_root(&path_buf).unwrap_or_else(|| {
+            if path_buf.is_file() {
+                path_buf.parent().unwrap_or(&path_buf).to_path_buf()
             } else {
-                None
+                path_buf.clone()
             }
-        } else {
-            None
-        };
-
-        // Create search options for reindexing
-        let options = SearchOptions {
-            mode: SearchMode::Semantic, // Use semantic mode to ensure embeddings are computed
-            query: String::new(),       // Empty query for reindexing only
-            path: path_buf.clone(),
-            top_k: None,
-            threshold: None,
-            case_insensitive: false,
-            whole_word: false,
-            fixed_string: false,
-            line_numbers: false,
-            context_lines: 0,
-            before_context_lines: 0,
-            after_context_lines: 0,
-            recursive: true,
-            json_output: false,
-            jsonl_output: true,
-            no_snippet: false,
-            reindex: force, // Use the force parameter directly
-            show_scores: false,
-            show_filenames: false,
-            files_with_matches: false,
-            files_without_matches: false,
-            exclude_patterns: get_default_exclude_patterns(),
-            include_patterns: Vec::new(),
-            respect_gitignore: true,
-            full_section: false,
-            rerank: false,
-            rerank_model: None,
-            embedding_model: None,
-            ast_pattern: None,
-            ast_lang: None,
-            ast_selector: None,
-            ast_strictness: None,
-        };
+        });
 
-        // Perform reindexing
         let start_time = std::time::Instant::now();
-        let reindex_result = match cs_engine::search_enhanced_with_indexing_progress(
-            &options,
-            None, // No search progress callback
+        let update_stats = cs_index::smart_update_index_cancellable(
+            &index_root,
+            force,
+            None,
             progress_callback,
-            None, // No detailed indexing progress callback
+            true,
+            true,
+            &get_default_exclude_patterns(),
+            request.model.as_deref(),
+            cancel,
         )
         .await
-        {
-            Ok(_) => {
-                let duration = start_time.elapsed();
+        .map_err(|e| ErrorData::internal_error(format!("Reindexing failed: {}", e), None))?;
+        let duration = start_time.elapsed();
 
-                // Invalidate cache after reindexing
-                self.context.stats_cache.invalidate(&path_buf).await;
+        // Invalidate cache after reindexing
+        self.context.stats_cache.invalidate(&path_buf).await;
+        self.context.stats_cache.invalidate(&index_root).await;
 
-                json!({
-                    "status": "success",
-                    "duration_ms": duration.as_millis(),
-                    "path": path_buf.to_string_lossy(),
-                    "force": force,
-                })
-            }
-            Err(e) => {
-                return Err(ErrorData::internal_error(
-                    format!("Reindexing failed: {}", e),
-                    None,
-                ));
-            }
-        };
+        let mut reindex_result = json!({
+            "status": "success",
+            "duration_ms": duration.as_millis(),
+            "path": path_buf.to_string_lossy(),
+            "index_root": index_root.to_string_lossy(),
+            "force": force,
+            "update_stats": update_stats,
+        });
+        if let Ok(index_stats) = cs_index::get_index_stats(&index_root) {
+            reindex_result["total_files"] = json!(index_stats.total_files);
+            reindex_result["total_chunks"] = json!(index_stats.total_chunks);
+            reindex_result["embedding_model"] = json!(index_stats.embedding_model);
+        }
 
         let structured_result = json!({
             "reindex_result": reindex_result,
@@ -1902,11 +2421,305 @@ impl CcMcpServer {
         });
 
         let summary = format!(
-            "Successfully reindexed {} in {}ms",
+            "Reindexed {} in {}ms: {} files indexed ({} added, {} modified), {} up to date, {} removed, {} errors",
             path_buf.display(),
-            reindex_result.get("duration_ms").unwrap_or(&json!(0))
+            duration.as_millis(),
+            update_stats.files_indexed,
+            update_stats.files_added,
+            update_stats.files_modified,
+            update_stats.files_up_to_date,
+            update_stats.orphaned_files_removed,
+            update_stats.files_errored
         );
 
         Ok((summary, structured_result))
     }
+
+    pub async fn handle_clean_index(
+        &self,
+        request: CleanIndexRequest,
+    ) -> Result<(String, Value), ErrorData> {
+        let path_buf = PathBuf::from(&request.path);
+        if !path_buf.is_dir() {
+            return Err(ErrorData::invalid_params(
+                format!("Not a directory: {}", path_buf.display()),
+                None,
+            ));
+        }
+        let orphans_only = request.orphans_only.unwrap_or(false);
+
+        let lock = self.context.get_index_lock(&path_buf).await;
+        let _guard = lock.lock().await;
+
+        let had_index = path_buf.join(".cs").exists();
+        let (summary, cleanup_stats) = if orphans_only {
+            let stats = cs_index::cleanup_index(&path_buf, true, &get_default_exclude_patterns())
+                .map_err(|e| {
+                ErrorData::internal_error(format!("Cleanup failed: {}", e), None)
+            })?;
+            let summary = format!(
+                "Removed {} orphaned entries and {} orphaned sidecars from {}",
+                stats.orphaned_entries_removed,
+                stats.orphaned_sidecars_removed,
+                path_buf.display()
+            );
+            (summary, Some(stats))
+        } else {
+            cs_index::clean_index(&path_buf).map_err(|e| {
+                ErrorData::internal_error(format!("Removing the index failed: {}", e), None)
+            })?;
+            let summary = if had_index {
+                format!("Removed the index of {}", path_buf.display())
+            } else {
+                format!("No index to remove in {}", path_buf.display())
+            };
+            (summary, None)
+        };
+        self.context.stats_cache.invalidate(&path_buf).await;
+
+        let structured_result = json!({
+            "clean_result": {
+                "path": path_buf.to_string_lossy(),
+                "orphans_only": orphans_only,
+                "had_index": had_index,
+                "cleanup_stats": cleanup_stats,
+            },
+            "metadata": {
+                "completed_at": chrono::Utc::now().to_rfc3339(),
+            }
+        });
+        Ok((summary, structured_result))
+    }
+
+    pub fn handle_get_chunk(&self, request: GetChunkRequest) -> Result<(String, Value), ErrorData> {
+        let file = PathBuf::from(&request.path);
+        if !file.is_file() {
+            return Err(ErrorData::invalid_params(
+                format!("Not a file: {}", file.display()),
+                None,
+            ));
+        }
+        let file = file.canonicalize().unwrap_or(file);
+        let content = cs_core::encoding::read_to_string(&file).map_err(|e| {
+            ErrorData::invalid_params(format!("Cannot read {}: {}", file.display(), e), None)
+        })?;
+        let line_starts = line_starts(&content);
+        let (chunks, chunk_source) = cs_engine::file_chunks(&file, &content);
+
+        let (chunk, source) = if let Some(hash) = request.chunk_hash.as_deref() {
+            if hash.is_empty() {
+                return Err(ErrorData::invalid_params("chunk_hash is empty", None));
+            }
+            let mut matches = cs_engine::chunks_with_id(&file, &content, chunks, hash).into_iter();
+            match (matches.next(), matches.next()) {
+                (Some(chunk), None) => (chunk, chunk_source),
+                (Some(_), Some(_)) => {
+                    return Err(ErrorData::invalid_params(
+                        format!(
+                            "chunk_hash '{}' matches several chunks; give more of it",
+                            hash
+                        ),
+                        None,
+                    ));
+                }
+                (None, _) => {
+                    return Err(ErrorData::invalid_params(
+                        format!(
+                            "No chunk in {} has hash {}; the file may have changed, search again",
+                            file.display(),
+                            hash
+                        ),
+                        None,
+                    ));
+                }
+            }
+        } else if request.byte_start.is_some() || request.line_start.is_some() {
+            let by_lines = request.byte_start.is_none();
+            let requested = if let Some(start) = request.byte_start {
+                let end = request.byte_end.unwrap_or(start);
+                if start > end || content.get(start..end).is_none() {
+                    return Err(ErrorData::invalid_params(
+                        format!(
+                            "Byte range {}..{} is out of bounds or splits a character ({} bytes)",
+                            start,
+                            end,
+                            content.len()
+                        ),
+                        None,
+                    ));
+                }
+                cs_core::Span {
+                    byte_start: start,
+                    byte_end: end,
+                    line_start: line_of(&line_starts, start),
+                    line_end: line_of(&line_starts, end.saturating_sub(1).max(start)),
+                }
+            } else {
+                let start = request.line_start.unwrap_or(1);
+                let end = request.line_end.unwrap_or(start);
+                if start == 0 || start > end || end > line_starts.len() {
+                    return Err(ErrorData::invalid_params(
+                        format!(
+                            "Line range {}-{} is outside 1-{}",
+                            start,
+                            end,
+                            line_starts.len()
+                        ),
+                        None,
+                    ));
+                }
+                let byte_end = line_starts.get(end).copied().unwrap_or(content.len());
+                let byte_end = if content[..byte_end].ends_with('\n') {
+                    byte_end - 1
+                } else {
+                    byte_end
+                };
+                cs_core::Span {
+                    byte_start: line_starts[start - 1],
+                    byte_end,
+                    line_start: start,
+                    line_end: end,
+                }
+            };
+
+            // The smallest chunk holding the span; a span across chunks is returned as is
+            let enclosing = chunks
+                .into_iter()
+                .filter(|chunk| {
+                    if by_lines {
+                        chunk.span.line_start <= requested.line_start
+                            && chunk.span.line_end >= requested.line_end
+                    } else {
+                        chunk.span.byte_start <= requested.byte_start
+                            && chunk.span.byte_end >= requested.byte_end
+                    }
+                })
+                .min_by_key(|chunk| chunk.span.byte_end - chunk.span.byte_start);
+            match enclosing {
+                Some(chunk) => (chunk, chunk_source),
+                None => (cs_engine::FileChunk::plain(requested), "range"),
+            }
+        } else {
+            if content.len() > MAX_WHOLE_FILE_BYTES {
+                return Err(ErrorData::invalid_params(
+                    format!(
+                        "{} is {} bytes, over the {} byte limit for whole files; pass a span or chunk_hash",
+                        file.display(),
+                        content.len(),
+                        MAX_WHOLE_FILE_BYTES
+                    ),
+                    None,
+                ));
+            }
+            let span = cs_core::Span {
+                byte_start: 0,
+                byte_end: content.len(),
+                line_start: 1,
+                line_end: line_starts.len().max(1),
+            };
+            (cs_engine::FileChunk::plain(span), "file")
+        };
+
+        let span = &chunk.span;
+        let text = content.get(span.byte_start..span.byte_end).ok_or_else(|| {
+            ErrorData::internal_error(
+                format!("Chunk span of {} is out of bounds", file.display()),
+                None,
+            )
+        })?;
+        let context_lines = request.context_lines.unwrap_or(DEFAULT_CHUNK_CONTEXT_LINES);
+        let line_range = |first: usize, last: usize| -> String {
+            if first > last || first == 0 || first > line_starts.len() {
+                return String::new();
+            }
+            let end = line_starts.get(last).copied().unwrap_or(content.len());
+            content[line_starts[first - 1]..end].to_string()
+        };
+        let before = line_range(
+            span.line_start.saturating_sub(context_lines).max(1),
+            span.line_start.saturating_sub(1),
+        );
+        let after = line_range(
+            span.line_end + 1,
+            (span.line_end + context_lines).min(line_starts.len()),
+        );
+
+        let result = json!({
+            "file": {
+                "path": file.to_string_lossy(),
+                "language": cs_core::Language::from_path(&file).map(|language| language.to_string()),
+            },
+            "chunk": {
+                "span": span,
+                "text": text,
+                "chunk_hash": cs_engine::chunk_id(&file, span, &content),
+                "chunk_type": chunk.chunk_type,
+                "breadcrumb": chunk.breadcrumb,
+                "ancestry": chunk.ancestry,
+                "estimated_tokens": chunk.estimated_tokens,
+            },
+            "context": {
+                "lines": context_lines,
+                "before": before,
+                "after": after,
+            },
+            // index: metadata from the sidecar; chunker: file changed or unindexed, chunked now;
+            // range: span not inside one chunk; file: whole file
+            "source": source,
+        });
+
+        let summary = format!(
+            "{}:{}-{}{}",
+            file.display(),
+            span.line_start,
+            span.line_end,
+            chunk
+                .breadcrumb
+                .as_deref()
+                .or(chunk.chunk_type.as_deref())
+                .map(|label| format!(" ({})", label))
+                .unwrap_or_default()
+        );
+        Ok((summary, result))
+    }
+
+    pub fn handle_file_outline(
+        &self,
+        request: FileOutlineRequest,
+    ) -> Result<(String, Value), ErrorData> {
+        let file = PathBuf::from(&request.path);
+        if !file.is_file() {
+            return Err(ErrorData::invalid_params(
+                format!("Not a file: {}", file.display()),
+                None,
+            ));
+        }
+        let language = cs_core::Language::from_path(&file)
+            .filter(|language| cs_chunk::ParseableLanguage::try_from(*language).is_ok())
+            .ok_or_else(|| {
+                ErrorData::invalid_params(
+                    format!(
+                        "No parser for {}; outlines need a tree-sitter language",
+                        file.display()
+                    ),
+                    None,
+                )
+            })?;
+        let content = cs_core::encoding::read_to_string(&file).map_err(|e| {
+            ErrorData::invalid_params(format!("Cannot read {}: {}", file.display(), e), None)
+        })?;
+
+        let symbols = outline_symbols(&content, language)
+            .map_err(|e| ErrorData::internal_error(format!("Failed to parse: {}", e), None))?;
+
+        let summary = format!("{} symbols in {}", symbols.len(), file.display());
+        let result = json!({
+            "file": {
+                "path": file.to_string_lossy(),
+                "language": language.to_string(),
+            },
+            "symbols": symbols,
+        });
+        Ok((summary, result))
+    }
 }
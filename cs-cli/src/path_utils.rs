@@ -48,6 +48,15 @@ pub fn build_include_patterns(paths: &[PathBuf]) -> Vec<IncludePattern> {
     includes
 }
 
+/// Excludes for searches of the project at `root` without command-line flags: `.csignore`,
+/// `exclude` in `cs.toml`, then the defaults
+pub fn project_exclude_patterns(root: &Path, project: &cs_models::ProjectConfig) -> Vec<String> {
+    let mut patterns = cs_core::read_csignore_patterns(root).unwrap_or_default();
+    patterns.extend(project.exclude.iter().cloned());
+    patterns.extend(cs_core::get_default_exclude_patterns());
+    patterns
+}
+
 pub(crate) fn split_path_patterns(path: &Path) -> Vec<String> {
     let path_str = path.to_string_lossy();
     if !path_str.contains(';') {
@@ -6,33 +6,42 @@ pub struct StatusReporter {
     term: Term,
     multi_progress: MultiProgress,
     pub quiet: bool,
+    /// Results are JSON: progress and informational messages stay off stderr, which then
+    /// carries only warnings and errors
+    pub json: bool,
 }
 
 impl StatusReporter {
-    pub fn new(quiet: bool) -> Self {
+    pub fn new(quiet: bool, json: bool) -> Self {
         Self {
             term: Term::stderr(),
             multi_progress: MultiProgress::new(),
             quiet,
+            json,
         }
     }
 
+    /// Whether progress indicators and informational messages are shown
+    pub fn shows_progress(&self) -> bool {
+        !self.quiet && !self.json
+    }
+
     pub fn info(&self, msg: &str) {
-        if !self.quiet {
+        if self.shows_progress() {
             let _ = self.term.write_line(&format!(
                 "{} {}",
-                style("ℹ").cyan().bold(),
-                style(msg).dim()
+                style("ℹ").for_stderr().cyan().bold(),
+                style(msg).for_stderr().dim()
             ));
         }
     }
 
     pub fn success(&self, msg: &str) {
-        if !self.quiet {
+        if self.shows_progress() {
             let _ = self.term.write_line(&format!(
                 "{} {}",
-                style("✓").green().bold(),
-                style(msg).green()
+                style("✓").for_stderr().green().bold(),
+                style(msg).for_stderr().green()
             ));
         }
     }
@@ -41,22 +50,24 @@ impl StatusReporter {
         if !self.quiet {
             let _ = self.term.write_line(&format!(
                 "{} {}",
-                style("⚠").yellow().bold(),
-                style(msg).yellow()
+                style("⚠").for_stderr().yellow().bold(),
+                style(msg).for_stderr().yellow()
             ));
         }
     }
 
     #[allow(dead_code)]
     pub fn error(&self, msg: &str) {
-        let _ = self
-            .term
-            .write_line(&format!("{} {}", style("✗").red().bold(), style(msg).red()));
+        let _ = self.term.write_line(&format!(
+            "{} {}",
+            style("✗").for_stderr().red().bold(),
+            style(msg).for_stderr().red()
+        ));
     }
 
     #[allow(dead_code)]
     pub fn create_file_progress(&self, total: u64, operation: &str) -> Option<ProgressBar> {
-        if self.quiet {
+        if !self.shows_progress() {
             return None;
         }
 
@@ -65,7 +76,7 @@ impl StatusReporter {
             ProgressStyle::default_bar()
                 .template(&format!(
                     "{{spinner:.green}} {} {{wide_bar:.cyan/blue}} {{pos}}/{{len}} {{msg}}",
-                    style(operation).bold()
+                    style(operation).for_stderr().bold()
                 ))
                 .unwrap_or_else(|_| ProgressStyle::default_bar())
                 .progress_chars("█▉▊▋▌▍▎▏  ")
@@ -76,7 +87,7 @@ impl StatusReporter {
     }
 
     pub fn create_spinner(&self, msg: &str) -> Option<ProgressBar> {
-        if self.quiet {
+        if !self.shows_progress() {
             return None;
         }
 
@@ -96,7 +107,7 @@ impl StatusReporter {
     pub fn update_file_progress(&self, pb: &Option<ProgressBar>, file_name: &str) {
         if let Some(pb) = pb {
             pb.inc(1);
-            pb.set_message(format!("{}", style(file_name).dim()));
+            pb.set_message(format!("{}", style(file_name).for_stderr().dim()));
         }
     }
 
@@ -104,15 +115,15 @@ impl StatusReporter {
         if let Some(pb) = pb {
             pb.finish_with_message(format!(
                 "{} {}",
-                style("✓").green().bold(),
-                style(success_msg).green()
+                style("✓").for_stderr().green().bold(),
+                style(success_msg).for_stderr().green()
             ));
         }
     }
 
     #[allow(dead_code)]
     pub fn streaming_files(&self, files: &[std::path::PathBuf]) {
-        if !self.quiet && !files.is_empty() {
+        if self.shows_progress() && !files.is_empty() {
             let max_display = 5;
             let display_files = if files.len() > max_display {
                 &files[..max_display]
@@ -128,8 +139,8 @@ impl StatusReporter {
 
                 let _ = self.term.write_line(&format!(
                     "  {} {}",
-                    style(format!("{}.", i + 1)).dim(),
-                    style(&file_name).cyan()
+                    style(format!("{}.", i + 1)).for_stderr().dim(),
+                    style(&file_name).for_stderr().cyan()
                 ));
 
                 // Small delay to create streaming effect
@@ -140,20 +151,22 @@ impl StatusReporter {
                 let remaining = files.len() - max_display;
                 let _ = self.term.write_line(&format!(
                     "  {} {}",
-                    style("...").dim(),
-                    style(format!("and {} more files", remaining)).dim()
+                    style("...").for_stderr().dim(),
+                    style(format!("and {} more files", remaining))
+                        .for_stderr()
+                        .dim()
                 ));
             }
         }
     }
 
     pub fn section_header(&self, title: &str) {
-        if !self.quiet {
+        if self.shows_progress() {
             let _ = self.term.write_line("");
             let _ = self.term.write_line(&format!(
                 "{} {}",
-                style("▸").blue().bold(),
-                style(title).bold()
+                style("▸").for_stderr().blue().bold(),
+                style(title).for_stderr().bold()
             ));
         }
     }
@@ -0,0 +1,443 @@
+//! `cs --rpc`: a long-lived JSON-RPC 2.0 server on stdio for editor extensions, framed like LSP
+//! (`Content-Length` headers) so `vscode-jsonrpc` and similar libraries speak it as is. One
+//! process serves one project root. Requests:
+//!
+//! - `search` `{ query, mode?, path?, topK?, threshold?, ignoreCase?, offset? }`: results in the
+//!   `--json` schema, `{ matches, partial, epoch }`
+//! - `outline` `{ path }`: the definitions of one file, as the MCP `file_outline` tool returns them
+//! - `indexStatus` `{}`: whether the root is indexed and the `--status --json` statistics
+//!
+//! The server watches the root and, once edits settle, updates the index in the background.
+//! Whenever the index changes, by that update or by the update before a search, it pushes an
+//! `indexUpdated` notification with the new epoch. `exit`, or the end of input, stops it.
+
+use crate::jsonrpc::{
+    METHOD_NOT_FOUND, RpcError, notification, read_message, response, write_message,
+};
+use anyhow::Result;
+use cs_core::{SearchMode, SearchOptions};
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncWrite};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// Quiet time after the last file change before the index is updated
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+const DEFAULT_TOP_K: usize = 20;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchParams {
+    query: String,
+    /// `regex`, `lexical`, `semantic` (default), `hybrid` or `symbol`
+    mode: Option<String>,
+    /// File or directory, relative to the root; the root by default
+    path: Option<PathBuf>,
+    top_k: Option<usize>,
+    threshold: Option<f32>,
+    #[serde(default)]
+    ignore_case: bool,
+    #[serde(default)]
+    offset: usize,
+}
+
+#[derive(Deserialize)]
+struct OutlineParams {
+    path: PathBuf,
+}
+
+/// What made the index change, in `indexUpdated`
+#[derive(Clone, Copy)]
+enum Trigger {
+    Watch,
+    Search,
+}
+
+struct RpcServer {
+    root: PathBuf,
+    /// Epoch of the index as last reported to the client
+    epoch: Option<u64>,
+}
+
+/// Serve requests for the project at `root` from `reader` until `exit` or the end of input,
+/// updating the index when files under `root` change if `watch` is set
+pub async fn serve<R, W>(root: PathBuf, reader: R, mut writer: W, watch: bool) -> Result<()>
+where
+    R: AsyncBufRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    // Messages are read on their own task, so waiting for one never cancels a read halfway
+    let (messages_tx, mut messages) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut reader = reader;
+        loop {
+            let message = read_message(&mut reader).await;
+            let last = !matches!(message, Ok(Some(_)));
+            if messages_tx.send(message).is_err() || last {
+                break;
+            }
+        }
+    });
+
+    let (changes_tx, mut changes) = mpsc::unbounded_channel();
+    // Dropping the watcher stops it
+    let _watcher = if watch {
+        Some(watch_root(&root, changes_tx)?)
+    } else {
+        None
+    };
+
+    let mut server = RpcServer {
+        epoch: cs_index::index_epoch(&root),
+        root,
+    };
+    loop {
+        tokio::select! {
+            message = messages.recv() => {
+                let Some(message) = message.transpose()?.flatten() else {
+                    break;
+                };
+                if !server.handle_message(message, &mut writer).await? {
+                    break;
+                }
+            }
+            Some(()) = changes.recv() => {
+                // Let a burst of saves settle, then update once
+                tokio::time::sleep(DEBOUNCE).await;
+                while changes.try_recv().is_ok() {}
+                server.update_index(&mut writer).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Watch `root` recursively, sending `()` for every change outside the index and VCS metadata
+fn watch_root(
+    root: &Path,
+    changes: mpsc::UnboundedSender<()>,
+) -> Result<notify::RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if !matches!(event.kind, EventKind::Access(_))
+            && event.paths.iter().any(|path| is_watched(path))
+        {
+            let _ = changes.send(());
+        }
+    })?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+    Ok(watcher)
+}
+
+/// Whether a change to `path` can change the index: the index's own writes and VCS metadata
+/// cannot
+fn is_watched(path: &Path) -> bool {
+    !path.components().any(|component| {
+        matches!(component, Component::Normal(name)
+            if name == ".cs" || name == ".git" || name == ".jj" || name == ".hg")
+    })
+}
+
+impl RpcServer {
+    /// Answer one message; `false` once the client asked to exit
+    async fn handle_message<W: AsyncWrite + Unpin>(
+        &mut self,
+        message: Value,
+        writer: &mut W,
+    ) -> Result<bool> {
+        // Responses to requests we never send, and anything else without a method
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Ok(true);
+        };
+        if method == "exit" {
+            return Ok(false);
+        }
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let result = self.handle(method, params).await;
+        match message.get("id").cloned() {
+            Some(id) => write_message(writer, &response(id, result)).await?,
+            None => {
+                if let Err(e) = result
+                    && e.code != METHOD_NOT_FOUND
+                {
+                    tracing::warn!("{} failed: {}", method, e.message);
+                }
+            }
+        }
+        self.report_epoch(writer, Trigger::Search, None, false)
+            .await?;
+        Ok(true)
+    }
+
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "search" => {
+                let params: SearchParams = serde_json::from_value(params)
+                    .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                self.search(params).await
+            }
+            "outline" => {
+                let params: OutlineParams = serde_json::from_value(params)
+                    .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                self.outline(&params.path)
+            }
+            "indexStatus" => self.index_status(),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("{} is not supported", method),
+            )),
+        }
+    }
+
+    async fn search(&self, params: SearchParams) -> Result<Value, RpcError> {
+        let mode = match params.mode.as_deref().unwrap_or("semantic") {
+            "regex" => SearchMode::Regex,
+            "lexical" => SearchMode::Lexical,
+            "semantic" => SearchMode::Semantic,
+            "hybrid" => SearchMode::Hybrid,
+            "symbol" => SearchMode::Symbol,
+            other => {
+                return Err(RpcError::invalid_params(format!(
+                    "mode must be regex, lexical, semantic, hybrid or symbol, not {}",
+                    other
+                )));
+            }
+        };
+        // Modes without embeddings report no model, as in --json
+        let embedded = matches!(mode, SearchMode::Semantic | SearchMode::Hybrid);
+        let project = cs_models::ProjectConfig::discover(&self.root)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let options = SearchOptions {
+            mode,
+            query: params.query,
+            path: self.resolve(params.path.as_deref()),
+            top_k: params.top_k.or(project.top_k).or(Some(DEFAULT_TOP_K)),
+            threshold: params.threshold.or(project.threshold),
+            case_insensitive: params.ignore_case,
+            offset: params.offset,
+            show_scores: true,
+            requester: Some("rpc".to_string()),
+            exclude_patterns: crate::path_utils::project_exclude_patterns(&self.root, &project),
+            ..Default::default()
+        };
+        let results = cs_engine::search_enhanced(&options).await?;
+        let model = embedded
+            .then(|| cs_engine::resolve_model_for_path(&options.path, None).ok())
+            .flatten()
+            .map(|resolved| resolved.canonical_name);
+        let matches: Vec<_> = results
+            .matches
+            .iter()
+            .map(|result| {
+                cs_core::JsonSearchResult::from_search_result(
+                    result,
+                    model.as_deref(),
+                    results.partial,
+                )
+            })
+            .collect();
+        Ok(json!({
+            "matches": matches,
+            "partial": results.partial,
+            "epoch": cs_index::index_epoch(&self.root),
+        }))
+    }
+
+    fn outline(&self, path: &Path) -> Result<Value, RpcError> {
+        let file = self.resolve(Some(path));
+        if !file.is_file() {
+            return Err(RpcError::invalid_params(format!(
+                "Not a file: {}",
+                file.display()
+            )));
+        }
+        let language = cs_core::Language::from_path(&file)
+            .filter(|language| cs_chunk::ParseableLanguage::try_from(*language).is_ok())
+            .ok_or_else(|| RpcError::invalid_params(format!("No parser for {}", file.display())))?;
+        let content = cs_core::encoding::read_to_string(&file).map_err(|e| {
+            RpcError::invalid_params(format!("Cannot read {}: {}", file.display(), e))
+        })?;
+        let symbols = crate::mcp_server::outline_symbols(&content, language)?;
+        Ok(json!({
+            "file": { "path": file.to_string_lossy(), "language": language.to_string() },
+            "symbols": symbols,
+        }))
+    }
+
+    fn index_status(&self) -> Result<Value, RpcError> {
+        let Some(epoch) = cs_index::index_epoch(&self.root) else {
+            return Ok(json!({ "root": self.root, "indexed": false }));
+        };
+        let stats = cs_index::get_index_stats(&self.root)?;
+        Ok(json!({
+            "root": self.root,
+            "indexed": true,
+            "epoch": epoch,
+            "stats": stats,
+        }))
+    }
+
+    /// Update the index after files changed, with embeddings when it has them
+    async fn update_index<W: AsyncWrite + Unpin>(&mut self, writer: &mut W) -> Result<()> {
+        if cs_index::index_epoch(&self.root).is_none() {
+            return Ok(());
+        }
+        let project = cs_models::ProjectConfig::discover(&self.root)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let exclude_patterns = crate::path_utils::project_exclude_patterns(&self.root, &project);
+        let update = cs_index::smart_update_index_cancellable(
+            &self.root,
+            false,
+            None,
+            None,
+            cs_index::index_embedding_model(&self.root).is_some(),
+            true,
+            &exclude_patterns,
+            None,
+            &CancellationToken::new(),
+        )
+        .await;
+        match update {
+            Ok(stats) => {
+                // The epoch has one-second resolution, so an update right after another may
+                // leave it as it was
+                let changed = stats.files_indexed + stats.orphaned_files_removed > 0;
+                self.report_epoch(writer, Trigger::Watch, Some(stats), changed)
+                    .await
+            }
+            Err(e) => {
+                tracing::warn!("Failed to update the index of {:?}: {}", self.root, e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Push `indexUpdated` if the index changed since the client last heard of it, or
+    /// regardless when `changed`
+    async fn report_epoch<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+        trigger: Trigger,
+        stats: Option<cs_index::UpdateStats>,
+        changed: bool,
+    ) -> Result<()> {
+        let epoch = cs_index::index_epoch(&self.root);
+        if epoch == self.epoch && !changed {
+            return Ok(());
+        }
+        self.epoch = epoch;
+        let params = json!({
+            "root": self.root,
+            "epoch": epoch,
+            "trigger": match trigger {
+                Trigger::Watch => "watch",
+                Trigger::Search => "search",
+            },
+            "stats": stats,
+        });
+        write_message(writer, &notification("indexUpdated", params)).await
+    }
+
+    fn resolve(&self, path: Option<&Path>) -> PathBuf {
+        match path {
+            Some(path) if path.is_absolute() => path.to_path_buf(),
+            Some(path) => self.root.join(path),
+            None => self.root.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc::frame;
+    use std::fs;
+    use tempfile::TempDir;
+    use tokio::io::{AsyncWriteExt, BufReader};
+
+    #[tokio::test]
+    async fn test_requests_and_index_notifications() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        fs::write(root.join("lib.rs"), "fn retry() {}\n\nfn backoff() {}\n").unwrap();
+        cs_index::smart_update_index(&root, false, true, &[])
+            .await
+            .unwrap();
+
+        let (client, server_end) = tokio::io::duplex(64 * 1024);
+        let (server_reader, server_writer) = tokio::io::split(server_end);
+        let server = tokio::spawn(serve(
+            root.clone(),
+            BufReader::new(server_reader),
+            server_writer,
+            true,
+        ));
+        let (client_reader, mut client_writer) = tokio::io::split(client);
+        let mut client_reader = BufReader::new(client_reader);
+        let mut send = async |message: Value| {
+            client_writer.write_all(&frame(message)).await.unwrap();
+        };
+
+        send(json!({ "jsonrpc": "2.0", "id": 1, "method": "search", "params": { "query": "retry", "mode": "regex" } })).await;
+        let reply = read_message(&mut client_reader).await.unwrap().unwrap();
+        assert_eq!(reply["id"], 1);
+        let matches = reply["result"]["matches"].as_array().unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0]["span"]["line_start"], 1);
+        assert!(matches[0]["model"].is_null());
+
+        send(json!({ "jsonrpc": "2.0", "id": 2, "method": "outline", "params": { "path": "lib.rs" } })).await;
+        let reply = read_message(&mut client_reader).await.unwrap().unwrap();
+        let names: Vec<_> = reply["result"]["symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|symbol| symbol["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["retry", "backoff"]);
+
+        send(json!({ "jsonrpc": "2.0", "id": 3, "method": "indexStatus" })).await;
+        let reply = read_message(&mut client_reader).await.unwrap().unwrap();
+        assert_eq!(reply["result"]["indexed"], true);
+        assert_eq!(reply["result"]["stats"]["total_files"], 1);
+
+        send(json!({ "jsonrpc": "2.0", "id": 4, "method": "search", "params": { "query": "x", "mode": "fuzzy" } })).await;
+        let reply = read_message(&mut client_reader).await.unwrap().unwrap();
+        assert_eq!(reply["error"]["code"], crate::jsonrpc::INVALID_PARAMS);
+
+        // A new file is picked up by the watcher, indexed, and announced
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        fs::write(root.join("jitter.rs"), "fn jitter() {}\n").unwrap();
+        let update =
+            tokio::time::timeout(Duration::from_secs(20), read_message(&mut client_reader))
+                .await
+                .expect("no indexUpdated notification")
+                .unwrap()
+                .unwrap();
+        assert_eq!(update["method"], "indexUpdated");
+        assert_eq!(update["params"]["trigger"], "watch");
+        assert_eq!(update["params"]["stats"]["files_added"], 1);
+
+        send(json!({ "jsonrpc": "2.0", "method": "exit" })).await;
+        server.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_index_and_vcs_changes_are_not_watched() {
+        assert!(is_watched(Path::new("/repo/src/lib.rs")));
+        assert!(!is_watched(Path::new("/repo/.cs/manifest.json")));
+        assert!(!is_watched(Path::new("/repo/.git/index")));
+        assert!(is_watched(Path::new("/repo/docs/.csignore")));
+    }
+}
@@ -0,0 +1,171 @@
+//! `cs --stats`: what one search read, skipped and scored, and where its time went, printed
+//! to stderr after the results like `rg --stats`. With `--json`/`--jsonl` it is a single JSON
+//! line, so the results on stdout stay parseable.
+
+use cs_core::SearchOptions;
+use cs_core::file_limits::DEFAULT_MAX_EMBED_FILESIZE;
+use cs_core::stats::StatsReport;
+use cs_engine::IndexUse;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Phases that run one after another; every other phase is part of "search"
+const TOP_LEVEL_PHASES: &[&str] = &["index update", "search"];
+
+#[derive(Debug, Serialize)]
+pub struct RunStats {
+    pub results: usize,
+    /// `None` when searching stdin
+    pub files_skipped: Option<cs_index::SkippedCounts>,
+    /// Embedding model, for modes that read embeddings
+    pub model: Option<String>,
+    pub dimensions: Option<usize>,
+    #[serde(flatten)]
+    pub report: StatsReport,
+    pub total_ms: f64,
+}
+
+pub fn collect(
+    options: &SearchOptions,
+    report: StatsReport,
+    results: usize,
+    elapsed: Duration,
+) -> RunStats {
+    let index_use = cs_engine::index_use(&options.mode);
+    let files_skipped = if cs_engine::is_stdin_path(&options.path) {
+        None
+    } else {
+        // Indexing leaves out large files even when regex search would read them
+        let max_filesize = match index_use {
+            IndexUse::None => options.file_limits.max_filesize,
+            _ => {
+                options
+                    .file_limits
+                    .or_max_filesize(DEFAULT_MAX_EMBED_FILESIZE)
+                    .max_filesize
+            }
+        };
+        cs_index::count_skipped_files(
+            &options.path,
+            options.respect_gitignore,
+            &options.exclude_patterns,
+            max_filesize,
+        )
+        .map_err(|e| tracing::debug!("Could not count skipped files: {}", e))
+        .ok()
+    };
+    let model = match index_use {
+        IndexUse::Embeddings => {
+            cs_engine::resolve_model_for_path(&options.path, options.embedding_model.as_deref())
+                .map_err(|e| tracing::debug!("Could not resolve the embedding model: {}", e))
+                .ok()
+        }
+        _ => None,
+    };
+    RunStats {
+        results,
+        files_skipped,
+        dimensions: model.as_ref().map(|model| model.dimensions),
+        model: model.map(|model| model.canonical_name),
+        report,
+        total_ms: elapsed.as_secs_f64() * 1000.0,
+    }
+}
+
+pub fn render_text(stats: &RunStats) -> String {
+    let mut out = String::new();
+    let mut row = |label: &str, value: String| out.push_str(&format!("{:<22} {}\n", label, value));
+    row("results", stats.results.to_string());
+    row("files scanned", stats.report.files_scanned.to_string());
+    if let Some(skipped) = &stats.files_skipped {
+        row(
+            "files skipped",
+            format!(
+                "{} binary, {} too big, {} ignored",
+                skipped.binary, skipped.too_big, skipped.ignored
+            ),
+        );
+    }
+    row("chunks scored", stats.report.chunks_scored.to_string());
+    if stats.report.index_files_checked > 0 {
+        row(
+            "index freshness",
+            format!(
+                "{} checked, {} updated, {} removed",
+                stats.report.index_files_checked,
+                stats.report.index_files_updated,
+                stats.report.index_files_removed
+            ),
+        );
+    }
+    if let Some(model) = &stats.model {
+        let dimensions = stats
+            .dimensions
+            .map(|dimensions| format!(" ({} dims)", dimensions))
+            .unwrap_or_default();
+        row("model", format!("{}{}", model, dimensions));
+    }
+    // Stages inside "search" finish before it does; list them under it
+    let (top_level, within_search): (Vec<_>, Vec<_>) = stats
+        .report
+        .phases
+        .iter()
+        .partition(|phase| TOP_LEVEL_PHASES.contains(&phase.phase));
+    for phase in top_level {
+        row(phase.phase, format!("{:.1} ms", phase.ms));
+        if phase.phase == "search" {
+            for inner in &within_search {
+                row(&format!("  {}", inner.phase), format!("{:.1} ms", inner.ms));
+            }
+        }
+    }
+    row("total", format!("{:.1} ms", stats.total_ms));
+    out
+}
+
+/// Print to stderr, as JSON when the results are JSON
+pub fn print(stats: &RunStats, json: bool) {
+    if json {
+        eprintln!("{}", serde_json::json!({ "stats": stats }));
+    } else {
+        eprint!("\n{}", render_text(stats));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cs_core::stats::SearchStats;
+
+    #[test]
+    fn test_render_text_nests_search_phases() {
+        let stats = SearchStats::new();
+        stats.add_files_scanned(4);
+        stats.add_index_check(4, 1, 0);
+        stats.record_phase("index update", Duration::from_millis(2));
+        stats.record_phase("query embedding", Duration::from_millis(3));
+        stats.record_phase("search", Duration::from_millis(5));
+        let run = RunStats {
+            results: 2,
+            files_skipped: Some(cs_index::SkippedCounts {
+                binary: 1,
+                too_big: 0,
+                ignored: 7,
+            }),
+            model: Some("BAAI/bge-small-en-v1.5".to_string()),
+            dimensions: Some(384),
+            report: stats.report(),
+            total_ms: 12.0,
+        };
+
+        let text = render_text(&run);
+        assert!(text.contains("files skipped          1 binary, 0 too big, 7 ignored\n"));
+        assert!(text.contains("index freshness        4 checked, 1 updated, 0 removed\n"));
+        assert!(text.contains("model                  BAAI/bge-small-en-v1.5 (384 dims)\n"));
+        assert!(text.contains("search                 5.0 ms\n  query embedding      3.0 ms\n"));
+
+        let json = serde_json::to_value(&run).unwrap();
+        assert_eq!(json["files_scanned"], 4);
+        assert_eq!(json["files_skipped"]["ignored"], 7);
+    }
+}
@@ -0,0 +1,204 @@
+//! Per-mode result defaults, and `cs --help-modes`, which documents every search mode from
+//! those defaults and from what the engine reads from the index, so the description cannot
+//! drift from the behavior.
+
+use cs_core::SearchMode;
+use cs_engine::IndexUse;
+use serde::Serialize;
+
+/// Whether results in `mode` are ranked, so `--topk` and `top_k` in cs.toml cut them
+pub fn ranked(mode: &SearchMode) -> bool {
+    matches!(
+        mode,
+        SearchMode::Semantic | SearchMode::Lexical | SearchMode::Hybrid
+    )
+}
+
+/// Result limit for `mode` when neither `--topk` nor cs.toml sets one
+pub fn default_top_k(mode: &SearchMode) -> Option<usize> {
+    match mode {
+        SearchMode::Semantic => Some(10),
+        _ => None,
+    }
+}
+
+/// Minimum score for `mode` when neither `--threshold` nor cs.toml sets one; cs.toml only
+/// applies where there is a default to replace
+pub fn default_threshold(mode: &SearchMode) -> Option<f32> {
+    match mode {
+        SearchMode::Semantic => Some(0.6),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModeHelp {
+    pub mode: &'static str,
+    pub flag: &'static str,
+    pub summary: &'static str,
+    /// "none", "chunks" or "embeddings"
+    pub index: &'static str,
+    pub ranked: bool,
+    pub default_top_k: Option<usize>,
+    pub default_threshold: Option<f32>,
+    /// What the scores shown by `--scores` measure, for ranked modes
+    pub scores: Option<&'static str>,
+}
+
+pub fn modes() -> Vec<ModeHelp> {
+    [
+        (
+            SearchMode::Regex,
+            "regex",
+            "--regex",
+            "Grep-compatible regular expressions; the default unless default-search-mode is set",
+            None,
+        ),
+        (
+            SearchMode::Lexical,
+            "lexical",
+            "--lex",
+            "BM25 full-text ranking of indexed chunks",
+            Some("BM25 relative to the best hit, 0.0-1.0"),
+        ),
+        (
+            SearchMode::Semantic,
+            "semantic",
+            "--sem",
+            "Embedding similarity between the query and indexed chunks",
+            Some("cosine similarity, 0.0-1.0"),
+        ),
+        (
+            SearchMode::Hybrid,
+            "hybrid",
+            "--hybrid",
+            "Regex and semantic results fused by reciprocal rank; --hybrid-lex adds BM25, a $ metavariable adds AST matches",
+            Some("reciprocal rank fusion relative to first place in every list, 0.0-1.0"),
+        ),
+        (
+            SearchMode::Ast,
+            "ast",
+            "--ast",
+            "Structural patterns with $METAVARIABLES, matched by ast-grep (must be installed)",
+            None,
+        ),
+        (
+            SearchMode::Symbol,
+            "symbols",
+            "--symbols",
+            "Definition names found by the tree-sitter chunker, printed as whole definitions",
+            None,
+        ),
+    ]
+    .into_iter()
+    .map(|(mode, name, flag, summary, scores)| ModeHelp {
+        mode: name,
+        flag,
+        summary,
+        index: match cs_engine::index_use(&mode) {
+            IndexUse::None => "none",
+            IndexUse::Chunks => "chunks",
+            IndexUse::Embeddings => "embeddings",
+        },
+        ranked: ranked(&mode),
+        default_top_k: default_top_k(&mode),
+        default_threshold: default_threshold(&mode),
+        scores,
+    })
+    .collect()
+}
+
+fn index_text(mode: &ModeHelp) -> &'static str {
+    match mode.index {
+        "none" => "none, files are read directly",
+        "chunks" => "built or updated before searching, without embeddings",
+        _ => "built or updated before searching, with embeddings",
+    }
+}
+
+fn results_text(mode: &ModeHelp) -> String {
+    if !mode.ranked {
+        return "every match".to_string();
+    }
+    let mut text = match mode.default_top_k {
+        Some(top_k) => format!("top {}", top_k),
+        None => "all".to_string(),
+    };
+    if let Some(threshold) = mode.default_threshold {
+        text.push_str(&format!(", score >= {}", threshold));
+    }
+    if let Some(scores) = mode.scores {
+        text.push_str(&format!("; scores: {}", scores));
+    }
+    text
+}
+
+const AUTO_TEXT: &str =
+    "--auto picks regex, semantic, hybrid or AST search from the shape of each query.";
+const OVERRIDES_TEXT: &str = "--topk and --threshold override the defaults; top_k and threshold in cs.toml replace them per repository.";
+
+/// `cs --help-modes` output
+pub fn render_text() -> String {
+    let mut out = String::from("SEARCH MODES\n");
+    for mode in modes() {
+        out.push_str(&format!(
+            "\n  {:<10} {}\n  {:<10} index: {}\n  {:<10} results: {}\n",
+            mode.flag,
+            mode.summary,
+            "",
+            index_text(&mode),
+            "",
+            results_text(&mode)
+        ));
+    }
+    out.push_str(&format!("\n{}\n{}\n", AUTO_TEXT, OVERRIDES_TEXT));
+    out
+}
+
+/// The same content as a man page section
+pub fn render_roff() -> String {
+    let mut out = String::from(".SH \"SEARCH MODES\"\n");
+    for mode in modes() {
+        out.push_str(&format!(
+            ".TP\n\\fB{}\\fR\n{}\n.br\nIndex: {}\n.br\nResults: {}\n",
+            roff_escape(mode.flag),
+            roff_escape(mode.summary),
+            roff_escape(index_text(&mode)),
+            roff_escape(&results_text(&mode))
+        ));
+    }
+    out.push_str(&format!(
+        ".PP\n{}\n.PP\n{}\n",
+        roff_escape(AUTO_TEXT),
+        roff_escape(OVERRIDES_TEXT)
+    ));
+    out
+}
+
+fn roff_escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes_follow_engine_and_defaults() {
+        let modes = modes();
+        let semantic = modes.iter().find(|mode| mode.flag == "--sem").unwrap();
+        assert_eq!(semantic.index, "embeddings");
+        assert_eq!(
+            results_text(semantic),
+            "top 10, score >= 0.6; scores: cosine similarity, 0.0-1.0"
+        );
+        let lexical = modes.iter().find(|mode| mode.flag == "--lex").unwrap();
+        assert_eq!(lexical.index, "chunks");
+        let regex = modes.iter().find(|mode| mode.flag == "--regex").unwrap();
+        assert_eq!((regex.index, regex.ranked), ("none", false));
+
+        let roff = render_roff();
+        assert!(roff.starts_with(".SH \"SEARCH MODES\""));
+        assert!(roff.contains("\\fB\\-\\-sem\\fR"));
+    }
+}
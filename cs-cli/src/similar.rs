@@ -0,0 +1,171 @@
+//! `cs --similar-to FILE:START-END`: search by example. The selected lines are chunked the way
+//! indexing chunks code and each chunk's embedded text becomes a semantic query pattern, so a
+//! result scores as its best match against any part of the example. Results overlapping the
+//! example itself are left out.
+
+use anyhow::{Context, Result};
+use cs_core::ExcludedSpan;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A file, optionally narrowed to a 1-based inclusive line range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimilarTo {
+    pub file: PathBuf,
+    pub lines: Option<(usize, usize)>,
+}
+
+impl FromStr for SimilarTo {
+    type Err = String;
+
+    /// `FILE`, `FILE:LINE` or `FILE:START-END`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let Some((file, range)) = value.rsplit_once(':').filter(|(file, range)| {
+            !file.is_empty()
+                && !range.is_empty()
+                && range.chars().all(|c| c.is_ascii_digit() || c == '-')
+        }) else {
+            return Ok(Self {
+                file: PathBuf::from(value),
+                lines: None,
+            });
+        };
+
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let line = |number: &str| number.parse::<usize>().ok().filter(|line| *line > 0);
+        match (line(start), line(end)) {
+            (Some(start), Some(end)) if start <= end => Ok(Self {
+                file: PathBuf::from(file),
+                lines: Some((start, end)),
+            }),
+            _ => Err(format!(
+                "invalid line range '{}': expected START-END with 1 <= START <= END",
+                range
+            )),
+        }
+    }
+}
+
+impl SimilarTo {
+    /// The example's lines, left out of the results
+    pub fn excluded_span(&self) -> ExcludedSpan {
+        let (line_start, line_end) = self.lines.unwrap_or((1, usize::MAX));
+        ExcludedSpan {
+            file: self
+                .file
+                .canonicalize()
+                .unwrap_or_else(|_| self.file.clone()),
+            line_start,
+            line_end,
+        }
+    }
+
+    /// The selected lines of the file
+    pub fn read(&self) -> Result<String> {
+        let content = cs_core::encoding::read_to_string(&self.file)
+            .with_context(|| format!("Failed to read {}", self.file.display()))?;
+        let Some((start, end)) = self.lines else {
+            return Ok(content);
+        };
+        let total = content.lines().count();
+        if start > total {
+            anyhow::bail!(
+                "{} has {} lines; --similar-to starts at line {}",
+                self.file.display(),
+                total,
+                start
+            );
+        }
+        let mut excerpt: String = content
+            .lines()
+            .skip(start - 1)
+            .take(end - start + 1)
+            .collect::<Vec<_>>()
+            .join("\n");
+        excerpt.push('\n');
+        Ok(excerpt)
+    }
+
+    /// Query patterns for the example: the embedded text of each of its chunks, or the whole
+    /// excerpt when it has no chunks
+    pub fn patterns(
+        &self,
+        model: Option<&str>,
+        overrides: &cs_chunk::ChunkOverrides,
+    ) -> Result<Vec<String>> {
+        let excerpt = self.read()?;
+        if excerpt.trim().is_empty() {
+            anyhow::bail!(
+                "--similar-to {}: the selected lines are empty",
+                self.file.display()
+            );
+        }
+        let language = cs_core::Language::from_path(&self.file);
+        let chunks = cs_chunk::chunk_text_with_overrides(&excerpt, language, model, overrides)
+            .unwrap_or_default();
+        let mut patterns: Vec<String> = chunks
+            .iter()
+            .map(|chunk| chunk.embedding_text().trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect();
+        patterns.dedup();
+        if patterns.is_empty() {
+            patterns.push(excerpt.trim().to_string());
+        }
+        Ok(patterns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_and_read_similar_to() {
+        let spec: SimilarTo = "src/lib.rs:40-80".parse().unwrap();
+        assert_eq!(spec.file, PathBuf::from("src/lib.rs"));
+        assert_eq!(spec.lines, Some((40, 80)));
+        assert_eq!(
+            "src/lib.rs:7".parse::<SimilarTo>().unwrap().lines,
+            Some((7, 7))
+        );
+        assert_eq!("src/lib.rs".parse::<SimilarTo>().unwrap().lines, None);
+        assert!("src/lib.rs:80-40".parse::<SimilarTo>().is_err());
+        assert!("src/lib.rs:0-3".parse::<SimilarTo>().is_err());
+
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("retry.rs");
+        std::fs::write(
+            &file,
+            "use std::time::Duration;\n\nfn retry() {\n    sleep(Duration::from_secs(1));\n}\n",
+        )
+        .unwrap();
+        let spec: SimilarTo = format!("{}:3-5", file.display()).parse().unwrap();
+        assert_eq!(
+            spec.read().unwrap(),
+            "fn retry() {\n    sleep(Duration::from_secs(1));\n}\n"
+        );
+        let patterns = spec
+            .patterns(None, &cs_chunk::ChunkOverrides::default())
+            .unwrap();
+        assert_eq!(
+            patterns,
+            ["fn retry() {\n    sleep(Duration::from_secs(1));\n}"]
+        );
+
+        let excluded = spec.excluded_span();
+        let span = |line_start, line_end| cs_core::Span {
+            byte_start: 0,
+            byte_end: 0,
+            line_start,
+            line_end,
+        };
+        assert!(excluded.overlaps(&file, &span(1, 3)));
+        assert!(!excluded.overlaps(&file, &span(1, 2)));
+        assert!(!excluded.overlaps(&temp_dir.path().join("other.rs"), &span(3, 5)));
+
+        let past_end: SimilarTo = format!("{}:9-12", file.display()).parse().unwrap();
+        assert!(past_end.read().is_err());
+    }
+}
@@ -0,0 +1,200 @@
+// Files changed in version control, for `--changed`, `--staged` and `--since REF`
+// Asks Jujutsu when the directory is in a jj workspace, git otherwise; deleted files are left out
+
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Which changes to search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeScope {
+    /// Uncommitted changes, staged or not, plus untracked files
+    WorkingTree,
+    /// Changes staged for the next commit (git only)
+    Staged,
+    /// Everything that differs from a revision, committed or not
+    Since(String),
+}
+
+impl ChangeScope {
+    /// The scope asked for on the command line, if any (clap keeps the flags exclusive)
+    pub fn from_flags(changed: bool, staged: bool, since: Option<&str>) -> Option<Self> {
+        if changed {
+            Some(Self::WorkingTree)
+        } else if staged {
+            Some(Self::Staged)
+        } else {
+            since.map(|rev| Self::Since(rev.to_string()))
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::WorkingTree => "changed".to_string(),
+            Self::Staged => "staged".to_string(),
+            Self::Since(rev) => format!("changed since {}", rev),
+        }
+    }
+}
+
+/// Existing files under `dir` that are changed in `scope`, sorted
+pub fn changed_files(dir: &Path, scope: &ChangeScope) -> Result<Vec<PathBuf>> {
+    let listings = if is_jj_workspace(dir) {
+        let args: Vec<&str> = match scope {
+            ChangeScope::WorkingTree => vec!["diff", "--name-only", "."],
+            ChangeScope::Staged => {
+                bail!("--staged needs git; Jujutsu has no staging area (use --changed)")
+            }
+            ChangeScope::Since(rev) => vec!["diff", "--name-only", "--from", rev, "."],
+        };
+        vec![run(dir, "jj", &args)?]
+    } else {
+        // `--relative` limits the diff to `dir` and prints paths relative to it, as ls-files does
+        let diff = |extra: &[&str]| {
+            let mut args = vec!["diff", "--name-only", "--relative", "--diff-filter=d"];
+            args.extend_from_slice(extra);
+            run(dir, "git", &args)
+        };
+        match scope {
+            ChangeScope::WorkingTree => vec![
+                diff(&["HEAD"])?,
+                run(dir, "git", &["ls-files", "--others", "--exclude-standard"])?,
+            ],
+            ChangeScope::Staged => vec![diff(&["--cached"])?],
+            ChangeScope::Since(rev) => vec![diff(&[rev.as_str()])?],
+        }
+    };
+
+    let mut files: Vec<PathBuf> = listings
+        .iter()
+        .flat_map(|listing| listing.lines())
+        .filter(|line| !line.is_empty())
+        .map(|line| dir.join(line))
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// The changed files among `targets` (directories and files; the current directory when empty)
+pub fn changed_in_targets(targets: &[PathBuf], scope: &ChangeScope) -> Result<Vec<PathBuf>> {
+    let current_dir = [PathBuf::from(".")];
+    let targets = if targets.is_empty() {
+        &current_dir[..]
+    } else {
+        targets
+    };
+    let mut files = Vec::new();
+    for target in targets {
+        if target.is_dir() {
+            files.extend(changed_files(target, scope)?);
+        } else {
+            let parent = match target.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            let changed = changed_files(parent, scope)?;
+            let target = target.canonicalize().unwrap_or_else(|_| target.clone());
+            files.extend(
+                changed
+                    .into_iter()
+                    .filter(|file| file.canonicalize().is_ok_and(|file| file == target)),
+            );
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn is_jj_workspace(dir: &Path) -> bool {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    dir.ancestors()
+        .any(|ancestor| ancestor.join(".jj").is_dir())
+}
+
+fn run(dir: &Path, program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("Failed to run {} (is it installed?)", program))?;
+    if !output.status.success() {
+        bail!(
+            "{} {} failed in {}: {}",
+            program,
+            args.join(" "),
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=cs", "-c", "user.email=cs@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_changed_files_from_git() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir(root.join("src")).unwrap();
+        for name in ["src/kept.rs", "src/edited.rs", "src/deleted.rs"] {
+            fs::write(root.join(name), "fn original() {}\n").unwrap();
+        }
+        git(root, &["init", "-q"]);
+        git(root, &["add", "."]);
+        git(root, &["commit", "-q", "-m", "initial"]);
+
+        fs::write(root.join("src/edited.rs"), "fn edited() {}\n").unwrap();
+        fs::remove_file(root.join("src/deleted.rs")).unwrap();
+        fs::write(root.join("src/new.rs"), "fn new() {}\n").unwrap();
+        fs::write(root.join("staged.rs"), "fn staged() {}\n").unwrap();
+        git(root, &["add", "staged.rs"]);
+
+        let names = |files: Vec<PathBuf>| -> Vec<String> {
+            files
+                .iter()
+                .map(|file| {
+                    let relative = file.strip_prefix(root).unwrap();
+                    relative.to_string_lossy().replace('\\', "/")
+                })
+                .collect()
+        };
+        assert_eq!(
+            names(changed_files(root, &ChangeScope::WorkingTree).unwrap()),
+            ["src/edited.rs", "src/new.rs", "staged.rs"]
+        );
+        assert_eq!(
+            names(changed_files(root, &ChangeScope::Staged).unwrap()),
+            ["staged.rs"]
+        );
+        // Scoped to a subdirectory
+        assert_eq!(
+            names(changed_files(&root.join("src"), &ChangeScope::WorkingTree).unwrap()),
+            ["src/edited.rs", "src/new.rs"]
+        );
+        assert_eq!(
+            names(changed_files(root, &ChangeScope::Since("HEAD".to_string())).unwrap()),
+            ["src/edited.rs", "staged.rs"]
+        );
+        assert!(changed_files(root, &ChangeScope::Since("no-such-ref".to_string())).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! `cs --vimgrep`: one `file:line:column:text` line per match, the format Vim's `grepformat`,
+//! Telescope's `vimgrep_arguments` and Emacs compile-mode parse. The column is the 1-based byte
+//! column where the match starts (the chunk start in ranked modes) and the text is the whole
+//! line. Lines are read back from the file, since previews may hold several lines or only the
+//! matched part; results that cannot be read back, such as stdin and archive entries, fall
+//! back to column 1 and the first line of the preview.
+
+use cs_core::SearchResult;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Formats results, reading each file at most once
+#[derive(Default)]
+pub struct VimgrepFormatter {
+    /// File contents as searched (UTF-8), `None` when unreadable
+    files: HashMap<PathBuf, Option<Vec<u8>>>,
+}
+
+impl VimgrepFormatter {
+    pub fn line(&mut self, result: &SearchResult) -> String {
+        let content = self
+            .files
+            .entry(result.file.clone())
+            .or_insert_with(|| cs_core::encoding::read(&result.file).ok());
+        let (column, text) = content
+            .as_deref()
+            .and_then(|content| locate(content, result.span.byte_start))
+            .unwrap_or_else(|| (1, result.preview.lines().next().unwrap_or("").to_string()));
+        format!(
+            "{}:{}:{}:{}",
+            result.file.display(),
+            result.span.line_start,
+            column,
+            text
+        )
+    }
+}
+
+/// 1-based byte column of `offset` and the text of the line holding it
+fn locate(content: &[u8], offset: usize) -> Option<(usize, String)> {
+    if offset > content.len() {
+        return None;
+    }
+    let line_start = content[..offset]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |newline| newline + 1);
+    let line_end = content[offset..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(content.len(), |newline| offset + newline);
+    let line = &content[line_start..line_end];
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    Some((
+        offset - line_start + 1,
+        String::from_utf8_lossy(line).into_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_columns_and_lines() {
+        let content = "fn main() {\r\n    let café = open();\r\n}".as_bytes();
+        let open = content.windows(4).position(|w| w == b"open").unwrap();
+        // Byte columns, as Vim counts them: "é" takes two
+        assert_eq!(
+            locate(content, open),
+            Some((17, "    let café = open();".to_string()))
+        );
+        assert_eq!(locate(content, 0), Some((1, "fn main() {".to_string())));
+        assert_eq!(
+            locate(content, content.len() - 1),
+            Some((1, "}".to_string()))
+        );
+        assert_eq!(locate(content, content.len() + 1), None);
+    }
+}
@@ -403,6 +403,319 @@ fn test_line_numbers() {
     assert!(stdout.contains("2:matched line"));
 }
 
+#[test]
+fn test_multiline_line_numbers() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("sig.rs");
+    fs::write(&file, "// header\nfn foo(a,\n  b) {\n}\n").unwrap();
+
+    // Every line of a multi-line match is numbered, like ripgrep
+    let output = Command::new(cs_binary())
+        .args(["-U", "-n", r"fn foo\(a,\n\s*b\)", file.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with -U -n");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["2:fn foo(a,", "3:  b) {"]);
+
+    // Context lines take `-`
+    let output = Command::new(cs_binary())
+        .args([
+            "-U",
+            "-n",
+            "-B",
+            "1",
+            r"fn foo\(a,\n\s*b\)",
+            file.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run cs with -U -n -B 1");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["1-// header", "2:fn foo(a,", "3:  b) {"]);
+}
+
+#[test]
+fn test_filename_flags() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = temp_dir.path().join("first.txt");
+    let second = temp_dir.path().join("second.txt");
+    fs::write(&first, "todo one\n").unwrap();
+    fs::write(&second, "todo two\n").unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(cs_binary())
+            .args(args)
+            .output()
+            .expect("Failed to run cs");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let (first, second) = (first.to_str().unwrap(), second.to_str().unwrap());
+
+    // One file: no name unless -H; several: named unless --no-filename
+    assert!(!run(&["todo", first]).contains("first.txt"));
+    assert!(run(&["-H", "todo", first]).contains("first.txt"));
+    assert!(run(&["--with-filename", "todo", first]).contains("first.txt"));
+    assert!(run(&["todo", first, second]).contains("second.txt"));
+    assert!(!run(&["--no-filename", "todo", first, second]).contains(".txt"));
+    assert!(!run(&["--group", "--no-filename", "todo", first, second]).contains(".txt"));
+
+    // The last of -H and --no-filename wins
+    assert!(run(&["--no-filename", "-H", "todo", first]).contains("first.txt"));
+    assert!(!run(&["-H", "--no-filename", "todo", first]).contains("first.txt"));
+}
+
+#[test]
+fn test_vimgrep_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.txt");
+    fs::write(&file, "first line\n  todo: a, todo: b\r\nlast todo\n").unwrap();
+
+    let output = Command::new(cs_binary())
+        .args(["--vimgrep", "--no-filename", "todo", file.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with --vimgrep");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // Every match on its own line with its column, and always the file name
+    let name = file.display().to_string();
+    let expected: Vec<String> = [
+        "2:3:  todo: a, todo: b",
+        "2:12:  todo: a, todo: b",
+        "3:6:last todo",
+    ]
+    .iter()
+    .map(|line| format!("{}:{}", name, line))
+    .collect();
+    assert_eq!(stdout.lines().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_count_matches() {
+    let temp_dir = TempDir::new().unwrap();
+    let counted = temp_dir.path().join("counted.txt");
+    let empty = temp_dir.path().join("empty.txt");
+    fs::write(&counted, "todo one\nnothing\ntodo two\ntodo three").unwrap();
+    fs::write(&empty, "no markers here").unwrap();
+
+    let output = Command::new(cs_binary())
+        .args(["-c", "todo", counted.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with --count");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.trim(), "3");
+
+    // Multiple files: file:count, with zero counts for named files without matches
+    let output = Command::new(cs_binary())
+        .args([
+            "--count",
+            "todo",
+            counted.to_str().unwrap(),
+            empty.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run cs with --count");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("counted.txt:3"));
+    assert!(stdout.contains("empty.txt:0"));
+
+    // -l takes precedence over -c, like grep
+    let output = Command::new(cs_binary())
+        .args([
+            "-c",
+            "-l",
+            "todo",
+            counted.to_str().unwrap(),
+            empty.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run cs with -c -l");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("counted.txt"));
+    assert!(!stdout.contains(":3"));
+    assert!(!stdout.contains("empty.txt"));
+}
+
+#[test]
+fn test_invert_match() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("invert.txt");
+    fs::write(&file, "alpha\nbeta match\ngamma\nmatch match").unwrap();
+
+    let output = Command::new(cs_binary())
+        .args(["-v", "-n", "match", file.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with -v");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["1:alpha", "3:gamma"]);
+
+    let output = Command::new(cs_binary())
+        .args(["-v", "-c", "match", file.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with -v -c");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), "2");
+
+    // Invert match is regex-only
+    let output = Command::new(cs_binary())
+        .args(["-v", "--sem", "match", file.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with -v --sem");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_multiple_patterns() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("multi.txt");
+    fs::write(&file, "alpha one\nbeta two\ngamma -x three\ndelta").unwrap();
+    let pattern_file = temp_dir.path().join("patterns.txt");
+    fs::write(&pattern_file, "beta\n-x\n").unwrap();
+
+    // With -e every positional argument is a path
+    let output = Command::new(cs_binary())
+        .args(["-n", "-e", "alpha", "-e", "delta", file.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with -e");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["1:alpha one", "4:delta"]);
+
+    // -f patterns combine with -e and keep leading dashes literal
+    let output = Command::new(cs_binary())
+        .args([
+            "-n",
+            "-F",
+            "-f",
+            pattern_file.to_str().unwrap(),
+            "-e",
+            "alpha",
+            file.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run cs with -f");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["1:alpha one", "2:beta two", "3:gamma -x three"]);
+}
+
+#[test]
+fn test_only_matching_and_replace() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("kv.txt");
+    fs::write(&file, "name: alice, role: admin\nplain line\n").unwrap();
+
+    let output = Command::new(cs_binary())
+        .args(["-o", r"\w+: \w+", file.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with -o");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines, vec!["name: alice", "role: admin"]);
+
+    // Both matches are rewritten on a single output line
+    let output = Command::new(cs_binary())
+        .args([
+            "--replace",
+            "$2=$1",
+            r"(\w+): (\w+)",
+            file.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run cs with --replace");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.trim(), "alice=name, admin=role");
+}
+
+#[test]
+fn test_search_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new(cs_binary())
+        .args(["-n", "-H", "error", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run cs on stdin");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"ok\nerror: disk full\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("(standard input)"));
+    assert!(stdout.contains("2:error: disk full"));
+}
+
+#[test]
+fn test_symbols_mode_prints_definitions() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("lib.py");
+    fs::write(
+        &file,
+        "def upload(path):\n    return send(path)\n\n\ndef send(path):\n    upload_log(path)\n",
+    )
+    .unwrap();
+
+    let output = Command::new(cs_binary())
+        .args(["--symbols", "-n", "upload", file.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with --symbols");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // The whole definition is printed, and the call inside `send` is not a definition
+    assert!(stdout.starts_with("1:def upload(path):"));
+    assert!(stdout.contains("return send(path)"));
+    assert!(!stdout.contains("upload_log"));
+}
+
+#[test]
+fn test_auto_mode_picks_regex_for_code() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("lib.rs");
+    fs::write(&file, "fn parse_header() {}\nfn other() {}").unwrap();
+
+    let output = Command::new(cs_binary())
+        .args(["--auto", "fn parse_header", file.to_str().unwrap()])
+        .output()
+        .expect("Failed to run cs with --auto");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Auto mode: regex"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.trim(), "fn parse_header() {}");
+}
+
 #[test]
 #[serial]
 fn test_clean_command() {
@@ -468,6 +781,24 @@ fn test_no_matches_stderr_message() {
     assert!(stderr.contains("No matches found"));
 }
 
+#[test]
+fn test_json_output_keeps_stderr_clean() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("test.txt"), "hello world").unwrap();
+
+    // Status messages would end up in the stream of a consumer capturing stderr too
+    for pattern in ["hello", "nonexistent_pattern"] {
+        for format in ["--json", "--jsonl"] {
+            let output = Command::new(cs_binary())
+                .args([format, pattern, temp_dir.path().to_str().unwrap()])
+                .output()
+                .expect("Failed to run cs");
+            let stderr = String::from_utf8(output.stderr).unwrap();
+            assert!(stderr.is_empty(), "{} {}: {}", format, pattern, stderr);
+        }
+    }
+}
+
 #[test]
 fn test_nonexistent_directory_error() {
     let output = Command::new(cs_binary())
@@ -3,7 +3,10 @@ use tempfile::TempDir;
 use tokio::fs;
 
 // Import from the main.rs module
-use cs_search::{CcMcpServer, HybridSearchRequest, RegexSearchRequest, SemanticSearchRequest};
+use cs_search::{
+    CcMcpServer, CleanIndexRequest, FileOutlineRequest, GetChunkRequest, HybridSearchRequest,
+    RegexSearchRequest, SemanticSearchRequest,
+};
 
 #[tokio::test]
 async fn test_mcp_semantic_search_basic_functionality() {
@@ -382,3 +385,147 @@ async fn test_mcp_semantic_search_with_missing_files() {
         assert!(summary.contains("top_k: 20"));
     }
 }
+
+#[tokio::test]
+async fn test_mcp_get_chunk_by_span_and_hash() {
+    let temp_dir = create_test_files().await;
+    let server = CcMcpServer::new(temp_dir.path().to_path_buf()).unwrap();
+    let path = temp_dir
+        .path()
+        .join("test3.py")
+        .to_string_lossy()
+        .to_string();
+    let request = |line_start, chunk_hash| GetChunkRequest {
+        path: path.clone(),
+        byte_start: None,
+        byte_end: None,
+        line_start,
+        line_end: None,
+        chunk_hash,
+        context_lines: Some(2),
+    };
+
+    // A line inside process_data resolves to the whole function
+    let (_, response) = server.handle_get_chunk(request(Some(3), None)).unwrap();
+    let chunk = &response["chunk"];
+    assert_eq!(response["source"], "chunker");
+    assert!(
+        chunk["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("def process_data")
+    );
+    assert_eq!(chunk["span"]["line_start"], 1);
+    assert_eq!(response["context"]["before"], "");
+    assert!(
+        response["context"]["after"]
+            .as_str()
+            .unwrap()
+            .contains("def handle_error")
+    );
+
+    // The same chunk again by a hash prefix
+    let hash = chunk["chunk_hash"].as_str().unwrap()[..12].to_string();
+    let (_, by_hash) = server.handle_get_chunk(request(None, Some(hash))).unwrap();
+    assert_eq!(by_hash["chunk"], response["chunk"]);
+
+    assert!(
+        server
+            .handle_get_chunk(request(None, Some("0000000000".to_string())))
+            .is_err()
+    );
+    assert!(server.handle_get_chunk(request(Some(99), None)).is_err());
+
+    let (_, whole) = server.handle_get_chunk(request(None, None)).unwrap();
+    assert_eq!(whole["source"], "file");
+    assert!(
+        whole["chunk"]["text"]
+            .as_str()
+            .unwrap()
+            .ends_with("print(f\"Error: {error}\")")
+    );
+}
+
+#[tokio::test]
+async fn test_mcp_file_outline() {
+    let temp_dir = create_test_files().await;
+    let server = CcMcpServer::new(temp_dir.path().to_path_buf()).unwrap();
+    let source = temp_dir.path().join("shapes.py");
+    fs::write(
+        &source,
+        "class Shape:\n    def area(self):\n        return 0\n\ndef make_shape():\n    return Shape()\n",
+    )
+    .await
+    .unwrap();
+
+    let (_, response) = server
+        .handle_file_outline(FileOutlineRequest {
+            path: source.to_string_lossy().to_string(),
+        })
+        .unwrap();
+    let symbols: Vec<_> = response["symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| {
+            (
+                symbol["name"].as_str().unwrap(),
+                symbol["kind"].as_str().unwrap(),
+                symbol["depth"].as_u64().unwrap(),
+                symbol["span"]["line_start"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        symbols,
+        vec![
+            ("Shape", "class", 0, 1),
+            ("area", "method", 1, 2),
+            ("make_shape", "function", 0, 5),
+        ]
+    );
+
+    // Plain text has no parser
+    let text = temp_dir.path().join("test4.txt");
+    assert!(
+        server
+            .handle_file_outline(FileOutlineRequest {
+                path: text.to_string_lossy().to_string(),
+            })
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_mcp_clean_index() {
+    let temp_dir = create_test_files().await;
+    let server = CcMcpServer::new(temp_dir.path().to_path_buf()).unwrap();
+    cs_index::smart_update_index(temp_dir.path(), false, true, &[])
+        .await
+        .unwrap();
+    let request = |orphans_only| CleanIndexRequest {
+        path: temp_dir.path().to_string_lossy().to_string(),
+        orphans_only: Some(orphans_only),
+    };
+
+    // Deleted files are dropped from the index, which otherwise stays
+    fs::remove_file(temp_dir.path().join("test4.txt"))
+        .await
+        .unwrap();
+    let (_, response) = server.handle_clean_index(request(true)).await.unwrap();
+    assert_eq!(
+        response["clean_result"]["cleanup_stats"]["orphaned_entries_removed"],
+        1
+    );
+    assert_eq!(
+        cs_index::get_index_stats(temp_dir.path())
+            .unwrap()
+            .total_files,
+        3
+    );
+
+    let (summary, response) = server.handle_clean_index(request(false)).await.unwrap();
+    assert!(summary.starts_with("Removed the index"));
+    assert_eq!(response["clean_result"]["had_index"], true);
+    assert!(!temp_dir.path().join(".cs").join("manifest.json").exists());
+}
@@ -0,0 +1,217 @@
+use crate::SearchOptions;
+use std::fmt;
+use std::str::FromStr;
+
+/// Position in a ranked result list (`--cursor`), tied to the search it came from and to the
+/// index epoch that search ran against.
+///
+/// Printed as `<offset>.<epoch>.<search hash>`. A cursor only continues the same search, and
+/// only while the index is unchanged, so each page is cut from the same ranking; `-` stands
+/// for searches that use no index (regex).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchCursor {
+    /// Results before the next page
+    pub offset: usize,
+    /// Epoch of the index the first page came from (`cs_index::index_epoch`)
+    pub index_epoch: Option<u64>,
+    search: String,
+}
+
+impl SearchCursor {
+    /// Cursor to the results after the first `offset` of the search described by `options`
+    pub fn new(options: &SearchOptions, offset: usize, index_epoch: Option<u64>) -> Self {
+        Self {
+            offset,
+            index_epoch,
+            search: search_hash(options),
+        }
+    }
+
+    /// Whether the cursor was issued for the search described by `options`
+    pub fn matches(&self, options: &SearchOptions) -> bool {
+        self.search == search_hash(options)
+    }
+}
+
+/// Hash of everything that decides the result set or the ranking; the page size (`top_k`) and
+/// output options may differ between pages
+fn search_hash(options: &SearchOptions) -> String {
+    let patterns: Vec<_> = options.patterns().collect();
+    let inputs: [&dyn fmt::Debug; 38] = [
+        &options.mode,
+        &patterns,
+        &options.path,
+        &options.include_patterns,
+        &options.exclude_patterns,
+        &options.respect_gitignore,
+        &options.file_limits,
+        &options.search_zip,
+        &options.threshold,
+        &options.case_insensitive,
+        &options.whole_word,
+        &options.fixed_string,
+        &options.pcre2,
+        &options.multiline,
+        &options.invert_match,
+        &options.languages,
+        &options.only_chunk_types,
+        &options.exclude_tests,
+        &options.excluded_span,
+        &options.within,
+        &options.embedding_model,
+        &options.hybrid_lexical,
+        &options.fusion_weights,
+        &options.expand_query,
+        &options.rerank,
+        &options.rerank_model,
+        &options.rerank_candidates,
+        &options.rerank_blend,
+        &options.diversity,
+        &options.sparse_weight,
+        &options.negative_queries,
+        &options.negative_weight,
+        &options.stride_pooling,
+        &options.dedupe,
+        &options.ast_pattern,
+        &options.ast_lang,
+        &options.ast_selector,
+        &options.ast_strictness,
+    ];
+    let mut hasher = blake3::Hasher::new();
+    for input in inputs {
+        hasher.update(format!("{:?}|", input).as_bytes());
+    }
+    hasher.finalize().to_hex()[..16].to_string()
+}
+
+impl fmt::Display for SearchCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index_epoch {
+            Some(epoch) => write!(f, "{}.{}.{}", self.offset, epoch, self.search),
+            None => write!(f, "{}.-.{}", self.offset, self.search),
+        }
+    }
+}
+
+impl FromStr for SearchCursor {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid cursor '{}'", value);
+        let mut parts = value.trim().split('.');
+        let (Some(offset), Some(epoch), Some(search), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if search.len() != 16 || !search.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            offset: offset.parse().map_err(|_| invalid())?,
+            index_epoch: match epoch {
+                "-" => None,
+                epoch => Some(epoch.parse().map_err(|_| invalid())?),
+            },
+            search: search.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let options = SearchOptions {
+            query: "retry".to_string(),
+            top_k: Some(10),
+            ..Default::default()
+        };
+        let cursor = SearchCursor::new(&options, 10, Some(1_700_000_000));
+        let parsed: SearchCursor = cursor.to_string().parse().unwrap();
+        assert_eq!(parsed, cursor);
+        assert_eq!(parsed.offset, 10);
+
+        // Another page size continues the same search; another query does not
+        let next_page = SearchOptions {
+            top_k: Some(25),
+            ..options.clone()
+        };
+        assert!(parsed.matches(&next_page));
+        let other = SearchOptions {
+            query: "backoff".to_string(),
+            ..options
+        };
+        assert!(!parsed.matches(&other));
+
+        let regex = SearchCursor::new(&other, 5, None);
+        assert!(regex.to_string().starts_with("5.-."));
+        assert_eq!(regex.to_string().parse::<SearchCursor>().unwrap(), regex);
+        assert!("10.abc".parse::<SearchCursor>().is_err());
+        assert!("x.-.0123456789abcdef".parse::<SearchCursor>().is_err());
+    }
+
+    #[test]
+    fn test_cursor_rejects_other_results_or_ranking() {
+        use crate::within::ResultScope;
+        use crate::{ExcludedSpan, FusionWeights, IncludePattern};
+        use std::path::PathBuf;
+        use std::sync::Arc;
+
+        let options = SearchOptions {
+            query: "retry".to_string(),
+            top_k: Some(10),
+            ..Default::default()
+        };
+        let cursor = SearchCursor::new(&options, 10, Some(1));
+        let variants = [
+            SearchOptions {
+                include_patterns: vec![IncludePattern {
+                    path: PathBuf::from("c.rs"),
+                    is_dir: false,
+                }],
+                ..options.clone()
+            },
+            SearchOptions {
+                fusion_weights: FusionWeights {
+                    lexical: 2.0,
+                    ..Default::default()
+                },
+                ..options.clone()
+            },
+            SearchOptions {
+                rerank_candidates: Some(100),
+                ..options.clone()
+            },
+            SearchOptions {
+                rerank_blend: Some(0.5),
+                ..options.clone()
+            },
+            SearchOptions {
+                excluded_span: Some(ExcludedSpan {
+                    file: PathBuf::from("/repo/a.rs"),
+                    line_start: 1,
+                    line_end: 9,
+                }),
+                ..options.clone()
+            },
+            SearchOptions {
+                embedding_model: Some("bge-small".to_string()),
+                ..options.clone()
+            },
+            SearchOptions {
+                within: Some(Arc::new(ResultScope::parse("/repo/a.rs\n").unwrap())),
+                ..options.clone()
+            },
+            SearchOptions {
+                invert_match: true,
+                ..options.clone()
+            },
+        ];
+        for variant in &variants {
+            assert!(!cursor.matches(variant), "{:?}", variant);
+        }
+    }
+}
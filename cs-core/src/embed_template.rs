@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How the text embedded for a chunk is composed (`--embed-template`, `embed_template` in
+/// `cs.toml`), e.g. `{path} :: {symbol} :: {code}`.
+///
+/// `{path}` is the file path relative to the index root, `{symbol}` the chunk's definition
+/// (`Class::method`) and `{code}` the chunk text. A placeholder with no value is dropped
+/// together with the text that follows it, so chunks outside any definition embed as
+/// `path :: code`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct EmbedTemplate {
+    template: String,
+    pieces: Vec<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Path,
+    Symbol,
+    Code,
+}
+
+impl EmbedTemplate {
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// The text to embed for `code` from file `path`, defining `symbol`
+    pub fn render(&self, path: &str, symbol: Option<&str>, code: &str) -> String {
+        let mut text = String::with_capacity(path.len() + code.len() + 32);
+        let mut skip_text = false;
+        for piece in &self.pieces {
+            let value = match piece {
+                Piece::Text(literal) => {
+                    if !skip_text {
+                        text.push_str(literal);
+                    }
+                    continue;
+                }
+                Piece::Path => path,
+                Piece::Symbol => symbol.unwrap_or(""),
+                Piece::Code => code,
+            };
+            text.push_str(value);
+            skip_text = value.is_empty();
+        }
+        text
+    }
+}
+
+impl FromStr for EmbedTemplate {
+    type Err = String;
+
+    /// Parse a template; it must contain `{code}` and no other placeholders than
+    /// `{path}` and `{symbol}`
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut pieces = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                pieces.push(Piece::Text(rest[..open].to_string()));
+            }
+            let close = rest[open..]
+                .find('}')
+                .ok_or_else(|| format!("invalid embed template '{}': unclosed '{{'", template))?
+                + open;
+            pieces.push(match &rest[open + 1..close] {
+                "path" => Piece::Path,
+                "symbol" => Piece::Symbol,
+                "code" => Piece::Code,
+                other => {
+                    return Err(format!(
+                        "invalid embed template '{}': unknown placeholder '{{{}}}' (use {{path}}, {{symbol}} and {{code}})",
+                        template, other
+                    ));
+                }
+            });
+            rest = &rest[close + 1..];
+        }
+        if !rest.is_empty() {
+            pieces.push(Piece::Text(rest.to_string()));
+        }
+        if !pieces.contains(&Piece::Code) {
+            return Err(format!(
+                "invalid embed template '{}': it must include {{code}}",
+                template
+            ));
+        }
+        Ok(Self {
+            template: template.to_string(),
+            pieces,
+        })
+    }
+}
+
+impl TryFrom<String> for EmbedTemplate {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        template.parse()
+    }
+}
+
+impl From<EmbedTemplate> for String {
+    fn from(template: EmbedTemplate) -> Self {
+        template.template
+    }
+}
+
+impl fmt::Display for EmbedTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let template: EmbedTemplate = "{path} :: {symbol} :: {code}".parse().unwrap();
+        assert_eq!(
+            template.render(
+                "src/auth/middleware.rs",
+                Some("Auth::check"),
+                "fn check() {}"
+            ),
+            "src/auth/middleware.rs :: Auth::check :: fn check() {}"
+        );
+        // An empty symbol takes its separator with it
+        assert_eq!(
+            template.render("notes.md", None, "# Notes"),
+            "notes.md :: # Notes"
+        );
+
+        assert!("{path} {symbol}".parse::<EmbedTemplate>().is_err());
+        assert!("{file}: {code}".parse::<EmbedTemplate>().is_err());
+        assert!("{path: {code}".parse::<EmbedTemplate>().is_err());
+
+        let json = serde_json::to_string(&template).unwrap();
+        assert_eq!(json, "\"{path} :: {symbol} :: {code}\"");
+        assert_eq!(
+            serde_json::from_str::<EmbedTemplate>(&json).unwrap(),
+            template
+        );
+        assert!(serde_json::from_str::<EmbedTemplate>("\"{path}\"").is_err());
+    }
+}
@@ -0,0 +1,341 @@
+//! Text in encodings other than UTF-8. Legacy codebases keep sources in Windows-1252 and
+//! tools write UTF-16 logs; those files are transcoded to UTF-8 before they are searched,
+//! chunked or indexed. The encoding is judged from the first bytes of a file: a byte order
+//! mark, the zero bytes of UTF-16 text, or, for bytes that are not UTF-8, a statistical guess.
+//!
+//! Regex matches map their spans back to offsets in the file's own bytes
+//! (`Transcoded::source_offset`); chunk spans from indexing are offsets into the UTF-8 text.
+//! Line numbers are the same either way.
+
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use std::borrow::Cow;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of a file to judge its encoding
+pub const SAMPLE_SIZE: usize = 8192;
+
+/// Encoding of text that is not UTF-8, judged from its first bytes; `None` for UTF-8,
+/// including UTF-8 with a few invalid bytes
+pub fn detect(sample: &[u8]) -> Option<&'static Encoding> {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return (encoding != UTF_8).then_some(encoding);
+    }
+    if let Some(encoding) = utf16_without_bom(sample) {
+        return Some(encoding);
+    }
+    // Any valid multi-byte sequence means UTF-8 with damage, not another encoding
+    let (invalid, multibyte) = utf8_shape(sample);
+    if !invalid || multibyte || !high_bytes_in_words(sample) {
+        return None;
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(sample, true);
+    let encoding = detector.guess(None, false);
+    (encoding != UTF_8).then_some(encoding)
+}
+
+/// Whether the sample is UTF-16 text, whose zero bytes would otherwise mark it binary
+pub fn is_utf16(sample: &[u8]) -> bool {
+    detect(sample).is_some_and(|encoding| encoding == UTF_16LE || encoding == UTF_16BE)
+}
+
+/// Text of a whole file: UTF-8 as is, with invalid sequences replaced by U+FFFD, and other
+/// encodings transcoded
+pub fn decode(bytes: &[u8]) -> Cow<'_, str> {
+    match detect(sample(bytes)) {
+        Some(encoding) => encoding.decode(bytes).0,
+        None => String::from_utf8_lossy(bytes),
+    }
+}
+
+/// The UTF-8 text of `bytes` when they are in another encoding
+pub fn transcode(bytes: &[u8]) -> Option<String> {
+    let encoding = detect(sample(bytes))?;
+    Some(encoding.decode(bytes).0.into_owned())
+}
+
+/// Text transcoded from another encoding, with the way back to offsets in the original bytes
+#[derive(Debug, Clone)]
+pub struct Transcoded {
+    pub text: String,
+    /// Where each run of characters with the same encoded and UTF-8 lengths starts
+    steps: Vec<Step>,
+    source_len: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    utf8_start: usize,
+    source_start: usize,
+    utf8_len: usize,
+    source_len: usize,
+}
+
+impl Transcoded {
+    /// Offset in the original bytes of the character at `offset` in `text`
+    pub fn source_offset(&self, offset: usize) -> usize {
+        if offset >= self.text.len() {
+            return self.source_len;
+        }
+        let index = self.steps.partition_point(|step| step.utf8_start <= offset);
+        let Some(step) = index.checked_sub(1).map(|index| self.steps[index]) else {
+            return 0;
+        };
+        step.source_start + (offset - step.utf8_start) / step.utf8_len * step.source_len
+    }
+}
+
+/// `transcode`, keeping track of which bytes each character was decoded from
+pub fn transcode_mapped(bytes: &[u8]) -> Option<Transcoded> {
+    let encoding = detect(sample(bytes))?;
+    // A byte order mark is not part of the text but still counts in the offsets
+    let (encoding, bom_len) = Encoding::for_bom(bytes).unwrap_or((encoding, 0));
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text = String::with_capacity(bytes.len());
+    let mut steps: Vec<Step> = Vec::new();
+    let mut char_start = bom_len;
+    // One byte at a time, so each character's bytes are known when it comes out
+    for end in bom_len..=bytes.len() {
+        let last = end == bytes.len();
+        let input = if last { &[][..] } else { &bytes[end..=end] };
+        let consumed = end + input.len();
+        let utf8_start = text.len();
+        text.reserve(decoder.max_utf8_buffer_length(input.len()).unwrap_or(16));
+        let _ = decoder.decode_to_string(input, &mut text, last);
+        if text.len() == utf8_start {
+            continue;
+        }
+        let step = Step {
+            utf8_start,
+            source_start: char_start,
+            utf8_len: text.len() - utf8_start,
+            source_len: consumed - char_start,
+        };
+        let same_run = steps
+            .last()
+            .is_some_and(|run| run.utf8_len == step.utf8_len && run.source_len == step.source_len);
+        if !same_run {
+            steps.push(step);
+        }
+        char_start = consumed;
+    }
+    Some(Transcoded {
+        text,
+        steps,
+        source_len: bytes.len(),
+    })
+}
+
+/// The bytes as UTF-8: transcoded when in another encoding, unchanged otherwise
+pub fn into_utf8(bytes: Vec<u8>) -> Vec<u8> {
+    match transcode(&bytes) {
+        Some(text) => text.into_bytes(),
+        None => bytes,
+    }
+}
+
+/// `fs::read` that transcodes files in other encodings to UTF-8
+pub fn read(path: &Path) -> std::io::Result<Vec<u8>> {
+    Ok(into_utf8(std::fs::read(path)?))
+}
+
+/// `fs::read_to_string` that transcodes files in other encodings; invalid UTF-8 is still an
+/// error
+pub fn read_to_string(path: &Path) -> std::io::Result<String> {
+    let bytes = std::fs::read(path)?;
+    if let Some(text) = transcode(&bytes) {
+        return Ok(text);
+    }
+    String::from_utf8(bytes).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Encoding of the file at `path`, from its first `SAMPLE_SIZE` bytes
+pub fn detect_file(path: &Path) -> std::io::Result<Option<&'static Encoding>> {
+    let mut buffer = Vec::with_capacity(SAMPLE_SIZE);
+    std::fs::File::open(path)?
+        .take(SAMPLE_SIZE as u64)
+        .read_to_end(&mut buffer)?;
+    Ok(detect(&buffer))
+}
+
+fn sample(bytes: &[u8]) -> &[u8] {
+    &bytes[..bytes.len().min(SAMPLE_SIZE)]
+}
+
+/// UTF-16 without a byte order mark: text in the ASCII range has a zero high byte in most
+/// code units and almost never a zero low byte
+fn utf16_without_bom(sample: &[u8]) -> Option<&'static Encoding> {
+    let units = sample.len() / 2;
+    if units < 2 {
+        return None;
+    }
+    let zeros = |offset: usize| {
+        sample
+            .chunks_exact(2)
+            .filter(|unit| unit[offset] == 0)
+            .count()
+    };
+    let (even, odd) = (zeros(0), zeros(1));
+    let encoding = if odd * 2 > units && even * 20 < units {
+        UTF_16LE
+    } else if even * 2 > units && odd * 20 < units {
+        UTF_16BE
+    } else {
+        return None;
+    };
+    // Binary data with the same pattern decodes to control characters
+    let (text, _) = encoding.decode_without_bom_handling(sample);
+    let controls = text
+        .chars()
+        .filter(|c| c.is_control() && !c.is_whitespace())
+        .count();
+    (controls == 0).then_some(encoding)
+}
+
+/// Whether most runs of non-ASCII bytes sit in words, as the accented or non-Latin letters of
+/// a legacy encoding do (`caf\xe9`, a word in Cyrillic), rather than alone between spaces and
+/// punctuation like damage in UTF-8 text
+fn high_bytes_in_words(sample: &[u8]) -> bool {
+    let (mut runs, mut in_words) = (0, 0);
+    let mut i = 0;
+    while i < sample.len() {
+        if sample[i].is_ascii() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < sample.len() && !sample[i].is_ascii() {
+            i += 1;
+        }
+        let beside_word = start
+            .checked_sub(1)
+            .is_some_and(|before| sample[before].is_ascii_alphanumeric())
+            || sample.get(i).is_some_and(u8::is_ascii_alphanumeric);
+        runs += 1;
+        if beside_word || i - start >= 3 {
+            in_words += 1;
+        }
+    }
+    in_words * 2 > runs
+}
+
+/// Whether the bytes hold invalid UTF-8, and whether they hold valid multi-byte sequences; a
+/// sequence cut off at the end of the sample counts as neither
+fn utf8_shape(mut bytes: &[u8]) -> (bool, bool) {
+    let (mut invalid, mut multibyte) = (false, false);
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => {
+                multibyte |= !text.is_ascii();
+                return (invalid, multibyte);
+            }
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                multibyte |= !valid.is_ascii();
+                let Some(len) = e.error_len() else {
+                    return (invalid, multibyte);
+                };
+                invalid = true;
+                bytes = &rest[len..];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn test_utf16_with_and_without_bom() {
+        let text = "2024-01-01 12:00:00 service started\r\nconnection refused\r\n";
+        let plain = utf16le(text);
+        let mut with_bom = vec![0xFF, 0xFE];
+        with_bom.extend(&plain);
+        let big_endian: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+
+        assert_eq!(detect(&with_bom), Some(UTF_16LE));
+        assert_eq!(detect(&plain), Some(UTF_16LE));
+        assert_eq!(detect(&big_endian), Some(UTF_16BE));
+        assert!(is_utf16(&plain));
+        assert_eq!(decode(&with_bom), text);
+        assert_eq!(decode(&plain), text);
+
+        // Zero-padded binary data is not text
+        let binary: Vec<u8> = (1u8..=64).flat_map(|b| [b, 0]).collect();
+        assert!(!is_utf16(&binary));
+    }
+
+    #[test]
+    fn test_legacy_encodings_are_transcoded() {
+        // "Café résumé naïve" in Windows-1252
+        let latin1 = b"// Caf\xe9 r\xe9sum\xe9 na\xefve\nlet total = 0;\n";
+        assert_eq!(detect(latin1), Some(encoding_rs::WINDOWS_1252));
+        assert_eq!(
+            transcode(latin1).as_deref(),
+            Some("// Café résumé naïve\nlet total = 0;\n")
+        );
+    }
+
+    #[test]
+    fn test_utf8_is_left_alone() {
+        assert_eq!(detect(b"plain ascii"), None);
+        assert_eq!(detect("déjà vu".as_bytes()), None);
+        assert_eq!(transcode("déjà vu".as_bytes()), None);
+        // A stray invalid byte in otherwise UTF-8 text stays UTF-8, decoded lossily
+        let damaged = b"d\xc3\xa9j\xc3\xa0 vu \xff";
+        assert_eq!(detect(damaged), None);
+        assert_eq!(decode(damaged), "déjà vu \u{FFFD}");
+        // A multi-byte sequence cut off by the sample boundary is not damage
+        assert_eq!(detect(&"abc é".as_bytes()[..5]), None);
+        assert_eq!(detect(b"\xef\xbb\xbfwith bom"), None);
+        // Bytes standing alone between words are damage, even beside a Latin-1 letter
+        assert_eq!(detect(b"caf\xe9 menu\r\nbad \xff\xfe error here\n"), None);
+    }
+
+    #[test]
+    fn test_transcoded_offsets_point_at_source_bytes() {
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend(utf16le("ab\n\u{e9}rr \u{1F600}!"));
+        let transcoded = transcode_mapped(&utf16).unwrap();
+        assert_eq!(transcoded.text, "ab\n\u{e9}rr \u{1F600}!");
+        // Past the byte order mark, two bytes per character and four for the emoji
+        assert_eq!(transcoded.source_offset(0), 2);
+        assert_eq!(transcoded.source_offset(3), 8);
+        assert_eq!(transcoded.source_offset(5), 10);
+        assert_eq!(transcoded.source_offset(8), 16);
+        assert_eq!(transcoded.source_offset(12), 20);
+        assert_eq!(transcoded.source_offset(13), utf16.len());
+
+        let latin1 = b"caf\xe9 ok";
+        let transcoded = transcode_mapped(latin1).unwrap();
+        assert_eq!(transcoded.text, "café ok");
+        assert_eq!(transcoded.source_offset(3), 3);
+        assert_eq!(transcoded.source_offset(6), 5);
+        assert_eq!(
+            transcoded.source_offset(transcoded.text.len()),
+            latin1.len()
+        );
+        assert!(transcode_mapped(b"plain").is_none());
+    }
+
+    #[test]
+    fn test_read_to_string_transcodes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("app.log");
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(utf16le("error: disk full\n"));
+        std::fs::write(&log, bytes).unwrap();
+        assert_eq!(read_to_string(&log).unwrap(), "error: disk full\n");
+        assert_eq!(detect_file(&log).unwrap(), Some(UTF_16LE));
+
+        let damaged = dir.path().join("damaged.txt");
+        std::fs::write(&damaged, b"d\xc3\xa9j\xc3\xa0 \xff").unwrap();
+        assert!(read_to_string(&damaged).is_err());
+    }
+}
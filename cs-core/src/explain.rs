@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How a result's score was computed (`--explain`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreExplanation {
+    /// Cosine similarity between the chunk and its best-matching query pattern.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cosine: Option<f32>,
+    /// Normalized BM25 term-vector score (0..=1) fused into `cosine` for `--sparse` indexes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse: Option<f32>,
+    /// Closeness to the nearest `--not` phrase relative to the other chunks (0..=1); the score
+    /// was lowered in proportion to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative: Option<f32>,
+    /// 1-based position in the BM25 lexical results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lexical_rank: Option<usize>,
+    /// Raw BM25 score behind `lexical_rank`, before it was calibrated to 0..=1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lexical_score: Option<f32>,
+    /// Contributions of each ranked list to a hybrid result's fused RRF score.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rrf: Vec<RrfComponent>,
+    /// Score change from cross-encoder reranking.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerank: Option<RerankDelta>,
+    /// Kind of the matched chunk ("function", "class", "method", "module").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_type: Option<String>,
+    /// Which stride matched when a large chunk was embedded in overlapping windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stride: Option<StrideMatch>,
+}
+
+/// One ranked list's share of a fused hybrid score: `1 / (60 + rank)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RrfComponent {
+    /// "regex", "semantic", "lexical" or "ast".
+    pub list: String,
+    /// 1-based rank of the result in that list.
+    pub rank: usize,
+    pub contribution: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RerankDelta {
+    /// First-stage score (cosine similarity or RRF).
+    pub before: f32,
+    /// Score after reranking (cross-encoder score, or the `--rerank-blend` mix).
+    pub after: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrideMatch {
+    /// 0-based index of the matching stride.
+    pub index: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for ScoreExplanation {
+    /// One line, e.g. `cosine 0.734 · rrf regex #3 0.0159 + semantic #1 0.0164 · function`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(cosine) = self.cosine {
+            parts.push(format!("cosine {:.3}", cosine));
+        }
+        if let Some(sparse) = self.sparse {
+            parts.push(format!("sparse {:.2}", sparse));
+        }
+        if let Some(negative) = self.negative {
+            parts.push(format!("not {:.2}", negative));
+        }
+        if let Some(rank) = self.lexical_rank {
+            match self.lexical_score {
+                Some(score) => parts.push(format!("lexical #{} (bm25 {:.2})", rank, score)),
+                None => parts.push(format!("lexical #{}", rank)),
+            }
+        }
+        if !self.rrf.is_empty() {
+            let components: Vec<String> = self
+                .rrf
+                .iter()
+                .map(|c| format!("{} #{} {:.4}", c.list, c.rank, c.contribution))
+                .collect();
+            parts.push(format!("rrf {}", components.join(" + ")));
+        }
+        if let Some(rerank) = self.rerank {
+            parts.push(format!(
+                "rerank {:.3} -> {:.3} ({:+.3})",
+                rerank.before,
+                rerank.after,
+                rerank.after - rerank.before
+            ));
+        }
+        if let Some(chunk_type) = &self.chunk_type {
+            parts.push(chunk_type.clone());
+        }
+        if let Some(stride) = self.stride {
+            parts.push(format!("stride {}/{}", stride.index + 1, stride.total));
+        }
+        if parts.is_empty() {
+            return write!(f, "exact match");
+        }
+        write!(f, "{}", parts.join(" · "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explanation_display_and_json() {
+        let explanation = ScoreExplanation {
+            cosine: Some(0.7341),
+            sparse: Some(0.5),
+            rrf: vec![
+                RrfComponent {
+                    list: "regex".to_string(),
+                    rank: 3,
+                    contribution: 1.0 / 63.0,
+                },
+                RrfComponent {
+                    list: "semantic".to_string(),
+                    rank: 1,
+                    contribution: 1.0 / 61.0,
+                },
+            ],
+            rerank: Some(RerankDelta {
+                before: 0.5,
+                after: 0.75,
+            }),
+            chunk_type: Some("function".to_string()),
+            stride: Some(StrideMatch { index: 1, total: 4 }),
+            ..Default::default()
+        };
+        assert_eq!(
+            explanation.to_string(),
+            "cosine 0.734 · sparse 0.50 · rrf regex #3 0.0159 + semantic #1 0.0164 · rerank 0.500 -> 0.750 (+0.250) · function · stride 2/4"
+        );
+        assert_eq!(ScoreExplanation::default().to_string(), "exact match");
+
+        // Unset parts are left out of JSON
+        let json = serde_json::to_value(&explanation).unwrap();
+        assert!(json.get("lexical_rank").is_none());
+        assert_eq!(json["stride"]["total"], 4);
+    }
+}
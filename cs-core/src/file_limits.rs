@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Largest file chunked and embedded when indexing, unless `--max-filesize` says otherwise.
+/// Regex search reads files of any size by default.
+pub const DEFAULT_MAX_EMBED_FILESIZE: u64 = 2 * 1024 * 1024;
+
+/// Caps on the files one search or indexing run reads (`--max-filesize`, `--max-files`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct FileLimits {
+    /// Files larger than this many bytes are skipped.
+    pub max_filesize: Option<u64>,
+    /// Files beyond this count are skipped, in walk order.
+    pub max_files: Option<usize>,
+}
+
+/// Files left out by `FileLimits::apply`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SkippedFiles {
+    /// Over `max_filesize`.
+    pub too_large: usize,
+    /// Past `max_files`.
+    pub over_count: usize,
+}
+
+impl FileLimits {
+    /// These limits, with `bytes` as the size cap when none was given.
+    pub fn or_max_filesize(self, bytes: u64) -> Self {
+        Self {
+            max_filesize: self.max_filesize.or(Some(bytes)),
+            ..self
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_filesize.is_none() && self.max_files.is_none()
+    }
+
+    /// Drop files over the size cap, then keep at most `max_files` of the rest.
+    /// Files whose size can't be read are kept; reading them reports the error.
+    pub fn apply(&self, files: Vec<PathBuf>) -> (Vec<PathBuf>, SkippedFiles) {
+        let mut skipped = SkippedFiles::default();
+        let mut files: Vec<PathBuf> = match self.max_filesize {
+            Some(max) => files
+                .into_iter()
+                .filter(|file| {
+                    let too_large = std::fs::metadata(file).is_ok_and(|meta| meta.len() > max);
+                    skipped.too_large += usize::from(too_large);
+                    !too_large
+                })
+                .collect(),
+            None => files,
+        };
+        if let Some(max) = self.max_files
+            && files.len() > max
+        {
+            skipped.over_count = files.len() - max;
+            files.truncate(max);
+        }
+        (files, skipped)
+    }
+}
+
+impl SkippedFiles {
+    pub fn total(&self) -> usize {
+        self.too_large + self.over_count
+    }
+
+    /// One line per limit that dropped files, naming the flag that raises it.
+    pub fn warnings(&self, limits: &FileLimits) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.too_large > 0 {
+            warnings.push(format!(
+                "Skipped {} file{} larger than {} (raise with --max-filesize)",
+                self.too_large,
+                if self.too_large == 1 { "" } else { "s" },
+                format_filesize(limits.max_filesize.unwrap_or_default())
+            ));
+        }
+        if self.over_count > 0 {
+            warnings.push(format!(
+                "Stopped at {} files; skipped {} more (raise with --max-files)",
+                limits.max_files.unwrap_or_default(),
+                self.over_count
+            ));
+        }
+        warnings
+    }
+}
+
+/// Parse a size such as `2MB`, `512K`, `1.5m` or `4096` (bytes); units are powers of 1024.
+pub fn parse_filesize(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split_at = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split_at);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}': expected e.g. 512K, 2MB, 1G", value))?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1024,
+        "m" | "mb" => 1024 * 1024,
+        "g" | "gb" => 1024 * 1024 * 1024,
+        other => {
+            return Err(format!("invalid size unit '{}': use B, K, M or G", other));
+        }
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// `2.0 MB`, `512 KB`, `100 B`
+pub fn format_filesize(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = 1024 * KB;
+    const GB: u64 = 1024 * MB;
+    match bytes {
+        b if b >= GB => format!("{:.1} GB", b as f64 / GB as f64),
+        b if b >= MB => format!("{:.1} MB", b as f64 / MB as f64),
+        b if b >= KB => format!("{} KB", b / KB),
+        b => format!("{} B", b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_filesize() {
+        assert_eq!(parse_filesize("4096"), Ok(4096));
+        assert_eq!(parse_filesize("512K"), Ok(512 * 1024));
+        assert_eq!(parse_filesize("2MB"), Ok(2 * 1024 * 1024));
+        assert_eq!(parse_filesize("1.5m"), Ok(1024 * 1024 * 3 / 2));
+        assert_eq!(parse_filesize("1G"), Ok(1024 * 1024 * 1024));
+        assert!(parse_filesize("big").is_err());
+        assert!(parse_filesize("2TB").is_err());
+        assert_eq!(format_filesize(DEFAULT_MAX_EMBED_FILESIZE), "2.0 MB");
+    }
+
+    #[test]
+    fn test_apply_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let files: Vec<PathBuf> = [
+            ("a.txt", 10),
+            ("big.log", 5000),
+            ("b.txt", 10),
+            ("c.txt", 10),
+        ]
+        .iter()
+        .map(|(name, len)| {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, "x".repeat(*len)).unwrap();
+            path
+        })
+        .collect();
+
+        let (kept, skipped) = FileLimits::default().apply(files.clone());
+        assert_eq!(kept, files);
+        assert_eq!(skipped.total(), 0);
+
+        let limits = FileLimits {
+            max_filesize: Some(1024),
+            max_files: Some(2),
+        };
+        let (kept, skipped) = limits.apply(files.clone());
+        assert_eq!(kept, vec![files[0].clone(), files[2].clone()]);
+        assert_eq!(
+            skipped,
+            SkippedFiles {
+                too_large: 1,
+                over_count: 1
+            }
+        );
+        let warnings = skipped.warnings(&limits);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("1 file larger than 1 KB"));
+    }
+}
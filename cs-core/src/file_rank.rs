@@ -0,0 +1,169 @@
+use crate::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// How chunk scores combine into one score per file (`--files-ranked`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileAggregation {
+    /// Best chunk in the file.
+    #[default]
+    Max,
+    /// Average over the file's matching chunks.
+    Mean,
+    /// Best chunk plus halving contributions from the next best, so several strong hits
+    /// outrank a single one of the same score.
+    Weighted,
+}
+
+impl FromStr for FileAggregation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "max" => Ok(Self::Max),
+            "mean" => Ok(Self::Mean),
+            "weighted" => Ok(Self::Weighted),
+            other => Err(format!(
+                "unknown aggregation '{}'; expected max, mean or weighted",
+                other
+            )),
+        }
+    }
+}
+
+/// A file and its aggregated relevance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankedFile {
+    pub path: PathBuf,
+    pub score: f32,
+    /// Number of matching chunks the score was built from.
+    pub chunks: usize,
+}
+
+/// Aggregate chunk results per file, best file first (ties keep first-seen order).
+pub fn rank_files(results: &[SearchResult], aggregation: FileAggregation) -> Vec<RankedFile> {
+    let mut order: Vec<PathBuf> = Vec::new();
+    let mut scores: HashMap<&PathBuf, Vec<f32>> = HashMap::new();
+    for result in results {
+        let file_scores = scores.entry(&result.file).or_default();
+        if file_scores.is_empty() {
+            order.push(result.file.clone());
+        }
+        file_scores.push(result.score);
+    }
+
+    let mut ranked: Vec<RankedFile> = order
+        .into_iter()
+        .map(|path| {
+            let mut file_scores = scores.remove(&path).unwrap_or_default();
+            file_scores.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+            let score = match aggregation {
+                FileAggregation::Max => file_scores[0],
+                FileAggregation::Mean => file_scores.iter().sum::<f32>() / file_scores.len() as f32,
+                FileAggregation::Weighted => file_scores
+                    .iter()
+                    .enumerate()
+                    .map(|(rank, score)| score / (1u32 << rank.min(31)) as f32)
+                    .sum(),
+            };
+            RankedFile {
+                chunks: file_scores.len(),
+                path,
+                score,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    ranked
+}
+
+/// Put each file's results together (`--group`): files in the order of their first result,
+/// and each file's results in line order.
+pub fn group_by_file(results: &mut [SearchResult]) {
+    let mut first_seen: HashMap<PathBuf, usize> = HashMap::new();
+    for (position, result) in results.iter().enumerate() {
+        first_seen.entry(result.file.clone()).or_insert(position);
+    }
+    results.sort_by_key(|result| {
+        (
+            first_seen[&result.file],
+            result.span.line_start,
+            result.span.byte_start,
+        )
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Span;
+
+    fn result(file: &str, score: f32) -> SearchResult {
+        SearchResult {
+            file: PathBuf::from(file),
+            span: Span::new(0, 1, 1, 1).unwrap(),
+            score,
+            preview: String::new(),
+            lang: None,
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            signals: None,
+            explanation: None,
+        }
+    }
+
+    #[test]
+    fn test_rank_files_by_aggregation() {
+        let results = vec![
+            result("single.rs", 0.9),
+            result("many.rs", 0.8),
+            result("many.rs", 0.8),
+            result("many.rs", 0.6),
+        ];
+
+        let ranked = rank_files(&results, FileAggregation::Max);
+        assert_eq!(ranked[0].path, PathBuf::from("single.rs"));
+        assert_eq!(ranked[1].chunks, 3);
+
+        let ranked = rank_files(&results, FileAggregation::Mean);
+        assert_eq!(ranked[0].path, PathBuf::from("single.rs"));
+        assert!((ranked[1].score - 2.2 / 3.0).abs() < 1e-6);
+
+        // 0.8 + 0.4 + 0.15 beats a single 0.9
+        let ranked = rank_files(&results, FileAggregation::Weighted);
+        assert_eq!(ranked[0].path, PathBuf::from("many.rs"));
+        assert!((ranked[0].score - 1.35).abs() < 1e-6);
+        assert!((ranked[1].score - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_group_by_file_keeps_best_file_first() {
+        let at_line = |file: &str, line: usize| SearchResult {
+            span: Span::new(line * 10, line * 10 + 1, line, line).unwrap(),
+            ..result(file, 0.5)
+        };
+        let mut results = vec![
+            at_line("b.rs", 40),
+            at_line("a.rs", 7),
+            at_line("b.rs", 3),
+            at_line("a.rs", 2),
+        ];
+        group_by_file(&mut results);
+        let order: Vec<_> = results
+            .iter()
+            .map(|r| (r.file.to_str().unwrap(), r.span.line_start))
+            .collect();
+        assert_eq!(
+            order,
+            vec![("b.rs", 3), ("b.rs", 40), ("a.rs", 2), ("a.rs", 7)]
+        );
+    }
+}
@@ -1,4 +1,14 @@
+pub mod cursor;
+pub mod embed_template;
+pub mod encoding;
+pub mod explain;
+pub mod file_limits;
+pub mod file_rank;
 pub mod heatmap;
+pub mod paths;
+pub mod redact;
+pub mod stats;
+pub mod within;
 
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -32,6 +42,16 @@ pub enum CcError {
 
     #[error("Other error: {0}")]
     Other(String),
+
+    #[error("Operation cancelled")]
+    Cancelled,
+}
+
+impl CcError {
+    /// Whether `error` is a cancellation, which callers report quietly rather than as a failure
+    pub fn is_cancelled(error: &anyhow::Error) -> bool {
+        matches!(error.downcast_ref::<CcError>(), Some(CcError::Cancelled))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, CcError>;
@@ -53,6 +73,13 @@ pub enum Language {
     Swift,
     Kotlin,
     Zig,
+    ObjectiveC,
+    Shell,
+    Yaml,
+    Toml,
+    Json,
+    Protobuf,
+    Thrift,
     Pdf,
 }
 
@@ -76,6 +103,13 @@ impl Language {
             "swift" => Some(Language::Swift),
             "kt" | "kts" => Some(Language::Kotlin),
             "zig" => Some(Language::Zig),
+            "m" | "mm" => Some(Language::ObjectiveC),
+            "sh" | "bash" | "zsh" => Some(Language::Shell),
+            "yaml" | "yml" => Some(Language::Yaml),
+            "toml" => Some(Language::Toml),
+            "json" => Some(Language::Json),
+            "proto" => Some(Language::Protobuf),
+            "thrift" => Some(Language::Thrift),
             "pdf" => Some(Language::Pdf),
             _ => None,
         }
@@ -88,6 +122,44 @@ impl Language {
     }
 }
 
+impl std::str::FromStr for Language {
+    type Err = String;
+
+    /// Accepts the names printed by `Display` ("rust", "objc") as well as file extensions ("rs")
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let language = match s.to_lowercase().as_str() {
+            "rust" => Language::Rust,
+            "python" => Language::Python,
+            "javascript" => Language::JavaScript,
+            "typescript" => Language::TypeScript,
+            "haskell" => Language::Haskell,
+            "go" => Language::Go,
+            "java" => Language::Java,
+            "c" => Language::C,
+            "cpp" | "c++" => Language::Cpp,
+            "csharp" | "c#" => Language::CSharp,
+            "ruby" => Language::Ruby,
+            "php" => Language::Php,
+            "swift" => Language::Swift,
+            "kotlin" => Language::Kotlin,
+            "zig" => Language::Zig,
+            "objc" | "objective-c" => Language::ObjectiveC,
+            "shell" => Language::Shell,
+            "yaml" => Language::Yaml,
+            "toml" => Language::Toml,
+            "json" => Language::Json,
+            "protobuf" => Language::Protobuf,
+            "thrift" => Language::Thrift,
+            "pdf" => Language::Pdf,
+            other => {
+                return Language::from_extension(other)
+                    .ok_or_else(|| format!("unknown language '{}'", s));
+            }
+        };
+        Ok(language)
+    }
+}
+
 impl std::fmt::Display for Language {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let name = match self {
@@ -106,6 +178,13 @@ impl std::fmt::Display for Language {
             Language::Swift => "swift",
             Language::Kotlin => "kotlin",
             Language::Zig => "zig",
+            Language::ObjectiveC => "objc",
+            Language::Shell => "shell",
+            Language::Yaml => "yaml",
+            Language::Toml => "toml",
+            Language::Json => "json",
+            Language::Protobuf => "protobuf",
+            Language::Thrift => "thrift",
             Language::Pdf => "pdf",
         };
         write!(f, "{}", name)
@@ -229,26 +308,46 @@ pub struct SearchResult {
     pub chunk_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub index_epoch: Option<u64>,
+    /// Score breakdown, filled in when `SearchOptions::explain` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<explain::ScoreExplanation>,
+    /// Ranks in the keyword and vector rankings behind the score
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signals: Option<SearchSignals>,
 }
 
 /// Enhanced search results that include near-miss information for threshold queries
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResults {
     pub matches: Vec<SearchResult>,
     /// The highest scoring result below the threshold (if any)
     pub closest_below_threshold: Option<SearchResult>,
+    /// True when a soft timeout cut the search short; `matches` are the best found so far
+    pub partial: bool,
+    /// Matching files whose invalid UTF-8 was replaced with U+FFFD in previews
+    pub lossy_files: usize,
 }
 
+/// One result of `--json` / `--json-v1` (schema v1, see docs/reference/cli.md). Every field
+/// but `partial` and `explanation` is always present, `null` when it does not apply.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonSearchResult {
     pub file: String,
     pub span: Span,
     pub lang: Option<Language>,
+    /// Name of the innermost definition holding the match
     pub symbol: Option<String>,
     pub score: f32,
     pub signals: SearchSignals,
     pub preview: String,
-    pub model: String,
+    /// Embedding model of semantic and hybrid searches; `null` for modes without embeddings
+    pub model: Option<String>,
+    pub chunk_hash: Option<String>,
+    /// Set when the search hit its `--timeout` and returned partial results
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<explain::ScoreExplanation>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -264,40 +363,224 @@ pub struct JsonlSearchResult {
     pub chunk_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub index_epoch: Option<u64>,
+    /// Set when the search hit its `--timeout` and returned partial results
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<explain::ScoreExplanation>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where a result ranked in each list that produced it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct SearchSignals {
+    /// 1-based rank among keyword hits (BM25, else regex)
     pub lex_rank: Option<usize>,
+    /// 1-based rank by embedding similarity, before reranking
     pub vec_rank: Option<usize>,
-    pub rrf_score: f32,
+    /// Fused reciprocal rank score of a hybrid result, before reranking
+    pub rrf_score: Option<f32>,
+    /// Score on the mode's own scale (cosine similarity, BM25 or the RRF sum) before it was
+    /// calibrated to 0.0-1.0; shown in place of the score by `--scores-raw`
+    pub raw_score: Option<f32>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SearchMode {
     Regex,
     Lexical,
     Semantic,
     Hybrid,
     Ast,  // AST structural search using ast-grep
+    Symbol, // Definition names found by the tree-sitter chunker
+}
+
+/// Weight of each ranked list in hybrid reciprocal rank fusion; a result at `rank` in a list
+/// contributes `weight / (60 + rank)`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FusionWeights {
+    pub regex: f32,
+    pub semantic: f32,
+    pub lexical: f32,
+    pub ast: f32,
+}
+
+impl Default for FusionWeights {
+    fn default() -> Self {
+        Self {
+            regex: 1.0,
+            semantic: 1.0,
+            lexical: 1.0,
+            ast: 1.0,
+        }
+    }
+}
+
+impl FusionWeights {
+    /// Weight of a list by name ("regex", "semantic", "lexical", "ast")
+    pub fn weight(&self, list: &str) -> f32 {
+        match list {
+            "regex" => self.regex,
+            "semantic" => self.semantic,
+            "lexical" => self.lexical,
+            "ast" => self.ast,
+            _ => 1.0,
+        }
+    }
+}
+
+/// How semantic search holds the index's vectors (`[index_backend]` in `cs.toml`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexBackend {
+    pub quantization: Quantization,
+    /// Candidates re-scored with full-precision vectors, as a multiple of the result limit
+    pub rerank_factor: usize,
+}
+
+impl Default for IndexBackend {
+    fn default() -> Self {
+        Self {
+            quantization: Quantization::None,
+            rerank_factor: 4,
+        }
+    }
+}
+
+/// Vector encoding scanned by semantic search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quantization {
+    /// Full-precision `f32` vectors
+    #[default]
+    None,
+    /// One signed byte per dimension plus a scale per vector, a quarter of the memory; the
+    /// leading candidates are re-scored at full precision
+    Int8,
+}
+
+/// Limits on the `.cs` directory, applied by `cs --gc` and after indexing (`[gc]` in `cs.toml`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GcPolicy {
+    /// Size of the index directory, in megabytes, above which rebuildable caches are removed
+    /// oldest first; 0 for no limit
+    pub max_size_mb: u64,
+    /// Days after which an untouched rebuildable cache is removed; 0 to keep them
+    pub max_age_days: u64,
 }
 
-#[derive(Debug, Clone)]
+impl Default for GcPolicy {
+    fn default() -> Self {
+        Self {
+            max_size_mb: 2048,
+            max_age_days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IncludePattern {
     pub path: PathBuf,
     pub is_dir: bool,
 }
 
-#[derive(Debug, Clone)]
+/// How the strides of one long chunk combine into a single semantic result
+/// (`--stride-pooling`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StridePooling {
+    /// Score of the best-matching stride
+    #[default]
+    Max,
+    /// Average score of the chunk's strides that were scored
+    Mean,
+    /// Every stride is a result of its own
+    None,
+}
+
+impl std::str::FromStr for StridePooling {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "max" => Ok(Self::Max),
+            "mean" => Ok(Self::Mean),
+            "none" => Ok(Self::None),
+            other => Err(format!(
+                "unknown stride pooling '{}'; expected max, mean or none",
+                other
+            )),
+        }
+    }
+}
+
+/// Which result stays when several cover the same code (`--dedupe`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dedupe {
+    /// Every result is kept
+    #[default]
+    None,
+    /// The smallest result, such as a method rather than the class around it
+    Smallest,
+    /// The highest-scoring result
+    Score,
+}
+
+impl std::str::FromStr for Dedupe {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "smallest" => Ok(Self::Smallest),
+            "score" => Ok(Self::Score),
+            other => Err(format!(
+                "unknown dedupe preference '{}'; expected smallest, score or none",
+                other
+            )),
+        }
+    }
+}
+
+/// Lines of one file whose chunks are left out of semantic results (`--similar-to` leaves out
+/// the example it searches with)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExcludedSpan {
+    /// Canonical path of the file
+    pub file: PathBuf,
+    /// 1-based, inclusive
+    pub line_start: usize,
+    pub line_end: usize,
+}
+
+impl ExcludedSpan {
+    /// Whether a chunk at `span` of `file` overlaps the excluded lines
+    pub fn overlaps(&self, file: &Path, span: &Span) -> bool {
+        span.line_start <= self.line_end
+            && span.line_end >= self.line_start
+            && (file == self.file || file.canonicalize().is_ok_and(|file| file == self.file))
+    }
+}
+
+/// Serializable so `cs --daemon` can run a search sent over its socket; `stats` stays behind
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchOptions {
     pub mode: SearchMode,
     pub query: String,
+    /// Additional patterns OR-ed with `query` (grep `-e`/`-f`)
+    pub extra_patterns: Vec<String>,
     pub path: PathBuf,
     pub top_k: Option<usize>,
     pub threshold: Option<f32>,
     pub case_insensitive: bool,
     pub whole_word: bool,
     pub fixed_string: bool,
+    /// Match regex patterns with PCRE2 (`-P`) for look-around and backreferences
+    pub pcre2: bool,
+    /// Let regex matches span lines (`-U`); `^` and `$` then match at line breaks
+    pub multiline: bool,
     pub line_numbers: bool,
     pub context_lines: usize,
     pub before_context_lines: usize,
@@ -308,9 +591,68 @@ pub struct SearchOptions {
     pub no_snippet: bool,
     pub reindex: bool,
     pub show_scores: bool,
+    /// Report scores on each mode's own scale instead of calibrated 0.0-1.0 (`--scores-raw`);
+    /// `threshold` still applies to calibrated scores
+    pub raw_scores: bool,
     pub show_filenames: bool,
+    /// Print results under one heading per file, with line numbers (`--group`)
+    pub group_by_file: bool,
+    /// Print one `file:line:column:text` line per match, for editor quickfix lists (`--vimgrep`)
+    pub vimgrep: bool,
+    /// Syntax-highlight previews when colors are on (`--highlight`)
+    pub syntax_highlight: bool,
+    /// Size and count caps on the files a search reads (`--max-filesize`, `--max-files`)
+    pub file_limits: file_limits::FileLimits,
     pub files_with_matches: bool,
     pub files_without_matches: bool,
+    pub count: bool,
+    /// Only whether anything matches is wanted (`-q`): regex search stops reading files once
+    /// one matches
+    pub first_match: bool,
+    /// Counters for `--stats`, filled in by the search stages when set
+    #[serde(skip)]
+    pub stats: Option<std::sync::Arc<stats::SearchStats>>,
+    /// Previous results the search is restricted to (`--within`)
+    pub within: Option<std::sync::Arc<within::ResultScope>>,
+    /// Aggregate chunk scores per file and print a ranked file list (`--files-ranked`)
+    pub files_ranked: Option<file_rank::FileAggregation>,
+    pub invert_match: bool,
+    /// Print only the matched part of each line (`-o`, regex mode)
+    pub only_matching: bool,
+    /// Replacement template for regex matches, with `$1`/`${name}` capture expansion
+    pub replace: Option<String>,
+    /// Decompress archives and compressed files and search their entries (regex and lexical)
+    pub search_zip: bool,
+    /// Restrict semantic, lexical and symbol results to these chunk types ("function",
+    /// "class", "method", "module"); empty means no restriction
+    pub only_chunk_types: Vec<String>,
+    /// Leave out test files and test chunks (`--no-tests`)
+    pub exclude_tests: bool,
+    /// Leave out semantic results overlapping these lines (`--similar-to`)
+    pub excluded_span: Option<ExcludedSpan>,
+    /// Ranked results skipped before the first one returned (`--offset`, `--cursor`); `top_k`
+    /// is the page size
+    pub offset: usize,
+    /// Index epoch a `--cursor` was issued against: the automatic index refresh is skipped so
+    /// every page is cut from the same ranking, and the search fails if the index changed
+    pub index_epoch: Option<u64>,
+    /// Chunk and embed in memory instead of reading or writing an index under `path`
+    pub ephemeral: bool,
+    /// Fuse BM25 lexical hits into hybrid search as a third ranked list
+    pub hybrid_lexical: bool,
+    /// Also search synonym variants of the query in the semantic and lexical stages
+    /// (`--expand-query`)
+    pub expand_query: bool,
+    /// Attach a `ScoreExplanation` to every result (`--explain`)
+    pub explain: bool,
+    /// Only search files in these languages (empty searches all files)
+    pub languages: Vec<Language>,
+    pub fusion_weights: FusionWeights,
+    /// Who issued the search (`cli`, `tui`, `mcp:<client>`, `lsp:<client>`, `rpc`), for the
+    /// project audit log
+    pub requester: Option<String>,
+    /// Soft time budget for the search; on expiry the best results so far are returned
+    pub timeout: Option<std::time::Duration>,
     pub exclude_patterns: Vec<String>,
     pub include_patterns: Vec<IncludePattern>,
     pub respect_gitignore: bool,
@@ -318,6 +660,27 @@ pub struct SearchOptions {
     // Enhanced embedding options (search-time only)
     pub rerank: bool,
     pub rerank_model: Option<String>,
+    /// First-stage results the cross-encoder re-scores (default 50; never fewer than top_k)
+    pub rerank_candidates: Option<usize>,
+    /// Weight of the cross-encoder score against the first-stage score (0..=1); `None` or 1.0
+    /// ranks by the cross-encoder alone
+    pub rerank_blend: Option<f32>,
+    /// MMR novelty weight for semantic results (0..=1, `--diversity`); `None` or 0 keeps
+    /// similarity order
+    pub diversity: Option<f32>,
+    /// Weight of BM25 term-vector scores fused into semantic scores when the index stores them
+    /// (0..=1, `--sparse-weight`); `None` uses the default, 0 ranks by cosine alone
+    pub sparse_weight: Option<f32>,
+    /// How scores of a long chunk's strides combine into one result (`--stride-pooling`)
+    pub stride_pooling: StridePooling,
+    /// Keep one of the semantic, lexical or hybrid results that cover the same code
+    /// (`--dedupe`)
+    pub dedupe: Dedupe,
+    /// Phrases whose meaning semantic results are steered away from (`--not`)
+    pub negative_queries: Vec<String>,
+    /// How much similarity to a `--not` phrase lowers a semantic score (0..=1, `--not-weight`);
+    /// `None` uses the default
+    pub negative_weight: Option<f32>,
     pub embedding_model: Option<String>,
     // AST-specific options (for --ast mode)
     pub ast_pattern: Option<String>,      // AST pattern (overrides query if set)
@@ -344,21 +707,51 @@ impl JsonlSearchResult {
             },
             chunk_hash: result.chunk_hash.clone(),
             index_epoch: result.index_epoch,
+            partial: false,
+            explanation: result.explanation.clone(),
         }
     }
 }
 
+impl JsonSearchResult {
+    pub fn from_search_result(result: &SearchResult, model: Option<&str>, partial: bool) -> Self {
+        Self {
+            file: result.file.display().to_string(),
+            span: result.span.clone(),
+            lang: result.lang,
+            symbol: result.symbol.clone(),
+            score: result.score,
+            signals: result.signals.clone().unwrap_or_default(),
+            preview: result.preview.clone(),
+            model: model.map(str::to_string),
+            chunk_hash: result.chunk_hash.clone(),
+            partial,
+            explanation: result.explanation.clone(),
+        }
+    }
+}
+
+impl SearchOptions {
+    /// All patterns to search for: `query` followed by `extra_patterns`
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.query.as_str()).chain(self.extra_patterns.iter().map(String::as_str))
+    }
+}
+
 impl Default for SearchOptions {
     fn default() -> Self {
         Self {
             mode: SearchMode::Regex,
             query: String::new(),
+            extra_patterns: Vec::new(),
             path: PathBuf::from("."),
             top_k: None,
             threshold: None,
             case_insensitive: false,
             whole_word: false,
             fixed_string: false,
+            pcre2: false,
+            multiline: false,
             line_numbers: false,
             context_lines: 0,
             before_context_lines: 0,
@@ -369,9 +762,33 @@ impl Default for SearchOptions {
             no_snippet: false,
             reindex: false,
             show_scores: false,
+            raw_scores: false,
             show_filenames: false,
+            group_by_file: false,
+            vimgrep: false,
+            syntax_highlight: false,
+            file_limits: file_limits::FileLimits::default(),
             files_with_matches: false,
             files_without_matches: false,
+            count: false,
+            first_match: false,
+            stats: None,
+            within: None,
+            files_ranked: None,
+            invert_match: false,
+            only_matching: false,
+            replace: None,
+            search_zip: false,
+            only_chunk_types: Vec::new(),
+            exclude_tests: false,
+            ephemeral: false,
+            hybrid_lexical: false,
+            expand_query: false,
+            explain: false,
+            languages: Vec::new(),
+            fusion_weights: FusionWeights::default(),
+            requester: None,
+            timeout: None,
             exclude_patterns: get_default_exclude_patterns(),
             include_patterns: Vec::new(),
             respect_gitignore: true,
@@ -379,6 +796,17 @@ impl Default for SearchOptions {
             // Enhanced embedding options (search-time only)
             rerank: false,
             rerank_model: None,
+            rerank_candidates: None,
+            rerank_blend: None,
+            diversity: None,
+            sparse_weight: None,
+            stride_pooling: StridePooling::default(),
+            dedupe: Dedupe::default(),
+            negative_queries: Vec::new(),
+            negative_weight: None,
+            excluded_span: None,
+            offset: 0,
+            index_epoch: None,
             embedding_model: None,
             // AST defaults
             ast_pattern: None,
@@ -579,9 +1007,9 @@ pub fn build_exclude_patterns(
 }
 
 pub fn get_sidecar_path(repo_root: &Path, file_path: &Path) -> PathBuf {
-    let relative = file_path.strip_prefix(repo_root).unwrap_or(file_path);
+    let relative = paths::index_key(file_path, repo_root);
     let mut sidecar = repo_root.join(".cs");
-    sidecar.push(relative);
+    sidecar.push(&relative);
     let ext = relative
         .extension()
         .map(|e| format!("{}.cs", e.to_string_lossy()))
@@ -610,6 +1038,25 @@ pub fn compute_file_hash(path: &Path) -> Result<String> {
     Ok(hash.to_hex().to_string())
 }
 
+/// Stable id of a chunk (`chunk_hash`): blake3 of its path, span and text. `path` is relative
+/// to the index root and `/`-separated, so the id survives reindexing and moving the checkout,
+/// and changes only when the chunk's text or position does.
+pub fn compute_chunk_id(path: &str, span: &Span, text: &[u8]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(path.as_bytes());
+    hasher.update(&[0]);
+    for value in [
+        span.byte_start,
+        span.byte_end,
+        span.line_start,
+        span.line_end,
+    ] {
+        hasher.update(&(value as u64).to_le_bytes());
+    }
+    hasher.update(text);
+    hasher.finalize().to_hex().to_string()
+}
+
 /// PDF-specific utilities
 pub mod pdf {
     use std::path::{Path, PathBuf};
@@ -624,9 +1071,9 @@ pub mod pdf {
 
     /// Get path for cached PDF content
     pub fn get_content_cache_path(repo_root: &Path, file_path: &Path) -> PathBuf {
-        let relative = file_path.strip_prefix(repo_root).unwrap_or(file_path);
+        let relative = crate::paths::index_key(file_path, repo_root);
         let mut cache_path = repo_root.join(".cs").join("content");
-        cache_path.push(relative);
+        cache_path.push(&relative);
 
         // Add .txt extension to the cached file
         let ext = relative
@@ -691,6 +1138,113 @@ pub mod pdf {
     }
 }
 
+/// Archive and compressed-file utilities for `--search-zip`
+pub mod archive {
+    use std::path::{Path, PathBuf};
+
+    /// Separator between an archive path and the entry inside it, as in `bundle.zip!src/lib.rs`
+    pub const ENTRY_SEPARATOR: char = '!';
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ArchiveKind {
+        Gzip,
+        Zstd,
+        Bzip2,
+        Zip,
+        Tar,
+        TarGz,
+        TarZstd,
+        TarBz2,
+    }
+
+    impl ArchiveKind {
+        /// True for formats holding several entries rather than one compressed stream
+        pub fn is_container(self) -> bool {
+            !matches!(self, Self::Gzip | Self::Zstd | Self::Bzip2)
+        }
+    }
+
+    /// Detect an archive or compressed file by extension
+    pub fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        let kind = if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            ArchiveKind::TarGz
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            ArchiveKind::TarZstd
+        } else if name.ends_with(".tar.bz2") || name.ends_with(".tbz2") {
+            ArchiveKind::TarBz2
+        } else if name.ends_with(".tar") {
+            ArchiveKind::Tar
+        } else if name.ends_with(".zip") || name.ends_with(".jar") {
+            ArchiveKind::Zip
+        } else if name.ends_with(".gz") {
+            ArchiveKind::Gzip
+        } else if name.ends_with(".zst") {
+            ArchiveKind::Zstd
+        } else if name.ends_with(".bz2") {
+            ArchiveKind::Bzip2
+        } else {
+            return None;
+        };
+        Some(kind)
+    }
+
+    pub fn is_archive_file(path: &Path) -> bool {
+        archive_kind(path).is_some()
+    }
+
+    /// True for exclude globs such as `*.zip` or `*.tar.gz` that only match archives,
+    /// so `--search-zip` can lift them from the default .csignore patterns
+    pub fn is_archive_pattern(pattern: &str) -> bool {
+        pattern
+            .strip_prefix("*.")
+            .is_some_and(|ext| is_archive_file(Path::new(&format!("x.{}", ext))))
+    }
+
+    /// Display path for an entry inside a container archive
+    pub fn entry_path(archive: &Path, entry: &str) -> PathBuf {
+        PathBuf::from(format!("{}{}{}", archive.display(), ENTRY_SEPARATOR, entry))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_archive_kind() {
+            assert_eq!(
+                archive_kind(Path::new("logs/app.log.gz")),
+                Some(ArchiveKind::Gzip)
+            );
+            assert_eq!(
+                archive_kind(Path::new("src.TAR.GZ")),
+                Some(ArchiveKind::TarGz)
+            );
+            assert_eq!(archive_kind(Path::new("a.tgz")), Some(ArchiveKind::TarGz));
+            assert_eq!(archive_kind(Path::new("a.zip")), Some(ArchiveKind::Zip));
+            assert_eq!(
+                archive_kind(Path::new("a.tar.zst")),
+                Some(ArchiveKind::TarZstd)
+            );
+            assert_eq!(archive_kind(Path::new("a.rs")), None);
+            assert!(!ArchiveKind::Bzip2.is_container());
+            assert!(ArchiveKind::Tar.is_container());
+        }
+
+        #[test]
+        fn test_archive_patterns_and_entry_paths() {
+            assert!(is_archive_pattern("*.zip"));
+            assert!(is_archive_pattern("*.tar.gz"));
+            assert!(!is_archive_pattern("*.rar"));
+            assert!(!is_archive_pattern("target"));
+            assert_eq!(
+                entry_path(Path::new("dist/bundle.zip"), "src/lib.rs"),
+                PathBuf::from("dist/bundle.zip!src/lib.rs")
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -897,6 +1451,8 @@ mod tests {
             symbol: Some("main".to_string()),
             chunk_hash: Some("abc123".to_string()),
             index_epoch: Some(1699123456),
+            explanation: None,
+            signals: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -927,6 +1483,8 @@ mod tests {
             symbol: Some("authenticate".to_string()),
             chunk_hash: Some("abc123def456".to_string()),
             index_epoch: Some(1699123456),
+            explanation: None,
+            signals: None,
         };
 
         // Test with snippet
@@ -962,6 +1520,36 @@ mod tests {
         assert_eq!(sidecar, expected);
     }
 
+    #[test]
+    fn test_get_sidecar_path_outside_root_stays_in_index() {
+        let repo_root = PathBuf::from("/project");
+        let sidecar = get_sidecar_path(&repo_root, Path::new("/mnt/data/notes.md"));
+        assert_eq!(sidecar, PathBuf::from("/project/.cs/mnt/data/notes.md.cs"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_get_sidecar_path_windows_spellings() {
+        let repo_root = PathBuf::from(r"C:\work\project");
+        let expected = PathBuf::from(r"C:\work\project\.cs\src\main.rs.cs");
+        for file in [
+            r"C:\work\project\src\main.rs",
+            r"\\?\C:\work\project\src\main.rs",
+            r"c:\Work\PROJECT\src\main.rs",
+        ] {
+            assert_eq!(get_sidecar_path(&repo_root, Path::new(file)), expected);
+        }
+
+        let share = PathBuf::from(r"\\fileserver\repos\project");
+        assert_eq!(
+            get_sidecar_path(
+                &share,
+                Path::new(r"\\?\UNC\fileserver\repos\project\lib.rs")
+            ),
+            PathBuf::from(r"\\fileserver\repos\project\.cs\lib.rs.cs")
+        );
+    }
+
     #[test]
     fn test_get_sidecar_path_no_extension() {
         let repo_root = PathBuf::from("/project");
@@ -1021,12 +1609,39 @@ mod tests {
         assert_ne!(hash1, hash3);
     }
 
+    #[test]
+    fn test_compute_chunk_id() {
+        let span = Span::new(10, 40, 2, 4).unwrap();
+        let id = compute_chunk_id("src/retry.rs", &span, b"fn retry() {}");
+        assert_eq!(id.len(), 64);
+        assert_eq!(
+            id,
+            compute_chunk_id("src/retry.rs", &span, b"fn retry() {}")
+        );
+
+        // Path, position and text each change the id
+        assert_ne!(
+            id,
+            compute_chunk_id("src/other.rs", &span, b"fn retry() {}")
+        );
+        let moved = Span::new(12, 42, 3, 5).unwrap();
+        assert_ne!(
+            id,
+            compute_chunk_id("src/retry.rs", &moved, b"fn retry() {}")
+        );
+        assert_ne!(
+            id,
+            compute_chunk_id("src/retry.rs", &span, b"fn retry() { }")
+        );
+    }
+
     #[test]
     fn test_json_search_result_serialization() {
         let signals = SearchSignals {
             lex_rank: Some(1),
             vec_rank: Some(2),
-            rrf_score: 0.85,
+            rrf_score: Some(0.85),
+            raw_score: Some(0.85),
         };
 
         let result = JsonSearchResult {
@@ -1042,7 +1657,10 @@ mod tests {
             score: 0.95,
             signals,
             preview: "hello".to_string(),
-            model: "bge-small".to_string(),
+            model: Some("bge-small".to_string()),
+            chunk_hash: None,
+            partial: false,
+            explanation: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -1100,6 +1718,14 @@ mod tests {
         assert_eq!(Language::from_extension("SWIFT"), Some(Language::Swift));
         assert_eq!(Language::from_extension("KT"), Some(Language::Kotlin));
         assert_eq!(Language::from_extension("KTS"), Some(Language::Kotlin));
+        assert_eq!(Language::from_extension("M"), Some(Language::ObjectiveC));
+        assert_eq!(Language::from_extension("MM"), Some(Language::ObjectiveC));
+        assert_eq!(Language::from_extension("SH"), Some(Language::Shell));
+        assert_eq!(Language::from_extension("YML"), Some(Language::Yaml));
+        assert_eq!(Language::from_extension("TOML"), Some(Language::Toml));
+        assert_eq!(Language::from_extension("JSON"), Some(Language::Json));
+        assert_eq!(Language::from_extension("PROTO"), Some(Language::Protobuf));
+        assert_eq!(Language::from_extension("THRIFT"), Some(Language::Thrift));
         assert_eq!(Language::from_extension("PDF"), Some(Language::Pdf));
 
         // Test mixed case extensions
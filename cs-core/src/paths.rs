@@ -0,0 +1,168 @@
+//! Paths as index keys. Sidecars, cached content and manifest entries are keyed by a file's
+//! path relative to the index root, so the root prefix has to come off however the two paths
+//! were spelled. On Windows they often differ: `canonicalize` returns verbatim `\\?\C:\` and
+//! `\\?\UNC\server\share\` paths while the working directory is plain `C:\`, drive letters and
+//! names differ in case, and the file system ignores case. Paths are compared with verbatim
+//! prefixes simplified (dunce) and, on Windows, without regard to case; when the spellings
+//! still disagree, both sides are canonicalized.
+
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+/// `fs::canonicalize` without verbatim prefixes where Windows does not need them; the path as
+/// given, simplified, when it cannot be resolved
+pub fn canonicalize(path: &Path) -> PathBuf {
+    dunce::canonicalize(path).unwrap_or_else(|_| dunce::simplified(path).to_path_buf())
+}
+
+/// `path` relative to `root`, or `None` when it is outside it
+pub fn relative_to(path: &Path, root: &Path) -> Option<PathBuf> {
+    strip_root(path, root).or_else(|| strip_root(&canonicalize(path), &canonicalize(root)))
+}
+
+/// Whether two paths name the same location, compared like `relative_to` compares prefixes
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    relative_to(a, b).is_some_and(|relative| relative.as_os_str().is_empty())
+}
+
+/// Key of `file` under an index rooted at `root`: its path relative to the root, or for a file
+/// outside the root its own components, so that anything built from the key stays inside the
+/// index directory
+pub fn index_key(file: &Path, root: &Path) -> PathBuf {
+    relative_to(file, root).unwrap_or_else(|| {
+        file.components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect()
+    })
+}
+
+/// Lexical prefix strip: leading `.` components are ignored, verbatim prefixes simplified and,
+/// on Windows, components compared without regard to case; `..` needs the file system
+fn strip_root(path: &Path, root: &Path) -> Option<PathBuf> {
+    let path = dunce::simplified(path);
+    let root = dunce::simplified(root);
+    let has_parent = |path: &Path| path.components().any(|c| c == Component::ParentDir);
+    if path.is_absolute() != root.is_absolute() || has_parent(path) || has_parent(root) {
+        return None;
+    }
+    let mut components = path
+        .components()
+        .skip_while(|component| *component == Component::CurDir);
+    for expected in root
+        .components()
+        .skip_while(|component| *component == Component::CurDir)
+    {
+        let component = components.next()?;
+        if fold(component) != fold(expected) {
+            return None;
+        }
+    }
+    Some(components.collect())
+}
+
+#[cfg(windows)]
+fn fold(component: Component<'_>) -> OsString {
+    OsString::from(component.as_os_str().to_string_lossy().to_lowercase())
+}
+
+#[cfg(not(windows))]
+fn fold(component: Component<'_>) -> OsString {
+    component.as_os_str().to_os_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_to_strips_root() {
+        let root = Path::new("/home/user/project");
+        assert_eq!(
+            relative_to(Path::new("/home/user/project/src/main.rs"), root),
+            Some(PathBuf::from("src/main.rs"))
+        );
+        assert_eq!(relative_to(Path::new("/home/user/other.rs"), root), None);
+        // A shared string prefix is not a shared directory
+        assert_eq!(
+            relative_to(Path::new("/home/user/project2/main.rs"), root),
+            None
+        );
+        assert_eq!(
+            relative_to(Path::new("./src/lib.rs"), Path::new(".")),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+        assert_eq!(
+            relative_to(Path::new("src/lib.rs"), Path::new(".")),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+        assert_eq!(relative_to(Path::new("../lib.rs"), Path::new(".")), None);
+        assert!(same_path(root, Path::new("/home/user/project/")));
+    }
+
+    #[test]
+    fn test_relative_to_resolves_links_and_dots() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+
+        let dotted = root.join("src").join("..").join("src").join("lib.rs");
+        assert_eq!(
+            relative_to(&dotted, &root),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+        assert_eq!(
+            relative_to(&canonicalize(&root.join("src/lib.rs")), &root),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+    }
+
+    #[test]
+    fn test_index_key_stays_inside_index() {
+        let root = Path::new("/project");
+        assert_eq!(
+            index_key(Path::new("/project/src/a.rs"), root),
+            PathBuf::from("src/a.rs")
+        );
+        assert_eq!(
+            index_key(Path::new("/elsewhere/b.rs"), root),
+            PathBuf::from("elsewhere/b.rs")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_prefixes_and_case() {
+        let root = Path::new(r"C:\Users\dev\project");
+        for file in [
+            r"\\?\C:\Users\dev\project\src\main.rs",
+            r"c:\users\DEV\Project\src\main.rs",
+            r"C:\Users\dev\project\src\main.rs",
+        ] {
+            assert_eq!(
+                relative_to(Path::new(file), root),
+                Some(PathBuf::from(r"src\main.rs")),
+                "{}",
+                file
+            );
+        }
+        assert_eq!(
+            relative_to(Path::new(r"D:\Users\dev\project\src\main.rs"), root),
+            None
+        );
+
+        let share = Path::new(r"\\server\share\project");
+        assert_eq!(
+            relative_to(Path::new(r"\\?\UNC\server\share\project\lib.rs"), share),
+            Some(PathBuf::from("lib.rs"))
+        );
+        assert_eq!(
+            relative_to(Path::new(r"\\SERVER\Share\project\lib.rs"), share),
+            Some(PathBuf::from("lib.rs"))
+        );
+        assert_eq!(
+            index_key(Path::new(r"D:\elsewhere\lib.rs"), root),
+            PathBuf::from(r"elsewhere\lib.rs")
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Path redaction for output handed to third parties (`--redact-paths`). Paths under the index
+//! root become relative to it, paths under the home directory start with `~`, and the user
+//! name in any other home directory (`/home/<name>`, `/Users/<name>`, `C:\Users\<name>`) is
+//! replaced with `<user>`. Paths are rewritten wherever they appear: result paths, previews
+//! and messages alike.
+
+use crate::SearchResult;
+use regex::Regex;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// What a masked user name reads as
+pub const USER_PLACEHOLDER: &str = "<user>";
+
+/// Home directory prefixes followed by a user name
+static USER_DIR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(^|[^A-Za-z0-9_.-])(/home[/\\]|/Users[/\\]|[A-Za-z]:[/\\]Users[/\\])([^/\\\s"'<>:]+)"#,
+    )
+    .unwrap()
+});
+
+/// Rewrites paths relative to an index root and masks user names
+#[derive(Debug, Clone)]
+pub struct PathRedactor {
+    /// Spellings of the index root: as given and canonical
+    roots: Vec<PathBuf>,
+    home: Option<PathBuf>,
+}
+
+impl PathRedactor {
+    /// Redact for the index at `root`, with the home directory from `HOME` (`USERPROFILE`
+    /// on Windows)
+    pub fn new(root: &Path) -> Self {
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .filter(|home| !home.is_empty())
+            .map(PathBuf::from);
+        Self::with_home(root, home)
+    }
+
+    pub fn with_home(root: &Path, home: Option<PathBuf>) -> Self {
+        let mut roots = vec![root.to_path_buf()];
+        let canonical = crate::paths::canonicalize(root);
+        if canonical != root {
+            roots.push(canonical);
+        }
+        // A root at the top of the file system would relativize everything
+        roots.retain(|root| root.is_absolute() && root.parent().is_some());
+        Self {
+            roots,
+            home: home.filter(|home| home.is_absolute() && home.parent().is_some()),
+        }
+    }
+
+    /// `path` relative to the index root, under `~` when it is in the home directory, or with
+    /// the user name masked
+    pub fn path(&self, path: &Path) -> PathBuf {
+        if path.is_absolute() {
+            if let Some(relative) = self
+                .roots
+                .iter()
+                .find_map(|root| crate::paths::relative_to(path, root))
+            {
+                return if relative.as_os_str().is_empty() {
+                    PathBuf::from(".")
+                } else {
+                    relative
+                };
+            }
+            if let Some(relative) = self
+                .home
+                .as_deref()
+                .and_then(|home| crate::paths::relative_to(path, home))
+            {
+                return Path::new("~").join(relative);
+            }
+        }
+        PathBuf::from(mask_user_dirs(&path.to_string_lossy()).into_owned())
+    }
+
+    /// `text` with every path in it redacted like `path` does
+    pub fn text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for root in &self.roots {
+            text = replace_dir(&text, root, ".");
+        }
+        if let Some(home) = &self.home {
+            text = replace_dir(&text, home, "~");
+        }
+        mask_user_dirs(&text).into_owned()
+    }
+
+    /// Redact every string in a JSON value, keys included
+    pub fn json(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => *text = self.text(text),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| self.json(item)),
+            serde_json::Value::Object(map) => {
+                *map = std::mem::take(map)
+                    .into_iter()
+                    .map(|(key, mut item)| {
+                        self.json(&mut item);
+                        (self.text(&key), item)
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    /// A search result with its path and preview redacted
+    pub fn result(&self, result: &SearchResult) -> SearchResult {
+        SearchResult {
+            file: self.path(&result.file),
+            preview: self.text(&result.preview),
+            ..result.clone()
+        }
+    }
+}
+
+/// Replace `dir` where it starts a path in `text`: `dir/x` becomes `name/x` (just `x` when
+/// `name` is "."), and `dir` on its own becomes `name`
+fn replace_dir(text: &str, dir: &Path, name: &str) -> String {
+    let dir = dir.to_string_lossy();
+    let dir = dir.trim_end_matches(['/', '\\']);
+    if dir.is_empty() {
+        return text.to_string();
+    }
+    let is_path_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.');
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(dir) {
+        let (before, after) = rest.split_at(start);
+        let after = &after[dir.len()..];
+        let starts_path = !before
+            .chars()
+            .next_back()
+            .is_some_and(|c| is_path_char(c) || c == '/' || c == '\\');
+        out.push_str(before);
+        match after.chars().next() {
+            Some(sep @ ('/' | '\\')) if starts_path => {
+                if name != "." {
+                    out.push_str(name);
+                    out.push(sep);
+                }
+                rest = &after[1..];
+            }
+            next if starts_path && !next.is_some_and(is_path_char) => {
+                out.push_str(name);
+                rest = after;
+            }
+            _ => {
+                out.push_str(dir);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn mask_user_dirs(text: &str) -> Cow<'_, str> {
+    USER_DIR.replace_all(text, format!("${{1}}${{2}}{}", USER_PLACEHOLDER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor() -> PathRedactor {
+        PathRedactor::with_home(
+            Path::new("/home/alice/work/app"),
+            Some(PathBuf::from("/home/alice")),
+        )
+    }
+
+    #[test]
+    fn test_paths() {
+        let redactor = redactor();
+        assert_eq!(
+            redactor.path(Path::new("/home/alice/work/app/src/main.rs")),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(
+            redactor.path(Path::new("/home/alice/work/app")),
+            PathBuf::from(".")
+        );
+        assert_eq!(
+            redactor.path(Path::new("/home/alice/work/app2/lib.rs")),
+            PathBuf::from("~/work/app2/lib.rs")
+        );
+        assert_eq!(
+            redactor.path(Path::new("/home/bob/lib.rs")),
+            PathBuf::from("/home/<user>/lib.rs")
+        );
+        assert_eq!(
+            redactor.path(Path::new("src/lib.rs")),
+            PathBuf::from("src/lib.rs")
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let redactor = redactor();
+        assert_eq!(
+            redactor.text("see /home/alice/work/app/src/a.rs in /home/alice/work/app"),
+            "see src/a.rs in ."
+        );
+        assert_eq!(
+            redactor.text("cache: /home/alice/.cache/cs, temp: /tmp/x/home/alice"),
+            "cache: ~/.cache/cs, temp: /tmp/x/home/alice"
+        );
+        assert_eq!(
+            redactor.text(r"C:\Users\bob\repo and /Users/carol/repo"),
+            r"C:\Users\<user>\repo and /Users/<user>/repo"
+        );
+        assert_eq!(redactor.text("/home/alicea/x"), "/home/<user>/x");
+    }
+
+    #[test]
+    fn test_json() {
+        let mut value = serde_json::json!({
+            "path": "/home/alice/work/app/src/a.rs",
+            "files": {"/home/alice/work/app/b.rs": 1},
+            "count": 2
+        });
+        redactor().json(&mut value);
+        assert_eq!(
+            value,
+            serde_json::json!({"path": "src/a.rs", "files": {"b.rs": 1}, "count": 2})
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Counters behind `--stats`: the search stages add what they read, check and score, and the
+//! time each stage took. Nothing is sent anywhere; the CLI prints the report after the
+//! results.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Shared by the stages of one search through `SearchOptions::stats`
+#[derive(Debug, Default)]
+pub struct SearchStats {
+    files_scanned: AtomicUsize,
+    chunks_scored: AtomicUsize,
+    index_files_checked: AtomicUsize,
+    index_files_updated: AtomicUsize,
+    index_files_removed: AtomicUsize,
+    phases: Mutex<Vec<(&'static str, Duration)>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StatsReport {
+    /// Files read by regex search, or sidecars read by semantic search
+    pub files_scanned: usize,
+    /// Chunks given a semantic or lexical score
+    pub chunks_scored: usize,
+    /// Files compared against the index before searching
+    pub index_files_checked: usize,
+    /// Files (re)indexed because they were new or changed
+    pub index_files_updated: usize,
+    /// Index entries dropped because their file is gone
+    pub index_files_removed: usize,
+    pub phases: Vec<PhaseTime>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTime {
+    pub phase: &'static str,
+    pub ms: f64,
+}
+
+impl SearchStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_files_scanned(&self, files: usize) {
+        self.files_scanned.fetch_add(files, Ordering::Relaxed);
+    }
+
+    pub fn add_chunks_scored(&self, chunks: usize) {
+        self.chunks_scored.fetch_add(chunks, Ordering::Relaxed);
+    }
+
+    pub fn add_index_check(&self, checked: usize, updated: usize, removed: usize) {
+        self.index_files_checked
+            .fetch_add(checked, Ordering::Relaxed);
+        self.index_files_updated
+            .fetch_add(updated, Ordering::Relaxed);
+        self.index_files_removed
+            .fetch_add(removed, Ordering::Relaxed);
+    }
+
+    /// Add `elapsed` to `phase`; phases are reported in the order they first ran
+    pub fn record_phase(&self, phase: &'static str, elapsed: Duration) {
+        let Ok(mut phases) = self.phases.lock() else {
+            return;
+        };
+        match phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => phases.push((phase, elapsed)),
+        }
+    }
+
+    /// Run `f`, recording its duration under `phase`
+    pub fn time<T>(&self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record_phase(phase, start.elapsed());
+        result
+    }
+
+    pub fn report(&self) -> StatsReport {
+        let phases = self
+            .phases
+            .lock()
+            .map(|phases| {
+                phases
+                    .iter()
+                    .map(|(phase, elapsed)| PhaseTime {
+                        phase,
+                        ms: elapsed.as_secs_f64() * 1000.0,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        StatsReport {
+            files_scanned: self.files_scanned.load(Ordering::Relaxed),
+            chunks_scored: self.chunks_scored.load(Ordering::Relaxed),
+            index_files_checked: self.index_files_checked.load(Ordering::Relaxed),
+            index_files_updated: self.index_files_updated.load(Ordering::Relaxed),
+            index_files_removed: self.index_files_removed.load(Ordering::Relaxed),
+            phases,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_accumulate_phases_in_order() {
+        let stats = SearchStats::new();
+        stats.add_files_scanned(3);
+        stats.add_files_scanned(2);
+        stats.add_index_check(10, 1, 0);
+        stats.record_phase("index", Duration::from_millis(5));
+        let value = stats.time("search", || 42);
+        assert_eq!(value, 42);
+        stats.record_phase("index", Duration::from_millis(5));
+
+        let report = stats.report();
+        assert_eq!(report.files_scanned, 5);
+        assert_eq!(report.index_files_checked, 10);
+        let phases: Vec<&str> = report.phases.iter().map(|phase| phase.phase).collect();
+        assert_eq!(phases, vec!["index", "search"]);
+        assert!((report.phases[0].ms - 10.0).abs() < 1e-6);
+    }
+}
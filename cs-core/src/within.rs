@@ -0,0 +1,131 @@
+//! Previous results a search is restricted to (`--within`), so a search can be narrowed step
+//! by step: each line of `--jsonl` or `--json` output keeps the lines of its span, and a plain
+//! path (as printed by `-l`) keeps the whole file.
+
+use crate::Span;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResultScope {
+    /// Canonical path to the 1-based, inclusive line ranges kept; `None` keeps the whole file
+    files: BTreeMap<PathBuf, Option<Vec<(usize, usize)>>>,
+}
+
+impl ResultScope {
+    /// Read a result set; relative paths are resolved against the working directory
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut scope = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if !line.starts_with('{') {
+                scope.add(Path::new(line), None);
+                continue;
+            }
+            let value: serde_json::Value = serde_json::from_str(line)
+                .with_context(|| format!("Line {} is not a JSON result", number + 1))?;
+            // "path" in --jsonl and --files-ranked output, "file" in --json output
+            let Some(path) = value
+                .get("path")
+                .or_else(|| value.get("file"))
+                .and_then(|path| path.as_str())
+            else {
+                // Summary lines such as `{"stats": ...}` name no file
+                continue;
+            };
+            let lines = value.get("span").and_then(|span| {
+                let start = span.get("line_start")?.as_u64()? as usize;
+                let end = span.get("line_end")?.as_u64()? as usize;
+                Some((start, end))
+            });
+            scope.add(Path::new(path), lines);
+        }
+        Ok(scope)
+    }
+
+    fn add(&mut self, path: &Path, lines: Option<(usize, usize)>) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let ranges = self.files.entry(path).or_insert_with(|| Some(Vec::new()));
+        match (ranges.as_mut(), lines) {
+            (Some(ranges), Some(lines)) => ranges.push(lines),
+            _ => *ranges = None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Files holding previous results, in path order
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.files.keys().cloned().collect()
+    }
+
+    /// Whether `span` of `file` overlaps a previous result
+    pub fn contains(&self, file: &Path, span: &Span) -> bool {
+        let ranges = match self.files.get(file) {
+            Some(ranges) => ranges,
+            None => match file
+                .canonicalize()
+                .ok()
+                .and_then(|file| self.files.get(&file))
+            {
+                Some(ranges) => ranges,
+                None => return false,
+            },
+        };
+        ranges.as_ref().is_none_or(|ranges| {
+            ranges
+                .iter()
+                .any(|&(start, end)| span.line_start <= end && span.line_end >= start)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(line_start: usize, line_end: usize) -> Span {
+        Span {
+            byte_start: 0,
+            byte_end: 0,
+            line_start,
+            line_end,
+        }
+    }
+
+    #[test]
+    fn test_scope_keeps_spans_and_whole_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (auth, retry) = (root.join("auth.rs"), root.join("retry.rs"));
+        std::fs::write(&auth, "").unwrap();
+        std::fs::write(&retry, "").unwrap();
+
+        let previous = format!(
+            "{{\"path\":{:?},\"span\":{{\"byte_start\":0,\"byte_end\":9,\"line_start\":10,\"line_end\":20}}}}\n\
+             {{\"file\":{:?},\"span\":{{\"line_start\":40,\"line_end\":45}}}}\n\
+             {{\"stats\":{{\"results\":2}}}}\n\
+             {}\n",
+            auth.display().to_string(),
+            auth.display().to_string(),
+            retry.display()
+        );
+        let scope = ResultScope::parse(&previous).unwrap();
+
+        assert_eq!(scope.files(), vec![auth.clone(), retry.clone()]);
+        assert!(scope.contains(&auth, &lines(18, 25)));
+        assert!(scope.contains(&auth, &lines(45, 45)));
+        assert!(!scope.contains(&auth, &lines(21, 39)));
+        assert!(scope.contains(&retry, &lines(1, 1000)));
+        assert!(!scope.contains(&root.join("other.rs"), &lines(1, 1)));
+
+        assert!(ResultScope::parse("{not json").is_err());
+    }
+}
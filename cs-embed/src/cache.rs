@@ -0,0 +1,147 @@
+//! The local model cache behind `cs --models`: which models are downloaded and how much disk
+//! each takes, downloading ahead of time (e.g. before going offline) and deleting. fastembed
+//! keeps models in the Hugging Face cache layout, one `models--<org>--<name>` directory per
+//! repository, with files stored once under `blobs/` and linked from `snapshots/`.
+
+use crate::ModelDownloadCallback;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A model repository in the embedding or reranker cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedModel {
+    /// Hugging Face repository id (`org/name`)
+    pub repo: String,
+    pub path: PathBuf,
+    /// Bytes on disk
+    pub size: u64,
+}
+
+/// Downloaded embedding and reranker models, by repository id
+pub fn cached_models() -> Vec<CachedModel> {
+    let mut models = list_cache(&crate::model_cache_dir());
+    models.extend(list_cache(&crate::reranker_cache_dir()));
+    models.sort_by(|a, b| a.repo.cmp(&b.repo));
+    models
+}
+
+fn list_cache(cache_dir: &Path) -> Vec<CachedModel> {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let repo = name.to_str()?.strip_prefix("models--")?.replace("--", "/");
+            let path = entry.path();
+            path.is_dir().then(|| CachedModel {
+                repo,
+                size: disk_usage(&path),
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Repository a local embedding model downloads from; `None` unless the registry lists it
+/// with the fastembed provider
+pub fn model_repo(model_name: &str) -> Option<String> {
+    let registry = cs_models::ModelRegistry::default();
+    let (_, config) = registry.find(model_name)?;
+    if config.provider != "fastembed" {
+        return None;
+    }
+    let model = crate::FastEmbedder::fastembed_model(&config.name);
+    fastembed::TextEmbedding::get_model_info(&model)
+        .ok()
+        .map(|info| info.model_code.clone())
+}
+
+/// Bytes on disk under `path`. Symlinks are not followed, so snapshot files linking into
+/// `blobs/` are counted once.
+pub fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if metadata.is_file() {
+        return metadata.len();
+    }
+    if !metadata.is_dir() {
+        return 0;
+    }
+    fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| disk_usage(&entry.path()))
+        .sum()
+}
+
+/// Download a local embedding model into the cache; a cached model is only loaded
+pub fn download_model(model_name: &str, progress: Option<ModelDownloadCallback>) -> Result<()> {
+    crate::FastEmbedder::new_with_progress(model_name, progress)?;
+    Ok(())
+}
+
+/// Delete a downloaded model, returning the bytes freed
+pub fn remove_model(model: &CachedModel) -> Result<u64> {
+    fs::remove_dir_all(&model.path)
+        .with_context(|| format!("Failed to remove {}", model.path.display()))?;
+    Ok(model.size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_list_cache() {
+        let temp_dir = tempdir().unwrap();
+        let repo_dir = temp_dir
+            .path()
+            .join("models--nomic-ai--nomic-embed-text-v1.5");
+        fs::create_dir_all(repo_dir.join("blobs")).unwrap();
+        fs::create_dir_all(repo_dir.join("snapshots/abc")).unwrap();
+        fs::write(repo_dir.join("blobs/1234"), vec![0u8; 100]).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(
+            repo_dir.join("blobs/1234"),
+            repo_dir.join("snapshots/abc/model.onnx"),
+        )
+        .unwrap();
+        // Download lock files and other entries are not models
+        fs::create_dir_all(temp_dir.path().join(".locks")).unwrap();
+        fs::write(temp_dir.path().join("models--stray-file"), "x").unwrap();
+
+        let models = list_cache(temp_dir.path());
+        assert_eq!(
+            models,
+            [CachedModel {
+                repo: "nomic-ai/nomic-embed-text-v1.5".to_string(),
+                path: repo_dir.clone(),
+                size: 100,
+            }]
+        );
+        assert_eq!(remove_model(&models[0]).unwrap(), 100);
+        assert!(!repo_dir.exists());
+        assert!(list_cache(&temp_dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_model_repo() {
+        assert_eq!(
+            model_repo("nomic-v1.5").as_deref(),
+            Some("nomic-ai/nomic-embed-text-v1.5")
+        );
+        assert_eq!(
+            model_repo("BAAI/bge-small-en-v1.5").as_deref(),
+            Some("Xenova/bge-small-en-v1.5")
+        );
+        // API models and unknown names have nothing to download
+        assert_eq!(model_repo("jina-code-1.5b"), None);
+        assert_eq!(model_repo("not-a-model"), None);
+    }
+}
@@ -0,0 +1,509 @@
+//! Warm search daemon (`cs --daemon`).
+//!
+//! Loading a local model and decoding the index dominate the latency of a one-off semantic
+//! query. The daemon loads each model once and answers two kinds of requests over a Unix
+//! socket: embedding texts, and running the semantic stage of a search, which the caller of
+//! `serve` supplies (`cs_engine::daemon` keeps the indexes it searched in memory). While it
+//! runs, `create_embedder` hands out an embedder that forwards to it and `search` forwards
+//! whole searches; both fall back to working in-process when it is not running. Requests and
+//! responses are one JSON object per line.
+//!
+//! The daemon needs Unix domain sockets: on other platforms `serve` fails and nothing is
+//! forwarded.
+
+use crate::Embedder;
+use anyhow::{Result, anyhow};
+use cs_core::{SearchOptions, SearchResults};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Whether `create_embedder` and `search` may forward to a running daemon (`--no-daemon` turns
+/// it off)
+static FORWARDING: AtomicBool = AtomicBool::new(true);
+
+/// Models of the daemon running in this process; the searches it runs embed with them
+static SERVING: OnceLock<Arc<Embedders>> = OnceLock::new();
+
+pub fn set_forwarding(enabled: bool) {
+    FORWARDING.store(enabled, Ordering::SeqCst);
+}
+
+/// Socket the daemon listens on: `CS_DAEMON_SOCKET`, or `daemon.sock` in the user config
+/// directory
+pub fn socket_path() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os("CS_DAEMON_SOCKET") {
+        return Ok(PathBuf::from(path));
+    }
+    Ok(cs_models::UserConfig::config_dir()?.join("daemon.sock"))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Request {
+    Embed { model: String, texts: Vec<String> },
+    Search { options: Box<SearchOptions> },
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Response {
+    #[serde(default)]
+    dim: usize,
+    #[serde(default)]
+    embeddings: Vec<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    results: Option<SearchResults>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+type Loader = dyn Fn(&str) -> Result<Box<dyn Embedder>> + Send + Sync;
+type Searcher = dyn Fn(SearchOptions) -> Result<SearchResults> + Send + Sync;
+type EventSink = dyn Fn(&str) + Send + Sync;
+type SharedEmbedder = Arc<Mutex<Box<dyn Embedder>>>;
+
+/// The daemon's models, each loaded on first use and kept
+struct Embedders {
+    load: Box<Loader>,
+    on_event: Arc<EventSink>,
+    loaded: Mutex<HashMap<String, SharedEmbedder>>,
+}
+
+impl Embedders {
+    fn new(
+        on_event: Arc<EventSink>,
+        load: impl Fn(&str) -> Result<Box<dyn Embedder>> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            load: Box::new(load),
+            on_event,
+            loaded: Mutex::default(),
+        }
+    }
+
+    fn get(&self, model: &str) -> Result<SharedEmbedder> {
+        let mut loaded = self
+            .loaded
+            .lock()
+            .map_err(|_| anyhow!("Embedding daemon state poisoned"))?;
+        if let Some(embedder) = loaded.get(model) {
+            return Ok(embedder.clone());
+        }
+        let embedder = (self.load)(model)?;
+        (self.on_event)(&format!("Loaded {} ({} dims)", model, embedder.dim()));
+        let embedder = Arc::new(Mutex::new(embedder));
+        loaded.insert(model.to_string(), embedder.clone());
+        Ok(embedder)
+    }
+}
+
+fn lock(embedder: &SharedEmbedder) -> Result<std::sync::MutexGuard<'_, Box<dyn Embedder>>> {
+    embedder
+        .lock()
+        .map_err(|_| anyhow!("Embedding daemon model poisoned"))
+}
+
+/// A model loaded by the daemon, used by the searches it runs itself
+struct ResidentEmbedder {
+    embedder: SharedEmbedder,
+    model: String,
+    dim: usize,
+}
+
+impl Embedder for ResidentEmbedder {
+    fn id(&self) -> &'static str {
+        "daemon"
+    }
+
+    fn dim(&self) -> usize {
+        self.dim
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn embed(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        lock(&self.embedder)?.embed(texts)
+    }
+}
+
+/// An embedder backed by the daemon: inside the daemon its resident model, elsewhere one
+/// forwarding to a running daemon when forwarding is on. `None` leaves loading to the caller.
+pub(crate) fn connect(model: &str) -> Option<Result<Box<dyn Embedder>>> {
+    if let Some(embedders) = SERVING.get() {
+        return Some(embedders.get(model).and_then(|embedder| {
+            let dim = lock(&embedder)?.dim();
+            Ok(Box::new(ResidentEmbedder {
+                embedder,
+                model: model.to_string(),
+                dim,
+            }) as Box<dyn Embedder>)
+        }));
+    }
+    if !FORWARDING.load(Ordering::SeqCst) {
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        let socket = socket_path().ok()?;
+        match unix::DaemonEmbedder::connect(&socket, model) {
+            Ok(embedder) => Some(Ok(Box::new(embedder))),
+            Err(e) => {
+                unavailable(&socket, &e);
+                None
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = model;
+        None
+    }
+}
+
+/// Results of `options` from a running daemon. `None` when forwarding is off or no daemon
+/// answers, leaving the search to this process; errors of the search itself are returned.
+pub fn search(options: &SearchOptions) -> Option<Result<SearchResults>> {
+    if SERVING.get().is_some() || !FORWARDING.load(Ordering::SeqCst) {
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        let socket = socket_path().ok()?;
+        match unix::Client::connect(&socket) {
+            Ok(mut client) => Some(client.search(options)),
+            Err(e) => {
+                unavailable(&socket, &e);
+                None
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = options;
+        None
+    }
+}
+
+#[cfg(unix)]
+fn unavailable(socket: &std::path::Path, error: &anyhow::Error) {
+    if socket.exists() {
+        tracing::debug!(
+            "Embedding daemon at {} unavailable: {}",
+            socket.display(),
+            error
+        );
+    }
+}
+
+/// Run the daemon on `socket` until the process is stopped. Embedding requests load each model
+/// once and keep it; search requests go to `search`, whose embedders in this process are the
+/// resident ones. Each event (listening, model loaded) is reported to `on_event`.
+#[cfg(unix)]
+pub fn serve(
+    socket: &std::path::Path,
+    on_event: impl Fn(&str) + Send + Sync + 'static,
+    search: impl Fn(SearchOptions) -> Result<SearchResults> + Send + Sync + 'static,
+) -> Result<()> {
+    let embedders = Arc::new(Embedders::new(Arc::new(on_event), |model| {
+        crate::load_embedder(model, None)
+    }));
+    if SERVING.set(embedders.clone()).is_err() {
+        anyhow::bail!("This process already runs an embedding daemon");
+    }
+    unix::serve(socket, embedders, Arc::new(search))
+}
+
+#[cfg(not(unix))]
+pub fn serve(
+    _socket: &std::path::Path,
+    _on_event: impl Fn(&str) + Send + Sync + 'static,
+    _search: impl Fn(SearchOptions) -> Result<SearchResults> + Send + Sync + 'static,
+) -> Result<()> {
+    anyhow::bail!("The embedding daemon needs Unix sockets and is not available on this platform")
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::{Embedders, Request, Response, Searcher, lock};
+    use crate::Embedder;
+    use anyhow::{Context, Result, anyhow, bail};
+    use cs_core::{SearchOptions, SearchResults};
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+    use std::sync::Arc;
+
+    /// One connection to the daemon, answering requests in turn
+    pub(super) struct Client {
+        reader: BufReader<UnixStream>,
+        writer: UnixStream,
+    }
+
+    impl Client {
+        pub(super) fn connect(socket: &Path) -> Result<Self> {
+            let writer = UnixStream::connect(socket)?;
+            let reader = BufReader::new(writer.try_clone()?);
+            Ok(Self { reader, writer })
+        }
+
+        fn request(&mut self, request: &Request) -> Result<Response> {
+            let mut line = serde_json::to_string(request)?;
+            line.push('\n');
+            self.writer.write_all(line.as_bytes())?;
+
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                bail!("Embedding daemon closed the connection");
+            }
+            let response: Response = serde_json::from_str(&line)?;
+            if let Some(error) = response.error {
+                bail!("Embedding daemon: {}", error);
+            }
+            Ok(response)
+        }
+
+        pub(super) fn search(&mut self, options: &SearchOptions) -> Result<SearchResults> {
+            let request = Request::Search {
+                options: Box::new(options.clone()),
+            };
+            self.request(&request)?
+                .results
+                .ok_or_else(|| anyhow!("Embedding daemon returned no results"))
+        }
+    }
+
+    pub(super) struct DaemonEmbedder {
+        client: Client,
+        model: String,
+        dim: usize,
+    }
+
+    impl DaemonEmbedder {
+        /// Connect and have the daemon load `model`, which also reports its dimensions
+        pub(super) fn connect(socket: &Path, model: &str) -> Result<Self> {
+            let mut embedder = Self {
+                client: Client::connect(socket)?,
+                model: model.to_string(),
+                dim: 0,
+            };
+            embedder.dim = embedder.request(Vec::new())?.dim;
+            Ok(embedder)
+        }
+
+        fn request(&mut self, texts: Vec<String>) -> Result<Response> {
+            self.client.request(&Request::Embed {
+                model: self.model.clone(),
+                texts,
+            })
+        }
+    }
+
+    impl Embedder for DaemonEmbedder {
+        fn id(&self) -> &'static str {
+            "daemon"
+        }
+
+        fn dim(&self) -> usize {
+            self.dim
+        }
+
+        fn model_name(&self) -> &str {
+            &self.model
+        }
+
+        fn embed(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(self.request(texts.to_vec())?.embeddings)
+        }
+    }
+
+    pub(super) fn serve(
+        socket: &Path,
+        embedders: Arc<Embedders>,
+        search: Arc<Searcher>,
+    ) -> Result<()> {
+        if let Ok(metadata) = std::fs::symlink_metadata(socket) {
+            if UnixStream::connect(socket).is_ok() {
+                bail!(
+                    "An embedding daemon is already listening on {}",
+                    socket.display()
+                );
+            }
+            // Only a socket can be left behind by a daemon that was killed; anything else is a
+            // mistyped CS_DAEMON_SOCKET and must not be deleted
+            if !metadata.file_type().is_socket() {
+                bail!("{} exists and is not a socket", socket.display());
+            }
+            std::fs::remove_file(socket)?;
+        }
+        if let Some(parent) = socket.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let listener = UnixListener::bind(socket)
+            .with_context(|| format!("Failed to listen on {}", socket.display()))?;
+        (embedders.on_event)(&format!(
+            "Embedding daemon listening on {}",
+            socket.display()
+        ));
+
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::warn!("Embedding daemon connection failed: {}", e);
+                    continue;
+                }
+            };
+            let (embedders, search) = (embedders.clone(), search.clone());
+            std::thread::spawn(move || {
+                if let Err(e) = handle(stream, &embedders, &*search) {
+                    tracing::debug!("Embedding daemon client error: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn handle(stream: UnixStream, embedders: &Embedders, search: &Searcher) -> Result<()> {
+        let mut writer = stream.try_clone()?;
+        for line in BufReader::new(stream).lines() {
+            let response = match serde_json::from_str::<Request>(&line?) {
+                Ok(Request::Embed { model, texts }) => embed(embedders, &model, &texts),
+                Ok(Request::Search { options }) => search(*options).map(|results| Response {
+                    results: Some(results),
+                    ..Default::default()
+                }),
+                Err(e) => Err(anyhow!("Invalid request: {}", e)),
+            }
+            .unwrap_or_else(|e| Response {
+                error: Some(format!("{:#}", e)),
+                ..Default::default()
+            });
+            let mut line = serde_json::to_string(&response)?;
+            line.push('\n');
+            writer.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn embed(embedders: &Embedders, model: &str, texts: &[String]) -> Result<Response> {
+        let embedder = embedders.get(model)?;
+        let mut embedder = lock(&embedder)?;
+        let embeddings = if texts.is_empty() {
+            Vec::new()
+        } else {
+            embedder.embed(texts)?
+        };
+        Ok(Response {
+            dim: embedder.dim(),
+            embeddings,
+            ..Default::default()
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::DummyEmbedder;
+        use std::sync::Mutex;
+
+        fn no_search() -> Arc<Searcher> {
+            Arc::new(|_| bail!("unused"))
+        }
+
+        fn embedders(on_event: impl Fn(&str) + Send + Sync + 'static) -> Arc<Embedders> {
+            Arc::new(Embedders::new(Arc::new(on_event), |model| {
+                if model == "missing" {
+                    bail!("unknown model {}", model);
+                }
+                Ok(Box::new(DummyEmbedder::new_with_model(model)))
+            }))
+        }
+
+        #[test]
+        fn test_daemon_round_trip() {
+            let temp_dir = tempfile::TempDir::new().unwrap();
+            let socket = temp_dir.path().join("daemon.sock");
+            let (events, received) = std::sync::mpsc::channel::<String>();
+            let events = Mutex::new(events);
+            {
+                let socket = socket.clone();
+                std::thread::spawn(move || {
+                    serve(
+                        &socket,
+                        embedders(move |event: &str| {
+                            let _ = events.lock().unwrap().send(event.to_string());
+                        }),
+                        Arc::new(|options: SearchOptions| {
+                            if options.query.is_empty() {
+                                bail!("empty query");
+                            }
+                            Ok(SearchResults {
+                                matches: Vec::new(),
+                                closest_below_threshold: None,
+                                partial: options.top_k == Some(3),
+                                lossy_files: 0,
+                            })
+                        }),
+                    )
+                });
+            }
+            assert!(received.recv().unwrap().contains("listening"));
+
+            let mut embedder = DaemonEmbedder::connect(&socket, "bge-small").unwrap();
+            assert_eq!(embedder.dim(), 384);
+            assert!(received.recv().unwrap().starts_with("Loaded bge-small"));
+            let embeddings = embedder
+                .embed(&["fn main() {}".to_string(), "retry".to_string()])
+                .unwrap();
+            assert_eq!(embeddings.len(), 2);
+            assert_eq!(embeddings[0].len(), 384);
+
+            // A second client reuses the resident model
+            DaemonEmbedder::connect(&socket, "bge-small").unwrap();
+            assert!(received.try_recv().is_err());
+
+            let error = DaemonEmbedder::connect(&socket, "missing")
+                .err()
+                .unwrap()
+                .to_string();
+            assert!(error.contains("unknown model missing"), "{}", error);
+
+            // Whole searches go over the same socket, with their options intact
+            let mut client = Client::connect(&socket).unwrap();
+            let options = SearchOptions {
+                query: "retry".to_string(),
+                top_k: Some(3),
+                ..Default::default()
+            };
+            assert!(client.search(&options).unwrap().partial);
+            let error = client
+                .search(&SearchOptions::default())
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains("empty query"), "{}", error);
+
+            // A second daemon on the same socket refuses to start
+            let error = serve(&socket, embedders(|_| {}), no_search())
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains("already listening"), "{}", error);
+        }
+
+        #[test]
+        fn test_serve_keeps_non_socket_files() {
+            let temp_dir = tempfile::TempDir::new().unwrap();
+            let path = temp_dir.path().join("notes.txt");
+            std::fs::write(&path, "keep me").unwrap();
+            let error = serve(&path, embedders(|_| {}), no_search())
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains("is not a socket"), "{}", error);
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+        }
+    }
+}
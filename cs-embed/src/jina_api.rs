@@ -63,10 +63,11 @@ impl JinaApiEmbedder {
     /// * `task` - Task type for code embeddings (e.g., "nl2code.query", "nl2code.passage", "code2code.query")
     ///
     /// # Environment Variables
-    /// * `JINA_API_KEY` - Required API key for authentication
+    /// * `JINA_API_KEY` - Required API key for authentication (else read from the user
+    ///   config's `api-keys-file`)
     pub fn new(model_name: &str, dimensions: usize, task: Option<&str>) -> Result<Self> {
-        let api_key = std::env::var("JINA_API_KEY")
-            .context("JINA_API_KEY environment variable not set. Get your key at: https://jina.ai/?sui=apikey")?;
+        let api_key = crate::settings::api_key("JINA_API_KEY")
+            .context("JINA_API_KEY environment variable not set (or put it in the file named by `cs --config set api-keys-file PATH`). Get your key at: https://jina.ai/?sui=apikey")?;
 
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(1800)) // 30 minutes for large indexing operations
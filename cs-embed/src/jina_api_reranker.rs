@@ -54,10 +54,11 @@ impl JinaApiReranker {
     /// * `model_name` - The model identifier (e.g., "jina-reranker-v2-base-multilingual", "jina-reranker-v1-turbo-en")
     ///
     /// # Environment Variables
-    /// * `JINA_API_KEY` - Required API key for authentication
+    /// * `JINA_API_KEY` - Required API key for authentication (else read from the user
+    ///   config's `api-keys-file`)
     pub fn new(model_name: &str) -> Result<Self> {
-        let api_key = std::env::var("JINA_API_KEY")
-            .context("JINA_API_KEY environment variable not set. Get your key at: https://jina.ai/?sui=apikey")?;
+        let api_key = crate::settings::api_key("JINA_API_KEY")
+            .context("JINA_API_KEY environment variable not set (or put it in the file named by `cs --config set api-keys-file PATH`). Get your key at: https://jina.ai/?sui=apikey")?;
 
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(60)) // 1 minute for reranking
@@ -182,6 +183,7 @@ impl Reranker for JinaApiReranker {
                 query: query.to_string(),
                 document: documents[item.index].clone(),
                 score: item.relevance_score,
+                index: item.index,
             })
             .collect();
 
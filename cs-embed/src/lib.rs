@@ -3,7 +3,11 @@ use anyhow::Result;
 #[cfg(feature = "fastembed")]
 use std::path::{Path, PathBuf};
 
+#[cfg(feature = "fastembed")]
+pub mod cache;
+pub mod daemon;
 pub mod reranker;
+mod settings;
 pub mod tokenizer;
 
 #[cfg(feature = "jina-api")]
@@ -38,8 +42,30 @@ pub fn create_embedder_with_progress(
     model_name: Option<&str>,
     progress_callback: Option<ModelDownloadCallback>,
 ) -> Result<Box<dyn Embedder>> {
-    let model = model_name.unwrap_or("BAAI/bge-small-en-v1.5");
+    // Aliases ("nomic-v1.5") resolve to full model names; names the registry does not know
+    // are passed through to the backend
+    let registry = cs_models::ModelRegistry::default();
+    let model = model_name.unwrap_or(&registry.default_model);
+    let model = registry
+        .find(model)
+        .map_or(model, |(_, config)| config.name.as_str());
+
+    // A running `cs --daemon` already has the model loaded
+    if let Some(embedder) = daemon::connect(model) {
+        if let Some(ref callback) = progress_callback {
+            callback(&format!("Using embedding daemon for model: {}", model));
+        }
+        return embedder;
+    }
 
+    load_embedder(model, progress_callback)
+}
+
+/// Load `model`, a full model name, in this process
+pub(crate) fn load_embedder(
+    model: &str,
+    progress_callback: Option<ModelDownloadCallback>,
+) -> Result<Box<dyn Embedder>> {
     // Check if this is a Jina API model
     #[cfg(feature = "jina-api")]
     {
@@ -153,26 +179,10 @@ impl FastEmbedder {
     ) -> Result<Self> {
         use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 
-        let model = match model_name {
-            // Current models
-            "BAAI/bge-small-en-v1.5" => EmbeddingModel::BGESmallENV15,
-            "sentence-transformers/all-MiniLM-L6-v2" => EmbeddingModel::AllMiniLML6V2,
-
-            // Enhanced models with longer context
-            "nomic-embed-text-v1" => EmbeddingModel::NomicEmbedTextV1,
-            "nomic-embed-text-v1.5" => EmbeddingModel::NomicEmbedTextV15,
-            "jina-embeddings-v2-base-code" => EmbeddingModel::JinaEmbeddingsV2BaseCode,
-
-            // BGE variants
-            "BAAI/bge-base-en-v1.5" => EmbeddingModel::BGEBaseENV15,
-            "BAAI/bge-large-en-v1.5" => EmbeddingModel::BGELargeENV15,
-
-            // Default to Nomic v1.5 for better performance
-            _ => EmbeddingModel::NomicEmbedTextV15,
-        };
+        let model = Self::fastembed_model(model_name);
 
         // Configure permanent model cache directory
-        let model_cache_dir = Self::get_model_cache_dir()?;
+        let model_cache_dir = model_cache_dir();
         std::fs::create_dir_all(&model_cache_dir)?;
 
         if let Some(ref callback) = progress_callback {
@@ -210,7 +220,8 @@ impl FastEmbedder {
         let init_options = InitOptions::new(model.clone())
             .with_show_download_progress(progress_callback.is_some())
             .with_cache_dir(model_cache_dir)
-            .with_max_length(max_length);
+            .with_max_length(max_length)
+            .with_execution_providers(settings::execution_providers());
 
         let embedding = TextEmbedding::try_new(init_options)?;
 
@@ -242,29 +253,63 @@ impl FastEmbedder {
         })
     }
 
-    fn get_model_cache_dir() -> Result<PathBuf> {
-        // Use platform-appropriate cache directory
-        let cache_dir = if let Some(cache_home) = std::env::var_os("XDG_CACHE_HOME") {
-            PathBuf::from(cache_home).join("cs")
-        } else if let Some(home) = std::env::var_os("HOME") {
-            PathBuf::from(home).join(".cache").join("cs")
-        } else if let Some(appdata) = std::env::var_os("LOCALAPPDATA") {
-            PathBuf::from(appdata).join("cs").join("cache")
-        } else {
-            // Fallback to current directory if no home found
-            PathBuf::from(".cs_models")
-        };
+    fn fastembed_model(model_name: &str) -> fastembed::EmbeddingModel {
+        use fastembed::EmbeddingModel;
+
+        match model_name {
+            // Current models
+            "BAAI/bge-small-en-v1.5" => EmbeddingModel::BGESmallENV15,
+            "sentence-transformers/all-MiniLM-L6-v2" => EmbeddingModel::AllMiniLML6V2,
+
+            // Enhanced models with longer context
+            "nomic-embed-text-v1" => EmbeddingModel::NomicEmbedTextV1,
+            "nomic-embed-text-v1.5" => EmbeddingModel::NomicEmbedTextV15,
+            "jina-embeddings-v2-base-code" => EmbeddingModel::JinaEmbeddingsV2BaseCode,
 
-        Ok(cache_dir.join("models"))
+            // BGE variants
+            "BAAI/bge-base-en-v1.5" => EmbeddingModel::BGEBaseENV15,
+            "BAAI/bge-large-en-v1.5" => EmbeddingModel::BGELargeENV15,
+
+            // Default to Nomic v1.5 for better performance
+            _ => EmbeddingModel::NomicEmbedTextV15,
+        }
     }
 
     fn check_model_exists(cache_dir: &Path, model_name: &str) -> bool {
-        // Simple heuristic - check if model directory exists
-        let model_dir = cache_dir.join(model_name.replace("/", "_"));
-        model_dir.exists()
+        // The Hugging Face cache keeps each model repository in `models--<org>--<name>`
+        let model = Self::fastembed_model(model_name);
+        let Ok(info) = fastembed::TextEmbedding::get_model_info(&model) else {
+            return false;
+        };
+        let repo_dir = format!("models--{}", info.model_code.replace('/', "--"));
+        cache_dir.join(repo_dir).exists()
     }
 }
 
+/// Directory local models are downloaded to (`cache-dir` in the user config)
+#[cfg(feature = "fastembed")]
+pub fn model_cache_dir() -> PathBuf {
+    settings::cache_root().join("models")
+}
+
+/// Directory local reranker models are downloaded to
+#[cfg(feature = "fastembed")]
+pub fn reranker_cache_dir() -> PathBuf {
+    settings::cache_root().join("rerankers")
+}
+
+/// Whether a local model is already downloaded, so loading it needs no network
+#[cfg(feature = "fastembed")]
+pub fn is_model_cached(model_name: &str) -> bool {
+    FastEmbedder::check_model_exists(&model_cache_dir(), model_name)
+}
+
+/// Whether an API key is set, in the environment or in the user config's `api-keys-file`
+#[cfg(feature = "jina-api")]
+pub fn has_api_key(name: &str) -> bool {
+    settings::api_key(name).is_some()
+}
+
 #[cfg(feature = "fastembed")]
 impl Embedder for FastEmbedder {
     fn id(&self) -> &'static str {
@@ -8,6 +8,8 @@ pub struct RerankResult {
     pub query: String,
     pub document: String,
     pub score: f32,
+    /// Position of the document in the input slice (results may come back sorted by score)
+    pub index: usize,
 }
 
 pub trait Reranker: Send + Sync {
@@ -52,8 +54,8 @@ pub fn create_reranker_with_progress(
                 }
                 Err(e) => {
                     // API failed, log warning and fall back to local model
-                    eprintln!(
-                        "⚠️  Jina API reranker unavailable ({}), falling back to local model",
+                    tracing::warn!(
+                        "Jina API reranker unavailable ({}), falling back to local model",
                         e
                     );
                     if let Some(ref callback) = progress_callback {
@@ -115,6 +117,7 @@ impl Reranker for DummyReranker {
                     query: query.to_string(),
                     document: doc.clone(),
                     score: 0.5 + (i as f32 * 0.1) % 0.5, // Fake scores between 0.5-1.0
+                    index: i,
                 }
             })
             .collect())
@@ -170,7 +173,8 @@ impl FastReranker {
 
         let init_options = RerankInitOptions::new(model.clone())
             .with_show_download_progress(progress_callback.is_some())
-            .with_cache_dir(model_cache_dir);
+            .with_cache_dir(model_cache_dir)
+            .with_execution_providers(crate::settings::execution_providers());
 
         let reranker = TextRerank::try_new(init_options)?;
 
@@ -185,19 +189,7 @@ impl FastReranker {
     }
 
     fn get_model_cache_dir() -> Result<PathBuf> {
-        // Use platform-appropriate cache directory (same as embedder)
-        let cache_dir = if let Some(cache_home) = std::env::var_os("XDG_CACHE_HOME") {
-            PathBuf::from(cache_home).join("cs")
-        } else if let Some(home) = std::env::var_os("HOME") {
-            PathBuf::from(home).join(".cache").join("cs")
-        } else if let Some(appdata) = std::env::var_os("LOCALAPPDATA") {
-            PathBuf::from(appdata).join("cs").join("cache")
-        } else {
-            // Fallback to current directory if no home found
-            PathBuf::from(".cs_models")
-        };
-
-        Ok(cache_dir.join("rerankers"))
+        Ok(crate::reranker_cache_dir())
     }
 
     fn check_model_exists(cache_dir: &std::path::Path, model_name: &str) -> bool {
@@ -220,14 +212,14 @@ impl Reranker for FastReranker {
         // Get reranking scores - fastembed rerank takes (query, documents)
         let results = self.model.rerank(query, docs, true, None)?;
 
-        // Convert to our format
+        // Convert to our format; results come back sorted by score, so map through their index
         let rerank_results = results
             .into_iter()
-            .enumerate()
-            .map(|(i, result)| RerankResult {
+            .map(|result| RerankResult {
                 query: query.to_string(),
-                document: documents[i].clone(),
+                document: documents[result.index].clone(),
                 score: result.score,
+                index: result.index,
             })
             .collect();
 
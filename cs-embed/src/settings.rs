@@ -0,0 +1,82 @@
+// Embedding runtime settings from the user config (`cs --config set KEY VALUE`):
+// model cache directory, device for local models, and a file holding API keys
+
+/// The user config, or defaults when there is none or it cannot be read
+#[cfg(any(feature = "fastembed", feature = "jina-api"))]
+fn user_config() -> cs_models::UserConfig {
+    cs_models::UserConfig::load().unwrap_or_default()
+}
+
+/// Root of the model cache: `cache-dir` from the user config, else the platform cache directory
+#[cfg(feature = "fastembed")]
+pub(crate) fn cache_root() -> std::path::PathBuf {
+    use std::path::PathBuf;
+
+    if let Some(cache_dir) = user_config().cache_dir {
+        return cache_dir;
+    }
+    if let Some(cache_home) = std::env::var_os("XDG_CACHE_HOME") {
+        PathBuf::from(cache_home).join("cs")
+    } else if let Some(home) = std::env::var_os("HOME") {
+        PathBuf::from(home).join(".cache").join("cs")
+    } else if let Some(appdata) = std::env::var_os("LOCALAPPDATA") {
+        PathBuf::from(appdata).join("cs").join("cache")
+    } else {
+        // Fallback to current directory if no home found
+        PathBuf::from(".cs_models")
+    }
+}
+
+/// Execution providers for the configured `device`; ONNX Runtime falls back to the CPU when
+/// the device is unavailable
+#[cfg(feature = "fastembed")]
+pub(crate) fn execution_providers() -> Vec<fastembed::ExecutionProviderDispatch> {
+    use ort::execution_providers::{
+        CUDAExecutionProvider, CoreMLExecutionProvider, DirectMLExecutionProvider,
+    };
+
+    match user_config().device.as_str() {
+        "cuda" => vec![CUDAExecutionProvider::default().build()],
+        "coreml" => vec![CoreMLExecutionProvider::default().build()],
+        "directml" => vec![DirectMLExecutionProvider::default().build()],
+        _ => Vec::new(),
+    }
+}
+
+/// An API key from the environment, else from the user config's `api-keys-file`
+#[cfg(feature = "jina-api")]
+pub(crate) fn api_key(name: &str) -> Option<String> {
+    if let Ok(key) = std::env::var(name) {
+        return Some(key);
+    }
+    let content = std::fs::read_to_string(user_config().api_keys_file?).ok()?;
+    parse_key_file(&content, name)
+}
+
+/// Value of `name` in `NAME=value` lines; blank lines and `#` comments are skipped
+#[cfg(any(feature = "jina-api", test))]
+fn parse_key_file(content: &str, name: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let line = line.trim();
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line.split_once('=')?;
+        (!line.starts_with('#') && key.trim() == name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_file() {
+        let content = "# keys\nOTHER_KEY=abc\nexport JINA_API_KEY = \"jina_123\"\n";
+        assert_eq!(
+            parse_key_file(content, "JINA_API_KEY"),
+            Some("jina_123".to_string())
+        );
+        assert_eq!(parse_key_file(content, "MISSING_KEY"), None);
+        assert_eq!(parse_key_file("#JINA_API_KEY=x", "JINA_API_KEY"), None);
+    }
+}
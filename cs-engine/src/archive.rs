@@ -0,0 +1,288 @@
+// Archive and compressed-file expansion for --search-zip
+// Entries are decompressed one at a time and searched as virtual files named
+// `archive.zip!inner/path`; reading stops at a per-archive budget so a zip bomb or an archive
+// with huge numbers of entries cannot exhaust memory
+
+use anyhow::Result;
+use cs_core::archive::{ArchiveKind, archive_kind, entry_path};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// How much of an archive is read
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    /// Largest decompressed entry searched; bigger entries are skipped
+    entry_bytes: u64,
+    /// Decompressed bytes read from one archive, skipped entries included
+    archive_bytes: u64,
+    /// Entries read from one archive
+    archive_entries: usize,
+}
+
+const LIMITS: Limits = Limits {
+    entry_bytes: 64 * 1024 * 1024,
+    archive_bytes: 1024 * 1024 * 1024,
+    archive_entries: 100_000,
+};
+
+/// Decompressed bytes of archive entries kept in memory at once, e.g. for `--lexical`
+pub(crate) const MAX_ARCHIVE_BYTES: u64 = LIMITS.archive_bytes;
+
+/// A decompressed file from inside an archive
+pub(crate) struct ArchiveEntry {
+    /// `archive!entry` for container formats, the archive path itself for single streams
+    pub path: PathBuf,
+    pub content: Vec<u8>,
+}
+
+/// Decompress `path` and pass its text entries to `visit` one at a time; binary entries are
+/// skipped like binary files. Entries past the archive budget are skipped with a warning.
+pub(crate) fn for_each_entry(
+    path: &Path,
+    visit: impl FnMut(ArchiveEntry) -> Result<()>,
+) -> Result<()> {
+    read_entries(path, LIMITS, visit)
+}
+
+fn read_entries(
+    path: &Path,
+    limits: Limits,
+    visit: impl FnMut(ArchiveEntry) -> Result<()>,
+) -> Result<()> {
+    let Some(kind) = archive_kind(path) else {
+        anyhow::bail!("Not a supported archive: {}", path.display());
+    };
+    let file = BufReader::new(File::open(path)?);
+
+    let mut reader = EntryReader {
+        archive: path,
+        limits,
+        visit,
+        bytes: 0,
+        entries: 0,
+    };
+    match kind {
+        ArchiveKind::Gzip => reader.stream(flate2::read::MultiGzDecoder::new(file))?,
+        ArchiveKind::Zstd => reader.stream(zstd::Decoder::new(file)?)?,
+        ArchiveKind::Bzip2 => reader.stream(bzip2::read::BzDecoder::new(file))?,
+        ArchiveKind::Tar => reader.tar(file)?,
+        ArchiveKind::TarGz => reader.tar(flate2::read::MultiGzDecoder::new(file))?,
+        ArchiveKind::TarZstd => reader.tar(zstd::Decoder::new(file)?)?,
+        ArchiveKind::TarBz2 => reader.tar(bzip2::read::BzDecoder::new(file))?,
+        ArchiveKind::Zip => reader.zip(file)?,
+    }
+    if reader.over_budget() {
+        tracing::warn!(
+            "Searched only part of {}: --search-zip reads at most {} entries or {} MB per archive",
+            path.display(),
+            limits.archive_entries,
+            limits.archive_bytes / (1024 * 1024)
+        );
+    }
+    Ok(())
+}
+
+struct EntryReader<'a, F> {
+    archive: &'a Path,
+    limits: Limits,
+    visit: F,
+    /// Decompressed bytes and entries read so far
+    bytes: u64,
+    entries: usize,
+}
+
+impl<F: FnMut(ArchiveEntry) -> Result<()>> EntryReader<'_, F> {
+    fn over_budget(&self) -> bool {
+        self.bytes > self.limits.archive_bytes || self.entries > self.limits.archive_entries
+    }
+
+    fn stream(&mut self, reader: impl Read) -> Result<()> {
+        let name = self.archive.display().to_string();
+        self.entry(reader, self.archive.to_path_buf(), &name)
+    }
+
+    fn tar(&mut self, reader: impl Read) -> Result<()> {
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            if self.over_budget() {
+                break;
+            }
+            let entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = entry.path()?.to_string_lossy().into_owned();
+            self.entry(entry, entry_path(self.archive, &name), &name)?;
+        }
+        Ok(())
+    }
+
+    fn zip(&mut self, reader: impl Read + std::io::Seek) -> Result<()> {
+        let mut zip = zip::ZipArchive::new(reader)?;
+        for idx in 0..zip.len() {
+            if self.over_budget() {
+                break;
+            }
+            let entry = zip.by_index(idx)?;
+            if !entry.is_file() {
+                continue;
+            }
+            let name = entry.name().to_string();
+            self.entry(entry, entry_path(self.archive, &name), &name)?;
+        }
+        Ok(())
+    }
+
+    /// Read one entry within the limits and pass it on unless it is oversized, cut short by the
+    /// archive budget or binary (NUL-containing). Text in other encodings is left as is and
+    /// transcoded when searched.
+    fn entry(&mut self, reader: impl Read, path: PathBuf, name: &str) -> Result<()> {
+        self.entries += 1;
+        if self.over_budget() {
+            return Ok(());
+        }
+        let remaining = self.limits.archive_bytes - self.bytes;
+        let mut content = Vec::new();
+        reader
+            .take(self.limits.entry_bytes.min(remaining) + 1)
+            .read_to_end(&mut content)?;
+        self.bytes += content.len() as u64;
+        if content.len() as u64 > self.limits.entry_bytes {
+            tracing::debug!(
+                "Skipping archive entry {} larger than {} bytes",
+                name,
+                self.limits.entry_bytes
+            );
+            return Ok(());
+        }
+        if self.over_budget() {
+            return Ok(());
+        }
+        // Same NUL-byte heuristic as file collection
+        let sample = &content[..content.len().min(cs_core::encoding::SAMPLE_SIZE)];
+        if sample.contains(&0) && !cs_core::encoding::is_utf16(sample) {
+            return Ok(());
+        }
+        (self.visit)(ArchiveEntry { path, content })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn read_all(path: &Path) -> Vec<ArchiveEntry> {
+        read_within(path, LIMITS)
+    }
+
+    fn read_within(path: &Path, limits: Limits) -> Vec<ArchiveEntry> {
+        let mut entries = Vec::new();
+        read_entries(path, limits, |entry| {
+            entries.push(entry);
+            Ok(())
+        })
+        .unwrap();
+        entries
+    }
+
+    #[test]
+    fn test_read_zip_and_tar_gz_entries() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let zip_path = temp_dir.path().join("bundle.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("src/", options).unwrap();
+        zip.start_file("src/lib.rs", options).unwrap();
+        zip.write_all(b"fn zipped() {}\n").unwrap();
+        zip.start_file("logo.png", options).unwrap();
+        zip.write_all(b"\x89PNG\x00\x00").unwrap();
+        zip.finish().unwrap();
+
+        let entries = read_all(&zip_path);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, entry_path(&zip_path, "src/lib.rs"));
+        assert_eq!(entries[0].content, b"fn zipped() {}\n");
+
+        let tgz_path = temp_dir.path().join("src.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&tgz_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut tar = tar::Builder::new(encoder);
+        let data = b"print('tarred')\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, "pkg/main.py", &data[..])
+            .unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let entries = read_all(&tgz_path);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, entry_path(&tgz_path, "pkg/main.py"));
+    }
+
+    #[test]
+    fn test_read_single_stream() {
+        let temp_dir = TempDir::new().unwrap();
+        let gz_path = temp_dir.path().join("app.log.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&gz_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(b"ERROR disk full\n").unwrap();
+        encoder.finish().unwrap();
+
+        let entries = read_all(&gz_path);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, gz_path);
+        assert_eq!(entries[0].content, b"ERROR disk full\n");
+    }
+
+    #[test]
+    fn test_archive_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        let tar_path = temp_dir.path().join("many.tar");
+        let mut tar = tar::Builder::new(File::create(&tar_path).unwrap());
+        for idx in 0..5 {
+            let data = format!("entry {idx:04}\n");
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, format!("{idx}.txt"), data.as_bytes())
+                .unwrap();
+        }
+        tar.into_inner().unwrap();
+
+        let names = |limits| -> Vec<PathBuf> {
+            read_within(&tar_path, limits)
+                .into_iter()
+                .map(|entry| entry.path)
+                .collect()
+        };
+        assert_eq!(names(LIMITS).len(), 5);
+        let few_entries = Limits {
+            archive_entries: 3,
+            ..LIMITS
+        };
+        assert_eq!(
+            names(few_entries),
+            ["0.txt", "1.txt", "2.txt"].map(|name| entry_path(&tar_path, name))
+        );
+        // Each entry is 11 bytes: the third is cut short by the byte budget and skipped
+        let few_bytes = Limits {
+            archive_bytes: 25,
+            ..LIMITS
+        };
+        assert_eq!(
+            names(few_bytes),
+            ["0.txt", "1.txt"].map(|name| entry_path(&tar_path, name))
+        );
+    }
+}
@@ -144,6 +144,8 @@ pub async fn ast_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
                 symbol,
                 chunk_hash: None,
                 index_epoch: None,
+                signals: None,
+                explanation: None,
             }
         })
         .collect();
@@ -0,0 +1,210 @@
+// Search mode auto-selection from the shape of the query
+// Code-like queries go to regex, descriptive questions to semantic search
+
+use cs_core::SearchMode;
+
+/// Mode picked for a query, with a short human-readable reason for reporting
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeChoice {
+    pub mode: SearchMode,
+    pub reason: &'static str,
+}
+
+/// Leading words that make a short query read like a declaration rather than prose
+const CODE_KEYWORDS: &[&str] = &[
+    "fn",
+    "def",
+    "func",
+    "function",
+    "class",
+    "struct",
+    "enum",
+    "trait",
+    "impl",
+    "interface",
+    "type",
+    "import",
+    "from",
+    "use",
+    "const",
+    "let",
+    "var",
+    "package",
+    "module",
+    "pub",
+    "async",
+    "return",
+    "#include",
+    "#define",
+];
+
+const QUESTION_WORDS: &[&str] = &[
+    "how", "where", "what", "why", "which", "when", "who", "does", "is", "are", "can", "find",
+    "show",
+];
+
+/// Classify a query as regex, semantic, hybrid, or AST based on its characteristics
+pub fn auto_select_mode(query: &str) -> ModeChoice {
+    let query = query.trim();
+    let words: Vec<&str> = query.split_whitespace().collect();
+
+    if has_metavariable(query) {
+        return ModeChoice {
+            mode: SearchMode::Ast,
+            reason: "AST metavariables",
+        };
+    }
+
+    if looks_like_regex(query) {
+        return ModeChoice {
+            mode: SearchMode::Regex,
+            reason: "regex syntax",
+        };
+    }
+
+    if words.iter().any(|word| is_code_token(word)) {
+        return ModeChoice {
+            mode: SearchMode::Regex,
+            reason: "code identifiers or punctuation",
+        };
+    }
+
+    if words.len() <= 3
+        && words
+            .first()
+            .is_some_and(|word| CODE_KEYWORDS.contains(word))
+    {
+        return ModeChoice {
+            mode: SearchMode::Regex,
+            reason: "starts with a code keyword",
+        };
+    }
+
+    let first = words
+        .first()
+        .map(|word| word.to_lowercase())
+        .unwrap_or_default();
+    if query.ends_with('?') || (words.len() >= 3 && QUESTION_WORDS.contains(&first.as_str())) {
+        return ModeChoice {
+            mode: SearchMode::Semantic,
+            reason: "natural-language question",
+        };
+    }
+
+    if words.len() >= 4 {
+        return ModeChoice {
+            mode: SearchMode::Semantic,
+            reason: "descriptive phrase",
+        };
+    }
+
+    if words.len() >= 2 {
+        return ModeChoice {
+            mode: SearchMode::Hybrid,
+            reason: "short phrase",
+        };
+    }
+
+    ModeChoice {
+        mode: SearchMode::Regex,
+        reason: "single term",
+    }
+}
+
+/// `$NAME`, `$$ARGS`, `$$$` style ast-grep metavariables
+fn has_metavariable(query: &str) -> bool {
+    query.as_bytes().windows(2).any(|pair| {
+        pair[0] == b'$' && (pair[1] == b'$' || pair[1] == b'_' || pair[1].is_ascii_uppercase())
+    })
+}
+
+fn looks_like_regex(query: &str) -> bool {
+    const REGEX_MARKERS: &[&str] = &[
+        "\\b", "\\w", "\\d", "\\s", "\\.", ".*", ".+", ".?", "(?", "[^", "|",
+    ];
+    REGEX_MARKERS.iter().any(|marker| query.contains(marker))
+        || query.starts_with('^')
+        || (query.ends_with('$') && query.len() > 1)
+        || (query.contains('[') && query.contains(']'))
+}
+
+fn is_code_token(word: &str) -> bool {
+    const CODE_MARKERS: &[&str] = &[
+        "::", "->", "=>", "(", ")", "{", "}", ";", "=", "<", ">", "&", "#[", "@",
+    ];
+    if CODE_MARKERS.iter().any(|marker| word.contains(marker)) {
+        return true;
+    }
+
+    let word = word.trim_matches(|c: char| matches!(c, ',' | '?' | '!' | '"' | '\'' | ':'));
+    let is_identifier = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    if !is_identifier {
+        return false;
+    }
+
+    // snake_case / SCREAMING_CASE
+    let snake = word.trim_matches('_').contains('_');
+    // camelCase / PascalCase with an inner capital after a lowercase letter
+    let camel = word
+        .as_bytes()
+        .windows(2)
+        .any(|pair| pair[0].is_ascii_lowercase() && pair[1].is_ascii_uppercase());
+    // member access such as `self.retry` or `os.path`, but not a sentence-ending period
+    let dotted = word
+        .trim_end_matches('.')
+        .split('.')
+        .filter(|part| !part.is_empty())
+        .count()
+        > 1;
+
+    snake || camel || dotted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mode(query: &str) -> SearchMode {
+        auto_select_mode(query).mode
+    }
+
+    #[test]
+    fn test_code_queries_use_regex() {
+        assert_eq!(mode("fn parse_header"), SearchMode::Regex);
+        assert_eq!(mode("parseHeader"), SearchMode::Regex);
+        assert_eq!(mode("std::fs::read"), SearchMode::Regex);
+        assert_eq!(mode("self.retry_count"), SearchMode::Regex);
+        assert_eq!(mode("def upload"), SearchMode::Regex);
+        assert_eq!(mode("TODO"), SearchMode::Regex);
+        assert_eq!(mode(r"\berror\b"), SearchMode::Regex);
+        assert_eq!(mode("^import .*"), SearchMode::Regex);
+        assert_eq!(mode("foo|bar"), SearchMode::Regex);
+    }
+
+    #[test]
+    fn test_prose_queries_use_semantic() {
+        let choice = auto_select_mode("where do we retry failed uploads");
+        assert_eq!(choice.mode, SearchMode::Semantic);
+        assert_eq!(choice.reason, "natural-language question");
+
+        assert_eq!(mode("retry failed uploads?"), SearchMode::Semantic);
+        assert_eq!(
+            mode("use exponential backoff when uploads fail"),
+            SearchMode::Semantic
+        );
+        assert_eq!(
+            mode("database connection pooling and cleanup"),
+            SearchMode::Semantic
+        );
+    }
+
+    #[test]
+    fn test_short_phrases_and_patterns() {
+        assert_eq!(mode("connection timeout"), SearchMode::Hybrid);
+        assert_eq!(mode("function $NAME($$$)"), SearchMode::Ast);
+        assert_eq!(mode("costs $5"), SearchMode::Hybrid);
+    }
+}
@@ -0,0 +1,141 @@
+// Chunk-type filtering (`--only functions,classes`)
+// Semantic search filters sidecar chunks directly; lexical hits are whole files, so they are
+// narrowed to the chunks of the requested types that contain a query term
+
+use cs_core::{SearchOptions, SearchResult};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// True when `chunk_type` (as stored in `ChunkEntry.chunk_type`) passes the `--only` filter
+pub(crate) fn chunk_type_allowed(options: &SearchOptions, chunk_type: Option<&str>) -> bool {
+    options.only_chunk_types.is_empty()
+        || chunk_type.is_some_and(|kind| options.only_chunk_types.iter().any(|only| only == kind))
+}
+
+/// Replace whole-file lexical hits with the matching chunks of the requested types
+pub(crate) fn narrow_lexical_results(
+    options: &SearchOptions,
+    index_root: &Path,
+    results: Vec<SearchResult>,
+) -> Vec<SearchResult> {
+    let terms = query_terms(options);
+    let mut narrowed = Vec::new();
+
+    for result in results {
+        let sidecar = cs_core::get_sidecar_path(index_root, &result.file);
+        let Ok(entry) = cs_index::load_index_entry(&sidecar) else {
+            tracing::debug!(
+                "No sidecar for {:?}; dropping from --only results",
+                result.file
+            );
+            continue;
+        };
+        let Ok(content) = cs_core::encoding::read(&result.file) else {
+            continue;
+        };
+
+        for chunk in entry.chunks {
+            if !chunk_type_allowed(options, chunk.chunk_type_name())
+                || options.exclude_tests
+                    && super::test_filter::is_test_chunk_entry(&chunk, &content)
+            {
+                continue;
+            }
+            let Some(bytes) = content.get(chunk.span.byte_start..chunk.span.byte_end) else {
+                continue;
+            };
+            let text = String::from_utf8_lossy(bytes);
+            if !tokens(&text).any(|token| terms.contains(&token)) {
+                continue;
+            }
+
+            let preview = if options.full_section {
+                text.into_owned()
+            } else {
+                text.lines().take(3).collect::<Vec<_>>().join("\n")
+            };
+            narrowed.push(SearchResult {
+                span: chunk.span,
+                preview,
+                ..result.clone()
+            });
+        }
+    }
+
+    narrowed
+}
+
+/// Lowercased alphanumeric terms of the query, roughly what tantivy's default tokenizer indexes
+fn query_terms(options: &SearchOptions) -> HashSet<String> {
+    options
+        .patterns()
+        .flat_map(|pattern| tokens(pattern).collect::<Vec<_>>())
+        .filter(|term| !matches!(term.as_str(), "and" | "or" | "not"))
+        .collect()
+}
+
+fn tokens(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_type_allowed() {
+        let mut options = SearchOptions::default();
+        assert!(chunk_type_allowed(&options, None));
+        assert!(chunk_type_allowed(&options, Some("class")));
+
+        options.only_chunk_types = vec!["function".to_string(), "method".to_string()];
+        assert!(chunk_type_allowed(&options, Some("method")));
+        assert!(!chunk_type_allowed(&options, Some("class")));
+        assert!(!chunk_type_allowed(&options, None));
+    }
+
+    #[tokio::test]
+    async fn test_lexical_hits_narrowed_to_chunks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join(".cs")).unwrap();
+        let file = root.join("cache.py");
+        std::fs::write(
+            &file,
+            "# cache eviction notes\n\nclass Cache:\n    pass\n\n\ndef evict(cache):\n    return cache.pop()\n",
+        )
+        .unwrap();
+        cs_index::index_file(&file, false).await.unwrap();
+
+        let options = SearchOptions {
+            query: "evict".to_string(),
+            only_chunk_types: vec!["function".to_string()],
+            ..Default::default()
+        };
+        let hit = SearchResult {
+            file: file.clone(),
+            span: cs_core::Span {
+                byte_start: 0,
+                byte_end: 0,
+                line_start: 1,
+                line_end: 8,
+            },
+            score: 1.0,
+            preview: String::new(),
+            lang: None,
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            signals: None,
+            explanation: None,
+        };
+
+        let narrowed = narrow_lexical_results(&options, root, vec![hit]);
+        assert_eq!(narrowed.len(), 1);
+        assert_eq!(narrowed[0].span.line_start, 7);
+        assert!(narrowed[0].preview.starts_with("def evict(cache):"));
+        assert_eq!(narrowed[0].score, 1.0);
+    }
+}
@@ -0,0 +1,213 @@
+//! Stable chunk ids (`chunk_hash`) for results of every engine.
+//!
+//! A result's id names the smallest chunk of its file that holds it: the sidecar's chunks while
+//! the index is current for the file, otherwise the chunker's. A regex match and a semantic hit
+//! in the same function therefore share an id, and `get_chunk` / `--get-chunk` find the chunk
+//! again from the id alone. A result outside every chunk gets the id of its own span.
+
+use cs_core::{SearchResult, Span};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A chunk of a file, with whatever metadata its source provides
+#[derive(Debug, Clone)]
+pub struct FileChunk {
+    pub span: Span,
+    pub chunk_type: Option<String>,
+    pub breadcrumb: Option<String>,
+    pub ancestry: Vec<String>,
+    pub estimated_tokens: Option<usize>,
+}
+
+impl FileChunk {
+    /// An arbitrary span with no chunk metadata
+    pub fn plain(span: Span) -> Self {
+        Self {
+            span,
+            chunk_type: None,
+            breadcrumb: None,
+            ancestry: Vec::new(),
+            estimated_tokens: None,
+        }
+    }
+}
+
+/// Chunks of `file`: from its sidecar when the index is current for it, otherwise chunked now.
+/// Also returns which of the two it was (`"index"` or `"chunker"`).
+pub fn file_chunks(file: &Path, content: &str) -> (Vec<FileChunk>, &'static str) {
+    let indexed = crate::find_nearest_index_root(file).and_then(|root| {
+        let entry = cs_index::load_index_entry(&cs_core::get_sidecar_path(&root, file)).ok()?;
+        let hash = cs_core::compute_file_hash(file).ok()?;
+        (entry.metadata.hash == hash).then_some(entry)
+    });
+    if let Some(entry) = indexed {
+        let chunks = entry
+            .chunks
+            .into_iter()
+            .map(|chunk| FileChunk {
+                span: chunk.span,
+                chunk_type: chunk_type_name(&chunk.chunk_type).map(str::to_string),
+                breadcrumb: chunk.breadcrumb,
+                ancestry: chunk.ancestry.unwrap_or_default(),
+                estimated_tokens: chunk.estimated_tokens,
+            })
+            .collect();
+        return (chunks, "index");
+    }
+
+    let chunks = cs_chunk::chunk_text(content, cs_core::Language::from_path(file))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|chunk| FileChunk {
+            span: chunk.span,
+            chunk_type: chunk_type_name(&chunk.chunk_type).map(str::to_string),
+            breadcrumb: chunk.metadata.breadcrumb,
+            ancestry: chunk.metadata.ancestry,
+            estimated_tokens: Some(chunk.metadata.estimated_tokens),
+        })
+        .collect();
+    (chunks, "chunker")
+}
+
+pub use cs_index::chunk_type_name;
+
+/// The smallest chunk holding `span`
+pub fn enclosing_chunk<'a>(chunks: &'a [FileChunk], span: &Span) -> Option<&'a FileChunk> {
+    chunks
+        .iter()
+        .filter(|chunk| {
+            chunk.span.byte_start <= span.byte_start && chunk.span.byte_end >= span.byte_end
+        })
+        .min_by_key(|chunk| chunk.span.byte_end - chunk.span.byte_start)
+}
+
+/// Id of the text at `span` in `file`, whose contents are `content`; `None` when the span is
+/// out of bounds or splits a character
+pub fn chunk_id(file: &Path, span: &Span, content: &str) -> Option<String> {
+    let text = content.get(span.byte_start..span.byte_end)?;
+    Some(cs_core::compute_chunk_id(
+        &id_path(file),
+        span,
+        text.as_bytes(),
+    ))
+}
+
+/// Chunks of `file` whose id starts with `prefix`
+pub fn chunks_with_id(
+    file: &Path,
+    content: &str,
+    chunks: Vec<FileChunk>,
+    prefix: &str,
+) -> Vec<FileChunk> {
+    let path = id_path(file);
+    chunks
+        .into_iter()
+        .filter(|chunk| {
+            content
+                .get(chunk.span.byte_start..chunk.span.byte_end)
+                .is_some_and(|text| {
+                    cs_core::compute_chunk_id(&path, &chunk.span, text.as_bytes())
+                        .starts_with(prefix)
+                })
+        })
+        .collect()
+}
+
+/// Path hashed into ids: relative to the index root holding the file, or absolute when there
+/// is none, `/`-separated either way
+fn id_path(file: &Path) -> String {
+    let file = cs_core::paths::canonicalize(file);
+    let relative = crate::find_nearest_index_root(&file)
+        .and_then(|root| cs_core::paths::relative_to(&file, &root))
+        .unwrap_or_else(|| file.clone());
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Set `chunk_hash` on every result whose file can be read as UTF-8
+pub(crate) fn assign_chunk_ids(results: &mut [SearchResult]) {
+    let files: HashSet<&Path> = results
+        .iter()
+        .filter(|result| result.chunk_hash.is_none())
+        .map(|result| result.file.as_path())
+        .collect();
+    let contents: HashMap<PathBuf, (String, Vec<FileChunk>)> = files
+        .into_par_iter()
+        .filter_map(|path| {
+            let content = cs_core::encoding::read_to_string(path).ok()?;
+            let (chunks, _) = file_chunks(path, &content);
+            Some((path.to_path_buf(), (content, chunks)))
+        })
+        .collect();
+
+    for result in results
+        .iter_mut()
+        .filter(|result| result.chunk_hash.is_none())
+    {
+        let Some((content, chunks)) = contents.get(&result.file) else {
+            continue;
+        };
+        let span = enclosing_chunk(chunks, &result.span)
+            .map(|chunk| &chunk.span)
+            .unwrap_or(&result.span);
+        result.chunk_hash = chunk_id(&result.file, span, content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ids_name_the_enclosing_chunk() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join(".cs")).unwrap();
+        let file = temp_dir.path().join("retry.py");
+        let content = "def retry(request):\n    return send(request)\n\n\ndef backoff(attempt):\n    return 2 ** attempt\n";
+        std::fs::write(&file, content).unwrap();
+
+        let result = |byte_start, line| SearchResult {
+            file: file.clone(),
+            span: Span::new(byte_start, byte_start + 4, line, line).unwrap(),
+            score: 1.0,
+            preview: String::new(),
+            lang: None,
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            explanation: None,
+            signals: None,
+        };
+        let send = content.find("send").unwrap();
+        let attempt = content.rfind("attempt").unwrap();
+        let mut results = vec![result(0, 1), result(send, 2), result(attempt, 6)];
+        assign_chunk_ids(&mut results);
+
+        // Both matches in retry() share its id; backoff() has another
+        let ids: Vec<&str> = results
+            .iter()
+            .map(|result| result.chunk_hash.as_deref().unwrap())
+            .collect();
+        assert_eq!(ids[0], ids[1]);
+        assert_ne!(ids[0], ids[2]);
+
+        // The path is relative to the index root, so a moved checkout keeps its ids
+        let (chunks, _) = file_chunks(&file, content);
+        let retry = chunks_with_id(&file, content, chunks, &ids[0][..12]);
+        assert_eq!(retry.len(), 1);
+        assert_eq!(retry[0].span.line_start, 1);
+        assert_eq!(
+            ids[0],
+            cs_core::compute_chunk_id(
+                "retry.py",
+                &retry[0].span,
+                &content.as_bytes()[retry[0].span.byte_start..retry[0].span.byte_end]
+            )
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! The search side of `cs --daemon`. The daemon runs the semantic stage of searches sent over
+//! its socket with its resident models, against indexes it keeps in memory (`resident`); a
+//! search in another process forwards that stage to it while it runs.
+
+use super::{Deadline, search_deadline, semantic_v3};
+use anyhow::Result;
+use cs_core::{SearchOptions, SearchResults};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Serve `cs --daemon` on `socket` until the process is stopped, reporting each event to
+/// `on_event`. Call from a blocking thread of a Tokio runtime, which runs the searches.
+pub fn serve(socket: &Path, on_event: impl Fn(&str) + Send + Sync + 'static) -> Result<()> {
+    super::resident::keep_indexes_resident();
+    let runtime = tokio::runtime::Handle::current();
+    cs_embed::daemon::serve(socket, on_event, move |options| {
+        runtime.block_on(semantic_v3::semantic_search_v3_until(
+            &options,
+            None,
+            search_deadline(&options, None),
+        ))
+    })
+}
+
+/// The semantic stage of `options` run by a running daemon within what is left of `deadline`;
+/// `None` leaves it to this process, as do searches with `--stats`, which time their stages
+/// here
+pub(crate) fn forward(
+    options: &SearchOptions,
+    deadline: Deadline<'_>,
+) -> Option<Result<SearchResults>> {
+    if options.stats.is_some() {
+        return None;
+    }
+    // The daemon runs in another directory, so it gets absolute paths and its results are
+    // spelled as this process would have spelled them
+    let cwd = std::env::current_dir().ok()?;
+    let forwarded = SearchOptions {
+        path: cwd.join(&options.path),
+        timeout: deadline
+            .at
+            .map(|at| at.saturating_duration_since(Instant::now())),
+        ..options.clone()
+    };
+    let results = cs_embed::daemon::search(&forwarded)?;
+    let local_root = semantic_v3::index_root(&options.path);
+    Some(results.map(|results| relative_to(results, &cwd.join(&local_root), &local_root)))
+}
+
+/// `results` with files under `absolute_root` moved under `local_root`
+fn relative_to(
+    mut results: SearchResults,
+    absolute_root: &Path,
+    local_root: &Path,
+) -> SearchResults {
+    let relative = |file: &mut PathBuf| {
+        if let Ok(rest) = file.strip_prefix(absolute_root) {
+            *file = local_root.join(rest);
+        }
+    };
+    for result in results
+        .matches
+        .iter_mut()
+        .chain(results.closest_below_threshold.as_mut())
+    {
+        relative(&mut result.file);
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cs_core::{SearchResult, Span};
+
+    fn result(file: &str) -> SearchResult {
+        SearchResult {
+            file: PathBuf::from(file),
+            span: Span::new(0, 1, 1, 1).unwrap(),
+            score: 1.0,
+            preview: String::new(),
+            lang: None,
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            explanation: None,
+            signals: None,
+        }
+    }
+
+    #[test]
+    fn test_forwarded_results_keep_local_paths() {
+        let results = SearchResults {
+            matches: vec![result("/work/repo/./src/lib.rs"), result("/elsewhere/a.rs")],
+            closest_below_threshold: Some(result("/work/repo/./README.md")),
+            partial: false,
+            lossy_files: 0,
+        };
+        let results = relative_to(results, Path::new("/work/repo/."), Path::new("."));
+        let files: Vec<_> = results
+            .matches
+            .iter()
+            .chain(&results.closest_below_threshold)
+            .map(|result| result.file.to_str().unwrap())
+            .collect();
+        assert_eq!(files, ["./src/lib.rs", "/elsewhere/a.rs", "./README.md"]);
+    }
+}
@@ -0,0 +1,149 @@
+// Overlap deduplication (`--dedupe`)
+// Ranked modes can return several results for the same code: a class chunk and a method inside
+// it, strides of one long chunk, or in hybrid search a chunk and a regex hit in it. Results of
+// one file whose byte ranges share at least half of the smaller range count as one; `smallest`
+// keeps the smallest of them, in the place of the best-ranked one it replaces, and `score`
+// keeps the best-ranked one.
+
+use cs_core::{Dedupe, SearchMode, SearchOptions, SearchResult};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Candidates fetched per requested result, so dropping duplicates still fills the page
+pub(crate) const DEDUPE_POOL_FACTOR: usize = 3;
+
+/// Share of the smaller byte range two results must have in common to count as one
+const MIN_OVERLAP: f64 = 0.5;
+
+/// Whether `--dedupe` applies to this search; regex, AST and symbol results are matches, not
+/// chunks, and are never merged
+pub(crate) fn applies(options: &SearchOptions) -> bool {
+    options.dedupe != Dedupe::None
+        && matches!(
+            options.mode,
+            SearchMode::Semantic | SearchMode::Lexical | SearchMode::Hybrid
+        )
+}
+
+/// `results`, best first, with one result kept of each group covering the same code
+pub(crate) fn dedupe(results: Vec<SearchResult>, prefer: Dedupe) -> Vec<SearchResult> {
+    if prefer == Dedupe::None {
+        return results;
+    }
+    let mut kept: Vec<Option<SearchResult>> = Vec::with_capacity(results.len());
+    let mut by_file: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+    for result in results {
+        let same_file = by_file.entry(result.file.clone()).or_default();
+        let overlapping: Vec<usize> = same_file
+            .iter()
+            .copied()
+            .filter(|&i| {
+                kept[i]
+                    .as_ref()
+                    .is_some_and(|other| same_code(other, &result))
+            })
+            .collect();
+        let Some(&first) = overlapping.first() else {
+            same_file.push(kept.len());
+            kept.push(Some(result));
+            continue;
+        };
+        // Results arrive best first, so otherwise the one already kept wins
+        let smaller = |i: usize| {
+            kept[i]
+                .as_ref()
+                .is_some_and(|other| size(&result) < size(other))
+        };
+        if prefer == Dedupe::Smallest && overlapping.iter().all(|&i| smaller(i)) {
+            for &i in &overlapping {
+                kept[i] = None;
+            }
+            kept[first] = Some(result);
+        }
+    }
+    kept.into_iter().flatten().collect()
+}
+
+fn size(result: &SearchResult) -> usize {
+    result
+        .span
+        .byte_end
+        .saturating_sub(result.span.byte_start)
+        .max(1)
+}
+
+fn same_code(a: &SearchResult, b: &SearchResult) -> bool {
+    let start = a.span.byte_start.max(b.span.byte_start);
+    let end = a.span.byte_end.min(b.span.byte_end);
+    let shared = end.saturating_sub(start);
+    shared > 0 && shared as f64 >= MIN_OVERLAP * size(a).min(size(b)) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cs_core::Span;
+
+    fn result(file: &str, byte_start: usize, byte_end: usize, score: f32) -> SearchResult {
+        SearchResult {
+            file: file.into(),
+            span: Span {
+                byte_start,
+                byte_end,
+                line_start: 1,
+                line_end: 1,
+            },
+            score,
+            preview: String::new(),
+            lang: None,
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            signals: None,
+            explanation: None,
+        }
+    }
+
+    fn spans(results: &[SearchResult]) -> Vec<(&str, usize, usize)> {
+        results
+            .iter()
+            .map(|r| (r.file.to_str().unwrap(), r.span.byte_start, r.span.byte_end))
+            .collect()
+    }
+
+    #[test]
+    fn test_dedupe_prefers_smallest_or_score() {
+        let results = vec![
+            // A class, then a method inside it
+            result("a.rs", 0, 1000, 0.9),
+            result("b.rs", 0, 400, 0.8),
+            result("a.rs", 200, 400, 0.7),
+            // Strides of one chunk, overlapping by half
+            result("b.rs", 200, 600, 0.6),
+            // Same bytes in another file, and a sliver of the class
+            result("c.rs", 200, 400, 0.5),
+            result("a.rs", 990, 1200, 0.4),
+        ];
+
+        assert_eq!(dedupe(results.clone(), Dedupe::None).len(), 6);
+        assert_eq!(
+            spans(&dedupe(results.clone(), Dedupe::Score)),
+            vec![
+                ("a.rs", 0, 1000),
+                ("b.rs", 0, 400),
+                ("c.rs", 200, 400),
+                ("a.rs", 990, 1200)
+            ]
+        );
+        // The method takes the place of its class; equal strides keep the better one
+        assert_eq!(
+            spans(&dedupe(results, Dedupe::Smallest)),
+            vec![
+                ("a.rs", 200, 400),
+                ("b.rs", 0, 400),
+                ("c.rs", 200, 400),
+                ("a.rs", 990, 1200)
+            ]
+        );
+    }
+}
@@ -0,0 +1,332 @@
+// Ephemeral search (`--ephemeral`): chunk, embed and score files in memory
+// Nothing is written under the search path, so read-only mounts and other people's checkouts
+// can be searched semantically without leaving a `.cs` directory behind
+
+use anyhow::Result;
+use cs_chunk::{Chunk, ChunkType};
+use cs_core::explain::{ScoreExplanation, StrideMatch};
+use cs_core::file_limits::DEFAULT_MAX_EMBED_FILESIZE;
+use cs_core::{Language, SearchOptions, SearchResult, StridePooling};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::semantic_v3::cosine_similarity;
+use super::{
+    Deadline, SearchProgressCallback, chunk_filter, deadline_passed, filter_files_in_scope,
+    lexical_search_documents, negative, rerank, resolve_model_for_path, score_scale,
+    stride_pooling, test_filter,
+};
+
+/// Chunks embedded per model call, bounding memory on large trees
+const EMBED_BATCH_SIZE: usize = 64;
+
+pub(crate) fn semantic_search(
+    options: &SearchOptions,
+    progress_callback: Option<&SearchProgressCallback>,
+    deadline: Deadline<'_>,
+) -> Result<cs_core::SearchResults> {
+    let resolved_model = resolve_model_for_path(&options.path, options.embedding_model.as_deref())?;
+    let model_name = resolved_model.canonical_name.as_str();
+
+    // Like indexing, embedding skips huge files unless `--max-filesize` sets another cap
+    let embed_options = SearchOptions {
+        file_limits: options
+            .file_limits
+            .or_max_filesize(DEFAULT_MAX_EMBED_FILESIZE),
+        ..options.clone()
+    };
+    let chunk_overrides = cs_index::chunk_overrides(&options.path);
+    let mut chunks = Vec::new();
+    let mut partial = false;
+    for file in collect_search_files(&embed_options)? {
+        if deadline_passed(deadline) {
+            partial = true;
+            break;
+        }
+        let Ok(text) = cs_core::encoding::read_to_string(&file) else {
+            continue;
+        };
+        let language = Language::from_path(&file);
+        let file_chunks = match cs_chunk::chunk_text_with_overrides(
+            &text,
+            language,
+            Some(model_name),
+            &chunk_overrides,
+        ) {
+            Ok(file_chunks) => file_chunks,
+            Err(e) => {
+                tracing::debug!("Error chunking {:?}: {}", file, e);
+                continue;
+            }
+        };
+        chunks.extend(
+            file_chunks
+                .into_iter()
+                .filter(|chunk| chunk_allowed(options, &file, chunk))
+                .map(|chunk| (file.clone(), chunk)),
+        );
+    }
+
+    if let Some(callback) = progress_callback {
+        callback(&format!("Embedding {} chunks in memory...", chunks.len()));
+    }
+    let scored = embed_and_score(options, model_name, chunks, deadline, &mut partial)?;
+    // Keep the wider pool for reranking, which applies top_k itself
+    let mut results = rank_chunks(options, scored, rerank::candidate_limit(options));
+    results.partial = partial;
+    Ok(results)
+}
+
+pub(crate) fn lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
+    let documents = collect_search_files(options)?
+        .into_iter()
+        .filter_map(|file| {
+            let text = cs_core::encoding::read_to_string(&file).ok()?;
+            Some((file, text))
+        });
+    lexical_search_documents(options, documents)
+}
+
+fn collect_search_files(options: &SearchOptions) -> Result<Vec<PathBuf>> {
+    let files = if options.path.is_file() {
+        vec![options.path.clone()]
+    } else {
+        cs_index::collect_files(
+            &options.path,
+            options.respect_gitignore,
+            &options.exclude_patterns,
+        )?
+    };
+    Ok(filter_files_in_scope(files, options))
+}
+
+/// `--only`, `--no-tests`, `--similar-to`'s own span and `--within` for a chunk produced in
+/// memory
+fn chunk_allowed(options: &SearchOptions, file: &Path, chunk: &Chunk) -> bool {
+    chunk_filter::chunk_type_allowed(options, chunk_type_name(&chunk.chunk_type))
+        && !(options.exclude_tests
+            && test_filter::is_test_chunk(
+                &chunk.metadata.leading_trivia,
+                &chunk.metadata.ancestry,
+                &chunk.text,
+            ))
+        && !options
+            .excluded_span
+            .as_ref()
+            .is_some_and(|span| span.overlaps(file, &chunk.span))
+        && options
+            .within
+            .as_ref()
+            .is_none_or(|within| within.contains(file, &chunk.span))
+}
+
+/// The sidecar name of a chunk kind; plain text chunks have none
+fn chunk_type_name(chunk_type: &ChunkType) -> Option<&'static str> {
+    match chunk_type {
+        ChunkType::Function => Some("function"),
+        ChunkType::Class => Some("class"),
+        ChunkType::Method => Some("method"),
+        ChunkType::Module => Some("module"),
+        ChunkType::Text => None,
+    }
+}
+
+/// Embed `chunks` in batches and score each against the query patterns (best pattern wins),
+/// lowered for resemblance to `--not` phrases
+pub(crate) fn embed_and_score(
+    options: &SearchOptions,
+    model_name: &str,
+    chunks: Vec<(PathBuf, Chunk)>,
+    deadline: Deadline<'_>,
+    partial: &mut bool,
+) -> Result<Vec<(f32, PathBuf, Chunk)>> {
+    if chunks.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut embedder = cs_embed::create_embedder(Some(model_name))?;
+    let queries: Vec<String> = options.patterns().map(str::to_string).collect();
+    let mut query_embeddings =
+        embedder.embed(&[queries.as_slice(), &options.negative_queries].concat())?;
+    let negative_embeddings = query_embeddings.split_off(queries.len().min(query_embeddings.len()));
+
+    let mut scored = Vec::with_capacity(chunks.len());
+    let mut negatives = Vec::new();
+    let mut chunks = chunks.into_iter().peekable();
+    while chunks.peek().is_some() {
+        if deadline_passed(deadline) {
+            *partial = true;
+            break;
+        }
+        let batch: Vec<(PathBuf, Chunk)> = chunks.by_ref().take(EMBED_BATCH_SIZE).collect();
+        let texts: Vec<String> = batch
+            .iter()
+            .map(|(_, chunk)| chunk.embedding_text().into_owned())
+            .collect();
+        let embeddings = embedder.embed(&texts)?;
+        for ((file, chunk), embedding) in batch.into_iter().zip(&embeddings) {
+            let similarity = query_embeddings
+                .iter()
+                .map(|query_embedding| cosine_similarity(query_embedding, embedding))
+                .fold(f32::NEG_INFINITY, f32::max);
+            if !negative_embeddings.is_empty() {
+                negatives.push(negative::similarity(&negative_embeddings, embedding));
+            }
+            scored.push((similarity, file, chunk));
+        }
+    }
+    if !negatives.is_empty() {
+        let weight = negative::negative_weight(options);
+        for ((similarity, _, _), negative) in scored.iter_mut().zip(negative::normalize(&negatives))
+        {
+            *similarity = negative::penalize(*similarity, negative, weight);
+        }
+    }
+    Ok(scored)
+}
+
+/// Best scores first, strides pooled into their chunk, cut to `limit`; results under
+/// `threshold` only feed the near-miss hint
+pub(crate) fn rank_chunks(
+    options: &SearchOptions,
+    mut scored: Vec<(f32, PathBuf, Chunk)>,
+    limit: Option<usize>,
+) -> cs_core::SearchResults {
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    // One result per long chunk rather than one per stride, with the text of all its strides
+    let whole_chunks = match options.stride_pooling {
+        StridePooling::None => HashMap::new(),
+        _ => stride_pooling::whole_chunks(
+            scored
+                .iter()
+                .map(|(_, file, chunk)| (file.as_path(), chunk)),
+        ),
+    };
+    let scored = stride_pooling::pool(
+        scored,
+        options.stride_pooling,
+        |(similarity, _, _)| similarity,
+        |(_, file, chunk)| {
+            let stride = chunk.stride_info.as_ref()?;
+            Some((file.clone(), stride.original_chunk_id.clone()))
+        },
+    );
+
+    let mut matches = Vec::new();
+    let mut closest_below_threshold = None;
+    let limit = limit.unwrap_or(scored.len());
+    for (similarity, file, mut chunk) in scored.into_iter().take(limit) {
+        if let Some((span, text)) = chunk
+            .stride_info
+            .as_ref()
+            .and_then(|stride| whole_chunks.get(&(file.clone(), stride.original_chunk_id.clone())))
+        {
+            chunk.span = span.clone();
+            chunk.text = text.clone();
+        }
+        let explanation = options.explain.then(|| ScoreExplanation {
+            cosine: Some(similarity),
+            chunk_type: chunk_type_name(&chunk.chunk_type).map(str::to_string),
+            stride: chunk.stride_info.as_ref().map(|stride| StrideMatch {
+                index: stride.stride_index,
+                total: stride.total_strides,
+            }),
+            ..Default::default()
+        });
+        let preview = if options.full_section {
+            chunk.text.clone()
+        } else {
+            chunk.text.lines().take(3).collect::<Vec<_>>().join("\n")
+        };
+        let score = score_scale::semantic(similarity);
+        let mut result = SearchResult {
+            lang: Language::from_path(&file),
+            file,
+            span: chunk.span,
+            score,
+            preview,
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            signals: None,
+            explanation,
+        };
+        score_scale::set(&mut result, similarity, score);
+
+        if options.threshold.is_some_and(|threshold| score < threshold) {
+            closest_below_threshold.get_or_insert(result);
+        } else {
+            matches.push(result);
+        }
+    }
+
+    cs_core::SearchResults {
+        matches,
+        closest_below_threshold,
+        partial: false,
+        lossy_files: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cs_core::SearchMode;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lexical_search_writes_no_index() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("pool.py"),
+            "class ConnectionPool:\n    pass\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("notes.md"), "nothing relevant\n").unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Lexical,
+            query: "ConnectionPool".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            ephemeral: true,
+            ..Default::default()
+        };
+        let results = lexical_search(&options).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].file.ends_with("pool.py"));
+        assert!(!temp_dir.path().join(".cs").exists());
+    }
+
+    #[test]
+    fn test_rank_chunks_applies_top_k_and_threshold() {
+        let chunk = |text: &str| {
+            cs_chunk::chunk_text(text, None)
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap()
+        };
+        let scored = vec![
+            (0.4, Path::new("b.txt").to_path_buf(), chunk("beta")),
+            (0.9, Path::new("a.txt").to_path_buf(), chunk("alpha")),
+            (0.1, Path::new("c.txt").to_path_buf(), chunk("gamma")),
+        ];
+        let options = SearchOptions {
+            top_k: Some(2),
+            threshold: Some(0.5),
+            ..Default::default()
+        };
+
+        let results = rank_chunks(&options, scored, options.top_k);
+        assert_eq!(results.matches.len(), 1);
+        assert_eq!(results.matches[0].preview, "alpha");
+        assert_eq!(
+            results.closest_below_threshold.unwrap().file,
+            Path::new("b.txt")
+        );
+    }
+}
@@ -0,0 +1,162 @@
+// Score explanations (`--explain`)
+// Each stage records its part of the score on the result: cosine similarity, BM25 rank, RRF
+// components, the rerank change, and which stride of a large chunk matched
+
+use cs_core::explain::{RerankDelta, RrfComponent, ScoreExplanation, StrideMatch};
+use cs_core::{FusionWeights, SearchResult};
+
+/// The result's explanation, created on first use
+pub(crate) fn explanation(result: &mut SearchResult) -> &mut ScoreExplanation {
+    result.explanation.get_or_insert_with(Default::default)
+}
+
+/// Record each result's 1-based position and raw BM25 score in a lexical ranking; the
+/// calibrated score is the BM25 relative to the best hit
+pub(crate) fn record_lexical_ranks(results: &mut [SearchResult]) {
+    for (rank, result) in results.iter_mut().enumerate() {
+        let score = result
+            .signals
+            .as_ref()
+            .and_then(|signals| signals.raw_score)
+            .unwrap_or(result.score);
+        let explanation = explanation(result);
+        explanation.lexical_rank = Some(rank + 1);
+        explanation.lexical_score = Some(score);
+    }
+}
+
+/// Add the RRF components to a fused result, keeping what the stages recorded on their hits
+pub(crate) fn record_rrf(
+    result: &mut SearchResult,
+    ranks: &[(&str, usize, SearchResult)],
+    weights: &FusionWeights,
+) {
+    let explanation = explanation(result);
+    for (list, rank, hit) in ranks {
+        if let Some(hit_explanation) = &hit.explanation {
+            explanation.cosine = explanation.cosine.or(hit_explanation.cosine);
+            explanation.lexical_rank = explanation.lexical_rank.or(hit_explanation.lexical_rank);
+            explanation.lexical_score = explanation.lexical_score.or(hit_explanation.lexical_score);
+            if explanation.chunk_type.is_none() {
+                explanation.chunk_type = hit_explanation.chunk_type.clone();
+            }
+            explanation.stride = explanation.stride.or(hit_explanation.stride);
+        }
+        explanation.rrf.push(RrfComponent {
+            list: list.to_string(),
+            rank: *rank,
+            contribution: weights.weight(list) / (60.0 + *rank as f32),
+        });
+    }
+}
+
+/// Record the first-stage and reranked scores
+pub(crate) fn record_rerank(result: &mut SearchResult, before: f32) {
+    let after = result.score;
+    explanation(result).rerank = Some(RerankDelta { before, after });
+}
+
+/// Stride position of each sidecar chunk of one file
+///
+/// Sidecars record the position of each stride. Sidecars written before that do not, so there
+/// strides are recognized as runs of consecutive chunks with the same kind and breadcrumb
+/// whose byte ranges overlap.
+pub(crate) fn stride_positions(chunks: &[cs_index::ChunkEntry]) -> Vec<Option<StrideMatch>> {
+    if chunks.iter().any(|chunk| chunk.stride.is_some()) {
+        return chunks
+            .iter()
+            .map(|chunk| {
+                chunk.stride.as_ref().map(|stride| StrideMatch {
+                    index: stride.stride_index,
+                    total: stride.total_strides,
+                })
+            })
+            .collect();
+    }
+    let mut positions = vec![None; chunks.len()];
+    let mut run_start = 0;
+    for i in 1..=chunks.len() {
+        let continues_run = i < chunks.len() && {
+            let (previous, current) = (&chunks[i - 1], &chunks[i]);
+            current.span.byte_start < previous.span.byte_end
+                && current.span.byte_start > previous.span.byte_start
+                && current.chunk_type == previous.chunk_type
+                && current.breadcrumb == previous.breadcrumb
+        };
+        if continues_run {
+            continue;
+        }
+        let total = i - run_start;
+        if total > 1 {
+            for (index, position) in positions[run_start..i].iter_mut().enumerate() {
+                *position = Some(StrideMatch { index, total });
+            }
+        }
+        run_start = i;
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cs_core::Span;
+
+    fn entry(byte_start: usize, byte_end: usize) -> cs_index::ChunkEntry {
+        cs_index::ChunkEntry {
+            span: Span::new(byte_start, byte_end, 1, 1).unwrap(),
+            embedding: None,
+            chunk_type: cs_chunk::ChunkType::Function,
+            breadcrumb: None,
+            ancestry: None,
+            byte_length: None,
+            estimated_tokens: None,
+            leading_trivia: None,
+            trailing_trivia: None,
+            stride: None,
+        }
+    }
+
+    #[test]
+    fn test_stride_positions_from_overlapping_chunks() {
+        let chunks = vec![
+            entry(0, 100),
+            // A large function embedded in three overlapping windows
+            entry(100, 400),
+            entry(350, 650),
+            entry(600, 800),
+            entry(800, 900),
+        ];
+        let positions = stride_positions(&chunks);
+        assert_eq!(positions[0], None);
+        assert_eq!(positions[1], Some(StrideMatch { index: 0, total: 3 }));
+        assert_eq!(positions[3], Some(StrideMatch { index: 2, total: 3 }));
+        assert_eq!(positions[4], None);
+    }
+
+    #[test]
+    fn test_lexical_ranks_record_raw_bm25() {
+        let result = |raw: f32, score: f32| SearchResult {
+            file: "lib.rs".into(),
+            span: cs_core::Span::new(0, 1, 1, 1).unwrap(),
+            score,
+            preview: String::new(),
+            lang: None,
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            signals: Some(cs_core::SearchSignals {
+                raw_score: Some(raw),
+                ..Default::default()
+            }),
+            explanation: None,
+        };
+        let mut results = vec![result(7.25, 1.0), result(3.5, 0.48)];
+        record_lexical_ranks(&mut results);
+
+        let explanation = results[1].explanation.as_ref().unwrap();
+        assert_eq!(explanation.lexical_rank, Some(2));
+        assert_eq!(explanation.lexical_score, Some(3.5));
+        assert_eq!(explanation.to_string(), "lexical #2 (bm25 3.50)");
+    }
+}
@@ -2,27 +2,146 @@ use anyhow::Result;
 use cs_core::{CcError, IncludePattern, SearchMode, SearchOptions, SearchResult, Span};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use rayon::prelude::*;
-use regex::{Regex, RegexBuilder};
 use std::collections::HashMap;
 use std::fs;
+use std::ops::Range;
 use std::path::PathBuf as StdPathBuf;
 use std::path::{Path, PathBuf};
-use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Instant;
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::{BooleanQuery, Query, QueryParser};
 use tantivy::schema::{STORED, Schema, TEXT, Value};
 use tantivy::{Index, ReloadPolicy, TantivyDocument, doc};
+use tokio_util::sync::CancellationToken;
 use walkdir::WalkDir;
 
 mod semantic_v3;
+use semantic_v3::semantic_search_v3_until;
 pub use semantic_v3::{semantic_search_v3, semantic_search_v3_with_progress};
 
 mod ast_search;
 pub use ast_search::is_ast_pattern;
 
+mod auto_mode;
+pub use auto_mode::{ModeChoice, auto_select_mode};
+
+mod lossy;
+use lossy::DecodedLine;
+
+mod archive;
+
+mod chunk_filter;
+
+mod symbol_search;
+
+mod test_filter;
+
+mod ephemeral;
+
+mod rerank;
+pub use rerank::RERANK_MODEL_ALIASES;
+
+mod mmr;
+
+mod dedupe;
+
+mod sparse_fusion;
+
+mod negative;
+
+mod stride_pooling;
+
+mod quantized;
+
+mod explain;
+
+mod signals;
+
+mod score_scale;
+
+mod query_expansion;
+pub use query_expansion::{expand_query, query_variants};
+
+mod chunk_id;
+pub use chunk_id::{
+    FileChunk, chunk_id, chunk_type_name, chunks_with_id, enclosing_chunk, file_chunks,
+};
+
+mod stdin;
+pub use stdin::{STDIN_LABEL, STDIN_PATH, is_stdin_path};
+
+mod matcher;
+pub use matcher::SearchRegex;
+
+pub mod daemon;
+
+mod resident;
+
+/// Searches leave indexes as they are, see `set_read_only`
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Search indexes as they are, from now on: no automatic update before a search and no lexical
+/// index built on demand, so searching writes nothing (`cs --serve --mcp-read-only`)
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+fn read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
 pub type SearchProgressCallback = Box<dyn Fn(&str) + Send + Sync>;
 pub type IndexingProgressCallback = Box<dyn Fn(&str) + Send + Sync>;
 pub type DetailedIndexingProgressCallback = Box<dyn Fn(cs_index::EmbeddingProgress) + Send + Sync>;
 
+/// When a search stops gathering candidates: once its soft `timeout` passes (the results so far
+/// are returned, flagged partial) or once it is cancelled (the results are discarded).
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Deadline<'a> {
+    at: Option<Instant>,
+    cancel: Option<&'a CancellationToken>,
+}
+
+/// Deadline for a search with a soft `timeout`, measured from when the search phase starts
+/// (after any automatic index update).
+fn search_deadline<'a>(
+    options: &SearchOptions,
+    cancel: Option<&'a CancellationToken>,
+) -> Deadline<'a> {
+    Deadline {
+        at: options.timeout.map(|timeout| Instant::now() + timeout),
+        cancel,
+    }
+}
+
+pub(crate) fn deadline_passed(deadline: Deadline<'_>) -> bool {
+    deadline.at.is_some_and(|at| Instant::now() >= at)
+        || deadline.cancel.is_some_and(CancellationToken::is_cancelled)
+}
+
+/// `score(i)` for `i` in `0..count`, in parallel blocks of `block`, stopping between blocks
+/// once `deadline` passes (the first block always runs). True with the scores when the
+/// deadline cut the scan short.
+pub(crate) fn score_until<T: Send>(
+    count: usize,
+    block: usize,
+    deadline: Deadline<'_>,
+    score: impl Fn(usize) -> Option<T> + Sync,
+) -> (Vec<T>, bool) {
+    let mut scores = Vec::new();
+    let mut start = 0;
+    while start < count {
+        if start > 0 && deadline_passed(deadline) {
+            return (scores, true);
+        }
+        let end = (start + block).min(count);
+        scores.par_extend((start..end).into_par_iter().filter_map(&score));
+        start = end;
+    }
+    (scores, false)
+}
+
 /// Resolve the actual file path to read content from
 /// For PDFs: returns cache path and validates it exists
 /// For regular files: returns original path
@@ -42,12 +161,12 @@ fn resolve_content_path(file_path: &Path, repo_root: &Path) -> Result<PathBuf> {
     }
 }
 
-/// Read content from file for search result extraction
+/// Read raw content from file for search result extraction
 /// Regular files: read directly from source
 /// PDFs: read from preprocessed cache
-fn read_file_content(file_path: &Path, repo_root: &Path) -> Result<String> {
+fn read_file_content(file_path: &Path, repo_root: &Path) -> Result<Vec<u8>> {
     let content_path = resolve_content_path(file_path, repo_root)?;
-    Ok(fs::read_to_string(content_path)?)
+    Ok(fs::read(content_path)?)
 }
 
 /// Extract content from a file using a span (streaming version)
@@ -71,6 +190,18 @@ fn extract_lines_from_file(file_path: &Path, line_start: usize, line_end: usize)
         return Ok(String::new());
     }
 
+    // Lines of a file in another encoding only exist once it is transcoded
+    if cs_core::encoding::detect_file(file_path)?.is_some() {
+        let content = cs_core::encoding::read(file_path)?;
+        let text = String::from_utf8_lossy(&content);
+        return Ok(text
+            .lines()
+            .skip(line_start - 1)
+            .take(line_end.saturating_sub(line_start) + 1)
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+
     let file = fs::File::open(file_path)?;
     let reader = BufReader::new(file);
     let mut result = Vec::new();
@@ -79,15 +210,19 @@ fn extract_lines_from_file(file_path: &Path, line_start: usize, line_end: usize)
     let start_idx = line_start.saturating_sub(1);
     let end_idx = line_end.saturating_sub(1);
 
-    for (current_line, line_result) in reader.lines().enumerate() {
+    // Split on raw bytes so a few invalid UTF-8 bytes don't drop the whole preview
+    for (current_line, line_result) in reader.split(b'\n').enumerate() {
         if current_line > end_idx {
             break; // Stop reading once we've passed the needed lines
         }
 
-        let line = line_result?;
+        let mut line = line_result?;
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
 
         if current_line >= start_idx {
-            result.push(line);
+            result.push(String::from_utf8_lossy(&line).into_owned());
         }
     }
 
@@ -101,29 +236,34 @@ fn extract_lines_from_file(file_path: &Path, line_start: usize, line_end: usize)
 
 /// Split content into lines while preserving the exact number of trailing newline bytes per line.
 /// Handles Unix (\n), Windows (\r\n) and old Mac (\r) line endings.
+#[cfg(test)]
 fn split_lines_with_endings(content: &str) -> (Vec<String>, Vec<usize>) {
-    let mut lines = Vec::new();
-    let mut endings = Vec::new();
+    split_line_ranges(content.as_bytes())
+        .into_iter()
+        .map(|(range, ending)| (content[range].to_string(), ending))
+        .unzip()
+}
 
-    let bytes = content.as_bytes();
+/// Byte range of each line (without its ending) and the length of the ending that follows it.
+/// Splitting happens on ASCII bytes only, so ranges of valid UTF-8 input stay on char boundaries.
+fn split_line_ranges(bytes: &[u8]) -> Vec<(std::ops::Range<usize>, usize)> {
+    let mut lines = Vec::new();
     let mut start = 0usize;
     let mut i = 0usize;
 
     while i < bytes.len() {
         match bytes[i] {
             b'\n' => {
-                lines.push(content[start..i].to_string());
-                endings.push(1);
+                lines.push((start..i, 1));
                 i += 1;
                 start = i;
             }
             b'\r' => {
-                lines.push(content[start..i].to_string());
                 if i + 1 < bytes.len() && bytes[i + 1] == b'\n' {
-                    endings.push(2);
+                    lines.push((start..i, 2));
                     i += 2;
                 } else {
-                    endings.push(1);
+                    lines.push((start..i, 1));
                     i += 1;
                 }
                 start = i;
@@ -135,11 +275,10 @@ fn split_lines_with_endings(content: &str) -> (Vec<String>, Vec<usize>) {
     }
 
     if start < bytes.len() {
-        lines.push(content[start..].to_string());
-        endings.push(0);
+        lines.push((start..bytes.len(), 0));
     }
 
-    (lines, endings)
+    lines
 }
 
 fn canonicalize_for_matching(path: &Path) -> PathBuf {
@@ -185,7 +324,49 @@ fn filter_files_by_include(
         .collect()
 }
 
-fn find_nearest_index_root(path: &Path) -> Option<StdPathBuf> {
+/// Include patterns and `--no-tests` together: whether results from `path` may be returned
+fn path_in_scope(path: &Path, options: &SearchOptions) -> bool {
+    path_matches_include(path, &options.include_patterns)
+        && language_allowed(path, options)
+        && !test_filter::is_excluded_test_path(options, path)
+}
+
+fn filter_files_in_scope(files: Vec<PathBuf>, options: &SearchOptions) -> Vec<PathBuf> {
+    let files = filter_files_by_include(files, &options.include_patterns);
+    if !options.exclude_tests && options.languages.is_empty() {
+        return limit_files(files, options);
+    }
+    let files = files
+        .into_iter()
+        .filter(|path| {
+            language_allowed(path, options) && !test_filter::is_excluded_test_path(options, path)
+        })
+        .collect();
+    limit_files(files, options)
+}
+
+/// Apply `--max-filesize` and `--max-files`, warning about the files left out.
+/// Unlike indexing, search reads files of any size unless a limit is given.
+fn limit_files(files: Vec<PathBuf>, options: &SearchOptions) -> Vec<PathBuf> {
+    if options.file_limits.is_unlimited() {
+        return files;
+    }
+    let (files, skipped) = options.file_limits.apply(files);
+    for warning in skipped.warnings(&options.file_limits) {
+        tracing::warn!("{}", warning);
+    }
+    files
+}
+
+/// The `languages` filter; files of unknown language pass only when no filter is set
+fn language_allowed(path: &Path, options: &SearchOptions) -> bool {
+    options.languages.is_empty()
+        || cs_core::Language::from_path(path)
+            .is_some_and(|language| options.languages.contains(&language))
+}
+
+/// The directory holding the `.cs` index that covers `path`: `path` itself or an ancestor
+pub fn find_nearest_index_root(path: &Path) -> Option<StdPathBuf> {
     let mut current = if path.is_file() {
         path.parent().unwrap_or(path)
     } else {
@@ -209,27 +390,19 @@ pub struct ResolvedModel {
     pub dimensions: usize,
 }
 
-fn find_model_entry<'a>(
-    registry: &'a cs_models::ModelRegistry,
-    key: &str,
-) -> Option<(String, &'a cs_models::ModelConfig)> {
-    if let Some(config) = registry.get_model(key) {
-        return Some((key.to_string(), config));
-    }
-
-    registry
-        .models
-        .iter()
-        .find(|(_, config)| config.name == key)
-        .map(|(alias, config)| (alias.clone(), config))
-}
-
 pub(crate) fn resolve_model_from_root(
     index_root: &Path,
     cli_model: Option<&str>,
 ) -> Result<ResolvedModel> {
     use cs_models::ModelRegistry;
 
+    // Without --model, the project's cs.toml or the user config may name one
+    let configured_model = match cli_model {
+        Some(_) => None,
+        None => cs_models::configured_model(index_root)?,
+    };
+    let cli_model = cli_model.or(configured_model.as_deref());
+
     let registry = ModelRegistry::default();
     let index_dir = index_root.join(".cs");
     let manifest_path = index_dir.join("manifest.json");
@@ -238,9 +411,20 @@ pub(crate) fn resolve_model_from_root(
         let data = std::fs::read(&manifest_path)?;
         let manifest: cs_index::IndexManifest = serde_json::from_slice(&data)?;
 
+        if let Some(quantization) = manifest.embedding_quantization.as_deref()
+            && quantization != cs_index::EMBEDDING_QUANTIZATION
+        {
+            return Err(CcError::Embedding(format!(
+                "Index stores '{}' embeddings, which this version of cs cannot read. Rebuild it with `cs --clean .` then `cs --index`.",
+                quantization
+            ))
+            .into());
+        }
+
         if let Some(existing_model) = manifest.embedding_model {
-            let (alias, config_opt) = find_model_entry(&registry, &existing_model)
-                .map(|(alias, config)| (alias, Some(config)))
+            let (alias, config_opt) = registry
+                .find(&existing_model)
+                .map(|(alias, config)| (alias.to_string(), Some(config)))
                 .unwrap_or_else(|| (existing_model.clone(), None));
 
             let dims = manifest
@@ -249,19 +433,9 @@ pub(crate) fn resolve_model_from_root(
                 .unwrap_or(384);
 
             if let Some(requested) = cli_model {
-                let (_, requested_config) =
-                    find_model_entry(&registry, requested).ok_or_else(|| {
-                        CcError::Embedding(format!(
-                            "Unknown model '{}'. Available models: {}",
-                            requested,
-                            registry
-                                .models
-                                .keys()
-                                .cloned()
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        ))
-                    })?;
+                let (_, requested_config) = registry
+                    .resolve(Some(requested))
+                    .map_err(|e| CcError::Embedding(e.to_string()))?;
 
                 if requested_config.name != existing_model {
                     let suggested_alias = alias.clone();
@@ -301,29 +475,12 @@ pub(crate) fn resolve_model_from_root(
         }
     }
 
-    let (alias, config) = if let Some(requested) = cli_model {
-        find_model_entry(&registry, requested).ok_or_else(|| {
-            CcError::Embedding(format!(
-                "Unknown model '{}'. Available models: {}",
-                requested,
-                registry
-                    .models
-                    .keys()
-                    .cloned()
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            ))
-        })?
-    } else {
-        let alias = registry.default_model.clone();
-        let config = registry.get_default_model().ok_or_else(|| {
-            CcError::Embedding("No default embedding model configured".to_string())
-        })?;
-        (alias, config)
-    };
+    let (alias, config) = registry
+        .resolve(cli_model)
+        .map_err(|e| CcError::Embedding(e.to_string()))?;
 
     Ok(ResolvedModel {
-        canonical_name: config.name.clone(),
+        canonical_name: config.name,
         alias,
         dimensions: config.dimensions,
     })
@@ -340,6 +497,26 @@ pub fn resolve_model_for_path(path: &Path, cli_model: Option<&str>) -> Result<Re
     resolve_model_from_root(&index_root, cli_model)
 }
 
+/// What a search mode reads from the index, which is built or brought up to date before the
+/// search runs (unless `--ephemeral`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexUse {
+    /// Reads the files directly
+    None,
+    /// Indexed chunks and their terms, without embeddings
+    Chunks,
+    /// Indexed chunks with embeddings
+    Embeddings,
+}
+
+pub fn index_use(mode: &SearchMode) -> IndexUse {
+    match mode {
+        SearchMode::Regex | SearchMode::Ast | SearchMode::Symbol => IndexUse::None,
+        SearchMode::Lexical => IndexUse::Chunks,
+        SearchMode::Semantic | SearchMode::Hybrid => IndexUse::Embeddings,
+    }
+}
+
 pub async fn search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
     let results = search_enhanced(options).await?;
     Ok(results.matches)
@@ -372,6 +549,100 @@ pub async fn search_enhanced_with_indexing_progress(
     progress_callback: Option<SearchProgressCallback>,
     indexing_progress_callback: Option<IndexingProgressCallback>,
     detailed_indexing_progress_callback: Option<DetailedIndexingProgressCallback>,
+) -> Result<cs_core::SearchResults> {
+    search_enhanced_cancellable(
+        options,
+        progress_callback,
+        indexing_progress_callback,
+        detailed_indexing_progress_callback,
+        &CancellationToken::new(),
+    )
+    .await
+}
+
+/// Search that stops once `cancel` is cancelled and fails with `CcError::Cancelled`.
+///
+/// An automatic index update stops between files, so the index stays consistent; the search
+/// itself stops at the same points as a `timeout`.
+pub async fn search_enhanced_cancellable(
+    options: &SearchOptions,
+    progress_callback: Option<SearchProgressCallback>,
+    indexing_progress_callback: Option<IndexingProgressCallback>,
+    detailed_indexing_progress_callback: Option<DetailedIndexingProgressCallback>,
+    cancel: &CancellationToken,
+) -> Result<cs_core::SearchResults> {
+    // A later page ranks the results before it too, then drops them
+    let paged;
+    let options = if options.offset > 0 {
+        paged = SearchOptions {
+            top_k: options.top_k.map(|top_k| top_k + options.offset),
+            ..options.clone()
+        };
+        &paged
+    } else {
+        options
+    };
+    // Deduplication drops results, so ranked modes draw from a wider pool and cut it after
+    let page_size = options.top_k;
+    let pooled;
+    let options = if dedupe::applies(options) {
+        pooled = SearchOptions {
+            top_k: page_size.map(|top_k| top_k.saturating_mul(dedupe::DEDUPE_POOL_FACTOR)),
+            ..options.clone()
+        };
+        &pooled
+    } else {
+        options
+    };
+
+    // Piped input is searched in memory; there is nothing to index
+    let mut search_results = if is_stdin_path(&options.path) {
+        stdin::search_stdin(options, progress_callback).await?
+    } else {
+        search_path(
+            options,
+            progress_callback,
+            indexing_progress_callback,
+            detailed_indexing_progress_callback,
+            cancel,
+        )
+        .await?
+    };
+    if dedupe::applies(options) {
+        let matches = std::mem::take(&mut search_results.matches);
+        search_results.matches = dedupe::dedupe(matches, options.dedupe);
+        if let Some(top_k) = page_size {
+            search_results.matches.truncate(top_k);
+        }
+    }
+    let skipped = options.offset.min(search_results.matches.len());
+    search_results.matches.drain(..skipped);
+    if is_stdin_path(&options.path) {
+        return Ok(search_results);
+    }
+    // Ids read and chunk every matched file, and only structured output carries them
+    if options.json_output || options.jsonl_output {
+        chunk_id::assign_chunk_ids(&mut search_results.matches);
+    }
+
+    // An empty query only refreshes the index (MCP reindex) and is not audited; ephemeral
+    // searches promise to leave nothing behind, the audit log included
+    if !options.query.is_empty()
+        && !options.ephemeral
+        && let Some(index_root) = find_nearest_index_root(&options.path)
+    {
+        cs_index::audit::record_search(&index_root, options, search_results.matches.len());
+    }
+
+    Ok(search_results)
+}
+
+async fn search_path(
+    options: &SearchOptions,
+    progress_callback: Option<SearchProgressCallback>,
+    indexing_progress_callback: Option<IndexingProgressCallback>,
+    detailed_indexing_progress_callback: Option<DetailedIndexingProgressCallback>,
+    cancel: &CancellationToken,
 ) -> Result<cs_core::SearchResults> {
     // Validate that the search path exists
     if !options.path.exists() {
@@ -382,11 +653,36 @@ pub async fn search_enhanced_with_indexing_progress(
         .into());
     }
 
-    // Auto-update index if needed (unless it's regex-only or AST-only mode)
-    if !matches!(options.mode, SearchMode::Regex | SearchMode::Ast) {
-        let need_embeddings = matches!(options.mode, SearchMode::Semantic | SearchMode::Hybrid);
-        ensure_index_updated_with_progress(
+    let uses_index = !options.ephemeral && index_use(&options.mode) != IndexUse::None;
+    let index_root = find_nearest_index_root(&options.path);
+
+    // A cursor's later pages must come from the index its first page was ranked on
+    if let Some(epoch) = options.index_epoch.filter(|_| uses_index) {
+        let current = index_root.as_deref().and_then(cs_index::index_epoch);
+        if current != Some(epoch) {
+            return Err(CcError::Search(
+                "The index changed since this cursor was issued; repeat the search from the first page"
+                    .to_string(),
+            )
+            .into());
+        }
+    }
+
+    if uses_index && read_only() && index_root.is_none() {
+        return Err(CcError::Index(format!(
+            "No index at {}; searches are read-only, so build it with 'cs --index' first",
+            options.path.display()
+        ))
+        .into());
+    }
+
+    // Auto-update index if needed (unless it's regex-only or AST-only mode, or ephemeral)
+    if uses_index && options.index_epoch.is_none() && !read_only() {
+        let need_embeddings = index_use(&options.mode) == IndexUse::Embeddings;
+        let started = Instant::now();
+        let update = ensure_index_updated_with_progress(
             &options.path,
+            &options.include_patterns,
             options.reindex,
             need_embeddings,
             indexing_progress_callback,
@@ -394,23 +690,59 @@ pub async fn search_enhanced_with_indexing_progress(
             options.respect_gitignore,
             &options.exclude_patterns,
             options.embedding_model.as_deref(),
+            cancel,
         )
         .await?;
+        if let Some(stats) = &options.stats {
+            stats.record_phase("index update", started.elapsed());
+            stats.add_index_check(
+                update.files_indexed + update.files_up_to_date + update.files_errored,
+                update.files_indexed,
+                update.orphaned_files_removed,
+            );
+        }
     }
 
-    let search_results = match options.mode {
+    let deadline = search_deadline(options, Some(cancel));
+    let started = Instant::now();
+
+    let mut search_results = match options.mode {
         SearchMode::Regex => {
-            let matches = regex_search(options)?;
-            cs_core::SearchResults {
-                matches,
-                closest_below_threshold: None,
-            }
+            let mut results = regex_search_until(options, deadline)?;
+            signals::record_lex_ranks(&mut results.matches);
+            results
         }
         SearchMode::Lexical => {
-            let matches = lexical_search(options).await?;
+            let expanded = query_expansion::expanded_options(options);
+            let mut matches = if options.ephemeral {
+                ephemeral::lexical_search(&expanded)?
+            } else {
+                lexical_search(&expanded).await?
+            };
+            if !options.only_chunk_types.is_empty() && !options.ephemeral {
+                let index_root =
+                    find_nearest_index_root(&options.path).unwrap_or_else(|| options.path.clone());
+                matches = chunk_filter::narrow_lexical_results(options, &index_root, matches);
+            }
+            if options.search_zip {
+                // Archive entries are scored in their own in-memory index and merged by score
+                matches.extend(lexical_search_archives(options)?);
+                matches.sort_by(|a, b| {
+                    b.score
+                        .partial_cmp(&a.score)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+                matches.truncate(options.top_k.unwrap_or(100));
+            }
+            if options.explain {
+                explain::record_lexical_ranks(&mut matches);
+            }
+            signals::record_lex_ranks(&mut matches);
             cs_core::SearchResults {
                 matches,
                 closest_below_threshold: None,
+                partial: false,
+                lossy_files: 0,
             }
         }
         SearchMode::Ast => {
@@ -418,38 +750,195 @@ pub async fn search_enhanced_with_indexing_progress(
             cs_core::SearchResults {
                 matches,
                 closest_below_threshold: None,
+                partial: false,
+                lossy_files: 0,
             }
         }
-        SearchMode::Semantic => {
-            // Use v3 semantic search (reads pre-computed embeddings from sidecars using spans)
-            semantic_search_v3_with_progress(options, progress_callback).await?
-        }
+        SearchMode::Symbol => cs_core::SearchResults {
+            matches: symbol_search::symbol_search(options)?,
+            closest_below_threshold: None,
+            partial: false,
+            lossy_files: 0,
+        },
+        SearchMode::Semantic => semantic_search_until(options, progress_callback, deadline).await?,
         SearchMode::Hybrid => {
-            let matches = hybrid_search_with_progress(options, progress_callback).await?;
-            cs_core::SearchResults {
-                matches,
-                closest_below_threshold: None,
-            }
+            hybrid_search_with_progress(options, progress_callback, deadline).await?
         }
     };
+    if let Some(stats) = &options.stats {
+        stats.record_phase("search", started.elapsed());
+    }
 
+    if cancel.is_cancelled() {
+        return Err(CcError::Cancelled.into());
+    }
+    // Semantic search already skipped chunks outside --within; other modes match whole files
+    if let Some(within) = &options.within {
+        search_results
+            .matches
+            .retain(|result| within.contains(&result.file, &result.span));
+    }
+    // Only JSON output reports symbols, and finding them parses every matched file
+    if options.json_output {
+        symbol_search::fill_enclosing_symbols(&mut search_results.matches);
+    }
+    if uses_index
+        && let Some(epoch) = index_root
+            .or_else(|| find_nearest_index_root(&options.path))
+            .as_deref()
+            .and_then(cs_index::index_epoch)
+    {
+        for result in &mut search_results.matches {
+            result.index_epoch = Some(epoch);
+        }
+    }
+    if options.raw_scores {
+        score_scale::use_raw_scores(&mut search_results.matches);
+        score_scale::use_raw_scores(search_results.closest_below_threshold.as_mut_slice());
+    }
     Ok(search_results)
 }
 
+#[cfg(test)]
 fn regex_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
-    let pattern = if options.fixed_string {
-        regex::escape(&options.query)
-    } else if options.whole_word {
-        format!(r"\b{}\b", regex::escape(&options.query))
+    regex_search_until(options, Deadline::default()).map(|results| results.matches)
+}
+
+/// Parse every pattern with the BM25 query parser; multiple patterns match any of them
+fn parse_lexical_query(
+    query_parser: &QueryParser,
+    options: &SearchOptions,
+) -> Result<Box<dyn Query>> {
+    let mut queries = options
+        .patterns()
+        .map(|pattern| {
+            query_parser
+                .parse_query(pattern)
+                .map_err(|e| CcError::Search(format!("Failed to parse query: {}", e)).into())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if queries.len() == 1 {
+        Ok(queries.remove(0))
     } else {
-        options.query.clone()
-    };
+        Ok(Box::new(BooleanQuery::union(queries)))
+    }
+}
+
+/// Build the regex source for `patterns`, applying `-F`/`-w` to each and OR-ing them together
+pub fn build_regex_pattern<'a>(
+    patterns: impl IntoIterator<Item = &'a str>,
+    options: &SearchOptions,
+) -> String {
+    let mut sources: Vec<String> = patterns
+        .into_iter()
+        .map(|pattern| {
+            if options.fixed_string {
+                regex::escape(pattern)
+            } else if options.whole_word {
+                format!(r"\b{}\b", regex::escape(pattern))
+            } else {
+                pattern.to_string()
+            }
+        })
+        .collect();
+
+    if sources.len() == 1 {
+        sources.remove(0)
+    } else {
+        sources
+            .iter()
+            .map(|source| format!("(?:{})", source))
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+}
+
+/// Whether `-S`/`--smart-case` makes these patterns case-insensitive: like ripgrep, when
+/// they contain a literal character but no uppercase one. Escapes, class names such as
+/// `\pL` and group names do not count; a pattern that does not parse is left case-sensitive,
+/// unless it only needs PCRE2.
+pub fn smart_case_insensitive<'a>(
+    patterns: impl IntoIterator<Item = &'a str>,
+    fixed_string: bool,
+) -> bool {
+    let mut case = LiteralCase::default();
+    for pattern in patterns {
+        if fixed_string {
+            pattern.chars().for_each(|c| case.literal(c));
+        } else {
+            match regex_syntax::ast::parse::Parser::new().parse(pattern) {
+                Ok(ast) => case = regex_syntax::ast::visit(&ast, case).unwrap_or_default(),
+                // A PCRE2 pattern (`-P`): judge by the characters that are not escaped
+                Err(_) if matcher::needs_pcre2(pattern) => {
+                    let mut escaped = false;
+                    for c in pattern.chars() {
+                        if !escaped && c != '\\' {
+                            case.literal(c);
+                        }
+                        escaped = !escaped && c == '\\';
+                    }
+                }
+                Err(_) => return false,
+            }
+        }
+    }
+    case.any_literal && !case.any_uppercase
+}
+
+/// Literal characters seen while walking a pattern
+#[derive(Debug, Default, Clone, Copy)]
+struct LiteralCase {
+    any_literal: bool,
+    any_uppercase: bool,
+}
 
-    let regex = RegexBuilder::new(&pattern)
-        .case_insensitive(options.case_insensitive)
-        .build()
-        .map_err(CcError::Regex)?;
+impl LiteralCase {
+    fn literal(&mut self, c: char) {
+        self.any_literal = true;
+        self.any_uppercase |= c.is_uppercase();
+    }
+}
+
+impl regex_syntax::ast::Visitor for LiteralCase {
+    type Output = Self;
+    type Err = std::convert::Infallible;
+
+    fn finish(self) -> std::result::Result<Self, Self::Err> {
+        Ok(self)
+    }
+
+    fn visit_pre(&mut self, ast: &regex_syntax::ast::Ast) -> std::result::Result<(), Self::Err> {
+        if let regex_syntax::ast::Ast::Literal(literal) = ast {
+            self.literal(literal.c);
+        }
+        Ok(())
+    }
 
+    fn visit_class_set_item_pre(
+        &mut self,
+        item: &regex_syntax::ast::ClassSetItem,
+    ) -> std::result::Result<(), Self::Err> {
+        match item {
+            regex_syntax::ast::ClassSetItem::Literal(literal) => self.literal(literal.c),
+            regex_syntax::ast::ClassSetItem::Range(range) => {
+                self.literal(range.start.c);
+                self.literal(range.end.c);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// The compiled pattern of a regex or symbol search, with the engine `options` select
+pub fn build_search_regex(options: &SearchOptions) -> Result<SearchRegex> {
+    SearchRegex::new(&build_regex_pattern(options.patterns(), options), options)
+}
+
+/// Files a regex search reads, in walk order (for `-c`, which also counts the files without
+/// matches)
+pub fn regex_search_files(options: &SearchOptions) -> Result<Vec<StdPathBuf>> {
     // Default to recursive for directories (like grep) to maintain compatibility
     let should_recurse = options.path.is_dir() || options.recursive;
     let files = if should_recurse {
@@ -459,30 +948,84 @@ fn regex_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
             options.respect_gitignore,
             &options.exclude_patterns,
         )?;
-        filter_files_by_include(collected, &options.include_patterns)
+        filter_files_in_scope(collected, options)
     } else {
         // For non-recursive, use the local collect_files
         let collected = collect_files(&options.path, should_recurse, &options.exclude_patterns)?;
-        filter_files_by_include(collected, &options.include_patterns)
+        filter_files_in_scope(collected, options)
+    };
+    Ok(files)
+}
+
+/// Regex search that stops scanning new files once `deadline` passes.
+/// Returns the matches found so far, flagged `partial` if the search was cut short.
+fn regex_search_until(
+    options: &SearchOptions,
+    deadline: Deadline<'_>,
+) -> Result<cs_core::SearchResults> {
+    let regex = build_search_regex(options)?;
+    let files = regex_search_files(options)?;
+
+    // With --search-zip, archives are expanded entry by entry instead of read as files
+    let (archives, files) = if options.search_zip {
+        let files = files
+            .into_iter()
+            .filter(|path| !cs_core::archive::is_archive_file(path))
+            .collect();
+        (collect_archives(options)?, files)
+    } else {
+        (Vec::new(), files)
     };
 
-    let results: Vec<Vec<SearchResult>> = files
+    let timed_out = AtomicBool::new(false);
+    let found = AtomicBool::new(false);
+    let scanned = AtomicUsize::new(0);
+    let lossy_files = AtomicUsize::new(0);
+    let sources: Vec<(&PathBuf, bool)> = files
+        .iter()
+        .map(|path| (path, false))
+        .chain(archives.iter().map(|path| (path, true)))
+        .collect();
+    let results: Vec<Vec<SearchResult>> = sources
         .par_iter()
-        .filter_map(|file_path| match search_file(&regex, file_path, options) {
-            Ok(matches) => {
-                if matches.is_empty() {
+        .filter_map(|&(file_path, is_archive)| {
+            if deadline_passed(deadline) {
+                timed_out.store(true, Ordering::Relaxed);
+                return None;
+            }
+            if options.first_match && found.load(Ordering::Relaxed) {
+                return None;
+            }
+            scanned.fetch_add(1, Ordering::Relaxed);
+            let searched = if is_archive {
+                search_archive(&regex, file_path, options)
+            } else {
+                search_file_lossy(&regex, file_path, options)
+            };
+            match searched {
+                Ok((matches, lossy)) => {
+                    if matches.is_empty() {
+                        None
+                    } else {
+                        if lossy {
+                            lossy_files.fetch_add(1, Ordering::Relaxed);
+                        }
+                        found.store(true, Ordering::Relaxed);
+                        Some(matches)
+                    }
+                }
+                Err(e) => {
+                    tracing::debug!("Error searching {:?}: {}", file_path, e);
                     None
-                } else {
-                    Some(matches)
                 }
             }
-            Err(e) => {
-                tracing::debug!("Error searching {:?}: {}", file_path, e);
-                None
-            }
         })
         .collect();
 
+    if let Some(stats) = &options.stats {
+        stats.add_files_scanned(scanned.into_inner());
+    }
+
     let mut all_results: Vec<SearchResult> = results.into_iter().flatten().collect();
     // Deterministic ordering: file path, then line number
     all_results.sort_by(|a, b| {
@@ -497,163 +1040,381 @@ fn regex_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
         all_results.truncate(top_k);
     }
 
-    Ok(all_results)
+    Ok(cs_core::SearchResults {
+        matches: all_results,
+        closest_below_threshold: None,
+        partial: timed_out.load(Ordering::Relaxed),
+        lossy_files: lossy_files.load(Ordering::Relaxed),
+    })
 }
 
+#[cfg(test)]
 fn search_file(
-    regex: &Regex,
+    regex: &SearchRegex,
     file_path: &Path,
     options: &SearchOptions,
 ) -> Result<Vec<SearchResult>> {
+    search_file_lossy(regex, file_path, options).map(|(results, _)| results)
+}
+
+/// Search one file, decoding invalid UTF-8 lossily. Returns the matches and whether any
+/// line of the file needed replacement characters.
+fn search_file_lossy(
+    regex: &SearchRegex,
+    file_path: &Path,
+    options: &SearchOptions,
+) -> Result<(Vec<SearchResult>, bool)> {
     // Find repo root to locate cache
     let repo_root = find_nearest_index_root(file_path)
         .unwrap_or_else(|| file_path.parent().unwrap_or(file_path).to_path_buf());
 
     // For full_section mode, we need the entire content for parsing
     // For context previews, we need all lines for surrounding context
+    // Multiline (-U) matches may cross lines, so they run over the whole content
     // So we'll load content when needed, but optimize for the common case
-    if options.full_section || options.context_lines > 0 {
+    if options.full_section
+        || options.multiline
+        || options.context_lines > 0
+        || options.before_context_lines > 0
+        || options.after_context_lines > 0
+    {
         // Load full content when we need section parsing or context
         let content = read_file_content(file_path, &repo_root)?;
-        let (lines, line_ending_lengths) = split_lines_with_endings(&content);
+        search_raw(regex, file_path, options, &content)
+    } else {
+        // Streaming search (simple case)
+        search_file_streaming(regex, file_path, &repo_root, options)
+    }
+}
 
-        // If full_section is enabled, try to parse the file and find code sections
-        let code_sections = if options.full_section {
-            extract_code_sections(file_path, &content)
-        } else {
-            None
-        };
+/// Search content already in memory; `file_path` names it in results and picks the language
+/// for full-section parsing
+fn search_content(
+    regex: &SearchRegex,
+    file_path: &Path,
+    options: &SearchOptions,
+    content: &[u8],
+) -> Result<(Vec<SearchResult>, bool)> {
+    let line_ranges = split_line_ranges(content);
+    let (lines, line_ending_lengths): (Vec<DecodedLine>, Vec<usize>) = line_ranges
+        .iter()
+        .map(|(range, ending)| (DecodedLine::new(&content[range.clone()]), *ending))
+        .unzip();
+    let lossy = lines.iter().any(DecodedLine::is_lossy);
 
-        search_file_in_memory(
+    // If full_section is enabled, try to parse the file and find code sections
+    let code_sections = if options.full_section {
+        extract_code_sections(file_path, &String::from_utf8_lossy(content))
+    } else {
+        None
+    };
+
+    // -U runs the pattern over the whole content; an empty pattern still selects each line
+    let results = if options.multiline && !regex.as_str().is_empty() {
+        let line_starts: Vec<usize> = line_ranges.iter().map(|(range, _)| range.start).collect();
+        let decoded = DecodedLine::new(content);
+        search_multiline(
             regex,
             file_path,
             options,
+            &decoded,
             &lines,
+            &line_starts,
             &code_sections,
-            &line_ending_lengths,
         )
     } else {
-        // Streaming search (simple case)
-        search_file_streaming(regex, file_path, &repo_root, options)
+        search_file_in_memory(
+            regex,
+            file_path,
+            options,
+            &lines,
+            &code_sections,
+            &line_ending_lengths,
+        )?
+    };
+    Ok((results, lossy))
+}
+
+/// Search a file's raw bytes. Text in another encoding is transcoded to search it, and the
+/// spans of its matches are mapped back to offsets in the raw bytes.
+fn search_raw(
+    regex: &SearchRegex,
+    file_path: &Path,
+    options: &SearchOptions,
+    raw: &[u8],
+) -> Result<(Vec<SearchResult>, bool)> {
+    let Some(transcoded) = cs_core::encoding::transcode_mapped(raw) else {
+        return search_content(regex, file_path, options, raw);
+    };
+    let (mut results, lossy) =
+        search_content(regex, file_path, options, transcoded.text.as_bytes())?;
+    for result in &mut results {
+        result.span.byte_start = transcoded.source_offset(result.span.byte_start);
+        result.span.byte_end = transcoded.source_offset(result.span.byte_end);
     }
+    Ok((results, lossy))
+}
+
+/// Search every text entry of an archive (`--search-zip`)
+fn search_archive(
+    regex: &SearchRegex,
+    archive_path: &Path,
+    options: &SearchOptions,
+) -> Result<(Vec<SearchResult>, bool)> {
+    let mut results = Vec::new();
+    let mut lossy = false;
+    archive::for_each_entry(archive_path, |entry| {
+        let (matches, entry_lossy) = search_raw(regex, &entry.path, options, &entry.content)?;
+        lossy |= entry_lossy && !matches.is_empty();
+        results.extend(matches);
+        Ok(())
+    })?;
+    Ok((results, lossy))
+}
+
+/// Archives to expand for `--search-zip`: regular file collection skips them as binary
+fn collect_archives(options: &SearchOptions) -> Result<Vec<PathBuf>> {
+    let archives = if options.path.is_file() {
+        vec![options.path.clone()]
+    } else if options.path.is_dir() || options.recursive {
+        cs_index::collect_archive_files(
+            &options.path,
+            options.respect_gitignore,
+            &options.exclude_patterns,
+        )?
+    } else {
+        Vec::new()
+    };
+    Ok(filter_files_in_scope(
+        archives
+            .into_iter()
+            .filter(|path| cs_core::archive::is_archive_file(path))
+            .collect(),
+        options,
+    ))
 }
 
 /// In-memory search for cases requiring context or code sections
 fn search_file_in_memory(
-    regex: &Regex,
+    regex: &SearchRegex,
     file_path: &Path,
     options: &SearchOptions,
-    lines: &[String],
+    lines: &[DecodedLine],
     code_sections: &Option<Vec<(usize, usize, String)>>,
     line_ending_lengths: &[usize],
 ) -> Result<Vec<SearchResult>> {
     let mut results = Vec::new();
     let mut byte_offset = 0;
 
-    for (line_idx, line) in lines.iter().enumerate() {
-        let line_number = line_idx + 1;
+    let preview_for_line = |line_idx: usize| -> String {
+        if options.full_section
+            && let Some(sections) = code_sections
+            && let Some(section) = find_containing_section(sections, line_idx)
+        {
+            return section.clone();
+        }
+        // Fall back to context lines if no section found
+        get_context_preview(lines, line_idx, line_idx, options)
+    };
 
-        // Special handling for empty pattern - match the entire line once
-        // An empty regex pattern will match at every position, so we need to handle it specially
-        if regex.as_str().is_empty() {
-            // Empty pattern matches the whole line once (grep compatibility)
-            let preview = if options.full_section {
-                // Try to find the containing code section
-                if let Some(sections) = code_sections {
-                    if let Some(section) = find_containing_section(sections, line_idx) {
-                        section.clone()
-                    } else {
-                        // Fall back to context lines if no section found
-                        get_context_preview(lines, line_idx, options)
-                    }
-                } else {
-                    get_context_preview(lines, line_idx, options)
-                }
-            } else {
-                get_context_preview(lines, line_idx, options)
-            };
+    for (line_idx, decoded) in lines.iter().enumerate() {
+        let line_number = line_idx + 1;
+        let line = decoded.text.as_ref();
 
+        if options.invert_match {
+            // -v: emit each line that does not match the pattern, once
+            if !regex.is_match(line) {
+                results.push(SearchResult {
+                    file: file_path.to_path_buf(),
+                    span: Span {
+                        byte_start: byte_offset,
+                        byte_end: byte_offset + decoded.byte_len,
+                        line_start: line_number,
+                        line_end: line_number,
+                    },
+                    score: 1.0,
+                    preview: preview_for_line(line_idx),
+                    lang: cs_core::Language::from_path(file_path),
+                    symbol: None,
+                    chunk_hash: None,
+                    index_epoch: None,
+                    signals: None,
+                    explanation: None,
+                });
+            }
+        } else if regex.as_str().is_empty() {
+            // Special handling for empty pattern - match the entire line once
+            // An empty regex pattern will match at every position (grep compatibility)
             results.push(SearchResult {
                 file: file_path.to_path_buf(),
                 span: Span {
                     byte_start: byte_offset,
-                    byte_end: byte_offset + line.len(),
+                    byte_end: byte_offset + decoded.byte_len,
                     line_start: line_number,
                     line_end: line_number,
                 },
                 score: 1.0,
-                preview,
+                preview: preview_for_line(line_idx),
                 lang: cs_core::Language::from_path(file_path),
                 symbol: None,
                 chunk_hash: None,
                 index_epoch: None,
+                signals: None,
+                explanation: None,
             });
         } else {
             // Find all matches in the line with their positions
             for mat in regex.find_iter(line) {
-                let preview = if options.full_section {
-                    // Try to find the containing code section
-                    if let Some(sections) = code_sections {
-                        if let Some(section) = find_containing_section(sections, line_idx) {
-                            section.clone()
-                        } else {
-                            // Fall back to context lines if no section found
-                            get_context_preview(lines, line_idx, options)
-                        }
-                    } else {
-                        get_context_preview(lines, line_idx, options)
-                    }
-                } else {
-                    get_context_preview(lines, line_idx, options)
-                };
-
                 results.push(SearchResult {
                     file: file_path.to_path_buf(),
                     span: Span {
-                        byte_start: byte_offset + mat.start(),
-                        byte_end: byte_offset + mat.end(),
+                        byte_start: byte_offset + decoded.original_offset(mat.start),
+                        byte_end: byte_offset + decoded.original_offset(mat.end),
                         line_start: line_number,
                         line_end: line_number,
                     },
                     score: 1.0,
-                    preview,
+                    preview: match_preview(regex, line, &mat, preview_for_line(line_idx), options),
                     lang: cs_core::Language::from_path(file_path),
                     symbol: None,
                     chunk_hash: None,
                     index_epoch: None,
+                    signals: None,
+                    explanation: None,
                 });
             }
         }
 
         // Update byte offset for next line (add line length + actual line ending length)
-        byte_offset += line.len();
+        byte_offset += decoded.byte_len;
         byte_offset += line_ending_lengths.get(line_idx).copied().unwrap_or(0);
     }
 
     Ok(results)
 }
 
+/// Multiline search (`-U`): the pattern runs over the whole content, so a match may span
+/// lines. `decoded` is the content as text and `line_starts` the byte offset of each of
+/// `lines`; spans are exact byte ranges with the first and last line a match touches, and the
+/// preview holds all of those lines.
+fn search_multiline(
+    regex: &SearchRegex,
+    file_path: &Path,
+    options: &SearchOptions,
+    decoded: &DecodedLine,
+    lines: &[DecodedLine],
+    line_starts: &[usize],
+    code_sections: &Option<Vec<(usize, usize, String)>>,
+) -> Vec<SearchResult> {
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let text = decoded.text.as_ref();
+    let line_of = |offset: usize| {
+        line_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1)
+    };
+    let preview_for_lines = |first: usize, last: usize| -> String {
+        if options.full_section
+            && let Some(sections) = code_sections
+            && let Some(section) = find_containing_section(sections, first)
+        {
+            return section.clone();
+        }
+        get_context_preview(lines, first, last, options)
+    };
+    let result = |first: usize, last: usize, span: Range<usize>, preview: String| SearchResult {
+        file: file_path.to_path_buf(),
+        span: Span {
+            byte_start: span.start,
+            byte_end: span.end,
+            line_start: first + 1,
+            line_end: last + 1,
+        },
+        score: 1.0,
+        preview,
+        lang: cs_core::Language::from_path(file_path),
+        symbol: None,
+        chunk_hash: None,
+        index_epoch: None,
+        signals: None,
+        explanation: None,
+    };
+
+    // Each match as its byte range in the content and the lines it touches
+    let matches = regex.find_iter(text).into_iter().map(|mat| {
+        let start = decoded.original_offset(mat.start);
+        let end = decoded.original_offset(mat.end);
+        // A match ending with a line break ends on the line it breaks
+        let last = if end > start {
+            line_of(end - 1)
+        } else {
+            line_of(start)
+        };
+        (mat, start..end, line_of(start), last)
+    });
+
+    if options.invert_match {
+        // -v: the lines no match touches
+        let mut matched = vec![false; lines.len()];
+        for (_, _, first, last) in matches {
+            matched[first..=last]
+                .iter_mut()
+                .for_each(|line| *line = true);
+        }
+        return lines
+            .iter()
+            .enumerate()
+            .filter(|&(line_idx, _)| !matched[line_idx])
+            .map(|(line_idx, line)| {
+                let start = line_starts[line_idx];
+                result(
+                    line_idx,
+                    line_idx,
+                    start..start + line.byte_len,
+                    preview_for_lines(line_idx, line_idx),
+                )
+            })
+            .collect();
+    }
+
+    matches
+        .map(|(mat, span, first, last)| {
+            let preview = match_preview(regex, text, &mat, preview_for_lines(first, last), options);
+            result(first, last, span, preview)
+        })
+        .collect()
+}
+
 /// Streaming search for simple cases without context or code sections
 fn search_file_streaming(
-    regex: &Regex,
+    regex: &SearchRegex,
     file_path: &Path,
     repo_root: &Path,
-    _options: &SearchOptions,
-) -> Result<Vec<SearchResult>> {
+    options: &SearchOptions,
+) -> Result<(Vec<SearchResult>, bool)> {
     use std::io::{BufRead, BufReader};
 
     let content_path = resolve_content_path(file_path, repo_root)?;
     let file = std::fs::File::open(&content_path)?;
-    let mut reader = BufReader::new(file);
+    let mut reader = BufReader::with_capacity(cs_core::encoding::SAMPLE_SIZE, file);
+
+    // Files in other encodings are transcoded whole and searched in memory
+    if cs_core::encoding::detect(reader.fill_buf()?).is_some() {
+        let content = fs::read(&content_path)?;
+        return search_raw(regex, file_path, options, &content);
+    }
 
     let mut results = Vec::new();
-    let mut line = String::new();
+    let mut line = Vec::new();
     let mut byte_offset = 0usize;
     let mut line_number = 1usize;
+    let mut lossy = false;
 
     loop {
         line.clear();
-        let bytes_read = reader.read_line(&mut line)?;
+        let bytes_read = reader.read_until(b'\n', &mut line)?;
         if bytes_read == 0 {
             break;
         }
@@ -661,91 +1422,77 @@ fn search_file_streaming(
         // Determine the length of the trailing line ending (if any) and
         // normalise the line buffer so it no longer contains newline bytes.
         let mut newline_len = 0usize;
-        if line.ends_with("\r\n") {
+        if line.ends_with(b"\r\n") {
             line.pop(); // remove \n
             line.pop(); // remove \r
             newline_len = 2;
-        } else if line.ends_with(['\n', '\r']) {
+        } else if line.ends_with(b"\n") || line.ends_with(b"\r") {
             line.pop();
             newline_len = 1;
         }
 
-        // Old Mac-style files may use bare carriage returns as separators.
-        // When the trimmed line still contains '\r' characters, treat them as
-        // record separators so the byte offsets remain accurate.
-        let treat_cr_as_newline = line.contains('\r');
-
-        if treat_cr_as_newline {
-            let bytes = line.as_bytes();
-            let mut segment_start = 0usize;
-            while segment_start <= bytes.len() {
-                match bytes[segment_start..].iter().position(|&b| b == b'\r') {
-                    Some(rel_idx) => {
-                        let idx = segment_start + rel_idx;
-                        let segment_bytes = &bytes[segment_start..idx];
-                        let segment_str = std::str::from_utf8(segment_bytes)?;
-                        process_streaming_line(
-                            regex,
-                            file_path,
-                            segment_str,
-                            line_number,
-                            byte_offset,
-                            &mut results,
-                        );
-                        byte_offset += segment_bytes.len() + 1; // account for \r
-                        line_number += 1;
-                        segment_start = idx + 1;
-                    }
-                    None => {
-                        let segment_bytes = &bytes[segment_start..];
-                        let segment_str = std::str::from_utf8(segment_bytes)?;
-                        process_streaming_line(
-                            regex,
-                            file_path,
-                            segment_str,
-                            line_number,
-                            byte_offset,
-                            &mut results,
-                        );
-                        byte_offset += segment_bytes.len();
-                        line_number += 1;
-                        break;
-                    }
-                }
-            }
-            byte_offset += newline_len;
-        } else {
-            let line_str = line.as_str();
+        // Old Mac-style files may use bare carriage returns as separators, so
+        // treat them as record separators to keep the byte offsets accurate.
+        // Lines are matched as lossily decoded text; spans map back to the raw bytes.
+        for segment in line.split(|&b| b == b'\r') {
+            let decoded = DecodedLine::new(segment);
+            lossy |= decoded.is_lossy();
             process_streaming_line(
                 regex,
                 file_path,
-                line_str,
+                &decoded,
                 line_number,
                 byte_offset,
+                options,
                 &mut results,
             );
-            byte_offset += line_str.len() + newline_len;
+            byte_offset += segment.len() + 1; // account for the \r separator
             line_number += 1;
         }
+        // The last segment has no \r after it, only the line ending
+        byte_offset = byte_offset - 1 + newline_len;
     }
 
-    Ok(results)
+    Ok((results, lossy))
 }
 
 fn process_streaming_line(
-    regex: &Regex,
+    regex: &SearchRegex,
     file_path: &Path,
-    line: &str,
+    decoded: &DecodedLine,
     line_number: usize,
     byte_offset: usize,
+    options: &SearchOptions,
     results: &mut Vec<SearchResult>,
 ) {
-    if regex.as_str().is_empty() {
+    let line = decoded.text.as_ref();
+    if options.invert_match {
+        // -v: emit the whole line when it does not match the pattern
+        if !regex.is_match(line) {
+            results.push(SearchResult {
+                file: file_path.to_path_buf(),
+                span: Span {
+                    byte_start: byte_offset,
+                    byte_end: byte_offset + decoded.byte_len,
+                    line_start: line_number,
+                    line_end: line_number,
+                },
+                score: 1.0,
+                preview: line.to_string(),
+                lang: cs_core::Language::from_path(file_path),
+                symbol: None,
+                chunk_hash: None,
+                index_epoch: None,
+                signals: None,
+                explanation: None,
+            });
+        }
+    } else if regex.as_str().is_empty() {
         results.push(SearchResult {
             file: file_path.to_path_buf(),
             span: Span {
                 byte_start: byte_offset,
-                byte_end: byte_offset + line.len(),
+                byte_end: byte_offset + decoded.byte_len,
                 line_start: line_number,
                 line_end: line_number,
             },
@@ -755,28 +1502,57 @@ fn process_streaming_line(
             symbol: None,
             chunk_hash: None,
             index_epoch: None,
+            signals: None,
+            explanation: None,
         });
     } else {
         for mat in regex.find_iter(line) {
             results.push(SearchResult {
                 file: file_path.to_path_buf(),
                 span: Span {
-                    byte_start: byte_offset + mat.start(),
-                    byte_end: byte_offset + mat.end(),
+                    byte_start: byte_offset + decoded.original_offset(mat.start),
+                    byte_end: byte_offset + decoded.original_offset(mat.end),
                     line_start: line_number,
                     line_end: line_number,
                 },
                 score: 1.0,
-                preview: line.to_string(),
+                preview: match_preview(regex, line, &mat, line.to_string(), options),
                 lang: cs_core::Language::from_path(file_path),
                 symbol: None,
                 chunk_hash: None,
                 index_epoch: None,
+                signals: None,
+                explanation: None,
             });
         }
     }
 }
 
+/// Apply `-o`/`--replace` to the preview of one match: `-o` keeps only the match (expanded
+/// through the replacement template if any), `--replace` rewrites every match in the preview
+fn match_preview(
+    regex: &SearchRegex,
+    line: &str,
+    mat: &Range<usize>,
+    preview: String,
+    options: &SearchOptions,
+) -> String {
+    match (options.replace.as_deref(), options.only_matching) {
+        (Some(template), true) => regex.expand_at(line, mat.start, template),
+        (None, true) => line[mat.clone()].to_string(),
+        // Multiline matches may span the preview's lines
+        (Some(template), false) if options.multiline => {
+            regex.replace_all(&preview, template).into_owned()
+        }
+        (Some(template), false) => preview
+            .split('\n')
+            .map(|preview_line| regex.replace_all(preview_line, template))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        (None, false) => preview,
+    }
+}
+
 async fn lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
     // Handle both files and directories and reuse nearest existing .cs index up the tree
     let index_root = find_nearest_index_root(&options.path).unwrap_or_else(|| {
@@ -795,6 +1571,13 @@ async fn lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
     let tantivy_index_path = index_dir.join("tantivy_index");
 
     if !tantivy_index_path.exists() {
+        if read_only() {
+            return Err(CcError::Index(
+                "No lexical index yet; searches are read-only, so run a lexical search with 'cs --lex' first"
+                    .to_string(),
+            )
+            .into());
+        }
         return build_tantivy_index(options).await;
     }
 
@@ -815,9 +1598,7 @@ async fn lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
     let searcher = reader.searcher();
     let query_parser = QueryParser::for_index(&index, vec![content_field]);
 
-    let query = query_parser
-        .parse_query(&options.query)
-        .map_err(|e| CcError::Search(format!("Failed to parse query: {}", e)))?;
+    let query = parse_lexical_query(&query_parser, options)?;
 
     let top_docs = if let Some(top_k) = options.top_k {
         searcher.search(&query, &TopDocs::with_limit(top_k))?
@@ -825,6 +1606,11 @@ async fn lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
         searcher.search(&query, &TopDocs::with_limit(100))?
     };
 
+    if let Some(stats) = &options.stats {
+        // Every document matching the query gets a BM25 score, not only the top ones
+        stats.add_chunks_scored(searcher.search(&query, &Count)?);
+    }
+
     // First, collect all results with raw scores
     let mut raw_results = Vec::new();
     for (_score, doc_address) in top_docs {
@@ -839,7 +1625,7 @@ async fn lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
             .unwrap_or("");
 
         let file_path = PathBuf::from(path_text);
-        if !path_matches_include(&file_path, &options.include_patterns) {
+        if !path_in_scope(&file_path, options) {
             continue;
         }
         let preview = if options.full_section {
@@ -864,6 +1650,8 @@ async fn lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
                 symbol: None,
                 chunk_hash: None,
                 index_epoch: None,
+                signals: None,
+                explanation: None,
             },
         ));
     }
@@ -877,7 +1665,7 @@ async fn lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
             .fold(0.0f32, f32::max);
         if max_score > 0.0 {
             for (raw_score, mut result) in raw_results {
-                let normalized_score = raw_score / max_score;
+                let normalized_score = score_scale::lexical(raw_score, max_score);
 
                 // Apply threshold filtering with normalized score
                 if let Some(threshold) = options.threshold
@@ -886,7 +1674,7 @@ async fn lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
                     continue;
                 }
 
-                result.score = normalized_score;
+                score_scale::set(&mut result, raw_score, normalized_score);
                 results.push(result);
             }
         }
@@ -920,13 +1708,26 @@ async fn build_tantivy_index(options: &SearchOptions) -> Result<Vec<SearchResult
         .writer(50_000_000)
         .map_err(|e| CcError::Index(format!("Failed to create index writer: {}", e)))?;
 
-    let files = filter_files_by_include(
-        collect_files(index_root, true, &options.exclude_patterns)?,
-        &options.include_patterns,
+    let files = limit_files(
+        filter_files_by_include(
+            collect_files(index_root, true, &options.exclude_patterns)?,
+            &options.include_patterns,
+        ),
+        options,
     );
 
+    // Likely secrets stay out of the lexical index, as they stay out of the sidecars
+    let allow_secrets = cs_index::secrets_allowed(index_root);
     for file_path in &files {
-        if let Ok(content) = fs::read_to_string(file_path) {
+        if let Ok(content) = cs_core::encoding::read_to_string(file_path) {
+            let content = if allow_secrets {
+                content
+            } else {
+                let Some(content) = cs_index::secrets::lexical_content(file_path, content) else {
+                    continue;
+                };
+                content
+            };
             let doc = doc!(
                 content_field => content,
                 path_field => file_path.display().to_string()
@@ -958,9 +1759,7 @@ async fn build_tantivy_index(options: &SearchOptions) -> Result<Vec<SearchResult
     let searcher = reader.searcher();
     let query_parser = QueryParser::for_index(&index, vec![content_field]);
 
-    let query = query_parser
-        .parse_query(&options.query)
-        .map_err(|e| CcError::Search(format!("Failed to parse query: {}", e)))?;
+    let query = parse_lexical_query(&query_parser, options)?;
 
     let top_docs = if let Some(top_k) = options.top_k {
         searcher.search(&query, &TopDocs::with_limit(top_k))?
@@ -968,6 +1767,10 @@ async fn build_tantivy_index(options: &SearchOptions) -> Result<Vec<SearchResult
         searcher.search(&query, &TopDocs::with_limit(100))?
     };
 
+    if let Some(stats) = &options.stats {
+        stats.add_chunks_scored(searcher.search(&query, &Count)?);
+    }
+
     // First, collect all results with raw scores
     let mut raw_results = Vec::new();
     for (_score, doc_address) in top_docs {
@@ -1004,6 +1807,8 @@ async fn build_tantivy_index(options: &SearchOptions) -> Result<Vec<SearchResult
                 symbol: None,
                 chunk_hash: None,
                 index_epoch: None,
+                signals: None,
+                explanation: None,
             },
         ));
     }
@@ -1017,7 +1822,7 @@ async fn build_tantivy_index(options: &SearchOptions) -> Result<Vec<SearchResult
             .fold(0.0f32, f32::max);
         if max_score > 0.0 {
             for (raw_score, mut result) in raw_results {
-                let normalized_score = raw_score / max_score;
+                let normalized_score = score_scale::lexical(raw_score, max_score);
 
                 // Apply threshold filtering with normalized score
                 if let Some(threshold) = options.threshold
@@ -1026,7 +1831,7 @@ async fn build_tantivy_index(options: &SearchOptions) -> Result<Vec<SearchResult
                     continue;
                 }
 
-                result.score = normalized_score;
+                score_scale::set(&mut result, raw_score, normalized_score);
                 results.push(result);
             }
         }
@@ -1035,29 +1840,226 @@ async fn build_tantivy_index(options: &SearchOptions) -> Result<Vec<SearchResult
     Ok(results)
 }
 
+/// BM25 over archive entries (`--search-zip`) using a throwaway in-memory index; archive
+/// contents never enter the on-disk lexical index or the embedding index
+fn lexical_search_archives(options: &SearchOptions) -> Result<Vec<SearchResult>> {
+    // The in-RAM index holds every document, so entries count against one budget across archives
+    let mut documents = Vec::new();
+    let mut bytes = 0u64;
+    for archive_path in collect_archives(options)? {
+        if bytes > archive::MAX_ARCHIVE_BYTES {
+            tracing::warn!(
+                "Skipping {}: --lexical reads at most {} MB from archives",
+                archive_path.display(),
+                archive::MAX_ARCHIVE_BYTES / (1024 * 1024)
+            );
+            continue;
+        }
+        let read = archive::for_each_entry(&archive_path, |entry| {
+            bytes += entry.content.len() as u64;
+            let content = cs_core::encoding::decode(&entry.content).into_owned();
+            documents.push((entry.path, content));
+            Ok(())
+        });
+        if let Err(e) = read {
+            tracing::debug!("Error reading archive {:?}: {}", archive_path, e);
+        }
+    }
+    lexical_search_documents(options, documents)
+}
+
+/// BM25 over `(path, content)` documents in a throwaway in-RAM index; scores normalized to 0..1
+fn lexical_search_documents(
+    options: &SearchOptions,
+    documents: impl IntoIterator<Item = (PathBuf, String)>,
+) -> Result<Vec<SearchResult>> {
+    let mut schema_builder = Schema::builder();
+    let content_field = schema_builder.add_text_field("content", TEXT | STORED);
+    let path_field = schema_builder.add_text_field("path", TEXT | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+
+    let mut index_writer = index
+        .writer(50_000_000)
+        .map_err(|e| CcError::Index(format!("Failed to create index writer: {}", e)))?;
+    for (path, content) in documents {
+        index_writer.add_document(doc!(
+            content_field => content,
+            path_field => path.display().to_string()
+        ))?;
+    }
+    index_writer
+        .commit()
+        .map_err(|e| CcError::Index(format!("Failed to commit index: {}", e)))?;
+
+    let reader = index
+        .reader()
+        .map_err(|e| CcError::Index(format!("Failed to create index reader: {}", e)))?;
+    let searcher = reader.searcher();
+    let query_parser = QueryParser::for_index(&index, vec![content_field]);
+    let query = parse_lexical_query(&query_parser, options)?;
+    let top_docs = searcher.search(&query, &TopDocs::with_limit(options.top_k.unwrap_or(100)))?;
+
+    let max_score = top_docs
+        .iter()
+        .map(|(score, _)| *score)
+        .fold(0.0f32, f32::max);
+    let mut results = Vec::new();
+    for (raw_score, doc_address) in top_docs {
+        let score = score_scale::lexical(raw_score, max_score);
+        if options.threshold.is_some_and(|threshold| score < threshold) {
+            continue;
+        }
+
+        let retrieved_doc: TantivyDocument = searcher.doc(doc_address)?;
+        let path_text = retrieved_doc
+            .get_first(path_field)
+            .and_then(|field_value| field_value.as_str())
+            .unwrap_or("");
+        let content_text = retrieved_doc
+            .get_first(content_field)
+            .and_then(|field_value| field_value.as_str())
+            .unwrap_or("");
+        let preview = if options.full_section {
+            content_text.to_string()
+        } else {
+            content_text.lines().take(3).collect::<Vec<_>>().join("\n")
+        };
+
+        let mut result = SearchResult {
+            file: PathBuf::from(path_text),
+            span: Span {
+                byte_start: 0,
+                byte_end: content_text.len(),
+                line_start: 1,
+                line_end: content_text.lines().count(),
+            },
+            score,
+            preview,
+            lang: cs_core::Language::from_path(Path::new(path_text)),
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            signals: None,
+            explanation: None,
+        };
+        score_scale::set(&mut result, raw_score, score);
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
 #[allow(dead_code)]
 async fn hybrid_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
-    hybrid_search_with_progress(options, None).await
+    hybrid_search_with_progress(options, None, Deadline::default())
+        .await
+        .map(|results| results.matches)
+}
+
+/// Semantic search over the sidecar embeddings, or embedded in memory with `--ephemeral`
+async fn semantic_search_until(
+    options: &SearchOptions,
+    progress_callback: Option<SearchProgressCallback>,
+    deadline: Deadline<'_>,
+) -> Result<cs_core::SearchResults> {
+    let options = &*query_expansion::expanded_options(options);
+    if options.ephemeral {
+        let mut results =
+            ephemeral::semantic_search(options, progress_callback.as_ref(), deadline)?;
+        signals::record_vec_ranks(&mut results.matches);
+        results.partial |= rerank::rerank_results(
+            options,
+            &mut results.matches,
+            progress_callback.as_ref(),
+            deadline,
+        )
+        .await;
+        Ok(results)
+    } else if let Some(results) = daemon::forward(options, deadline) {
+        // A running `cs --daemon` has the model and the index loaded already
+        results
+    } else {
+        // Use v3 semantic search (reads pre-computed embeddings from sidecars using spans)
+        semantic_search_v3_until(options, progress_callback, deadline).await
+    }
 }
 
 async fn hybrid_search_with_progress(
     options: &SearchOptions,
     progress_callback: Option<SearchProgressCallback>,
-) -> Result<Vec<SearchResult>> {
+    deadline: Deadline<'_>,
+) -> Result<cs_core::SearchResults> {
+    // With --rerank, the stages fuse a wider pool and the fused list is reranked once
+    let first_stage_options;
+    let (options, final_options) = if options.rerank {
+        first_stage_options = SearchOptions {
+            top_k: rerank::candidate_limit(options),
+            rerank: false,
+            ..options.clone()
+        };
+        (&first_stage_options, options)
+    } else {
+        (options, options)
+    };
+    // Shared so the rerank stage can report progress after semantic search
+    let progress_callback =
+        progress_callback.map(std::sync::Arc::<dyn Fn(&str) + Send + Sync>::from);
+    let stage_callback = || {
+        progress_callback.clone().map(|callback| {
+            Box::new(move |message: &str| callback(message)) as SearchProgressCallback
+        })
+    };
+
     if let Some(ref callback) = progress_callback {
         callback("Running regex search...");
     }
-    let regex_results = regex_search(options)?;
+    let regex_search_results = regex_search_until(options, deadline)?;
+    let regex_results = regex_search_results.matches;
 
     if let Some(ref callback) = progress_callback {
         callback("Running semantic search...");
     }
-    let semantic_results = semantic_search_v3_with_progress(options, progress_callback).await?;
+    // The hybrid threshold applies to fused scores, not to cosine similarity
+    let semantic_options = SearchOptions {
+        threshold: None,
+        ..options.clone()
+    };
+    let semantic_results =
+        semantic_search_until(&semantic_options, stage_callback(), deadline).await?;
+    let mut partial = regex_search_results.partial || semantic_results.partial;
+
+    let lexical_results = if options.hybrid_lexical && deadline_passed(deadline) {
+        tracing::info!("Skipping lexical search in hybrid mode: timeout reached");
+        partial = true;
+        None
+    } else if options.hybrid_lexical {
+        if let Some(ref callback) = progress_callback {
+            callback("Running lexical search...");
+        }
+        match hybrid_lexical_search(options).await {
+            Ok(mut results) => {
+                if options.explain {
+                    explain::record_lexical_ranks(&mut results);
+                }
+                Some(results)
+            }
+            Err(e) => {
+                // Log error but continue with other search modes
+                tracing::warn!("Lexical search failed: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
 
     // Check if query looks like an AST pattern (contains metavariables)
     let has_ast_pattern = is_ast_pattern(&options.query);
-    let ast_results = if has_ast_pattern {
-        // Note: progress_callback already moved to semantic_search, so we can't use it here
+    let ast_results = if has_ast_pattern && deadline_passed(deadline) {
+        tracing::info!("Skipping AST search in hybrid mode: timeout reached");
+        partial = true;
+        None
+    } else if has_ast_pattern {
         tracing::info!("Detected AST pattern in query, including AST search in hybrid mode");
         match ast_search::ast_search(options).await {
             Ok(results) => Some(results),
@@ -1071,49 +2073,92 @@ async fn hybrid_search_with_progress(
         None
     };
 
-    let mut combined = HashMap::new();
-
-    for (rank, result) in regex_results.iter().enumerate() {
-        let key = format!("{}:{}", result.file.display(), result.span.line_start);
-        combined
-            .entry(key)
-            .or_insert(Vec::new())
-            .push((rank + 1, result.clone()));
+    let mut rankings = vec![
+        ("regex", regex_results.as_slice()),
+        ("semantic", semantic_results.matches.as_slice()),
+    ];
+    if let Some(ref lexical_results) = lexical_results {
+        rankings.push(("lexical", lexical_results));
+    }
+    // Add AST results if available
+    if let Some(ref ast_results) = ast_results {
+        rankings.push(("ast", ast_results));
     }
 
-    for (rank, result) in semantic_results.matches.iter().enumerate() {
-        let key = format!("{}:{}", result.file.display(), result.span.line_start);
-        combined
-            .entry(key)
-            .or_insert(Vec::new())
-            .push((rank + 1, result.clone()));
+    let mut matches = fuse_rrf(options, &rankings);
+    partial |= rerank::rerank_results(
+        final_options,
+        &mut matches,
+        stage_callback().as_ref(),
+        deadline,
+    )
+    .await;
+
+    Ok(cs_core::SearchResults {
+        matches,
+        closest_below_threshold: None,
+        partial,
+        lossy_files: regex_search_results.lossy_files,
+    })
+}
+
+/// BM25 hits as a hybrid ranking: indexed hits are narrowed to the chunks containing a query
+/// term so they fuse with semantic hits on the same chunk; ephemeral hits stay whole files
+async fn hybrid_lexical_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
+    // The hybrid threshold applies to fused scores, not to BM25 scores
+    let options = SearchOptions {
+        threshold: None,
+        ..query_expansion::expanded_options(options).into_owned()
+    };
+    if options.ephemeral {
+        return ephemeral::lexical_search(&options);
     }
+    let matches = lexical_search(&options).await?;
+    let index_root = find_nearest_index_root(&options.path).unwrap_or_else(|| options.path.clone());
+    Ok(chunk_filter::narrow_lexical_results(
+        &options,
+        &index_root,
+        matches,
+    ))
+}
 
-    // Add AST results if available
-    if let Some(ast_results) = ast_results {
-        for (rank, result) in ast_results.iter().enumerate() {
+/// Merge named ranked result lists with reciprocal rank fusion, keyed by file and start line
+fn fuse_rrf(options: &SearchOptions, rankings: &[(&str, &[SearchResult])]) -> Vec<SearchResult> {
+    let mut combined = HashMap::new();
+
+    for (list, ranking) in rankings {
+        for (rank, result) in ranking.iter().enumerate() {
             let key = format!("{}:{}", result.file.display(), result.span.line_start);
             combined
                 .entry(key)
                 .or_insert(Vec::new())
-                .push((rank + 1, result.clone()));
+                .push((*list, rank + 1, result.clone()));
         }
     }
 
-    // Calculate RRF scores according to original paper: RRFscore(d) = Σ(r∈R) 1/(k + r(d))
+    // Calculate RRF scores according to original paper: RRFscore(d) = Σ(r∈R) 1/(k + r(d)),
+    // with each list's term scaled by its fusion weight (1 unless cs.toml sets one)
     let mut rrf_results: Vec<SearchResult> = combined
         .into_values()
         .map(|ranks| {
-            let mut result = ranks[0].1.clone();
+            let mut result = ranks[0].2.clone();
             let rrf_score = ranks
                 .iter()
-                .map(|(rank, _)| 1.0 / (60.0 + *rank as f32))
+                .map(|(list, rank, _)| options.fusion_weights.weight(list) / (60.0 + *rank as f32))
                 .sum();
-            result.score = rrf_score;
+            result.signals = Some(signals::fused(&ranks, rrf_score));
+            score_scale::set(
+                &mut result,
+                rrf_score,
+                score_scale::hybrid(rrf_score, options, rankings),
+            );
+            if options.explain {
+                explain::record_rrf(&mut result, &ranks, &options.fusion_weights);
+            }
             result
         })
         .filter(|result| {
-            // Apply threshold filtering to raw RRF scores
+            // Apply threshold filtering to calibrated RRF scores
             if let Some(threshold) = options.threshold {
                 result.score >= threshold
             } else {
@@ -1122,7 +2167,7 @@ async fn hybrid_search_with_progress(
         })
         .collect();
 
-    rrf_results.retain(|result| path_matches_include(&result.file, &options.include_patterns));
+    rrf_results.retain(|result| path_in_scope(&result.file, options));
 
     // Sort by RRF score (highest first)
     rrf_results.sort_by(|a, b| {
@@ -1135,7 +2180,7 @@ async fn hybrid_search_with_progress(
         rrf_results.truncate(top_k);
     }
 
-    Ok(rrf_results)
+    rrf_results
 }
 
 fn build_globset(patterns: &[String]) -> GlobSet {
@@ -1225,6 +2270,7 @@ fn collect_files(
 #[allow(clippy::too_many_arguments)]
 async fn ensure_index_updated_with_progress(
     path: &Path,
+    include_patterns: &[IncludePattern],
     force_reindex: bool,
     need_embeddings: bool,
     progress_callback: Option<cs_index::ProgressCallback>,
@@ -1232,7 +2278,8 @@ async fn ensure_index_updated_with_progress(
     respect_gitignore: bool,
     exclude_patterns: &[String],
     model_override: Option<&str>,
-) -> Result<()> {
+    cancel: &CancellationToken,
+) -> Result<cs_index::UpdateStats> {
     // Find index root for .cs directory location
     let index_root_buf = find_nearest_index_root(path).unwrap_or_else(|| {
         if path.is_file() {
@@ -1243,10 +2290,62 @@ async fn ensure_index_updated_with_progress(
     });
     let index_root = &index_root_buf;
 
+    // Searches over a list of files (e.g. `--changed`) index just those files
+    if !include_patterns.is_empty() && include_patterns.iter().all(|pattern| !pattern.is_dir) {
+        let files: Vec<PathBuf> = include_patterns
+            .iter()
+            .map(|pattern| pattern.path.clone())
+            .collect();
+        let stats = cs_index::smart_update_files_cancellable(
+            index_root,
+            &files,
+            force_reindex,
+            progress_callback,
+            detailed_progress_callback,
+            need_embeddings,
+            model_override,
+            cancel,
+        )
+        .await?;
+        if stats.files_indexed > 0 {
+            tracing::info!(
+                "Index updated: {} listed files indexed",
+                stats.files_indexed
+            );
+        }
+        report_update(&stats);
+        return Ok(stats);
+    }
+
+    // `--reindex` on a subdirectory of the index re-indexes just that subdirectory
+    if force_reindex && path.is_dir() && !cs_core::paths::same_path(path, index_root) {
+        let stats = cs_index::smart_update_paths_cancellable(
+            index_root,
+            &[path.to_path_buf()],
+            true,
+            progress_callback,
+            detailed_progress_callback,
+            need_embeddings,
+            respect_gitignore,
+            exclude_patterns,
+            model_override,
+            cancel,
+        )
+        .await?;
+        tracing::info!(
+            "Index updated: {} files under {:?} reindexed, {} orphaned files removed",
+            stats.files_indexed,
+            path,
+            stats.orphaned_files_removed
+        );
+        report_update(&stats);
+        return Ok(stats);
+    }
+
     // Pass the original path to indexing function so it can index just that file/directory
     // The indexing function will use collect_files() which now handles individual files correctly
     if force_reindex {
-        let stats = cs_index::smart_update_index_with_detailed_progress(
+        let stats = cs_index::smart_update_index_cancellable(
             index_root,
             true,
             progress_callback,
@@ -1255,6 +2354,7 @@ async fn ensure_index_updated_with_progress(
             respect_gitignore,
             exclude_patterns, // Use search-specific exclude patterns
             model_override,
+            cancel,
         )
         .await?;
         if stats.files_indexed > 0 || stats.orphaned_files_removed > 0 {
@@ -1264,7 +2364,8 @@ async fn ensure_index_updated_with_progress(
                 stats.orphaned_files_removed
             );
         }
-        return Ok(());
+        report_update(&stats);
+        return Ok(stats);
     }
 
     // For incremental updates with individual files, we need special handling
@@ -1273,9 +2374,13 @@ async fn ensure_index_updated_with_progress(
         // Index just this one file
         use cs_index::index_file;
         index_file(path, need_embeddings).await?;
+        Ok(cs_index::UpdateStats {
+            files_indexed: 1,
+            ..Default::default()
+        })
     } else {
         // For directories, use the standard smart update
-        let stats = cs_index::smart_update_index_with_detailed_progress(
+        let stats = cs_index::smart_update_index_cancellable(
             index_root,
             false,
             progress_callback,
@@ -1284,6 +2389,7 @@ async fn ensure_index_updated_with_progress(
             respect_gitignore,
             exclude_patterns,
             model_override,
+            cancel,
         )
         .await?;
         if stats.files_indexed > 0 || stats.orphaned_files_removed > 0 {
@@ -1293,21 +2399,55 @@ async fn ensure_index_updated_with_progress(
                 stats.orphaned_files_removed
             );
         }
+        report_update(&stats);
+        Ok(stats)
     }
+}
 
-    Ok(())
+/// Log files an update embedded after they were indexed without embeddings, and warn about
+/// those it could not, which semantic search still misses, and about an update cut short by
+/// Ctrl-C
+fn report_update(stats: &cs_index::UpdateStats) {
+    if stats.interrupted {
+        tracing::warn!(
+            "Indexing interrupted by user after {} files; results may miss the rest",
+            stats.files_indexed
+        );
+    }
+    if stats.files_backfilled > stats.files_unembedded {
+        tracing::info!(
+            "Backfilled embeddings for {} files indexed without them",
+            stats.files_backfilled - stats.files_unembedded
+        );
+    }
+    if stats.files_unembedded > 0 {
+        tracing::warn!(
+            "{} files have no embeddings and are missing from semantic results; they are embedded on the next update",
+            stats.files_unembedded
+        );
+    }
 }
 
-fn get_context_preview(lines: &[String], line_idx: usize, options: &SearchOptions) -> String {
+/// Lines `first..=last` with the requested context around them
+fn get_context_preview(
+    lines: &[DecodedLine],
+    first: usize,
+    last: usize,
+    options: &SearchOptions,
+) -> String {
     let before = options.before_context_lines.max(options.context_lines);
     let after = options.after_context_lines.max(options.context_lines);
 
-    if before > 0 || after > 0 {
-        let start_idx = line_idx.saturating_sub(before);
-        let end_idx = (line_idx + after + 1).min(lines.len());
-        lines[start_idx..end_idx].join("\n")
+    if before > 0 || after > 0 || last > first {
+        let start_idx = first.saturating_sub(before);
+        let end_idx = (last + after + 1).min(lines.len());
+        lines[start_idx..end_idx]
+            .iter()
+            .map(|line| line.text.as_ref())
+            .collect::<Vec<_>>()
+            .join("\n")
     } else {
-        lines[line_idx].to_string()
+        lines[first].text.to_string()
     }
 }
 
@@ -1504,6 +2644,51 @@ mod tests {
         assert!(!results.is_empty());
     }
 
+    #[test]
+    fn test_regex_search_file_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("small.txt"), "needle\n").unwrap();
+        fs::write(temp_dir.path().join("other.txt"), "needle\n").unwrap();
+        let large = "needle in a haystack\n".repeat(200);
+        fs::write(temp_dir.path().join("large.txt"), large).unwrap();
+
+        let options = |file_limits| SearchOptions {
+            mode: SearchMode::Regex,
+            query: "needle".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            recursive: true,
+            file_limits,
+            ..Default::default()
+        };
+        let files = |results: Vec<SearchResult>| {
+            let mut files: Vec<_> = results.into_iter().map(|r| r.file).collect();
+            files.sort();
+            files.dedup();
+            files
+        };
+
+        // Regex search reads files of any size unless a limit is given
+        let all = files(regex_search(&options(Default::default())).unwrap());
+        assert_eq!(all.len(), 3);
+
+        let size_capped = cs_core::file_limits::FileLimits {
+            max_filesize: Some(1024),
+            max_files: None,
+        };
+        let small = files(regex_search(&options(size_capped)).unwrap());
+        assert_eq!(small.len(), 2);
+        assert!(small.iter().all(|file| !file.ends_with("large.txt")));
+
+        let count_capped = cs_core::file_limits::FileLimits {
+            max_filesize: None,
+            max_files: Some(1),
+        };
+        assert_eq!(
+            files(regex_search(&options(count_capped)).unwrap()).len(),
+            1
+        );
+    }
+
     #[test]
     fn test_regex_search_fixed_string() {
         let temp_dir = TempDir::new().unwrap();
@@ -1571,6 +2756,32 @@ mod tests {
         assert!(results.len() <= 5);
     }
 
+    #[test]
+    fn test_regex_search_first_match_stops_early() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..200 {
+            fs::write(
+                temp_dir.path().join(format!("file{}.txt", i)),
+                "test content",
+            )
+            .unwrap();
+        }
+
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "test".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            recursive: true,
+            first_match: true,
+            ..Default::default()
+        };
+
+        let results = regex_search(&options).unwrap();
+        assert!(!results.is_empty());
+        let files: std::collections::HashSet<_> = results.iter().map(|r| &r.file).collect();
+        assert!(files.len() < 200, "searched every file");
+    }
+
     #[test]
     fn test_regex_search_span_offsets() {
         // Test that span offsets are correctly calculated for multiple matches on a line
@@ -1610,6 +2821,398 @@ mod tests {
         assert_eq!(byte_starts.len(), 5); // All byte_starts should be unique
     }
 
+    #[test]
+    fn test_regex_search_invert_match() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("invert.txt");
+        fs::write(&test_file, "keep one\ndrop test test\nkeep two\ntest end").unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "test".to_string(),
+            path: test_file.clone(),
+            recursive: false,
+            invert_match: true,
+            ..Default::default()
+        };
+
+        let results = regex_search(&options).unwrap();
+
+        // One result per non-matching line, spanning the whole line
+        let lines: Vec<_> = results.iter().map(|r| r.span.line_start).collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert_eq!(results[0].preview, "keep one");
+        assert_eq!(results[1].span.byte_start, 24);
+        assert_eq!(results[1].span.byte_end, 32);
+
+        // Context lines go through the in-memory path and keep their surroundings
+        let options = SearchOptions {
+            after_context_lines: 1,
+            ..options
+        };
+        let results = regex_search(&options).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].preview.contains("drop test test"));
+        assert!(!results[1].preview.contains("drop test test"));
+        assert!(results[1].preview.contains("keep two"));
+        assert!(results[1].preview.contains("test end"));
+    }
+
+    #[test]
+    fn test_regex_search_multiple_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("multi.txt");
+        fs::write(&test_file, "foo.bar\nfooXbar\nbaz qux\nnothing").unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "foo.bar".to_string(),
+            extra_patterns: vec!["qux".to_string()],
+            path: test_file.clone(),
+            recursive: false,
+            ..Default::default()
+        };
+        let lines: Vec<_> = regex_search(&options)
+            .unwrap()
+            .iter()
+            .map(|r| r.span.line_start)
+            .collect();
+        assert_eq!(lines, vec![1, 2, 3]);
+
+        // -F escapes each pattern on its own
+        let options = SearchOptions {
+            fixed_string: true,
+            ..options
+        };
+        assert_eq!(
+            build_regex_pattern(options.patterns(), &options),
+            r"(?:foo\.bar)|(?:qux)"
+        );
+        let lines: Vec<_> = regex_search(&options)
+            .unwrap()
+            .iter()
+            .map(|r| r.span.line_start)
+            .collect();
+        assert_eq!(lines, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_regex_search_only_matching_and_replace() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("kv.txt");
+        fs::write(&test_file, "a=1 b=2\nnone\nc=3").unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: r"(\w)=(\d)".to_string(),
+            path: test_file.clone(),
+            recursive: false,
+            only_matching: true,
+            ..Default::default()
+        };
+        let previews = |options: &SearchOptions| -> Vec<String> {
+            regex_search(options)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.preview)
+                .collect()
+        };
+        assert_eq!(previews(&options), vec!["a=1", "b=2", "c=3"]);
+
+        let options = SearchOptions {
+            replace: Some("${2}:$1".to_string()),
+            ..options
+        };
+        assert_eq!(previews(&options), vec!["1:a", "2:b", "3:c"]);
+
+        // Without -o the whole line is rewritten, including context lines that match
+        let options = SearchOptions {
+            only_matching: false,
+            after_context_lines: 1,
+            ..options
+        };
+        let results = previews(&options);
+        assert_eq!(results[0], "1:a 2:b\nnone");
+        assert_eq!(results[2], "3:c");
+    }
+
+    #[test]
+    fn test_regex_search_invalid_utf8_keeps_results() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("latin1.txt");
+        fs::write(&test_file, b"caf\xe9 menu\r\nbad \xff\xfe error here\n").unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "error".to_string(),
+            path: test_file.clone(),
+            recursive: false,
+            ..Default::default()
+        };
+
+        let results = regex_search_until(&options, Deadline::default()).unwrap();
+        assert_eq!(results.lossy_files, 1);
+        assert_eq!(results.matches.len(), 1);
+        let result = &results.matches[0];
+        assert_eq!(result.span.line_start, 2);
+        assert_eq!(result.preview, "bad \u{FFFD}\u{FFFD} error here");
+        // Spans point at the raw bytes: an 11-byte first line, then "bad ", 2 invalid bytes, " "
+        assert_eq!(result.span.byte_start, 18);
+        assert_eq!(result.span.byte_end, 23);
+
+        // The in-memory path used for context previews decodes the same way
+        let options = SearchOptions {
+            context_lines: 1,
+            ..options
+        };
+        let results = regex_search_until(&options, Deadline::default()).unwrap();
+        assert_eq!(results.lossy_files, 1);
+        let result = &results.matches[0];
+        assert_eq!(result.span.byte_start, 18);
+        assert!(result.preview.starts_with("caf\u{FFFD} menu\n"));
+    }
+
+    #[test]
+    fn test_regex_search_transcodes_other_encodings() {
+        let temp_dir = TempDir::new().unwrap();
+        // Windows-1252 source and a UTF-16 log with a byte order mark
+        fs::write(
+            temp_dir.path().join("legacy.c"),
+            b"/* caf\xe9 */\nint error_count = 0;\n",
+        )
+        .unwrap();
+        let mut log = vec![0xFF, 0xFE];
+        log.extend(
+            "started\r\nerror: disk full\r\n"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
+        );
+        fs::write(temp_dir.path().join("service.log"), log).unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "error|café".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let results = regex_search_until(&options, Deadline::default()).unwrap();
+        assert_eq!(results.lossy_files, 0);
+        let mut found: Vec<(String, usize, &str, (usize, usize))> = results
+            .matches
+            .iter()
+            .map(|result| {
+                let name = result
+                    .file
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned();
+                let span = (result.span.byte_start, result.span.byte_end);
+                (name, result.span.line_start, result.preview.as_str(), span)
+            })
+            .collect();
+        found.sort();
+        // Spans are offsets in the files' own bytes: one byte for "é" in Windows-1252, a
+        // byte order mark and two bytes a character in UTF-16
+        assert_eq!(
+            found,
+            vec![
+                ("legacy.c".to_string(), 1, "/* café */", (3, 7)),
+                ("legacy.c".to_string(), 2, "int error_count = 0;", (15, 20)),
+                ("service.log".to_string(), 2, "error: disk full", (20, 30)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_regex_search_zip() {
+        use std::io::Write;
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("plain.txt"), "needle in plain text\n").unwrap();
+        let gz_path = temp_dir.path().join("app.log.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            fs::File::create(&gz_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(b"first\nneedle in a log\n").unwrap();
+        encoder.finish().unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "needle".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let results = regex_search(&options).unwrap();
+        assert_eq!(
+            results.len(),
+            1,
+            "archives are skipped as binary without -z"
+        );
+
+        let options = SearchOptions {
+            search_zip: true,
+            ..options
+        };
+        let results = regex_search(&options).unwrap();
+        assert_eq!(results.len(), 2);
+        let log_hit = results.iter().find(|r| r.file == gz_path).unwrap();
+        assert_eq!(log_hit.span.line_start, 2);
+        assert_eq!(log_hit.span.byte_start, 6);
+        assert_eq!(log_hit.preview, "needle in a log");
+    }
+
+    #[test]
+    fn test_regex_search_until_expired_deadline() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "needle").unwrap();
+        fs::write(temp_dir.path().join("b.txt"), "needle").unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "needle".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let results = regex_search_until(&options, Deadline::default()).unwrap();
+        assert_eq!(results.matches.len(), 2);
+        assert!(!results.partial);
+
+        // A deadline that already passed skips every file and flags the result
+        let deadline = Deadline {
+            at: Some(Instant::now()),
+            cancel: None,
+        };
+        let results = regex_search_until(&options, deadline).unwrap();
+        assert!(results.matches.is_empty());
+        assert!(results.partial);
+    }
+
+    #[test]
+    fn test_smart_case_insensitive() {
+        let smart = |pattern: &str| smart_case_insensitive([pattern], false);
+        assert!(smart("fixme"));
+        assert!(smart(r"\bfoo\S+\pL\w(?P<Name>x)"));
+        assert!(!smart("FixMe"));
+        assert!(!smart("[A-Z]x"));
+        assert!(!smart(r"\x41b"));
+        // Nothing literal to judge by, or not a pattern
+        assert!(!smart(r"\d+"));
+        assert!(!smart("(unclosed"));
+        assert!(smart(r"foo(?=\Sbar)"));
+        assert!(!smart(r"(\w)\1Z"));
+        assert!(!smart_case_insensitive(["todo", "FIXME"], false));
+        assert!(smart_case_insensitive(["a.b", "(x"], true));
+        assert!(!smart_case_insensitive([r"\S"], true));
+    }
+
+    #[test]
+    fn test_lexical_search_ignores_case() {
+        // Documents and queries are lowercased by the same tokenizer, with or without -i
+        let documents = || {
+            vec![
+                (
+                    PathBuf::from("a.rs"),
+                    "fn parse_error() -> ParseError".to_string(),
+                ),
+                (PathBuf::from("b.rs"), "fn render() {}".to_string()),
+            ]
+        };
+        for (query, case_insensitive) in [("parseerror", false), ("PARSE", true)] {
+            let options = SearchOptions {
+                mode: SearchMode::Lexical,
+                query: query.to_string(),
+                case_insensitive,
+                ..Default::default()
+            };
+            let results = lexical_search_documents(&options, documents()).unwrap();
+            let files: Vec<&Path> = results.iter().map(|result| result.file.as_path()).collect();
+            assert_eq!(files, [Path::new("a.rs")], "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_score_until_stops_between_blocks() {
+        let square = |i: usize| (i != 1).then_some(i * i);
+        let (mut scores, partial) = score_until(10, 3, Deadline::default(), square);
+        scores.sort();
+        assert_eq!(scores, vec![0, 4, 9, 16, 25, 36, 49, 64, 81]);
+        assert!(!partial);
+
+        // An expired deadline still scores the first block
+        let deadline = Deadline {
+            at: Some(Instant::now()),
+            cancel: None,
+        };
+        let (mut scores, partial) = score_until(10, 3, deadline, square);
+        scores.sort();
+        assert_eq!(scores, vec![0, 4]);
+        assert!(partial);
+        assert_eq!(score_until(0, 3, deadline, square), (Vec::new(), false));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_search_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "needle").unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "needle".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let cancel = CancellationToken::new();
+        let results = search_enhanced_cancellable(&options, None, None, None, &cancel)
+            .await
+            .unwrap();
+        assert_eq!(results.matches.len(), 1);
+
+        cancel.cancel();
+        let err = search_enhanced_cancellable(&options, None, None, None, &cancel)
+            .await
+            .unwrap_err();
+        assert!(CcError::is_cancelled(&err));
+    }
+
+    #[tokio::test]
+    async fn test_search_offset_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let lines: Vec<String> = (1..=6).map(|i| format!("needle {}", i)).collect();
+        fs::write(temp_dir.path().join("a.txt"), lines.join("\n")).unwrap();
+
+        let page = |offset| SearchOptions {
+            mode: SearchMode::Regex,
+            query: "needle".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            top_k: Some(2),
+            offset,
+            ..Default::default()
+        };
+        let first = search(&page(0)).await.unwrap();
+        let second = search(&page(2)).await.unwrap();
+        let line_starts = |results: &[SearchResult]| -> Vec<usize> {
+            results
+                .iter()
+                .map(|result| result.span.line_start)
+                .collect()
+        };
+        assert_eq!(line_starts(&first), [1, 2]);
+        assert_eq!(line_starts(&second), [3, 4]);
+        assert!(search(&page(10)).await.unwrap().is_empty());
+
+        // A cursor from another index state is refused rather than paging a new ranking
+        let stale = SearchOptions {
+            mode: SearchMode::Lexical,
+            index_epoch: Some(1),
+            ..page(2)
+        };
+        let err = search(&stale).await.unwrap_err();
+        assert!(err.to_string().contains("index changed"), "{}", err);
+    }
+
     #[test]
     fn test_search_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -1620,7 +3223,7 @@ mod tests {
         )
         .unwrap();
 
-        let regex = regex::Regex::new("rust").unwrap();
+        let regex = SearchRegex::new("rust", &SearchOptions::default()).unwrap();
         let options = SearchOptions::default();
 
         let results = search_file(&regex, &file_path, &options).unwrap();
@@ -1635,7 +3238,7 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         fs::write(&file_path, "line 1\nline 2\ntarget line\nline 4\nline 5").unwrap();
 
-        let regex = regex::Regex::new("target").unwrap();
+        let regex = SearchRegex::new("target", &SearchOptions::default()).unwrap();
         let options = SearchOptions {
             context_lines: 1,
             ..Default::default()
@@ -1653,6 +3256,66 @@ mod tests {
         assert!(results[0].preview.contains("line 4"));
     }
 
+    #[test]
+    fn test_multiline_search() {
+        let content = b"fn foo(\n    a: i32,\r\n) {\n}\nfn bar() {}\n";
+        let path = Path::new("a.rs");
+        let options = SearchOptions {
+            multiline: true,
+            ..Default::default()
+        };
+        let search = |pattern: &str, options: &SearchOptions| {
+            let regex = SearchRegex::new(pattern, options).unwrap();
+            search_content(&regex, path, options, content).unwrap().0
+        };
+
+        let results = search(r"fn \w+\([^)]*\)\s*\{", &options);
+        let spans: Vec<_> = results
+            .iter()
+            .map(|result| {
+                let span = &result.span;
+                (
+                    span.byte_start,
+                    span.byte_end,
+                    span.line_start,
+                    span.line_end,
+                )
+            })
+            .collect();
+        assert_eq!(spans, [(0, 24, 1, 3), (27, 37, 5, 5)]);
+        assert_eq!(results[0].preview, "fn foo(\n    a: i32,\n) {");
+
+        // A match ending with a line break ends on that line; ^ and $ see \r\n
+        let results = search(r"i32,\r?\n", &options);
+        assert_eq!(
+            (results[0].span.line_start, results[0].span.line_end),
+            (2, 2)
+        );
+        assert_eq!(search(r",$\s*^\)", &options).len(), 1);
+
+        let only_matching = SearchOptions {
+            only_matching: true,
+            ..options.clone()
+        };
+        assert_eq!(
+            search(r"\(\n[^)]*\)", &only_matching)[0].preview,
+            "(\n    a: i32,\r\n)"
+        );
+
+        let inverted = SearchOptions {
+            invert_match: true,
+            ..options.clone()
+        };
+        let lines: Vec<_> = search(r"foo\(\n.*", &inverted)
+            .iter()
+            .map(|result| result.span.line_start)
+            .collect();
+        assert_eq!(lines, [3, 4, 5]);
+
+        // Without -U lines are matched one at a time
+        assert!(search(r"\(\n", &SearchOptions::default()).is_empty());
+    }
+
     #[tokio::test]
     async fn test_search_main_function() {
         let temp_dir = TempDir::new().unwrap();
@@ -1671,6 +3334,98 @@ mod tests {
         assert!(!results.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_json_output_fills_signals_and_symbols() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("lib.rs");
+        fs::write(
+            &file,
+            "fn load() {\n    let retries = 3;\n}\n\nfn save() {\n    let retries = 5;\n}\n",
+        )
+        .unwrap();
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "retries".to_string(),
+            path: file.clone(),
+            json_output: true,
+            ..Default::default()
+        };
+        let results = search(&options).await.unwrap();
+        let found: Vec<_> = results
+            .iter()
+            .map(|r| {
+                (
+                    r.symbol.as_deref(),
+                    r.signals.as_ref().and_then(|s| s.lex_rank),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![(Some("load"), Some(1)), (Some("save"), Some(2))]
+        );
+        // Each match carries the id of its function
+        let ids: Vec<_> = results.iter().map(|r| r.chunk_hash.clone()).collect();
+        assert!(
+            ids.iter()
+                .all(|id| id.as_ref().is_some_and(|id| id.len() == 64))
+        );
+        assert_ne!(ids[0], ids[1]);
+
+        // A fused result keeps its rank in each list
+        let fused = fuse_rrf(
+            &options,
+            &[("regex", &results[..]), ("semantic", &results[1..])],
+        );
+        let save = fused
+            .iter()
+            .find(|r| r.symbol.as_deref() == Some("save"))
+            .unwrap();
+        let rrf_score = 1.0 / 62.0 + 1.0 / 61.0;
+        assert_eq!(
+            save.signals,
+            Some(cs_core::SearchSignals {
+                lex_rank: Some(2),
+                vec_rank: Some(1),
+                rrf_score: Some(rrf_score),
+                raw_score: Some(rrf_score),
+            })
+        );
+        // Calibrated against a result ranked first in both lists
+        assert!((save.score - rrf_score * 61.0 / 2.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_lexical_hits_are_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".cs")).unwrap();
+        let file = root.join("upload.py");
+        fs::write(
+            &file,
+            "import os\n\n\ndef list_files():\n    return os.listdir()\n\n\ndef retry_upload(upload):\n    return upload.send()\n",
+        )
+        .unwrap();
+        cs_index::index_file(&file, false).await.unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Hybrid,
+            query: "retry failed upload".to_string(),
+            path: root.to_path_buf(),
+            threshold: Some(0.02),
+            hybrid_lexical: true,
+            // The default `tmp` exclude would match the temp directory itself
+            exclude_patterns: Vec::new(),
+            ..Default::default()
+        };
+        let results = hybrid_lexical_search(&options).await.unwrap();
+
+        // Only the chunk containing a query term, keyed like the semantic hit on that chunk
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].span.line_start, 8);
+        assert!(results[0].preview.starts_with("def retry_upload"));
+    }
+
     #[tokio::test]
     async fn test_regex_search_mixed_line_endings() {
         // Regression test for byte offset issues with different line endings
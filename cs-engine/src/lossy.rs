@@ -0,0 +1,101 @@
+// Lossy UTF-8 decoding for regex matching and previews
+// Invalid byte sequences are shown as U+FFFD while spans still point at the original bytes
+
+use std::borrow::Cow;
+
+const REPLACEMENT_LEN: usize = char::REPLACEMENT_CHARACTER.len_utf8();
+
+/// One line of a file as text, with enough bookkeeping to map text positions back to bytes
+#[derive(Debug, Clone)]
+pub(crate) struct DecodedLine<'a> {
+    pub text: Cow<'a, str>,
+    /// Length of the line in the original file, excluding the line ending
+    pub byte_len: usize,
+    /// Position in `text` and original byte length of each substituted sequence
+    substitutions: Vec<(usize, usize)>,
+}
+
+impl<'a> DecodedLine<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self {
+                text: Cow::Borrowed(text),
+                byte_len: bytes.len(),
+                substitutions: Vec::new(),
+            },
+            Err(_) => {
+                let mut text = String::with_capacity(bytes.len() + REPLACEMENT_LEN);
+                let mut substitutions = Vec::new();
+                for chunk in bytes.utf8_chunks() {
+                    text.push_str(chunk.valid());
+                    if !chunk.invalid().is_empty() {
+                        substitutions.push((text.len(), chunk.invalid().len()));
+                        text.push(char::REPLACEMENT_CHARACTER);
+                    }
+                }
+                Self {
+                    text: Cow::Owned(text),
+                    byte_len: bytes.len(),
+                    substitutions,
+                }
+            }
+        }
+    }
+
+    /// True when invalid bytes were replaced to produce `text`
+    pub fn is_lossy(&self) -> bool {
+        !self.substitutions.is_empty()
+    }
+
+    /// Byte offset in the original line for a char boundary `pos` in `text`
+    pub fn original_offset(&self, pos: usize) -> usize {
+        let mut offset = pos;
+        for &(at, len) in &self.substitutions {
+            if at >= pos {
+                break;
+            }
+            offset = offset + len - REPLACEMENT_LEN;
+        }
+        offset
+    }
+}
+
+impl From<String> for DecodedLine<'static> {
+    fn from(text: String) -> Self {
+        Self {
+            byte_len: text.len(),
+            text: Cow::Owned(text),
+            substitutions: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_line_is_borrowed() {
+        let line = DecodedLine::new("héllo".as_bytes());
+        assert!(matches!(line.text, Cow::Borrowed("héllo")));
+        assert!(!line.is_lossy());
+        assert_eq!(line.original_offset(3), 3);
+    }
+
+    #[test]
+    fn test_invalid_bytes_map_back_to_original_offsets() {
+        // "a" + two invalid bytes + "bc" + one invalid byte + "d"
+        let bytes = b"a\xff\xfebc\x80d";
+        let line = DecodedLine::new(bytes);
+        assert!(line.is_lossy());
+        assert_eq!(line.text, "a\u{FFFD}\u{FFFD}bc\u{FFFD}d");
+        assert_eq!(line.byte_len, bytes.len());
+
+        let bc = line.text.find("bc").unwrap();
+        assert_eq!(line.original_offset(bc), 3);
+        assert_eq!(line.original_offset(bc + 2), 5);
+        let d = line.text.find('d').unwrap();
+        assert_eq!(line.original_offset(d), 6);
+        assert_eq!(line.original_offset(line.text.len()), bytes.len());
+    }
+}
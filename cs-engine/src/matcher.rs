@@ -0,0 +1,201 @@
+//! The pattern engine behind regex and symbol search: the `regex` crate, or PCRE2 with `-P`
+//! for look-around and backreferences. PCRE2 is the optional `pcre2` cargo feature; without
+//! it `-P` fails with an error saying how to get it.
+
+use anyhow::Result;
+use cs_core::{CcError, SearchOptions};
+use regex::RegexBuilder;
+use regex_syntax::ast::{ErrorKind, parse::Parser};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// A compiled search pattern
+#[derive(Debug, Clone)]
+pub enum SearchRegex {
+    Standard(regex::Regex),
+    #[cfg(feature = "pcre2")]
+    Pcre2(pcre2::bytes::Regex),
+}
+
+impl SearchRegex {
+    /// Compile `pattern` with the engine and case sensitivity `options` ask for
+    pub fn new(pattern: &str, options: &SearchOptions) -> Result<Self> {
+        if options.pcre2 {
+            if options.replace.is_some() {
+                anyhow::bail!("--replace is not supported with -P/--pcre2");
+            }
+            return Self::pcre2(pattern, options);
+        }
+        // With -U, `^` and `$` match at every line break, `\r\n` included
+        match RegexBuilder::new(pattern)
+            .case_insensitive(options.case_insensitive)
+            .multi_line(options.multiline)
+            .crlf(options.multiline)
+            .build()
+        {
+            Ok(regex) => Ok(Self::Standard(regex)),
+            Err(e) if needs_pcre2(pattern) => Err(anyhow::anyhow!(
+                "{}\nThe pattern uses look-around or backreferences: search with -P/--pcre2{}",
+                e,
+                PCRE2_HINT
+            )),
+            Err(e) => Err(CcError::Regex(e).into()),
+        }
+    }
+
+    #[cfg(feature = "pcre2")]
+    fn pcre2(pattern: &str, options: &SearchOptions) -> Result<Self> {
+        let regex = pcre2::bytes::RegexBuilder::new()
+            .caseless(options.case_insensitive)
+            .multi_line(options.multiline)
+            .crlf(options.multiline)
+            .utf(true)
+            .ucp(true)
+            .jit_if_available(true)
+            .build(pattern)
+            .map_err(|e| CcError::Search(format!("Invalid PCRE2 pattern: {}", e)))?;
+        Ok(Self::Pcre2(regex))
+    }
+
+    #[cfg(not(feature = "pcre2"))]
+    fn pcre2(_pattern: &str, _options: &SearchOptions) -> Result<Self> {
+        anyhow::bail!("-P/--pcre2 is not available{}", PCRE2_HINT)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Standard(regex) => regex.as_str(),
+            #[cfg(feature = "pcre2")]
+            Self::Pcre2(regex) => regex.as_str(),
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            Self::Standard(regex) => regex.is_match(text),
+            // Matching fails only when PCRE2 hits its backtracking limit; treat it as no match
+            #[cfg(feature = "pcre2")]
+            Self::Pcre2(regex) => regex.is_match(text.as_bytes()).unwrap_or_else(|e| {
+                tracing::debug!("PCRE2 match failed: {}", e);
+                false
+            }),
+        }
+    }
+
+    /// Byte ranges of the successive non-overlapping matches in `text`
+    pub fn find_iter(&self, text: &str) -> Vec<Range<usize>> {
+        match self {
+            Self::Standard(regex) => regex.find_iter(text).map(|mat| mat.range()).collect(),
+            #[cfg(feature = "pcre2")]
+            Self::Pcre2(regex) => regex
+                .find_iter(text.as_bytes())
+                .map_while(|mat| match mat {
+                    Ok(mat) => Some(mat.start()..mat.end()),
+                    Err(e) => {
+                        tracing::debug!("PCRE2 match failed: {}", e);
+                        None
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// `template` expanded with the captures of the match starting at `start` (`-o` with
+    /// `--replace`)
+    pub(crate) fn expand_at(&self, text: &str, start: usize, template: &str) -> String {
+        match self {
+            Self::Standard(regex) => regex
+                .captures_at(text, start)
+                .map(|caps| {
+                    let mut expanded = String::new();
+                    caps.expand(template, &mut expanded);
+                    expanded
+                })
+                .unwrap_or_default(),
+            // `new` refuses --replace with PCRE2
+            #[cfg(feature = "pcre2")]
+            Self::Pcre2(_) => String::new(),
+        }
+    }
+
+    /// Every match in `text` replaced by `template` (`--replace`)
+    pub(crate) fn replace_all<'t>(&self, text: &'t str, template: &str) -> Cow<'t, str> {
+        match self {
+            Self::Standard(regex) => regex.replace_all(text, template),
+            #[cfg(feature = "pcre2")]
+            Self::Pcre2(_) => Cow::Borrowed(text),
+        }
+    }
+}
+
+#[cfg(feature = "pcre2")]
+const PCRE2_HINT: &str = "";
+
+#[cfg(not(feature = "pcre2"))]
+const PCRE2_HINT: &str =
+    " (needs a build with the pcre2 feature: cargo install cs-search --features pcre2)";
+
+/// Whether `pattern` is refused by the `regex` crate for look-around or backreferences
+pub(crate) fn needs_pcre2(pattern: &str) -> bool {
+    Parser::new().parse(pattern).is_err_and(|e| {
+        matches!(
+            e.kind(),
+            ErrorKind::UnsupportedLookAround | ErrorKind::UnsupportedBackreference
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_engine() {
+        let options = SearchOptions {
+            case_insensitive: true,
+            ..Default::default()
+        };
+        let regex = SearchRegex::new("fo+", &options).unwrap();
+        assert_eq!(regex.find_iter("Foo fooo f"), [0..3, 4..8]);
+        assert!(regex.is_match("FOO"));
+        assert_eq!(regex.replace_all("foo bar", "x"), "x bar");
+    }
+
+    #[test]
+    fn test_look_around_asks_for_pcre2() {
+        let error = SearchRegex::new(r"foo(?=bar)", &SearchOptions::default())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("-P/--pcre2"), "{}", error);
+        let error = SearchRegex::new(r"(\w)\1", &SearchOptions::default())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("-P/--pcre2"), "{}", error);
+        let error = SearchRegex::new("(unclosed", &SearchOptions::default())
+            .unwrap_err()
+            .to_string();
+        assert!(!error.contains("-P/--pcre2"), "{}", error);
+    }
+
+    #[test]
+    fn test_pcre2_engine() {
+        let options = SearchOptions {
+            pcre2: true,
+            ..Default::default()
+        };
+        let regex = SearchRegex::new(r"(\w)\1(?=!)", &options);
+        if cfg!(feature = "pcre2") {
+            let regex = regex.unwrap();
+            assert_eq!(regex.find_iter("aa bb! éé!"), [3..5, 7..11]);
+            assert!(!regex.is_match("aa bb"));
+        } else {
+            assert!(regex.unwrap_err().to_string().contains("pcre2 feature"));
+        }
+
+        let replace = SearchOptions {
+            replace: Some("x".to_string()),
+            ..options
+        };
+        assert!(SearchRegex::new("a", &replace).is_err());
+    }
+}
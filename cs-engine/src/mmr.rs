@@ -0,0 +1,81 @@
+// Maximal Marginal Relevance (`--diversity`)
+// Picks results one at a time, trading query similarity against similarity to what was already
+// picked, so near-duplicate chunks (overlapping strides, copied code) do not fill the top-K
+
+use super::semantic_v3::cosine_similarity;
+
+/// Candidates considered per requested result; MMR only reorders within this pool
+pub(crate) const MMR_POOL_FACTOR: usize = 5;
+
+/// Upper bound on the pool when no top-K is set (selection is quadratic in the pool size)
+pub(crate) const MMR_MAX_POOL: usize = 1000;
+
+/// Indices of `relevance` in MMR order, `k` of them at most
+///
+/// `diversity` is the weight of novelty: 0 keeps relevance order, 1 only avoids redundancy.
+/// Candidates without an embedding count as dissimilar to everything.
+pub(crate) fn mmr_order(
+    relevance: &[f32],
+    embeddings: &[Option<&[f32]>],
+    k: usize,
+    diversity: f32,
+) -> Vec<usize> {
+    let mut selected: Vec<usize> = Vec::with_capacity(k.min(relevance.len()));
+    // Highest similarity of each candidate to anything selected so far
+    let mut redundancy = vec![f32::NEG_INFINITY; relevance.len()];
+    let mut remaining: Vec<usize> = (0..relevance.len()).collect();
+
+    while selected.len() < k && !remaining.is_empty() {
+        let (position, &best) = remaining
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| {
+                let score = |i: usize| {
+                    let penalty = if redundancy[i].is_finite() {
+                        redundancy[i]
+                    } else {
+                        0.0
+                    };
+                    (1.0 - diversity) * relevance[i] - diversity * penalty
+                };
+                score(**a)
+                    .partial_cmp(&score(**b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    // Ties go to the more relevant (earlier) candidate
+                    .then_with(|| b.cmp(a))
+            })
+            .expect("remaining is not empty");
+        remaining.swap_remove(position);
+        selected.push(best);
+
+        if let Some(picked) = embeddings[best] {
+            for &i in &remaining {
+                if let Some(candidate) = embeddings[i] {
+                    redundancy[i] = redundancy[i].max(cosine_similarity(picked, candidate));
+                }
+            }
+        }
+    }
+
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mmr_skips_near_duplicates() {
+        let a = [1.0, 0.0];
+        let a_copy = [0.99, 0.01];
+        let b = [0.0, 1.0];
+        let relevance = [0.9, 0.89, 0.7];
+        let embeddings = [Some(&a[..]), Some(&a_copy[..]), Some(&b[..])];
+
+        // No diversity: plain relevance order
+        assert_eq!(mmr_order(&relevance, &embeddings, 2, 0.0), vec![0, 1]);
+        // With diversity the duplicate of the first pick drops behind the distinct chunk
+        assert_eq!(mmr_order(&relevance, &embeddings, 3, 0.5), vec![0, 2, 1]);
+        assert_eq!(mmr_order(&relevance, &embeddings, 10, 0.5).len(), 3);
+    }
+}
@@ -0,0 +1,78 @@
+// Negative queries (`--not "unit tests"`)
+// Each `--not` phrase is embedded alongside the query. A chunk's similarity to its closest
+// negative phrase is rescaled across the scored chunks (least similar 0, most similar 1), and
+// its score shrinks by `weight` times that share: chunks about the unwanted concept sink, the
+// rest keep their score, and `--threshold` can only drop more results
+
+use super::semantic_v3::cosine_similarity;
+use cs_core::SearchOptions;
+
+/// `--not-weight` when none is given
+pub(crate) const DEFAULT_NEGATIVE_WEIGHT: f32 = 0.5;
+
+pub(crate) fn negative_weight(options: &SearchOptions) -> f32 {
+    options
+        .negative_weight
+        .unwrap_or(DEFAULT_NEGATIVE_WEIGHT)
+        .clamp(0.0, 1.0)
+}
+
+/// Cosine similarity of `embedding` to its closest negative phrase
+pub(crate) fn similarity(negatives: &[Vec<f32>], embedding: &[f32]) -> f32 {
+    negatives
+        .iter()
+        .map(|negative| cosine_similarity(negative, embedding))
+        .fold(f32::NEG_INFINITY, f32::max)
+}
+
+/// Negative similarities rescaled to 0..=1 across the chunks; all zero when every chunk is
+/// equally close, since there is nothing to steer away from
+pub(crate) fn normalize(similarities: &[f32]) -> Vec<f32> {
+    let min = similarities.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = similarities
+        .iter()
+        .copied()
+        .fold(f32::NEG_INFINITY, f32::max);
+    let range = max - min;
+    if range.is_nan() || range <= 0.0 {
+        return vec![0.0; similarities.len()];
+    }
+    similarities
+        .iter()
+        .map(|similarity| (similarity - min) / range)
+        .collect()
+}
+
+/// `score` lowered by `weight` times the chunk's normalized negative similarity
+pub(crate) fn penalize(score: f32, negative: f32, weight: f32) -> f32 {
+    score - weight * negative * score.abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_similarity_lowers_scores() {
+        let negatives = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        assert!((similarity(&negatives, &[0.0, 1.0]) - 1.0).abs() < 1e-6);
+
+        let normalized = normalize(&[0.25, 0.5, 0.75]);
+        assert_eq!(normalized, vec![0.0, 0.5, 1.0]);
+        assert_eq!(normalize(&[0.4, 0.4]), vec![0.0, 0.0]);
+
+        assert_eq!(penalize(0.8, 0.0, 0.5), 0.8);
+        assert!((penalize(0.8, 1.0, 0.5) - 0.4).abs() < 1e-6);
+        assert!(penalize(-0.2, 1.0, 0.5) < -0.2);
+
+        let options = SearchOptions {
+            negative_weight: Some(3.0),
+            ..Default::default()
+        };
+        assert_eq!(negative_weight(&options), 1.0);
+        assert_eq!(
+            negative_weight(&SearchOptions::default()),
+            DEFAULT_NEGATIVE_WEIGHT
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Two-stage semantic scoring for indexes configured with `quantization = "int8"` under
+//! `[index_backend]` in `cs.toml`: embeddings are held as int8 codes while sidecars load, the
+//! codes are scanned for a candidate pool, and only the pool gets its full-precision vectors
+//! back from the sidecars to be scored exactly.
+
+use anyhow::Result;
+use cs_ann::{AnnIndex, Int8Index};
+use cs_core::{Quantization, SearchOptions};
+use cs_index::ChunkEntry;
+use cs_index::sparse::SparseVector;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::{rerank, sparse_fusion};
+
+pub(crate) struct QuantizedChunks {
+    codes: Int8Index,
+    rerank_factor: usize,
+}
+
+impl QuantizedChunks {
+    /// `None` unless the config governing `index_root` asks for int8 quantization
+    pub(crate) fn configured(index_root: &Path) -> Option<Self> {
+        let backend = cs_models::ProjectConfig::discover(index_root)
+            .ok()
+            .flatten()
+            .and_then(|config| config.index_backend)?;
+        (backend.quantization == Quantization::Int8).then(|| Self {
+            codes: Int8Index::new(),
+            rerank_factor: backend.rerank_factor.max(1),
+        })
+    }
+
+    /// Swap `chunk`'s embedding for codes under `position` in `file_chunks`; an embedding the
+    /// codes cannot hold (mixed dimensions) stays in full precision
+    pub(crate) fn quantize(&mut self, position: usize, chunk: &mut ChunkEntry) {
+        if let Some(embedding) = chunk.embedding.as_deref()
+            && self.codes.add(position as u32, embedding).is_ok()
+        {
+            chunk.embedding = None;
+        }
+    }
+
+    /// Keep the chunks that score best on the codes, `rerank_factor` times the result limit,
+    /// with their full-precision embeddings restored; `file_sparse` and `sparse_scores` stay
+    /// parallel to `file_chunks`
+    pub(crate) fn restore_candidates(
+        self,
+        options: &SearchOptions,
+        index_root: &Path,
+        query_embeddings: &[Vec<f32>],
+        file_chunks: &mut Vec<(PathBuf, ChunkEntry)>,
+        file_sparse: &mut Vec<Option<SparseVector>>,
+        sparse_scores: &mut Option<Vec<f32>>,
+    ) -> Result<()> {
+        let mut approximate: Vec<f32> = file_chunks
+            .iter()
+            .map(|(_, chunk)| match chunk.embedding.as_deref() {
+                Some(embedding) => query_embeddings
+                    .iter()
+                    .map(|query| super::semantic_v3::cosine_similarity(query, embedding))
+                    .fold(f32::NEG_INFINITY, f32::max),
+                None => f32::NEG_INFINITY,
+            })
+            .collect();
+        if !self.codes.is_empty() {
+            for query in query_embeddings {
+                for (position, score) in self.codes.search(query, self.codes.len())? {
+                    let best = &mut approximate[position as usize];
+                    *best = best.max(score);
+                }
+            }
+        }
+        if let Some(scores) = sparse_scores.as_ref() {
+            let weight = sparse_fusion::sparse_weight(options);
+            for (score, sparse) in approximate.iter_mut().zip(scores) {
+                *score = sparse_fusion::fuse(*score, *sparse, weight);
+            }
+        }
+
+        let limit = rerank::candidate_limit(options).unwrap_or(file_chunks.len());
+        let mut ranked: Vec<usize> = (0..file_chunks.len()).collect();
+        ranked.sort_by(|a, b| {
+            approximate[*b]
+                .partial_cmp(&approximate[*a])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        ranked.truncate(limit.saturating_mul(self.rerank_factor));
+        let keep: HashSet<usize> = ranked.into_iter().collect();
+
+        // Full-precision vectors for the pool, one sidecar read per file
+        let mut by_file: HashMap<&Path, Vec<usize>> = HashMap::new();
+        for &position in &keep {
+            if file_chunks[position].1.embedding.is_none() {
+                by_file
+                    .entry(file_chunks[position].0.as_path())
+                    .or_default()
+                    .push(position);
+            }
+        }
+        let mut restored: HashMap<usize, Vec<f32>> = HashMap::new();
+        for (file, positions) in by_file {
+            let Ok(entry) =
+                cs_index::load_index_entry(&cs_core::get_sidecar_path(index_root, file))
+            else {
+                continue;
+            };
+            let mut exact: HashMap<(usize, usize), Vec<f32>> = entry
+                .chunks
+                .into_iter()
+                .filter_map(|chunk| {
+                    let span = (chunk.span.byte_start, chunk.span.byte_end);
+                    chunk.embedding.map(|embedding| (span, embedding))
+                })
+                .collect();
+            for position in positions {
+                let span = &file_chunks[position].1.span;
+                if let Some(embedding) = exact.remove(&(span.byte_start, span.byte_end)) {
+                    restored.insert(position, embedding);
+                }
+            }
+        }
+
+        // A candidate whose sidecar changed since loading drops out rather than scoring on codes
+        let survivors: Vec<bool> = file_chunks
+            .iter()
+            .enumerate()
+            .map(|(position, (_, chunk))| {
+                keep.contains(&position)
+                    && (chunk.embedding.is_some() || restored.contains_key(&position))
+            })
+            .collect();
+        *file_chunks = std::mem::take(file_chunks)
+            .into_iter()
+            .enumerate()
+            .filter(|(position, _)| survivors[*position])
+            .map(|(position, (file, mut chunk))| {
+                if let Some(embedding) = restored.remove(&position) {
+                    chunk.embedding = Some(embedding);
+                }
+                (file, chunk)
+            })
+            .collect();
+        *file_sparse = retain_survivors(std::mem::take(file_sparse), &survivors);
+        if let Some(scores) = sparse_scores.as_mut() {
+            *scores = retain_survivors(std::mem::take(scores), &survivors);
+        }
+        Ok(())
+    }
+}
+
+fn retain_survivors<T>(items: Vec<T>, survivors: &[bool]) -> Vec<T> {
+    items
+        .into_iter()
+        .zip(survivors)
+        .filter_map(|(item, survived)| survived.then_some(item))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cs_core::Span;
+    use cs_index::IndexEntry;
+
+    fn chunk(index: usize, embedding: Vec<f32>) -> ChunkEntry {
+        ChunkEntry {
+            span: Span::new(index * 10, index * 10 + 10, index + 1, index + 1).unwrap(),
+            embedding: Some(embedding),
+            chunk_type: cs_chunk::ChunkType::Text,
+            breadcrumb: None,
+            ancestry: None,
+            byte_length: None,
+            estimated_tokens: None,
+            leading_trivia: None,
+            trailing_trivia: None,
+            stride: None,
+        }
+    }
+
+    #[test]
+    fn test_restore_candidates_keeps_best_with_exact_embeddings() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut quantized = QuantizedChunks {
+            codes: Int8Index::new(),
+            rerank_factor: 2,
+        };
+
+        // Chunk `i` of each file points further from the query axis as `i` grows
+        let mut file_chunks = Vec::new();
+        for name in ["a.rs", "b.rs"] {
+            let file = root.join(name);
+            let offset = if name == "a.rs" { 0.0 } else { 0.05 };
+            let chunks: Vec<ChunkEntry> = (0..5)
+                .map(|i| chunk(i, vec![1.0, i as f32 * 0.3 + offset, 0.1]))
+                .collect();
+            let sidecar = cs_core::get_sidecar_path(root, &file);
+            std::fs::create_dir_all(sidecar.parent().unwrap()).unwrap();
+            let entry = IndexEntry {
+                metadata: cs_core::FileMetadata {
+                    path: file.clone(),
+                    hash: String::new(),
+                    last_modified: 0,
+                    size: 0,
+                },
+                chunks: chunks.clone(),
+                embedding_info: None,
+                sparse: None,
+                withheld: None,
+            };
+            cs_index::save_index_entry(&sidecar, &entry).unwrap();
+            for mut chunk in chunks {
+                quantized.quantize(file_chunks.len(), &mut chunk);
+                assert!(chunk.embedding.is_none());
+                file_chunks.push((file.clone(), chunk));
+            }
+        }
+        let mut file_sparse = vec![None; file_chunks.len()];
+        let mut sparse_scores = Some((0..file_chunks.len()).map(|i| i as f32 / 1000.0).collect());
+
+        let options = SearchOptions {
+            top_k: Some(2),
+            ..Default::default()
+        };
+        quantized
+            .restore_candidates(
+                &options,
+                root,
+                &[vec![1.0, 0.0, 0.0]],
+                &mut file_chunks,
+                &mut file_sparse,
+                &mut sparse_scores,
+            )
+            .unwrap();
+
+        let kept: Vec<(String, usize)> = file_chunks
+            .iter()
+            .map(|(file, chunk)| {
+                let name = file.file_name().unwrap().to_string_lossy().to_string();
+                (name, chunk.span.byte_start / 10)
+            })
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                ("a.rs".to_string(), 0),
+                ("a.rs".to_string(), 1),
+                ("b.rs".to_string(), 0),
+                ("b.rs".to_string(), 1),
+            ]
+        );
+        assert_eq!(file_chunks[1].1.embedding, Some(vec![1.0, 0.3, 0.1]));
+        assert_eq!(file_sparse.len(), 4);
+        assert_eq!(sparse_scores, Some(vec![0.0, 0.001, 0.005, 0.006]));
+    }
+}
@@ -0,0 +1,182 @@
+// Query expansion (`--expand-query`)
+// Terms of the query are expanded with code-aware synonyms ("auth" -> "authentication",
+// "login", ...); each variant is searched as an extra pattern and a chunk keeps its best score.
+// An external expander (e.g. an LLM wrapper script) can replace the built-in thesaurus.
+
+use cs_core::SearchOptions;
+use std::borrow::Cow;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Command that expands a query: it gets the query on stdin and prints one variant per line
+const QUERY_EXPANDER_ENV: &str = "CS_QUERY_EXPANDER";
+
+/// Synonyms used per expanded term and variants per query, keeping embedding cost bounded
+const MAX_SYNONYMS_PER_TERM: usize = 3;
+const MAX_VARIANTS: usize = 8;
+
+/// Groups of interchangeable terms; every term expands to the others in its group
+const THESAURUS: &[&[&str]] = &[
+    &["auth", "authentication", "login", "credentials", "signin"],
+    &["authz", "authorization", "permission", "access control"],
+    &["password", "secret", "token", "credential"],
+    &["db", "database", "sql", "storage"],
+    &["config", "configuration", "settings", "options"],
+    &["err", "error", "exception", "failure"],
+    &["init", "initialize", "setup", "bootstrap"],
+    &["delete", "remove", "destroy", "drop"],
+    &["fn", "function", "method", "func"],
+    &["util", "utility", "helper", "utils"],
+    &["req", "request", "query"],
+    &["res", "resp", "response", "reply"],
+    &["msg", "message", "event", "notification"],
+    &["conn", "connection", "socket", "client"],
+    &["cache", "memoize", "memoization"],
+    &["retry", "backoff", "reattempt"],
+    &["log", "logging", "logger", "tracing"],
+    &["parse", "parser", "tokenize", "deserialize"],
+    &["serialize", "encode", "marshal"],
+    &["api", "endpoint", "route", "handler"],
+    &["async", "await", "future", "promise"],
+    &["env", "environment", "variables"],
+    &["perf", "performance", "latency", "throughput"],
+    &["mem", "memory", "allocation", "heap"],
+    &["user", "account", "profile"],
+    &["arg", "argument", "parameter", "param"],
+    &["tmp", "temp", "temporary"],
+    &["ctx", "context"],
+    &["impl", "implementation"],
+    &["dir", "directory", "folder"],
+];
+
+/// Variants of `query` with one thesaurus term replaced by a synonym, original excluded
+pub fn expand_query(query: &str) -> Vec<String> {
+    let mut variants = Vec::new();
+    for word in query.split_whitespace() {
+        let term = word
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        let Some(group) = THESAURUS
+            .iter()
+            .find(|group| group.contains(&term.as_str()))
+        else {
+            continue;
+        };
+        for synonym in group
+            .iter()
+            .filter(|synonym| **synonym != term)
+            .take(MAX_SYNONYMS_PER_TERM)
+        {
+            let variant = query
+                .split_whitespace()
+                .map(|other| if other == word { *synonym } else { other })
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !variants.contains(&variant) {
+                variants.push(variant);
+            }
+        }
+    }
+    variants.truncate(MAX_VARIANTS);
+    variants
+}
+
+/// Variants from the `CS_QUERY_EXPANDER` command when set, else from the built-in thesaurus
+pub fn query_variants(query: &str) -> Vec<String> {
+    let Some(command) = std::env::var_os(QUERY_EXPANDER_ENV) else {
+        return expand_query(query);
+    };
+    match run_expander(&command.to_string_lossy(), query) {
+        Ok(variants) => variants,
+        Err(e) => {
+            tracing::warn!(
+                "{} failed ({}); using the built-in thesaurus",
+                QUERY_EXPANDER_ENV,
+                e
+            );
+            expand_query(query)
+        }
+    }
+}
+
+fn run_expander(command: &str, query: &str) -> anyhow::Result<Vec<String>> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    let mut child = shell.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(query.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("exited with {}", output.status);
+    }
+
+    let mut variants: Vec<String> = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let line = line.trim();
+        if !line.is_empty() && line != query && !variants.iter().any(|v| v == line) {
+            variants.push(line.to_string());
+        }
+    }
+    variants.truncate(MAX_VARIANTS);
+    Ok(variants)
+}
+
+/// `options` with the query variants added as extra patterns when `--expand-query` is on
+pub(crate) fn expanded_options(options: &SearchOptions) -> Cow<'_, SearchOptions> {
+    if !options.expand_query {
+        return Cow::Borrowed(options);
+    }
+    let mut expanded = options.clone();
+    for pattern in options.patterns() {
+        for variant in query_variants(pattern) {
+            if !expanded.patterns().any(|existing| existing == variant) {
+                expanded.extra_patterns.push(variant);
+            }
+        }
+    }
+    tracing::info!("Expanded query: {:?}", expanded.extra_patterns);
+    Cow::Owned(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_query_with_thesaurus() {
+        assert_eq!(
+            expand_query("auth flow"),
+            vec!["authentication flow", "login flow", "credentials flow"]
+        );
+        // Matching ignores case and punctuation around the term
+        assert_eq!(expand_query("DB?")[0], "database");
+        assert!(expand_query("render widget").is_empty());
+        assert!(expand_query("auth db config err retry").len() <= MAX_VARIANTS);
+    }
+
+    #[test]
+    fn test_expanded_options_adds_patterns() {
+        let options = SearchOptions {
+            query: "retry".to_string(),
+            ..Default::default()
+        };
+        assert!(expanded_options(&options).extra_patterns.is_empty());
+
+        let options = SearchOptions {
+            expand_query: true,
+            extra_patterns: vec!["backoff".to_string()],
+            ..options
+        };
+        let expanded = expanded_options(&options);
+        let patterns: Vec<_> = expanded.patterns().collect();
+        assert_eq!(patterns, vec!["retry", "backoff", "reattempt"]);
+    }
+}
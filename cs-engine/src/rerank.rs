@@ -0,0 +1,213 @@
+// Cross-encoder reranking (`--rerank`)
+// The first stage (embedding similarity or RRF) fetches a wider candidate pool; the cross-encoder
+// reads each candidate chunk together with the query and re-scores it before the top_k cut
+
+use cs_core::{SearchOptions, SearchResult};
+
+use super::{
+    Deadline, SearchProgressCallback, deadline_passed, explain, extract_content_from_span,
+};
+
+/// First-stage results re-scored when `--rerank-candidates` is not given
+pub(crate) const DEFAULT_RERANK_CANDIDATES: usize = 50;
+
+/// Short reranker names accepted by `--rerank-model`
+pub const RERANK_MODEL_ALIASES: &[&str] =
+    &["bge", "bge-base", "bge-v2-m3", "jina", "jina-v1", "jina-v2"];
+
+/// Map short reranker aliases to full model names
+pub(crate) fn rerank_model_name(alias: Option<&str>) -> &str {
+    match alias {
+        // Jina reranker aliases (v2 is now default - multilingual, better performance)
+        Some("jina") | Some("jina-v2") => "jina-reranker-v2-base-multilingual",
+        Some("jina-v1") => "jina-reranker-v1-turbo-en",
+        // TODO: Uncomment when jina-reranker-v3 is available in Jina API
+        // Some("jina-v3") => "jina-reranker-v3",
+
+        // BGE reranker aliases
+        Some("bge") | Some("bge-base") => "BAAI/bge-reranker-base",
+        Some("bge-v2-m3") => "rozgo/bge-reranker-v2-m3",
+
+        // Pass through full model names
+        Some(name) => name,
+
+        // Default to jina-v2 (best available multilingual model)
+        None => "jina-reranker-v2-base-multilingual",
+    }
+}
+
+fn rerank_candidates(options: &SearchOptions) -> usize {
+    options
+        .rerank_candidates
+        .unwrap_or(DEFAULT_RERANK_CANDIDATES)
+}
+
+/// How many first-stage results to keep so reranking can promote ones below the top_k cut
+pub(crate) fn candidate_limit(options: &SearchOptions) -> Option<usize> {
+    match options.top_k {
+        Some(top_k) if options.rerank => Some(top_k.max(rerank_candidates(options))),
+        top_k => top_k,
+    }
+}
+
+/// Re-score the leading candidates with the cross-encoder, re-sort and apply top_k
+///
+/// Returns true when the deadline cut reranking short. A reranker that cannot be loaded or
+/// fails keeps the first-stage order, so `--rerank` never fails a search.
+pub(crate) async fn rerank_results(
+    options: &SearchOptions,
+    results: &mut Vec<SearchResult>,
+    progress_callback: Option<&SearchProgressCallback>,
+    deadline: Deadline<'_>,
+) -> bool {
+    let mut partial = false;
+    if options.rerank && !results.is_empty() {
+        if deadline_passed(deadline) {
+            tracing::info!("Skipping reranking: search timeout reached");
+            partial = true;
+        } else {
+            let model_name = rerank_model_name(options.rerank_model.as_deref());
+            let count = results.len().min(rerank_candidates(options));
+            if let Some(callback) = progress_callback {
+                callback(&format!(
+                    "Reranking {} results with {}...",
+                    count, model_name
+                ));
+            }
+            let started = std::time::Instant::now();
+            if let Err(e) = rerank_leading(options, model_name, &mut results[..count]).await {
+                tracing::warn!("Reranking failed, using original scores: {}", e);
+            }
+            if let Some(stats) = &options.stats {
+                stats.record_phase("rerank", started.elapsed());
+            }
+        }
+    }
+
+    if let Some(top_k) = options.top_k {
+        results.truncate(top_k);
+    }
+    partial
+}
+
+async fn rerank_leading(
+    options: &SearchOptions,
+    model_name: &str,
+    candidates: &mut [SearchResult],
+) -> anyhow::Result<()> {
+    // The cross-encoder reads whole chunks, not the three-line preview
+    let mut documents = Vec::with_capacity(candidates.len());
+    for result in candidates.iter() {
+        let text = extract_content_from_span(&result.file, &result.span)
+            .await
+            .unwrap_or_else(|_| result.preview.clone());
+        documents.push(text);
+    }
+
+    let mut reranker = cs_embed::create_reranker(Some(model_name))?;
+    let query = options.patterns().collect::<Vec<_>>().join("\n");
+    let rerank_results = reranker.rerank(&query, &documents)?;
+
+    let mut rerank_scores = vec![None; candidates.len()];
+    for rerank_result in rerank_results {
+        if let Some(score) = rerank_scores.get_mut(rerank_result.index) {
+            *score = Some(rerank_result.score);
+        }
+    }
+    let rerank_scores: Vec<f32> = rerank_scores
+        .into_iter()
+        .zip(candidates.iter())
+        .map(|(score, result)| score.unwrap_or(result.score))
+        .collect();
+
+    let first_stage: Vec<f32> = candidates.iter().map(|result| result.score).collect();
+    let scores = blend_scores(&first_stage, &rerank_scores, options.rerank_blend);
+    for ((result, score), before) in candidates.iter_mut().zip(scores).zip(first_stage) {
+        result.score = score;
+        if options.explain {
+            explain::record_rerank(result, before);
+        }
+    }
+    candidates.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(())
+}
+
+/// Cross-encoder scores as is, or with a `--rerank-blend` weight, a mix of both stages after
+/// min-max normalizing each over the candidates (the two stages score on different scales)
+fn blend_scores(first_stage: &[f32], rerank: &[f32], weight: Option<f32>) -> Vec<f32> {
+    let Some(weight) = weight.filter(|weight| *weight < 1.0) else {
+        return rerank.to_vec();
+    };
+    let first_stage = min_max_normalize(first_stage);
+    let rerank = min_max_normalize(rerank);
+    first_stage
+        .iter()
+        .zip(&rerank)
+        .map(|(first, rerank)| weight * rerank + (1.0 - weight) * first)
+        .collect()
+}
+
+fn min_max_normalize(scores: &[f32]) -> Vec<f32> {
+    let min = scores.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if max - min <= f32::EPSILON {
+        return vec![1.0; scores.len()];
+    }
+    scores
+        .iter()
+        .map(|score| (score - min) / (max - min))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_limit_widens_only_when_reranking() {
+        let options = SearchOptions {
+            top_k: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(candidate_limit(&options), Some(10));
+
+        let options = SearchOptions {
+            rerank: true,
+            ..options
+        };
+        assert_eq!(candidate_limit(&options), Some(DEFAULT_RERANK_CANDIDATES));
+
+        let options = SearchOptions {
+            rerank_candidates: Some(5),
+            ..options
+        };
+        assert_eq!(candidate_limit(&options), Some(10));
+    }
+
+    #[test]
+    fn test_blend_scores() {
+        let first_stage = [0.9, 0.7, 0.5];
+        let rerank = [-2.0, 4.0, 1.0];
+
+        // Default: cross-encoder scores only
+        assert_eq!(blend_scores(&first_stage, &rerank, None), rerank.to_vec());
+        assert_eq!(
+            blend_scores(&first_stage, &rerank, Some(1.0)),
+            rerank.to_vec()
+        );
+
+        // Even mix of both stages, each normalized to 0..1
+        let blended = blend_scores(&first_stage, &rerank, Some(0.5));
+        assert!((blended[0] - 0.5).abs() < 1e-6);
+        assert!((blended[1] - 0.75).abs() < 1e-6);
+        assert!((blended[2] - 0.25).abs() < 1e-6);
+
+        // Weight 0 keeps the first-stage order
+        let first_only = blend_scores(&first_stage, &rerank, Some(0.0));
+        assert!(first_only[0] > first_only[1] && first_only[1] > first_only[2]);
+    }
+}
@@ -0,0 +1,158 @@
+//! Indexes kept in memory by `cs --daemon`: the doc index and every sidecar decoded so far, per
+//! index root, so repeated semantic searches skip reading and decoding them. What is kept for a
+//! root is dropped once its index epoch (`cs_index::index_epoch`) changes.
+
+use anyhow::Result;
+use cs_ann::DocIndex;
+use cs_index::IndexEntry;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+/// Set by `keep_indexes_resident`; otherwise every search loads the index anew
+static RESIDENT: OnceLock<ResidentIndexes> = OnceLock::new();
+
+/// Keep every index this process searches in memory from now on
+pub(crate) fn keep_indexes_resident() {
+    let _ = RESIDENT.set(ResidentIndexes::default());
+}
+
+/// The resident copy of the index at `index_root`, when indexes are kept resident
+pub(crate) fn index(index_root: &Path) -> Option<Arc<ResidentIndex>> {
+    RESIDENT.get()?.index(index_root)
+}
+
+#[derive(Default)]
+pub(crate) struct ResidentIndexes {
+    /// By canonical index root
+    indexes: Mutex<HashMap<PathBuf, Arc<ResidentIndex>>>,
+}
+
+impl ResidentIndexes {
+    /// The index at `index_root` as of its current epoch, empty after the index changed;
+    /// `None` when there is no index
+    pub(crate) fn index(&self, index_root: &Path) -> Option<Arc<ResidentIndex>> {
+        let epoch = cs_index::index_epoch(index_root)?;
+        let root = index_root
+            .canonicalize()
+            .unwrap_or_else(|_| index_root.to_path_buf());
+        let mut indexes = self.indexes.lock().unwrap_or_else(PoisonError::into_inner);
+        let index = indexes
+            .entry(root)
+            .and_modify(|index| {
+                if index.epoch != epoch {
+                    *index = Arc::new(ResidentIndex::new(epoch));
+                }
+            })
+            .or_insert_with(|| Arc::new(ResidentIndex::new(epoch)));
+        Some(index.clone())
+    }
+}
+
+/// One index as of one epoch, filled in as searches read it
+pub(crate) struct ResidentIndex {
+    epoch: u64,
+    /// Loaded on first use; looked for again while the index has none
+    doc_index: Mutex<Option<Arc<DocIndex>>>,
+    /// Decoded sidecars by path inside the index directory
+    sidecars: Mutex<HashMap<PathBuf, Arc<IndexEntry>>>,
+}
+
+impl ResidentIndex {
+    fn new(epoch: u64) -> Self {
+        Self {
+            epoch,
+            doc_index: Mutex::default(),
+            sidecars: Mutex::default(),
+        }
+    }
+
+    /// The doc index of the index at `index_root` (`cs_index::doc_index::load`)
+    pub(crate) fn doc_index(&self, index_root: &Path) -> Option<Arc<DocIndex>> {
+        let mut doc_index = self
+            .doc_index
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if doc_index.is_none() {
+            *doc_index = cs_index::doc_index::load(index_root).map(Arc::new);
+        }
+        doc_index.clone()
+    }
+
+    /// The sidecar at `path` in `index_dir`, decoded on first use
+    pub(crate) fn sidecar(&self, path: &Path, index_dir: &Path) -> Result<Arc<IndexEntry>> {
+        let key = path.strip_prefix(index_dir).unwrap_or(path).to_path_buf();
+        if let Some(entry) = self
+            .sidecars
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+        {
+            return Ok(entry.clone());
+        }
+        // Decoded outside the lock, so sidecars of one batch decode in parallel
+        let entry = Arc::new(cs_index::load_index_entry(path)?);
+        self.sidecars
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, entry.clone());
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cs_core::{FileMetadata, get_sidecar_path};
+    use cs_index::{IndexManifest, save_index_entry};
+
+    fn write_sidecar(root: &Path, manifest: &mut IndexManifest, name: &str) -> PathBuf {
+        let metadata = FileMetadata {
+            path: root.join(name),
+            hash: name.to_string(),
+            last_modified: 0,
+            size: 0,
+        };
+        let entry = IndexEntry {
+            metadata: metadata.clone(),
+            chunks: Vec::new(),
+            embedding_info: None,
+            sparse: None,
+            withheld: None,
+        };
+        let sidecar = get_sidecar_path(root, &root.join(name));
+        save_index_entry(&sidecar, &entry).unwrap();
+        manifest.files.insert(PathBuf::from(name), metadata);
+        let manifest = serde_json::to_vec(manifest).unwrap();
+        std::fs::write(root.join(".cs").join("manifest.json"), manifest).unwrap();
+        sidecar
+    }
+
+    #[test]
+    fn test_resident_index_follows_the_index_epoch() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let index_dir = root.join(".cs");
+        let indexes = ResidentIndexes::default();
+        assert!(indexes.index(root).is_none());
+
+        let mut manifest = IndexManifest::default();
+        let sidecar = write_sidecar(root, &mut manifest, "lib.rs");
+        let index = indexes.index(root).unwrap();
+        assert_eq!(
+            index.sidecar(&sidecar, &index_dir).unwrap().metadata.hash,
+            "lib.rs"
+        );
+        assert!(index.doc_index(root).is_none());
+
+        // Kept in memory: later searches of the same index no longer read the sidecar
+        std::fs::remove_file(&sidecar).unwrap();
+        let index = indexes.index(root).unwrap();
+        assert!(index.sidecar(&sidecar, &index_dir).is_ok());
+
+        // A changed index is read again
+        write_sidecar(root, &mut manifest, "main.rs");
+        let index = indexes.index(root).unwrap();
+        assert!(index.sidecar(&sidecar, &index_dir).is_err());
+    }
+}
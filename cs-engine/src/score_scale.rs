@@ -0,0 +1,108 @@
+// Calibrated scores (`--threshold`, `--scores`; `--scores-raw` for the raw ones)
+// Each mode ranks on its own scale, so the same `--threshold` used to mean different things in
+// each. Scores are mapped to 0..=1, where 1 is the best a result can do in its mode:
+// - semantic: cosine similarity (after sparse fusion and `--not`), clamped to 0..=1
+// - lexical: BM25 divided by the best BM25 score of the search, so the top hit scores 1
+// - hybrid: the RRF sum divided by that of a result ranked first in every list that found
+//   anything, so 1 is the top hit of every list and 0.5 the top hit of one of two lists
+// The score on the mode's own scale is kept as `signals.raw_score`
+
+use cs_core::{SearchOptions, SearchResult};
+
+/// Calibrated semantic score of a cosine similarity
+pub(crate) fn semantic(similarity: f32) -> f32 {
+    similarity.clamp(0.0, 1.0)
+}
+
+/// Calibrated lexical score of a BM25 score, given the best BM25 score of the search
+pub(crate) fn lexical(bm25: f32, best: f32) -> f32 {
+    if best > 0.0 {
+        (bm25 / best).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Calibrated hybrid score of an RRF sum, given the lists that were fused
+pub(crate) fn hybrid(
+    rrf: f32,
+    options: &SearchOptions,
+    rankings: &[(&str, &[SearchResult])],
+) -> f32 {
+    let best: f32 = rankings
+        .iter()
+        .filter(|(_, ranking)| !ranking.is_empty())
+        .map(|(list, _)| options.fusion_weights.weight(list) / 61.0)
+        .sum();
+    if best > 0.0 {
+        (rrf / best).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Keep `raw` as the result's raw score and give it the calibrated `score`
+pub(crate) fn set(result: &mut SearchResult, raw: f32, score: f32) {
+    result
+        .signals
+        .get_or_insert_with(Default::default)
+        .raw_score = Some(raw);
+    result.score = score;
+}
+
+/// Show raw scores in place of calibrated ones (`--scores-raw`); results without a raw score,
+/// such as regex matches, keep theirs
+pub(crate) fn use_raw_scores(results: &mut [SearchResult]) {
+    for result in results {
+        if let Some(raw) = result
+            .signals
+            .as_ref()
+            .and_then(|signals| signals.raw_score)
+        {
+            result.score = raw;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores_share_one_scale() {
+        assert_eq!(semantic(0.72), 0.72);
+        assert_eq!(semantic(-0.1), 0.0);
+
+        assert_eq!(lexical(12.0, 12.0), 1.0);
+        assert_eq!(lexical(3.0, 12.0), 0.25);
+        assert_eq!(lexical(3.0, 0.0), 0.0);
+
+        let options = SearchOptions::default();
+        let hit = SearchResult {
+            file: "lib.rs".into(),
+            span: cs_core::Span::new(0, 1, 1, 1).unwrap(),
+            score: 1.0,
+            preview: String::new(),
+            lang: None,
+            symbol: None,
+            chunk_hash: None,
+            index_epoch: None,
+            signals: None,
+            explanation: None,
+        };
+        let found = [hit.clone()];
+        let rankings: [(&str, &[SearchResult]); 2] = [("regex", &found), ("semantic", &found)];
+        // First in both lists, first in one of them, and a regex list that found nothing
+        assert!((hybrid(2.0 / 61.0, &options, &rankings) - 1.0).abs() < 1e-6);
+        assert!((hybrid(1.0 / 61.0, &options, &rankings) - 0.5).abs() < 1e-6);
+        let semantic_only: [(&str, &[SearchResult]); 2] = [("regex", &[]), ("semantic", &found)];
+        assert!((hybrid(1.0 / 61.0, &options, &semantic_only) - 1.0).abs() < 1e-6);
+
+        let mut results = vec![hit.clone(), hit];
+        set(&mut results[0], 9.5, 0.5);
+        assert_eq!(results[0].score, 0.5);
+        use_raw_scores(&mut results);
+        assert_eq!(results[0].score, 9.5);
+        assert_eq!(results[1].score, 1.0);
+    }
+}
@@ -1,12 +1,27 @@
 use anyhow::Result;
-use cs_core::{CcError, SearchOptions, SearchResult};
+use cs_core::explain::{ScoreExplanation, StrideMatch};
+use cs_core::{CcError, SearchOptions, SearchResult, Span, StridePooling};
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap};
 use std::path::Path;
 use walkdir::WalkDir;
 
 use super::{
-    SearchProgressCallback, extract_content_from_span, find_nearest_index_root,
-    resolve_model_from_root,
+    Deadline, ResolvedModel, SearchProgressCallback, chunk_filter, deadline_passed, explain,
+    extract_content_from_span, find_nearest_index_root, mmr, negative, quantized::QuantizedChunks,
+    rerank, resolve_model_from_root, score_scale, score_until, search_deadline, signals,
+    sparse_fusion, stride_pooling, test_filter,
 };
+use crate::resident::ResidentIndex;
+
+/// Sidecar bytes read per parallel batch while loading embeddings
+const SIDECAR_BATCH_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Chunks scored between timeout checks
+const SCORE_BLOCK_CHUNKS: usize = 64 * 1024;
+
+/// Candidates per result taken from the doc index; their files' sidecars are read and scored
+const DOC_CANDIDATE_FACTOR: usize = 4;
 
 /// New semantic search implementation using span-based storage
 pub async fn semantic_search_v3(options: &SearchOptions) -> Result<cs_core::SearchResults> {
@@ -17,14 +32,17 @@ pub async fn semantic_search_v3_with_progress(
     options: &SearchOptions,
     progress_callback: Option<SearchProgressCallback>,
 ) -> Result<cs_core::SearchResults> {
-    // Find the index root
-    let index_root = find_nearest_index_root(&options.path).unwrap_or_else(|| {
-        if options.path.is_file() {
-            options.path.parent().unwrap_or(&options.path).to_path_buf()
-        } else {
-            options.path.clone()
-        }
-    });
+    semantic_search_v3_until(options, progress_callback, search_deadline(options, None)).await
+}
+
+/// Semantic search that stops loading sidecars, scoring chunks and reranking once `deadline`
+/// passes, ranking whatever was scored so far and flagging the results as partial.
+pub(crate) async fn semantic_search_v3_until(
+    options: &SearchOptions,
+    progress_callback: Option<SearchProgressCallback>,
+    deadline: Deadline<'_>,
+) -> Result<cs_core::SearchResults> {
+    let index_root = index_root(&options.path);
 
     let index_dir = index_root.join(".cs");
     if !index_dir.exists() {
@@ -34,34 +52,147 @@ pub async fn semantic_search_v3_with_progress(
         .into());
     }
 
-    if let Some(ref callback) = progress_callback {
-        callback("Loading embeddings from sidecar files...");
-    }
+    // What the daemon already read of this index (`cs --daemon`)
+    let resident = super::resident::index(&index_root);
 
     // Collect all sidecar files and their embeddings
     let mut file_chunks: Vec<(std::path::PathBuf, cs_index::ChunkEntry)> = Vec::new();
+    // BM25 term vectors parallel to `file_chunks`, for indexes built with `--sparse`
+    let mut file_sparse: Vec<Option<cs_index::sparse::SparseVector>> = Vec::new();
+    // Stride positions of chunks from split-up large chunks, kept for `--explain`, and the
+    // span of the whole chunk each stride belongs to, for stride pooling
+    let mut stride_matches = HashMap::new();
+    let mut stride_spans = HashMap::new();
+    // Model and dimensions behind the loaded embeddings, checked against the query model
+    let mut sidecar_models = BTreeSet::new();
+    let mut partial = false;
+    // Int8 codes standing in for the embeddings, when `cs.toml` configures quantization
+    let mut quantized = QuantizedChunks::configured(&index_root);
+    // Only chunks under the searched file or directory are ranked, so top_k fills from a
+    // small subtree instead of being filtered down from the whole index
+    let scope = search_scope(&options.path, &index_root);
+    // With a result limit, the doc index ranks the chunks in scope on their int8 codes and
+    // only the sidecars of the best candidates are read; that needs the query embedded first
+    let doc_limit = doc_index_limit(options);
+    let doc_index = doc_limit.and_then(|_| match &resident {
+        Some(resident) => resident.doc_index(&index_root),
+        None => cs_index::doc_index::load(&index_root).map(std::sync::Arc::new),
+    });
+    let mut embedded = doc_index
+        .as_ref()
+        .map(|_| embed_queries(&index_root, options, &progress_callback))
+        .transpose()?;
+    let started = std::time::Instant::now();
+    let candidates = match (&doc_index, doc_limit, &embedded) {
+        (Some(doc_index), Some(limit), Some(embedded)) => candidate_sidecars(
+            doc_index,
+            &index_root,
+            scope.as_deref(),
+            options,
+            &embedded.queries,
+            limit,
+        ),
+        _ => None,
+    };
+    if candidates.is_some()
+        && let Some(stats) = &options.stats
+    {
+        stats.record_phase("doc index", started.elapsed());
+    }
+
+    if let Some(ref callback) = progress_callback {
+        callback("Loading embeddings from sidecar files...");
+    }
 
-    for entry in WalkDir::new(&index_dir) {
-        let entry = entry?;
-        if entry.file_type().is_file() {
+    // Sidecars are read and decoded in parallel, a batch at a time so the full-precision
+    // embeddings in flight stay within SIDECAR_BATCH_BYTES before they are merged (and
+    // quantized, when configured)
+    let started = std::time::Instant::now();
+    let mut sidecars = Vec::new();
+    if let Some(candidates) = candidates {
+        for path in candidates {
+            let size = path.metadata().map_or(0, |metadata| metadata.len());
+            sidecars.push((path, size));
+        }
+    } else {
+        // Without the doc index, only the sidecars under the scope are read
+        let sidecar_root = scope
+            .as_deref()
+            .and_then(|target| scope_sidecar_root(target, &index_root))
+            .filter(|root| root.exists())
+            .unwrap_or_else(|| index_dir.clone());
+        for entry in WalkDir::new(&sidecar_root) {
+            let entry = entry?;
             let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("cs") {
-                // Load the sidecar file
-                if let Ok(index_entry) = cs_index::load_index_entry(path) {
-                    let original_file = reconstruct_original_path(path, &index_dir, &index_root);
-                    if let Some(original_file) = original_file {
-                        if !super::path_matches_include(&original_file, &options.include_patterns) {
-                            continue;
-                        }
-                        for chunk in index_entry.chunks {
-                            if chunk.embedding.is_some() {
-                                file_chunks.push((original_file.clone(), chunk));
-                            }
-                        }
-                    }
+            if entry.file_type().is_file()
+                && path.extension().and_then(|s| s.to_str()) == Some("cs")
+            {
+                let size = entry.metadata().map_or(0, |metadata| metadata.len());
+                sidecars.push((path.to_path_buf(), size));
+            }
+        }
+    }
+
+    for batch in sidecar_batches(&sidecars) {
+        if deadline_passed(deadline) {
+            partial = true;
+            break;
+        }
+        let loaded: Vec<LoadedSidecar> = batch
+            .par_iter()
+            .filter_map(|(path, _)| {
+                if deadline_passed(deadline) {
+                    return None;
+                }
+                load_sidecar(
+                    path,
+                    &index_dir,
+                    &index_root,
+                    scope.as_deref(),
+                    options,
+                    resident.as_deref(),
+                )
+            })
+            .collect();
+        if loaded.len() < batch.len() && deadline_passed(deadline) {
+            partial = true;
+        }
+        if let Some(stats) = &options.stats {
+            stats.add_files_scanned(loaded.len());
+        }
+
+        for sidecar in loaded {
+            if let Some(model) = sidecar.model {
+                sidecar_models.insert(model);
+            }
+            for (mut chunk, sparse, stride) in sidecar.chunks {
+                if let Some((stride, logical)) = stride {
+                    let key = (sidecar.file.clone(), chunk.span.byte_start);
+                    stride_matches.insert(key.clone(), stride);
+                    stride_spans.insert(key, logical);
+                }
+                if let Some(quantized) = quantized.as_mut() {
+                    quantized.quantize(file_chunks.len(), &mut chunk);
                 }
+                file_chunks.push((sidecar.file.clone(), chunk));
+                file_sparse.push(sparse);
             }
         }
+        if partial {
+            break;
+        }
+    }
+    if let Some(stats) = &options.stats {
+        stats.record_phase("load sidecars", started.elapsed());
+    }
+
+    if file_chunks.is_empty() && partial {
+        return Ok(cs_core::SearchResults {
+            matches: Vec::new(),
+            closest_below_threshold: None,
+            partial,
+            lossy_files: 0,
+        });
     }
 
     if file_chunks.is_empty() {
@@ -78,89 +209,154 @@ pub async fn semantic_search_v3_with_progress(
         ));
     }
 
-    // Create embedder and embed the query
-    if let Some(ref callback) = progress_callback {
-        callback("Loading embedding model...");
-    }
-
-    let resolved_model = resolve_model_from_root(&index_root, options.embedding_model.as_deref())?;
-    if let Some(ref callback) = progress_callback {
-        callback(&format!(
-            "Using embedding model {} ({} dims)",
-            resolved_model.alias, resolved_model.dimensions
-        ));
-    }
-
-    let mut embedder = cs_embed::create_embedder(Some(resolved_model.canonical_name.as_str()))?;
-    let query_embeddings = embedder.embed(std::slice::from_ref(&options.query))?;
+    let QueryEmbeddings {
+        model: resolved_model,
+        dimensions,
+        queries: query_embeddings,
+        negatives: negative_embeddings,
+    } = match embedded.take() {
+        Some(embedded) => embedded,
+        None => embed_queries(&index_root, options, &progress_callback)?,
+    };
+    verify_sidecar_models(
+        &sidecar_models,
+        &resolved_model,
+        options.embedding_model.as_deref(),
+        dimensions,
+    )?;
 
     if query_embeddings.is_empty() {
         return Ok(cs_core::SearchResults {
             matches: Vec::new(),
             closest_below_threshold: None,
+            partial,
+            lossy_files: 0,
         });
     }
 
-    let query_embedding = &query_embeddings[0];
-
     if let Some(ref callback) = progress_callback {
         callback("Computing similarity scores...");
     }
 
-    // Compute similarities
-    let mut similarities: Vec<(f32, &std::path::PathBuf, &cs_index::ChunkEntry)> = Vec::new();
+    // Term-vector scores fused into the cosine similarity, when the index stores them
+    let mut sparse_scores = sparse_fusion::sparse_scores(options, &file_sparse);
+    // A quantized scan narrows the chunks to candidates that get exact scores below
+    if let Some(quantized) = quantized {
+        quantized.restore_candidates(
+            options,
+            &index_root,
+            &query_embeddings,
+            &mut file_chunks,
+            &mut file_sparse,
+            &mut sparse_scores,
+        )?;
+    }
+
+    // Compute similarities; with several patterns a chunk scores as its best match
+    let sparse_weight = sparse_fusion::sparse_weight(options);
+    let (mut scored, timed_out) = score_until(
+        file_chunks.len(),
+        SCORE_BLOCK_CHUNKS,
+        deadline,
+        |position| {
+            let embedding = file_chunks[position].1.embedding.as_ref()?;
+            let cosine = query_embeddings
+                .iter()
+                .map(|query_embedding| cosine_similarity(query_embedding, embedding))
+                .fold(f32::NEG_INFINITY, f32::max);
+            let similarity = match &sparse_scores {
+                Some(scores) => sparse_fusion::fuse(cosine, scores[position], sparse_weight),
+                None => cosine,
+            };
+            Some((similarity, cosine, position))
+        },
+    );
+    partial |= timed_out;
+    if let Some(stats) = &options.stats {
+        stats.add_chunks_scored(scored.len());
+    }
 
-    for (file_path, chunk) in &file_chunks {
-        if let Some(ref embedding) = chunk.embedding {
-            let similarity = cosine_similarity(query_embedding, embedding);
-            similarities.push((similarity, file_path, chunk));
+    // `--not`: lower the scores of chunks resembling a negative phrase
+    let negatives = if negative_embeddings.is_empty() {
+        Vec::new()
+    } else {
+        let similarities: Vec<f32> = scored
+            .par_iter()
+            .map(|(_, _, position)| {
+                let embedding = file_chunks[*position]
+                    .1
+                    .embedding
+                    .as_deref()
+                    .unwrap_or_default();
+                negative::similarity(&negative_embeddings, embedding)
+            })
+            .collect();
+        let negatives = negative::normalize(&similarities);
+        let weight = negative::negative_weight(options);
+        for ((similarity, _, _), negative) in scored.iter_mut().zip(&negatives) {
+            *similarity = negative::penalize(*similarity, *negative, weight);
+        }
+        negatives
+    };
+
+    let mut similarities: Vec<(f32, &std::path::PathBuf, &cs_index::ChunkEntry)> =
+        Vec::with_capacity(scored.len());
+    // Cosine and sparse parts of adjusted scores, and `--not` closeness, kept for `--explain`
+    let mut fused_parts = HashMap::new();
+    let mut negative_parts = HashMap::new();
+    for (index, (similarity, cosine, position)) in scored.into_iter().enumerate() {
+        let (file_path, chunk) = &file_chunks[position];
+        if options.explain && (sparse_scores.is_some() || !negatives.is_empty()) {
+            let sparse = sparse_scores.as_ref().map(|scores| scores[position]);
+            fused_parts.insert((file_path.clone(), chunk.span.byte_start), (cosine, sparse));
+        }
+        if let Some(negative) = negatives.get(index).filter(|_| options.explain) {
+            negative_parts.insert((file_path.clone(), chunk.span.byte_start), *negative);
         }
+        similarities.push((similarity, file_path, chunk));
     }
 
     // Sort by similarity (highest first)
     similarities.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
 
+    // One result per long chunk rather than one per stride, spanning all of its strides
+    let mut similarities = stride_pooling::pool(
+        similarities,
+        options.stride_pooling,
+        |(similarity, _, _)| similarity,
+        |(_, file_path, chunk)| {
+            stride_spans
+                .get(&((*file_path).clone(), chunk.span.byte_start))
+                .map(|logical: &Span| (*file_path, logical.byte_start))
+        },
+    );
+
+    // Reranking draws from a wider pool and cuts to top_k afterwards
+    let limit = rerank::candidate_limit(options).unwrap_or(similarities.len());
+
+    if let Some(diversity) = options.diversity.filter(|diversity| *diversity > 0.0) {
+        diversify(&mut similarities, options, limit, diversity);
+    }
+
     // Apply threshold and top_k filtering
     let mut results = Vec::new();
     let mut closest_below_threshold: Option<SearchResult> = None;
-    let limit = options.top_k.unwrap_or(similarities.len());
 
     for (similarity, file_path, chunk) in similarities.into_iter().take(limit) {
-        let is_below_threshold = options
-            .threshold
-            .is_some_and(|threshold| similarity < threshold);
-
-        // Check if we're filtering by a specific file or directory (apply to both above/below threshold)
-        let passes_path_filter = if options.path.is_file() {
-            let target_file = options
-                .path
-                .canonicalize()
-                .unwrap_or_else(|_| options.path.clone());
-            let result_file = file_path
-                .canonicalize()
-                .unwrap_or_else(|_| file_path.clone());
-            result_file == target_file
-        } else if options.path != Path::new(".") {
-            // Filter by directory path - only include files within the specified directory
-            let target_dir = options
-                .path
-                .canonicalize()
-                .unwrap_or_else(|_| options.path.clone());
-            let result_file = file_path
-                .canonicalize()
-                .unwrap_or_else(|_| file_path.clone());
-            result_file.starts_with(&target_dir)
-        } else {
-            true
+        let score = score_scale::semantic(similarity);
+        let is_below_threshold = options.threshold.is_some_and(|threshold| score < threshold);
+
+        // A pooled stride stands for its whole chunk
+        let span = match options.stride_pooling {
+            StridePooling::None => &chunk.span,
+            _ => stride_spans
+                .get(&(file_path.clone(), chunk.span.byte_start))
+                .unwrap_or(&chunk.span),
         };
 
-        if !passes_path_filter {
-            continue;
-        }
-
         // Extract content from the file using the span, skip if file doesn't exist
         let content = if options.full_section {
-            match extract_content_from_span(file_path, &chunk.span).await {
+            match extract_content_from_span(file_path, span).await {
                 Ok(content) => content,
                 Err(_) => {
                     // Skip files that no longer exist (stale index entries)
@@ -168,7 +364,7 @@ pub async fn semantic_search_v3_with_progress(
                 }
             }
         } else {
-            match extract_content_from_span(file_path, &chunk.span).await {
+            match extract_content_from_span(file_path, span).await {
                 Ok(full_content) => {
                     // Take first 3 lines for preview
                     full_content.lines().take(3).collect::<Vec<_>>().join("\n")
@@ -180,16 +376,33 @@ pub async fn semantic_search_v3_with_progress(
             }
         };
 
-        let search_result = SearchResult {
+        let mut search_result = SearchResult {
             file: file_path.clone(),
-            span: chunk.span.clone(),
-            score: similarity,
+            span: span.clone(),
+            score,
             preview: content,
             lang: cs_core::Language::from_path(file_path),
             symbol: None,
             chunk_hash: None,
             index_epoch: None,
+            signals: None,
+            explanation: options.explain.then(|| {
+                let parts = fused_parts.get(&(file_path.clone(), chunk.span.byte_start));
+                ScoreExplanation {
+                    cosine: Some(parts.map_or(similarity, |(cosine, _)| *cosine)),
+                    sparse: parts.and_then(|(_, sparse)| *sparse),
+                    negative: negative_parts
+                        .get(&(file_path.clone(), chunk.span.byte_start))
+                        .copied(),
+                    chunk_type: chunk.chunk_type_name().map(str::to_string),
+                    stride: stride_matches
+                        .get(&(file_path.clone(), chunk.span.byte_start))
+                        .copied(),
+                    ..Default::default()
+                }
+            }),
         };
+        score_scale::set(&mut search_result, similarity, score);
 
         if is_below_threshold {
             // Track the closest below-threshold result (first one since sorted by highest first)
@@ -202,86 +415,240 @@ pub async fn semantic_search_v3_with_progress(
         }
     }
 
-    // Apply reranking if enabled
-    if options.rerank && !results.is_empty() {
-        if let Some(ref callback) = progress_callback {
-            callback("Reranking results for improved relevance...");
-        }
+    signals::record_vec_ranks(&mut results);
 
-        // Map short aliases to full model names
-        let rerank_model_name = match options.rerank_model.as_deref() {
-            // Jina reranker aliases (v2 is now default - multilingual, better performance)
-            Some("jina") | Some("jina-v2") => Some("jina-reranker-v2-base-multilingual"),
-            Some("jina-v1") => Some("jina-reranker-v1-turbo-en"),
-            // TODO: Uncomment when jina-reranker-v3 is available in Jina API
-            // Some("jina-v3") => Some("jina-reranker-v3"),
+    // Apply reranking if enabled (and there is still time for it)
+    partial |=
+        rerank::rerank_results(options, &mut results, progress_callback.as_ref(), deadline).await;
 
-            // BGE reranker aliases
-            Some("bge") | Some("bge-base") => Some("BAAI/bge-reranker-base"),
-            Some("bge-v2-m3") => Some("rozgo/bge-reranker-v2-m3"),
+    Ok(cs_core::SearchResults {
+        matches: results,
+        closest_below_threshold,
+        partial,
+        lossy_files: 0,
+    })
+}
 
-            // Pass through full model names
-            Some(name) => Some(name),
+/// A stride's position and the span of the whole chunk it belongs to
+type StridePlacement = (StrideMatch, Span);
+
+/// Chunks kept from one sidecar, with their term vectors and stride placements
+struct LoadedSidecar {
+    file: std::path::PathBuf,
+    chunks: Vec<(
+        cs_index::ChunkEntry,
+        Option<cs_index::sparse::SparseVector>,
+        Option<StridePlacement>,
+    )>,
+    /// Model and dimensions of the embeddings
+    model: Option<(Option<String>, usize)>,
+}
 
-            // Default to jina-v2 (best available multilingual model)
-            None => Some("jina-reranker-v2-base-multilingual"),
-        };
+/// The query model with the embeddings of the patterns and of the `--not` phrases
+struct QueryEmbeddings {
+    model: ResolvedModel,
+    /// Dimensions of the model's embeddings
+    dimensions: usize,
+    queries: Vec<Vec<f32>>,
+    negatives: Vec<Vec<f32>>,
+}
 
-        match cs_embed::create_reranker(rerank_model_name) {
-            Ok(mut reranker) => {
-                let documents: Vec<String> = results.iter().map(|r| r.preview.clone()).collect();
-
-                match reranker.rerank(&options.query, &documents) {
-                    Ok(rerank_results) => {
-                        // Create a map from document text to indices for handling duplicates
-                        let mut doc_to_indices: std::collections::HashMap<String, Vec<usize>> =
-                            std::collections::HashMap::new();
-                        for (i, result) in results.iter().enumerate() {
-                            doc_to_indices
-                                .entry(result.preview.clone())
-                                .or_default()
-                                .push(i);
-                        }
-
-                        // Update results with reranked scores
-                        // The reranker returns results in reranked order, so we match by document text
-                        for rerank_result in rerank_results.iter() {
-                            if let Some(indices) = doc_to_indices.get_mut(&rerank_result.document)
-                                && let Some(idx) = indices.pop()
-                            {
-                                results[idx].score = rerank_result.score;
-                            }
-                        }
-
-                        // Re-sort by reranked scores
-                        results.sort_by(|a, b| {
-                            b.score
-                                .partial_cmp(&a.score)
-                                .unwrap_or(std::cmp::Ordering::Equal)
-                        });
-
-                        // Apply top_k limit again after reranking
-                        if let Some(limit) = options.top_k {
-                            results.truncate(limit);
-                        }
-                    }
-                    Err(e) => {
-                        tracing::warn!("Reranking failed, using original scores: {}", e);
-                    }
-                }
-            }
-            Err(e) => {
-                tracing::warn!("Failed to create reranker, using original scores: {}", e);
+/// Load the query model and embed the patterns and `--not` phrases
+fn embed_queries(
+    index_root: &Path,
+    options: &SearchOptions,
+    progress_callback: &Option<SearchProgressCallback>,
+) -> Result<QueryEmbeddings> {
+    if let Some(callback) = progress_callback {
+        callback("Loading embedding model...");
+    }
+
+    let resolved_model = resolve_model_from_root(index_root, options.embedding_model.as_deref())?;
+    if let Some(callback) = progress_callback {
+        callback(&format!(
+            "Using embedding model {} ({} dims)",
+            resolved_model.alias, resolved_model.dimensions
+        ));
+    }
+
+    let started = std::time::Instant::now();
+    let mut embedder = cs_embed::create_embedder(Some(resolved_model.canonical_name.as_str()))?;
+    if let Some(stats) = &options.stats {
+        stats.record_phase("model load", started.elapsed());
+    }
+    let queries: Vec<String> = options.patterns().map(str::to_string).collect();
+    let started = std::time::Instant::now();
+    let mut query_embeddings =
+        embedder.embed(&[queries.as_slice(), &options.negative_queries].concat())?;
+    let negative_embeddings = query_embeddings.split_off(queries.len().min(query_embeddings.len()));
+    if let Some(stats) = &options.stats {
+        stats.record_phase("query embedding", started.elapsed());
+    }
+    Ok(QueryEmbeddings {
+        model: resolved_model,
+        dimensions: embedder.dim(),
+        queries: query_embeddings,
+        negatives: negative_embeddings,
+    })
+}
+
+/// Result limit the doc index can pick candidates for; `None` when the search has to score
+/// every chunk in scope: without a limit, or when it ranks on more than the embeddings
+/// (`--not`, `--diversity`) or filters on chunk contents (`--only`, `--no-tests`)
+fn doc_index_limit(options: &SearchOptions) -> Option<usize> {
+    let limit = rerank::candidate_limit(options)?;
+    (options.negative_queries.is_empty()
+        && options.diversity.is_none_or(|diversity| diversity <= 0.0)
+        && options.only_chunk_types.is_empty()
+        && !options.exclude_tests)
+        .then_some(limit)
+}
+
+/// Sidecars of the files holding the best `DOC_CANDIDATE_FACTOR` times `limit` chunks in scope
+/// for any of `queries`, ranked on the doc index; `None` when the doc index cannot tell
+/// (scope outside the index root, embeddings of another model)
+fn candidate_sidecars(
+    doc_index: &cs_ann::DocIndex,
+    index_root: &Path,
+    scope: Option<&Path>,
+    options: &SearchOptions,
+    queries: &[Vec<f32>],
+    limit: usize,
+) -> Option<Vec<std::path::PathBuf>> {
+    let relative_scope = match scope {
+        Some(target) => Some(cs_core::paths::relative_to(target, index_root)?),
+        None => None,
+    };
+    let span_filters = options.excluded_span.is_some() || options.within.is_some();
+    let allowed = doc_index.allow(
+        |path| {
+            relative_scope
+                .as_deref()
+                .is_none_or(|scope| Path::new(path).starts_with(scope))
+                && super::path_in_scope(&index_root.join(path), options)
+        },
+        |path, span| {
+            if !span_filters {
+                return true;
             }
-        }
+            let file = index_root.join(path);
+            !options
+                .excluded_span
+                .as_ref()
+                .is_some_and(|excluded| excluded.overlaps(&file, span))
+                && options
+                    .within
+                    .as_ref()
+                    .is_none_or(|within| within.contains(&file, span))
+        },
+    );
+
+    let mut files = BTreeSet::new();
+    for query in queries {
+        let hits = doc_index
+            .search(query, limit.saturating_mul(DOC_CANDIDATE_FACTOR), &allowed)
+            .ok()?;
+        files.extend(hits.into_iter().map(|hit| hit.file));
     }
+    Some(
+        files
+            .into_iter()
+            .filter_map(|file| doc_index.file(file))
+            .map(|path| cs_core::get_sidecar_path(index_root, &index_root.join(path)))
+            .collect(),
+    )
+}
 
-    Ok(cs_core::SearchResults {
-        matches: results,
-        closest_below_threshold,
+/// Read one sidecar, or take it from the resident index, and keep the chunks the search may
+/// return; `None` for unreadable sidecars and files outside the search
+fn load_sidecar(
+    path: &Path,
+    index_dir: &Path,
+    index_root: &Path,
+    scope: Option<&Path>,
+    options: &SearchOptions,
+    resident: Option<&ResidentIndex>,
+) -> Option<LoadedSidecar> {
+    let original_file = reconstruct_original_path(path, index_dir, index_root)?;
+    if !super::path_in_scope(&original_file, options) || !in_search_scope(&original_file, scope) {
+        return None;
+    }
+    let index_entry = match resident {
+        Some(resident) => std::sync::Arc::unwrap_or_clone(resident.sidecar(path, index_dir).ok()?),
+        None => cs_index::load_index_entry(path).ok()?,
+    };
+    // Chunk text is only needed to spot test functions by name
+    let content = if options.exclude_tests {
+        cs_core::encoding::read(&original_file).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let strides = if options.explain || options.stride_pooling != StridePooling::None {
+        explain::stride_positions(&index_entry.chunks)
+    } else {
+        Vec::new()
+    };
+    let logical_spans = stride_pooling::logical_spans(&index_entry.chunks, &strides);
+    let model = index_entry
+        .chunks
+        .iter()
+        .find_map(|chunk| chunk.embedding.as_ref().map(Vec::len))
+        .map(|dimensions| {
+            let model = index_entry.embedding_info.map(|info| info.model);
+            (model, dimensions)
+        });
+
+    let mut sparse = index_entry.sparse.map(Vec::into_iter);
+    let mut chunks = Vec::new();
+    for (position, chunk) in index_entry.chunks.into_iter().enumerate() {
+        let sparse = sparse.as_mut().and_then(Iterator::next);
+        if chunk.embedding.is_some()
+            && chunk_filter::chunk_type_allowed(options, chunk.chunk_type_name())
+            && !(options.exclude_tests && test_filter::is_test_chunk_entry(&chunk, &content))
+            && !options
+                .excluded_span
+                .as_ref()
+                .is_some_and(|span| span.overlaps(&original_file, &chunk.span))
+            && options
+                .within
+                .as_ref()
+                .is_none_or(|within| within.contains(&original_file, &chunk.span))
+        {
+            let stride = strides
+                .get(position)
+                .copied()
+                .flatten()
+                .zip(logical_spans.get(position).cloned().flatten());
+            chunks.push((chunk, sparse, stride));
+        }
+    }
+    Some(LoadedSidecar {
+        file: original_file,
+        chunks,
+        model,
     })
 }
 
+/// Consecutive runs of sidecars totalling at most SIDECAR_BATCH_BYTES on disk (a larger
+/// sidecar forms a batch of its own)
+fn sidecar_batches(sidecars: &[(std::path::PathBuf, u64)]) -> Vec<&[(std::path::PathBuf, u64)]> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0;
+    for (i, (_, size)) in sidecars.iter().enumerate() {
+        if i > start && bytes + size > SIDECAR_BATCH_BYTES {
+            batches.push(&sidecars[start..i]);
+            start = i;
+            bytes = 0;
+        }
+        bytes += size;
+    }
+    if start < sidecars.len() {
+        batches.push(&sidecars[start..]);
+    }
+    batches
+}
+
 fn reconstruct_original_path(
     sidecar_path: &Path,
     index_dir: &Path,
@@ -304,7 +671,128 @@ fn reconstruct_original_path(
     Some(repo_root.join(original_path))
 }
 
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+/// Root of the index searched for `path`: the nearest directory holding one, else the path
+/// itself (or its directory)
+pub(crate) fn index_root(path: &Path) -> std::path::PathBuf {
+    find_nearest_index_root(path).unwrap_or_else(|| {
+        if path.is_file() {
+            path.parent().unwrap_or(path).to_path_buf()
+        } else {
+            path.to_path_buf()
+        }
+    })
+}
+
+/// File or directory a search is limited to; `None` when it covers the whole index
+fn search_scope(path: &Path, index_root: &Path) -> Option<std::path::PathBuf> {
+    if path == Path::new(".") {
+        return None;
+    }
+    let target = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let root = index_root
+        .canonicalize()
+        .unwrap_or_else(|_| index_root.to_path_buf());
+    (target != root).then_some(target)
+}
+
+/// Sidecar, or directory of sidecars, holding the chunks under `target`
+fn scope_sidecar_root(target: &Path, index_root: &Path) -> Option<std::path::PathBuf> {
+    let relative = cs_core::paths::relative_to(target, index_root)?;
+    if target.is_file() {
+        Some(cs_core::get_sidecar_path(
+            index_root,
+            &index_root.join(&relative),
+        ))
+    } else {
+        Some(index_root.join(".cs").join(relative))
+    }
+}
+
+fn in_search_scope(file: &Path, scope: Option<&Path>) -> bool {
+    scope.is_none_or(|target| {
+        file.canonicalize()
+            .unwrap_or_else(|_| file.to_path_buf())
+            .starts_with(target)
+    })
+}
+
+/// Fail with switching guidance when sidecar embeddings cannot be compared with the query's,
+/// rather than scoring vectors of different models against each other
+fn verify_sidecar_models(
+    sidecar_models: &BTreeSet<(Option<String>, usize)>,
+    resolved_model: &ResolvedModel,
+    requested: Option<&str>,
+    query_dimensions: usize,
+) -> Result<()> {
+    let registry = cs_models::ModelRegistry::default();
+    let alias_of = |model: &str| {
+        registry
+            .find(model)
+            .map_or_else(|| model.to_string(), |(alias, _)| alias.to_string())
+    };
+    let requested = requested.map_or_else(|| resolved_model.alias.clone(), alias_of);
+
+    for (model, dimensions) in sidecar_models {
+        if *dimensions == query_dimensions {
+            continue;
+        }
+        let built_with = alias_of(model.as_deref().unwrap_or(&resolved_model.canonical_name));
+        return Err(CcError::Embedding(format!(
+            "Index built with '{}' ({} dims), but you asked for '{}' ({} dims). Run `cs --switch-model {}` to rebuild the index with '{}', or search with `--model {}` to use the index as it is.",
+            built_with, dimensions, requested, query_dimensions, requested, requested, built_with
+        ))
+        .into());
+    }
+
+    let models: BTreeSet<&str> = sidecar_models
+        .iter()
+        .filter_map(|(model, _)| model.as_deref())
+        .collect();
+    if models.len() > 1 {
+        tracing::warn!(
+            "Index mixes embeddings from {}; run `cs --switch-model {}` to rebuild it with one model",
+            models.into_iter().collect::<Vec<_>>().join(", "),
+            requested
+        );
+    }
+    Ok(())
+}
+
+/// Reorder the leading above-threshold candidates by MMR so near-duplicate chunks fall behind
+/// distinct ones; candidates outside the pool keep their similarity order
+fn diversify(
+    similarities: &mut [(f32, &std::path::PathBuf, &cs_index::ChunkEntry)],
+    options: &SearchOptions,
+    limit: usize,
+    diversity: f32,
+) {
+    let above_threshold = similarities
+        .iter()
+        .take_while(|(similarity, _, _)| {
+            options
+                .threshold
+                .is_none_or(|threshold| score_scale::semantic(*similarity) >= threshold)
+        })
+        .count();
+    let pool = above_threshold.min(if options.top_k.is_some() {
+        limit.saturating_mul(mmr::MMR_POOL_FACTOR)
+    } else {
+        mmr::MMR_MAX_POOL
+    });
+
+    let relevance: Vec<f32> = similarities[..pool].iter().map(|c| c.0).collect();
+    let embeddings: Vec<Option<&[f32]>> = similarities[..pool]
+        .iter()
+        .map(|c| c.2.embedding.as_deref())
+        .collect();
+    let reordered: Vec<_> = mmr::mmr_order(&relevance, &embeddings, pool, diversity)
+        .into_iter()
+        .map(|i| similarities[i])
+        .collect();
+    similarities[..pool].copy_from_slice(&reordered);
+}
+
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() {
         return 0.0;
     }
@@ -319,3 +807,146 @@ fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
         dot_product / (norm_a * norm_b)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sidecar_batches_bound_bytes() {
+        let mib = 1024 * 1024;
+        let sidecars: Vec<(std::path::PathBuf, u64)> = [40, 20, 10, 100, 1, 1]
+            .iter()
+            .enumerate()
+            .map(|(i, size)| (format!("{}.rs.cs", i).into(), size * mib))
+            .collect();
+        let sizes: Vec<Vec<u64>> = sidecar_batches(&sidecars)
+            .into_iter()
+            .map(|batch| batch.iter().map(|(_, size)| size / mib).collect())
+            .collect();
+        assert_eq!(sizes, vec![vec![40, 20], vec![10], vec![100], vec![1, 1]]);
+        assert!(sidecar_batches(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_search_scope() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/small")).unwrap();
+        std::fs::write(root.join("src/small/one.rs"), "fn one() {}").unwrap();
+        std::fs::write(root.join("src/two.rs"), "fn two() {}").unwrap();
+
+        assert_eq!(search_scope(Path::new("."), root), None);
+        assert_eq!(search_scope(root, root), None);
+
+        let scope = search_scope(&root.join("src/small"), root);
+        assert!(in_search_scope(
+            &root.join("src/small/one.rs"),
+            scope.as_deref()
+        ));
+        assert!(!in_search_scope(&root.join("src/two.rs"), scope.as_deref()));
+
+        let sidecars = scope_sidecar_root(scope.as_deref().unwrap(), root).unwrap();
+        assert!(
+            sidecars.ends_with(".cs/src/small"),
+            "{}",
+            sidecars.display()
+        );
+
+        let scope = search_scope(&root.join("src/two.rs"), root);
+        let sidecar = scope_sidecar_root(scope.as_deref().unwrap(), root).unwrap();
+        assert!(
+            sidecar.ends_with(".cs/src/two.rs.cs"),
+            "{}",
+            sidecar.display()
+        );
+        assert!(in_search_scope(&root.join("src/two.rs"), scope.as_deref()));
+        assert!(!in_search_scope(
+            &root.join("src/small/one.rs"),
+            scope.as_deref()
+        ));
+    }
+
+    #[test]
+    fn test_candidate_sidecars_rank_within_scope() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/small")).unwrap();
+        let mut doc_index = cs_ann::DocIndex::new();
+        // Ten files close to the query, and one far from it in a small subtree
+        for i in 0..10 {
+            let file = doc_index.add_file(&format!("src/{i}.rs"));
+            let span = Span::new(0, 10, 1, 1).unwrap();
+            doc_index.add(file, span, &[1.0, i as f32 * 0.01]).unwrap();
+        }
+        let file = doc_index.add_file("src/small/one.rs");
+        let span = Span::new(0, 10, 1, 1).unwrap();
+        doc_index.add(file, span, &[0.0, 1.0]).unwrap();
+
+        let options = SearchOptions {
+            query: "retry".to_string(),
+            top_k: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(doc_index_limit(&options), Some(1));
+        let queries = [vec![1.0, 0.0]];
+        let sidecars = candidate_sidecars(&doc_index, root, None, &options, &queries, 1).unwrap();
+        assert_eq!(sidecars.len(), DOC_CANDIDATE_FACTOR);
+        assert!(sidecars[0].ends_with(".cs/src/0.rs.cs"));
+
+        let scope = search_scope(&root.join("src/small"), root);
+        let sidecars =
+            candidate_sidecars(&doc_index, root, scope.as_deref(), &options, &queries, 1).unwrap();
+        assert_eq!(sidecars.len(), 1);
+        assert!(sidecars[0].ends_with(".cs/src/small/one.rs.cs"));
+
+        // Embeddings of another model leave the choice to the sidecars
+        assert!(candidate_sidecars(&doc_index, root, None, &options, &[vec![1.0]], 1).is_none());
+
+        // Searches scoring every chunk read every sidecar
+        for options in [
+            SearchOptions {
+                top_k: None,
+                ..options.clone()
+            },
+            SearchOptions {
+                negative_queries: vec!["test".to_string()],
+                ..options.clone()
+            },
+            SearchOptions {
+                only_chunk_types: vec!["function".to_string()],
+                ..options
+            },
+        ] {
+            assert_eq!(doc_index_limit(&options), None);
+        }
+    }
+
+    #[test]
+    fn test_verify_sidecar_models() {
+        let resolved = ResolvedModel {
+            canonical_name: "BAAI/bge-small-en-v1.5".to_string(),
+            alias: "bge-small".to_string(),
+            dimensions: 384,
+        };
+        let mut sidecars = BTreeSet::from([
+            (Some("BAAI/bge-small-en-v1.5".to_string()), 384),
+            // Sidecars from before the model was recorded
+            (None, 384),
+        ]);
+        assert!(verify_sidecar_models(&sidecars, &resolved, None, 384).is_ok());
+
+        sidecars.insert((Some("nomic-embed-text-v1.5".to_string()), 768));
+        let error = verify_sidecar_models(&sidecars, &resolved, None, 384)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains(
+                "Index built with 'nomic-v1.5' (768 dims), but you asked for 'bge-small' (384 dims)"
+            ),
+            "{}",
+            error
+        );
+        assert!(error.contains("cs --switch-model bge-small"), "{}", error);
+    }
+}
@@ -0,0 +1,41 @@
+// Rank signals (`SearchResult::signals`, the `signals` object of `--json`)
+// Each ranking stage records where a result placed: keyword ranks for regex and BM25, vector
+// ranks before reranking, and every list's rank once hybrid search fuses them
+
+use cs_core::{SearchResult, SearchSignals};
+
+/// The result's signals, created on first use
+fn signals(result: &mut SearchResult) -> &mut SearchSignals {
+    result.signals.get_or_insert_with(Default::default)
+}
+
+/// Record each result's 1-based position in a keyword (regex or BM25) ranking
+pub(crate) fn record_lex_ranks(results: &mut [SearchResult]) {
+    for (rank, result) in results.iter_mut().enumerate() {
+        signals(result).lex_rank = Some(rank + 1);
+    }
+}
+
+/// Record each result's 1-based position in an embedding similarity ranking
+pub(crate) fn record_vec_ranks(results: &mut [SearchResult]) {
+    for (rank, result) in results.iter_mut().enumerate() {
+        signals(result).vec_rank = Some(rank + 1);
+    }
+}
+
+/// Signals of a fused hybrid result from its rank in each list; a BM25 rank is preferred to a
+/// regex rank as the keyword rank
+pub(crate) fn fused(ranks: &[(&str, usize, SearchResult)], rrf_score: f32) -> SearchSignals {
+    let rank_in = |list: &str| {
+        ranks
+            .iter()
+            .find(|(name, _, _)| *name == list)
+            .map(|(_, rank, _)| *rank)
+    };
+    SearchSignals {
+        lex_rank: rank_in("lexical").or_else(|| rank_in("regex")),
+        vec_rank: rank_in("semantic"),
+        rrf_score: Some(rrf_score),
+        raw_score: Some(rrf_score),
+    }
+}
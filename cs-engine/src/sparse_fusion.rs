@@ -0,0 +1,81 @@
+// Sparse+dense fusion for indexes built with `--sparse`
+// BM25 over the stored term vectors, normalized to the best chunk, lifts each cosine similarity
+// towards 1 by `weight`; chunks without a term match keep their cosine score, so `--threshold`
+// means the same with or without sparse vectors
+
+use cs_core::SearchOptions;
+use cs_index::sparse::{Bm25, SparseVector, query_terms};
+
+/// `--sparse-weight` when none is given
+pub(crate) const DEFAULT_SPARSE_WEIGHT: f32 = 0.3;
+
+/// Normalized BM25 score (0..=1) of each chunk for its best-matching query pattern; `None`
+/// when fusion is off or no chunk has a term vector
+pub(crate) fn sparse_scores(
+    options: &SearchOptions,
+    vectors: &[Option<SparseVector>],
+) -> Option<Vec<f32>> {
+    if sparse_weight(options) <= 0.0 || vectors.iter().all(Option::is_none) {
+        return None;
+    }
+    let bm25 = Bm25::new(vectors.iter().flatten());
+    let queries: Vec<Vec<u32>> = options.patterns().map(query_terms).collect();
+    let scores: Vec<f32> = vectors
+        .iter()
+        .map(|vector| {
+            vector.as_ref().map_or(0.0, |vector| {
+                queries
+                    .iter()
+                    .map(|query| bm25.score(query, vector))
+                    .fold(0.0, f32::max)
+            })
+        })
+        .collect();
+    let best = scores.iter().copied().fold(0.0, f32::max);
+    (best > 0.0).then(|| scores.into_iter().map(|score| score / best).collect())
+}
+
+pub(crate) fn sparse_weight(options: &SearchOptions) -> f32 {
+    options
+        .sparse_weight
+        .unwrap_or(DEFAULT_SPARSE_WEIGHT)
+        .clamp(0.0, 1.0)
+}
+
+/// `cosine` moved towards 1 by `weight` times the normalized sparse score
+pub(crate) fn fuse(cosine: f32, sparse: f32, weight: f32) -> f32 {
+    cosine + weight * sparse * (1.0 - cosine)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cs_index::sparse::sparse_vector;
+
+    #[test]
+    fn test_sparse_scores_and_fusion() {
+        let options = SearchOptions {
+            query: "parse_retry_policy".to_string(),
+            ..Default::default()
+        };
+        let vectors = vec![
+            Some(sparse_vector(
+                "fn parse_retry_policy(env: &Env) -> RetryPolicy",
+            )),
+            Some(sparse_vector("fn load_config(path: &Path) -> Config")),
+            None,
+        ];
+        let scores = sparse_scores(&options, &vectors).unwrap();
+        assert_eq!(scores, vec![1.0, 0.0, 0.0]);
+
+        assert_eq!(fuse(0.6, 0.0, 0.3), 0.6);
+        assert!((fuse(0.6, 1.0, 0.3) - 0.72).abs() < 1e-6);
+        assert!(fuse(0.9, 1.0, 1.0) <= 1.0);
+
+        let off = SearchOptions {
+            sparse_weight: Some(0.0),
+            ..options
+        };
+        assert!(sparse_scores(&off, &vectors).is_none());
+    }
+}
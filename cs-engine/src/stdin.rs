@@ -0,0 +1,163 @@
+// Searching piped input (`cat log.txt | cs "pattern" -`)
+// Regex runs over the raw bytes; semantic chunks and embeds the text in memory without an index
+
+use anyhow::Result;
+use cs_core::{CcError, SearchMode, SearchOptions};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::{
+    Deadline, SearchProgressCallback, build_search_regex, ephemeral, fuse_rrf,
+    resolve_model_for_path,
+};
+
+/// Path argument that means "read standard input", as in grep
+pub const STDIN_PATH: &str = "-";
+
+/// File name reported for matches in standard input
+pub const STDIN_LABEL: &str = "(standard input)";
+
+pub fn is_stdin_path(path: &Path) -> bool {
+    path == Path::new(STDIN_PATH)
+}
+
+pub(crate) async fn search_stdin(
+    options: &SearchOptions,
+    progress_callback: Option<SearchProgressCallback>,
+) -> Result<cs_core::SearchResults> {
+    let mut content = Vec::new();
+    std::io::stdin().lock().read_to_end(&mut content)?;
+    search_piped(options, &content, progress_callback)
+}
+
+fn search_piped(
+    options: &SearchOptions,
+    content: &[u8],
+    progress_callback: Option<SearchProgressCallback>,
+) -> Result<cs_core::SearchResults> {
+    match options.mode {
+        SearchMode::Regex => regex_piped(options, content),
+        SearchMode::Semantic => semantic_piped(options, content, progress_callback),
+        SearchMode::Hybrid => {
+            let regex_results = regex_piped(options, content)?;
+            let semantic_results = semantic_piped(options, content, progress_callback)?;
+            Ok(cs_core::SearchResults {
+                matches: fuse_rrf(
+                    options,
+                    &[
+                        ("regex", &regex_results.matches),
+                        ("semantic", &semantic_results.matches),
+                    ],
+                ),
+                closest_below_threshold: None,
+                partial: false,
+                lossy_files: regex_results.lossy_files,
+            })
+        }
+        SearchMode::Lexical | SearchMode::Ast | SearchMode::Symbol => Err(CcError::Search(format!(
+            "{:?} search is not supported on standard input; use regex, semantic or hybrid search",
+            options.mode
+        ))
+        .into()),
+    }
+}
+
+fn regex_piped(options: &SearchOptions, content: &[u8]) -> Result<cs_core::SearchResults> {
+    let regex = build_search_regex(options)?;
+    let (mut matches, lossy) =
+        super::search_content(&regex, Path::new(STDIN_LABEL), options, content)?;
+    if let Some(top_k) = options.top_k {
+        matches.truncate(top_k);
+    }
+
+    Ok(cs_core::SearchResults {
+        lossy_files: usize::from(lossy && !matches.is_empty()),
+        matches,
+        closest_below_threshold: None,
+        partial: false,
+    })
+}
+
+fn semantic_piped(
+    options: &SearchOptions,
+    content: &[u8],
+    progress_callback: Option<SearchProgressCallback>,
+) -> Result<cs_core::SearchResults> {
+    let text = String::from_utf8_lossy(content);
+    // Use the model of an index in the working directory, if any, so scores are comparable
+    let resolved_model =
+        resolve_model_for_path(Path::new("."), options.embedding_model.as_deref())?;
+    let model_name = resolved_model.canonical_name.as_str();
+
+    let chunks = cs_chunk::chunk_text_with_overrides(
+        &text,
+        None,
+        Some(model_name),
+        &cs_index::chunk_overrides(Path::new(".")),
+    )?;
+    if chunks.is_empty() {
+        return Ok(cs_core::SearchResults {
+            matches: Vec::new(),
+            closest_below_threshold: None,
+            partial: false,
+            lossy_files: 0,
+        });
+    }
+
+    if let Some(ref callback) = progress_callback {
+        callback(&format!(
+            "Embedding {} chunks from standard input...",
+            chunks.len()
+        ));
+    }
+
+    let chunks = chunks
+        .into_iter()
+        .map(|chunk| (PathBuf::from(STDIN_LABEL), chunk))
+        .collect();
+    // With several patterns a chunk scores as its best match, as with indexed search
+    let mut partial = false;
+    let scored = ephemeral::embed_and_score(
+        options,
+        model_name,
+        chunks,
+        Deadline::default(),
+        &mut partial,
+    )?;
+    Ok(ephemeral::rank_chunks(options, scored, options.top_k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regex_search_piped_input() {
+        let options = SearchOptions {
+            mode: SearchMode::Regex,
+            query: "error".to_string(),
+            path: STDIN_PATH.into(),
+            line_numbers: true,
+            ..Default::default()
+        };
+        let results = search_piped(&options, b"ok\nerror: disk\nok\nerror: net\n", None).unwrap();
+
+        assert_eq!(results.matches.len(), 2);
+        assert_eq!(results.matches[0].file, Path::new(STDIN_LABEL));
+        assert_eq!(results.matches[0].span.line_start, 2);
+        assert_eq!(results.matches[0].span.byte_start, 3);
+        assert_eq!(results.matches[1].preview, "error: net");
+    }
+
+    #[test]
+    fn test_lexical_search_piped_input_is_rejected() {
+        let options = SearchOptions {
+            mode: SearchMode::Lexical,
+            query: "error".to_string(),
+            path: STDIN_PATH.into(),
+            ..Default::default()
+        };
+        let err = search_piped(&options, b"error\n", None).unwrap_err();
+        assert!(err.to_string().contains("not supported on standard input"));
+    }
+}
@@ -0,0 +1,292 @@
+// Stride pooling (`--stride-pooling`)
+// A chunk longer than the model's window is embedded as overlapping strides, each scored on its
+// own, so one long function could take several result slots and crowd out others. Strides of
+// one logical chunk collapse into a single result covering all of them, scored by their best
+// stride (max) or their average (mean)
+
+use cs_chunk::Chunk;
+use cs_core::explain::StrideMatch;
+use cs_core::{Span, StridePooling};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+
+/// Span of the whole logical chunk for each stride, given the stride positions of one file's
+/// chunks
+pub(crate) fn logical_spans(
+    chunks: &[cs_index::ChunkEntry],
+    strides: &[Option<StrideMatch>],
+) -> Vec<Option<Span>> {
+    if chunks.iter().any(|chunk| chunk.stride.is_some()) {
+        return recorded_logical_spans(chunks);
+    }
+    strides
+        .iter()
+        .enumerate()
+        .map(|(position, stride)| {
+            let stride = (*stride)?;
+            let first = &chunks.get(position.checked_sub(stride.index)?)?.span;
+            let last = &chunks.get(position - stride.index + stride.total - 1)?.span;
+            Some(Span {
+                byte_start: first.byte_start,
+                byte_end: last.byte_end,
+                line_start: first.line_start,
+                line_end: last.line_end,
+            })
+        })
+        .collect()
+}
+
+/// Logical spans from the strides recorded in the sidecar, joined by `original_chunk_id`
+fn recorded_logical_spans(chunks: &[cs_index::ChunkEntry]) -> Vec<Option<Span>> {
+    let mut wholes: HashMap<&str, Span> = HashMap::new();
+    for chunk in chunks {
+        let Some(stride) = &chunk.stride else {
+            continue;
+        };
+        let span = &chunk.span;
+        wholes
+            .entry(stride.original_chunk_id.as_str())
+            .and_modify(|whole| {
+                if span.byte_start < whole.byte_start {
+                    whole.byte_start = span.byte_start;
+                    whole.line_start = span.line_start;
+                }
+                if span.byte_end > whole.byte_end {
+                    whole.byte_end = span.byte_end;
+                    whole.line_end = span.line_end;
+                }
+            })
+            .or_insert_with(|| span.clone());
+    }
+    chunks
+        .iter()
+        .map(|chunk| {
+            let stride = chunk.stride.as_ref()?;
+            wholes.get(stride.original_chunk_id.as_str()).cloned()
+        })
+        .collect()
+}
+
+/// Span and text of each strided chunk produced in memory, rebuilt from its strides and keyed
+/// by file and `original_chunk_id`; chunks with missing strides are left out
+pub(crate) fn whole_chunks<'a>(
+    chunks: impl Iterator<Item = (&'a Path, &'a Chunk)>,
+) -> HashMap<(PathBuf, String), (Span, String)> {
+    let mut strides: HashMap<(PathBuf, String), Vec<&Chunk>> = HashMap::new();
+    for (file, chunk) in chunks {
+        if let Some(stride) = &chunk.stride_info {
+            strides
+                .entry((file.to_path_buf(), stride.original_chunk_id.clone()))
+                .or_default()
+                .push(chunk);
+        }
+    }
+    strides
+        .into_iter()
+        .filter_map(|(key, mut strides)| {
+            strides.sort_by_key(|chunk| chunk.span.byte_start);
+            let first = strides.first()?;
+            if strides.len() != first.stride_info.as_ref()?.total_strides {
+                return None;
+            }
+            let mut span = first.span.clone();
+            let mut text = first.text.clone();
+            for stride in &strides[1..] {
+                // Each stride repeats the tail of the previous one
+                let overlap = span.byte_end.checked_sub(stride.span.byte_start)?;
+                text.push_str(stride.text.get(overlap..)?);
+                span.byte_end = stride.span.byte_end;
+                span.line_end = stride.span.line_end;
+            }
+            (text.len() == span.byte_end - span.byte_start).then_some((key, (span, text)))
+        })
+        .collect()
+}
+
+/// Keep one item per logical chunk, the best-scoring stride, with the pooled score; items
+/// without a `group` pass through. The result is sorted best first.
+pub(crate) fn pool<T, K: Hash + Eq>(
+    scored: Vec<T>,
+    pooling: StridePooling,
+    score: impl Fn(&mut T) -> &mut f32,
+    group: impl Fn(&T) -> Option<K>,
+) -> Vec<T> {
+    if pooling == StridePooling::None {
+        return scored;
+    }
+    let mut pooled: Vec<T> = Vec::with_capacity(scored.len());
+    // Position in `pooled`, best score, score sum and stride count of each logical chunk
+    let mut groups: HashMap<K, (usize, f32, f32, usize)> = HashMap::new();
+    for mut item in scored {
+        let Some(key) = group(&item) else {
+            pooled.push(item);
+            continue;
+        };
+        let item_score = *score(&mut item);
+        match groups.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let (position, best, sum, count) = entry.get_mut();
+                *sum += item_score;
+                *count += 1;
+                if item_score > *best {
+                    *best = item_score;
+                    pooled[*position] = item;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((pooled.len(), item_score, item_score, 1));
+                pooled.push(item);
+            }
+        }
+    }
+    for (position, best, sum, count) in groups.into_values() {
+        *score(&mut pooled[position]) = match pooling {
+            StridePooling::Mean => sum / count as f32,
+            _ => best,
+        };
+    }
+    let mut ranked: Vec<(f32, T)> = pooled
+        .into_iter()
+        .map(|mut item| (*score(&mut item), item))
+        .collect();
+    ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    ranked.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        byte_start: usize,
+        byte_end: usize,
+        line_start: usize,
+        line_end: usize,
+    ) -> cs_index::ChunkEntry {
+        cs_index::ChunkEntry {
+            span: Span::new(byte_start, byte_end, line_start, line_end).unwrap(),
+            embedding: None,
+            chunk_type: cs_chunk::ChunkType::Function,
+            breadcrumb: None,
+            ancestry: None,
+            byte_length: None,
+            estimated_tokens: None,
+            leading_trivia: None,
+            trailing_trivia: None,
+            stride: None,
+        }
+    }
+
+    #[test]
+    fn test_strides_pool_into_one_result() {
+        let chunks = vec![
+            entry(0, 100, 1, 5),
+            entry(100, 400, 6, 20),
+            entry(350, 650, 18, 32),
+            entry(600, 800, 30, 40),
+        ];
+        let strides = crate::explain::stride_positions(&chunks);
+        let spans = logical_spans(&chunks, &strides);
+        assert!(spans[0].is_none());
+        let whole = spans[2].as_ref().unwrap();
+        assert_eq!((whole.byte_start, whole.byte_end), (100, 800));
+        assert_eq!((whole.line_start, whole.line_end), (6, 40));
+
+        // (score, chunk position): the three strides of the long function and a short chunk
+        let scored = vec![(0.9, 2), (0.7, 0), (0.6, 1), (0.3, 3)];
+        let group = |item: &(f32, usize)| spans[item.1].as_ref().map(|span| span.byte_start);
+
+        let max = pool(
+            scored.clone(),
+            StridePooling::Max,
+            |item| &mut item.0,
+            group,
+        );
+        assert_eq!(max, vec![(0.9, 2), (0.7, 0)]);
+
+        let mean = pool(
+            scored.clone(),
+            StridePooling::Mean,
+            |item| &mut item.0,
+            group,
+        );
+        assert_eq!(mean[0], (0.7, 0));
+        assert_eq!(mean[1].1, 2);
+        assert!((mean[1].0 - 0.6).abs() < 1e-6);
+
+        assert_eq!(
+            pool(
+                scored.clone(),
+                StridePooling::None,
+                |item| &mut item.0,
+                group
+            ),
+            scored
+        );
+    }
+
+    #[test]
+    fn test_recorded_strides_join_by_chunk_id() {
+        let stride = |index: usize, id: &str| cs_chunk::StrideInfo {
+            original_chunk_id: id.to_string(),
+            stride_index: index,
+            total_strides: 2,
+            overlap_start: 0,
+            overlap_end: 0,
+        };
+        // Two adjacent long functions of the same kind; only the recorded ids separate them
+        let mut chunks = vec![
+            entry(0, 300, 1, 15),
+            entry(250, 500, 13, 25),
+            entry(500, 800, 26, 40),
+            entry(750, 900, 38, 45),
+        ];
+        for (chunk, (index, id)) in
+            chunks
+                .iter_mut()
+                .zip([(0, "parse"), (1, "parse"), (0, "render"), (1, "render")])
+        {
+            chunk.stride = Some(stride(index, id));
+        }
+
+        let strides = crate::explain::stride_positions(&chunks);
+        assert_eq!(strides[2], Some(StrideMatch { index: 0, total: 2 }));
+        let spans = logical_spans(&chunks, &strides);
+        let render = spans[3].as_ref().unwrap();
+        assert_eq!((render.byte_start, render.byte_end), (500, 900));
+        assert_eq!((render.line_start, render.line_end), (26, 45));
+        assert_eq!(spans[0].as_ref().unwrap().byte_end, 500);
+    }
+
+    #[test]
+    fn test_whole_chunks_rebuilt_from_strides() {
+        let source = "fn long() {\n    step_one();\n    step_two();\n}\n";
+        let stride =
+            |index: usize, byte_start: usize, byte_end: usize, lines: (usize, usize)| Chunk {
+                span: Span::new(byte_start, byte_end, lines.0, lines.1).unwrap(),
+                text: source[byte_start..byte_end].to_string(),
+                chunk_type: cs_chunk::ChunkType::Function,
+                stride_info: Some(cs_chunk::StrideInfo {
+                    original_chunk_id: "long".to_string(),
+                    stride_index: index,
+                    total_strides: 2,
+                    overlap_start: 0,
+                    overlap_end: 0,
+                }),
+                metadata: Default::default(),
+            };
+        let file = Path::new("lib.rs");
+        let strides = [stride(1, 12, 46, (2, 4)), stride(0, 0, 28, (1, 2))];
+
+        let wholes = whole_chunks(strides.iter().map(|chunk| (file, chunk)));
+        let (span, text) = &wholes[&(file.to_path_buf(), "long".to_string())];
+        assert_eq!((span.byte_start, span.byte_end), (0, 46));
+        assert_eq!((span.line_start, span.line_end), (1, 4));
+        assert_eq!(text, source);
+
+        // A stride filtered out of the search leaves the chunk unrebuilt
+        assert!(whole_chunks(strides[..1].iter().map(|chunk| (file, chunk))).is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+// Symbol search: match the query against definition names found by the tree-sitter chunker
+// A lightweight ctags-style lookup that needs no index and returns whole definitions
+
+use anyhow::Result;
+use cs_chunk::{Chunk, ChunkConfig, ChunkType};
+use cs_core::{Language, SearchOptions, SearchResult};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::{SearchRegex, build_search_regex, chunk_filter, filter_files_in_scope, test_filter};
+
+/// Score for a name equal to the query; other regex matches score lower and sort after
+const EXACT_MATCH_SCORE: f32 = 1.0;
+const PARTIAL_MATCH_SCORE: f32 = 0.5;
+
+pub(crate) fn symbol_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
+    let regex = build_search_regex(options)?;
+
+    let files = if options.path.is_file() {
+        vec![options.path.clone()]
+    } else {
+        cs_index::collect_files(
+            &options.path,
+            options.respect_gitignore,
+            &options.exclude_patterns,
+        )?
+    };
+    let files: Vec<_> = filter_files_in_scope(files, options)
+        .into_iter()
+        .filter(|path| Language::from_path(path).is_some_and(|lang| lang != Language::Pdf))
+        .collect();
+
+    let mut results: Vec<SearchResult> = files
+        .par_iter()
+        .flat_map_iter(|path| match search_file_symbols(&regex, path, options) {
+            Ok(results) => results,
+            Err(e) => {
+                tracing::debug!("Error reading symbols from {:?}: {}", path, e);
+                Vec::new()
+            }
+        })
+        .collect();
+
+    // Exact names first, then file and line order
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.span.line_start.cmp(&b.span.line_start))
+    });
+    if let Some(top_k) = options.top_k {
+        results.truncate(top_k);
+    }
+    Ok(results)
+}
+
+/// Set `symbol` on results that lack one to the innermost named definition holding them
+pub(crate) fn fill_enclosing_symbols(results: &mut [SearchResult]) {
+    let files: HashSet<&Path> = results
+        .iter()
+        .filter(|result| result.symbol.is_none())
+        .map(|result| result.file.as_path())
+        .filter(|path| Language::from_path(path).is_some_and(|lang| lang != Language::Pdf))
+        .collect();
+    let definitions: HashMap<PathBuf, Vec<Chunk>> = files
+        .into_par_iter()
+        .filter_map(|path| Some((path.to_path_buf(), parse_definitions(path).ok()?)))
+        .collect();
+
+    for result in results.iter_mut().filter(|result| result.symbol.is_none()) {
+        let Some(chunks) = definitions.get(&result.file) else {
+            continue;
+        };
+        result.symbol = chunks
+            .iter()
+            .filter(|chunk| {
+                chunk.metadata.name.is_some()
+                    && chunk.span.line_start <= result.span.line_start
+                    && chunk.span.line_end >= result.span.line_end
+            })
+            .min_by_key(|chunk| chunk.span.byte_end - chunk.span.byte_start)
+            .and_then(|chunk| chunk.metadata.name.clone());
+    }
+}
+
+/// Chunks of a file as whole definitions, not embedding-sized strides
+fn parse_definitions(path: &Path) -> Result<Vec<Chunk>> {
+    let content = std::fs::read(path)?;
+    let text = cs_core::encoding::decode(&content);
+    let config = ChunkConfig {
+        enable_striding: false,
+        ..ChunkConfig::default()
+    };
+    cs_chunk::chunk_text_with_config(&text, Language::from_path(path), &config)
+}
+
+fn search_file_symbols(
+    regex: &SearchRegex,
+    path: &Path,
+    options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    let language = Language::from_path(path);
+    let chunks = parse_definitions(path)?;
+
+    Ok(chunks
+        .into_iter()
+        .filter(|chunk| {
+            let chunk_type = match chunk.chunk_type {
+                ChunkType::Function => "function",
+                ChunkType::Class => "class",
+                ChunkType::Method => "method",
+                ChunkType::Module => "module",
+                ChunkType::Text => return false,
+            };
+            chunk_filter::chunk_type_allowed(options, Some(chunk_type))
+                && !(options.exclude_tests
+                    && test_filter::is_test_chunk(
+                        &chunk.metadata.leading_trivia,
+                        &chunk.metadata.ancestry,
+                        &chunk.text,
+                    ))
+        })
+        .filter_map(|chunk| {
+            let name = chunk.metadata.name?;
+            if !regex.is_match(&name) {
+                return None;
+            }
+            let exact = options.patterns().any(|pattern| {
+                name == pattern || (options.case_insensitive && name.eq_ignore_ascii_case(pattern))
+            });
+            Some(SearchResult {
+                file: path.to_path_buf(),
+                span: chunk.span,
+                score: if exact {
+                    EXACT_MATCH_SCORE
+                } else {
+                    PARTIAL_MATCH_SCORE
+                },
+                preview: chunk.text,
+                lang: language,
+                symbol: Some(name),
+                chunk_hash: None,
+                index_epoch: None,
+                signals: None,
+                explanation: None,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cs_core::SearchMode;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_symbol_search_returns_definitions() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            "struct Parser;\n\nfn parse() {}\n\nfn parse_header() {\n    // parse here\n}\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "fn parse() {}\n").unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Symbol,
+            query: "parse".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let results = symbol_search(&options).unwrap();
+
+        // Exact match first; comments and plain text files never match
+        let symbols: Vec<_> = results.iter().map(|r| r.symbol.as_deref()).collect();
+        assert_eq!(symbols, vec![Some("parse"), Some("parse_header")]);
+        assert_eq!(results[1].span.line_start, 5);
+        assert!(results[1].preview.ends_with('}'));
+
+        let options = SearchOptions {
+            query: "parser".to_string(),
+            case_insensitive: true,
+            ..options
+        };
+        let results = symbol_search(&options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.as_deref(), Some("Parser"));
+        assert_eq!(results[0].score, EXACT_MATCH_SCORE);
+    }
+
+    #[test]
+    fn test_symbol_search_without_tests() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            "fn parse() {}\n\n#[test]\nfn parse_empty() {}\n\n#[cfg(test)]\nmod tests {\n    fn parse_fixture() {}\n}\n",
+        )
+        .unwrap();
+        fs::create_dir(temp_dir.path().join("tests")).unwrap();
+        fs::write(
+            temp_dir.path().join("tests/parse.rs"),
+            "fn parse_all() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("parse_test.go"),
+            "func parseCase() {}\n",
+        )
+        .unwrap();
+
+        let options = SearchOptions {
+            mode: SearchMode::Symbol,
+            query: "parse".to_string(),
+            path: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        assert_eq!(symbol_search(&options).unwrap().len(), 5);
+
+        let options = SearchOptions {
+            exclude_tests: true,
+            ..options
+        };
+        let symbols: Vec<_> = symbol_search(&options)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.symbol.unwrap())
+            .collect();
+        assert_eq!(symbols, vec!["parse"]);
+    }
+}
@@ -0,0 +1,223 @@
+// Test-code filtering (`--no-tests`)
+// Files are recognized by path conventions; inside other files, chunks are recognized by test
+// attributes, enclosing test modules and test-function naming conventions
+
+use cs_core::SearchOptions;
+use std::path::Path;
+
+/// Directory names that hold test code
+const TEST_DIRS: &[&str] = &["test", "tests", "__tests__", "spec", "specs", "testdata"];
+
+/// True when `path` is excluded by `--no-tests` because of where it lives or how it is named
+pub(crate) fn is_excluded_test_path(options: &SearchOptions, path: &Path) -> bool {
+    if !options.exclude_tests {
+        return false;
+    }
+    // Only look below the search root, so a checkout under ~/tests/ is not all test code
+    let root = super::canonicalize_for_matching(if options.path.is_file() {
+        options.path.parent().unwrap_or(&options.path)
+    } else {
+        &options.path
+    });
+    let candidate = super::canonicalize_for_matching(path);
+    is_test_path(candidate.strip_prefix(&root).unwrap_or(&candidate))
+}
+
+pub(crate) fn is_test_path(path: &Path) -> bool {
+    let mut components = path.components().peekable();
+    while let Some(component) = components.next() {
+        let name = component.as_os_str().to_string_lossy();
+        if components.peek().is_some() {
+            if TEST_DIRS.contains(&name.as_ref()) {
+                return true;
+            }
+        } else {
+            return is_test_file_name(&name);
+        }
+    }
+    false
+}
+
+fn is_test_file_name(name: &str) -> bool {
+    let stem = match name.split_once('.') {
+        Some((stem, _)) => stem,
+        None => name,
+    };
+    // foo_test.go, test_foo.py, foo.test.ts, foo.spec.js, foo_spec.rb, FooTest.java, FooTests.cs
+    stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_tests")
+        || stem.ends_with("_spec")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+        || ((stem.ends_with("Test") || stem.ends_with("Tests"))
+            && stem.starts_with(|c: char| c.is_ascii_uppercase()))
+}
+
+/// True when a chunk is test code: marked with a test attribute or decorator, nested in a
+/// test module or class, or a test function by naming convention (Go, Python, Zig)
+pub(crate) fn is_test_chunk(leading_trivia: &[String], ancestry: &[String], text: &str) -> bool {
+    if leading_trivia
+        .iter()
+        .any(|trivia| is_test_attribute(trivia))
+    {
+        return true;
+    }
+    if ancestry
+        .iter()
+        .any(|scope| matches!(scope.as_str(), "test" | "tests") || is_test_class_name(scope))
+    {
+        return true;
+    }
+
+    // First line of the definition itself, after any attributes and comments
+    let header = text
+        .lines()
+        .map(str::trim_start)
+        .find(|line| {
+            !line.is_empty()
+                && !line.starts_with('@')
+                && !line.starts_with("//")
+                && !line.starts_with('#')
+        })
+        .unwrap_or_default();
+    let header = header.strip_prefix("pub ").unwrap_or(header);
+    let header = header.strip_prefix("async ").unwrap_or(header);
+    header.starts_with("def test_")
+        || header.starts_with("fn test_")
+        || header.starts_with("test \"")
+        || header.starts_with("test {")
+        || header
+            .strip_prefix("func Test")
+            .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase() || c == '('))
+        || header.starts_with("mod tests")
+        || header.strip_prefix("class ").is_some_and(|rest| {
+            is_test_class_name(
+                rest.split(|c: char| !c.is_alphanumeric() && c != '_')
+                    .next()
+                    .unwrap_or_default(),
+            )
+        })
+}
+
+/// `is_test_chunk` for an indexed chunk, reading its text from the file `content`
+pub(crate) fn is_test_chunk_entry(chunk: &cs_index::ChunkEntry, content: &[u8]) -> bool {
+    let text = content
+        .get(chunk.span.byte_start..chunk.span.byte_end)
+        .map(String::from_utf8_lossy)
+        .unwrap_or_default();
+    is_test_chunk(
+        chunk.leading_trivia.as_deref().unwrap_or_default(),
+        chunk.ancestry.as_deref().unwrap_or_default(),
+        &text,
+    )
+}
+
+/// `#[test]`, `#[tokio::test]`, `#[cfg(test)]`, `@pytest.mark...`, `[Fact]`, `[TestMethod]`
+fn is_test_attribute(trivia: &str) -> bool {
+    let trivia = trivia.trim();
+    if let Some(attribute) = trivia.strip_prefix("#[") {
+        return attribute
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|word| word == "test" || word == "rstest");
+    }
+    if let Some(decorator) = trivia.strip_prefix('@') {
+        return decorator.starts_with("pytest") || decorator.starts_with("Test");
+    }
+    if let Some(attribute) = trivia.strip_prefix('[') {
+        return ["Test", "TestMethod", "TestCase", "Fact", "Theory"]
+            .iter()
+            .any(|name| attribute.starts_with(name));
+    }
+    false
+}
+
+/// `TestParser`, `ParserTest`, `ParserTests` (xUnit-style test classes)
+fn is_test_class_name(name: &str) -> bool {
+    let starts_upper = name.starts_with(|c: char| c.is_ascii_uppercase());
+    starts_upper
+        && (name.ends_with("Test")
+            || name.ends_with("Tests")
+            || name
+                .strip_prefix("Test")
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_test_paths() {
+        for path in [
+            "tests/integration.rs",
+            "src/__tests__/App.tsx",
+            "pkg/server/server_test.go",
+            "app/test_models.py",
+            "web/button.test.tsx",
+            "web/button.spec.js",
+            "spec/models/user_spec.rb",
+            "src/main/java/ParserTest.java",
+            "Api.Tests/ClientTests.cs",
+        ] {
+            assert!(is_test_path(Path::new(path)), "{}", path);
+        }
+        for path in [
+            "src/lib.rs",
+            "src/testing.rs",
+            "contest/main.go",
+            "latest.py",
+            "src/Attest.java",
+            "tests",
+        ] {
+            assert!(!is_test_path(Path::new(path)), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_test_chunks() {
+        let none: &[String] = &[];
+        let attr = |text: &str| vec![text.to_string()];
+
+        assert!(is_test_chunk(&attr("#[test]"), none, "fn parses() {}"));
+        assert!(is_test_chunk(
+            &attr("#[tokio::test]"),
+            none,
+            "async fn parses() {}"
+        ));
+        assert!(is_test_chunk(&attr("#[cfg(test)]"), none, "mod checks {}"));
+        assert!(is_test_chunk(
+            none,
+            &["tests".to_string()],
+            "fn helper() {}"
+        ));
+        assert!(is_test_chunk(none, none, "func TestParse(t *testing.T) {}"));
+        assert!(is_test_chunk(none, none, "async def test_login(client):"));
+        assert!(is_test_chunk(none, none, "test \"parses header\" {}"));
+        assert!(is_test_chunk(
+            &attr("[Fact]"),
+            none,
+            "public void Parses() {}"
+        ));
+        assert!(is_test_chunk(
+            none,
+            &["ParserTests".to_string()],
+            "void a() {}"
+        ));
+
+        assert!(!is_test_chunk(&attr("#[inline]"), none, "fn parse() {}"));
+        assert!(!is_test_chunk(
+            &attr("/// Parse a test header"),
+            none,
+            "fn parse() {}"
+        ));
+        assert!(!is_test_chunk(none, none, "func Testify() {}"));
+        assert!(is_test_chunk(none, none, "class TestParser:"));
+        assert!(!is_test_chunk(none, none, "class Testimony:"));
+        assert!(!is_test_chunk(
+            none,
+            &["Contest".to_string()],
+            "def testimony():"
+        ));
+    }
+}
@@ -0,0 +1,301 @@
+//! Opt-in, append-only query audit log.
+//!
+//! When enabled for a project (`.cs/audit/config.json`), every search run against it appends
+//! one JSON line to `.cs/audit/audit.log`: when it ran, the mode, who asked (CLI, TUI or the
+//! MCP client name), how many results came back, and either a BLAKE3 hash of the query or the
+//! query itself. The log rotates to `audit.log.1`, `audit.log.2`, ... once it reaches the
+//! configured size. `clean_index` leaves the audit directory in place.
+
+use anyhow::Result;
+use cs_core::{SearchMode, SearchOptions};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const AUDIT_DIR: &str = "audit";
+const CONFIG_FILE: &str = "config.json";
+const LOG_FILE: &str = "audit.log";
+
+/// How the query text is stored in each record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryStorage {
+    /// BLAKE3 hash of the query; repeated queries can be correlated without revealing them
+    Hash,
+    /// The query text itself
+    Full,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditConfig {
+    pub enabled: bool,
+    pub store_query: QueryStorage,
+    /// Rotate once the current log would grow past this many bytes
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: u64,
+    /// Number of rotated logs to keep
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+}
+
+fn default_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_keep() -> usize {
+    5
+}
+
+impl AuditConfig {
+    pub fn new(store_query: QueryStorage) -> Self {
+        Self {
+            enabled: true,
+            store_query,
+            max_bytes: default_max_bytes(),
+            keep: default_keep(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub mode: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requester: Option<String>,
+    pub results: usize,
+    pub path: String,
+}
+
+pub fn audit_dir(root: &Path) -> PathBuf {
+    root.join(".cs").join(AUDIT_DIR)
+}
+
+/// Audit settings for `root`; `None` when auditing was never configured
+pub fn load_config(root: &Path) -> Result<Option<AuditConfig>> {
+    let path = audit_dir(root).join(CONFIG_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&path)?;
+    Ok(Some(serde_json::from_str(&data)?))
+}
+
+pub fn save_config(root: &Path, config: &AuditConfig) -> Result<()> {
+    let dir = audit_dir(root);
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(CONFIG_FILE), serde_json::to_string_pretty(config)?)?;
+    Ok(())
+}
+
+/// Append a record for a finished search if auditing is enabled for `root`
+///
+/// Audit failures are logged and never fail the search itself.
+pub fn record_search(root: &Path, options: &SearchOptions, results: usize) {
+    let outcome = load_config(root).and_then(|config| match config {
+        Some(config) if config.enabled => {
+            append(root, &config, &search_record(&config, options, results))
+        }
+        _ => Ok(()),
+    });
+    if let Err(e) = outcome {
+        tracing::warn!("Failed to write audit record under {:?}: {}", root, e);
+    }
+}
+
+fn search_record(config: &AuditConfig, options: &SearchOptions, results: usize) -> AuditRecord {
+    let query = options.patterns().collect::<Vec<_>>().join("\n");
+    let (query, query_hash) = match config.store_query {
+        QueryStorage::Full => (Some(query), None),
+        QueryStorage::Hash => (
+            None,
+            Some(blake3::hash(query.as_bytes()).to_hex().to_string()),
+        ),
+    };
+    AuditRecord {
+        timestamp: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default(),
+        mode: mode_name(&options.mode).to_string(),
+        query,
+        query_hash,
+        requester: options.requester.clone(),
+        results,
+        path: options.path.display().to_string(),
+    }
+}
+
+fn mode_name(mode: &SearchMode) -> &'static str {
+    match mode {
+        SearchMode::Regex => "regex",
+        SearchMode::Lexical => "lexical",
+        SearchMode::Semantic => "semantic",
+        SearchMode::Hybrid => "hybrid",
+        SearchMode::Ast => "ast",
+        SearchMode::Symbol => "symbol",
+    }
+}
+
+/// Append one JSON line, rotating first if it would push the log past `max_bytes`
+pub fn append(root: &Path, config: &AuditConfig, record: &AuditRecord) -> Result<()> {
+    let dir = audit_dir(root);
+    fs::create_dir_all(&dir)?;
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+
+    let log_path = dir.join(LOG_FILE);
+    let current_len = fs::metadata(&log_path).map(|meta| meta.len()).unwrap_or(0);
+    if current_len > 0 && current_len + line.len() as u64 > config.max_bytes {
+        rotate(&log_path, config.keep)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Shift `<log>.N` to `<log>.N+1`, dropping the oldest beyond `keep`
+pub(crate) fn rotate(log_path: &Path, keep: usize) -> Result<()> {
+    if keep == 0 {
+        fs::remove_file(log_path)?;
+        return Ok(());
+    }
+    let rotated = |n: usize| {
+        let mut name = log_path.as_os_str().to_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    };
+    if rotated(keep).exists() {
+        fs::remove_file(rotated(keep))?;
+    }
+    for n in (1..keep).rev() {
+        if rotated(n).exists() {
+            fs::rename(rotated(n), rotated(n + 1))?;
+        }
+    }
+    fs::rename(log_path, rotated(1))?;
+    Ok(())
+}
+
+/// The last `count` records, oldest first, reading into rotated logs when needed
+pub fn tail(root: &Path, count: usize) -> Result<Vec<AuditRecord>> {
+    let dir = audit_dir(root);
+    let mut logs = vec![dir.join(LOG_FILE)];
+    let mut n = 1;
+    while dir.join(format!("{}.{}", LOG_FILE, n)).exists() {
+        logs.push(dir.join(format!("{}.{}", LOG_FILE, n)));
+        n += 1;
+    }
+
+    // Newest log first; each log is read whole and its lines prepended
+    let mut records = Vec::new();
+    for log in logs {
+        if records.len() >= count {
+            break;
+        }
+        let Ok(data) = fs::read_to_string(&log) else {
+            continue;
+        };
+        let mut older = data
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<AuditRecord>, _>>()?;
+        older.append(&mut records);
+        records = older;
+    }
+
+    let skip = records.len().saturating_sub(count);
+    Ok(records.split_off(skip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn options(query: &str) -> SearchOptions {
+        SearchOptions {
+            mode: SearchMode::Semantic,
+            query: query.to_string(),
+            requester: Some("mcp:test-agent".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_disabled_until_configured() {
+        let temp_dir = TempDir::new().unwrap();
+        record_search(temp_dir.path(), &options("secret"), 3);
+        assert!(!audit_dir(temp_dir.path()).exists());
+
+        let mut config = AuditConfig::new(QueryStorage::Full);
+        config.enabled = false;
+        save_config(temp_dir.path(), &config).unwrap();
+        record_search(temp_dir.path(), &options("secret"), 3);
+        assert!(tail(temp_dir.path(), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_records_hash_or_full_query() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        save_config(root, &AuditConfig::new(QueryStorage::Hash)).unwrap();
+        record_search(root, &options("auth flow"), 4);
+        save_config(root, &AuditConfig::new(QueryStorage::Full)).unwrap();
+        record_search(root, &options("auth flow"), 2);
+
+        let records = tail(root, 10).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].query, None);
+        assert_eq!(
+            records[0].query_hash.as_deref(),
+            Some(blake3::hash(b"auth flow").to_hex().as_str())
+        );
+        assert_eq!(records[0].mode, "semantic");
+        assert_eq!(records[0].requester.as_deref(), Some("mcp:test-agent"));
+        assert_eq!(records[0].results, 4);
+        assert_eq!(records[1].query.as_deref(), Some("auth flow"));
+        assert_eq!(records[1].query_hash, None);
+    }
+
+    #[test]
+    fn test_rotation_keeps_newest_logs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let config = AuditConfig {
+            max_bytes: 1,
+            keep: 2,
+            ..AuditConfig::new(QueryStorage::Full)
+        };
+        save_config(root, &config).unwrap();
+
+        for query in ["one", "two", "three", "four"] {
+            record_search(root, &options(query), 0);
+        }
+
+        // One record per file: the current log plus two rotated ones
+        let dir = audit_dir(root);
+        assert!(dir.join("audit.log.2").exists());
+        assert!(!dir.join("audit.log.3").exists());
+        let queries: Vec<_> = tail(root, 10)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.query.unwrap())
+            .collect();
+        assert_eq!(queries, vec!["two", "three", "four"]);
+        assert_eq!(tail(root, 1).unwrap()[0].query.as_deref(), Some("four"));
+    }
+}
@@ -0,0 +1,352 @@
+//! Index and search performance on the current tree (`cs --bench`).
+//!
+//! Every file is chunked as indexing would chunk it, a sample of the chunks is embedded, an
+//! ANN index is built and generated queries are embedded and searched one at a time. Nothing
+//! is written; the JSON report carries the version and model so runs can be compared across
+//! releases and backends.
+
+use super::{
+    apply_index_file_limits,
+    calibrate::{sample_evenly, signature_query},
+    chunk_overrides, collect_files, embed_template, embedding_input, load_index_entry,
+    load_or_create_manifest, normalize_manifest_paths, path_utils,
+};
+use anyhow::Result;
+use cs_ann::{AnnIndex, Int8Index, SimpleIndex};
+use cs_core::Quantization;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Instant;
+
+/// Chunks embedded for the embedding throughput when `--bench-sample` is not given
+pub const DEFAULT_EMBED_SAMPLE: usize = 256;
+/// Queries timed for the latency percentiles
+const QUERIES: usize = 50;
+/// Chunks per embedder call
+const EMBED_BATCH: usize = 32;
+/// Results fetched per query
+const QUERY_TOP_K: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    /// Version of cs that ran the benchmark
+    pub version: String,
+    pub model: String,
+    pub dimensions: usize,
+    /// `simple`, or `int8` when `cs.toml` configures quantization
+    pub ann_backend: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// Time to create the embedder, including loading a local model
+    pub model_load_seconds: f64,
+    pub chunking: ChunkingBench,
+    pub embedding: EmbeddingBench,
+    pub ann_build: AnnBuildBench,
+    pub query_latency: LatencyBench,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChunkingBench {
+    pub chunks: usize,
+    pub seconds: f64,
+    pub files_per_sec: f64,
+    pub chunks_per_sec: f64,
+    pub mb_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EmbeddingBench {
+    /// Chunks embedded, spread evenly over the tree
+    pub chunks: usize,
+    /// Estimated tokens in those chunks
+    pub tokens: usize,
+    pub seconds: f64,
+    pub chunks_per_sec: f64,
+    pub tokens_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AnnBuildBench {
+    pub vectors: usize,
+    /// `index` when built from the existing index's embeddings, `sample` from the
+    /// benchmark's own
+    pub source: String,
+    pub seconds: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencyBench {
+    pub queries: usize,
+    pub top_k: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Benchmark the tree at `path` with `model`: the index's model when `None` and the tree is
+/// indexed, otherwise the default
+pub fn bench(
+    path: &Path,
+    model: Option<&str>,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+    embed_sample: usize,
+) -> Result<BenchReport> {
+    let indexed_model = model
+        .is_none()
+        .then(|| indexed_embedding_model(path))
+        .flatten();
+    let (_, model_config) =
+        cs_models::ModelRegistry::default().resolve(model.or(indexed_model.as_deref()))?;
+
+    let start = Instant::now();
+    let mut embedder = cs_embed::create_embedder(Some(model_config.name.as_str()))?;
+    let model_load_seconds = start.elapsed().as_secs_f64();
+
+    let mut report = bench_with_embedder(
+        path,
+        &mut embedder,
+        respect_gitignore,
+        exclude_patterns,
+        embed_sample,
+    )?;
+    report.model_load_seconds = model_load_seconds;
+    Ok(report)
+}
+
+fn bench_with_embedder(
+    path: &Path,
+    embedder: &mut Box<dyn cs_embed::Embedder>,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+    embed_sample: usize,
+) -> Result<BenchReport> {
+    let files = collect_files(path, respect_gitignore, exclude_patterns)?;
+    let (files, _) = apply_index_file_limits(files);
+    let model = embedder.model_name().to_string();
+
+    // Chunking, with the embedded text of every chunk kept for sampling
+    let overrides = chunk_overrides(path);
+    let template = embed_template(path);
+    let start = Instant::now();
+    let per_file: Vec<(u64, Vec<String>)> = files
+        .par_iter()
+        .filter_map(|file| {
+            let content = cs_core::encoding::read_to_string(file).ok()?;
+            let relative = path_utils::to_manifest_path(&path_utils::to_standard_path(file, path));
+            let chunks = cs_chunk::chunk_text_with_overrides(
+                &content,
+                cs_core::Language::from_path(file),
+                Some(&model),
+                &overrides,
+            )
+            .unwrap_or_default();
+            let texts = chunks
+                .iter()
+                .map(|chunk| embedding_input(chunk, &relative, template.as_ref()))
+                .collect();
+            Some((content.len() as u64, texts))
+        })
+        .collect();
+    let chunking_seconds = start.elapsed().as_secs_f64();
+
+    let bytes: u64 = per_file.iter().map(|(bytes, _)| bytes).sum();
+    let texts: Vec<String> = per_file
+        .into_iter()
+        .flat_map(|(_, texts)| texts)
+        .filter(|text| !text.trim().is_empty())
+        .collect();
+    if texts.is_empty() {
+        anyhow::bail!("No chunks to benchmark under {}", path.display());
+    }
+    let files_read = files.len();
+    let chunking = ChunkingBench {
+        chunks: texts.len(),
+        seconds: chunking_seconds,
+        files_per_sec: per_second(files_read as f64, chunking_seconds),
+        chunks_per_sec: per_second(texts.len() as f64, chunking_seconds),
+        mb_per_sec: per_second(bytes as f64 / 1_000_000.0, chunking_seconds),
+    };
+
+    // Embedding throughput over a sample spread across the tree
+    let sample = sample_evenly(&texts, embed_sample.max(1));
+    let tokens = sample
+        .iter()
+        .map(|text| cs_chunk::TokenEstimator::estimate_tokens(text))
+        .sum();
+    let start = Instant::now();
+    let mut vectors: Vec<Vec<f32>> = Vec::with_capacity(sample.len());
+    for batch in sample.chunks(EMBED_BATCH) {
+        vectors.extend(embedder.embed(batch)?);
+    }
+    let embedding_seconds = start.elapsed().as_secs_f64();
+    let embedding = EmbeddingBench {
+        chunks: sample.len(),
+        tokens,
+        seconds: embedding_seconds,
+        chunks_per_sec: per_second(sample.len() as f64, embedding_seconds),
+        tokens_per_sec: per_second(tokens as f64, embedding_seconds),
+    };
+
+    // ANN build over the whole index when it holds this model's vectors, so the build time
+    // reflects the real corpus
+    let dimensions = embedder.dim();
+    let (ann_vectors, source) = match indexed_embeddings(path, &model, dimensions) {
+        Some(indexed) if !indexed.is_empty() => (indexed, "index"),
+        _ => (vectors, "sample"),
+    };
+    let quantization = cs_models::ProjectConfig::discover(path)
+        .ok()
+        .flatten()
+        .and_then(|config| config.index_backend)
+        .map(|backend| backend.quantization)
+        .unwrap_or_default();
+    let start = Instant::now();
+    let (ann, ann_backend): (Box<dyn AnnIndex>, &str) = match quantization {
+        Quantization::Int8 => (Box::new(Int8Index::build(&ann_vectors)?), "int8"),
+        Quantization::None => (Box::new(SimpleIndex::build(&ann_vectors)?), "simple"),
+    };
+    let ann_build = AnnBuildBench {
+        vectors: ann_vectors.len(),
+        source: source.to_string(),
+        seconds: start.elapsed().as_secs_f64(),
+    };
+
+    // Query latency: embed one query and search, as a semantic search does
+    let queries: Vec<String> = sample_evenly(&texts, QUERIES)
+        .iter()
+        .map(|text| {
+            signature_query(text).unwrap_or_else(|| {
+                text.split_whitespace()
+                    .take(8)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+        })
+        .collect();
+    let mut latencies = Vec::with_capacity(queries.len());
+    for query in &queries {
+        let start = Instant::now();
+        let embedding = embedder
+            .embed(std::slice::from_ref(query))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Embedder returned no vector for {:?}", query))?;
+        ann.search(&embedding, QUERY_TOP_K)?;
+        latencies.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        model,
+        dimensions,
+        ann_backend: ann_backend.to_string(),
+        files: files_read,
+        bytes,
+        model_load_seconds: 0.0,
+        chunking,
+        embedding,
+        ann_build,
+        query_latency: latency(&mut latencies),
+    })
+}
+
+/// Embedding model recorded in the index at `path`, if there is one
+fn indexed_embedding_model(path: &Path) -> Option<String> {
+    let manifest_path = path.join(".cs").join("manifest.json");
+    if !manifest_path.exists() {
+        return None;
+    }
+    load_or_create_manifest(&manifest_path)
+        .ok()?
+        .embedding_model
+}
+
+/// Every embedding of `model` in the index at `path`
+fn indexed_embeddings(path: &Path, model: &str, dimensions: usize) -> Option<Vec<Vec<f32>>> {
+    let manifest_path = path.join(".cs").join("manifest.json");
+    if !manifest_path.exists() {
+        return None;
+    }
+    let mut manifest = load_or_create_manifest(&manifest_path).ok()?;
+    if manifest.embedding_model.as_deref() != Some(model) {
+        return None;
+    }
+    normalize_manifest_paths(&mut manifest, path);
+    let vectors = manifest
+        .files
+        .keys()
+        .filter_map(|manifest_path| {
+            let file = path.join(path_utils::from_manifest_path(manifest_path));
+            load_index_entry(&cs_core::get_sidecar_path(path, &file)).ok()
+        })
+        .flat_map(|entry| entry.chunks)
+        .filter_map(|chunk| chunk.embedding)
+        .filter(|embedding| embedding.len() == dimensions)
+        .collect();
+    Some(vectors)
+}
+
+fn per_second(amount: f64, seconds: f64) -> f64 {
+    if seconds > 0.0 { amount / seconds } else { 0.0 }
+}
+
+fn latency(latencies: &mut [f64]) -> LatencyBench {
+    if latencies.is_empty() {
+        return LatencyBench::default();
+    }
+    latencies.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let percentile = |p: f64| latencies[((latencies.len() - 1) as f64 * p).round() as usize];
+    LatencyBench {
+        queries: latencies.len(),
+        top_k: QUERY_TOP_K,
+        mean_ms: latencies.iter().sum::<f64>() / latencies.len() as f64,
+        p50_ms: percentile(0.5),
+        p90_ms: percentile(0.9),
+        p99_ms: percentile(0.99),
+        max_ms: latencies[latencies.len() - 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_bench_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let function =
+            "fn handle_request(request: Request) -> Response {\n    route(request)\n}\n\n";
+        fs::write(root.join("lib.rs"), function.repeat(40)).unwrap();
+        fs::write(
+            root.join("notes.md"),
+            "Retry failed requests with backoff.\n",
+        )
+        .unwrap();
+
+        let mut embedder: Box<dyn cs_embed::Embedder> =
+            Box::new(cs_embed::DummyEmbedder::new_with_model("dummy"));
+        let report = bench_with_embedder(root, &mut embedder, true, &[], 8).unwrap();
+
+        assert!(!root.join(".cs").exists());
+        assert_eq!(report.files, 2);
+        assert_eq!(report.model, "dummy");
+        assert!(report.chunking.chunks > 8);
+        assert_eq!(report.embedding.chunks, 8);
+        assert!(report.embedding.tokens > 0);
+        assert_eq!(report.ann_build.source, "sample");
+        assert_eq!(report.ann_build.vectors, 8);
+        assert_eq!(
+            report.query_latency.queries,
+            report.chunking.chunks.min(QUERIES)
+        );
+        assert!(report.query_latency.p50_ms <= report.query_latency.p99_ms);
+        assert!(report.query_latency.p99_ms <= report.query_latency.max_ms);
+    }
+}
@@ -0,0 +1,412 @@
+//! Index bundles for reusing an index on another machine, such as a CI runner.
+//!
+//! A bundle is a zstd-compressed tar of the manifest, the sidecar of every indexed file and the
+//! extracted text of indexed PDFs, led by `bundle.json` with the format version, the embedding
+//! model and the chunk settings. Everything in it is keyed by paths relative to the index root,
+//! so a bundle exported from one checkout imports into another checkout of the same repository
+//! wherever it lives. The lexical `tantivy_index` stores paths as they were spelled when it was
+//! built, so it stays out and is rebuilt on the first lexical search. Import checks the bundle
+//! against the project and unpacks it beside the index, checking every entry and that the
+//! manifest has its sidecars, before it replaces the index with the bundle's. It then counts
+//! the files that differ from the checkout; the next update re-indexes just those.
+
+use super::{
+    IndexManifest, chunk_overrides, clean_index_keeping, embed_template, load_or_create_manifest,
+    normalize_manifest_paths, path_utils, save_manifest, secrets_allowed, sparse_vectors,
+};
+use anyhow::Result;
+use cs_chunk::ChunkOverrides;
+use cs_core::compute_file_hash;
+use cs_core::embed_template::EmbedTemplate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Version of the bundle layout; import refuses newer ones
+pub const BUNDLE_FORMAT: u32 = 1;
+
+const HEADER_FILE: &str = "bundle.json";
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// `bundle.json`, the first entry of every bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleHeader {
+    pub format: u32,
+    /// cs version that wrote the bundle
+    pub cs_version: String,
+    pub created: u64,
+    pub embedding_model: Option<String>,
+    pub embedding_dimensions: Option<usize>,
+    #[serde(default)]
+    pub chunking: ChunkOverrides,
+    #[serde(default)]
+    pub embed_template: Option<EmbedTemplate>,
+    #[serde(default)]
+    pub sparse_vectors: bool,
+    #[serde(default)]
+    pub secrets_allowed: bool,
+    pub files: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BundleStats {
+    pub files: usize,
+    pub embedding_model: Option<String>,
+    /// Size of the bundle file
+    pub bytes: u64,
+    /// On import, indexed files missing from the checkout or changed in it
+    pub files_stale: usize,
+}
+
+/// Write the index at `repo_root` to a bundle at `bundle_path`
+pub fn export_bundle(repo_root: &Path, bundle_path: &Path) -> Result<BundleStats> {
+    let index_dir = repo_root.join(".cs");
+    let manifest_path = index_dir.join(MANIFEST_FILE);
+    if !manifest_path.exists() {
+        anyhow::bail!(
+            "No index at {}; run 'cs --index' first",
+            repo_root.display()
+        );
+    }
+    let mut manifest = load_or_create_manifest(&manifest_path)?;
+    normalize_manifest_paths(&mut manifest, repo_root);
+
+    let header = BundleHeader {
+        format: BUNDLE_FORMAT,
+        cs_version: env!("CARGO_PKG_VERSION").to_string(),
+        created: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        embedding_model: manifest.embedding_model.clone(),
+        embedding_dimensions: manifest.embedding_dimensions,
+        chunking: manifest.chunking,
+        embed_template: manifest.embed_template.clone(),
+        sparse_vectors: manifest.sparse_vectors,
+        secrets_allowed: manifest.secrets_allowed,
+        files: manifest.files.len(),
+    };
+
+    let file = fs::File::create(bundle_path)
+        .map_err(|e| anyhow::anyhow!("Failed to create bundle {}: {}", bundle_path.display(), e))?;
+    let mut archive = tar::Builder::new(zstd::Encoder::new(file, 0)?.auto_finish());
+    append_bytes(
+        &mut archive,
+        HEADER_FILE,
+        &serde_json::to_vec_pretty(&header)?,
+    )?;
+
+    // Manifest last, so an import cut short never leaves a manifest without its sidecars
+    let mut keys: Vec<&PathBuf> = manifest.files.keys().collect();
+    keys.sort();
+    for key in keys {
+        let standard_path = path_utils::from_manifest_path(key);
+        let sidecar = path_utils::get_sidecar_path_for_standard_path(&index_dir, &standard_path);
+        if !sidecar.exists() {
+            anyhow::bail!(
+                "The index at {} has no sidecar for {}; run 'cs --clean-orphans' and 'cs --index' first",
+                repo_root.display(),
+                standard_path.display()
+            );
+        }
+        archive.append_path_with_name(&sidecar, sidecar.strip_prefix(&index_dir)?)?;
+        if cs_core::pdf::is_pdf_file(&standard_path) {
+            let cache =
+                cs_core::pdf::get_content_cache_path(repo_root, &repo_root.join(&standard_path));
+            if cache.exists() {
+                archive.append_path_with_name(&cache, cache.strip_prefix(&index_dir)?)?;
+            }
+        }
+    }
+    append_bytes(
+        &mut archive,
+        MANIFEST_FILE,
+        &serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    archive.into_inner()?;
+
+    Ok(BundleStats {
+        files: header.files,
+        embedding_model: header.embedding_model,
+        bytes: fs::metadata(bundle_path)?.len(),
+        files_stale: 0,
+    })
+}
+
+/// Replace the index at `repo_root` with the bundle at `bundle_path`. With `model`, the
+/// bundle must have been embedded with that model; its chunk settings, embed template and
+/// sparse vectors must match the project's, or the next update would rebuild the index anyway.
+pub fn import_bundle(
+    repo_root: &Path,
+    bundle_path: &Path,
+    model: Option<&str>,
+) -> Result<BundleStats> {
+    let file = fs::File::open(bundle_path)
+        .map_err(|e| anyhow::anyhow!("Failed to open bundle {}: {}", bundle_path.display(), e))?;
+    let mut archive = tar::Archive::new(zstd::Decoder::new(file)?);
+    let mut entries = archive.entries()?;
+
+    let not_a_bundle = || anyhow::anyhow!("Not an index bundle: {}", bundle_path.display());
+    let mut first = entries.next().ok_or_else(not_a_bundle)??;
+    if first.path()?.as_ref() != Path::new(HEADER_FILE) {
+        return Err(not_a_bundle());
+    }
+    let mut data = Vec::new();
+    first.read_to_end(&mut data)?;
+    let header: BundleHeader = serde_json::from_slice(&data)?;
+    check_compatible(repo_root, &header, model)?;
+
+    // Unpack next to the index and check everything before replacing it, so a bad or
+    // truncated bundle leaves the index as it was
+    let index_dir = repo_root.join(".cs");
+    fs::create_dir_all(&index_dir)?;
+    let staging = tempfile::Builder::new()
+        .prefix(".bundle-import")
+        .tempdir_in(&index_dir)?;
+    let mut manifest: Option<IndexManifest> = None;
+    for entry in entries {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            anyhow::bail!("Bundle entry outside the index: {}", path.display());
+        }
+        let entry_type = entry.header().entry_type();
+        if !entry_type.is_file() && !entry_type.is_dir() {
+            anyhow::bail!(
+                "Bundle entry is not a file or directory: {}",
+                path.display()
+            );
+        }
+        if path == Path::new(MANIFEST_FILE) {
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            manifest = Some(serde_json::from_slice(&data)?);
+        } else {
+            entry.unpack_in(staging.path())?;
+        }
+    }
+    let Some(mut manifest) = manifest else {
+        anyhow::bail!(
+            "Bundle {} is incomplete: it has no manifest",
+            bundle_path.display()
+        );
+    };
+    for key in manifest.files.keys() {
+        let standard_path = path_utils::from_manifest_path(key);
+        if !path_utils::get_sidecar_path_for_standard_path(staging.path(), &standard_path).exists()
+        {
+            anyhow::bail!(
+                "Bundle {} is incomplete: it has no sidecar for {}",
+                bundle_path.display(),
+                standard_path.display()
+            );
+        }
+    }
+
+    clean_index_keeping(repo_root, Some(staging.path()))?;
+    move_into(staging.path(), &index_dir)?;
+
+    let files_stale = manifest
+        .files
+        .iter()
+        .filter(|(key, metadata)| {
+            let file = repo_root.join(path_utils::from_manifest_path(key));
+            compute_file_hash(&file).map_or(true, |hash| hash != metadata.hash)
+        })
+        .count();
+    // A new epoch, so caches of the replaced index are not reused
+    manifest.updated = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    save_manifest(&index_dir.join(MANIFEST_FILE), &manifest)?;
+
+    Ok(BundleStats {
+        files: manifest.files.len(),
+        embedding_model: manifest.embedding_model,
+        bytes: fs::metadata(bundle_path)?.len(),
+        files_stale,
+    })
+}
+
+fn check_compatible(repo_root: &Path, header: &BundleHeader, model: Option<&str>) -> Result<()> {
+    if header.format > BUNDLE_FORMAT {
+        anyhow::bail!(
+            "The bundle uses format {} but this cs ({}) reads up to format {}; upgrade cs",
+            header.format,
+            env!("CARGO_PKG_VERSION"),
+            BUNDLE_FORMAT
+        );
+    }
+    if let Some(bundled) = &header.embedding_model {
+        let (_, config) = cs_models::ModelRegistry::default()
+            .resolve(Some(bundled))
+            .map_err(|e| anyhow::anyhow!("The bundle's model is not available: {}", e))?;
+        if let Some(dims) = header.embedding_dimensions
+            && dims != config.dimensions
+        {
+            anyhow::bail!(
+                "The bundle's {} embeddings have {} dimensions but the model has {}",
+                bundled,
+                dims,
+                config.dimensions
+            );
+        }
+    }
+    if let Some(model) = model
+        && header.embedding_model.as_deref() != Some(model)
+    {
+        anyhow::bail!(
+            "The bundle was embedded with {} but this project uses {}",
+            header.embedding_model.as_deref().unwrap_or("no model"),
+            model
+        );
+    }
+    let mismatch = [
+        (
+            header.chunking != chunk_overrides(repo_root),
+            "chunk settings",
+        ),
+        (
+            header.embed_template != embed_template(repo_root),
+            "embed template",
+        ),
+        (
+            header.sparse_vectors != sparse_vectors(repo_root),
+            "sparse vector setting",
+        ),
+        (
+            header.secrets_allowed != secrets_allowed(repo_root),
+            "secret scanning setting",
+        ),
+    ]
+    .into_iter()
+    .find_map(|(differs, setting)| differs.then_some(setting));
+    if let Some(setting) = mismatch {
+        anyhow::bail!(
+            "The bundle was built with other {} than this project uses; rebuild it with the project's settings",
+            setting
+        );
+    }
+    Ok(())
+}
+
+/// Move the contents of `from` into `to`, merging directories that exist in both
+fn move_into(from: &Path, to: &Path) -> Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() && target.is_dir() {
+            move_into(&entry.path(), &target)?;
+        } else {
+            fs::rename(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn append_bytes<W: std::io::Write>(
+    archive: &mut tar::Builder<W>,
+    name: &str,
+    data: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_bundle_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("ci");
+        let target = temp_dir.path().join("checkout");
+        for root in [&source, &target] {
+            fs::create_dir_all(root.join("src")).unwrap();
+            fs::write(root.join("src/lib.rs"), "fn one() {}\n").unwrap();
+            fs::write(root.join("README.md"), "# Notes\n").unwrap();
+        }
+        fs::write(target.join("README.md"), "# Edited notes\n").unwrap();
+        super::super::smart_update_index(&source, false, true, &[])
+            .await
+            .unwrap();
+
+        let bundle = temp_dir.path().join("index.tar.zst");
+        let exported = export_bundle(&source, &bundle).unwrap();
+        assert_eq!(exported.files, 2);
+        assert!(exported.bytes > 0);
+
+        let imported = import_bundle(&target, &bundle, None).unwrap();
+        assert_eq!((imported.files, imported.files_stale), (2, 1));
+        assert!(target.join(".cs/src/lib.rs.cs").exists());
+        let stats = super::super::get_index_stats(&target).unwrap();
+        assert_eq!(stats.total_files, 2);
+
+        // Only the edited file is re-indexed
+        let stats = super::super::smart_update_index(&target, false, true, &[])
+            .await
+            .unwrap();
+        assert_eq!((stats.files_modified, stats.files_up_to_date), (1, 1));
+
+        let err = import_bundle(&target, &bundle, Some("BAAI/bge-small-en-v1.5")).unwrap_err();
+        assert!(err.to_string().contains("this project uses"), "{}", err);
+        assert!(import_bundle(&target, &target.join("README.md"), None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bad_bundle_keeps_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("repo");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "fn one() {}\n").unwrap();
+        super::super::smart_update_index(&root, false, true, &[])
+            .await
+            .unwrap();
+        let bundle = temp_dir.path().join("index.tar.zst");
+        export_bundle(&root, &bundle).unwrap();
+        let data = fs::read(&bundle).unwrap();
+
+        // Cut off after the header
+        let truncated = temp_dir.path().join("truncated.tar.zst");
+        fs::write(&truncated, &data[..data.len() / 2]).unwrap();
+
+        // The header alone, with no sidecars or manifest
+        let mut archive = tar::Archive::new(zstd::Decoder::new(&data[..]).unwrap());
+        let mut header = Vec::new();
+        let mut first = archive.entries().unwrap().next().unwrap().unwrap();
+        first.read_to_end(&mut header).unwrap();
+        let header_only = temp_dir.path().join("header-only.tar.zst");
+        let file = fs::File::create(&header_only).unwrap();
+        let mut builder = tar::Builder::new(zstd::Encoder::new(file, 0).unwrap().auto_finish());
+        append_bytes(&mut builder, HEADER_FILE, &header).unwrap();
+        builder.into_inner().unwrap();
+
+        for bad in [&truncated, &header_only] {
+            assert!(import_bundle(&root, bad, None).is_err());
+            assert!(root.join(".cs/src/lib.rs.cs").exists());
+            assert!(root.join(".cs/manifest.json").exists());
+            let leftovers = fs::read_dir(root.join(".cs"))
+                .unwrap()
+                .filter(|entry| {
+                    let name = entry.as_ref().unwrap().file_name();
+                    name.to_string_lossy().starts_with(".bundle-import")
+                })
+                .count();
+            assert_eq!(leftovers, 0);
+        }
+        let stats = super::super::get_index_stats(&root).unwrap();
+        assert_eq!(stats.total_files, 1);
+    }
+}
@@ -0,0 +1,503 @@
+//! Threshold calibration for the index's embedding model (`cs --calibrate`).
+//!
+//! Queries with a known answer file are run against every chunk embedding in the index: either
+//! user-provided `query<TAB>path` lines (the `--shadow-queries` format) or queries generated
+//! from the signature line of chunks sampled across the index. The cosine scores of the
+//! expected file and of everything else that ranks above it show where this model separates
+//! relevant from irrelevant code, which is where `--threshold` belongs.
+
+use super::{
+    IndexManifest, load_index_entry, load_or_create_manifest, normalize_manifest_paths, path_utils,
+    shadow::ShadowQuery, sparse,
+};
+use anyhow::Result;
+use cs_core::compute_file_hash;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Queries generated when none are given
+pub const DEFAULT_SAMPLES: usize = 50;
+/// Cut-offs reported in `hit_rates`, up to the `top_k` asked for
+const HIT_RATE_CUTOFFS: [usize; 6] = [1, 3, 5, 10, 20, 50];
+/// The suggested top-k keeps this share of the hits found at the largest cut-off
+const TOP_K_RECALL: f32 = 0.95;
+/// Words too common in signatures to say anything about a chunk
+const SIGNATURE_NOISE: &[&str] = &[
+    "async",
+    "class",
+    "const",
+    "def",
+    "export",
+    "fn",
+    "func",
+    "function",
+    "impl",
+    "let",
+    "mut",
+    "private",
+    "protected",
+    "pub",
+    "public",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "this",
+    "var",
+    "void",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibrationReport {
+    pub model: String,
+    /// Chunks with embeddings the queries ran against
+    pub chunks: usize,
+    pub files: usize,
+    /// Whether the queries were generated from sampled chunks rather than given
+    pub generated: bool,
+    pub queries: Vec<CalibrationQuery>,
+    /// Best score in the expected file, per query whose expected file is indexed
+    pub expected_scores: ScoreDistribution,
+    /// Scores of chunks from other files that made the top `top_k`
+    pub other_scores: ScoreDistribution,
+    /// Share of queries with the expected file within each cut-off
+    pub hit_rates: Vec<HitRate>,
+    /// Threshold that best separates expected-file scores from the others
+    pub suggested_threshold: Option<f32>,
+    /// Smallest cut-off keeping nearly all the hits of the largest one
+    pub suggested_top_k: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibrationQuery {
+    pub query: String,
+    pub expected: Option<String>,
+    /// 1-based rank of the first chunk in the expected file
+    pub rank: Option<usize>,
+    pub expected_score: Option<f32>,
+    pub top_score: Option<f32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScoreDistribution {
+    pub count: usize,
+    pub min: Option<f32>,
+    pub p10: Option<f32>,
+    pub p25: Option<f32>,
+    pub median: Option<f32>,
+    pub p75: Option<f32>,
+    pub p90: Option<f32>,
+    pub max: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HitRate {
+    pub top_k: usize,
+    pub hit_rate: f32,
+}
+
+struct CalibrationChunk {
+    file: usize,
+    embedding: Vec<f32>,
+}
+
+/// Calibrate the index at `repo_root` with `queries`, or with `samples` queries generated from
+/// its chunks when `None`, ranking up to `top_k` chunks per query
+pub fn calibrate(
+    repo_root: &Path,
+    queries: Option<Vec<ShadowQuery>>,
+    samples: usize,
+    top_k: usize,
+) -> Result<CalibrationReport> {
+    let manifest = load_manifest(repo_root)?;
+    let model = manifest.embedding_model.clone().ok_or_else(|| {
+        anyhow::anyhow!("The index has no embedding model recorded; run 'cs --index' first")
+    })?;
+    let mut embedder = cs_embed::create_embedder(Some(model.as_str()))?;
+    calibrate_with_embedder(repo_root, &mut embedder, queries, samples, top_k)
+}
+
+fn calibrate_with_embedder(
+    repo_root: &Path,
+    embedder: &mut Box<dyn cs_embed::Embedder>,
+    queries: Option<Vec<ShadowQuery>>,
+    samples: usize,
+    top_k: usize,
+) -> Result<CalibrationReport> {
+    let manifest = load_manifest(repo_root)?;
+    let mut entries: Vec<_> = manifest.files.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let dim = embedder.dim();
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut chunks: Vec<CalibrationChunk> = Vec::new();
+    // Signature lines of current files, for generated queries
+    let mut signatures: Vec<(usize, String)> = Vec::new();
+    for (manifest_path, metadata) in entries {
+        let standard_path = path_utils::from_manifest_path(manifest_path);
+        let file_path = repo_root.join(&standard_path);
+        let Ok(entry) = load_index_entry(&cs_core::get_sidecar_path(repo_root, &file_path)) else {
+            continue;
+        };
+        let content = match compute_file_hash(&file_path) {
+            Ok(hash) if hash == metadata.hash => cs_core::encoding::read_to_string(&file_path).ok(),
+            _ => None,
+        };
+
+        let file = files.len();
+        let before = chunks.len();
+        for chunk in entry.chunks {
+            let Some(embedding) = chunk.embedding.filter(|embedding| embedding.len() == dim) else {
+                continue;
+            };
+            if let Some(query) = content
+                .as_deref()
+                .and_then(|text| text.get(chunk.span.byte_start..chunk.span.byte_end))
+                .and_then(signature_query)
+            {
+                signatures.push((file, query));
+            }
+            chunks.push(CalibrationChunk { file, embedding });
+        }
+        if chunks.len() > before {
+            files.push(standard_path);
+        }
+    }
+    if chunks.is_empty() {
+        anyhow::bail!(
+            "The index at {} has no embeddings for {}; run 'cs --index' first",
+            repo_root.display(),
+            embedder.model_name()
+        );
+    }
+
+    let generated = queries.is_none();
+    let queries: Vec<(ShadowQuery, Option<usize>)> = match queries {
+        Some(queries) => queries
+            .into_iter()
+            .map(|query| {
+                let file = query
+                    .expected
+                    .as_deref()
+                    .and_then(|expected| expected_file(&files, expected));
+                (query, file)
+            })
+            .collect(),
+        None => sample_evenly(&signatures, samples)
+            .into_iter()
+            .map(|(file, query)| {
+                (
+                    ShadowQuery {
+                        query,
+                        expected: Some(files[file].to_string_lossy().to_string()),
+                    },
+                    Some(file),
+                )
+            })
+            .collect(),
+    };
+    if queries.is_empty() {
+        anyhow::bail!("No queries to calibrate with");
+    }
+
+    let texts: Vec<String> = queries
+        .iter()
+        .map(|(query, _)| query.query.clone())
+        .collect();
+    let embeddings = embedder.embed(&texts)?;
+
+    let mut reports = Vec::with_capacity(queries.len());
+    let mut expected_scores = Vec::new();
+    let mut other_scores = Vec::new();
+    for ((query, expected), embedding) in queries.into_iter().zip(embeddings) {
+        let mut scored: Vec<(f32, usize)> = chunks
+            .par_iter()
+            .map(|chunk| (cosine(&embedding, &chunk.embedding), chunk.file))
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let rank = expected.and_then(|file| scored.iter().position(|(_, f)| *f == file));
+        let expected_score = rank.map(|position| scored[position].0);
+        if let Some(score) = expected_score {
+            expected_scores.push(score);
+        }
+        if expected.is_some() {
+            other_scores.extend(
+                scored
+                    .iter()
+                    .take(top_k)
+                    .filter(|(_, file)| Some(*file) != expected)
+                    .map(|(score, _)| *score),
+            );
+        }
+        reports.push(CalibrationQuery {
+            query: query.query,
+            expected: query.expected,
+            rank: rank.map(|position| position + 1),
+            expected_score,
+            top_score: scored.first().map(|(score, _)| *score),
+        });
+    }
+
+    let judged = reports
+        .iter()
+        .filter(|query| query.expected.is_some())
+        .count();
+    let hit_rates: Vec<HitRate> = if judged == 0 {
+        Vec::new()
+    } else {
+        HIT_RATE_CUTOFFS
+            .iter()
+            .copied()
+            .filter(|cutoff| *cutoff < top_k)
+            .chain(std::iter::once(top_k))
+            .map(|cutoff| HitRate {
+                top_k: cutoff,
+                hit_rate: reports
+                    .iter()
+                    .filter(|query| query.rank.is_some_and(|rank| rank <= cutoff))
+                    .count() as f32
+                    / judged as f32,
+            })
+            .collect()
+    };
+    let suggested_top_k = hit_rates
+        .last()
+        .filter(|last| last.hit_rate > 0.0)
+        .and_then(|last| {
+            hit_rates
+                .iter()
+                .find(|rate| rate.hit_rate >= last.hit_rate * TOP_K_RECALL)
+                .map(|rate| rate.top_k)
+        });
+
+    Ok(CalibrationReport {
+        model: embedder.model_name().to_string(),
+        chunks: chunks.len(),
+        files: files.len(),
+        generated,
+        queries: reports,
+        suggested_threshold: suggest_threshold(&expected_scores, &other_scores),
+        expected_scores: distribution(&mut expected_scores),
+        other_scores: distribution(&mut other_scores),
+        hit_rates,
+        suggested_top_k,
+    })
+}
+
+fn load_manifest(repo_root: &Path) -> Result<IndexManifest> {
+    let manifest_path = repo_root.join(".cs").join("manifest.json");
+    if !manifest_path.exists() {
+        anyhow::bail!(
+            "No index found at {}; run 'cs --index' first",
+            repo_root.display()
+        );
+    }
+    let mut manifest = load_or_create_manifest(&manifest_path)?;
+    normalize_manifest_paths(&mut manifest, repo_root);
+    Ok(manifest)
+}
+
+/// A query made of the words in a chunk's first line: `fn parse_retry_policy(env: &Env)`
+/// gives `parse retry policy env`. `None` when fewer than two words are left.
+pub(crate) fn signature_query(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let mut words: Vec<String> = Vec::new();
+    for identifier in line.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        for word in sparse::identifier_parts(identifier) {
+            if word.chars().count() > 2
+                && word.chars().all(char::is_alphabetic)
+                && !SIGNATURE_NOISE.contains(&word.as_str())
+                && !words.contains(&word)
+            {
+                words.push(word);
+            }
+        }
+    }
+    (words.len() >= 2).then(|| words.join(" "))
+}
+
+/// Up to `limit` items spread evenly across `items`, so every part of the tree is sampled
+pub(crate) fn sample_evenly<T: Clone>(items: &[T], limit: usize) -> Vec<T> {
+    if items.len() <= limit {
+        return items.to_vec();
+    }
+    (0..limit)
+        .map(|i| items[i * items.len() / limit].clone())
+        .collect()
+}
+
+/// The indexed file a user-given expected path names; a path suffix is enough
+fn expected_file(files: &[PathBuf], expected: &str) -> Option<usize> {
+    let expected = Path::new(expected.trim_start_matches("./"));
+    files.iter().position(|file| file.ends_with(expected))
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// The threshold keeping the most expected-file scores while dropping the most other scores
+/// (largest share kept minus share let through), rounded down to two decimals
+fn suggest_threshold(expected: &[f32], other: &[f32]) -> Option<f32> {
+    if expected.is_empty() {
+        return None;
+    }
+    let share_at_least = |scores: &[f32], threshold: f32| -> f32 {
+        if scores.is_empty() {
+            return 0.0;
+        }
+        scores.iter().filter(|score| **score >= threshold).count() as f32 / scores.len() as f32
+    };
+    let mut best: Option<(f32, f32)> = None;
+    for &threshold in expected {
+        let separation = share_at_least(expected, threshold) - share_at_least(other, threshold);
+        // Ties go to the lower threshold, which loses fewer results
+        let better = match best {
+            None => true,
+            Some((best_separation, best_threshold)) => {
+                separation > best_separation
+                    || (separation == best_separation && threshold < best_threshold)
+            }
+        };
+        if better {
+            best = Some((separation, threshold));
+        }
+    }
+    best.map(|(_, threshold)| (threshold * 100.0).floor() / 100.0)
+}
+
+fn distribution(scores: &mut [f32]) -> ScoreDistribution {
+    if scores.is_empty() {
+        return ScoreDistribution::default();
+    }
+    scores.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let percentile = |p: f32| Some(scores[((scores.len() - 1) as f32 * p).round() as usize]);
+    ScoreDistribution {
+        count: scores.len(),
+        min: scores.first().copied(),
+        p10: percentile(0.1),
+        p25: percentile(0.25),
+        median: percentile(0.5),
+        p75: percentile(0.75),
+        p90: percentile(0.9),
+        max: scores.last().copied(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{index_single_file, save_index_entry, save_manifest};
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Embeds text as letter frequencies, so texts sharing words score higher
+    struct LetterEmbedder;
+
+    impl cs_embed::Embedder for LetterEmbedder {
+        fn id(&self) -> &'static str {
+            "letters"
+        }
+
+        fn dim(&self) -> usize {
+            26
+        }
+
+        fn model_name(&self) -> &str {
+            "letters"
+        }
+
+        fn embed(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    ('a'..='z')
+                        .map(|c| text.matches(c).count() as f32 + 0.01)
+                        .collect()
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_signature_query() {
+        assert_eq!(
+            signature_query("\n  pub fn parseRetryPolicy(env: &Env) -> u32 {\n}").as_deref(),
+            Some("parse retry policy env")
+        );
+        assert_eq!(signature_query("fn go() {}"), None);
+    }
+
+    #[test]
+    fn test_calibrate_reports_scores_and_suggestions() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let mut embedder: Box<dyn cs_embed::Embedder> = Box::new(LetterEmbedder);
+        let mut manifest = IndexManifest {
+            embedding_model: Some("letters".to_string()),
+            ..Default::default()
+        };
+        for (name, content) in [
+            ("zebra.py", "def zigzag_zebra_zone():\n    return 'zzz'\n"),
+            (
+                "vowels.py",
+                "def audio_queue_aeiou():\n    return 'aeiou'\n",
+            ),
+            ("fox.py", "def quick_brown_fox():\n    return 'jumps'\n"),
+        ] {
+            let path = root.join(name);
+            fs::write(&path, content).unwrap();
+            let entry = index_single_file(&path, root, Some(&mut embedder)).unwrap();
+            save_index_entry(&cs_core::get_sidecar_path(root, &path), &entry).unwrap();
+            manifest
+                .files
+                .insert(entry.metadata.path.clone(), entry.metadata);
+        }
+        save_manifest(&root.join(".cs").join("manifest.json"), &manifest).unwrap();
+
+        // Generated from each file's signature, and each finds its own file first
+        let report = calibrate_with_embedder(root, &mut embedder, None, 10, 3).unwrap();
+        assert!(report.generated);
+        assert_eq!(report.files, 3);
+        assert_eq!(report.queries.len(), 3);
+        assert!(report.queries.iter().all(|query| query.rank == Some(1)));
+        assert_eq!(report.expected_scores.count, 3);
+        assert_eq!(report.other_scores.count, 6);
+        assert_eq!(report.hit_rates[0].top_k, 1);
+        assert_eq!(report.hit_rates[0].hit_rate, 1.0);
+        assert_eq!(report.suggested_top_k, Some(1));
+        let threshold = report.suggested_threshold.unwrap();
+        assert!(threshold <= report.expected_scores.min.unwrap());
+        assert!(threshold > report.other_scores.min.unwrap());
+
+        // Given queries may name a file by suffix, or one that is not indexed
+        let queries =
+            crate::shadow::parse_shadow_queries("zebra zone\tzebra.py\nnothing\tgone.py\n");
+        let report = calibrate_with_embedder(root, &mut embedder, Some(queries), 10, 3).unwrap();
+        assert!(!report.generated);
+        assert_eq!(report.queries[0].rank, Some(1));
+        assert_eq!(report.queries[1].rank, None);
+        assert_eq!(report.hit_rates.last().unwrap().hit_rate, 0.5);
+    }
+
+    #[test]
+    fn test_suggest_threshold_separates_scores() {
+        assert_eq!(suggest_threshold(&[], &[0.5]), None);
+        assert_eq!(
+            suggest_threshold(&[0.81, 0.72, 0.65], &[0.6, 0.55, 0.4]),
+            Some(0.65)
+        );
+        // One expected score among the others: keeping it costs less than it gains
+        assert_eq!(suggest_threshold(&[0.9, 0.7, 0.3], &[0.5, 0.4]), Some(0.7));
+    }
+}
@@ -0,0 +1,212 @@
+//! The doc index, `.cs/docs.bin`: int8 codes of every embedded chunk, each stored with the id
+//! of its file and its span (`cs_ann::DocIndex`).
+//!
+//! Semantic search ranks the chunks in its scope on the codes and then decodes only the
+//! sidecars of the best candidates, instead of every sidecar in the index. The doc index is
+//! rebuilt from the sidecars after indexing runs and stamped with the index epoch it was built
+//! at; once the index changes, a doc index with an older stamp is ignored until the next run
+//! rebuilds it.
+
+use super::{atomic_write, index_epoch, load_index_entry, load_or_create_manifest, path_utils};
+use anyhow::Result;
+use cs_ann::{DocIndex, Int8Index};
+use cs_core::get_sidecar_path;
+use rayon::prelude::*;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub const DOC_INDEX_FILE: &str = "docs.bin";
+
+/// Leads the file, followed by the epoch stamp and the bincode-encoded `DocIndex`
+const MAGIC: &[u8; 8] = b"cs-docs1";
+
+/// Sidecars decoded at once while building
+const BUILD_BATCH_FILES: usize = 256;
+
+fn doc_index_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(".cs").join(DOC_INDEX_FILE)
+}
+
+/// The doc index of the index at `repo_root`; `None` when there is none or it was built
+/// before the index last changed
+pub fn load(repo_root: &Path) -> Option<DocIndex<Int8Index>> {
+    let epoch = index_epoch(repo_root)?;
+    let data = fs::read(doc_index_path(repo_root)).ok()?;
+    let body = data.strip_prefix(MAGIC.as_slice())?;
+    let (stamp, index) = body.split_first_chunk::<8>()?;
+    if u64::from_le_bytes(*stamp) != epoch {
+        return None;
+    }
+    bincode::deserialize(index).ok()
+}
+
+/// Epoch stamp of the doc index at `repo_root`, read without decoding the rest
+fn stamp(repo_root: &Path) -> Option<u64> {
+    let mut header = [0u8; 16];
+    fs::File::open(doc_index_path(repo_root))
+        .and_then(|mut file| file.read_exact(&mut header))
+        .ok()?;
+    let (magic, stamp) = header.split_at(8);
+    (magic == MAGIC).then(|| u64::from_le_bytes(stamp.try_into().unwrap()))
+}
+
+/// Rebuild the doc index at `repo_root` unless it matches the index already. Indexes
+/// without embeddings or with BM25 term vectors (`--sparse`) get none; returns whether one
+/// was written.
+pub fn refresh(repo_root: &Path) -> Result<bool> {
+    let Some(epoch) = index_epoch(repo_root) else {
+        return Ok(false);
+    };
+    if stamp(repo_root) == Some(epoch) {
+        return Ok(false);
+    }
+    let path = doc_index_path(repo_root);
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+
+    let manifest = load_or_create_manifest(&repo_root.join(".cs").join("manifest.json"))?;
+    // Term-vector scores depend on every chunk in the index, so those indexes get none
+    if manifest.embedding_model.is_none() || manifest.sparse_vectors {
+        return Ok(false);
+    }
+    let mut files: Vec<PathBuf> = manifest
+        .files
+        .keys()
+        .map(|key| path_utils::from_manifest_path(key))
+        .collect();
+    files.sort();
+
+    let mut index = DocIndex::<Int8Index>::new();
+    for batch in files.chunks(BUILD_BATCH_FILES) {
+        let loaded: Vec<_> = batch
+            .par_iter()
+            .filter_map(|file| {
+                let sidecar = get_sidecar_path(repo_root, &repo_root.join(file));
+                let entry = load_index_entry(&sidecar).ok()?;
+                let chunks: Vec<_> = entry
+                    .chunks
+                    .into_iter()
+                    .filter_map(|chunk| Some((chunk.span, chunk.embedding?)))
+                    .collect();
+                (!chunks.is_empty()).then_some((file, chunks))
+            })
+            .collect();
+        for (file, chunks) in loaded {
+            let relative: Vec<_> = file
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect();
+            let file = index.add_file(&relative.join("/"));
+            for (span, embedding) in chunks {
+                index.add(file, span, &embedding)?;
+            }
+        }
+    }
+
+    // An index run that finished meanwhile leaves the next one to rebuild
+    if index.is_empty() || index_epoch(repo_root) != Some(epoch) {
+        return Ok(false);
+    }
+    let mut data = MAGIC.to_vec();
+    data.extend_from_slice(&epoch.to_le_bytes());
+    bincode::serialize_into(&mut data, &index)?;
+    atomic_write(&path, &data)?;
+    Ok(true)
+}
+
+/// `refresh` run after indexing; failures, such as an index mixing models of different
+/// dimensions, are logged and leave semantic search reading the sidecars
+pub(crate) fn refresh_after_indexing(repo_root: &Path) {
+    if let Err(e) = refresh(repo_root) {
+        tracing::debug!("Doc index not built: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChunkEntry, IndexEntry, IndexManifest, save_index_entry, save_manifest};
+    use cs_chunk::ChunkType;
+    use cs_core::{FileMetadata, Span};
+    use tempfile::TempDir;
+
+    fn write_file(root: &Path, manifest: &mut IndexManifest, name: &str, vectors: &[[f32; 2]]) {
+        let metadata = FileMetadata {
+            path: root.join(name),
+            hash: name.to_string(),
+            last_modified: 0,
+            size: 0,
+        };
+        let chunks = vectors
+            .iter()
+            .enumerate()
+            .map(|(i, vector)| ChunkEntry {
+                span: Span::new(i * 10, i * 10 + 10, i + 1, i + 1).unwrap(),
+                embedding: Some(vector.to_vec()),
+                chunk_type: ChunkType::Function,
+                breadcrumb: None,
+                ancestry: None,
+                byte_length: None,
+                estimated_tokens: None,
+                leading_trivia: None,
+                trailing_trivia: None,
+                stride: None,
+            })
+            .collect();
+        let entry = IndexEntry {
+            metadata: metadata.clone(),
+            chunks,
+            embedding_info: None,
+            sparse: None,
+            withheld: None,
+        };
+        save_index_entry(&get_sidecar_path(root, &root.join(name)), &entry).unwrap();
+        manifest
+            .files
+            .insert(path_utils::to_manifest_path(Path::new(name)), metadata);
+    }
+
+    #[test]
+    fn test_doc_index_follows_the_index_epoch() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let manifest_path = root.join(".cs").join("manifest.json");
+        let mut manifest = IndexManifest {
+            embedding_model: Some("test".to_string()),
+            ..Default::default()
+        };
+        write_file(root, &mut manifest, "src/lib.rs", &[[1.0, 0.0], [0.0, 1.0]]);
+        write_file(root, &mut manifest, "README.md", &[[0.5, 0.5]]);
+        save_manifest(&manifest_path, &manifest).unwrap();
+
+        assert!(load(root).is_none());
+        assert!(refresh(root).unwrap());
+        // Nothing changed since
+        assert!(!refresh(root).unwrap());
+        let index = load(root).unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.files(), ["README.md", "src/lib.rs"]);
+        let hits = index
+            .search(&[0.0, 1.0], 1, &index.allow_scope("src"))
+            .unwrap();
+        assert_eq!(
+            (index.file(hits[0].file), hits[0].span.byte_start),
+            (Some("src/lib.rs"), 10)
+        );
+
+        // A changed index makes the doc index stale until it is rebuilt
+        write_file(root, &mut manifest, "src/main.rs", &[[1.0, 1.0]]);
+        save_manifest(&manifest_path, &manifest).unwrap();
+        assert!(load(root).is_none());
+        assert!(refresh(root).unwrap());
+        assert_eq!(load(root).unwrap().len(), 4);
+
+        // Indexes without embeddings get none
+        manifest.embedding_model = None;
+        save_manifest(&manifest_path, &manifest).unwrap();
+        assert!(!refresh(root).unwrap());
+        assert!(!doc_index_path(root).exists());
+    }
+}
@@ -0,0 +1,197 @@
+//! Chunk and token totals for `cs --index --dry-run`.
+//!
+//! The tree is walked and chunked exactly as indexing would for each model, but nothing is
+//! embedded or written, so the size of an API-backed index is known before the first request
+//! is sent. Token counts come from `TokenEstimator` and are approximate.
+
+use super::{
+    apply_index_file_limits, chunk_overrides, collect_files, embed_template, embedding_input,
+    path_utils,
+};
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexEstimate {
+    /// Files that would be indexed
+    pub files: usize,
+    /// Total size of those files
+    pub bytes: u64,
+    /// Files that could not be read as text (PDFs are extracted only when indexing)
+    pub files_unreadable: usize,
+    /// Files left out by `--max-filesize` or `--max-files`
+    pub files_skipped: usize,
+    /// One entry per registry model, sorted by alias
+    pub models: Vec<ModelEstimate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelEstimate {
+    pub alias: String,
+    pub model: String,
+    pub provider: String,
+    pub chunks: usize,
+    pub tokens: usize,
+    /// API cost at the registry's list price; `None` for local models
+    pub estimated_cost_usd: Option<f64>,
+}
+
+/// Chunk size, overlap and striding, which together decide the chunks
+type ChunkKey = (usize, usize, bool);
+
+/// Bytes read from one file, and its (chunks, tokens) for each chunk config
+type FileTotals = (u64, Vec<(usize, usize)>);
+
+/// Chunk every file under `path` for each model in `registry` and total the chunks and
+/// estimated tokens that would be sent to the embedder.
+pub fn estimate_index(
+    path: &Path,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+    registry: &cs_models::ModelRegistry,
+) -> Result<IndexEstimate> {
+    let files = collect_files(path, respect_gitignore, exclude_patterns)?;
+    let (files, files_skipped) = apply_index_file_limits(files);
+    let aliases = registry.aliases();
+    let models: Vec<&str> = aliases
+        .iter()
+        .map(|alias| registry.models[*alias].name.as_str())
+        .collect();
+
+    // Models sharing a chunk size and overlap produce the same chunks; chunk once per config
+    let overrides = chunk_overrides(path);
+    let template = embed_template(path);
+    let chunk_key = |model: &str| {
+        let config = cs_chunk::model_chunk_config(Some(model), &overrides);
+        (
+            config.max_tokens,
+            config.stride_overlap,
+            config.enable_striding,
+        )
+    };
+    let mut configs: Vec<ChunkKey> = models.iter().map(|model| chunk_key(model)).collect();
+    configs.sort_unstable();
+    configs.dedup();
+    let representatives: Vec<&str> = configs
+        .iter()
+        .map(|config| {
+            *models
+                .iter()
+                .find(|model| chunk_key(model) == *config)
+                .expect("every config comes from a model")
+        })
+        .collect();
+
+    let per_file: Vec<Option<FileTotals>> = files
+        .par_iter()
+        .map(|file| {
+            let content = cs_core::encoding::read_to_string(file).ok()?;
+            let language = cs_core::Language::from_path(file);
+            let relative = path_utils::to_manifest_path(&path_utils::to_standard_path(file, path));
+            let totals = representatives
+                .iter()
+                .map(|model| {
+                    let chunks = cs_chunk::chunk_text_with_overrides(
+                        &content,
+                        language,
+                        Some(model),
+                        &overrides,
+                    )
+                    .unwrap_or_default();
+                    let tokens = chunks
+                        .iter()
+                        .map(|chunk| {
+                            let text = embedding_input(chunk, &relative, template.as_ref());
+                            cs_chunk::TokenEstimator::estimate_tokens(&text)
+                        })
+                        .sum();
+                    (chunks.len(), tokens)
+                })
+                .collect();
+            Some((content.len() as u64, totals))
+        })
+        .collect();
+
+    let mut estimate = IndexEstimate {
+        files_skipped,
+        ..Default::default()
+    };
+    let mut by_config: BTreeMap<ChunkKey, (usize, usize)> = BTreeMap::new();
+    for file in per_file {
+        let Some((bytes, totals)) = file else {
+            estimate.files_unreadable += 1;
+            continue;
+        };
+        estimate.files += 1;
+        estimate.bytes += bytes;
+        for (config, (chunks, tokens)) in configs.iter().zip(totals) {
+            let entry = by_config.entry(*config).or_default();
+            entry.0 += chunks;
+            entry.1 += tokens;
+        }
+    }
+
+    estimate.models = aliases
+        .iter()
+        .map(|alias| {
+            let config = &registry.models[*alias];
+            let (chunks, tokens) = by_config
+                .get(&chunk_key(&config.name))
+                .copied()
+                .unwrap_or_default();
+            ModelEstimate {
+                alias: alias.to_string(),
+                model: config.name.clone(),
+                provider: config.provider.clone(),
+                chunks,
+                tokens,
+                estimated_cost_usd: config.estimated_cost(tokens),
+            }
+        })
+        .collect();
+    Ok(estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_estimate_index_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let function =
+            "fn handler() {\n    let value = compute();\n    println!(\"{}\", value);\n}\n";
+        fs::write(root.join("lib.rs"), function.repeat(200)).unwrap();
+        fs::write(root.join("notes.txt"), "short note about retries").unwrap();
+
+        let registry = cs_models::ModelRegistry::default();
+        let estimate = estimate_index(root, true, &[], &registry).unwrap();
+
+        assert!(!root.join(".cs").exists());
+        assert_eq!(estimate.files, 2);
+        assert_eq!(estimate.models.len(), registry.models.len());
+        let model = |alias: &str| {
+            estimate
+                .models
+                .iter()
+                .find(|model| model.alias == alias)
+                .unwrap()
+        };
+        let (small, large) = (model("bge-small"), model("nomic-v1.5"));
+        assert!(small.tokens > 0 && large.tokens > 0);
+        // Smaller chunks for the 512-token model
+        assert!(small.chunks >= large.chunks);
+        assert_eq!(small.estimated_cost_usd, None);
+        assert!(
+            model("jina-v4")
+                .estimated_cost_usd
+                .is_some_and(|cost| cost > 0.0)
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Append-only index event log for external tooling.
+//!
+//! Every indexing run that reads files appends JSON lines to `.cs/events.log`: `index_started`,
+//! then `file_indexed` or `file_failed` for each file, then `index_finished` with the totals
+//! and how the run ended. Dashboards and build systems can follow index health from it
+//! without parsing human output. Every event carries the schema version `v` and the `run` it
+//! belongs to; the schema is documented in `docs/reference/index-events.md`. The log rotates
+//! to `events.log.1` once it reaches `MAX_BYTES` and, like the audit log, survives `--clean`
+//! and rebuilds. Failing to write an event is logged and never fails indexing.
+
+use super::{IndexEntry, audit};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Version of the event schema; bumped when a field changes meaning or goes away
+pub const SCHEMA_VERSION: u32 = 1;
+
+const LOG_FILE: &str = "events.log";
+
+/// Rotate once the current log would grow past this many bytes
+const MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Rotated logs kept besides the current one
+const KEEP: usize = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEvent {
+    /// Schema version, `SCHEMA_VERSION` when written
+    pub v: u32,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Run the event belongs to, unique per indexing run
+    pub run: String,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    IndexStarted {
+        operation: IndexOperation,
+        /// Embedding model, when the run computes embeddings
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        /// Files the run is going to read
+        files: usize,
+    },
+    FileIndexed {
+        /// Relative to the index root
+        path: PathBuf,
+        chunks: usize,
+        /// Whether every chunk got an embedding
+        embedded: bool,
+        duration_ms: u64,
+    },
+    FileFailed {
+        /// Relative to the index root
+        path: PathBuf,
+        error: String,
+    },
+    IndexFinished {
+        status: RunStatus,
+        files_indexed: usize,
+        files_failed: usize,
+        chunks: usize,
+        duration_ms: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexOperation {
+    /// A new or rebuilt index (`--index`, `--force`)
+    Build,
+    /// Changed, added and backfilled files of an existing index, as before a search
+    Update,
+    /// Files added one by one (`--add`)
+    Add,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Completed,
+    /// Stopped by Ctrl-C or a cancelled request
+    Cancelled,
+    /// Stopped by an error
+    Failed,
+}
+
+pub fn events_log_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(".cs").join(LOG_FILE)
+}
+
+/// Whether `path` is named like the event log or one of its rotations (`events.log.1`)
+pub(crate) fn is_event_log(path: &Path) -> bool {
+    let Some(rest) = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix(LOG_FILE))
+    else {
+        return false;
+    };
+    rest.is_empty()
+        || rest
+            .strip_prefix('.')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Events of one indexing run. `index_started` is held back until the first file event, so
+/// runs with nothing to do leave no trace; a run dropped before `finish` is recorded as failed.
+pub struct EventLog {
+    repo_root: PathBuf,
+    run: String,
+    started: Instant,
+    started_ms: u64,
+    state: Mutex<RunState>,
+}
+
+#[derive(Default)]
+struct RunState {
+    pending_start: Option<EventKind>,
+    files_indexed: usize,
+    files_failed: usize,
+    chunks: usize,
+    finished: bool,
+}
+
+impl EventLog {
+    pub fn start(
+        repo_root: &Path,
+        operation: IndexOperation,
+        model: Option<&str>,
+        files: usize,
+    ) -> Self {
+        let started_ms = timestamp_ms();
+        Self {
+            repo_root: repo_root.to_path_buf(),
+            run: format!("{:x}-{}", started_ms, std::process::id()),
+            started: Instant::now(),
+            started_ms,
+            state: Mutex::new(RunState {
+                pending_start: Some(EventKind::IndexStarted {
+                    operation,
+                    model: model.map(str::to_string),
+                    files,
+                }),
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn file_indexed(&self, entry: &IndexEntry, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.files_indexed += 1;
+        state.chunks += entry.chunks.len();
+        self.write(
+            &mut state,
+            EventKind::FileIndexed {
+                path: self.key(&entry.metadata.path),
+                chunks: entry.chunks.len(),
+                embedded: entry.is_embedded(),
+                duration_ms: duration.as_millis() as u64,
+            },
+        );
+    }
+
+    pub fn file_failed(&self, file: &Path, error: &anyhow::Error) {
+        let mut state = self.state.lock().unwrap();
+        state.files_failed += 1;
+        self.write(
+            &mut state,
+            EventKind::FileFailed {
+                path: self.key(file),
+                error: error.to_string(),
+            },
+        );
+    }
+
+    pub fn finish(&self, status: RunStatus) {
+        let mut state = self.state.lock().unwrap();
+        if state.finished {
+            return;
+        }
+        state.finished = true;
+        // A run that read no files has nothing to report
+        if state.pending_start.is_some() {
+            return;
+        }
+        let finished = EventKind::IndexFinished {
+            status,
+            files_indexed: state.files_indexed,
+            files_failed: state.files_failed,
+            chunks: state.chunks,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+        };
+        self.write(&mut state, finished);
+    }
+
+    /// Path of a file relative to the index root, whether given absolute or as a manifest path
+    fn key(&self, file: &Path) -> PathBuf {
+        cs_core::paths::index_key(&self.repo_root.join(file), &self.repo_root)
+    }
+
+    fn write(&self, state: &mut RunState, kind: EventKind) {
+        let start = state
+            .pending_start
+            .take()
+            .map(|start| (self.started_ms, start));
+        let lines: Result<String> = start
+            .into_iter()
+            .chain([(timestamp_ms(), kind)])
+            .map(|(timestamp_ms, kind)| {
+                let event = IndexEvent {
+                    v: SCHEMA_VERSION,
+                    timestamp_ms,
+                    run: self.run.clone(),
+                    kind,
+                };
+                Ok(serde_json::to_string(&event)? + "\n")
+            })
+            .collect();
+        if let Err(e) = lines.and_then(|lines| append(&self.repo_root, &lines)) {
+            tracing::warn!(
+                "Failed to write index event under {:?}: {}",
+                self.repo_root,
+                e
+            );
+        }
+    }
+}
+
+impl Drop for EventLog {
+    fn drop(&mut self) {
+        self.finish(RunStatus::Failed);
+    }
+}
+
+/// Append lines, rotating first if they would push the log past `MAX_BYTES`
+fn append(repo_root: &Path, lines: &str) -> Result<()> {
+    let log_path = events_log_path(repo_root);
+    fs::create_dir_all(repo_root.join(".cs"))?;
+    let current_len = fs::metadata(&log_path).map(|meta| meta.len()).unwrap_or(0);
+    if current_len > 0 && current_len + lines.len() as u64 > MAX_BYTES {
+        audit::rotate(&log_path, KEEP)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Events of the current log, oldest first
+pub fn read_events(repo_root: &Path) -> Result<Vec<IndexEvent>> {
+    let Ok(data) = fs::read_to_string(events_log_path(repo_root)) else {
+        return Ok(Vec::new());
+    };
+    data.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+fn timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn kinds(root: &Path) -> Vec<EventKind> {
+        read_events(root)
+            .unwrap()
+            .into_iter()
+            .map(|event| {
+                assert_eq!(event.v, SCHEMA_VERSION);
+                event.kind
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_indexing_runs_are_logged() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("lib.rs"), "fn one() {}\n\nfn two() {}\n").unwrap();
+
+        super::super::smart_update_index(root, false, true, &[])
+            .await
+            .unwrap();
+        let events = read_events(root).unwrap();
+        assert!(events.iter().all(|event| event.run == events[0].run));
+        let kinds = kinds(root);
+        assert_eq!(kinds.len(), 3);
+        assert_eq!(
+            kinds[0],
+            EventKind::IndexStarted {
+                operation: IndexOperation::Update,
+                model: None,
+                files: 1
+            }
+        );
+        let EventKind::FileIndexed {
+            path,
+            chunks,
+            embedded,
+            ..
+        } = &kinds[1]
+        else {
+            panic!("expected file_indexed, got {:?}", kinds[1]);
+        };
+        assert_eq!(path, Path::new("lib.rs"));
+        assert!(*chunks > 0);
+        assert!(!embedded);
+        assert!(matches!(
+            kinds[2],
+            EventKind::IndexFinished {
+                status: RunStatus::Completed,
+                files_indexed: 1,
+                files_failed: 0,
+                ..
+            }
+        ));
+
+        // Nothing to update leaves no events; the log outlives a clean
+        super::super::smart_update_index(root, false, true, &[])
+            .await
+            .unwrap();
+        super::super::clean_index(root).unwrap();
+        assert_eq!(read_events(root).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_unfinished_run_is_recorded_as_failed() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        {
+            let log = EventLog::start(root, IndexOperation::Build, Some("bge-small"), 2);
+            log.file_failed(&root.join("src/bad.rs"), &anyhow::anyhow!("unreadable"));
+        }
+        let kinds = kinds(root);
+        assert_eq!(
+            kinds[1],
+            EventKind::FileFailed {
+                path: PathBuf::from("src/bad.rs"),
+                error: "unreadable".to_string()
+            }
+        );
+        assert!(matches!(
+            kinds[2],
+            EventKind::IndexFinished {
+                status: RunStatus::Failed,
+                files_failed: 1,
+                ..
+            }
+        ));
+    }
+}
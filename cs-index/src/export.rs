@@ -0,0 +1,441 @@
+//! Symbol export in ctags and LSIF formats.
+//!
+//! Symbols come from the same chunker the index uses, run over the files recorded in the
+//! index manifest, so editors and code-intel pipelines see exactly the definitions cs
+//! searches over. The manifest's update time is written into the output as the index
+//! epoch, and files edited since the last index run are counted as stale.
+
+use super::{IndexManifest, load_or_create_manifest, normalize_manifest_paths, path_utils};
+use anyhow::Result;
+use cs_chunk::ChunkConfig;
+use cs_core::{Language, Span, compute_file_hash};
+use serde::Serialize;
+use serde_json::json;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Zero-based position; `character` counts UTF-16 code units as LSIF expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedSymbol {
+    pub name: String,
+    pub kind: String,
+    /// Path relative to the repository root
+    pub path: PathBuf,
+    pub language: Option<String>,
+    /// Enclosing definitions joined with `::`
+    pub scope: Option<String>,
+    pub span: Span,
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SymbolExport {
+    pub symbols: Vec<ExportedSymbol>,
+    /// Manifest update time (seconds since the epoch) of the index the files came from
+    pub index_updated: u64,
+    pub files: usize,
+    /// Indexed files whose content changed or disappeared since the last index run
+    pub stale_files: usize,
+}
+
+/// Collect definitions from every file in the index at `repo_root`
+pub fn collect_symbols(repo_root: &Path) -> Result<SymbolExport> {
+    let manifest_path = repo_root.join(".cs").join("manifest.json");
+    if !manifest_path.exists() {
+        anyhow::bail!(
+            "No index found at {}; run 'cs --index' first",
+            repo_root.display()
+        );
+    }
+    let mut manifest: IndexManifest = load_or_create_manifest(&manifest_path)?;
+    normalize_manifest_paths(&mut manifest, repo_root);
+
+    let mut entries: Vec<_> = manifest.files.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut export = SymbolExport {
+        index_updated: manifest.updated,
+        ..Default::default()
+    };
+    // Whole definitions, not embedding-sized strides
+    let config = ChunkConfig {
+        enable_striding: false,
+        ..ChunkConfig::default()
+    };
+
+    for (manifest_path, metadata) in entries {
+        let standard_path = path_utils::from_manifest_path(manifest_path);
+        let file_path = repo_root.join(&standard_path);
+
+        match compute_file_hash(&file_path) {
+            Ok(hash) if hash == metadata.hash => {}
+            Ok(_) => export.stale_files += 1,
+            Err(_) => {
+                export.stale_files += 1;
+                continue;
+            }
+        }
+
+        let language = Language::from_path(&file_path);
+        if language.is_none_or(|lang| lang == Language::Pdf) {
+            continue;
+        }
+        let Ok(content) = cs_core::encoding::read_to_string(&file_path) else {
+            continue;
+        };
+        let chunks = match cs_chunk::chunk_text_with_config(&content, language, &config) {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                tracing::warn!("Failed to chunk {}: {}", file_path.display(), e);
+                continue;
+            }
+        };
+
+        export.files += 1;
+        let line_starts = line_starts(&content);
+        for chunk in chunks {
+            let Some(kind) = crate::chunk_type_name(&chunk.chunk_type) else {
+                continue;
+            };
+            let Some(name) = chunk.metadata.name.filter(|name| !name.is_empty()) else {
+                continue;
+            };
+            export.symbols.push(ExportedSymbol {
+                name,
+                kind: kind.to_string(),
+                path: standard_path.clone(),
+                language: language.map(|lang| lang.to_string()),
+                scope: chunk.metadata.breadcrumb,
+                start: position(&content, &line_starts, chunk.span.byte_start),
+                end: position(&content, &line_starts, chunk.span.byte_end),
+                span: chunk.span,
+            });
+        }
+    }
+
+    Ok(export)
+}
+
+/// Write a sorted, extended-format ctags file with line-number addresses
+pub fn write_ctags(export: &SymbolExport, out: &mut impl Write) -> Result<()> {
+    writeln!(
+        out,
+        "!_TAG_FILE_FORMAT\t2\t/extended format; --format=1 will not append ;\" to lines/"
+    )?;
+    writeln!(
+        out,
+        "!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/"
+    )?;
+    writeln!(out, "!_TAG_PROGRAM_NAME\tcs\t//")?;
+    writeln!(
+        out,
+        "!_TAG_PROGRAM_VERSION\t{}\t//",
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(
+        out,
+        "!_TAG_CS_INDEX_UPDATED\t{}\t/manifest update time of the index these tags came from/",
+        export.index_updated
+    )?;
+
+    let mut lines: Vec<String> = export
+        .symbols
+        .iter()
+        .filter(|symbol| !symbol.name.contains(['\t', '\n']))
+        .map(|symbol| {
+            let mut line = format!(
+                "{}\t{}\t{};\"\tkind:{}\tline:{}\tend:{}",
+                symbol.name,
+                symbol.path.display(),
+                symbol.span.line_start,
+                symbol.kind,
+                symbol.span.line_start,
+                symbol.span.line_end
+            );
+            if let Some(language) = &symbol.language {
+                line.push_str(&format!("\tlanguage:{}", language));
+            }
+            if let Some(scope) = &symbol.scope {
+                line.push_str(&format!("\tscope:{}", scope));
+            }
+            line
+        })
+        .collect();
+    // ctags readers binary-search on the raw bytes of the tag name
+    lines.sort();
+
+    for line in lines {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Write an LSIF dump (one JSON object per line) with a definition range and a
+/// document symbol result per file
+pub fn write_lsif(export: &SymbolExport, repo_root: &Path, out: &mut impl Write) -> Result<()> {
+    let repo_root = repo_root.canonicalize()?;
+    let mut next_id = 0usize;
+    let mut emit = |out: &mut dyn Write, mut value: serde_json::Value| -> Result<usize> {
+        next_id += 1;
+        value["id"] = json!(next_id);
+        serde_json::to_writer(&mut *out, &value)?;
+        writeln!(out)?;
+        Ok(next_id)
+    };
+
+    emit(
+        out,
+        json!({
+            "type": "vertex",
+            "label": "metaData",
+            "version": "0.4.3",
+            "projectRoot": file_uri(&repo_root),
+            "positionEncoding": "utf-16",
+            "toolInfo": { "name": "cs", "version": env!("CARGO_PKG_VERSION") },
+            "csIndexUpdated": export.index_updated,
+        }),
+    )?;
+    let project = emit(
+        out,
+        json!({ "type": "vertex", "label": "project", "kind": "code" }),
+    )?;
+
+    let mut documents = Vec::new();
+    let mut remaining = export.symbols.as_slice();
+    while let Some(first) = remaining.first() {
+        let count = remaining
+            .iter()
+            .take_while(|symbol| symbol.path == first.path)
+            .count();
+        let (symbols, rest) = remaining.split_at(count);
+        remaining = rest;
+
+        let mut document = json!({
+            "type": "vertex",
+            "label": "document",
+            "uri": file_uri(&repo_root.join(&first.path)),
+        });
+        if let Some(language) = &first.language {
+            document["languageId"] = json!(language);
+        }
+        let document = emit(out, document)?;
+        documents.push(document);
+
+        let mut ranges = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            let range = json!({ "start": symbol.start, "end": symbol.end });
+            ranges.push(emit(
+                out,
+                json!({
+                    "type": "vertex",
+                    "label": "range",
+                    "start": symbol.start,
+                    "end": symbol.end,
+                    "tag": {
+                        "type": "definition",
+                        "text": symbol.name,
+                        "kind": lsp_symbol_kind(&symbol.kind),
+                        "fullRange": range,
+                    },
+                }),
+            )?);
+        }
+
+        emit(
+            out,
+            json!({ "type": "edge", "label": "contains", "outV": document, "inVs": ranges }),
+        )?;
+        let symbol_result = emit(
+            out,
+            json!({
+                "type": "vertex",
+                "label": "documentSymbolResult",
+                "result": ranges.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+            }),
+        )?;
+        emit(
+            out,
+            json!({
+                "type": "edge",
+                "label": "textDocument/documentSymbol",
+                "outV": document,
+                "inV": symbol_result,
+            }),
+        )?;
+    }
+
+    if !documents.is_empty() {
+        emit(
+            out,
+            json!({ "type": "edge", "label": "contains", "outV": project, "inVs": documents }),
+        )?;
+    }
+    Ok(())
+}
+
+/// LSP `SymbolKind` for an exported kind name
+pub fn lsp_symbol_kind(kind: &str) -> u8 {
+    match kind {
+        "module" => 2,
+        "class" => 5,
+        "method" => 6,
+        _ => 12,
+    }
+}
+
+/// `file://` URI of an absolute path, each segment percent-encoded (RFC 8089)
+pub fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let encoded = path
+        .split('/')
+        .map(percent_encode_segment)
+        .collect::<Vec<_>>()
+        .join("/");
+    if encoded.starts_with('/') {
+        format!("file://{}", encoded)
+    } else {
+        format!("file:///{}", encoded)
+    }
+}
+
+/// Escape every byte of a path segment but unreserved characters and `:` (for `C:`)
+fn percent_encode_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~:".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(pos, _)| pos + 1))
+        .collect()
+}
+
+fn position(text: &str, line_starts: &[usize], byte: usize) -> Position {
+    let byte = byte.min(text.len());
+    let line = line_starts.partition_point(|&start| start <= byte) - 1;
+    let character = text
+        .get(line_starts[line]..byte)
+        .map(|prefix| prefix.encode_utf16().count())
+        .unwrap_or(0);
+    Position { line, character }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{index_single_file, save_index_entry, save_manifest};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn indexed_repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".cs")).unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            "mod shapes {\n    pub struct Circle;\n\n    impl Circle {\n        pub fn área(&self) -> f64 {\n            0.0\n        }\n    }\n}\n\nfn main() {}\n",
+        )
+        .unwrap();
+        fs::write(root.join("notes.txt"), "no symbols here\n").unwrap();
+
+        let mut manifest = IndexManifest::default();
+        for name in ["lib.rs", "notes.txt"] {
+            let path = root.join(name);
+            let entry = index_single_file(&path, root, None).unwrap();
+            save_index_entry(&cs_core::get_sidecar_path(root, &path), &entry).unwrap();
+            manifest
+                .files
+                .insert(entry.metadata.path.clone(), entry.metadata);
+        }
+        save_manifest(&root.join(".cs").join("manifest.json"), &manifest).unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_file_uri_percent_encodes_segments() {
+        assert_eq!(
+            file_uri(Path::new("/home/me/My Repo/#1 100%/café.rs")),
+            "file:///home/me/My%20Repo/%231%20100%25/caf%C3%A9.rs"
+        );
+        assert_eq!(file_uri(Path::new("/src/lib.rs")), "file:///src/lib.rs");
+        assert_eq!(
+            file_uri(Path::new("C:\\src\\a b.rs")),
+            "file:///C:/src/a%20b.rs"
+        );
+    }
+
+    #[test]
+    fn test_ctags_export() {
+        let repo = indexed_repo();
+        let export = collect_symbols(repo.path()).unwrap();
+        assert_eq!(export.stale_files, 0);
+        assert!(export.index_updated > 0);
+
+        let mut out = Vec::new();
+        write_ctags(&export, &mut out).unwrap();
+        let tags = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = tags.lines().filter(|l| !l.starts_with("!_")).collect();
+
+        assert!(
+            lines.contains(&"main\tlib.rs\t11;\"\tkind:function\tline:11\tend:11\tlanguage:rust")
+        );
+        let method = lines
+            .iter()
+            .find(|line| line.starts_with("área\t"))
+            .expect("method tag");
+        assert!(method.contains("kind:method"));
+        assert!(method.ends_with("scope:shapes::Circle"));
+        assert!(!tags.contains("notes.txt"));
+
+        let mut sorted = lines.clone();
+        sorted.sort();
+        assert_eq!(lines, sorted);
+
+        fs::write(repo.path().join("lib.rs"), "fn main() {}\n").unwrap();
+        assert_eq!(collect_symbols(repo.path()).unwrap().stale_files, 1);
+    }
+
+    #[test]
+    fn test_lsif_export() {
+        let repo = indexed_repo();
+        let export = collect_symbols(repo.path()).unwrap();
+
+        let mut out = Vec::new();
+        write_lsif(&export, repo.path(), &mut out).unwrap();
+        let items: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(items[0]["label"], "metaData");
+        for (idx, item) in items.iter().enumerate() {
+            assert_eq!(item["id"], idx + 1);
+        }
+        let documents: Vec<_> = items.iter().filter(|i| i["label"] == "document").collect();
+        assert_eq!(documents.len(), 1);
+        assert!(documents[0]["uri"].as_str().unwrap().ends_with("/lib.rs"));
+
+        let method = items
+            .iter()
+            .find(|i| i["label"] == "range" && i["tag"]["text"] == "área")
+            .expect("method range");
+        assert_eq!(method["tag"]["kind"], 6);
+        assert_eq!(method["start"], json!({ "line": 4, "character": 8 }));
+        assert_eq!(method["end"], json!({ "line": 6, "character": 9 }));
+    }
+}
@@ -0,0 +1,366 @@
+//! Garbage collection of the index directory, for `cs --gc` and after indexing.
+//!
+//! Besides the manifest and one sidecar per indexed file, `.cs` collects files nothing reads
+//! any more: sidecars and extracted PDF text of files dropped from the manifest, and temporary
+//! files of interrupted writes. Those are always removed. It also holds caches that are built
+//! once and rebuilt when missing, the lexical `tantivy_index` and the `.shadow` partitions of
+//! model comparisons; they are removed once older than the policy's age limit, and oldest
+//! first while the directory is over its size budget. Sidecars are never evicted, so an index
+//! that is over budget on its own stays as it is and is reported over budget.
+
+use super::{
+    is_sidecar_file, load_or_create_manifest, normalize_manifest_paths, path_utils,
+    shadow::SHADOW_DIR,
+};
+use anyhow::Result;
+use cs_core::GcPolicy;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// Lexical search index built by `cs --lex` and never updated after
+const TANTIVY_DIR: &str = "tantivy_index";
+
+/// Extracted text of indexed PDFs
+const CONTENT_DIR: &str = "content";
+
+/// Temporary files younger than this may belong to a write in progress
+const TEMP_FILE_GRACE: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcStats {
+    /// Sidecars without a manifest entry
+    pub orphaned_sidecars_removed: usize,
+    /// Extracted PDF text of files no longer indexed
+    pub content_caches_removed: usize,
+    /// Leftovers of interrupted writes
+    pub temp_files_removed: usize,
+    /// Rebuildable caches removed for their age or the size budget, relative to `.cs`
+    pub caches_removed: Vec<PathBuf>,
+    pub bytes_freed: u64,
+    /// Size of the index directory afterwards
+    pub index_size_bytes: u64,
+    /// Whether the index directory is still larger than the budget
+    pub over_budget: bool,
+}
+
+/// A cache that is rebuilt when missing, with its size and last change
+struct Cache {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// The policy for the index covering `repo_root`: `[gc]` in its `cs.toml`, or the defaults
+pub fn gc_policy(repo_root: &Path) -> GcPolicy {
+    cs_models::ProjectConfig::discover(repo_root)
+        .ok()
+        .flatten()
+        .and_then(|config| config.gc)
+        .unwrap_or_default()
+}
+
+/// Remove garbage from the index at `repo_root`, then caches that are too old or that keep
+/// the directory over its size budget
+pub fn collect_garbage(repo_root: &Path, policy: &GcPolicy) -> Result<GcStats> {
+    let index_dir = repo_root.join(".cs");
+    let mut stats = GcStats::default();
+    if !index_dir.is_dir() {
+        return Ok(stats);
+    }
+
+    remove_unreferenced_files(repo_root, &index_dir, &mut stats)?;
+
+    let now = SystemTime::now();
+    let mut caches = find_caches(&index_dir);
+    caches.sort_by_key(|cache| cache.modified);
+    if policy.max_age_days > 0 {
+        let max_age = Duration::from_secs(policy.max_age_days * 24 * 60 * 60);
+        let (expired, kept): (Vec<_>, Vec<_>) = caches
+            .into_iter()
+            .partition(|cache| now.duration_since(cache.modified).unwrap_or_default() > max_age);
+        for cache in expired {
+            remove_cache(&index_dir, cache, &mut stats)?;
+        }
+        caches = kept;
+    }
+
+    stats.index_size_bytes = dir_size(&index_dir);
+    if policy.max_size_mb > 0 {
+        enforce_budget(
+            &index_dir,
+            caches,
+            policy.max_size_mb * 1024 * 1024,
+            &mut stats,
+        )?;
+    }
+    Ok(stats)
+}
+
+/// `collect_garbage` with the project's policy, run after indexing; failures are logged
+/// rather than failing the indexing run
+pub fn collect_garbage_after_indexing(repo_root: &Path) {
+    match collect_garbage(repo_root, &gc_policy(repo_root)) {
+        Ok(stats) => {
+            if stats.bytes_freed > 0 {
+                tracing::info!("Index garbage collection freed {} bytes", stats.bytes_freed);
+            }
+            if stats.over_budget {
+                tracing::warn!(
+                    "Index directory is {} MB, over the [gc] max_size_mb budget",
+                    stats.index_size_bytes / (1024 * 1024)
+                );
+            }
+        }
+        Err(e) => tracing::warn!("Index garbage collection failed: {}", e),
+    }
+}
+
+/// Sidecars and PDF text of files missing from the manifest, and stale temporary files
+fn remove_unreferenced_files(
+    repo_root: &Path,
+    index_dir: &Path,
+    stats: &mut GcStats,
+) -> Result<()> {
+    let manifest_path = index_dir.join("manifest.json");
+    if !manifest_path.exists() {
+        return Ok(());
+    }
+    let mut manifest = load_or_create_manifest(&manifest_path)?;
+    normalize_manifest_paths(&mut manifest, repo_root);
+    let content_dir = index_dir.join(CONTENT_DIR);
+    let now = SystemTime::now();
+
+    let walker = WalkDir::new(index_dir).into_iter().filter_entry(|entry| {
+        entry.depth() != 1 || !is_cache_dir(entry.file_name().to_str().unwrap_or_default())
+    });
+    for entry in walker.filter_map(|entry| entry.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy();
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+
+        if name.starts_with(".tmp") {
+            let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+            if modified.is_some_and(|modified| {
+                now.duration_since(modified).unwrap_or_default() > TEMP_FILE_GRACE
+            }) {
+                fs::remove_file(path)?;
+                stats.temp_files_removed += 1;
+                stats.bytes_freed += size;
+            }
+            continue;
+        }
+
+        if let Ok(cached) = path.strip_prefix(&content_dir)
+            && let Some(source) = content_source(cached)
+        {
+            if !manifest
+                .files
+                .contains_key(&path_utils::to_manifest_path(&source))
+            {
+                fs::remove_file(path)?;
+                stats.content_caches_removed += 1;
+                stats.bytes_freed += size;
+            }
+            continue;
+        }
+
+        if path.extension().and_then(|ext| ext.to_str()) == Some("cs")
+            && let Some(standard_path) = path_utils::sidecar_to_standard_path(path, index_dir)
+            && !manifest
+                .files
+                .contains_key(&path_utils::to_manifest_path(&standard_path))
+            // Sidecars of older layouts are left to `--clean-orphans`
+            && is_sidecar_file(path)
+        {
+            fs::remove_file(path)?;
+            stats.orphaned_sidecars_removed += 1;
+            stats.bytes_freed += size;
+        }
+    }
+    Ok(())
+}
+
+/// Source file of extracted PDF text, from its path under `content/` (`docs/a.pdf.txt`)
+fn content_source(cached: &Path) -> Option<PathBuf> {
+    let name = cached.file_name()?.to_str()?.strip_suffix(".txt")?;
+    cs_core::pdf::is_pdf_file(Path::new(name)).then(|| cached.with_file_name(name))
+}
+
+fn is_cache_dir(name: &str) -> bool {
+    name == TANTIVY_DIR || name == SHADOW_DIR
+}
+
+/// `tantivy_index` and each shadow partition
+fn find_caches(index_dir: &Path) -> Vec<Cache> {
+    let mut dirs = vec![index_dir.join(TANTIVY_DIR)];
+    if let Ok(entries) = fs::read_dir(index_dir.join(SHADOW_DIR)) {
+        dirs.extend(
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path()),
+        );
+    }
+    dirs.into_iter()
+        .filter(|dir| dir.is_dir())
+        .map(|dir| {
+            let files: Vec<fs::Metadata> = WalkDir::new(&dir)
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.metadata().ok())
+                .filter(|metadata| metadata.is_file())
+                .collect();
+            Cache {
+                size: files.iter().map(|metadata| metadata.len()).sum(),
+                modified: files
+                    .iter()
+                    .filter_map(|metadata| metadata.modified().ok())
+                    .max()
+                    .unwrap_or(SystemTime::UNIX_EPOCH),
+                path: dir,
+            }
+        })
+        .collect()
+}
+
+/// Remove `caches`, oldest first, until the directory fits in `budget` bytes
+fn enforce_budget(
+    index_dir: &Path,
+    caches: Vec<Cache>,
+    budget: u64,
+    stats: &mut GcStats,
+) -> Result<()> {
+    for cache in caches {
+        if stats.index_size_bytes <= budget {
+            break;
+        }
+        stats.index_size_bytes = stats.index_size_bytes.saturating_sub(cache.size);
+        remove_cache(index_dir, cache, stats)?;
+    }
+    stats.over_budget = stats.index_size_bytes > budget;
+    Ok(())
+}
+
+fn remove_cache(index_dir: &Path, cache: Cache, stats: &mut GcStats) -> Result<()> {
+    fs::remove_dir_all(&cache.path)?;
+    stats.bytes_freed += cache.size;
+    stats.caches_removed.push(
+        cache
+            .path
+            .strip_prefix(index_dir)
+            .unwrap_or(&cache.path)
+            .to_path_buf(),
+    );
+    Ok(())
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IndexManifest, index_single_file, save_index_entry, save_manifest};
+    use cs_core::get_sidecar_path;
+    use tempfile::TempDir;
+
+    fn set_modified(path: &Path, days_ago: u64) {
+        let time = SystemTime::now() - Duration::from_secs(days_ago * 24 * 60 * 60);
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_collect_garbage_removes_orphans_and_old_caches() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let index_dir = root.join(".cs");
+        fs::write(root.join("kept.rs"), "fn kept() {}\n").unwrap();
+        fs::write(root.join("Program.cs"), "class Program {}\n").unwrap();
+
+        // Two indexed files, one of them C#, and the sidecar of a file dropped from the manifest
+        let mut manifest = IndexManifest::default();
+        for name in ["kept.rs", "Program.cs"] {
+            let path = root.join(name);
+            let entry = index_single_file(&path, root, None).unwrap();
+            save_index_entry(&get_sidecar_path(root, &path), &entry).unwrap();
+            manifest.record_file(entry.metadata, false);
+        }
+        let orphan = get_sidecar_path(root, &root.join("gone.rs"));
+        fs::copy(get_sidecar_path(root, &root.join("kept.rs")), &orphan).unwrap();
+        save_manifest(&index_dir.join("manifest.json"), &manifest).unwrap();
+
+        fs::create_dir_all(index_dir.join("content/docs")).unwrap();
+        fs::write(index_dir.join("content/docs/gone.pdf.txt"), "old text").unwrap();
+        fs::write(index_dir.join(".tmpA1b2C3"), "partial").unwrap();
+        set_modified(&index_dir.join(".tmpA1b2C3"), 1);
+        fs::write(index_dir.join(".tmpFresh"), "in progress").unwrap();
+
+        fs::create_dir_all(index_dir.join("tantivy_index")).unwrap();
+        fs::write(index_dir.join("tantivy_index/meta.json"), "{}").unwrap();
+        set_modified(&index_dir.join("tantivy_index/meta.json"), 40);
+        let shadow = index_dir.join(".shadow/nomic-embed-text-v1.5");
+        fs::create_dir_all(&shadow).unwrap();
+        fs::write(shadow.join("ann.bin"), vec![0u8; 4096]).unwrap();
+
+        let stats = collect_garbage(root, &GcPolicy::default()).unwrap();
+        assert_eq!(stats.orphaned_sidecars_removed, 1);
+        assert_eq!(stats.content_caches_removed, 1);
+        assert_eq!(stats.temp_files_removed, 1);
+        assert_eq!(stats.caches_removed, vec![PathBuf::from("tantivy_index")]);
+        assert!(!stats.over_budget);
+        assert!(!orphan.exists());
+        assert!(get_sidecar_path(root, &root.join("Program.cs")).exists());
+        assert!(index_dir.join(".tmpFresh").exists());
+        assert!(shadow.exists());
+    }
+
+    #[test]
+    fn test_budget_evicts_oldest_caches_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let index_dir = temp_dir.path().join(".cs");
+        fs::create_dir_all(&index_dir).unwrap();
+        fs::write(index_dir.join("manifest.json"), vec![b' '; 1000]).unwrap();
+        for (model, days_ago) in [("old", 5), ("new", 1)] {
+            let dir = index_dir.join(SHADOW_DIR).join(model);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("ann.bin"), vec![0u8; 4000]).unwrap();
+            set_modified(&dir.join("ann.bin"), days_ago);
+        }
+
+        let mut caches = find_caches(&index_dir);
+        caches.sort_by_key(|cache| cache.modified);
+        let mut stats = GcStats {
+            index_size_bytes: dir_size(&index_dir),
+            ..Default::default()
+        };
+        enforce_budget(&index_dir, caches, 6000, &mut stats).unwrap();
+        assert_eq!(
+            stats.caches_removed,
+            vec![Path::new(SHADOW_DIR).join("old")]
+        );
+        assert!(!stats.over_budget);
+
+        // Caches alone cannot bring the index under a budget smaller than the rest of it
+        let caches = find_caches(&index_dir);
+        enforce_budget(&index_dir, caches, 500, &mut stats).unwrap();
+        assert!(stats.over_budget);
+        assert!(index_dir.join("manifest.json").exists());
+    }
+}
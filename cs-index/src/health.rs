@@ -0,0 +1,148 @@
+//! Index integrity checks for `cs --doctor`.
+//!
+//! The manifest, the per-file sidecars and the shadow ANN partitions are written at different
+//! times, so an interrupted run or a manual edit can leave them disagreeing. These checks only
+//! read the index; `cs --clean-orphans` or a rebuild repairs what they find.
+
+use super::{load_index_entry, load_or_create_manifest, normalize_manifest_paths, path_utils};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexHealth {
+    /// Files listed in the manifest
+    pub files: usize,
+    /// Manifest entries without a sidecar
+    pub missing_sidecars: Vec<PathBuf>,
+    /// Sidecars without a manifest entry
+    pub orphaned_sidecars: Vec<PathBuf>,
+    /// Sidecars that cannot be decoded
+    pub unreadable_sidecars: Vec<PathBuf>,
+    /// Sidecars embedded with a different model or dimensions than the manifest records
+    pub model_mismatches: Vec<PathBuf>,
+    /// Shadow partitions whose ANN vectors disagree with their chunk list
+    pub shadow_problems: Vec<String>,
+}
+
+impl IndexHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.missing_sidecars.is_empty()
+            && self.orphaned_sidecars.is_empty()
+            && self.unreadable_sidecars.is_empty()
+            && self.model_mismatches.is_empty()
+            && self.shadow_problems.is_empty()
+    }
+}
+
+/// Check that the manifest, sidecars and shadow partitions of the index at `repo_root` agree;
+/// `None` when there is no index
+pub fn check_index_health(repo_root: &Path) -> Result<Option<IndexHealth>> {
+    let index_dir = repo_root.join(".cs");
+    let manifest_path = index_dir.join("manifest.json");
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    let mut manifest = load_or_create_manifest(&manifest_path)?;
+    normalize_manifest_paths(&mut manifest, repo_root);
+
+    let mut health = IndexHealth {
+        files: manifest.files.len(),
+        ..Default::default()
+    };
+
+    let mut listed = HashSet::new();
+    for manifest_key in manifest.files.keys() {
+        let standard_path = path_utils::from_manifest_path(manifest_key);
+        let sidecar_path =
+            path_utils::get_sidecar_path_for_standard_path(&index_dir, &standard_path);
+        listed.insert(standard_path.clone());
+
+        if !sidecar_path.exists() {
+            health.missing_sidecars.push(standard_path);
+            continue;
+        }
+        match load_index_entry(&sidecar_path) {
+            Ok(entry) => {
+                let mismatched = entry.embedding_info.is_some_and(|info| {
+                    manifest
+                        .embedding_model
+                        .as_ref()
+                        .is_some_and(|model| *model != info.model)
+                        || manifest
+                            .embedding_dimensions
+                            .is_some_and(|dimensions| dimensions != info.dimensions)
+                });
+                if mismatched {
+                    health.model_mismatches.push(standard_path);
+                }
+            }
+            Err(_) => health.unreadable_sidecars.push(standard_path),
+        }
+    }
+
+    for entry in WalkDir::new(&index_dir).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if entry.file_type().is_file()
+            && path.extension().and_then(|s| s.to_str()) == Some("cs")
+            && let Some(standard_path) = path_utils::sidecar_to_standard_path(path, &index_dir)
+            && !listed.contains(&standard_path)
+        {
+            health.orphaned_sidecars.push(standard_path);
+        }
+    }
+
+    health.shadow_problems = super::shadow::check_shadows(repo_root);
+
+    health.missing_sidecars.sort();
+    health.orphaned_sidecars.sort();
+    health.unreadable_sidecars.sort();
+    health.model_mismatches.sort();
+    Ok(Some(health))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{IndexManifest, index_single_file, save_index_entry, save_manifest};
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_index_health() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        assert!(check_index_health(root).unwrap().is_none());
+
+        let mut embedder: Box<dyn cs_embed::Embedder> =
+            Box::new(cs_embed::DummyEmbedder::new_with_model("test-model"));
+        let mut manifest = IndexManifest::default();
+        manifest.set_embedding_model("test-model".to_string(), 384);
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            let file = root.join(name);
+            fs::write(&file, "fn main() {}\n").unwrap();
+            let entry = index_single_file(&file, root, Some(&mut embedder)).unwrap();
+            save_index_entry(&cs_core::get_sidecar_path(root, &file), &entry).unwrap();
+            manifest
+                .files
+                .insert(entry.metadata.path.clone(), entry.metadata);
+        }
+        save_manifest(&root.join(".cs").join("manifest.json"), &manifest).unwrap();
+        assert!(check_index_health(root).unwrap().unwrap().is_healthy());
+
+        // Break the index three ways
+        fs::remove_file(cs_core::get_sidecar_path(root, &root.join("a.rs"))).unwrap();
+        fs::write(cs_core::get_sidecar_path(root, &root.join("b.rs")), b"junk").unwrap();
+        fs::write(root.join(".cs").join("gone.rs.cs"), b"").unwrap();
+
+        let health = check_index_health(root).unwrap().unwrap();
+        assert!(!health.is_healthy());
+        assert_eq!(health.files, 3);
+        assert_eq!(health.missing_sidecars, vec![PathBuf::from("a.rs")]);
+        assert_eq!(health.unreadable_sidecars, vec![PathBuf::from("b.rs")]);
+        assert_eq!(health.orphaned_sidecars, vec![PathBuf::from("gone.rs")]);
+        assert!(health.model_mismatches.is_empty());
+    }
+}
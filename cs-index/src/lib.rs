@@ -1,18 +1,44 @@
 use anyhow::Result;
+use cs_chunk::{ChunkOverrides, ChunkType, StrideInfo};
+use cs_core::embed_template::EmbedTemplate;
+use cs_core::file_limits::{DEFAULT_MAX_EMBED_FILESIZE, FileLimits};
 use cs_core::{FileMetadata, Language, Span, compute_file_hash, get_sidecar_path};
 use ignore::{WalkBuilder, overrides::OverrideBuilder};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::sync::Once;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::SystemTime;
+use std::sync::{Arc, Mutex, Once};
+use std::time::{Instant, SystemTime};
 use tempfile::NamedTempFile;
+use tokio_util::sync::CancellationToken;
 use walkdir::WalkDir;
 
+pub mod audit;
+pub mod bench;
+pub mod bundle;
+pub mod calibrate;
+pub mod doc_index;
+pub mod estimate;
+pub mod events;
+pub mod export;
+pub mod gc;
+pub mod health;
+pub mod progress;
+pub mod remote;
+pub mod repo_map;
+pub mod secrets;
+pub mod shadow;
+pub mod shard;
+pub mod sparse;
+
+use events::{EventLog, IndexOperation, RunStatus};
+pub use progress::IndexProgress;
+use progress::ProgressTracker;
+
 pub type ProgressCallback = Box<dyn Fn(&str) + Send + Sync>;
 
 /// Detailed progress information for embedding operations
@@ -24,6 +50,8 @@ pub struct EmbeddingProgress {
     pub chunk_index: usize,
     pub total_chunks: usize,
     pub chunk_size: usize,
+    /// Counts, throughput and ETA of the whole run
+    pub overall: IndexProgress,
 }
 
 pub type DetailedProgressCallback = Box<dyn Fn(EmbeddingProgress) + Send + Sync>;
@@ -77,6 +105,221 @@ pub fn request_interrupt() {
     INTERRUPTED.store(true, Ordering::SeqCst);
 }
 
+/// Caps on the files indexing reads, see `set_file_limits`
+static FILE_LIMITS: Mutex<FileLimits> = Mutex::new(FileLimits {
+    max_filesize: None,
+    max_files: None,
+});
+
+/// Limit the files indexed from now on (`--max-filesize`, `--max-files`). Without a size cap,
+/// files over `DEFAULT_MAX_EMBED_FILESIZE` are left out of the index.
+pub fn set_file_limits(limits: FileLimits) {
+    if let Ok(mut current) = FILE_LIMITS.lock() {
+        *current = limits;
+    }
+}
+
+fn index_file_limits() -> FileLimits {
+    FILE_LIMITS
+        .lock()
+        .map(|limits| *limits)
+        .unwrap_or_default()
+        .or_max_filesize(DEFAULT_MAX_EMBED_FILESIZE)
+}
+
+/// Drop the files over the indexing limits, warning about each limit that was hit.
+/// Returns the files to index and how many were skipped.
+fn apply_index_file_limits(files: Vec<PathBuf>) -> (Vec<PathBuf>, usize) {
+    let limits = index_file_limits();
+    let (files, skipped) = limits.apply(files);
+    for warning in skipped.warnings(&limits) {
+        tracing::warn!("{}", warning);
+    }
+    (files, skipped.total())
+}
+
+/// `collect_files`, limited to the files indexing reads
+fn collect_index_files(
+    path: &Path,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+) -> Result<Vec<PathBuf>> {
+    let files = collect_files(path, respect_gitignore, exclude_patterns)?;
+    Ok(apply_index_file_limits(files).0)
+}
+
+/// Chunk settings from the command line, see `set_chunk_overrides`
+static CHUNK_OVERRIDES: Mutex<ChunkOverrides> = Mutex::new(ChunkOverrides {
+    max_tokens: None,
+    overlap: None,
+    stride: None,
+});
+
+/// Chunk files indexed from now on with these settings (`--chunk-tokens`, `--chunk-overlap`,
+/// `--no-stride`). Settings left unset come from the project's `cs.toml`, then the model.
+pub fn set_chunk_overrides(overrides: ChunkOverrides) {
+    if let Ok(mut current) = CHUNK_OVERRIDES.lock() {
+        *current = overrides;
+    }
+}
+
+/// Chunk settings for the index covering `path`: command-line flags, then `cs.toml`
+pub fn chunk_overrides(path: &Path) -> ChunkOverrides {
+    let flags = CHUNK_OVERRIDES
+        .lock()
+        .map(|overrides| *overrides)
+        .unwrap_or_default();
+    let project = cs_models::ProjectConfig::discover(path)
+        .ok()
+        .flatten()
+        .map(|config| ChunkOverrides {
+            max_tokens: config.chunk_tokens,
+            overlap: config.chunk_overlap,
+            stride: config.stride,
+        })
+        .unwrap_or_default();
+    flags.or(project)
+}
+
+/// Embed template from the command line, see `set_embed_template`
+static EMBED_TEMPLATE: Mutex<Option<EmbedTemplate>> = Mutex::new(None);
+
+/// Compose the embedded text of files indexed from now on with `template`
+/// (`--embed-template`); `None` falls back to the project's `cs.toml`, then the chunk text alone.
+pub fn set_embed_template(template: Option<EmbedTemplate>) {
+    if let Ok(mut current) = EMBED_TEMPLATE.lock() {
+        *current = template;
+    }
+}
+
+/// Embed template for the index covering `path`: the command line, then `cs.toml`
+pub fn embed_template(path: &Path) -> Option<EmbedTemplate> {
+    EMBED_TEMPLATE
+        .lock()
+        .ok()
+        .and_then(|template| template.clone())
+        .or_else(|| {
+            cs_models::ProjectConfig::discover(path)
+                .ok()
+                .flatten()
+                .and_then(|config| config.embed_template)
+        })
+}
+
+/// `--sparse` from the command line, see `set_sparse_vectors`
+static SPARSE_VECTORS: Mutex<Option<bool>> = Mutex::new(None);
+
+/// Store BM25 term vectors next to the embeddings of files indexed from now on (`--sparse`);
+/// `None` falls back to `sparse` in the project's `cs.toml`.
+pub fn set_sparse_vectors(enabled: Option<bool>) {
+    if let Ok(mut current) = SPARSE_VECTORS.lock() {
+        *current = enabled;
+    }
+}
+
+/// Whether the index covering `path` stores sparse vectors: the command line, then `cs.toml`
+pub fn sparse_vectors(path: &Path) -> bool {
+    SPARSE_VECTORS
+        .lock()
+        .ok()
+        .and_then(|enabled| *enabled)
+        .or_else(|| {
+            cs_models::ProjectConfig::discover(path)
+                .ok()
+                .flatten()
+                .and_then(|config| config.sparse)
+        })
+        .unwrap_or(false)
+}
+
+/// Remote index from the command line, see `set_remote`
+static REMOTE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Pull sidecars from the remote index at `url` instead of embedding files it already has, from
+/// now on (`--remote`, see `remote`); `None` falls back to `remote` in the project's `cs.toml`.
+pub fn set_remote(url: Option<String>) {
+    if let Ok(mut current) = REMOTE.lock() {
+        *current = url;
+    }
+}
+
+/// Remote for the index covering `path`: the command line, then `cs.toml`
+pub fn remote_url(path: &Path) -> Option<String> {
+    REMOTE.lock().ok().and_then(|url| url.clone()).or_else(|| {
+        cs_models::ProjectConfig::discover(path)
+            .ok()
+            .flatten()
+            .and_then(|config| config.remote)
+    })
+}
+
+/// `--allow-secrets` from the command line, see `set_allow_secrets`
+static ALLOW_SECRETS: Mutex<Option<bool>> = Mutex::new(None);
+
+/// Index files and chunks that look like secrets instead of withholding them, from now on
+/// (`--allow-secrets`, see `secrets`); `None` falls back to `allow_secrets` in the project's
+/// `cs.toml`.
+pub fn set_allow_secrets(allowed: Option<bool>) {
+    if let Ok(mut current) = ALLOW_SECRETS.lock() {
+        *current = allowed;
+    }
+}
+
+/// Whether the index covering `path` keeps likely secrets: the command line, then `cs.toml`
+pub fn secrets_allowed(path: &Path) -> bool {
+    ALLOW_SECRETS
+        .lock()
+        .ok()
+        .and_then(|allowed| *allowed)
+        .or_else(|| {
+            cs_models::ProjectConfig::discover(path)
+                .ok()
+                .flatten()
+                .and_then(|config| config.allow_secrets)
+        })
+        .unwrap_or(false)
+}
+
+/// Text sent to the embedder for `chunk` of the file at `path` (relative to the index root)
+pub fn embedding_input(
+    chunk: &cs_chunk::Chunk,
+    path: &Path,
+    template: Option<&EmbedTemplate>,
+) -> String {
+    match template {
+        Some(template) => template.render(
+            &path.to_string_lossy().replace('\\', "/"),
+            chunk.symbol().as_deref(),
+            &chunk.embedding_text(),
+        ),
+        None => chunk.embedding_text().into_owned(),
+    }
+}
+
+/// Tokens cancelled by Ctrl-C, see `cancel_on_interrupt`
+static INTERRUPT_TOKENS: Mutex<Vec<CancellationToken>> = Mutex::new(Vec::new());
+
+fn install_interrupt_handler() {
+    HANDLER_INIT.call_once(|| {
+        let _ = ctrlc::set_handler(move || {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+            if let Ok(tokens) = INTERRUPT_TOKENS.lock() {
+                tokens.iter().for_each(CancellationToken::cancel);
+            }
+            tracing::info!("Interrupted by user, cleaning up");
+        });
+    });
+}
+
+/// Cancel `cancel` on Ctrl-C. Indexing installs its own Ctrl-C handler, which would otherwise
+/// swallow the interrupt for the rest of a search.
+pub fn cancel_on_interrupt(cancel: &CancellationToken) {
+    install_interrupt_handler();
+    if let Ok(mut tokens) = INTERRUPT_TOKENS.lock() {
+        tokens.push(cancel.clone());
+    }
+}
+
 /// Build override patterns for excluding files during directory traversal
 fn build_overrides(
     base_path: &Path,
@@ -99,13 +342,78 @@ fn build_overrides(
 pub struct IndexEntry {
     pub metadata: FileMetadata,
     pub chunks: Vec<ChunkEntry>,
+    /// Model that produced the chunk embeddings; `None` for sidecars written before it was
+    /// recorded or without embeddings
+    pub embedding_info: Option<EmbeddingInfo>,
+    /// BM25 term vectors, one per chunk in `chunks`; `None` unless the index stores them
+    /// (`--sparse`)
+    pub sparse: Option<Vec<sparse::SparseVector>>,
+    /// What secret scanning kept out of `chunks`; not stored in the sidecar
+    #[serde(skip)]
+    pub withheld: Option<secrets::WithheldSecrets>,
+}
+
+impl IndexEntry {
+    /// Whether every chunk carries an embedding
+    pub fn is_embedded(&self) -> bool {
+        self.chunks.iter().all(|chunk| chunk.embedding.is_some())
+    }
+}
+
+/// Leads every sidecar whose chunks carry a typed kind and stride metadata. bincode layouts
+/// cannot tell themselves apart; older sidecars open with the length of the file path instead.
+const SIDECAR_MAGIC: &[u8; 8] = b"cs-sc\0v2";
+
+/// Sidecar layout before `SIDECAR_MAGIC`, with chunks naming their kind
+#[derive(Deserialize)]
+struct UntypedIndexEntry {
+    metadata: FileMetadata,
+    chunks: Vec<UntypedChunkEntry>,
+    embedding_info: Option<EmbeddingInfo>,
+    sparse: Option<Vec<sparse::SparseVector>>,
+}
+
+/// Sidecar layout before `sparse`; bincode cannot skip a missing trailing field
+#[derive(Deserialize)]
+struct DenseIndexEntry {
+    metadata: FileMetadata,
+    chunks: Vec<UntypedChunkEntry>,
+    embedding_info: Option<EmbeddingInfo>,
+}
+
+/// Sidecar layout before `embedding_info`
+#[derive(Deserialize)]
+struct LegacyIndexEntry {
+    metadata: FileMetadata,
+    chunks: Vec<UntypedChunkEntry>,
+}
+
+/// Storage format of embedding vectors in sidecars
+pub const EMBEDDING_QUANTIZATION: &str = "f32";
+
+/// Which model and vector format produced a set of embeddings
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EmbeddingInfo {
+    pub model: String,
+    pub dimensions: usize,
+    pub quantization: String,
+}
+
+impl EmbeddingInfo {
+    pub fn new(model: &str, dimensions: usize) -> Self {
+        Self {
+            model: model.to_string(),
+            dimensions,
+            quantization: EMBEDDING_QUANTIZATION.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChunkEntry {
     pub span: Span,
     pub embedding: Option<Vec<f32>>,
-    pub chunk_type: Option<String>, // "function", "class", "method", or None for generic
+    pub chunk_type: ChunkType,
     #[serde(default)]
     pub breadcrumb: Option<String>,
     #[serde(default)]
@@ -118,6 +426,68 @@ pub struct ChunkEntry {
     pub leading_trivia: Option<Vec<String>>,
     #[serde(default)]
     pub trailing_trivia: Option<Vec<String>>,
+    /// Which window of a chunk too long to embed whole this is; `None` for chunks embedded
+    /// whole and in sidecars written before strides were recorded
+    #[serde(default)]
+    pub stride: Option<StrideInfo>,
+}
+
+impl ChunkEntry {
+    /// Name of the chunk's kind as shown in results; `None` for plain text
+    pub fn chunk_type_name(&self) -> Option<&'static str> {
+        chunk_type_name(&self.chunk_type)
+    }
+}
+
+/// Name of a chunk type as shown in results and accepted by `--only`; `None` for plain text
+pub fn chunk_type_name(chunk_type: &ChunkType) -> Option<&'static str> {
+    match chunk_type {
+        ChunkType::Function => Some("function"),
+        ChunkType::Class => Some("class"),
+        ChunkType::Method => Some("method"),
+        ChunkType::Module => Some("module"),
+        ChunkType::Text => None,
+    }
+}
+
+/// Chunk layout before typed kinds and strides
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct UntypedChunkEntry {
+    span: Span,
+    embedding: Option<Vec<f32>>,
+    /// "function", "class", "method", "module", or `None` for plain text
+    chunk_type: Option<String>,
+    breadcrumb: Option<String>,
+    ancestry: Option<Vec<String>>,
+    byte_length: Option<usize>,
+    estimated_tokens: Option<usize>,
+    leading_trivia: Option<Vec<String>>,
+    trailing_trivia: Option<Vec<String>>,
+}
+
+impl From<UntypedChunkEntry> for ChunkEntry {
+    fn from(chunk: UntypedChunkEntry) -> Self {
+        let chunk_type = match chunk.chunk_type.as_deref() {
+            Some("function") => ChunkType::Function,
+            Some("class") => ChunkType::Class,
+            Some("method") => ChunkType::Method,
+            Some("module") => ChunkType::Module,
+            _ => ChunkType::Text,
+        };
+        Self {
+            span: chunk.span,
+            embedding: chunk.embedding,
+            chunk_type,
+            breadcrumb: chunk.breadcrumb,
+            ancestry: chunk.ancestry,
+            byte_length: chunk.byte_length,
+            estimated_tokens: chunk.estimated_tokens,
+            leading_trivia: chunk.leading_trivia,
+            trailing_trivia: chunk.trailing_trivia,
+            stride: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -130,6 +500,28 @@ pub struct IndexManifest {
     pub embedding_model: Option<String>,
     /// Embedding model dimensions (for validation)
     pub embedding_dimensions: Option<usize>,
+    /// Storage format of the embeddings ("f32")
+    #[serde(default)]
+    pub embedding_quantization: Option<String>,
+    /// Chunk settings the index was built with; empty for the model's defaults
+    #[serde(default, skip_serializing_if = "ChunkOverrides::is_empty")]
+    pub chunking: ChunkOverrides,
+    /// Template the embedded text was composed with; `None` for the chunk text alone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embed_template: Option<EmbedTemplate>,
+    /// Whether sidecars store BM25 term vectors next to the embeddings
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sparse_vectors: bool,
+    /// Files indexed without embeddings, which semantic search cannot find until they are
+    /// embedded; `None` in manifests written before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unembedded: Option<BTreeSet<PathBuf>>,
+    /// Whether likely secrets were indexed instead of withheld (`--allow-secrets`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secrets_allowed: bool,
+    /// Files with chunks withheld as likely secrets, and what was withheld
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub withheld_secrets: BTreeMap<PathBuf, secrets::WithheldSecrets>,
 }
 
 impl Default for IndexManifest {
@@ -146,7 +538,72 @@ impl Default for IndexManifest {
             files: HashMap::new(),
             embedding_model: None, // Default to None for backward compatibility
             embedding_dimensions: None,
+            embedding_quantization: None,
+            chunking: ChunkOverrides::default(),
+            embed_template: None,
+            sparse_vectors: false,
+            unembedded: Some(BTreeSet::new()),
+            secrets_allowed: false,
+            withheld_secrets: BTreeMap::new(),
+        }
+    }
+}
+
+impl IndexManifest {
+    /// Record the model that produces this index's embeddings
+    pub fn set_embedding_model(&mut self, model: String, dimensions: usize) {
+        self.embedding_model = Some(model);
+        self.embedding_dimensions = Some(dimensions);
+        self.embedding_quantization = Some(EMBEDDING_QUANTIZATION.to_string());
+    }
+
+    /// Add or replace the entry of an indexed file, noting whether its chunks were embedded
+    pub fn record_file(&mut self, metadata: FileMetadata, embedded: bool) {
+        if let Some(unembedded) = &mut self.unembedded {
+            if embedded {
+                unembedded.remove(&metadata.path);
+            } else {
+                unembedded.insert(metadata.path.clone());
+            }
+        }
+        self.files.insert(metadata.path.clone(), metadata);
+    }
+
+    /// Add or replace an indexed file as `record_file` does, also noting what was withheld from
+    /// it as likely secrets
+    pub fn record_entry(&mut self, entry: &IndexEntry) {
+        match &entry.withheld {
+            Some(withheld) => {
+                self.withheld_secrets
+                    .insert(entry.metadata.path.clone(), withheld.clone());
+            }
+            None => {
+                self.withheld_secrets.remove(&entry.metadata.path);
+            }
         }
+        self.record_file(entry.metadata.clone(), entry.is_embedded());
+    }
+
+    /// Whether the file under manifest key `key` is indexed without embeddings
+    pub fn is_unembedded(&self, key: &Path) -> bool {
+        self.unembedded
+            .as_ref()
+            .is_some_and(|unembedded| unembedded.contains(key))
+    }
+
+    /// Indexed files whose chunks have no embeddings
+    pub fn unembedded_files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.unembedded
+            .iter()
+            .flatten()
+            .filter(|key| self.files.contains_key(*key))
+    }
+
+    /// Indexed files with likely secrets withheld, and what was withheld
+    pub fn withheld_files(&self) -> impl Iterator<Item = (&PathBuf, &secrets::WithheldSecrets)> {
+        self.withheld_secrets
+            .iter()
+            .filter(|(key, _)| self.files.contains_key(*key))
     }
 }
 
@@ -173,18 +630,94 @@ pub fn collect_files(
     exclude_patterns: &[String],
 ) -> Result<Vec<PathBuf>> {
     let index_dir = path.join(".cs");
+    let walker = build_walker(path, respect_gitignore, exclude_patterns)?;
+    Ok(filter_and_collect_files(walker, &index_dir))
+}
+
+/// Collect archives and compressed files (see `cs_core::archive`) under `path`, with the same
+/// ignore rules as `collect_files`, which skips them as binary
+pub fn collect_archive_files(
+    path: &Path,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+) -> Result<Vec<PathBuf>> {
+    let index_dir = path.join(".cs");
+    let walker = build_walker(path, respect_gitignore, exclude_patterns)?;
+    Ok(walker
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_type().is_some_and(|ft| ft.is_file())
+                && cs_core::archive::is_archive_file(entry.path())
+                && !entry.path().starts_with(&index_dir)
+        })
+        .map(|entry| entry.path().to_path_buf())
+        .collect())
+}
+
+/// Files under a search root that a search passes over, for `cs --stats`
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct SkippedCounts {
+    /// Walked but not text (archives included)
+    pub binary: usize,
+    /// Text files over `max_filesize`
+    pub too_big: usize,
+    /// Hidden, ignored by .gitignore/.csignore, or excluded by pattern
+    pub ignored: usize,
+}
+
+/// Count the files `collect_files` leaves out of `path`, plus text files over `max_filesize`.
+/// Ignored files are the difference to an unfiltered walk (outside `.git` and `.cs`), so this
+/// walks the tree twice and is meant for reporting only.
+pub fn count_skipped_files(
+    path: &Path,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+    max_filesize: Option<u64>,
+) -> Result<SkippedCounts> {
+    let index_dir = path.join(".cs");
+    let is_file = |entry: &ignore::DirEntry| entry.file_type().is_some_and(|ft| ft.is_file());
+
+    let mut counts = SkippedCounts::default();
+    let mut walked = 0;
+    for entry in build_walker(path, respect_gitignore, exclude_patterns)?.filter_map(|e| e.ok()) {
+        if !is_file(&entry) || entry.path().starts_with(&index_dir) {
+            continue;
+        }
+        walked += 1;
+        if !is_text_file(entry.path()) {
+            counts.binary += 1;
+        } else if let Some(max) = max_filesize
+            && entry.metadata().is_ok_and(|metadata| metadata.len() > max)
+        {
+            counts.too_big += 1;
+        }
+    }
+
+    let all_files = WalkBuilder::new(path)
+        .standard_filters(false)
+        .filter_entry(|entry| !matches!(entry.file_name().to_str(), Some(".git" | ".cs")))
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(is_file)
+        .count();
+    counts.ignored = all_files.saturating_sub(walked);
+    Ok(counts)
+}
 
+fn build_walker(
+    path: &Path,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+) -> Result<ignore::Walk> {
     if respect_gitignore {
         let overrides = build_overrides(path, exclude_patterns)?;
-        let walker = WalkBuilder::new(path)
+        Ok(WalkBuilder::new(path)
             .git_ignore(true)
             .git_global(true)
             .git_exclude(true)
             .hidden(true)
             .overrides(overrides)
-            .build();
-
-        Ok(filter_and_collect_files(walker, &index_dir))
+            .build())
     } else {
         // Use WalkBuilder without gitignore support, but still apply overrides
         use cs_core::get_default_exclude_patterns;
@@ -195,13 +728,11 @@ pub fn collect_files(
         all_patterns.extend(exclude_patterns.iter().cloned());
         let combined_overrides = build_overrides(path, &all_patterns)?;
 
-        let walker = WalkBuilder::new(path)
+        Ok(WalkBuilder::new(path)
             .git_ignore(false)
             .hidden(true)
             .overrides(combined_overrides)
-            .build();
-
-        Ok(filter_and_collect_files(walker, &index_dir))
+            .build())
     }
 }
 
@@ -221,6 +752,7 @@ pub async fn index_directory(
     respect_gitignore: bool,
     exclude_patterns: &[String],
     model: Option<&str>,
+    cancel: &CancellationToken,
 ) -> Result<()> {
     tracing::info!(
         "index_directory called with compute_embeddings={}",
@@ -231,46 +763,32 @@ pub async fn index_directory(
 
     let manifest_path = index_dir.join("manifest.json");
     let mut manifest = load_or_create_manifest(&manifest_path)?;
-    normalize_manifest_paths(&mut manifest, path);
+    migrate_manifest(&mut manifest, path)?;
+    manifest.chunking = chunk_overrides(path);
+    manifest.embed_template = embed_template(path);
+    manifest.sparse_vectors = sparse_vectors(path);
+    manifest.secrets_allowed = secrets_allowed(path);
 
     // Handle model configuration for embeddings
     let resolved_model = if compute_embeddings {
         // Resolve the model name and get its dimensions
-        let model_registry = cs_models::ModelRegistry::default();
-        let selected_model = if let Some(model_name) = model {
-            // User specified a model
-            if let Some(model_config) = model_registry.get_model(model_name) {
-                model_config.name.clone()
-            } else {
-                return Err(anyhow::anyhow!(
-                    "Unknown model '{}'. Available models: bge-small, nomic-v1.5, jina-code",
-                    model_name
-                ));
-            }
-        } else {
-            // Use default model
-            let default_config = model_registry
-                .get_default_model()
-                .ok_or_else(|| anyhow::anyhow!("No default model available"))?;
-            default_config.name.clone()
-        };
+        let (_, model_config) = cs_models::ModelRegistry::default().resolve(model)?;
 
         // Set the model info in the manifest for new indexes
-        manifest.embedding_model = Some(selected_model.clone());
-        if let Some(model_name) = model {
-            if let Some(model_config) = model_registry.get_model(model_name) {
-                manifest.embedding_dimensions = Some(model_config.dimensions);
-            }
-        } else if let Some(default_config) = model_registry.get_default_model() {
-            manifest.embedding_dimensions = Some(default_config.dimensions);
-        }
+        manifest.set_embedding_model(model_config.name.clone(), model_config.dimensions);
 
-        Some(selected_model)
+        Some(model_config.name)
     } else {
         None
     };
 
-    let files = collect_files(path, respect_gitignore, exclude_patterns)?;
+    let files = collect_index_files(path, respect_gitignore, exclude_patterns)?;
+    let events = Arc::new(EventLog::start(
+        path,
+        IndexOperation::Build,
+        resolved_model.as_deref(),
+        files.len(),
+    ));
 
     if compute_embeddings {
         // Sequential processing with small-batch embeddings for streaming performance
@@ -278,15 +796,19 @@ pub async fn index_directory(
         let mut embedder = cs_embed::create_embedder(resolved_model.as_deref())?;
 
         for file_path in files.iter() {
+            if INTERRUPTED.load(Ordering::SeqCst) || cancel.is_cancelled() {
+                break;
+            }
+            let file_started = Instant::now();
             match index_single_file(file_path, path, Some(&mut embedder)) {
                 Ok(entry) => {
                     // Write sidecar immediately
                     let sidecar_path = get_sidecar_path(path, file_path);
                     save_index_entry(&sidecar_path, &entry)?;
+                    events.file_indexed(&entry, file_started.elapsed());
 
                     // Update and save manifest immediately
-                    let manifest_key = entry.metadata.path.clone();
-                    manifest.files.insert(manifest_key, entry.metadata);
+                    manifest.record_entry(&entry);
                     manifest.updated = SystemTime::now()
                         .duration_since(SystemTime::UNIX_EPOCH)
                         .unwrap()
@@ -302,6 +824,7 @@ pub async fn index_directory(
 
                     if !(is_binary_skip || is_utf8_error && is_git_file) {
                         tracing::warn!("Failed to index {:?}: {}", file_path, e);
+                        events.file_failed(file_path, &e);
                     }
                 }
             }
@@ -314,13 +837,22 @@ pub async fn index_directory(
         let (tx, rx) = mpsc::channel();
         let files_clone = files.clone();
         let path_clone = path.to_path_buf();
+        let worker_cancel = cancel.clone();
+        let worker_events = events.clone();
 
         // Spawn worker thread for parallel processing
         let worker_handle = thread::spawn(move || {
             files_clone.par_iter().for_each(|file_path| {
+                if INTERRUPTED.load(Ordering::SeqCst) || worker_cancel.is_cancelled() {
+                    return;
+                }
+                let file_started = Instant::now();
                 match index_single_file(file_path, &path_clone, None) {
                     Ok(entry) => {
-                        if tx.send((file_path.clone(), entry)).is_err() {
+                        if tx
+                            .send((file_path.clone(), entry, file_started.elapsed()))
+                            .is_err()
+                        {
                             // Receiver dropped, stop processing
                         }
                     }
@@ -334,6 +866,7 @@ pub async fn index_directory(
 
                         if !(is_binary_skip || is_utf8_error && is_git_file) {
                             tracing::warn!("Failed to index {:?}: {}", file_path, e);
+                            worker_events.file_failed(file_path, &e);
                         }
                     }
                 }
@@ -341,14 +874,19 @@ pub async fn index_directory(
         });
 
         // Main thread: stream results as they arrive
-        while let Ok((file_path, entry)) = rx.recv() {
+        while let Ok((file_path, entry, duration)) = rx.recv() {
+            if INTERRUPTED.load(Ordering::SeqCst) || cancel.is_cancelled() {
+                drop(rx); // Drop receiver to signal worker to stop
+                break;
+            }
+
             // Write sidecar immediately
             let sidecar_path = get_sidecar_path(path, &file_path);
             save_index_entry(&sidecar_path, &entry)?;
+            events.file_indexed(&entry, duration);
 
             // Update and save manifest immediately
-            let manifest_key = entry.metadata.path.clone();
-            manifest.files.insert(manifest_key, entry.metadata);
+            manifest.record_entry(&entry);
             manifest.updated = SystemTime::now()
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap()
@@ -372,6 +910,17 @@ pub async fn index_directory(
         save_manifest(&manifest_path, &manifest)?;
     }
 
+    if cancel.is_cancelled() {
+        events.finish(RunStatus::Cancelled);
+        return Err(cs_core::CcError::Cancelled.into());
+    }
+    events.finish(if INTERRUPTED.load(Ordering::SeqCst) {
+        RunStatus::Cancelled
+    } else {
+        RunStatus::Completed
+    });
+    gc::collect_garbage_after_indexing(path);
+    doc_index::refresh_after_indexing(path);
     Ok(())
 }
 
@@ -382,26 +931,36 @@ pub async fn index_file(file_path: &Path, compute_embeddings: bool) -> Result<()
 
     let manifest_path = index_dir.join("manifest.json");
     let mut manifest = load_or_create_manifest(&manifest_path)?;
-
+    migrate_manifest(&mut manifest, &repo_root)?;
+
+    // Use the model from the existing index, or default if none specified
+    let model_name = manifest.embedding_model.as_deref();
+    let events = EventLog::start(
+        &repo_root,
+        IndexOperation::Add,
+        model_name.filter(|_| compute_embeddings),
+        1,
+    );
+    let file_started = Instant::now();
     let entry = if compute_embeddings {
-        // Use the model from the existing index, or default if none specified
-        let model_name = manifest.embedding_model.as_deref();
         let mut embedder = cs_embed::create_embedder(model_name)?;
-        index_single_file(file_path, &repo_root, Some(&mut embedder))?
+        index_single_file(file_path, &repo_root, Some(&mut embedder))
     } else {
-        index_single_file(file_path, &repo_root, None)?
+        index_single_file(file_path, &repo_root, None)
     };
+    let entry = entry.inspect_err(|e| events.file_failed(file_path, e))?;
     let sidecar_path = get_sidecar_path(&repo_root, file_path);
 
     save_index_entry(&sidecar_path, &entry)?;
-    let manifest_key = entry.metadata.path.clone();
-    manifest.files.insert(manifest_key, entry.metadata);
+    events.file_indexed(&entry, file_started.elapsed());
+    manifest.record_entry(&entry);
     manifest.updated = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap()
         .as_secs();
 
     save_manifest(&manifest_path, &manifest)?;
+    events.finish(RunStatus::Completed);
 
     Ok(())
 }
@@ -420,101 +979,77 @@ pub async fn update_index(
             respect_gitignore,
             exclude_patterns,
             None, // model - use existing from manifest for update
+            &CancellationToken::new(),
         )
         .await;
     }
 
     let manifest_path = index_dir.join("manifest.json");
     let mut manifest = load_or_create_manifest(&manifest_path)?;
+    migrate_manifest(&mut manifest, path)?;
+
+    let files = collect_index_files(path, respect_gitignore, exclude_patterns)?;
+    let stale: Vec<&PathBuf> = files
+        .par_iter()
+        .filter(|file_path| {
+            let manifest_key =
+                path_utils::to_manifest_path(&path_utils::to_standard_path(file_path, path));
+            match manifest.files.get(&manifest_key) {
+                Some(metadata) => match compute_file_hash(file_path) {
+                    Ok(hash) => hash != metadata.hash,
+                    Err(_) => false,
+                },
+                None => true,
+            }
+        })
+        .collect();
+    let model_name = manifest.embedding_model.clone();
+    let events = EventLog::start(
+        path,
+        IndexOperation::Update,
+        model_name.as_deref().filter(|_| compute_embeddings),
+        stale.len(),
+    );
 
-    let files = collect_files(path, respect_gitignore, exclude_patterns)?;
-
-    let updates: Vec<(PathBuf, IndexEntry)> = if compute_embeddings {
-        // Sequential processing when computing embeddings (for memory efficiency)
-        let model_name = manifest.embedding_model.as_deref();
-        let mut embedder = cs_embed::create_embedder(model_name)?;
-        files
-            .iter()
-            .filter_map(|file_path| {
-                let manifest_key =
-                    path_utils::to_manifest_path(&path_utils::to_standard_path(file_path, path));
-
-                let needs_update = match manifest.files.get(&manifest_key) {
-                    Some(metadata) => match compute_file_hash(file_path) {
-                        Ok(hash) => hash != metadata.hash,
-                        Err(_) => false,
-                    },
-                    None => true,
-                };
-                if needs_update {
-                    match index_single_file(file_path, path, Some(&mut embedder)) {
-                        Ok(entry) => Some((file_path.clone(), entry)),
-                        Err(e) => {
-                            // Suppress warnings for binary files and UTF-8 errors in .git directories
-                            let error_msg = e.to_string();
-                            let is_binary_skip = error_msg.contains("Binary file, skipping");
-                            let is_utf8_error =
-                                error_msg.contains("stream did not contain valid UTF-8");
-                            let is_git_file =
-                                file_path.components().any(|c| c.as_os_str() == ".git");
-
-                            if !(is_binary_skip || is_utf8_error && is_git_file) {
-                                tracing::warn!("Failed to index {:?}: {}", file_path, e);
-                            }
-                            None
-                        }
-                    }
-                } else {
-                    None
+    let index = |file_path: &PathBuf, embedder: Option<&mut Box<dyn cs_embed::Embedder>>| {
+        let file_started = Instant::now();
+        match index_single_file(file_path, path, embedder) {
+            Ok(entry) => Some((file_path.clone(), entry, file_started.elapsed())),
+            Err(e) => {
+                // Suppress warnings for binary files and UTF-8 errors in .git directories
+                let error_msg = e.to_string();
+                let is_binary_skip = error_msg.contains("Binary file, skipping");
+                let is_utf8_error = error_msg.contains("stream did not contain valid UTF-8");
+                let is_git_file = file_path.components().any(|c| c.as_os_str() == ".git");
+
+                if !(is_binary_skip || is_utf8_error && is_git_file) {
+                    tracing::warn!("Failed to index {:?}: {}", file_path, e);
+                    events.file_failed(file_path, &e);
                 }
-            })
+                None
+            }
+        }
+    };
+    let updates: Vec<(PathBuf, IndexEntry, std::time::Duration)> = if compute_embeddings {
+        // Sequential processing when computing embeddings (for memory efficiency)
+        let mut embedder = cs_embed::create_embedder(model_name.as_deref())?;
+        stale
+            .into_iter()
+            .filter_map(|file_path| index(file_path, Some(&mut embedder)))
             .collect()
     } else {
         // Parallel processing when not computing embeddings
-        files
-            .par_iter()
-            .filter_map(|file_path| {
-                let manifest_key =
-                    path_utils::to_manifest_path(&path_utils::to_standard_path(file_path, path));
-
-                let needs_update = match manifest.files.get(&manifest_key) {
-                    Some(metadata) => match compute_file_hash(file_path) {
-                        Ok(hash) => hash != metadata.hash,
-                        Err(_) => false,
-                    },
-                    None => true,
-                };
-
-                if needs_update {
-                    match index_single_file(file_path, path, None) {
-                        Ok(entry) => Some((file_path.clone(), entry)),
-                        Err(e) => {
-                            // Suppress warnings for binary files and UTF-8 errors in .git directories
-                            let error_msg = e.to_string();
-                            let is_binary_skip = error_msg.contains("Binary file, skipping");
-                            let is_utf8_error =
-                                error_msg.contains("stream did not contain valid UTF-8");
-                            let is_git_file =
-                                file_path.components().any(|c| c.as_os_str() == ".git");
-
-                            if !(is_binary_skip || is_utf8_error && is_git_file) {
-                                tracing::warn!("Failed to index {:?}: {}", file_path, e);
-                            }
-                            None
-                        }
-                    }
-                } else {
-                    None
-                }
-            })
+        stale
+            .into_par_iter()
+            .filter_map(|file_path| index(file_path, None))
             .collect()
     };
 
-    for (file_path, entry) in updates {
+    for (file_path, entry, duration) in updates {
         let sidecar_path = get_sidecar_path(path, &file_path);
         save_index_entry(&sidecar_path, &entry)?;
-        let manifest_key = entry.metadata.path.clone();
-        manifest.files.insert(manifest_key, entry.metadata);
+        events.file_indexed(&entry, duration);
+        manifest.record_entry(&entry);
     }
 
     if !manifest.files.is_empty() {
@@ -524,14 +1059,48 @@ pub async fn update_index(
             .as_secs();
         save_manifest(&manifest_path, &manifest)?;
     }
+    events.finish(RunStatus::Completed);
 
     Ok(())
 }
 
+/// Remove the index for `path`; the query audit log and the index event log, if any, survive
+/// rebuilds and `--clean`
 pub fn clean_index(path: &Path) -> Result<()> {
+    clean_index_keeping(path, None)
+}
+
+/// `clean_index`, also keeping `staged` (a bundle being imported into `.cs`)
+fn clean_index_keeping(path: &Path, staged: Option<&Path>) -> Result<()> {
     let index_dir = path.join(".cs");
-    if index_dir.exists() {
+    if !index_dir.exists() {
+        return Ok(());
+    }
+    // The logs and the project config (`.cs/config.toml`) outlive the index
+    let audit_dir = audit::audit_dir(path);
+    let config_file = index_dir.join("config.toml");
+    let kept = |entry_path: &Path| {
+        entry_path == audit_dir
+            || entry_path == config_file
+            || events::is_event_log(entry_path)
+            || staged == Some(entry_path)
+    };
+    let entries = fs::read_dir(&index_dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    if !entries.iter().any(|entry_path| kept(entry_path)) {
         fs::remove_dir_all(&index_dir)?;
+        return Ok(());
+    }
+    for entry_path in entries {
+        if kept(&entry_path) {
+            continue;
+        }
+        if entry_path.is_dir() {
+            fs::remove_dir_all(&entry_path)?;
+        } else {
+            fs::remove_file(&entry_path)?;
+        }
     }
     Ok(())
 }
@@ -548,7 +1117,7 @@ pub fn cleanup_index(
 
     let manifest_path = index_dir.join("manifest.json");
     let mut manifest = load_or_create_manifest(&manifest_path)?;
-    normalize_manifest_paths(&mut manifest, path);
+    migrate_manifest(&mut manifest, path)?;
 
     // Use the new unified cleanup validation
     let stats = cleanup_validation::validate_and_cleanup_index(
@@ -576,6 +1145,30 @@ pub fn cleanup_index(
     Ok(stats)
 }
 
+/// Epoch of the index at `repo_root`: a hash of its manifest, which changes whenever an index
+/// run changes the index, even twice within a second; `None` when there is no index. Kept to
+/// 48 bits so it survives JSON consumers that read numbers as doubles.
+pub fn index_epoch(repo_root: &Path) -> Option<u64> {
+    let data = fs::read(repo_root.join(".cs").join("manifest.json")).ok()?;
+    let hash = blake3::hash(&data);
+    let mut bytes = [0u8; 8];
+    bytes[..6].copy_from_slice(&hash.as_bytes()[..6]);
+    Some(u64::from_le_bytes(bytes))
+}
+
+/// Embedding model of the index at `repo_root`; `None` when there is no index or it was built
+/// without embeddings
+pub fn index_embedding_model(repo_root: &Path) -> Option<String> {
+    #[derive(Deserialize)]
+    struct ManifestModel {
+        embedding_model: Option<String>,
+    }
+    let data = fs::read(repo_root.join(".cs").join("manifest.json")).ok()?;
+    serde_json::from_slice::<ManifestModel>(&data)
+        .ok()
+        .and_then(|manifest| manifest.embedding_model)
+}
+
 pub fn get_index_stats(path: &Path) -> Result<IndexStats> {
     let index_dir = path.join(".cs");
     if !index_dir.exists() {
@@ -590,12 +1183,18 @@ pub fn get_index_stats(path: &Path) -> Result<IndexStats> {
         total_files: manifest.files.len(),
         index_created: manifest.created,
         index_updated: manifest.updated,
+        embedding_model: manifest.embedding_model.clone(),
+        embedding_dimensions: manifest.embedding_dimensions,
         ..Default::default()
     };
 
-    // Calculate total chunks and size
-    for file_path in manifest.files.keys() {
+    // Calculate total chunks and size, with breakdowns
+    for (file_path, metadata) in &manifest.files {
         let standard_path = path_utils::from_manifest_path(file_path);
+        if is_stale(&path.join(&standard_path), metadata) {
+            stats.stale_files.push(standard_path.clone());
+        }
+
         let sidecar_path =
             path_utils::get_sidecar_path_for_standard_path(&index_dir, &standard_path);
         if sidecar_path.exists()
@@ -611,8 +1210,48 @@ pub fn get_index_stats(path: &Path) -> Result<IndexStats> {
                 .filter(|c| c.embedding.is_some())
                 .count();
             stats.embedded_chunks += embedded;
+
+            let language = Language::from_path(&standard_path)
+                .map_or_else(|| "other".to_string(), |language| language.to_string());
+            let breakdown = stats.languages.entry(language).or_default();
+            breakdown.files += 1;
+            breakdown.chunks += entry.chunks.len();
+            breakdown.embedded_chunks += embedded;
+
+            if embedded > 0 {
+                let model = entry
+                    .embedding_info
+                    .as_ref()
+                    .map_or_else(|| "unknown".to_string(), |info| info.model.clone());
+                let breakdown = stats.models.entry(model).or_default();
+                breakdown.files += 1;
+                breakdown.chunks += entry.chunks.len();
+                breakdown.embedded_chunks += embedded;
+            }
+
+            for chunk in &entry.chunks {
+                let chunk_type = chunk.chunk_type_name().unwrap_or("text");
+                *stats.chunk_types.entry(chunk_type.to_string()).or_default() += 1;
+            }
+
+            stats.largest_files.push(FileStats {
+                path: standard_path,
+                size_bytes: entry.metadata.size,
+                chunks: entry.chunks.len(),
+            });
         }
     }
+    stats.stale_files.sort();
+    stats.largest_files.sort_by(|a, b| {
+        b.size_bytes
+            .cmp(&a.size_bytes)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    stats.largest_files.truncate(LARGEST_FILES);
+    stats.withheld_secrets = manifest
+        .withheld_files()
+        .map(|(key, withheld)| (path_utils::from_manifest_path(key), withheld.clone()))
+        .collect();
 
     // Calculate index size on disk
     if let Ok(entries) = WalkDir::new(&index_dir)
@@ -631,6 +1270,19 @@ pub fn get_index_stats(path: &Path) -> Result<IndexStats> {
     Ok(stats)
 }
 
+/// Whether a file changed or was deleted since it was indexed, judged by size and mtime
+fn is_stale(file_path: &Path, metadata: &FileMetadata) -> bool {
+    let Ok(fs_meta) = fs::metadata(file_path) else {
+        return true;
+    };
+    let last_modified = fs_meta
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs());
+    fs_meta.len() != metadata.size || last_modified != Some(metadata.last_modified)
+}
+
 pub async fn smart_update_index(
     path: &Path,
     compute_embeddings: bool,
@@ -682,22 +1334,228 @@ pub async fn smart_update_index_with_detailed_progress(
     respect_gitignore: bool,
     exclude_patterns: &[String],
     model: Option<&str>,
+) -> Result<UpdateStats> {
+    smart_update_index_cancellable(
+        path,
+        force_rebuild,
+        progress_callback,
+        detailed_progress_callback,
+        compute_embeddings,
+        respect_gitignore,
+        exclude_patterns,
+        model,
+        &CancellationToken::new(),
+    )
+    .await
+}
+
+/// Indexing that stops once `cancel` is cancelled, returning `CcError::Cancelled`.
+///
+/// Cancellation is checked between files. Every finished file already has its sidecar and
+/// manifest entry written, so a cancelled run leaves a consistent, partly updated index that the
+/// next run completes.
+#[allow(clippy::too_many_arguments)]
+pub async fn smart_update_index_cancellable(
+    path: &Path,
+    force_rebuild: bool,
+    progress_callback: Option<ProgressCallback>,
+    detailed_progress_callback: Option<DetailedProgressCallback>,
+    compute_embeddings: bool,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+    model: Option<&str>,
+    cancel: &CancellationToken,
+) -> Result<UpdateStats> {
+    smart_update_scoped(
+        path,
+        None,
+        force_rebuild,
+        progress_callback,
+        detailed_progress_callback,
+        compute_embeddings,
+        respect_gitignore,
+        exclude_patterns,
+        model,
+        cancel,
+    )
+    .await
+}
+
+/// Update the index at `path` for `files` only, leaving every other entry as it is; with
+/// `force`, the files are re-indexed even when unchanged. Used to scope indexing to a file list
+/// such as the files changed in version control.
+#[allow(clippy::too_many_arguments)]
+pub async fn smart_update_files_cancellable(
+    path: &Path,
+    files: &[PathBuf],
+    force: bool,
+    progress_callback: Option<ProgressCallback>,
+    detailed_progress_callback: Option<DetailedProgressCallback>,
+    compute_embeddings: bool,
+    model: Option<&str>,
+    cancel: &CancellationToken,
+) -> Result<UpdateStats> {
+    smart_update_scoped(
+        path,
+        Some(files),
+        force,
+        progress_callback,
+        detailed_progress_callback,
+        compute_embeddings,
+        true,
+        &[],
+        model,
+        cancel,
+    )
+    .await
+}
+
+/// Update the index at `root` for the files under `paths` only, each a subdirectory or a file,
+/// leaving every other entry as it is. Directories are walked like `--index` walks the root;
+/// entries of files under them that are gone or now excluded are removed, as are entries of
+/// listed files that no longer exist. With `force`, the files are re-indexed even when
+/// unchanged.
+#[allow(clippy::too_many_arguments)]
+pub async fn smart_update_paths_cancellable(
+    root: &Path,
+    paths: &[PathBuf],
+    force: bool,
+    progress_callback: Option<ProgressCallback>,
+    detailed_progress_callback: Option<DetailedProgressCallback>,
+    compute_embeddings: bool,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+    model: Option<&str>,
+    cancel: &CancellationToken,
+) -> Result<UpdateStats> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            files.extend(collect_files(path, respect_gitignore, exclude_patterns)?);
+        } else if path.is_file() {
+            files.push(path.clone());
+        }
+    }
+    let removed = remove_stale_entries(root, paths, &files)?;
+    let mut stats = smart_update_scoped(
+        root,
+        Some(&files),
+        force,
+        progress_callback,
+        detailed_progress_callback,
+        compute_embeddings,
+        respect_gitignore,
+        exclude_patterns,
+        model,
+        cancel,
+    )
+    .await?;
+    stats.orphaned_files_removed += removed;
+    Ok(stats)
+}
+
+/// Remove the entries under `paths` of files missing from `files`; returns how many
+fn remove_stale_entries(root: &Path, paths: &[PathBuf], files: &[PathBuf]) -> Result<usize> {
+    let index_dir = root.join(".cs");
+    let manifest_path = index_dir.join("manifest.json");
+    if !manifest_path.exists() {
+        return Ok(0);
+    }
+    let mut manifest = load_or_create_manifest(&manifest_path)?;
+    migrate_manifest(&mut manifest, root)?;
+    let scopes: Vec<PathBuf> = paths
+        .iter()
+        .map(|path| path_utils::to_standard_path(path, root))
+        .collect();
+    let current: HashSet<PathBuf> = files
+        .iter()
+        .map(|file| path_utils::to_standard_path(file, root))
+        .collect();
+    let stale: Vec<PathBuf> = manifest
+        .files
+        .keys()
+        .filter(|key| {
+            let standard_path = path_utils::from_manifest_path(key);
+            scopes.iter().any(|scope| standard_path.starts_with(scope))
+                && !current.contains(&standard_path)
+        })
+        .cloned()
+        .collect();
+    if stale.is_empty() {
+        return Ok(0);
+    }
+    let mut stats = CleanupStats::default();
+    for key in &stale {
+        cleanup_validation::remove_manifest_entry(
+            &mut manifest,
+            key,
+            root,
+            &index_dir,
+            &mut stats,
+        )?;
+    }
+    manifest.updated = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    save_manifest(&manifest_path, &manifest)?;
+    Ok(stats.orphaned_entries_removed)
+}
+
+/// Incremental update of the files under `path`, or of `scope` when given
+#[allow(clippy::too_many_arguments)]
+async fn smart_update_scoped(
+    path: &Path,
+    scope: Option<&[PathBuf]>,
+    force_rebuild: bool,
+    progress_callback: Option<ProgressCallback>,
+    detailed_progress_callback: Option<DetailedProgressCallback>,
+    compute_embeddings: bool,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+    model: Option<&str>,
+    cancel: &CancellationToken,
 ) -> Result<UpdateStats> {
     let index_dir = path.join(".cs");
     let mut stats = UpdateStats::default();
 
     // Set up interrupt handler (only once per process)
-    HANDLER_INIT.call_once(|| {
-        let _ = ctrlc::set_handler(move || {
-            INTERRUPTED.store(true, Ordering::SeqCst);
-            eprintln!("\nIndexing interrupted by user. Cleaning up...");
-        });
-    });
+    install_interrupt_handler();
 
     // Reset interrupt flag for this indexing operation
     INTERRUPTED.store(false, Ordering::SeqCst);
 
-    if force_rebuild {
+    // Chunks cut or composed with other settings would mix in one index, so rebuild them all
+    let chunking = chunk_overrides(path);
+    let template = embed_template(path);
+    let sparse = sparse_vectors(path);
+    let allow_secrets = secrets_allowed(path);
+    let manifest_path = index_dir.join("manifest.json");
+    let settings_changed = if manifest_path.exists() {
+        let manifest = load_or_create_manifest(&manifest_path)?;
+        [
+            (manifest.chunking != chunking, "Chunk settings"),
+            (manifest.embed_template != template, "Embed template"),
+            (manifest.sparse_vectors != sparse, "Sparse vector setting"),
+            (
+                manifest.secrets_allowed != allow_secrets,
+                "Secret scanning setting",
+            ),
+        ]
+        .into_iter()
+        .find_map(|(changed, setting)| changed.then_some(setting))
+    } else {
+        None
+    };
+    if let Some(setting) = settings_changed {
+        tracing::warn!(
+            "{} changed since the index was built; rebuilding it",
+            setting
+        );
+    }
+
+    // A scoped rebuild re-indexes the listed files below instead of wiping the whole index
+    if (force_rebuild && scope.is_none()) || settings_changed.is_some() {
         clean_index(path)?;
         index_directory(
             path,
@@ -705,10 +1563,12 @@ pub async fn smart_update_index_with_detailed_progress(
             respect_gitignore,
             exclude_patterns,
             model,
+            cancel,
         )
         .await?;
         let index_stats = get_index_stats(path)?;
         stats.files_indexed = index_stats.total_files;
+        stats.secrets_withheld = index_stats.withheld_secrets;
         return Ok(stats);
     }
 
@@ -721,31 +1581,18 @@ pub async fn smart_update_index_with_detailed_progress(
 
     // Then perform incremental update
     fs::create_dir_all(&index_dir)?;
-    let manifest_path = index_dir.join("manifest.json");
     let mut manifest = load_or_create_manifest(&manifest_path)?;
-    normalize_manifest_paths(&mut manifest, &repo_root);
+    migrate_manifest(&mut manifest, &repo_root)?;
+    manifest.chunking = chunking;
+    manifest.embed_template = template;
+    manifest.sparse_vectors = sparse;
+    manifest.secrets_allowed = allow_secrets;
 
     // Handle model configuration for embeddings
     let (resolved_model, _model_dimensions) = if compute_embeddings {
         // Resolve the model name and get its dimensions
-        let model_registry = cs_models::ModelRegistry::default();
-        let (selected_model, model_dims) = if let Some(model_name) = model {
-            // User specified a model
-            if let Some(model_config) = model_registry.get_model(model_name) {
-                (model_config.name.clone(), model_config.dimensions)
-            } else {
-                return Err(anyhow::anyhow!(
-                    "Unknown model '{}'. Available models: bge-small, nomic-v1.5, jina-code",
-                    model_name
-                ));
-            }
-        } else {
-            // Use default model
-            let default_config = model_registry
-                .get_default_model()
-                .ok_or_else(|| anyhow::anyhow!("No default model available"))?;
-            (default_config.name.clone(), default_config.dimensions)
-        };
+        let (_, model_config) = cs_models::ModelRegistry::default().resolve(model)?;
+        let (selected_model, model_dims) = (model_config.name, model_config.dimensions);
 
         // Check for model compatibility with existing index
         let (final_model, final_dims) = if let Some(existing_model) = &manifest.embedding_model {
@@ -789,8 +1636,7 @@ pub async fn smart_update_index_with_detailed_progress(
         } else {
             // This is either a new index or an old index without model info
             // Set the model info in the manifest
-            manifest.embedding_model = Some(selected_model.clone());
-            manifest.embedding_dimensions = Some(model_dims);
+            manifest.set_embedding_model(selected_model.clone(), model_dims);
             (selected_model, model_dims)
         };
 
@@ -801,23 +1647,49 @@ pub async fn smart_update_index_with_detailed_progress(
 
     // For incremental updates, only process files in the search scope
     // The cleanup phase already handled removing orphaned files from the entire repo
-    let current_files = collect_files(path, respect_gitignore, exclude_patterns)?;
+    let current_files = match scope {
+        Some(files) => files
+            .iter()
+            .filter(|file| file.is_file())
+            .cloned()
+            .collect(),
+        None => collect_files(path, respect_gitignore, exclude_patterns)?,
+    };
+    let (current_files, files_skipped) = apply_index_file_limits(current_files);
+    stats.files_skipped = files_skipped;
 
     // First pass: determine which files need updating and collect stats
     let mut files_to_update = Vec::new();
+    let mut backfill = Vec::new();
     let mut manifest_changed = false;
 
     for file_path in current_files {
         // Check for interrupt
         if INTERRUPTED.load(Ordering::SeqCst) {
-            eprintln!("Indexing interrupted during file scanning.");
+            tracing::info!("Indexing interrupted during file scanning");
+            stats.interrupted = true;
             return Ok(stats);
         }
+        if cancel.is_cancelled() {
+            return Err(cs_core::CcError::Cancelled.into());
+        }
 
         let manifest_key =
             path_utils::to_manifest_path(&path_utils::to_standard_path(&file_path, &repo_root));
 
-        if let Some(metadata) = manifest.files.get(&manifest_key) {
+        if force_rebuild {
+            if manifest.files.contains_key(&manifest_key) {
+                stats.files_modified += 1;
+            } else {
+                stats.files_added += 1;
+            }
+            files_to_update.push(file_path);
+        } else if compute_embeddings && manifest.is_unembedded(&manifest_key) {
+            // Indexed without embeddings, so semantic search cannot see it yet
+            stats.files_backfilled += 1;
+            backfill.push(manifest_key);
+            files_to_update.push(file_path);
+        } else if let Some(metadata) = manifest.files.get(&manifest_key) {
             let fs_meta = match fs::metadata(&file_path) {
                 Ok(m) => m,
                 Err(_) => {
@@ -875,18 +1747,31 @@ pub async fn smart_update_index_with_detailed_progress(
     }
 
     // Second pass: index the files that need updating
+    let events = Arc::new(EventLog::start(
+        path,
+        IndexOperation::Update,
+        resolved_model.as_deref(),
+        files_to_update.len(),
+    ));
     if compute_embeddings {
         // Sequential processing with streaming - write each file immediately
         let mut embedder = cs_embed::create_embedder(resolved_model.as_deref())?;
         let mut _processed_count = 0;
+        let mut tracker = ProgressTracker::new(&files_to_update);
+        let shared = if files_to_update.is_empty() {
+            None
+        } else {
+            remote::SharedIndex::open(path, &manifest)
+        };
 
         for file_path in files_to_update.iter() {
             // Check for interrupt
             if INTERRUPTED.load(Ordering::SeqCst) {
-                eprintln!(
-                    "Indexing interrupted. {} files processed.",
-                    _processed_count
-                );
+                tracing::info!("Indexing interrupted after {} files", _processed_count);
+                stats.interrupted = true;
+                break;
+            }
+            if cancel.is_cancelled() {
                 break;
             }
 
@@ -897,28 +1782,41 @@ pub async fn smart_update_index_with_detailed_progress(
             }
 
             // Call detailed progress version if callback is provided, otherwise use regular version
-            let result = if let Some(ref detailed_callback) = detailed_progress_callback {
+            tracker.start_file(file_path);
+            let file_started = Instant::now();
+            let pulled = shared.as_ref().and_then(|shared| shared.fetch(file_path));
+            if pulled.is_some() {
+                stats.files_pulled += 1;
+            }
+            let result = if let Some(entry) = pulled {
+                Ok(entry)
+            } else if let Some(ref detailed_callback) = detailed_progress_callback {
                 index_single_file_with_progress(
                     file_path,
                     path,
                     Some(&mut embedder),
-                    Some(detailed_callback),
-                    _processed_count,
-                    files_to_update.len(),
+                    Some((detailed_callback, &mut tracker)),
                 )
             } else {
                 index_single_file(file_path, path, Some(&mut embedder))
             };
+            tracker.finish_file();
 
             match result {
                 Ok(entry) => {
                     // Write sidecar immediately
                     let sidecar_path = get_sidecar_path(path, file_path);
                     save_index_entry(&sidecar_path, &entry)?;
+                    events.file_indexed(&entry, file_started.elapsed());
 
                     // Update and save manifest immediately
-                    let manifest_key = entry.metadata.path.clone();
-                    manifest.files.insert(manifest_key, entry.metadata);
+                    manifest.record_entry(&entry);
+                    if let Some(withheld) = &entry.withheld {
+                        stats.secrets_withheld.insert(
+                            path_utils::from_manifest_path(&entry.metadata.path),
+                            withheld.clone(),
+                        );
+                    }
                     manifest.updated = SystemTime::now()
                         .duration_since(SystemTime::UNIX_EPOCH)
                         .unwrap()
@@ -935,6 +1833,7 @@ pub async fn smart_update_index_with_detailed_progress(
 
                     if !(is_binary_skip || is_utf8_error && is_git_file) {
                         tracing::warn!("Failed to index {:?}: {}", file_path, e);
+                        events.file_failed(file_path, &e);
                     }
                     stats.files_errored += 1;
                 }
@@ -950,6 +1849,8 @@ pub async fn smart_update_index_with_detailed_progress(
         let (tx, rx) = mpsc::channel();
         let files_clone = files_to_update.clone();
         let path_clone = path.to_path_buf();
+        let worker_cancel = cancel.clone();
+        let worker_events = events.clone();
 
         // Spawn worker thread for parallel processing
         let worker_handle = thread::spawn(move || {
@@ -961,10 +1862,17 @@ pub async fn smart_update_index_with_detailed_progress(
                 if INTERRUPTED.load(Ordering::SeqCst) {
                     return Err("interrupted");
                 }
+                if worker_cancel.is_cancelled() {
+                    return Err("cancelled");
+                }
 
+                let file_started = Instant::now();
                 match index_single_file(file_path, &path_clone, None) {
                     Ok(entry) => {
-                        if tx.send((file_path.clone(), entry)).is_err() {
+                        if tx
+                            .send((file_path.clone(), entry, file_started.elapsed()))
+                            .is_err()
+                        {
                             // Receiver dropped, stop processing
                             return Err("receiver_dropped");
                         }
@@ -979,6 +1887,7 @@ pub async fn smart_update_index_with_detailed_progress(
 
                         if !(is_binary_skip || is_utf8_error && is_git_file) {
                             tracing::warn!("Failed to index {:?}: {}", file_path, e);
+                            worker_events.file_failed(file_path, &e);
                         }
                     }
                 }
@@ -993,16 +1902,18 @@ pub async fn smart_update_index_with_detailed_progress(
 
         // Main thread: stream results as they arrive
         let mut _processed_count = 0;
-        while let Ok((file_path, entry)) = rx.recv() {
+        while let Ok((file_path, entry, duration)) = rx.recv() {
             // Check for interrupt
             if INTERRUPTED.load(Ordering::SeqCst) {
-                eprintln!(
-                    "Indexing interrupted. {} files processed.",
-                    _processed_count
-                );
+                tracing::info!("Indexing interrupted after {} files", _processed_count);
+                stats.interrupted = true;
                 drop(rx); // Drop receiver to signal worker to stop
                 break;
             }
+            if cancel.is_cancelled() {
+                drop(rx);
+                break;
+            }
 
             if let Some(ref callback) = progress_callback
                 && let Some(file_name) = file_path.file_name()
@@ -1013,10 +1924,16 @@ pub async fn smart_update_index_with_detailed_progress(
             // Write sidecar immediately
             let sidecar_path = get_sidecar_path(path, &file_path);
             save_index_entry(&sidecar_path, &entry)?;
+            events.file_indexed(&entry, duration);
 
             // Update and save manifest immediately
-            let manifest_key = entry.metadata.path.clone();
-            manifest.files.insert(manifest_key, entry.metadata);
+            manifest.record_entry(&entry);
+            if let Some(withheld) = &entry.withheld {
+                stats.secrets_withheld.insert(
+                    path_utils::from_manifest_path(&entry.metadata.path),
+                    withheld.clone(),
+                );
+            }
             manifest.updated = SystemTime::now()
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap()
@@ -1033,6 +1950,11 @@ pub async fn smart_update_index_with_detailed_progress(
             .map_err(|_| anyhow::anyhow!("Worker thread panicked"))?;
     }
 
+    stats.files_unembedded = backfill
+        .iter()
+        .filter(|key| manifest.is_unembedded(key))
+        .count();
+
     // For sequential processing (embeddings), manifest is already saved after each file
     // Only save manifest for parallel processing or if there were metadata-only changes
     if !compute_embeddings
@@ -1045,6 +1967,20 @@ pub async fn smart_update_index_with_detailed_progress(
         save_manifest(&manifest_path, &manifest)?;
     }
 
+    if cancel.is_cancelled() {
+        events.finish(RunStatus::Cancelled);
+        return Err(cs_core::CcError::Cancelled.into());
+    }
+    events.finish(if INTERRUPTED.load(Ordering::SeqCst) {
+        RunStatus::Cancelled
+    } else {
+        RunStatus::Completed
+    });
+    if stats.files_indexed > 0 || stats.orphaned_files_removed > 0 {
+        gc::collect_garbage_after_indexing(path);
+    }
+    // Also catches up on index changes made outside indexing runs
+    doc_index::refresh_after_indexing(path);
     Ok(stats)
 }
 
@@ -1053,16 +1989,14 @@ fn index_single_file(
     repo_root: &Path,
     embedder: Option<&mut Box<dyn cs_embed::Embedder>>,
 ) -> Result<IndexEntry> {
-    index_single_file_with_progress(file_path, repo_root, embedder, None, 0, 1)
+    index_single_file_with_progress(file_path, repo_root, embedder, None)
 }
 
 fn index_single_file_with_progress(
     file_path: &Path,
     repo_root: &Path,
     embedder: Option<&mut Box<dyn cs_embed::Embedder>>,
-    detailed_progress: Option<&DetailedProgressCallback>,
-    file_index: usize,
-    total_files: usize,
+    detailed_progress: Option<(&DetailedProgressCallback, &mut ProgressTracker)>,
 ) -> Result<IndexEntry> {
     // Skip binary files to avoid UTF-8 warnings
     if !is_text_file(file_path) {
@@ -1071,7 +2005,7 @@ fn index_single_file_with_progress(
 
     // Preprocess file (extracts PDFs to cache, returns path to readable content)
     let content_path = preprocess_file(file_path, repo_root)?;
-    let content = fs::read_to_string(&content_path)?;
+    let content = cs_core::encoding::read_to_string(&content_path)?;
 
     // Always use the ORIGINAL file for hash and metadata
     let hash = compute_file_hash(file_path)?;
@@ -1090,6 +2024,10 @@ fn index_single_file_with_progress(
         size: metadata.len(),
     };
 
+    let embedding_info = embedder
+        .as_ref()
+        .map(|embedder| EmbeddingInfo::new(embedder.model_name(), embedder.dim()));
+
     // Detect language for tree-sitter parsing
     let lang = if cs_core::pdf::is_pdf_file(file_path) {
         Some(Language::Pdf)
@@ -1098,7 +2036,32 @@ fn index_single_file_with_progress(
     };
 
     let model_name = embedder.as_ref().map(|e| e.model_name());
-    let chunks = cs_chunk::chunk_text_with_model(&content, lang, model_name)?;
+    let template = embed_template(repo_root);
+    let chunks = cs_chunk::chunk_text_with_overrides(
+        &content,
+        lang,
+        model_name,
+        &chunk_overrides(repo_root),
+    )?;
+    // Chunks holding likely secrets are neither embedded nor stored
+    let (chunks, withheld) = if secrets_allowed(repo_root) {
+        (chunks, None)
+    } else {
+        secrets::withhold(file_path, &content, chunks)
+    };
+
+    let sparse = (embedder.is_some() && sparse_vectors(repo_root)).then(|| {
+        chunks
+            .iter()
+            .map(|chunk| {
+                sparse::sparse_vector(&embedding_input(
+                    chunk,
+                    &file_metadata.path,
+                    template.as_ref(),
+                ))
+            })
+            .collect()
+    });
 
     let chunk_entries: Vec<ChunkEntry> = if let Some(embedder) = embedder {
         let total_chunks = chunks.len();
@@ -1109,7 +2072,7 @@ fn index_single_file_with_progress(
             .to_string();
 
         // Process chunks with progress reporting
-        if let Some(ref callback) = detailed_progress {
+        if let Some((callback, tracker)) = detailed_progress {
             tracing::info!(
                 "Computing embeddings for {} chunks in {:?}",
                 total_chunks,
@@ -1124,15 +2087,17 @@ fn index_single_file_with_progress(
                 // Report progress before processing chunk
                 callback(EmbeddingProgress {
                     file_name: file_name.clone(),
-                    file_index,
-                    total_files,
+                    file_index: tracker.files_done(),
+                    total_files: tracker.files_total(),
                     chunk_index,
                     total_chunks,
                     chunk_size: chunk.text.len(),
+                    overall: tracker.snapshot(chunk_index as f64 / total_chunks as f64),
                 });
 
                 // Embed single chunk
-                let embeddings = embedder.embed(std::slice::from_ref(&chunk.text))?;
+                let text = embedding_input(&chunk, &file_metadata.path, template.as_ref());
+                let embeddings = embedder.embed(&[text])?;
                 let embedding = embeddings.into_iter().next().ok_or_else(|| {
                     anyhow::anyhow!(
                         "Embedder returned empty results for chunk {} in file {:?}. This may indicate an issue with the embedding model or chunk content.",
@@ -1140,14 +2105,7 @@ fn index_single_file_with_progress(
                         file_path
                     )
                 })?;
-
-                let chunk_type_str = match chunk.chunk_type {
-                    cs_chunk::ChunkType::Function => Some("function".to_string()),
-                    cs_chunk::ChunkType::Class => Some("class".to_string()),
-                    cs_chunk::ChunkType::Method => Some("method".to_string()),
-                    cs_chunk::ChunkType::Module => Some("module".to_string()),
-                    cs_chunk::ChunkType::Text => None,
-                };
+                tracker.chunk_embedded();
 
                 let breadcrumb = chunk.metadata.breadcrumb.clone();
                 let ancestry = if chunk.metadata.ancestry.is_empty() {
@@ -1169,19 +2127,23 @@ fn index_single_file_with_progress(
                 chunk_entries.push(ChunkEntry {
                     span: chunk.span,
                     embedding: Some(embedding),
-                    chunk_type: chunk_type_str,
+                    chunk_type: chunk.chunk_type,
                     breadcrumb,
                     ancestry,
                     byte_length: Some(chunk.metadata.byte_length),
                     estimated_tokens: Some(chunk.metadata.estimated_tokens),
                     leading_trivia,
                     trailing_trivia,
+                    stride: chunk.stride_info,
                 });
             }
             chunk_entries
         } else {
             // Fallback to batch processing for backward compatibility
-            let chunk_texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+            let chunk_texts: Vec<String> = chunks
+                .iter()
+                .map(|c| embedding_input(c, &file_metadata.path, template.as_ref()))
+                .collect();
             tracing::info!(
                 "Computing embeddings for {} chunks in {:?}",
                 chunk_texts.len(),
@@ -1203,13 +2165,6 @@ fn index_single_file_with_progress(
                 .into_iter()
                 .zip(embeddings)
                 .map(|(chunk, embedding)| {
-                    let chunk_type_str = match chunk.chunk_type {
-                        cs_chunk::ChunkType::Function => Some("function".to_string()),
-                        cs_chunk::ChunkType::Class => Some("class".to_string()),
-                        cs_chunk::ChunkType::Method => Some("method".to_string()),
-                        cs_chunk::ChunkType::Module => Some("module".to_string()),
-                        cs_chunk::ChunkType::Text => None,
-                    };
                     let breadcrumb = chunk.metadata.breadcrumb.clone();
                     let ancestry = if chunk.metadata.ancestry.is_empty() {
                         None
@@ -1229,13 +2184,14 @@ fn index_single_file_with_progress(
                     ChunkEntry {
                         span: chunk.span,
                         embedding: Some(embedding),
-                        chunk_type: chunk_type_str,
+                        chunk_type: chunk.chunk_type,
                         breadcrumb,
                         ancestry,
                         byte_length: Some(chunk.metadata.byte_length),
                         estimated_tokens: Some(chunk.metadata.estimated_tokens),
                         leading_trivia,
                         trailing_trivia,
+                        stride: chunk.stride_info,
                     }
                 })
                 .collect()
@@ -1245,13 +2201,6 @@ fn index_single_file_with_progress(
         chunks
             .into_iter()
             .map(|chunk| {
-                let chunk_type_str = match chunk.chunk_type {
-                    cs_chunk::ChunkType::Function => Some("function".to_string()),
-                    cs_chunk::ChunkType::Class => Some("class".to_string()),
-                    cs_chunk::ChunkType::Method => Some("method".to_string()),
-                    cs_chunk::ChunkType::Module => Some("module".to_string()),
-                    cs_chunk::ChunkType::Text => None,
-                };
                 let breadcrumb = chunk.metadata.breadcrumb.clone();
                 let ancestry = if chunk.metadata.ancestry.is_empty() {
                     None
@@ -1271,13 +2220,14 @@ fn index_single_file_with_progress(
                 ChunkEntry {
                     span: chunk.span,
                     embedding: None,
-                    chunk_type: chunk_type_str,
+                    chunk_type: chunk.chunk_type,
                     breadcrumb,
                     ancestry,
                     byte_length: Some(chunk.metadata.byte_length),
                     estimated_tokens: Some(chunk.metadata.estimated_tokens),
                     leading_trivia,
                     trailing_trivia,
+                    stride: chunk.stride_info,
                 }
             })
             .collect()
@@ -1286,6 +2236,9 @@ fn index_single_file_with_progress(
     Ok(IndexEntry {
         metadata: file_metadata,
         chunks: chunk_entries,
+        embedding_info,
+        sparse,
+        withheld,
     })
 }
 
@@ -1298,6 +2251,96 @@ fn load_or_create_manifest(path: &Path) -> Result<IndexManifest> {
     }
 }
 
+/// Rewrite manifest keys recorded before they were always root-relative. Older runs stored
+/// absolute keys when the file and root paths were spelled differently (another working
+/// directory, a canonicalized root), and put those files' sidecars next to the source file
+/// instead of under `.cs/`. Keys under the root become relative and their sidecars move into
+/// the index, and the migrated manifest is saved; returns how many keys changed. Manifests
+/// that do not yet record which files lack embeddings read it from the sidecars. Callers that
+/// write the index run this in place of `normalize_manifest_paths`.
+fn migrate_manifest(manifest: &mut IndexManifest, repo_root: &Path) -> Result<usize> {
+    let index_dir = repo_root.join(".cs");
+    let mut migrated = 0;
+    for key in manifest.files.keys() {
+        let standard_key = if key.is_absolute() {
+            path_utils::to_standard_path(key, repo_root)
+        } else {
+            path_utils::from_manifest_path(key)
+        };
+        if path_utils::to_manifest_path(&standard_key) == *key {
+            continue;
+        }
+        migrated += 1;
+        if !key.is_absolute() || standard_key.is_absolute() {
+            continue;
+        }
+        let legacy = path_utils::get_sidecar_path_for_standard_path(&index_dir, key);
+        let sidecar = path_utils::get_sidecar_path_for_standard_path(&index_dir, &standard_key);
+        // Only a file that is really this entry's sidecar moves; `Foo.cs` may be a C# source
+        if !is_sidecar_of(&legacy, key, &standard_key, repo_root) {
+            continue;
+        }
+        if sidecar.exists() {
+            fs::remove_file(&legacy)?;
+        } else {
+            if let Some(parent) = sidecar.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&legacy, &sidecar)?;
+        }
+    }
+    normalize_manifest_paths(manifest, repo_root);
+    if migrated > 0 {
+        tracing::info!(
+            "Migrated {} manifest entries to index-root-relative paths",
+            migrated
+        );
+    }
+
+    // Manifests that predate coverage tracking learn it from the sidecars, once
+    let coverage_unknown = manifest.unembedded.is_none();
+    if coverage_unknown {
+        let unembedded = manifest
+            .files
+            .keys()
+            .filter(|key| {
+                let sidecar = path_utils::get_sidecar_path_for_standard_path(
+                    &index_dir,
+                    &path_utils::from_manifest_path(key),
+                );
+                load_index_entry(&sidecar).is_ok_and(|entry| !entry.is_embedded())
+            })
+            .cloned()
+            .collect();
+        manifest.unembedded = Some(unembedded);
+    }
+
+    if migrated > 0 || coverage_unknown {
+        save_manifest(&index_dir.join("manifest.json"), manifest)?;
+    }
+    Ok(migrated)
+}
+
+fn is_sidecar_file(path: &Path) -> bool {
+    use std::io::Read;
+    let mut magic = [0u8; SIDECAR_MAGIC.len()];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| magic == *SIDECAR_MAGIC)
+}
+
+/// Whether `path` holds a sidecar, in any layout, for the file keyed `key` in the manifest.
+/// Sidecars written beside sources predate `SIDECAR_MAGIC`, so the recorded file path tells.
+fn is_sidecar_of(path: &Path, key: &Path, standard_key: &Path, repo_root: &Path) -> bool {
+    load_index_entry(path).is_ok_and(|entry| {
+        let recorded = &entry.metadata.path;
+        recorded == key
+            || (recorded.is_absolute()
+                && path_utils::to_standard_path(recorded, repo_root) == standard_key)
+            || path_utils::from_manifest_path(recorded) == standard_key
+    })
+}
+
 fn normalize_manifest_paths(manifest: &mut IndexManifest, repo_root: &Path) {
     let original_entries = std::mem::take(&mut manifest.files);
     let mut normalized = HashMap::with_capacity(original_entries.len());
@@ -1328,8 +2371,9 @@ fn save_manifest(path: &Path, manifest: &IndexManifest) -> Result<()> {
     atomic_write(path, &data)
 }
 
-fn save_index_entry(path: &Path, entry: &IndexEntry) -> Result<()> {
-    let data = bincode::serialize(entry)?;
+pub fn save_index_entry(path: &Path, entry: &IndexEntry) -> Result<()> {
+    let mut data = SIDECAR_MAGIC.to_vec();
+    bincode::serialize_into(&mut data, entry)?;
     atomic_write(path, &data)
 }
 
@@ -1350,8 +2394,45 @@ fn atomic_write(path: &Path, data: &[u8]) -> Result<()> {
 }
 
 pub fn load_index_entry(path: &Path) -> Result<IndexEntry> {
-    let data = fs::read(path)?;
-    Ok(bincode::deserialize(&data)?)
+    decode_index_entry(&fs::read(path)?)
+}
+
+/// A sidecar's bytes, in the current or an older layout
+fn decode_index_entry(data: &[u8]) -> Result<IndexEntry> {
+    if let Some(entry) = data.strip_prefix(SIDECAR_MAGIC.as_slice()) {
+        return Ok(bincode::deserialize(entry)?);
+    }
+    let typed = |chunks: Vec<UntypedChunkEntry>| chunks.into_iter().map(ChunkEntry::from).collect();
+    match bincode::deserialize::<UntypedIndexEntry>(data) {
+        Ok(entry) => Ok(IndexEntry {
+            metadata: entry.metadata,
+            chunks: typed(entry.chunks),
+            embedding_info: entry.embedding_info,
+            sparse: entry.sparse,
+            withheld: None,
+        }),
+        Err(e) => {
+            if let Ok(dense) = bincode::deserialize::<DenseIndexEntry>(data) {
+                return Ok(IndexEntry {
+                    metadata: dense.metadata,
+                    chunks: typed(dense.chunks),
+                    embedding_info: dense.embedding_info,
+                    sparse: None,
+                    withheld: None,
+                });
+            }
+            let Ok(legacy) = bincode::deserialize::<LegacyIndexEntry>(data) else {
+                return Err(e.into());
+            };
+            Ok(IndexEntry {
+                metadata: legacy.metadata,
+                chunks: typed(legacy.chunks),
+                embedding_info: None,
+                sparse: None,
+                withheld: None,
+            })
+        }
+    }
 }
 
 fn find_repo_root(path: &Path) -> Result<PathBuf> {
@@ -1441,8 +2522,9 @@ fn is_text_file(path: &Path) -> bool {
                         return true;
                     }
 
-                    // Check for NUL bytes in the read portion
-                    !buffer[..bytes_read].contains(&0)
+                    // Check for NUL bytes in the read portion; UTF-16 text has them too
+                    let sample = &buffer[..bytes_read];
+                    !sample.contains(&0) || cs_core::encoding::is_utf16(sample)
                 }
                 Err(_) => false, // If we can't read, assume binary
             }
@@ -1508,8 +2590,59 @@ pub struct IndexStats {
     pub index_size_bytes: u64,
     pub index_created: u64,
     pub index_updated: u64,
+    /// Model the index was built with, from the manifest
+    #[serde(default)]
+    pub embedding_model: Option<String>,
+    #[serde(default)]
+    pub embedding_dimensions: Option<usize>,
+    /// Files and chunks per language ("other" for files without a known language)
+    #[serde(default)]
+    pub languages: BTreeMap<String, BreakdownStats>,
+    /// Chunks per chunk type ("text" for chunks outside any code structure)
+    #[serde(default)]
+    pub chunk_types: BTreeMap<String, usize>,
+    /// Files and chunks per embedding model ("unknown" for sidecars that predate recording it)
+    #[serde(default)]
+    pub models: BTreeMap<String, BreakdownStats>,
+    /// Indexed files changed or deleted since they were indexed
+    #[serde(default)]
+    pub stale_files: Vec<PathBuf>,
+    /// Largest indexed files, biggest first
+    #[serde(default)]
+    pub largest_files: Vec<FileStats>,
+    /// Indexed files with likely secrets withheld (relative to the index root), and what was
+    /// withheld
+    #[serde(default)]
+    pub withheld_secrets: BTreeMap<PathBuf, secrets::WithheldSecrets>,
+}
+
+impl IndexStats {
+    /// Share of chunks that have an embedding, 0.0 to 1.0
+    pub fn embedding_coverage(&self) -> f64 {
+        if self.total_chunks == 0 {
+            return 0.0;
+        }
+        self.embedded_chunks as f64 / self.total_chunks as f64
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BreakdownStats {
+    pub files: usize,
+    pub chunks: usize,
+    pub embedded_chunks: usize,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileStats {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub chunks: usize,
+}
+
+/// Files listed in `IndexStats::largest_files`
+const LARGEST_FILES: usize = 10;
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateStats {
     pub files_indexed: usize,
@@ -1518,6 +2651,25 @@ pub struct UpdateStats {
     pub files_up_to_date: usize,
     pub files_errored: usize,
     pub orphaned_files_removed: usize,
+    /// Left out by `--max-filesize` or `--max-files`
+    #[serde(default)]
+    pub files_skipped: usize,
+    /// Unchanged files reindexed because they had been indexed without embeddings
+    #[serde(default)]
+    pub files_backfilled: usize,
+    /// Of those, files that still have no embeddings (the update was interrupted or failed)
+    #[serde(default)]
+    pub files_unembedded: usize,
+    /// Indexed files whose sidecar came from the remote index instead of being embedded
+    #[serde(default)]
+    pub files_pulled: usize,
+    /// Indexed files with likely secrets withheld (relative to the index root), and what was
+    /// withheld
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub secrets_withheld: BTreeMap<PathBuf, secrets::WithheldSecrets>,
+    /// Ctrl-C stopped the update early; the index holds the files done so far
+    #[serde(default)]
+    pub interrupted: bool,
 }
 
 #[cfg(test)]
@@ -1615,9 +2767,10 @@ mod tests {
             &test_file,
             test_path,
             Some(&mut empty_embedder),
-            Some(&dummy_callback),
-            0,
-            1,
+            Some((
+                &dummy_callback,
+                &mut ProgressTracker::new(std::slice::from_ref(&test_file)),
+            )),
         );
 
         assert!(result.is_err());
@@ -1682,6 +2835,143 @@ mod tests {
         }
     }
 
+    /// Chunks in the layout sidecars had before typed kinds and strides
+    fn untyped(chunks: &[ChunkEntry]) -> Vec<UntypedChunkEntry> {
+        chunks
+            .iter()
+            .map(|chunk| UntypedChunkEntry {
+                span: chunk.span.clone(),
+                embedding: chunk.embedding.clone(),
+                chunk_type: chunk.chunk_type_name().map(str::to_string),
+                breadcrumb: chunk.breadcrumb.clone(),
+                ancestry: chunk.ancestry.clone(),
+                byte_length: chunk.byte_length,
+                estimated_tokens: chunk.estimated_tokens,
+                leading_trivia: chunk.leading_trivia.clone(),
+                trailing_trivia: chunk.trailing_trivia.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sidecar_keeps_chunk_types_and_strides() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("lib.rs");
+        fs::write(&test_file, "fn first() {}\n\nfn second() {}\n").unwrap();
+        let sidecar = temp_dir.path().join("lib.rs.cs");
+
+        let mut entry = index_single_file(&test_file, temp_dir.path(), None).unwrap();
+        assert!(!entry.chunks.is_empty());
+        assert!(
+            entry
+                .chunks
+                .iter()
+                .all(|chunk| chunk.chunk_type == ChunkType::Function && chunk.stride.is_none())
+        );
+        entry.chunks[0].stride = Some(StrideInfo {
+            original_chunk_id: "first".to_string(),
+            stride_index: 1,
+            total_strides: 3,
+            overlap_start: 4,
+            overlap_end: 0,
+        });
+        save_index_entry(&sidecar, &entry).unwrap();
+        let loaded = load_index_entry(&sidecar).unwrap();
+        assert_eq!(loaded.chunks[0].chunk_type, ChunkType::Function);
+        let stride = loaded.chunks[0].stride.as_ref().unwrap();
+        assert_eq!(stride.original_chunk_id, "first");
+        assert_eq!((stride.stride_index, stride.total_strides), (1, 3));
+
+        // Sidecars whose chunks name their kind load with typed kinds and no strides
+        #[derive(Serialize)]
+        struct UntypedEntry<'a> {
+            metadata: &'a FileMetadata,
+            chunks: Vec<UntypedChunkEntry>,
+            embedding_info: &'a Option<EmbeddingInfo>,
+            sparse: &'a Option<Vec<sparse::SparseVector>>,
+        }
+        let untyped_entry = UntypedEntry {
+            metadata: &entry.metadata,
+            chunks: untyped(&entry.chunks),
+            embedding_info: &entry.embedding_info,
+            sparse: &entry.sparse,
+        };
+        fs::write(&sidecar, bincode::serialize(&untyped_entry).unwrap()).unwrap();
+        let loaded = load_index_entry(&sidecar).unwrap();
+        assert_eq!(loaded.chunks.len(), entry.chunks.len());
+        assert_eq!(loaded.chunks[0].chunk_type, ChunkType::Function);
+        assert_eq!(loaded.chunks[0].chunk_type_name(), Some("function"));
+        assert!(loaded.chunks[0].stride.is_none());
+    }
+
+    #[test]
+    fn test_sidecar_records_embedding_info() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("test.txt");
+        fs::write(&test_file, "hello world").unwrap();
+        let sidecar = temp_dir.path().join("test.txt.cs");
+
+        let mut embedder: Box<dyn cs_embed::Embedder> =
+            Box::new(cs_embed::DummyEmbedder::new_with_model("test-model"));
+        let entry = index_single_file(&test_file, temp_dir.path(), Some(&mut embedder)).unwrap();
+        save_index_entry(&sidecar, &entry).unwrap();
+        let info = load_index_entry(&sidecar).unwrap().embedding_info.unwrap();
+        assert_eq!(info, EmbeddingInfo::new("test-model", 384));
+        assert_eq!(info.quantization, "f32");
+
+        // Sidecars written before the model was recorded still load
+        #[derive(Serialize)]
+        struct LegacyEntry<'a> {
+            metadata: &'a FileMetadata,
+            chunks: Vec<UntypedChunkEntry>,
+        }
+        let legacy = LegacyEntry {
+            metadata: &entry.metadata,
+            chunks: untyped(&entry.chunks),
+        };
+        fs::write(&sidecar, bincode::serialize(&legacy).unwrap()).unwrap();
+        let loaded = load_index_entry(&sidecar).unwrap();
+        assert_eq!(loaded.chunks.len(), entry.chunks.len());
+        assert_eq!(loaded.embedding_info, None);
+    }
+
+    #[test]
+    fn test_sidecar_stores_sparse_vectors() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("retry.rs");
+        fs::write(&test_file, "fn parse_retry_policy() -> u32 {\n    3\n}\n").unwrap();
+        let sidecar = temp_dir.path().join("retry.rs.cs");
+        let mut embedder: Box<dyn cs_embed::Embedder> = Box::new(cs_embed::DummyEmbedder::new());
+
+        let entry = index_single_file(&test_file, temp_dir.path(), Some(&mut embedder)).unwrap();
+        assert!(entry.sparse.is_none());
+
+        fs::write(temp_dir.path().join("cs.toml"), "sparse = true\n").unwrap();
+        let entry = index_single_file(&test_file, temp_dir.path(), Some(&mut embedder)).unwrap();
+        save_index_entry(&sidecar, &entry).unwrap();
+        let sparse = load_index_entry(&sidecar).unwrap().sparse.unwrap();
+        assert_eq!(sparse.len(), entry.chunks.len());
+        let query = sparse::query_terms("retry policy");
+        assert!(query.iter().all(|term| sparse[0].get(*term) > 0.0));
+
+        // Sidecars written before sparse vectors still load
+        #[derive(Serialize)]
+        struct DenseEntry<'a> {
+            metadata: &'a FileMetadata,
+            chunks: Vec<UntypedChunkEntry>,
+            embedding_info: &'a Option<EmbeddingInfo>,
+        }
+        let dense = DenseEntry {
+            metadata: &entry.metadata,
+            chunks: untyped(&entry.chunks),
+            embedding_info: &entry.embedding_info,
+        };
+        fs::write(&sidecar, bincode::serialize(&dense).unwrap()).unwrap();
+        let loaded = load_index_entry(&sidecar).unwrap();
+        assert_eq!(loaded.embedding_info, entry.embedding_info);
+        assert!(loaded.sparse.is_none());
+    }
+
     #[tokio::test]
     async fn test_smart_update_index() {
         let temp_dir = TempDir::new().unwrap();
@@ -1722,6 +3012,344 @@ mod tests {
         assert_eq!(stats4.files_indexed, 1);
     }
 
+    #[tokio::test]
+    async fn test_smart_update_files_only_touches_listed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+        fs::write(test_path.join("file1.txt"), "initial content").unwrap();
+        fs::write(test_path.join("file2.txt"), "other content").unwrap();
+        let cancel = CancellationToken::new();
+
+        let listed = [test_path.join("file1.txt")];
+        let stats = smart_update_files_cancellable(
+            test_path, &listed, false, None, None, false, None, &cancel,
+        )
+        .await
+        .unwrap();
+        assert_eq!((stats.files_added, stats.files_indexed), (1, 1));
+        assert_eq!(get_index_stats(test_path).unwrap().total_files, 1);
+
+        // Unchanged files are skipped unless forced
+        let stats = smart_update_files_cancellable(
+            test_path, &listed, false, None, None, false, None, &cancel,
+        )
+        .await
+        .unwrap();
+        assert_eq!((stats.files_up_to_date, stats.files_indexed), (1, 0));
+        let stats = smart_update_files_cancellable(
+            test_path, &listed, true, None, None, false, None, &cancel,
+        )
+        .await
+        .unwrap();
+        assert_eq!((stats.files_modified, stats.files_indexed), (1, 1));
+        assert_eq!(get_index_stats(test_path).unwrap().total_files, 1);
+    }
+
+    #[tokio::test]
+    async fn test_smart_update_paths_only_touches_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+        fs::create_dir_all(test_path.join("sub")).unwrap();
+        fs::write(test_path.join("sub/a.txt"), "first").unwrap();
+        fs::write(test_path.join("sub/b.txt"), "second").unwrap();
+        fs::write(test_path.join("other.txt"), "outside").unwrap();
+        smart_update_index(test_path, false, true, &[])
+            .await
+            .unwrap();
+        let cancel = CancellationToken::new();
+        let scope = [test_path.join("sub")];
+
+        let stats = smart_update_paths_cancellable(
+            test_path,
+            &scope,
+            true,
+            None,
+            None,
+            false,
+            true,
+            &[],
+            None,
+            &cancel,
+        )
+        .await
+        .unwrap();
+        assert_eq!((stats.files_modified, stats.files_indexed), (2, 2));
+
+        // Files gone from the subdirectory lose their entries; the rest stay
+        fs::remove_file(test_path.join("sub/b.txt")).unwrap();
+        fs::write(test_path.join("other.txt"), "changed, but outside").unwrap();
+        let stats = smart_update_paths_cancellable(
+            test_path,
+            &scope,
+            false,
+            None,
+            None,
+            false,
+            true,
+            &[],
+            None,
+            &cancel,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            (stats.orphaned_files_removed, stats.files_up_to_date),
+            (1, 1)
+        );
+        assert_eq!(get_index_stats(test_path).unwrap().total_files, 2);
+        assert!(!test_path.join(".cs/sub/b.txt.cs").exists());
+    }
+
+    #[tokio::test]
+    async fn test_smart_update_skips_files_over_default_size_cap() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+        fs::write(test_path.join("small.txt"), "small content").unwrap();
+        let huge = "line of log output\n".repeat(DEFAULT_MAX_EMBED_FILESIZE as usize / 16);
+        fs::write(test_path.join("huge.txt"), huge).unwrap();
+
+        let stats = smart_update_index(test_path, false, true, &[])
+            .await
+            .unwrap();
+        assert_eq!((stats.files_indexed, stats.files_skipped), (1, 1));
+        assert_eq!(get_index_stats(test_path).unwrap().total_files, 1);
+    }
+
+    #[tokio::test]
+    async fn test_smart_update_rebuilds_when_chunk_settings_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+        fs::write(test_path.join("file1.txt"), "initial content").unwrap();
+        fs::write(test_path.join("file2.txt"), "other content").unwrap();
+        smart_update_index(test_path, false, true, &[])
+            .await
+            .unwrap();
+
+        let stats = smart_update_index(test_path, false, true, &[])
+            .await
+            .unwrap();
+        assert_eq!(stats.files_indexed, 0);
+
+        fs::write(test_path.join("cs.toml"), "chunk_tokens = 100\n").unwrap();
+        let stats = smart_update_index(test_path, false, true, &[])
+            .await
+            .unwrap();
+        assert_eq!(stats.files_indexed, 3);
+        let manifest = load_or_create_manifest(&test_path.join(".cs/manifest.json")).unwrap();
+        assert_eq!(manifest.chunking.max_tokens, Some(100));
+
+        fs::write(
+            test_path.join("cs.toml"),
+            "chunk_tokens = 100\nembed_template = \"{path} :: {symbol} :: {code}\"\n",
+        )
+        .unwrap();
+        let stats = smart_update_index(test_path, false, true, &[])
+            .await
+            .unwrap();
+        assert_eq!(stats.files_indexed, 3);
+        let manifest = load_or_create_manifest(&test_path.join(".cs/manifest.json")).unwrap();
+        assert_eq!(
+            manifest.embed_template.unwrap().as_str(),
+            "{path} :: {symbol} :: {code}"
+        );
+    }
+
+    #[test]
+    fn test_embedding_input_with_template() {
+        let source = "impl Auth {\n    fn check(&self) {}\n}\n";
+        let chunks = cs_chunk::chunk_text(source, Some(Language::Rust)).unwrap();
+        let method = chunks
+            .iter()
+            .find(|chunk| chunk.metadata.name.as_deref() == Some("check"))
+            .unwrap();
+        let path = Path::new("src/auth/middleware.rs");
+        assert_eq!(embedding_input(method, path, None), method.text);
+
+        let template: EmbedTemplate = "{path} :: {symbol} :: {code}".parse().unwrap();
+        assert_eq!(
+            embedding_input(method, path, Some(&template)),
+            format!("src/auth/middleware.rs :: Auth::check :: {}", method.text)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_update_leaves_index_usable() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+        fs::write(test_path.join("file1.txt"), "initial content").unwrap();
+        smart_update_index(test_path, false, true, &[])
+            .await
+            .unwrap();
+
+        fs::write(test_path.join("file2.txt"), "new file content").unwrap();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let err = smart_update_index_cancellable(
+            test_path,
+            false,
+            None,
+            None,
+            false,
+            true,
+            &[],
+            None,
+            &cancel,
+        )
+        .await
+        .unwrap_err();
+        assert!(cs_core::CcError::is_cancelled(&err));
+
+        // The earlier entries survive and the next run picks up where this one stopped
+        assert_eq!(get_index_stats(test_path).unwrap().total_files, 1);
+        let stats = smart_update_index(test_path, false, true, &[])
+            .await
+            .unwrap();
+        assert_eq!(stats.files_added, 1);
+        assert_eq!(stats.files_up_to_date, 1);
+    }
+
+    #[test]
+    fn test_migrate_manifest_to_relative_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        let file = root.join("src/lib.rs");
+        fs::write(&file, "fn main() {}\n").unwrap();
+        fs::write(root.join("src/Legacy"), "data").unwrap();
+        fs::write(root.join("src/Legacy.cs"), "class Legacy {}").unwrap();
+
+        // An older run keyed one file absolutely, with its sidecar beside the source, and
+        // another without the "./" prefix; a C# file sits where a sidecar could be
+        let entry = index_single_file(&file, root, None).unwrap();
+        let legacy_sidecar = root.join("src/lib.rs.cs");
+        // in the layout of that time, before `SIDECAR_MAGIC` and `embedding_info`
+        #[derive(Serialize)]
+        struct LegacyEntry {
+            metadata: FileMetadata,
+            chunks: Vec<UntypedChunkEntry>,
+        }
+        let legacy_entry = LegacyEntry {
+            metadata: FileMetadata {
+                path: file.clone(),
+                ..entry.metadata.clone()
+            },
+            chunks: untyped(&entry.chunks),
+        };
+        fs::write(&legacy_sidecar, bincode::serialize(&legacy_entry).unwrap()).unwrap();
+        let mut manifest = IndexManifest::default();
+        for key in [
+            file.clone(),
+            root.join("src/Legacy"),
+            PathBuf::from("notes.txt"),
+        ] {
+            let metadata = FileMetadata {
+                path: key.clone(),
+                ..entry.metadata.clone()
+            };
+            manifest.files.insert(key, metadata);
+        }
+
+        assert_eq!(migrate_manifest(&mut manifest, root).unwrap(), 3);
+        let mut keys: Vec<_> = manifest.files.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                PathBuf::from("./notes.txt"),
+                PathBuf::from("./src/Legacy"),
+                PathBuf::from("./src/lib.rs"),
+            ]
+        );
+        assert_eq!(
+            manifest.files[Path::new("./src/lib.rs")].path,
+            Path::new("./src/lib.rs")
+        );
+        assert!(!legacy_sidecar.exists());
+        assert!(load_index_entry(&get_sidecar_path(root, &file)).is_ok());
+        assert_eq!(
+            fs::read_to_string(root.join("src/Legacy.cs")).unwrap(),
+            "class Legacy {}"
+        );
+
+        // The migration is saved, and a second run finds nothing to do
+        let saved = load_or_create_manifest(&root.join(".cs/manifest.json")).unwrap();
+        assert!(saved.files.contains_key(Path::new("./src/lib.rs")));
+        assert_eq!(migrate_manifest(&mut manifest, root).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_manifest_tracks_files_without_embeddings() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.txt"), "first file").unwrap();
+        fs::write(root.join("b.txt"), "second file").unwrap();
+        smart_update_index(root, false, true, &[]).await.unwrap();
+
+        let manifest_path = root.join(".cs/manifest.json");
+        let mut manifest = load_or_create_manifest(&manifest_path).unwrap();
+        assert_eq!(manifest.unembedded_files().count(), 2);
+
+        // A manifest from before coverage was tracked reads it from the sidecars
+        let sidecar = get_sidecar_path(root, &root.join("a.txt"));
+        let mut entry = load_index_entry(&sidecar).unwrap();
+        for chunk in &mut entry.chunks {
+            chunk.embedding = Some(vec![1.0, 0.0]);
+        }
+        save_index_entry(&sidecar, &entry).unwrap();
+        manifest.unembedded = None;
+        assert!(!manifest.is_unembedded(Path::new("./b.txt")));
+        migrate_manifest(&mut manifest, root).unwrap();
+        assert_eq!(
+            manifest.unembedded_files().collect::<Vec<_>>(),
+            vec![Path::new("./b.txt")]
+        );
+        let saved = load_or_create_manifest(&manifest_path).unwrap();
+        assert!(saved.is_unembedded(Path::new("./b.txt")));
+
+        // Embedding a file takes it off the list
+        let metadata = manifest.files[Path::new("./b.txt")].clone();
+        manifest.record_file(metadata, true);
+        assert_eq!(manifest.unembedded_files().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_secrets_withheld_from_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let key = format!("AKIA{}", "Q3T7WZ2K9XJ4M8RB");
+        fs::write(
+            root.join("config.py"),
+            format!(
+                "def connect():\n    return 'db'\n\n\ndef credentials():\n    return '{key}'\n"
+            ),
+        )
+        .unwrap();
+        fs::write(root.join("server.pem"), "certificate body").unwrap();
+
+        let content = fs::read_to_string(root.join("config.py")).unwrap();
+        let offset = content.find(&key).unwrap();
+        let covers_key =
+            |chunk: &ChunkEntry| chunk.span.byte_start <= offset && offset < chunk.span.byte_end;
+
+        let stats = smart_update_index(root, false, true, &[]).await.unwrap();
+        let withheld = &stats.secrets_withheld;
+        assert_eq!(withheld.len(), 2);
+        assert!(withheld[Path::new("server.pem")].whole_file);
+        assert_eq!(withheld[Path::new("config.py")].chunks, 1);
+
+        let entry = load_index_entry(&get_sidecar_path(root, &root.join("config.py"))).unwrap();
+        assert!(!entry.chunks.is_empty());
+        assert!(!entry.chunks.iter().any(covers_key));
+        assert_eq!(get_index_stats(root).unwrap().withheld_secrets.len(), 2);
+
+        // Allowing secrets rebuilds the index with them
+        fs::write(root.join("cs.toml"), "allow_secrets = true\n").unwrap();
+        let stats = smart_update_index(root, false, true, &[]).await.unwrap();
+        assert!(stats.secrets_withheld.is_empty());
+        let entry = load_index_entry(&get_sidecar_path(root, &root.join("config.py"))).unwrap();
+        assert!(entry.chunks.iter().any(covers_key));
+    }
+
     #[test]
     fn test_cleanup_index() {
         let temp_dir = TempDir::new().unwrap();
@@ -1754,6 +3382,34 @@ mod tests {
         assert_eq!(updated_manifest.files.len(), 0);
     }
 
+    #[test]
+    fn test_index_epoch_changes_within_a_second() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        assert_eq!(index_epoch(root), None);
+
+        let manifest_path = root.join(".cs").join("manifest.json");
+        let mut manifest = IndexManifest::default();
+        save_manifest(&manifest_path, &manifest).unwrap();
+        let first = index_epoch(root).unwrap();
+        assert!(first < 1 << 48);
+
+        // A second run in the same second leaves `updated` as it was
+        manifest.files.insert(
+            PathBuf::from("./lib.rs"),
+            FileMetadata {
+                path: PathBuf::from("./lib.rs"),
+                hash: "hash".to_string(),
+                last_modified: 0,
+                size: 0,
+            },
+        );
+        save_manifest(&manifest_path, &manifest).unwrap();
+        let second = index_epoch(root).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(index_epoch(root), Some(second));
+    }
+
     #[test]
     fn test_get_index_stats() {
         let temp_dir = TempDir::new().unwrap();
@@ -1783,6 +3439,50 @@ mod tests {
 
         let stats = get_index_stats(test_path).unwrap();
         assert_eq!(stats.total_files, 1);
+        // The source file does not exist, so its entry is stale
+        assert_eq!(stats.stale_files.len(), 1);
+    }
+
+    #[test]
+    fn test_get_index_stats_breakdown() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+        let index_dir = test_path.join(".cs");
+        fs::write(
+            test_path.join("main.rs"),
+            "fn main() {}\n\nfn helper() {}\n",
+        )
+        .unwrap();
+        fs::write(test_path.join("notes.txt"), "some notes").unwrap();
+
+        let mut embedder: Box<dyn cs_embed::Embedder> =
+            Box::new(cs_embed::DummyEmbedder::new_with_model("test-model"));
+        let mut manifest = IndexManifest::default();
+        for (name, embed) in [("main.rs", true), ("notes.txt", false)] {
+            let file = test_path.join(name);
+            let embedder = embed.then_some(&mut embedder);
+            let entry = index_single_file(&file, test_path, embedder).unwrap();
+            save_index_entry(&get_sidecar_path(test_path, &file), &entry).unwrap();
+            manifest
+                .files
+                .insert(entry.metadata.path.clone(), entry.metadata);
+        }
+        save_manifest(&index_dir.join("manifest.json"), &manifest).unwrap();
+
+        let stats = get_index_stats(test_path).unwrap();
+        let rust = &stats.languages["rust"];
+        assert_eq!(rust.files, 1);
+        assert_eq!(rust.embedded_chunks, rust.chunks);
+        assert_eq!(stats.languages["other"].embedded_chunks, 0);
+        assert_eq!(stats.chunk_types["function"], 2);
+        assert_eq!(stats.models["test-model"].files, 1);
+        assert!(stats.embedding_coverage() > 0.0 && stats.embedding_coverage() < 1.0);
+        assert!(stats.stale_files.is_empty());
+        assert_eq!(stats.largest_files[0].path, PathBuf::from("main.rs"));
+
+        fs::write(test_path.join("main.rs"), "fn main() { changed() }\n").unwrap();
+        let stats = get_index_stats(test_path).unwrap();
+        assert_eq!(stats.stale_files, vec![PathBuf::from("main.rs")]);
     }
 
     #[test]
@@ -1840,6 +3540,16 @@ mod tests {
         .unwrap(); // "Hello\0World"
         assert!(!is_text_file(&binary_file));
 
+        // UTF-16 text has NUL bytes too
+        let utf16_file = temp_path.join("utf16.log");
+        let mut file = File::create(&utf16_file).unwrap();
+        let utf16: Vec<u8> = "2024-01-15 ERROR: Failed to connect"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        file.write_all(&utf16).unwrap();
+        assert!(is_text_file(&utf16_file));
+
         // Create an empty file (should be considered text)
         let empty_file = temp_path.join("empty.txt");
         File::create(&empty_file).unwrap();
@@ -1867,6 +3577,25 @@ mod tests {
         assert!(!test_path.join("level1").join("level2").exists());
         assert!(!test_path.join("level1").exists());
     }
+
+    #[test]
+    fn test_count_skipped_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path();
+        fs::write(test_path.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(test_path.join("big.log"), "x".repeat(200)).unwrap();
+        fs::write(test_path.join("data.bin"), [0x48, 0x00, 0x57]).unwrap();
+        fs::write(test_path.join(".hidden"), "secret").unwrap();
+        fs::write(test_path.join("gen.txt"), "generated").unwrap();
+        fs::create_dir_all(test_path.join(".cs")).unwrap();
+        fs::write(test_path.join(".cs").join("manifest.json"), "{}").unwrap();
+
+        let counts =
+            count_skipped_files(test_path, true, &["gen.txt".to_string()], Some(100)).unwrap();
+        assert_eq!(counts.binary, 1);
+        assert_eq!(counts.too_big, 1);
+        assert_eq!(counts.ignored, 2);
+    }
 }
 
 // ============================================================================
@@ -1924,7 +3653,7 @@ mod cleanup_validation {
     }
 
     /// Remove a manifest entry and its associated files
-    fn remove_manifest_entry(
+    pub(super) fn remove_manifest_entry(
         manifest: &mut IndexManifest,
         manifest_path: &Path,
         repo_root: &Path,
@@ -1932,6 +3661,9 @@ mod cleanup_validation {
         stats: &mut CleanupStats,
     ) -> Result<()> {
         manifest.files.remove(manifest_path);
+        if let Some(unembedded) = &mut manifest.unembedded {
+            unembedded.remove(manifest_path);
+        }
 
         // Remove sidecar file
         let standard_path = path_utils::from_manifest_path(manifest_path);
@@ -2005,11 +3737,8 @@ mod path_utils {
 
     /// Convert an absolute path to a standardized relative path from repo root
     pub fn to_standard_path(absolute_path: &Path, repo_root: &Path) -> PathBuf {
-        if let Ok(relative) = absolute_path.strip_prefix(repo_root) {
-            relative.to_path_buf()
-        } else {
-            absolute_path.to_path_buf()
-        }
+        cs_core::paths::relative_to(absolute_path, repo_root)
+            .unwrap_or_else(|| absolute_path.to_path_buf())
     }
 
     /// Convert a standardized path to a manifest path (with "./" prefix for compatibility)
@@ -2035,18 +3764,11 @@ mod path_utils {
     /// Convert a sidecar path back to a standardized original path
     pub fn sidecar_to_standard_path(sidecar_path: &Path, index_dir: &Path) -> Option<PathBuf> {
         let relative_path = sidecar_path.strip_prefix(index_dir).ok()?;
-        let original_path = relative_path.with_extension("");
-
-        // Handle the .cs extension removal
-        if let Some(name) = original_path.file_name() {
-            let name_str = name.to_string_lossy();
-            if let Some(original_name) = name_str.strip_suffix(".cs") {
-                let mut result = original_path.clone();
-                result.set_file_name(original_name);
-                return Some(result);
-            }
+        // Only the sidecar's own `.cs` comes off; `Program.cs.cs` belongs to `Program.cs`
+        let name = relative_path.file_name()?.to_string_lossy();
+        match name.strip_suffix(".cs") {
+            Some(original_name) => Some(relative_path.with_file_name(original_name)),
+            None => Some(relative_path.to_path_buf()),
         }
-
-        Some(original_path)
     }
 }
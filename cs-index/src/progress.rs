@@ -0,0 +1,165 @@
+//! Progress of an indexing run: counts, throughput and time remaining.
+//!
+//! The ETA is weighted by bytes rather than files, since one large file can take as long to embed
+//! as hundreds of small ones.
+
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Snapshot of an indexing run, carried by every `EmbeddingProgress`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct IndexProgress {
+    /// Files finished, including ones skipped as binary or failed
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub chunks_embedded: usize,
+    pub elapsed_secs: f64,
+    pub embeddings_per_sec: f64,
+    /// `None` until there is enough throughput to extrapolate from
+    pub eta_secs: Option<f64>,
+}
+
+impl fmt::Display for IndexProgress {
+    /// One line, e.g. `12/340 files · 1.2/8.4 MB · 45.3 chunks/s · ETA 2m05s`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MB: f64 = 1024.0 * 1024.0;
+        write!(
+            f,
+            "{}/{} files · {:.1}/{:.1} MB · {:.1} chunks/s",
+            self.files_done,
+            self.files_total,
+            self.bytes_done as f64 / MB,
+            self.bytes_total as f64 / MB,
+            self.embeddings_per_sec
+        )?;
+        if let Some(eta) = self.eta_secs {
+            let eta = eta.round() as u64;
+            if eta >= 60 {
+                write!(f, " · ETA {}m{:02}s", eta / 60, eta % 60)?;
+            } else {
+                write!(f, " · ETA {}s", eta)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Accumulates an `IndexProgress` while files are embedded one after another
+pub(crate) struct ProgressTracker {
+    started: Instant,
+    progress: IndexProgress,
+    /// Size of the file being embedded
+    current_bytes: u64,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(files: &[PathBuf]) -> Self {
+        let bytes_total = files
+            .iter()
+            .filter_map(|file| fs::metadata(file).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        Self {
+            started: Instant::now(),
+            progress: IndexProgress {
+                files_total: files.len(),
+                bytes_total,
+                ..Default::default()
+            },
+            current_bytes: 0,
+        }
+    }
+
+    pub(crate) fn start_file(&mut self, file: &Path) {
+        self.current_bytes = fs::metadata(file).map_or(0, |metadata| metadata.len());
+    }
+
+    pub(crate) fn chunk_embedded(&mut self) {
+        self.progress.chunks_embedded += 1;
+    }
+
+    pub(crate) fn finish_file(&mut self) {
+        self.progress.files_done += 1;
+        self.progress.bytes_done += self.current_bytes;
+        self.current_bytes = 0;
+    }
+
+    pub(crate) fn files_done(&self) -> usize {
+        self.progress.files_done
+    }
+
+    pub(crate) fn files_total(&self) -> usize {
+        self.progress.files_total
+    }
+
+    /// Snapshot with `file_fraction` of the current file done
+    pub(crate) fn snapshot(&self, file_fraction: f64) -> IndexProgress {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let bytes_done =
+            self.progress.bytes_done as f64 + self.current_bytes as f64 * file_fraction;
+        let rate = if elapsed > 0.0 {
+            bytes_done / elapsed
+        } else {
+            0.0
+        };
+        IndexProgress {
+            elapsed_secs: elapsed,
+            embeddings_per_sec: if elapsed > 0.0 {
+                self.progress.chunks_embedded as f64 / elapsed
+            } else {
+                0.0
+            },
+            eta_secs: (rate > 0.0)
+                .then(|| (self.progress.bytes_total as f64 - bytes_done).max(0.0) / rate),
+            ..self.progress.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tracker_weights_eta_by_bytes() {
+        let temp_dir = TempDir::new().unwrap();
+        let small = temp_dir.path().join("small.rs");
+        let large = temp_dir.path().join("large.rs");
+        fs::write(&small, vec![b'a'; 100]).unwrap();
+        fs::write(&large, vec![b'a'; 300]).unwrap();
+
+        let mut tracker = ProgressTracker::new(&[small.clone(), large.clone()]);
+        let progress = tracker.snapshot(0.0);
+        assert_eq!((progress.files_total, progress.bytes_total), (2, 400));
+        assert_eq!(progress.eta_secs, None);
+
+        tracker.start_file(&small);
+        tracker.chunk_embedded();
+        tracker.finish_file();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let progress = tracker.snapshot(0.0);
+        assert_eq!((progress.files_done, progress.bytes_done), (1, 100));
+        assert_eq!(progress.chunks_embedded, 1);
+        assert!(progress.embeddings_per_sec > 0.0);
+        // A quarter of the bytes took `elapsed`, so three times that remains
+        let eta = progress.eta_secs.unwrap();
+        assert!((eta - 3.0 * progress.elapsed_secs).abs() < 0.01);
+
+        assert!(
+            progress
+                .to_string()
+                .starts_with("1/2 files · 0.0/0.0 MB · ")
+        );
+        let progress = IndexProgress {
+            eta_secs: Some(125.0),
+            ..progress
+        };
+        assert!(progress.to_string().ends_with("ETA 2m05s"));
+    }
+}
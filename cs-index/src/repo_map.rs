@@ -0,0 +1,419 @@
+//! Topic map of an indexed repository (`cs --map`).
+//!
+//! Every chunk embedding in the index is clustered with spherical k-means (cosine distance,
+//! k-means++ seeding from a fixed seed, so the same index always gives the same map). Each
+//! cluster is labelled with the code terms that set it apart from the other clusters and
+//! listed with the files holding most of its chunks.
+
+use super::{
+    IndexManifest, load_index_entry, load_or_create_manifest, normalize_manifest_paths, path_utils,
+    sparse,
+};
+use anyhow::Result;
+use cs_core::compute_file_hash;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Most clusters picked when `--clusters` is not given
+const MAX_DEFAULT_CLUSTERS: usize = 16;
+/// k-means stops after this many rounds even if chunks still move between clusters
+const MAX_ITERATIONS: usize = 25;
+/// Terms in a cluster label
+const LABEL_TERMS: usize = 5;
+/// Files listed per cluster
+const REPRESENTATIVE_FILES: usize = 5;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepoMap {
+    /// Chunks with embeddings that were clustered
+    pub chunks: usize,
+    pub files: usize,
+    /// Indexed files whose content changed or disappeared since the last index run; their
+    /// chunks are clustered but give no label terms
+    pub stale_files: usize,
+    /// Largest cluster first
+    pub clusters: Vec<Cluster>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Cluster {
+    /// Terms most specific to the cluster, strongest first
+    pub label: Vec<String>,
+    pub chunks: usize,
+    pub files: usize,
+    /// Files with the most chunks in the cluster
+    pub representative_files: Vec<RepresentativeFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepresentativeFile {
+    /// Path relative to the repository root
+    pub path: PathBuf,
+    pub chunks: usize,
+}
+
+struct MapChunk {
+    file: usize,
+    embedding: Vec<f32>,
+    terms: HashSet<String>,
+}
+
+/// Cluster the chunk embeddings of the index at `repo_root` into `clusters` groups, or a
+/// number picked from the chunk count when `None`
+pub fn build_repo_map(repo_root: &Path, clusters: Option<usize>) -> Result<RepoMap> {
+    let manifest_path = repo_root.join(".cs").join("manifest.json");
+    if !manifest_path.exists() {
+        anyhow::bail!(
+            "No index found at {}; run 'cs --index' first",
+            repo_root.display()
+        );
+    }
+    let mut manifest: IndexManifest = load_or_create_manifest(&manifest_path)?;
+    normalize_manifest_paths(&mut manifest, repo_root);
+
+    let mut entries: Vec<_> = manifest.files.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut map = RepoMap::default();
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut chunks: Vec<MapChunk> = Vec::new();
+    for (manifest_path, metadata) in entries {
+        let standard_path = path_utils::from_manifest_path(manifest_path);
+        let file_path = repo_root.join(&standard_path);
+        let Ok(entry) = load_index_entry(&cs_core::get_sidecar_path(repo_root, &file_path)) else {
+            continue;
+        };
+        // Chunk text is only trusted while the file matches what was indexed
+        let content = match compute_file_hash(&file_path) {
+            Ok(hash) if hash == metadata.hash => cs_core::encoding::read_to_string(&file_path).ok(),
+            _ => None,
+        };
+        if content.is_none() {
+            map.stale_files += 1;
+        }
+
+        let file = files.len();
+        let before = chunks.len();
+        for chunk in entry.chunks {
+            let Some(mut embedding) = chunk.embedding else {
+                continue;
+            };
+            // Vectors from another model can't be compared; the first chunk sets the size
+            if chunks
+                .first()
+                .is_some_and(|first| first.embedding.len() != embedding.len())
+            {
+                continue;
+            }
+            if !normalize(&mut embedding) {
+                continue;
+            }
+            let terms = content
+                .as_deref()
+                .and_then(|text| text.get(chunk.span.byte_start..chunk.span.byte_end))
+                .map(|text| {
+                    sparse::terms(text)
+                        .filter(|term| term.chars().count() > 2)
+                        .collect()
+                })
+                .unwrap_or_default();
+            chunks.push(MapChunk {
+                file,
+                embedding,
+                terms,
+            });
+        }
+        if chunks.len() > before {
+            files.push(standard_path);
+        }
+    }
+
+    map.chunks = chunks.len();
+    map.files = files.len();
+    if chunks.is_empty() {
+        return Ok(map);
+    }
+
+    let k = clusters
+        .unwrap_or_else(|| default_cluster_count(chunks.len()))
+        .clamp(1, chunks.len());
+    let vectors: Vec<&[f32]> = chunks
+        .iter()
+        .map(|chunk| chunk.embedding.as_slice())
+        .collect();
+    let assignments = kmeans(&vectors, k);
+
+    let mut members: Vec<Vec<&MapChunk>> = vec![Vec::new(); k];
+    for (chunk, cluster) in chunks.iter().zip(&assignments) {
+        members[*cluster].push(chunk);
+    }
+    members.retain(|members| !members.is_empty());
+
+    let labels = label_clusters(&members);
+    map.clusters = members
+        .iter()
+        .zip(labels)
+        .map(|(members, label)| {
+            let mut per_file: HashMap<usize, usize> = HashMap::new();
+            for chunk in members {
+                *per_file.entry(chunk.file).or_default() += 1;
+            }
+            let mut ranked: Vec<(usize, usize)> = per_file.into_iter().collect();
+            ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| files[a.0].cmp(&files[b.0])));
+            Cluster {
+                label,
+                chunks: members.len(),
+                files: ranked.len(),
+                representative_files: ranked
+                    .into_iter()
+                    .take(REPRESENTATIVE_FILES)
+                    .map(|(file, chunks)| RepresentativeFile {
+                        path: files[file].clone(),
+                        chunks,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    map.clusters
+        .sort_by(|a, b| b.chunks.cmp(&a.chunks).then_with(|| a.label.cmp(&b.label)));
+    Ok(map)
+}
+
+/// About √(n/2) clusters, at least 2 and at most `MAX_DEFAULT_CLUSTERS`
+fn default_cluster_count(chunks: usize) -> usize {
+    ((chunks as f64 / 2.0).sqrt().round() as usize).clamp(2, MAX_DEFAULT_CLUSTERS)
+}
+
+/// Scale to unit length; false for a zero vector
+fn normalize(vector: &mut [f32]) -> bool {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return false;
+    }
+    vector.iter_mut().for_each(|x| *x /= norm);
+    true
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Spherical k-means over unit vectors; returns each vector's cluster
+fn kmeans(vectors: &[&[f32]], k: usize) -> Vec<usize> {
+    let mut centroids = seed_centroids(vectors, k);
+    let mut assignments = vec![usize::MAX; vectors.len()];
+
+    for _ in 0..MAX_ITERATIONS {
+        let next: Vec<usize> = vectors
+            .par_iter()
+            .map(|vector| nearest(&centroids, vector))
+            .collect();
+        if next == assignments {
+            break;
+        }
+        assignments = next;
+
+        let dimensions = vectors[0].len();
+        let mut sums = vec![vec![0.0f32; dimensions]; k];
+        for (vector, cluster) in vectors.iter().zip(&assignments) {
+            for (sum, x) in sums[*cluster].iter_mut().zip(vector.iter()) {
+                *sum += x;
+            }
+        }
+        for (centroid, mut sum) in centroids.iter_mut().zip(sums) {
+            // An emptied cluster keeps its centroid and is dropped if it stays empty
+            if normalize(&mut sum) {
+                *centroid = sum;
+            }
+        }
+    }
+    assignments
+}
+
+/// k-means++: each next seed is drawn with probability proportional to its distance from
+/// the seeds so far
+fn seed_centroids(vectors: &[&[f32]], k: usize) -> Vec<Vec<f32>> {
+    let mut rng = SplitMix64(0x5eed_c0de);
+    let mut centroids = vec![vectors[rng.below(vectors.len())].to_vec()];
+    let mut distances: Vec<f32> = vectors
+        .iter()
+        .map(|vector| 1.0 - dot(&centroids[0], vector))
+        .collect();
+
+    while centroids.len() < k {
+        let total: f64 = distances.iter().map(|d| f64::from(d.max(0.0))).sum();
+        let chosen = if total <= f64::EPSILON {
+            // Every vector sits on a seed; any pick is as good as another
+            rng.below(vectors.len())
+        } else {
+            let mut target = rng.unit() * total;
+            distances
+                .iter()
+                .position(|d| {
+                    target -= f64::from(d.max(0.0));
+                    target <= 0.0
+                })
+                .unwrap_or(vectors.len() - 1)
+        };
+        let centroid = vectors[chosen].to_vec();
+        for (distance, vector) in distances.iter_mut().zip(vectors) {
+            *distance = distance.min(1.0 - dot(&centroid, vector));
+        }
+        centroids.push(centroid);
+    }
+    centroids
+}
+
+fn nearest(centroids: &[Vec<f32>], vector: &[f32]) -> usize {
+    centroids
+        .iter()
+        .map(|centroid| dot(centroid, vector))
+        .enumerate()
+        .fold((0, f32::NEG_INFINITY), |best, (cluster, similarity)| {
+            if similarity > best.1 {
+                (cluster, similarity)
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+/// Label each cluster with the terms found in many of its chunks but few other clusters:
+/// the share of its chunks containing a term, times ln(clusters / clusters containing it)
+fn label_clusters(members: &[Vec<&MapChunk>]) -> Vec<Vec<String>> {
+    let chunk_counts: Vec<HashMap<&str, usize>> = members
+        .iter()
+        .map(|chunks| {
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for chunk in chunks {
+                for term in &chunk.terms {
+                    *counts.entry(term.as_str()).or_default() += 1;
+                }
+            }
+            counts
+        })
+        .collect();
+    let mut cluster_freq: HashMap<&str, usize> = HashMap::new();
+    for counts in &chunk_counts {
+        for term in counts.keys() {
+            *cluster_freq.entry(term).or_default() += 1;
+        }
+    }
+
+    let clusters = members.len() as f32;
+    chunk_counts
+        .iter()
+        .zip(members)
+        .map(|(counts, chunks)| {
+            let mut scored: Vec<(f32, &str)> = counts
+                .iter()
+                .map(|(term, count)| {
+                    // A single cluster has nothing to contrast with; rank by frequency alone
+                    let specificity = if members.len() == 1 {
+                        1.0
+                    } else {
+                        (clusters / cluster_freq[term] as f32).ln()
+                    };
+                    (*count as f32 / chunks.len() as f32 * specificity, *term)
+                })
+                .filter(|(score, _)| *score > 0.0)
+                .collect();
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+            scored
+                .into_iter()
+                .take(LABEL_TERMS)
+                .map(|(_, term)| term.to_string())
+                .collect()
+        })
+        .collect()
+}
+
+/// Small deterministic generator for k-means++ seeding
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{index_single_file, save_index_entry, save_manifest};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_repo_map_clusters_topics() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".cs")).unwrap();
+        let files = [
+            (
+                "retry.rs",
+                "fn retry_backoff() {}\n\nfn retry_delay() {}\n",
+                [1.0, 0.1, 0.0],
+            ),
+            (
+                "backoff.rs",
+                "fn backoff_retry() {}\n\nfn jitter_retry() {}\n",
+                [0.9, 0.0, 0.1],
+            ),
+            (
+                "render.rs",
+                "fn render_widget() {}\n\nfn render_frame() {}\n",
+                [0.0, 1.0, 0.1],
+            ),
+        ];
+
+        let mut manifest = IndexManifest::default();
+        for (name, source, embedding) in files {
+            let path = root.join(name);
+            fs::write(&path, source).unwrap();
+            let mut entry = index_single_file(&path, root, None).unwrap();
+            for chunk in &mut entry.chunks {
+                chunk.embedding = Some(embedding.to_vec());
+            }
+            save_index_entry(&cs_core::get_sidecar_path(root, &path), &entry).unwrap();
+            manifest
+                .files
+                .insert(entry.metadata.path.clone(), entry.metadata);
+        }
+        save_manifest(&root.join(".cs").join("manifest.json"), &manifest).unwrap();
+
+        let map = build_repo_map(root, Some(2)).unwrap();
+        assert_eq!((map.files, map.chunks, map.stale_files), (3, 6, 0));
+        assert_eq!(map.clusters.len(), 2);
+        let (retry, render) = (&map.clusters[0], &map.clusters[1]);
+        assert_eq!((retry.chunks, retry.files), (4, 2));
+        assert_eq!(retry.label[0], "retry");
+        assert_eq!(render.label[0], "render");
+        assert_eq!(
+            render.representative_files[0].path,
+            PathBuf::from("render.rs")
+        );
+        // The same index always gives the same map
+        let again = build_repo_map(root, Some(2)).unwrap();
+        assert_eq!(again.clusters[0].label, retry.label);
+
+        assert!(build_repo_map(&root.join("missing"), None).is_err());
+    }
+}
@@ -0,0 +1,701 @@
+//! Shadow embedding sets for evaluating a new model before switching the primary index.
+//!
+//! A shadow lives under `.cs/.shadow/<model>/` and holds embeddings for a sample of the
+//! files already in the primary index, stored in its own ANN partition. Comparative
+//! queries run against the primary embeddings for the same files and against the shadow,
+//! so a model migration can be judged without touching the primary sidecars.
+
+use super::{
+    INDEX_INTERRUPTED_MSG, INTERRUPTED, IndexManifest, atomic_write, index_single_file,
+    load_index_entry, load_or_create_manifest, normalize_manifest_paths, path_utils,
+};
+use anyhow::Result;
+use cs_ann::{AnnIndex, SimpleIndex};
+use cs_core::Span;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::{Instant, SystemTime};
+
+pub(crate) const SHADOW_DIR: &str = ".shadow";
+const SHADOW_MANIFEST: &str = "shadow.json";
+const SHADOW_ANN: &str = "ann.bin";
+
+/// Metadata for a shadow embedding set; chunk ids in the ANN partition index into `chunks`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowManifest {
+    pub model: String,
+    pub dimensions: usize,
+    /// Model of the primary index when the shadow was built
+    pub primary_model: String,
+    pub created: u64,
+    /// Sampled files, as manifest paths
+    pub files: Vec<PathBuf>,
+    pub chunks: Vec<ShadowChunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowChunk {
+    pub file: PathBuf,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShadowBuildStats {
+    pub files_sampled: usize,
+    pub files_embedded: usize,
+    pub files_errored: usize,
+    pub chunks_embedded: usize,
+    pub dimensions: usize,
+}
+
+/// A comparative query, optionally with the file a good answer should come from
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowQuery {
+    pub query: String,
+    pub expected: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShadowHit {
+    pub file: PathBuf,
+    pub span: Span,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShadowQueryReport {
+    pub query: String,
+    pub expected: Option<String>,
+    pub primary: Vec<ShadowHit>,
+    pub shadow: Vec<ShadowHit>,
+    /// Jaccard overlap of the files returned by both models
+    pub overlap: f32,
+    /// 1-based rank of the first hit in the expected file
+    pub primary_rank: Option<usize>,
+    pub shadow_rank: Option<usize>,
+    pub primary_ms: f64,
+    pub shadow_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShadowReport {
+    pub primary_model: String,
+    pub shadow_model: String,
+    pub files_sampled: usize,
+    pub primary_chunks: usize,
+    pub shadow_chunks: usize,
+    pub top_k: usize,
+    pub queries: Vec<ShadowQueryReport>,
+    pub mean_overlap: f32,
+    pub primary_mean_top_score: f32,
+    pub shadow_mean_top_score: f32,
+    /// Mean reciprocal rank over queries with an expected file
+    pub primary_mrr: Option<f32>,
+    pub shadow_mrr: Option<f32>,
+    /// Fraction of queries with an expected file that found it within `top_k`
+    pub primary_hit_rate: Option<f32>,
+    pub shadow_hit_rate: Option<f32>,
+    pub primary_avg_ms: f64,
+    pub shadow_avg_ms: f64,
+}
+
+/// Directory holding the shadow embedding set for `model`
+pub fn shadow_dir(repo_root: &Path, model: &str) -> PathBuf {
+    let slug: String = model
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    repo_root.join(".cs").join(SHADOW_DIR).join(slug)
+}
+
+/// Pick up to `limit` files spread evenly across the sorted list, so the sample is
+/// stable between runs and covers the whole tree rather than one directory
+pub fn sample_files(files: &[PathBuf], limit: usize) -> Vec<PathBuf> {
+    let mut sorted = files.to_vec();
+    sorted.sort();
+    if sorted.len() <= limit {
+        return sorted;
+    }
+    (0..limit)
+        .map(|i| sorted[i * sorted.len() / limit].clone())
+        .collect()
+}
+
+/// Parse comparative queries: one per line, `query<TAB>expected/path` to score relevance.
+/// Blank lines and `#` comments are skipped.
+pub fn parse_shadow_queries(text: &str) -> Vec<ShadowQuery> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.split_once('\t') {
+            Some((query, expected)) if !expected.trim().is_empty() => ShadowQuery {
+                query: query.trim().to_string(),
+                expected: Some(expected.trim().to_string()),
+            },
+            Some((query, _)) => ShadowQuery {
+                query: query.trim().to_string(),
+                expected: None,
+            },
+            None => ShadowQuery {
+                query: line.to_string(),
+                expected: None,
+            },
+        })
+        .collect()
+}
+
+/// Build (or rebuild) the shadow embedding set for `model` over a sample of the indexed files
+pub fn build_shadow_index(
+    repo_root: &Path,
+    model: &str,
+    sample_size: usize,
+    progress_callback: Option<super::ProgressCallback>,
+) -> Result<ShadowBuildStats> {
+    // Fail on a missing index before paying for a model load
+    load_primary_manifest(repo_root)?;
+    let mut embedder = cs_embed::create_embedder(Some(model))?;
+    build_shadow_index_with_embedder(repo_root, &mut embedder, sample_size, progress_callback)
+}
+
+fn build_shadow_index_with_embedder(
+    repo_root: &Path,
+    embedder: &mut Box<dyn cs_embed::Embedder>,
+    sample_size: usize,
+    progress_callback: Option<super::ProgressCallback>,
+) -> Result<ShadowBuildStats> {
+    let manifest = load_primary_manifest(repo_root)?;
+    let primary_model = manifest.embedding_model.clone().ok_or_else(|| {
+        anyhow::anyhow!(
+            "The index at {} has no embeddings. Run 'cs --index' first.",
+            repo_root.display()
+        )
+    })?;
+    let model = embedder.model_name().to_string();
+    if primary_model == model {
+        anyhow::bail!(
+            "The primary index already uses {}; choose a different model for the shadow",
+            model
+        );
+    }
+
+    let files: Vec<PathBuf> = manifest.files.keys().cloned().collect();
+    let sampled = sample_files(&files, sample_size);
+
+    let dir = shadow_dir(repo_root, &model);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+
+    let mut stats = ShadowBuildStats {
+        files_sampled: sampled.len(),
+        ..Default::default()
+    };
+    let mut vectors = Vec::new();
+    let mut chunks = Vec::new();
+
+    for (i, manifest_path) in sampled.iter().enumerate() {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!(INDEX_INTERRUPTED_MSG));
+        }
+        if let Some(ref callback) = progress_callback {
+            callback(&format!(
+                "[{}/{}] {}",
+                i + 1,
+                sampled.len(),
+                manifest_path.display()
+            ));
+        }
+
+        let file_path = repo_root.join(path_utils::from_manifest_path(manifest_path));
+        match index_single_file(&file_path, repo_root, Some(&mut *embedder)) {
+            Ok(entry) => {
+                stats.files_embedded += 1;
+                for chunk in entry.chunks {
+                    if let Some(embedding) = chunk.embedding {
+                        vectors.push(embedding);
+                        chunks.push(ShadowChunk {
+                            file: manifest_path.clone(),
+                            span: chunk.span,
+                        });
+                    }
+                }
+            }
+            Err(e) => {
+                stats.files_errored += 1;
+                tracing::warn!("Failed to embed {:?} for shadow index: {}", file_path, e);
+            }
+        }
+    }
+
+    let ann = SimpleIndex::build(&vectors)?;
+    stats.chunks_embedded = chunks.len();
+    stats.dimensions = vectors.first().map_or(embedder.dim(), Vec::len);
+
+    let shadow = ShadowManifest {
+        model,
+        dimensions: stats.dimensions,
+        primary_model,
+        created: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        files: sampled,
+        chunks,
+    };
+
+    fs::create_dir_all(&dir)?;
+    ann.save(&dir.join(SHADOW_ANN))?;
+    atomic_write(
+        &dir.join(SHADOW_MANIFEST),
+        &serde_json::to_vec_pretty(&shadow)?,
+    )?;
+
+    Ok(stats)
+}
+
+/// Run `queries` against the primary embeddings of the sampled files and against the
+/// shadow embedding set for `model`, reporting agreement and quality metrics
+pub fn compare_shadow(
+    repo_root: &Path,
+    model: &str,
+    queries: &[ShadowQuery],
+    top_k: usize,
+) -> Result<ShadowReport> {
+    let manifest = load_primary_manifest(repo_root)?;
+    let primary_model = manifest
+        .embedding_model
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("The primary index has no embedding model recorded"))?;
+    load_shadow_manifest(repo_root, model)?;
+    let mut primary_embedder = cs_embed::create_embedder(Some(primary_model.as_str()))?;
+    let mut shadow_embedder = cs_embed::create_embedder(Some(model))?;
+    compare_shadow_with_embedders(
+        repo_root,
+        &mut primary_embedder,
+        &mut shadow_embedder,
+        queries,
+        top_k,
+    )
+}
+
+fn compare_shadow_with_embedders(
+    repo_root: &Path,
+    primary_embedder: &mut Box<dyn cs_embed::Embedder>,
+    shadow_embedder: &mut Box<dyn cs_embed::Embedder>,
+    queries: &[ShadowQuery],
+    top_k: usize,
+) -> Result<ShadowReport> {
+    let model = shadow_embedder.model_name().to_string();
+    let shadow = load_shadow_manifest(repo_root, &model)?;
+    let shadow_ann = SimpleIndex::load(&shadow_dir(repo_root, &model).join(SHADOW_ANN))?;
+
+    if primary_embedder.model_name() != shadow.primary_model {
+        tracing::warn!(
+            "Primary index model changed from {} to {} since the shadow was built",
+            shadow.primary_model,
+            primary_embedder.model_name()
+        );
+    }
+
+    // Primary partition restricted to the sampled files so both sides search the same corpus
+    let index_dir = repo_root.join(".cs");
+    let mut primary_vectors = Vec::new();
+    let mut primary_chunks = Vec::new();
+    for manifest_path in &shadow.files {
+        let standard_path = path_utils::from_manifest_path(manifest_path);
+        let sidecar_path =
+            path_utils::get_sidecar_path_for_standard_path(&index_dir, &standard_path);
+        let Ok(entry) = load_index_entry(&sidecar_path) else {
+            continue;
+        };
+        for chunk in entry.chunks {
+            if let Some(embedding) = chunk.embedding {
+                primary_vectors.push(embedding);
+                primary_chunks.push(ShadowChunk {
+                    file: manifest_path.clone(),
+                    span: chunk.span,
+                });
+            }
+        }
+    }
+    if primary_vectors.is_empty() {
+        anyhow::bail!(
+            "The primary index has no embeddings for the sampled files. Run 'cs --index' and rebuild the shadow."
+        );
+    }
+    let primary_ann = SimpleIndex::build(&primary_vectors)?;
+
+    let mut reports = Vec::with_capacity(queries.len());
+    for query in queries {
+        let (primary, primary_ms) = run_query(
+            primary_embedder,
+            &primary_ann,
+            &primary_chunks,
+            query,
+            top_k,
+        )?;
+        let (shadow_hits, shadow_ms) =
+            run_query(shadow_embedder, &shadow_ann, &shadow.chunks, query, top_k)?;
+
+        let primary_rank = query
+            .expected
+            .as_deref()
+            .and_then(|expected| first_rank(&primary, expected));
+        let shadow_rank = query
+            .expected
+            .as_deref()
+            .and_then(|expected| first_rank(&shadow_hits, expected));
+
+        reports.push(ShadowQueryReport {
+            query: query.query.clone(),
+            expected: query.expected.clone(),
+            overlap: file_overlap(&primary, &shadow_hits),
+            primary,
+            shadow: shadow_hits,
+            primary_rank,
+            shadow_rank,
+            primary_ms,
+            shadow_ms,
+        });
+    }
+
+    Ok(summarize(
+        primary_embedder.model_name().to_string(),
+        shadow,
+        primary_chunks.len(),
+        top_k,
+        reports,
+    ))
+}
+
+fn load_primary_manifest(repo_root: &Path) -> Result<IndexManifest> {
+    let manifest_path = repo_root.join(".cs").join("manifest.json");
+    if !manifest_path.exists() {
+        anyhow::bail!(
+            "No index found at {}; run 'cs --index' first",
+            repo_root.display()
+        );
+    }
+    let mut manifest = load_or_create_manifest(&manifest_path)?;
+    normalize_manifest_paths(&mut manifest, repo_root);
+    Ok(manifest)
+}
+
+fn load_shadow_manifest(repo_root: &Path, model: &str) -> Result<ShadowManifest> {
+    let path = shadow_dir(repo_root, model).join(SHADOW_MANIFEST);
+    if !path.exists() {
+        anyhow::bail!(
+            "No shadow index for {} at {}; build one first with 'cs --shadow-index {}'",
+            model,
+            repo_root.display(),
+            model
+        );
+    }
+    Ok(serde_json::from_slice(&fs::read(&path)?)?)
+}
+
+/// Problems with the shadow partitions under `.cs/.shadow`: unreadable files, or ANN vectors
+/// that do not match the chunk list or dimensions in the shadow manifest
+pub(crate) fn check_shadows(repo_root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(repo_root.join(".cs").join(SHADOW_DIR)) else {
+        return Vec::new();
+    };
+    let mut problems = Vec::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let dir = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let manifest = fs::read(dir.join(SHADOW_MANIFEST))
+            .map_err(anyhow::Error::from)
+            .and_then(|data| Ok(serde_json::from_slice::<ShadowManifest>(&data)?));
+        let manifest = match manifest {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                problems.push(format!("{}: unreadable {} ({})", name, SHADOW_MANIFEST, e));
+                continue;
+            }
+        };
+        let ann = match SimpleIndex::load(&dir.join(SHADOW_ANN)) {
+            Ok(ann) => ann,
+            Err(e) => {
+                problems.push(format!("{}: unreadable {} ({})", name, SHADOW_ANN, e));
+                continue;
+            }
+        };
+        if ann.len() != manifest.chunks.len() {
+            problems.push(format!(
+                "{}: {} ANN vectors for {} chunks",
+                name,
+                ann.len(),
+                manifest.chunks.len()
+            ));
+        } else if !ann.is_empty() && ann.dim() != manifest.dimensions {
+            problems.push(format!(
+                "{}: {}-dimensional ANN vectors, {} expected",
+                name,
+                ann.dim(),
+                manifest.dimensions
+            ));
+        }
+    }
+    problems.sort();
+    problems
+}
+
+fn run_query(
+    embedder: &mut Box<dyn cs_embed::Embedder>,
+    ann: &SimpleIndex,
+    chunks: &[ShadowChunk],
+    query: &ShadowQuery,
+    top_k: usize,
+) -> Result<(Vec<ShadowHit>, f64)> {
+    let start = Instant::now();
+    let embedding = embedder
+        .embed(std::slice::from_ref(&query.query))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Embedder returned no vector for {:?}", query.query))?;
+    let hits = ann
+        .search(&embedding, top_k)?
+        .into_iter()
+        .filter_map(|(id, score)| {
+            chunks.get(id as usize).map(|chunk| ShadowHit {
+                file: chunk.file.clone(),
+                span: chunk.span.clone(),
+                score,
+            })
+        })
+        .collect();
+    Ok((hits, start.elapsed().as_secs_f64() * 1000.0))
+}
+
+fn first_rank(hits: &[ShadowHit], expected: &str) -> Option<usize> {
+    let expected = Path::new(expected.trim_start_matches("./"));
+    hits.iter()
+        .position(|hit| path_utils::from_manifest_path(&hit.file).ends_with(expected))
+        .map(|i| i + 1)
+}
+
+fn file_overlap(a: &[ShadowHit], b: &[ShadowHit]) -> f32 {
+    let a: HashSet<&PathBuf> = a.iter().map(|hit| &hit.file).collect();
+    let b: HashSet<&PathBuf> = b.iter().map(|hit| &hit.file).collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f32 / union as f32
+}
+
+fn summarize(
+    primary_model: String,
+    shadow: ShadowManifest,
+    primary_chunks: usize,
+    top_k: usize,
+    queries: Vec<ShadowQueryReport>,
+) -> ShadowReport {
+    let n = queries.len().max(1) as f32;
+    let mean = |f: &dyn Fn(&ShadowQueryReport) -> f32| queries.iter().map(f).sum::<f32>() / n;
+    let mean_ms = |f: &dyn Fn(&ShadowQueryReport) -> f64| {
+        queries.iter().map(f).sum::<f64>() / queries.len().max(1) as f64
+    };
+
+    let judged: Vec<&ShadowQueryReport> = queries.iter().filter(|q| q.expected.is_some()).collect();
+    let relevance = |rank: &dyn Fn(&ShadowQueryReport) -> Option<usize>| {
+        if judged.is_empty() {
+            return (None, None);
+        }
+        let count = judged.len() as f32;
+        let mrr = judged
+            .iter()
+            .map(|q| rank(q).map_or(0.0, |r| 1.0 / r as f32))
+            .sum::<f32>()
+            / count;
+        let hits = judged.iter().filter(|q| rank(q).is_some()).count() as f32 / count;
+        (Some(mrr), Some(hits))
+    };
+    let (primary_mrr, primary_hit_rate) = relevance(&|q| q.primary_rank);
+    let (shadow_mrr, shadow_hit_rate) = relevance(&|q| q.shadow_rank);
+
+    ShadowReport {
+        primary_model,
+        shadow_model: shadow.model,
+        files_sampled: shadow.files.len(),
+        primary_chunks,
+        shadow_chunks: shadow.chunks.len(),
+        top_k,
+        mean_overlap: mean(&|q| q.overlap),
+        primary_mean_top_score: mean(&|q| q.primary.first().map_or(0.0, |h| h.score)),
+        shadow_mean_top_score: mean(&|q| q.shadow.first().map_or(0.0, |h| h.score)),
+        primary_mrr,
+        shadow_mrr,
+        primary_hit_rate,
+        shadow_hit_rate,
+        primary_avg_ms: mean_ms(&|q| q.primary_ms),
+        shadow_avg_ms: mean_ms(&|q| q.shadow_ms),
+        queries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{save_index_entry, save_manifest};
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Embeds text as letter frequencies, optionally restricted to vowels or consonants,
+    /// so two instances behave like different models with different dimensions
+    struct LetterEmbedder {
+        name: &'static str,
+        letters: &'static str,
+    }
+
+    impl cs_embed::Embedder for LetterEmbedder {
+        fn id(&self) -> &'static str {
+            "letters"
+        }
+
+        fn dim(&self) -> usize {
+            self.letters.len()
+        }
+
+        fn model_name(&self) -> &str {
+            self.name
+        }
+
+        fn embed(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    self.letters
+                        .chars()
+                        .map(|c| text.matches(c).count() as f32 + 0.01)
+                        .collect()
+                })
+                .collect())
+        }
+    }
+
+    fn embedder(name: &'static str, letters: &'static str) -> Box<dyn cs_embed::Embedder> {
+        Box::new(LetterEmbedder { name, letters })
+    }
+
+    fn build_primary(root: &Path, files: &[(&str, &str)]) {
+        let mut primary = embedder("primary", "abcdefghijklmnopqrstuvwxyz");
+        let mut manifest = IndexManifest {
+            embedding_model: Some("primary".to_string()),
+            ..Default::default()
+        };
+        for (name, content) in files {
+            let path = root.join(name);
+            fs::write(&path, content).unwrap();
+            let entry = index_single_file(&path, root, Some(&mut primary)).unwrap();
+            save_index_entry(&cs_core::get_sidecar_path(root, &path), &entry).unwrap();
+            manifest
+                .files
+                .insert(entry.metadata.path.clone(), entry.metadata);
+        }
+        save_manifest(&root.join(".cs").join("manifest.json"), &manifest).unwrap();
+    }
+
+    #[test]
+    fn test_sample_files_is_stable_and_spread() {
+        let files: Vec<PathBuf> = (0..10)
+            .rev()
+            .map(|i| PathBuf::from(format!("f{i}.rs")))
+            .collect();
+
+        assert_eq!(sample_files(&files, 20).len(), 10);
+        let sample = sample_files(&files, 3);
+        assert_eq!(
+            sample,
+            vec![
+                PathBuf::from("f0.rs"),
+                PathBuf::from("f3.rs"),
+                PathBuf::from("f6.rs")
+            ]
+        );
+        assert_eq!(sample, sample_files(&files, 3));
+    }
+
+    #[test]
+    fn test_parse_shadow_queries() {
+        let queries = parse_shadow_queries(
+            "# comment\nerror handling\tsrc/error.rs\n\nretry logic\nparse args\t \n",
+        );
+        assert_eq!(
+            queries,
+            vec![
+                ShadowQuery {
+                    query: "error handling".to_string(),
+                    expected: Some("src/error.rs".to_string()),
+                },
+                ShadowQuery {
+                    query: "retry logic".to_string(),
+                    expected: None,
+                },
+                ShadowQuery {
+                    query: "parse args".to_string(),
+                    expected: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shadow_build_and_compare() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        build_primary(
+            root,
+            &[
+                ("a_zebra.txt", "zzz zebra zigzag"),
+                ("b_vowels.txt", "aeiou aeiou aeiou"),
+                ("c_mixed.txt", "the quick brown fox"),
+            ],
+        );
+
+        let mut shadow = embedder("shadow", "aeiouz");
+        let stats = build_shadow_index_with_embedder(root, &mut shadow, 2, None).unwrap();
+        assert_eq!(stats.files_sampled, 2);
+        assert_eq!(stats.files_embedded, 2);
+        assert_eq!(stats.dimensions, 6);
+        assert!(shadow_dir(root, "shadow").join(SHADOW_ANN).exists());
+
+        // The primary index is untouched and the shadow does not look like a sidecar
+        let primary_stats = super::super::get_index_stats(root).unwrap();
+        assert_eq!(primary_stats.total_files, 3);
+
+        let mut primary = embedder("primary", "abcdefghijklmnopqrstuvwxyz");
+        let queries = parse_shadow_queries("zigzag zebra\ta_zebra.txt\naeiou\tnot-there.txt\n");
+        let report =
+            compare_shadow_with_embedders(root, &mut primary, &mut shadow, &queries, 1).unwrap();
+
+        assert_eq!(report.files_sampled, 2);
+        assert_eq!(report.queries.len(), 2);
+        assert_eq!(report.queries[0].primary_rank, Some(1));
+        assert_eq!(report.queries[0].shadow_rank, Some(1));
+        assert_eq!(report.queries[0].overlap, 1.0);
+        assert_eq!(report.primary_mrr, Some(0.5));
+        assert_eq!(report.shadow_hit_rate, Some(0.5));
+    }
+
+    #[test]
+    fn test_shadow_rejects_primary_model() {
+        let temp_dir = TempDir::new().unwrap();
+        build_primary(temp_dir.path(), &[("a.txt", "alpha")]);
+
+        let mut same = embedder("primary", "abc");
+        let err =
+            build_shadow_index_with_embedder(temp_dir.path(), &mut same, 10, None).unwrap_err();
+        assert!(err.to_string().contains("already uses primary"));
+    }
+}
@@ -0,0 +1,418 @@
+//! Sharded indexing for splitting the embedding work of very large repositories across machines.
+//!
+//! Each worker runs over its own checkout of the same commit and keeps only the files whose
+//! manifest path hashes into its shard, so no coordinator has to hand out file lists: shard
+//! `i/N` on any machine selects the same files. A worker writes a self-contained artifact
+//! (`shard.json` plus one sidecar per file); the merge step copies every artifact into the
+//! index, keyed by the same repository-relative paths a local `cs --index` would use.
+
+use super::{
+    INDEX_INTERRUPTED_MSG, INTERRUPTED, IndexManifest, apply_index_file_limits, atomic_write,
+    collect_files, index_single_file, load_index_entry, load_or_create_manifest,
+    normalize_manifest_paths, path_utils, save_index_entry, save_manifest,
+};
+use anyhow::Result;
+use cs_core::FileMetadata;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
+
+const SHARD_MANIFEST: &str = "shard.json";
+const SHARD_SIDECARS: &str = "sidecars";
+
+/// One slice of a sharded indexing run, written `i/N` with `1 <= i <= N`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardSpec {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl std::str::FromStr for ShardSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = || -> Option<ShardSpec> {
+            let (index, count) = s.split_once('/')?;
+            Some(ShardSpec {
+                index: index.trim().parse().ok()?,
+                count: count.trim().parse().ok()?,
+            })
+        };
+        match parse() {
+            Some(spec) if spec.index >= 1 && spec.index <= spec.count => Ok(spec),
+            _ => anyhow::bail!("Invalid shard '{}': expected I/N with 1 <= I <= N", s),
+        }
+    }
+}
+
+impl std::fmt::Display for ShardSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+impl ShardSpec {
+    /// Whether the file at `manifest_path` belongs to this shard; depends only on the path
+    pub fn contains(&self, manifest_path: &Path) -> bool {
+        let key = path_utils::from_manifest_path(manifest_path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let hash = blake3::hash(key.as_bytes());
+        let bucket = u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap());
+        (bucket % self.count as u64) as usize == self.index - 1
+    }
+
+    /// Artifact directory name, e.g. `shard-2-of-8`
+    pub fn dir_name(&self) -> String {
+        format!("shard-{}-of-{}", self.index, self.count)
+    }
+}
+
+/// Header of a shard artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardManifest {
+    pub shard: ShardSpec,
+    pub embedding_model: String,
+    pub embedding_dimensions: usize,
+    pub created: u64,
+    pub files: HashMap<PathBuf, FileMetadata>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShardBuildStats {
+    pub files_in_shard: usize,
+    pub files_indexed: usize,
+    pub files_errored: usize,
+    pub chunks_embedded: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShardMergeStats {
+    pub shards: usize,
+    pub files_merged: usize,
+}
+
+/// Embed the files of `shard` under `repo_root` into an artifact at `out_dir/shard-I-of-N`
+pub fn build_shard(
+    repo_root: &Path,
+    shard: ShardSpec,
+    out_dir: &Path,
+    respect_gitignore: bool,
+    exclude_patterns: &[String],
+    model: &str,
+    progress_callback: Option<super::ProgressCallback>,
+) -> Result<(PathBuf, ShardBuildStats)> {
+    let mut embedder = cs_embed::create_embedder(Some(model))?;
+
+    let files: Vec<PathBuf> = collect_files(repo_root, respect_gitignore, exclude_patterns)?
+        .into_iter()
+        .filter(|file| {
+            let standard_path = path_utils::to_standard_path(file, repo_root);
+            shard.contains(&path_utils::to_manifest_path(&standard_path))
+        })
+        .collect();
+    let (files, _) = apply_index_file_limits(files);
+
+    let dir = out_dir.join(shard.dir_name());
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    let sidecar_dir = dir.join(SHARD_SIDECARS);
+
+    let mut stats = ShardBuildStats {
+        files_in_shard: files.len(),
+        ..Default::default()
+    };
+    let mut manifest = ShardManifest {
+        shard,
+        embedding_model: embedder.model_name().to_string(),
+        embedding_dimensions: embedder.dim(),
+        created: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        files: HashMap::new(),
+    };
+
+    for (i, file_path) in files.iter().enumerate() {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!(INDEX_INTERRUPTED_MSG));
+        }
+        if let Some(ref callback) = progress_callback {
+            callback(&format!(
+                "[{}/{}] {}",
+                i + 1,
+                files.len(),
+                path_utils::to_standard_path(file_path, repo_root).display()
+            ));
+        }
+
+        match index_single_file(file_path, repo_root, Some(&mut embedder)) {
+            Ok(entry) => {
+                let standard_path = path_utils::from_manifest_path(&entry.metadata.path);
+                save_index_entry(
+                    &path_utils::get_sidecar_path_for_standard_path(&sidecar_dir, &standard_path),
+                    &entry,
+                )?;
+                stats.files_indexed += 1;
+                stats.chunks_embedded += entry
+                    .chunks
+                    .iter()
+                    .filter(|chunk| chunk.embedding.is_some())
+                    .count();
+                manifest
+                    .files
+                    .insert(entry.metadata.path.clone(), entry.metadata);
+            }
+            Err(e) => {
+                if !e.to_string().contains("Binary file, skipping") {
+                    stats.files_errored += 1;
+                    tracing::warn!("Failed to index {:?} for shard {}: {}", file_path, shard, e);
+                }
+            }
+        }
+    }
+
+    atomic_write(
+        &dir.join(SHARD_MANIFEST),
+        &serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    Ok((dir, stats))
+}
+
+pub fn load_shard_manifest(shard_dir: &Path) -> Result<ShardManifest> {
+    let path = shard_dir.join(SHARD_MANIFEST);
+    let data = fs::read(&path)
+        .map_err(|e| anyhow::anyhow!("Not a shard artifact: {} ({})", shard_dir.display(), e))?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+/// Merge a complete set of shard artifacts into the index at `repo_root`
+///
+/// All shards must come from the same `N`, cover `1..=N` exactly once and share one
+/// embedding model, which must also match any embeddings already in the index.
+pub fn merge_shards(repo_root: &Path, shard_dirs: &[PathBuf]) -> Result<ShardMergeStats> {
+    let mut shards = shard_dirs
+        .iter()
+        .map(|dir| Ok((dir.clone(), load_shard_manifest(dir)?)))
+        .collect::<Result<Vec<_>>>()?;
+    shards.sort_by_key(|(_, manifest)| manifest.shard.index);
+
+    let Some((_, first)) = shards.first() else {
+        anyhow::bail!("No shards to merge");
+    };
+    let count = first.shard.count;
+    let model = first.embedding_model.clone();
+    let dimensions = first.embedding_dimensions;
+
+    for (dir, manifest) in &shards {
+        if manifest.shard.count != count {
+            anyhow::bail!(
+                "Shard {} is {} but other shards are out of {}",
+                dir.display(),
+                manifest.shard,
+                count
+            );
+        }
+        if manifest.embedding_model != model {
+            anyhow::bail!(
+                "Shard {} was embedded with {} but shard {} used {}",
+                manifest.shard,
+                manifest.embedding_model,
+                first.shard,
+                model
+            );
+        }
+    }
+    let indices: Vec<usize> = shards.iter().map(|(_, m)| m.shard.index).collect();
+    if indices != (1..=count).collect::<Vec<_>>() {
+        anyhow::bail!(
+            "Incomplete shard set: have {:?} of 1..={}; every shard must be merged exactly once",
+            indices,
+            count
+        );
+    }
+
+    let index_dir = repo_root.join(".cs");
+    fs::create_dir_all(&index_dir)?;
+    let manifest_path = index_dir.join("manifest.json");
+    let mut manifest: IndexManifest = load_or_create_manifest(&manifest_path)?;
+    normalize_manifest_paths(&mut manifest, repo_root);
+    if let Some(existing) = &manifest.embedding_model
+        && *existing != model
+        && !manifest.files.is_empty()
+    {
+        anyhow::bail!(
+            "The index at {} uses {} but the shards were embedded with {}. Run 'cs --clean' first.",
+            repo_root.display(),
+            existing,
+            model
+        );
+    }
+
+    let mut stats = ShardMergeStats {
+        shards: shards.len(),
+        ..Default::default()
+    };
+    for (dir, shard) in &shards {
+        let sidecar_dir = dir.join(SHARD_SIDECARS);
+        for (manifest_key, metadata) in &shard.files {
+            let standard_path = path_utils::from_manifest_path(manifest_key);
+            let entry = load_index_entry(&path_utils::get_sidecar_path_for_standard_path(
+                &sidecar_dir,
+                &standard_path,
+            ))?;
+            save_index_entry(
+                &path_utils::get_sidecar_path_for_standard_path(&index_dir, &standard_path),
+                &entry,
+            )?;
+            manifest
+                .files
+                .insert(manifest_key.clone(), metadata.clone());
+            stats.files_merged += 1;
+        }
+    }
+
+    manifest.set_embedding_model(model, dimensions);
+    manifest.updated = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    save_manifest(&manifest_path, &manifest)?;
+
+    // The lexical index is rebuilt from the merged sidecars on the next search
+    let tantivy_dir = index_dir.join("tantivy_index");
+    if tantivy_dir.exists() {
+        fs::remove_dir_all(&tantivy_dir)?;
+    }
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_shard_spec_parse() {
+        let spec: ShardSpec = "2/8".parse().unwrap();
+        assert_eq!(spec, ShardSpec { index: 2, count: 8 });
+        assert_eq!(spec.dir_name(), "shard-2-of-8");
+        assert!("0/4".parse::<ShardSpec>().is_err());
+        assert!("5/4".parse::<ShardSpec>().is_err());
+        assert!("3".parse::<ShardSpec>().is_err());
+    }
+
+    #[test]
+    fn test_shards_partition_files() {
+        let files: Vec<PathBuf> = (0..200)
+            .map(|i| PathBuf::from(format!("./src/mod_{}/file_{}.rs", i % 7, i)))
+            .collect();
+        let shards: Vec<ShardSpec> = (1..=4).map(|index| ShardSpec { index, count: 4 }).collect();
+
+        let mut seen = HashSet::new();
+        for shard in &shards {
+            let members: Vec<_> = files.iter().filter(|f| shard.contains(f)).collect();
+            assert!(!members.is_empty());
+            for file in members {
+                assert!(seen.insert(file.clone()), "{:?} is in two shards", file);
+            }
+        }
+        assert_eq!(seen.len(), files.len());
+
+        // `./` prefixes and bare relative paths land in the same shard
+        let shard = shards
+            .iter()
+            .find(|s| s.contains(Path::new("./src/lib.rs")))
+            .unwrap();
+        assert!(shard.contains(Path::new("src/lib.rs")));
+    }
+
+    fn write_shard(root: &Path, spec: ShardSpec, model: &str, files: &[&str]) -> PathBuf {
+        let dir = root.join(spec.dir_name());
+        let mut manifest = ShardManifest {
+            shard: spec,
+            embedding_model: model.to_string(),
+            embedding_dimensions: 2,
+            created: 0,
+            files: HashMap::new(),
+        };
+        for file in files {
+            let metadata = FileMetadata {
+                path: PathBuf::from(format!("./{}", file)),
+                hash: format!("hash-{}", file),
+                last_modified: 0,
+                size: 1,
+            };
+            let entry = super::super::IndexEntry {
+                metadata: metadata.clone(),
+                chunks: Vec::new(),
+                embedding_info: None,
+                sparse: None,
+                withheld: None,
+            };
+            save_index_entry(
+                &path_utils::get_sidecar_path_for_standard_path(
+                    &dir.join(SHARD_SIDECARS),
+                    Path::new(file),
+                ),
+                &entry,
+            )
+            .unwrap();
+            manifest.files.insert(metadata.path.clone(), metadata);
+        }
+        atomic_write(
+            &dir.join(SHARD_MANIFEST),
+            &serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_merge_shards() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let shards_root = temp_dir.path().join("shards");
+        let repo = temp_dir.path().join("repo");
+        fs::create_dir_all(&repo).unwrap();
+
+        let one = write_shard(
+            &shards_root,
+            ShardSpec { index: 1, count: 2 },
+            "model-a",
+            &["a.rs", "src/b.rs"],
+        );
+        let two = write_shard(
+            &shards_root,
+            ShardSpec { index: 2, count: 2 },
+            "model-a",
+            &["src/c.py"],
+        );
+
+        let err = merge_shards(&repo, std::slice::from_ref(&one)).unwrap_err();
+        assert!(err.to_string().contains("Incomplete shard set"));
+
+        let stats = merge_shards(&repo, &[two.clone(), one.clone()]).unwrap();
+        assert_eq!(stats.shards, 2);
+        assert_eq!(stats.files_merged, 3);
+
+        let manifest = load_or_create_manifest(&repo.join(".cs/manifest.json")).unwrap();
+        assert_eq!(manifest.embedding_model.as_deref(), Some("model-a"));
+        assert_eq!(manifest.files.len(), 3);
+        let entry = load_index_entry(&repo.join(".cs/src/b.rs.cs")).unwrap();
+        assert_eq!(entry.metadata.hash, "hash-src/b.rs");
+
+        let other_model = write_shard(
+            &temp_dir.path().join("other"),
+            ShardSpec { index: 1, count: 1 },
+            "model-b",
+            &["a.rs"],
+        );
+        let err = merge_shards(&repo, &[other_model]).unwrap_err();
+        assert!(err.to_string().contains("Run 'cs --clean' first"));
+    }
+}
@@ -0,0 +1,195 @@
+//! BM25 term vectors stored next to dense embeddings (`--sparse`).
+//!
+//! A chunk's embedded text is split into code-aware terms (whole identifiers plus their
+//! camelCase and snake_case parts, lowercased) and stored as hashed term counts. Semantic search
+//! scores the query's terms against them with BM25 and fuses that into the cosine similarity, so
+//! an exact identifier in the query lifts the chunks that contain it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// BM25 term-frequency saturation
+const K1: f32 = 1.2;
+/// BM25 length normalization
+const B: f32 = 0.75;
+
+/// Term counts of one chunk, keyed by hashed term and sorted by `indices`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SparseVector {
+    pub indices: Vec<u32>,
+    pub values: Vec<f32>,
+}
+
+impl SparseVector {
+    /// Count of the term with id `index`, or 0
+    pub fn get(&self, index: u32) -> f32 {
+        self.indices
+            .binary_search(&index)
+            .map_or(0.0, |position| self.values[position])
+    }
+
+    /// Number of terms in the chunk
+    pub fn length(&self) -> f32 {
+        self.values.iter().sum()
+    }
+}
+
+/// Term counts of `text`
+pub fn sparse_vector(text: &str) -> SparseVector {
+    let mut counts: BTreeMap<u32, f32> = BTreeMap::new();
+    for term in terms(text) {
+        *counts.entry(term_id(&term)).or_default() += 1.0;
+    }
+    SparseVector {
+        indices: counts.keys().copied().collect(),
+        values: counts.into_values().collect(),
+    }
+}
+
+/// Distinct term ids of a query
+pub fn query_terms(query: &str) -> Vec<u32> {
+    let mut ids: Vec<u32> = terms(query).map(|term| term_id(&term)).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// BM25 statistics over the chunks being searched
+pub struct Bm25 {
+    doc_freq: HashMap<u32, u32>,
+    docs: usize,
+    average_length: f32,
+}
+
+impl Bm25 {
+    pub fn new<'a>(vectors: impl IntoIterator<Item = &'a SparseVector>) -> Self {
+        let mut doc_freq: HashMap<u32, u32> = HashMap::new();
+        let (mut docs, mut total_length) = (0, 0.0);
+        for vector in vectors {
+            docs += 1;
+            total_length += vector.length();
+            for index in &vector.indices {
+                *doc_freq.entry(*index).or_default() += 1;
+            }
+        }
+        Self {
+            doc_freq,
+            docs,
+            average_length: if docs == 0 {
+                0.0
+            } else {
+                total_length / docs as f32
+            },
+        }
+    }
+
+    /// BM25 score of a chunk for the query terms
+    pub fn score(&self, query: &[u32], vector: &SparseVector) -> f32 {
+        let length_norm = if self.average_length > 0.0 {
+            1.0 - B + B * vector.length() / self.average_length
+        } else {
+            1.0
+        };
+        query
+            .iter()
+            .map(|term| {
+                let tf = vector.get(*term);
+                if tf == 0.0 {
+                    return 0.0;
+                }
+                let df = self.doc_freq.get(term).copied().unwrap_or(0) as f32;
+                let idf = (1.0 + (self.docs as f32 - df + 0.5) / (df + 0.5)).ln();
+                idf * tf * (K1 + 1.0) / (tf + K1 * length_norm)
+            })
+            .sum()
+    }
+}
+
+/// Lowercased identifiers and, for compound ones, their parts: `parseHTTPRequest` gives
+/// `parsehttprequest`, `parse`, `http` and `request`
+pub(crate) fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| word.chars().any(char::is_alphabetic))
+        .flat_map(|word| {
+            let parts = identifier_parts(word);
+            let whole = word.trim_matches('_').to_lowercase();
+            let compound = parts.len() > 1;
+            std::iter::once(whole)
+                .chain(parts.into_iter().filter(move |_| compound))
+                .filter(|term| term.chars().count() > 1 && term.chars().any(char::is_alphabetic))
+        })
+}
+
+pub(crate) fn identifier_parts(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    for segment in word.split('_').filter(|segment| !segment.is_empty()) {
+        let chars: Vec<char> = segment.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, current) = (chars[i - 1], chars[i]);
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            // fooBar, HTTPServer (split before the S), v2Api
+            let boundary = (prev.is_lowercase() && current.is_uppercase())
+                || (prev.is_uppercase() && current.is_uppercase() && next_is_lower)
+                || (prev.is_ascii_digit() != current.is_ascii_digit());
+            if boundary {
+                parts.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        parts.push(chars[start..].iter().collect::<String>().to_lowercase());
+    }
+    parts
+}
+
+/// FNV-1a, so term ids are stable across runs and platforms
+fn term_id(term: &str) -> u32 {
+    term.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terms_split_identifiers() {
+        let terms: Vec<String> =
+            terms("fn parseHTTPRequest(max_retry_count: u32) -> v2Api").collect();
+        assert_eq!(
+            terms,
+            [
+                "fn",
+                "parsehttprequest",
+                "parse",
+                "http",
+                "request",
+                "max_retry_count",
+                "max",
+                "retry",
+                "count",
+                "u32",
+                "v2api",
+                "api"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bm25_prefers_exact_identifier() {
+        let chunks = [
+            sparse_vector("fn load_config(path: &Path) -> Config { read(path) }"),
+            sparse_vector("fn parse_retry_policy(env: &Env) -> RetryPolicy { env.policy() }"),
+            sparse_vector("fn retry(request: Request) { send(request) }"),
+        ];
+        let bm25 = Bm25::new(&chunks);
+        let query = query_terms("parse_retry_policy");
+        let scores: Vec<f32> = chunks
+            .iter()
+            .map(|chunk| bm25.score(&query, chunk))
+            .collect();
+        assert_eq!(scores[0], 0.0);
+        assert!(scores[1] > scores[2] && scores[2] > 0.0, "{:?}", scores);
+    }
+}
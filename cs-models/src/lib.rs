@@ -3,9 +3,22 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+mod project_config;
 mod user_config;
+pub use project_config::ProjectConfig;
 pub use user_config::UserConfig;
 
+/// Embedding model to use for `path` when no `--model` is given: the project's `cs.toml`, then
+/// the user's `default-model`; `None` leaves the choice to the registry default
+pub fn configured_model(path: &Path) -> Result<Option<String>> {
+    if let Some(model) = ProjectConfig::discover(path)?.and_then(|config| config.model) {
+        return Ok(Some(model));
+    }
+    Ok(UserConfig::load()
+        .ok()
+        .and_then(|config| config.default_model))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
     pub name: String,
@@ -13,6 +26,21 @@ pub struct ModelConfig {
     pub dimensions: usize,
     pub max_tokens: usize,
     pub description: String,
+    /// API price in US dollars per million input tokens; `None` for models that run locally
+    #[serde(default)]
+    pub usd_per_million_tokens: Option<f64>,
+}
+
+/// Jina AI's pay-as-you-go list price, used for `--index --dry-run` cost estimates; see
+/// jina.ai/embeddings for current rates
+const JINA_USD_PER_MILLION_TOKENS: f64 = 0.05;
+
+impl ModelConfig {
+    /// What embedding `tokens` tokens would cost through the model's API; `None` when local
+    pub fn estimated_cost(&self, tokens: usize) -> Option<f64> {
+        self.usd_per_million_tokens
+            .map(|price| price * tokens as f64 / 1_000_000.0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +61,7 @@ impl Default for ModelRegistry {
                 dimensions: 384,
                 max_tokens: 512,
                 description: "Small, fast English embedding model".to_string(),
+                usd_per_million_tokens: None,
             },
         );
 
@@ -44,6 +73,7 @@ impl Default for ModelRegistry {
                 dimensions: 384,
                 max_tokens: 256,
                 description: "Lightweight English embedding model".to_string(),
+                usd_per_million_tokens: None,
             },
         );
 
@@ -57,6 +87,7 @@ impl Default for ModelRegistry {
                 max_tokens: 8192,
                 description: "High-quality English embedding model with large context window"
                     .to_string(),
+                usd_per_million_tokens: None,
             },
         );
 
@@ -69,6 +100,7 @@ impl Default for ModelRegistry {
                 max_tokens: 8192,
                 description: "Code-specific embedding model optimized for programming tasks"
                     .to_string(),
+                usd_per_million_tokens: None,
             },
         );
 
@@ -82,6 +114,7 @@ impl Default for ModelRegistry {
                 max_tokens: 32768,
                 description: "Jina AI API: 494M parameter code embedding model for NL2Code, code similarity, and cross-language retrieval (requires JINA_API_KEY)"
                     .to_string(),
+                usd_per_million_tokens: Some(JINA_USD_PER_MILLION_TOKENS),
             },
         );
 
@@ -94,6 +127,7 @@ impl Default for ModelRegistry {
                 max_tokens: 32768,
                 description: "Jina AI API: 1.54B parameter advanced code embedding model with enhanced retrieval capabilities (requires JINA_API_KEY)"
                     .to_string(),
+                usd_per_million_tokens: Some(JINA_USD_PER_MILLION_TOKENS),
             },
         );
 
@@ -106,6 +140,7 @@ impl Default for ModelRegistry {
                 max_tokens: 8192,
                 description: "Jina AI API: 570M parameter multilingual text embedding model (requires JINA_API_KEY)"
                     .to_string(),
+                usd_per_million_tokens: Some(JINA_USD_PER_MILLION_TOKENS),
             },
         );
 
@@ -118,6 +153,7 @@ impl Default for ModelRegistry {
                 max_tokens: 8192,
                 description: "Jina AI API: 3.8B parameter multimodal embedding model - BEST for indexing large code files (supports 8K+ tokens, outputs 1536d for jina-code-1.5b compatibility) (requires JINA_API_KEY)"
                     .to_string(),
+                usd_per_million_tokens: Some(JINA_USD_PER_MILLION_TOKENS),
             },
         );
 
@@ -151,40 +187,108 @@ impl ModelRegistry {
     pub fn get_default_model(&self) -> Option<&ModelConfig> {
         self.models.get(&self.default_model)
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProjectConfig {
-    pub model: String,
-    pub chunk_size: usize,
-    pub chunk_overlap: usize,
-    pub index_backend: String,
-}
+    /// Look up a model by alias ("nomic-v1.5") or full name ("nomic-embed-text-v1.5"),
+    /// ignoring case; returns the alias with the config
+    pub fn find(&self, name: &str) -> Option<(&str, &ModelConfig)> {
+        if let Some((alias, config)) = self.models.get_key_value(name) {
+            return Some((alias.as_str(), config));
+        }
+        self.models
+            .iter()
+            .find(|(alias, config)| {
+                alias.eq_ignore_ascii_case(name) || config.name.eq_ignore_ascii_case(name)
+            })
+            .map(|(alias, config)| (alias.as_str(), config))
+    }
 
-impl Default for ProjectConfig {
-    fn default() -> Self {
-        Self {
-            model: "bge-small".to_string(),
-            chunk_size: 512,
-            chunk_overlap: 128,
-            index_backend: "hnsw".to_string(),
+    /// Resolve a requested model, or the default model when none is requested
+    ///
+    /// Unknown names fail with the closest known alias as a suggestion and the list of aliases.
+    pub fn resolve(&self, requested: Option<&str>) -> Result<(String, ModelConfig)> {
+        let Some(name) = requested else {
+            let config = self
+                .get_default_model()
+                .ok_or_else(|| anyhow::anyhow!("No default embedding model configured"))?;
+            return Ok((self.default_model.clone(), config.clone()));
+        };
+        if let Some((alias, config)) = self.find(name) {
+            return Ok((alias.to_string(), config.clone()));
         }
+
+        let suggestion = self
+            .suggest(name)
+            .map(|alias| format!(" Did you mean '{}'?", alias))
+            .unwrap_or_default();
+        anyhow::bail!(
+            "Unknown model '{}'.{} Available models: {}",
+            name,
+            suggestion,
+            self.aliases().join(", ")
+        )
+    }
+
+    /// Model aliases, sorted
+    pub fn aliases(&self) -> Vec<&str> {
+        let mut aliases: Vec<&str> = self.models.keys().map(String::as_str).collect();
+        aliases.sort_unstable();
+        aliases
+    }
+
+    /// The alias closest to a mistyped model name, when one is plausibly meant
+    pub fn suggest(&self, name: &str) -> Option<&str> {
+        let name = name.to_lowercase();
+        self.aliases()
+            .into_iter()
+            .filter_map(|alias| {
+                let full_name = self.models[alias].name.to_lowercase();
+                let distance = edit_distance(&name, alias).min(edit_distance(&name, &full_name));
+                // Partial names ("nomic", "minilm-l6") count as close matches
+                let partial = alias.contains(name.as_str()) || full_name.contains(name.as_str());
+                let close = distance <= (name.len() / 3).max(2);
+                (partial || close).then_some((if partial { 0 } else { distance }, alias))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, alias)| alias)
     }
 }
 
-impl ProjectConfig {
-    pub fn load(path: &Path) -> Result<Self> {
-        if path.exists() {
-            let data = std::fs::read_to_string(path)?;
-            Ok(serde_json::from_str(&data)?)
-        } else {
-            Ok(Self::default())
+/// Levenshtein distance between two strings, by characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
         }
+        previous = current;
     }
+    previous[b.len()]
+}
 
-    pub fn save(&self, path: &Path) -> Result<()> {
-        let data = serde_json::to_string_pretty(self)?;
-        std::fs::write(path, data)?;
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_aliases_and_full_names() {
+        let registry = ModelRegistry::default();
+
+        let (alias, config) = registry.resolve(None).unwrap();
+        assert_eq!(alias, "bge-small");
+        assert_eq!(config.name, "BAAI/bge-small-en-v1.5");
+
+        let (alias, config) = registry.resolve(Some("nomic-embed-text-v1.5")).unwrap();
+        assert_eq!(alias, "nomic-v1.5");
+        assert_eq!(config.dimensions, 768);
+        assert_eq!(registry.resolve(Some("MiniLM")).unwrap().0, "minilm");
+
+        let error = registry.resolve(Some("nomc-v1.5")).unwrap_err().to_string();
+        assert!(error.contains("Did you mean 'nomic-v1.5'?"), "{}", error);
+        assert!(error.contains("bge-small, jina-code"), "{}", error);
+        assert_eq!(registry.suggest("jina-code-1.5"), Some("jina-code-1.5b"));
+        assert_eq!(registry.suggest("word2vec"), None);
     }
 }
@@ -0,0 +1,206 @@
+use anyhow::Result;
+use cs_core::embed_template::EmbedTemplate;
+use cs_core::{FusionWeights, GcPolicy, IndexBackend, Language};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Per-repository search defaults, read from `cs.toml` at the repository root or from
+/// `.cs/config.toml`. Every setting is optional; command-line flags take precedence.
+///
+/// ```toml
+/// model = "bge-small"
+/// top_k = 20
+/// threshold = 0.65
+/// exclude = ["fixtures/", "*.snap"]
+/// languages = ["rust", "python"]
+/// chunk_tokens = 600
+/// chunk_overlap = 120
+/// stride = true
+/// embed_template = "{path} :: {symbol} :: {code}"
+/// sparse = true
+/// remote = "s3://team-indexes/semcs"
+/// allow_secrets = false
+/// redact_paths = true
+///
+/// [fusion]
+/// semantic = 2.0
+/// regex = 1.0
+///
+/// [index_backend]
+/// quantization = "int8"
+/// rerank_factor = 4
+///
+/// [gc]
+/// max_size_mb = 2048
+/// max_age_days = 30
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Embedding model used for indexing and semantic search
+    pub model: Option<String>,
+    /// Result limit for semantic, lexical, and hybrid search
+    pub top_k: Option<usize>,
+    /// Minimum similarity for semantic search
+    pub threshold: Option<f32>,
+    /// Extra exclude patterns, added to the default and `.csignore` excludes
+    pub exclude: Vec<String>,
+    /// Only search files in these languages (names like "rust" or extensions like "rs")
+    pub languages: Vec<String>,
+    /// Weights of the ranked lists fused by hybrid search
+    pub fusion: Option<FusionWeights>,
+    /// Target tokens per chunk, replacing the model's default
+    pub chunk_tokens: Option<usize>,
+    /// Tokens shared by neighbouring strides, replacing the model's default
+    pub chunk_overlap: Option<usize>,
+    /// `false` keeps definitions longer than `chunk_tokens` whole instead of striding them
+    pub stride: Option<bool>,
+    /// How the embedded text of each chunk is composed from its path, symbol and code
+    pub embed_template: Option<EmbedTemplate>,
+    /// Store BM25 term vectors next to embeddings so semantic search rewards exact identifiers
+    pub sparse: Option<bool>,
+    /// Shared index to pull sidecars from instead of embedding files locally, e.g.
+    /// `s3://bucket/prefix`, an `https://` URL or a directory
+    pub remote: Option<String>,
+    /// Index files and chunks that look like secrets instead of withholding them
+    pub allow_secrets: Option<bool>,
+    /// Print paths relative to the index root, with user names masked, in search output and
+    /// MCP tool results
+    pub redact_paths: Option<bool>,
+    /// Vector encoding for semantic search, e.g. int8 quantization for very large indexes
+    pub index_backend: Option<IndexBackend>,
+    /// Size budget and age limit for caches in the index directory
+    pub gc: Option<GcPolicy>,
+}
+
+impl ProjectConfig {
+    /// Config file names, checked in this order in each directory
+    pub const FILE_NAMES: [&'static str; 2] = ["cs.toml", ".cs/config.toml"];
+
+    /// Load a config file, validating language names
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+        for language in &config.languages {
+            language
+                .parse::<Language>()
+                .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        }
+        Ok(config)
+    }
+
+    /// The config file governing `path`: the first one found in it or its ancestors
+    pub fn find(path: &Path) -> Option<PathBuf> {
+        let start = if path.is_file() { path.parent()? } else { path };
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        start.ancestors().find_map(|dir| {
+            Self::FILE_NAMES
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|candidate| candidate.is_file())
+        })
+    }
+
+    /// Load the config governing `path`, or `None` when there is none
+    pub fn discover(path: &Path) -> Result<Option<Self>> {
+        Self::find(path).map(|file| Self::load(&file)).transpose()
+    }
+
+    /// Parsed `languages`; names were validated by `load`
+    pub fn languages(&self) -> Vec<Language> {
+        self.languages
+            .iter()
+            .filter_map(|language| language.parse().ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_discover_from_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("src/deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(ProjectConfig::discover(&nested).unwrap(), None);
+
+        std::fs::write(
+            temp_dir.path().join("cs.toml"),
+            "top_k = 20\nlanguages = [\"rust\", \"py\"]\n\n[fusion]\nsemantic = 2.0\n",
+        )
+        .unwrap();
+        let config = ProjectConfig::discover(&nested).unwrap().unwrap();
+        assert_eq!(config.top_k, Some(20));
+        assert_eq!(config.model, None);
+        assert_eq!(config.languages(), vec![Language::Rust, Language::Python]);
+        let fusion = config.fusion.unwrap();
+        assert_eq!(fusion.semantic, 2.0);
+        assert_eq!(fusion.regex, 1.0);
+        assert_eq!(config.chunk_tokens, None);
+
+        std::fs::write(
+            temp_dir.path().join("cs.toml"),
+            "chunk_tokens = 600\nchunk_overlap = 120\nstride = false\n",
+        )
+        .unwrap();
+        let config = ProjectConfig::discover(&nested).unwrap().unwrap();
+        assert_eq!(
+            (config.chunk_tokens, config.chunk_overlap, config.stride),
+            (Some(600), Some(120), Some(false))
+        );
+
+        std::fs::write(
+            temp_dir.path().join("cs.toml"),
+            "embed_template = \"{path} :: {code}\"\n",
+        )
+        .unwrap();
+        let config = ProjectConfig::discover(&nested).unwrap().unwrap();
+        assert_eq!(config.embed_template.unwrap().as_str(), "{path} :: {code}");
+        std::fs::write(
+            temp_dir.path().join("cs.toml"),
+            "embed_template = \"{path}\"\n",
+        )
+        .unwrap();
+        assert!(ProjectConfig::discover(&nested).is_err());
+
+        std::fs::write(temp_dir.path().join("cs.toml"), "sparse = true\n").unwrap();
+        let config = ProjectConfig::discover(&nested).unwrap().unwrap();
+        assert_eq!(config.sparse, Some(true));
+
+        std::fs::write(
+            temp_dir.path().join("cs.toml"),
+            "[index_backend]\nquantization = \"int8\"\n",
+        )
+        .unwrap();
+        let backend = ProjectConfig::discover(&nested)
+            .unwrap()
+            .unwrap()
+            .index_backend
+            .unwrap();
+        assert_eq!(backend.quantization, cs_core::Quantization::Int8);
+        assert_eq!(backend.rerank_factor, 4);
+        std::fs::write(
+            temp_dir.path().join("cs.toml"),
+            "[index_backend]\nquantization = \"pq\"\n",
+        )
+        .unwrap();
+        assert!(ProjectConfig::discover(&nested).is_err());
+
+        std::fs::write(temp_dir.path().join("cs.toml"), "[gc]\nmax_size_mb = 100\n").unwrap();
+        let gc = ProjectConfig::discover(&nested)
+            .unwrap()
+            .unwrap()
+            .gc
+            .unwrap();
+        assert_eq!((gc.max_size_mb, gc.max_age_days), (100, 30));
+
+        std::fs::write(temp_dir.path().join("cs.toml"), "languages = [\"cobol\"]\n").unwrap();
+        assert!(ProjectConfig::discover(&nested).is_err());
+        std::fs::write(temp_dir.path().join("cs.toml"), "topk = 5\n").unwrap();
+        assert!(ProjectConfig::discover(&nested).is_err());
+    }
+}
@@ -5,6 +5,7 @@ use std::path::PathBuf;
 /// User-level configuration stored in system config directory
 /// Location: ~/.config/cs/config.toml (Linux/macOS) or %APPDATA%\cs\config.toml (Windows)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UserConfig {
     // Model configuration - Hybrid Strategy
     /// Model to use for indexing (default: jina-v4 for large file support)
@@ -20,7 +21,7 @@ pub struct UserConfig {
     /// Default similarity threshold
     pub default_threshold: f32,
 
-    /// Default search mode: "regex", "sem", "lex", or "hybrid"
+    /// Default search mode: "regex", "sem", "lex", "hybrid", or "auto" (pick from the query shape)
     pub default_search_mode: String,
 
     // Output formatting
@@ -40,6 +41,39 @@ pub struct UserConfig {
     /// Reranking model to use ("jina" or "bge")
     pub rerank_model: String,
 
+    // Embedding runtime
+    /// Embedding model used when neither --model nor the project's cs.toml names one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+
+    /// File with `NAME=value` lines (e.g. `JINA_API_KEY=...`), read when the variable is unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_keys_file: Option<PathBuf>,
+
+    /// Directory for downloaded embedding and reranker models
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Device for local models: "cpu", "cuda", "coreml", or "directml" (falls back to CPU)
+    pub device: String,
+
+    // MCP server
+    /// Directories `cs --serve` tool calls may name; empty allows any path
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mcp_roots: Vec<PathBuf>,
+
+    /// Refuse MCP tools that change an index, and search indexes as they are
+    pub mcp_read_only: bool,
+
+    /// Searches each MCP client may start per minute (0 = unlimited)
+    pub mcp_rate_limit: u32,
+
+    /// MCP searches running at once; more wait their turn (0 = unlimited)
+    pub mcp_max_concurrent: usize,
+
+    /// Seconds an MCP search may take before returning partial results (0 = no limit)
+    pub mcp_timeout_secs: u64,
+
     // Other preferences
     /// Quiet mode (suppress status messages)
     pub quiet_mode: bool,
@@ -67,6 +101,19 @@ impl Default for UserConfig {
             rerank_enabled: false,
             rerank_model: "jina".to_string(),
 
+            // Embedding runtime defaults
+            default_model: None,
+            api_keys_file: None,
+            cache_dir: None,
+            device: "cpu".to_string(),
+
+            // MCP server defaults
+            mcp_roots: Vec::new(),
+            mcp_read_only: false,
+            mcp_rate_limit: 120,
+            mcp_max_concurrent: 2,
+            mcp_timeout_secs: 0,
+
             // Other defaults
             quiet_mode: false,
         }
@@ -74,6 +121,9 @@ impl Default for UserConfig {
 }
 
 impl UserConfig {
+    /// Accepted values of `device`
+    pub const DEVICES: [&'static str; 4] = ["cpu", "cuda", "coreml", "directml"];
+
     /// Get the system configuration directory for cc
     pub fn config_dir() -> Result<PathBuf> {
         directories::ProjectDirs::from("", "", "cs")
@@ -134,6 +184,25 @@ impl UserConfig {
             }
             "rerank-enabled" | "rerank_enabled" => Some(self.rerank_enabled.to_string()),
             "rerank-model" | "rerank_model" => Some(self.rerank_model.clone()),
+            "default-model" | "default_model" => {
+                Some(self.default_model.clone().unwrap_or_default())
+            }
+            "api-keys-file" | "api_keys_file" => Some(display_path(&self.api_keys_file)),
+            "cache-dir" | "cache_dir" => Some(display_path(&self.cache_dir)),
+            "device" => Some(self.device.clone()),
+            "mcp-roots" | "mcp_roots" => Some(
+                self.mcp_roots
+                    .iter()
+                    .map(|root| root.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            "mcp-read-only" | "mcp_read_only" => Some(self.mcp_read_only.to_string()),
+            "mcp-rate-limit" | "mcp_rate_limit" => Some(self.mcp_rate_limit.to_string()),
+            "mcp-max-concurrent" | "mcp_max_concurrent" => {
+                Some(self.mcp_max_concurrent.to_string())
+            }
+            "mcp-timeout-secs" | "mcp_timeout_secs" => Some(self.mcp_timeout_secs.to_string()),
             "quiet-mode" | "quiet_mode" => Some(self.quiet_mode.to_string()),
             _ => None,
         }
@@ -163,9 +232,9 @@ impl UserConfig {
                 Ok(())
             }
             "default-search-mode" | "default_search_mode" => {
-                if !["regex", "sem", "lex", "hybrid"].contains(&value) {
+                if !["regex", "sem", "lex", "hybrid", "auto"].contains(&value) {
                     return Err(anyhow::anyhow!(
-                        "Invalid search mode: {}. Must be one of: regex, sem, lex, hybrid",
+                        "Invalid search mode: {}. Must be one of: regex, sem, lex, hybrid, auto",
                         value
                     ));
                 }
@@ -214,6 +283,69 @@ impl UserConfig {
                 self.rerank_model = value.to_string();
                 Ok(())
             }
+            "default-model" | "default_model" => {
+                // An empty value goes back to the built-in default
+                if value.is_empty() {
+                    self.default_model = None;
+                    return Ok(());
+                }
+                crate::ModelRegistry::default().resolve(Some(value))?;
+                self.default_model = Some(value.to_string());
+                Ok(())
+            }
+            "api-keys-file" | "api_keys_file" => {
+                self.api_keys_file = optional_path(value);
+                Ok(())
+            }
+            "cache-dir" | "cache_dir" => {
+                self.cache_dir = optional_path(value);
+                Ok(())
+            }
+            "device" => {
+                if !Self::DEVICES.contains(&value) {
+                    return Err(anyhow::anyhow!(
+                        "Invalid device: {}. Must be one of: {}",
+                        value,
+                        Self::DEVICES.join(", ")
+                    ));
+                }
+                self.device = value.to_string();
+                Ok(())
+            }
+            "mcp-roots" | "mcp_roots" => {
+                // Comma-separated; an empty value allows any path again
+                self.mcp_roots = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|root| !root.is_empty())
+                    .map(PathBuf::from)
+                    .collect();
+                Ok(())
+            }
+            "mcp-read-only" | "mcp_read_only" => {
+                self.mcp_read_only = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean for mcp-read-only: {}", value))?;
+                Ok(())
+            }
+            "mcp-rate-limit" | "mcp_rate_limit" => {
+                self.mcp_rate_limit = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid number for mcp-rate-limit: {}", value))?;
+                Ok(())
+            }
+            "mcp-max-concurrent" | "mcp_max_concurrent" => {
+                self.mcp_max_concurrent = value.parse().map_err(|_| {
+                    anyhow::anyhow!("Invalid number for mcp-max-concurrent: {}", value)
+                })?;
+                Ok(())
+            }
+            "mcp-timeout-secs" | "mcp_timeout_secs" => {
+                self.mcp_timeout_secs = value.parse().map_err(|_| {
+                    anyhow::anyhow!("Invalid number for mcp-timeout-secs: {}", value)
+                })?;
+                Ok(())
+            }
             "quiet-mode" | "quiet_mode" => {
                 self.quiet_mode = value
                     .parse()
@@ -225,6 +357,17 @@ impl UserConfig {
     }
 }
 
+fn display_path(path: &Option<PathBuf>) -> String {
+    path.as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default()
+}
+
+/// A path setting; an empty value clears it
+fn optional_path(value: &str) -> Option<PathBuf> {
+    (!value.is_empty()).then(|| PathBuf::from(value))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,6 +395,56 @@ mod tests {
 
         // Test invalid value
         assert!(config.set("default-topk", "not-a-number").is_err());
+
+        config.set("default-search-mode", "auto").unwrap();
+        assert_eq!(config.default_search_mode, "auto");
+        assert!(config.set("default-search-mode", "fuzzy").is_err());
+    }
+
+    #[test]
+    fn test_embedding_runtime_settings() {
+        let mut config = UserConfig::default();
+        assert_eq!(config.get("device"), Some("cpu".to_string()));
+        assert_eq!(config.get("default-model"), Some(String::new()));
+
+        config.set("default-model", "nomic-v1.5").unwrap();
+        config
+            .set("default-model", "BAAI/bge-small-en-v1.5")
+            .unwrap();
+        assert!(config.set("default-model", "no-such-model").is_err());
+        config.set("default-model", "").unwrap();
+        assert_eq!(config.default_model, None);
+
+        config.set("cache-dir", "/tmp/models").unwrap();
+        assert_eq!(config.cache_dir, Some(PathBuf::from("/tmp/models")));
+        config.set("device", "cuda").unwrap();
+        assert!(config.set("device", "tpu").is_err());
+
+        config
+            .set("mcp-roots", "/srv/repos, /home/dev/work")
+            .unwrap();
+        assert_eq!(
+            config.mcp_roots,
+            vec![PathBuf::from("/srv/repos"), PathBuf::from("/home/dev/work")]
+        );
+        assert_eq!(
+            config.get("mcp-roots"),
+            Some("/srv/repos,/home/dev/work".to_string())
+        );
+        config.set("mcp-read-only", "true").unwrap();
+        assert!(config.mcp_read_only);
+        assert!(config.set("mcp-read-only", "yes").is_err());
+        assert_eq!(config.get("mcp-rate-limit"), Some("120".to_string()));
+        config.set("mcp-max-concurrent", "4").unwrap();
+        assert_eq!(config.mcp_max_concurrent, 4);
+        config.set("mcp-timeout-secs", "10").unwrap();
+        assert_eq!(config.mcp_timeout_secs, 10);
+        assert!(config.set("mcp-rate-limit", "-1").is_err());
+
+        // Files written before these settings existed still load
+        let old: UserConfig = toml::from_str("index_model = \"bge-small\"\n").unwrap();
+        assert_eq!(old.device, "cpu");
+        assert_eq!(old.default_topk, 10);
     }
 
     #[test]
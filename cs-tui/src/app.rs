@@ -1,4 +1,4 @@
-use crate::colors::DEBOUNCE_MS;
+use crate::colors::{DEBOUNCE_MS, DEFAULT_TOP_K, TOP_K_STEP};
 use crate::commands::{execute_command, show_chunks};
 use crate::config::{PreviewMode, TuiConfig};
 use crate::events::UiEvent;
@@ -7,6 +7,7 @@ use crate::preview::{
 };
 use crate::rendering::{draw_preview, draw_query_input, draw_results_list, draw_status_bar};
 use crate::state::{PreviewCache, TuiState};
+use crate::utils::format_threshold;
 use anyhow::Result;
 use cs_core::{SearchMode, SearchOptions};
 use cs_index::get_index_stats;
@@ -28,6 +29,7 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
 pub struct TuiApp {
     pub state: TuiState,
@@ -37,7 +39,8 @@ pub struct TuiApp {
     progress_tx: UnboundedSender<UiEvent>,
     progress_rx: UnboundedReceiver<UiEvent>,
     current_generation: u64,
-    active_search: Option<JoinHandle<()>>,
+    /// The running search and the token that stops its indexing between files
+    active_search: Option<(JoinHandle<()>, CancellationToken)>,
 }
 
 impl TuiApp {
@@ -50,12 +53,16 @@ impl TuiApp {
             state: TuiState {
                 query: query.clone(),
                 mode: config.search_mode.clone(),
+                top_k: DEFAULT_TOP_K,
+                threshold: default_threshold(&config.search_mode),
+                results_mode: config.search_mode.clone(),
                 results: Vec::new(),
                 selected_idx: 0,
                 preview_content: String::new(),
                 preview_lines: Vec::new(),
                 preview_mode: config.preview_mode.clone(),
                 full_file_mode: config.full_file_mode,
+                group_by_file: config.group_by_file,
                 scroll_offset: 0,
                 status_message: "Ready. Type to search...".to_string(),
                 search_path,
@@ -106,6 +113,7 @@ impl TuiApp {
 
         // Main event loop
         let result = self.event_loop(&mut terminal).await;
+        self.cancel_active_search();
 
         // Restore terminal
         disable_raw_mode()?;
@@ -158,6 +166,14 @@ impl TuiApp {
                         // Ctrl+F: Toggle snippet/full file
                         self.toggle_full_file_mode();
                     }
+                    KeyCode::Char('g') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                        // Ctrl+G: Toggle grouping results by file
+                        self.toggle_group_by_file();
+                    }
+                    KeyCode::Char('o') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                        // Ctrl+O: Open in $EDITOR, then come back to the results
+                        self.open_selected_and_resume(terminal)?;
+                    }
                     KeyCode::Char('d') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                         // Ctrl+D: Show chunk metadata
                         show_chunks(&mut self.state);
@@ -167,9 +183,27 @@ impl TuiApp {
                         self.toggle_select();
                     }
                     KeyCode::Tab => {
-                        self.cycle_mode();
+                        self.cycle_mode(true);
                         self.trigger_search();
                     }
+                    KeyCode::BackTab => {
+                        self.cycle_mode(false);
+                        self.trigger_search();
+                    }
+                    KeyCode::Left if key.modifiers.contains(event::KeyModifiers::ALT) => {
+                        // Alt+Left/Right: Lower/raise the score threshold
+                        self.adjust_threshold(false);
+                    }
+                    KeyCode::Right if key.modifiers.contains(event::KeyModifiers::ALT) => {
+                        self.adjust_threshold(true);
+                    }
+                    KeyCode::Up if key.modifiers.contains(event::KeyModifiers::ALT) => {
+                        // Alt+Up/Down: More/fewer results
+                        self.adjust_top_k(true);
+                    }
+                    KeyCode::Down if key.modifiers.contains(event::KeyModifiers::ALT) => {
+                        self.adjust_top_k(false);
+                    }
                     KeyCode::Up if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                         // Ctrl+Up: Navigate search history
                         self.history_previous();
@@ -259,22 +293,53 @@ impl TuiApp {
             search_mode: self.state.mode.clone(),
             preview_mode: self.state.preview_mode.clone(),
             full_file_mode: self.state.full_file_mode,
+            group_by_file: self.state.group_by_file,
         };
         let _ = config.save(); // Silently ignore errors
     }
 
-    fn cycle_mode(&mut self) {
-        self.state.mode = match self.state.mode {
-            SearchMode::Semantic => SearchMode::Regex,
-            SearchMode::Regex => SearchMode::Hybrid,
-            SearchMode::Hybrid => SearchMode::Semantic,
-            SearchMode::Lexical => SearchMode::Semantic, // Skip lexical for now
-            SearchMode::Ast => SearchMode::Semantic, // Skip AST for now
+    /// Step through semantic, regex, lexical and hybrid (backwards with Shift+Tab)
+    fn cycle_mode(&mut self, forward: bool) {
+        self.state.mode = match (&self.state.mode, forward) {
+            (SearchMode::Semantic, true) | (SearchMode::Lexical, false) => SearchMode::Regex,
+            (SearchMode::Regex, true) | (SearchMode::Hybrid, false) => SearchMode::Lexical,
+            (SearchMode::Lexical, true) | (SearchMode::Semantic, false) => SearchMode::Hybrid,
+            (SearchMode::Hybrid, true) | (SearchMode::Regex, false) => SearchMode::Semantic,
+            (SearchMode::Ast | SearchMode::Symbol, _) => SearchMode::Semantic, // Not offered in the TUI
         };
+        // Scores are on a different scale in each mode
+        self.state.threshold = default_threshold(&self.state.mode);
         self.state.status_message = format!("Switched to {:?} mode", self.state.mode);
         self.save_config();
     }
 
+    fn adjust_threshold(&mut self, raise: bool) {
+        let step = threshold_step(&self.state.mode);
+        let current = self.state.threshold.unwrap_or(0.0);
+        let next = if raise {
+            current + step
+        } else {
+            current - step
+        };
+        // Half a step guards against float drift leaving a near-zero threshold
+        self.state.threshold = (next > step / 2.0).then_some(next);
+        self.state.status_message = match self.state.threshold {
+            Some(threshold) => format!("Threshold ≥ {}", format_threshold(threshold)),
+            None => "Threshold off".to_string(),
+        };
+        self.trigger_search();
+    }
+
+    fn adjust_top_k(&mut self, more: bool) {
+        self.state.top_k = if more {
+            self.state.top_k + TOP_K_STEP
+        } else {
+            self.state.top_k.saturating_sub(TOP_K_STEP).max(TOP_K_STEP)
+        };
+        self.state.status_message = format!("Top {} results", self.state.top_k);
+        self.trigger_search();
+    }
+
     fn cycle_preview_mode(&mut self) {
         self.state.preview_mode = match self.state.preview_mode {
             PreviewMode::Heatmap => PreviewMode::Syntax,
@@ -288,7 +353,7 @@ impl TuiApp {
 
     fn toggle_full_file_mode(&mut self) {
         self.state.full_file_mode = !self.state.full_file_mode;
-        self.state.scroll_offset = 0; // Reset scroll when toggling
+        self.scroll_to_selected();
         self.update_preview();
         let mode_text = if self.state.full_file_mode {
             "Full File"
@@ -299,6 +364,30 @@ impl TuiApp {
         self.save_config();
     }
 
+    fn toggle_group_by_file(&mut self) {
+        self.state.group_by_file = !self.state.group_by_file;
+        if self.state.group_by_file {
+            cs_core::file_rank::group_by_file(&mut self.state.results);
+        } else {
+            // Back to plain ranking order
+            self.state
+                .results
+                .sort_by(|a, b| b.score.total_cmp(&a.score));
+        }
+        self.state.selected_idx = 0;
+        if !self.state.results.is_empty() {
+            self.list_state.select(Some(0));
+        }
+        self.scroll_to_selected();
+        self.update_preview();
+        self.state.status_message = if self.state.group_by_file {
+            "Results grouped by file".to_string()
+        } else {
+            "Results in rank order".to_string()
+        };
+        self.save_config();
+    }
+
     fn scroll_up(&mut self) {
         if self.state.full_file_mode && self.state.scroll_offset > 0 {
             self.state.scroll_offset = self.state.scroll_offset.saturating_sub(10);
@@ -363,7 +452,7 @@ impl TuiApp {
             self.handle_progress_event(event);
         }
 
-        if let Some(handle) = self.active_search.as_ref()
+        if let Some((handle, _)) = self.active_search.as_ref()
             && handle.is_finished()
         {
             self.active_search = None;
@@ -412,6 +501,7 @@ impl TuiApp {
             UiEvent::SearchCompleted {
                 generation,
                 results,
+                mode,
                 summary,
                 query,
             } => {
@@ -427,8 +517,12 @@ impl TuiApp {
                 self.state.last_indexing_update = None;
                 self.state.selected_files.clear();
                 self.state.results = results;
+                self.state.results_mode = mode;
+                if self.state.group_by_file {
+                    cs_core::file_rank::group_by_file(&mut self.state.results);
+                }
                 self.state.selected_idx = 0;
-                self.state.scroll_offset = 0;
+                self.scroll_to_selected();
                 if self.state.results.is_empty() {
                     self.list_state.select(None);
                 } else {
@@ -510,9 +604,7 @@ impl TuiApp {
         }
 
         // Cancel any in-flight search task and advance the generation counter.
-        if let Some(handle) = self.active_search.take() {
-            handle.abort();
-        }
+        self.cancel_active_search();
         self.current_generation = self.current_generation.wrapping_add(1);
         let generation = self.current_generation;
 
@@ -537,14 +629,6 @@ impl TuiApp {
 
         terminal.draw(|f| self.draw(f))?;
 
-        let threshold = match self.state.mode {
-            SearchMode::Semantic => Some(0.6),
-            SearchMode::Hybrid => None,
-            SearchMode::Regex => None,
-            SearchMode::Lexical => None,
-            SearchMode::Ast => None,
-        };
-
         // Use the centralized pattern builder from cs-core
         let exclude_patterns = cs_core::build_exclude_patterns(
             Some(&self.state.search_path),
@@ -556,12 +640,15 @@ impl TuiApp {
         let options = SearchOptions {
             mode: self.state.mode.clone(),
             query: self.state.query.clone(),
+            extra_patterns: Vec::new(),
             path: self.state.search_path.clone(),
-            top_k: Some(50),
-            threshold,
+            top_k: Some(self.state.top_k),
+            threshold: self.state.threshold,
             case_insensitive: false,
             whole_word: false,
             fixed_string: false,
+            pcre2: false,
+            multiline: false,
             line_numbers: true,
             context_lines: 0,
             before_context_lines: 0,
@@ -572,15 +659,50 @@ impl TuiApp {
             no_snippet: false,
             reindex: false,
             show_scores: true,
+            raw_scores: false,
             show_filenames: true,
+            group_by_file: self.state.group_by_file,
+            vimgrep: false,
+            syntax_highlight: false,
+            file_limits: Default::default(),
             files_with_matches: false,
             files_without_matches: false,
+            count: false,
+            first_match: false,
+            stats: None,
+            within: None,
+            files_ranked: None,
+            invert_match: false,
+            only_matching: false,
+            replace: None,
+            search_zip: false,
+            only_chunk_types: Vec::new(),
+            exclude_tests: false,
+            ephemeral: false,
+            hybrid_lexical: false,
+            expand_query: false,
+            explain: false,
+            languages: Vec::new(),
+            fusion_weights: Default::default(),
+            requester: Some("tui".to_string()),
+            timeout: None,
             exclude_patterns,
             include_patterns: Vec::new(),
             respect_gitignore: true,
             full_section: false,
             rerank: false,
             rerank_model: None,
+            rerank_candidates: None,
+            rerank_blend: None,
+            diversity: None,
+            sparse_weight: None,
+            stride_pooling: cs_core::StridePooling::default(),
+            dedupe: cs_core::Dedupe::default(),
+            negative_queries: Vec::new(),
+            negative_weight: None,
+            excluded_span: None,
+            offset: 0,
+            index_epoch: None,
             embedding_model: None,
             ast_pattern: None,
             ast_lang: None,
@@ -590,9 +712,12 @@ impl TuiApp {
 
         let progress_tx = self.progress_tx.clone();
         let started_at = Instant::now();
+        let cancel = CancellationToken::new();
+        let task_cancel = cancel.clone();
 
         let handle = tokio::spawn(async move {
             let query_for_history = options.query.clone();
+            let mode = options.mode.clone();
             let search_progress_sender = progress_tx.clone();
             let detailed_sender = progress_tx.clone();
             let completion_sender = progress_tx.clone();
@@ -644,11 +769,12 @@ impl TuiApp {
                     }
                 });
 
-            let result = cs_engine::search_enhanced_with_indexing_progress(
+            let result = cs_engine::search_enhanced_cancellable(
                 &options,
                 Some(search_progress_callback),
                 None, // Skip basic callback - only use detailed callback to avoid flashing
                 Some(detailed_indexing_progress_callback),
+                &task_cancel,
             )
             .await;
 
@@ -667,6 +793,7 @@ impl TuiApp {
                     let _ = completion_sender.send(UiEvent::SearchCompleted {
                         generation,
                         results: search_results.matches,
+                        mode,
                         summary,
                         query: query_for_history,
                     });
@@ -682,26 +809,27 @@ impl TuiApp {
             let _ = detailed_sender.send(UiEvent::IndexingDone { generation });
         });
 
-        self.active_search = Some(handle);
+        self.active_search = Some((handle, cancel));
 
         Ok(())
     }
 
+    /// Stop the running search. Aborting the task alone would not stop an index update, which
+    /// runs without yielding, so the token is cancelled as well.
+    fn cancel_active_search(&mut self) {
+        if let Some((handle, cancel)) = self.active_search.take() {
+            cancel.cancel();
+            handle.abort();
+        }
+    }
+
     fn next_result(&mut self) {
         if self.state.results.is_empty() {
             return;
         }
         self.state.selected_idx = (self.state.selected_idx + 1) % self.state.results.len();
         self.list_state.select(Some(self.state.selected_idx));
-
-        // In full file mode, reset scroll to show the matched chunk
-        if self.state.full_file_mode
-            && let Some(result) = self.state.results.get(self.state.selected_idx)
-        {
-            // Position scroll so matched line is near the top (but with some context above)
-            self.state.scroll_offset = result.span.line_start.saturating_sub(6);
-        }
-
+        self.scroll_to_selected();
         self.update_preview();
     }
 
@@ -715,18 +843,19 @@ impl TuiApp {
             self.state.selected_idx -= 1;
         }
         self.list_state.select(Some(self.state.selected_idx));
-
-        // In full file mode, reset scroll to show the matched chunk
-        if self.state.full_file_mode
-            && let Some(result) = self.state.results.get(self.state.selected_idx)
-        {
-            // Position scroll so matched line is near the top (but with some context above)
-            self.state.scroll_offset = result.span.line_start.saturating_sub(6);
-        }
-
+        self.scroll_to_selected();
         self.update_preview();
     }
 
+    /// In full file mode, scroll the preview so the selected result's span is near the top
+    fn scroll_to_selected(&mut self) {
+        self.state.scroll_offset = match self.state.results.get(self.state.selected_idx) {
+            // Keep some context above the matched line
+            Some(result) if self.state.full_file_mode => result.span.line_start.saturating_sub(6),
+            _ => 0,
+        };
+    }
+
     fn update_preview(&mut self) {
         // Guard against empty results or invalid index
         if self.state.results.is_empty() {
@@ -855,6 +984,14 @@ impl TuiApp {
             let score = result.score;
             let match_line = result.span.line_start;
             let query = self.state.query.clone();
+            // Every result in this file, for the score gutter of the chunk view
+            let result_scores: Vec<(usize, f32)> = self
+                .state
+                .results
+                .iter()
+                .filter(|other| other.file == result.file)
+                .map(|other| (other.span.line_start, other.score))
+                .collect();
 
             self.state.preview_lines = match self.state.preview_mode {
                 PreviewMode::Heatmap => render_heatmap_preview(
@@ -884,6 +1021,7 @@ impl TuiApp {
                     chunk_meta.as_ref(),
                     is_pdf,
                     &chunk_spans,
+                    &result_scores,
                     self.state.full_file_mode,
                     self.state.preview_mode == PreviewMode::Chunks,
                 ),
@@ -896,13 +1034,42 @@ impl TuiApp {
     }
 
     fn open_selected(&self) -> Result<()> {
+        if self.launch_editor()?.is_some() {
+            // Don't re-enable raw mode - just exit
+            std::process::exit(0);
+        }
+        Ok(())
+    }
+
+    /// Like Enter, but restore the TUI once the editor exits instead of quitting
+    fn open_selected_and_resume<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let Some(success) = self.launch_editor()? else {
+            return Ok(());
+        };
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        terminal.clear()?;
+        self.state.status_message = if success {
+            "Back from editor".to_string()
+        } else {
+            "Editor exited with error".to_string()
+        };
+        // The file may have changed while it was open
+        self.state.preview_cache = None;
+        self.update_preview();
+        Ok(())
+    }
+
+    /// Leave the alternate screen and run $EDITOR on the selected files at their matched
+    /// lines; `None` when there is nothing to open, else whether the editor succeeded
+    fn launch_editor(&self) -> Result<Option<bool>> {
         // Collect files to open (selected files or current result)
         let files_to_open: Vec<(PathBuf, usize)> = if self.state.selected_files.is_empty() {
             // No files selected, open current result
             if let Some(result) = self.state.results.get(self.state.selected_idx) {
                 vec![(result.file.clone(), result.span.line_start)]
             } else {
-                return Ok(());
+                return Ok(None);
             }
         } else {
             // Open all selected files at their first match line
@@ -920,7 +1087,7 @@ impl TuiApp {
         };
 
         if files_to_open.is_empty() {
-            return Ok(());
+            return Ok(None);
         }
 
         let editor = std::env::var("EDITOR")
@@ -989,8 +1156,23 @@ impl TuiApp {
         if !status.success() {
             eprintln!("Editor exited with error");
         }
+        Ok(Some(status.success()))
+    }
+}
+
+/// Threshold a mode starts with; only semantic scores are comparable enough across queries
+fn default_threshold(mode: &SearchMode) -> Option<f32> {
+    match mode {
+        SearchMode::Semantic => Some(0.6),
+        _ => None,
+    }
+}
 
-        // Don't re-enable raw mode - just exit
-        std::process::exit(0);
+/// Threshold change per keypress: fused hybrid scores are small and BM25 scores unbounded
+fn threshold_step(mode: &SearchMode) -> f32 {
+    match mode {
+        SearchMode::Hybrid => 0.002,
+        SearchMode::Lexical => 0.5,
+        _ => 0.05,
     }
 }
@@ -18,3 +18,5 @@ pub const COLOR_CHUNK_LINE_NUM: Color = Color::Rgb(255, 215, 0); // Gold - highl
 
 pub const SPINNER_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
 pub const DEBOUNCE_MS: u64 = 300;
+pub const DEFAULT_TOP_K: usize = 50;
+pub const TOP_K_STEP: usize = 10;
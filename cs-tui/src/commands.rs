@@ -52,21 +52,26 @@ fn show_help(state: &mut TuiState) {
         "".to_string(),
         "━━━ KEYBINDINGS ━━━".to_string(),
         "".to_string(),
-        "  Tab              - Cycle search modes (SEM/REG/HYB)".to_string(),
+        "  Tab, Shift+Tab   - Cycle search modes (SEM/REG/LEX/HYB)".to_string(),
+        "  Alt+Left/Right   - Lower/raise the score threshold".to_string(),
+        "  Alt+Up/Down      - More/fewer results (top-k)".to_string(),
         "  Ctrl+V           - Cycle preview modes (Heatmap/Syntax/Chunks)".to_string(),
         "  Ctrl+F           - Toggle snippet/full file view".to_string(),
+        "  Ctrl+G           - Group results under a heading per file".to_string(),
         "  Ctrl+D           - Show chunk metadata (debug)".to_string(),
         "  Ctrl+Space       - Multi-select files".to_string(),
         "  Ctrl+Up/Down     - Navigate search history".to_string(),
         "  Up/Down          - Navigate results".to_string(),
         "  PgUp/PgDn        - Scroll preview".to_string(),
         "  Enter            - Open in $EDITOR".to_string(),
+        "  Ctrl+O           - Open in $EDITOR, then return here".to_string(),
         "  Esc, q, Ctrl+C   - Quit".to_string(),
         "".to_string(),
         "━━━ SEARCH MODES ━━━".to_string(),
         "".to_string(),
         "  SEM - Semantic: Find code by meaning".to_string(),
         "  REG - Regex: Pattern matching".to_string(),
+        "  LEX - Lexical: BM25 keyword ranking".to_string(),
         "  HYB - Hybrid: Combined semantic + regex".to_string(),
         "".to_string(),
         "━━━ PREVIEW MODES ━━━".to_string(),
@@ -283,7 +288,7 @@ fn load_chunk_spans(repo_root: &Path, file_path: &Path) -> Result<Vec<IndexedChu
         .iter()
         .map(|chunk| IndexedChunkMeta {
             span: chunk.span.clone(),
-            chunk_type: chunk.chunk_type.clone(),
+            chunk_type: chunk.chunk_type_name().map(str::to_string),
             breadcrumb: chunk.breadcrumb.clone(),
             ancestry: chunk.ancestry.clone().unwrap_or_default(),
             estimated_tokens: chunk.estimated_tokens,
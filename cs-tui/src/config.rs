@@ -16,6 +16,9 @@ pub struct TuiConfig {
     pub search_mode: SearchMode,
     pub preview_mode: PreviewMode,
     pub full_file_mode: bool,
+    /// Results listed under a heading per file (Ctrl+G)
+    #[serde(default)]
+    pub group_by_file: bool,
 }
 
 mod search_mode_serde {
@@ -31,6 +34,7 @@ mod search_mode_serde {
             SearchMode::Hybrid => "hybrid",
             SearchMode::Lexical => "lexical",
             SearchMode::Ast => "ast",
+            SearchMode::Symbol => "symbol",
         };
         serializer.serialize_str(s)
     }
@@ -46,6 +50,7 @@ mod search_mode_serde {
             "hybrid" => SearchMode::Hybrid,
             "lexical" => SearchMode::Lexical,
             "ast" => SearchMode::Ast,
+            "symbol" => SearchMode::Symbol,
             _ => SearchMode::Semantic, // Default fallback
         })
     }
@@ -57,6 +62,7 @@ impl Default for TuiConfig {
             search_mode: SearchMode::Semantic,
             preview_mode: PreviewMode::Heatmap,
             full_file_mode: true,
+            group_by_file: false,
         }
     }
 }
@@ -1,4 +1,4 @@
-use cs_core::SearchResult;
+use cs_core::{SearchMode, SearchResult};
 
 #[derive(Debug)]
 pub enum UiEvent {
@@ -17,6 +17,7 @@ pub enum UiEvent {
     SearchCompleted {
         generation: u64,
         results: Vec<SearchResult>,
+        mode: SearchMode,
         summary: String,
         query: String,
     },
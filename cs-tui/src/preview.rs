@@ -3,17 +3,21 @@ use crate::chunks::{
 };
 use crate::colors::*;
 use crate::utils::{
-    apply_heatmap_color_to_token, calculate_token_similarity, find_repo_root, split_into_tokens,
-    syntax_set, theme_set,
+    apply_heatmap_color_to_token, calculate_token_similarity, find_repo_root, score_to_color,
+    split_into_tokens, syntax_set, theme_set,
 };
 use cs_core::pdf;
 use cs_index::load_index_entry;
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use syntect::easy::HighlightLines;
 
+/// Width of the score gutter in the chunk view, e.g. `0.812 `
+const SCORE_GUTTER_WIDTH: usize = 6;
+
 pub fn load_preview_lines(
     path: &Path,
 ) -> Result<(Vec<String>, bool, Vec<IndexedChunkMeta>), String> {
@@ -36,8 +40,11 @@ pub fn load_preview_lines(
         let lines: Vec<String> = content.lines().map(|line| line.to_string()).collect();
         (content, lines)
     } else {
-        let content = fs::read_to_string(&resolved_path)
+        let bytes = fs::read(&resolved_path)
             .map_err(|err| format!("Could not read {}: {}", resolved_path.display(), err))?;
+        // Show files with a few invalid UTF-8 bytes instead of failing the whole preview;
+        // files in other encodings are transcoded
+        let content = cs_core::encoding::decode(&bytes).into_owned();
         let lines: Vec<String> = content.lines().map(|line| line.to_string()).collect();
         (content, lines)
     };
@@ -87,7 +94,7 @@ fn load_chunk_spans(repo_root: &Path, file_path: &Path) -> Result<Vec<IndexedChu
         .iter()
         .map(|chunk| IndexedChunkMeta {
             span: chunk.span.clone(),
-            chunk_type: chunk.chunk_type.clone(),
+            chunk_type: chunk.chunk_type_name().map(str::to_string),
             breadcrumb: chunk.breadcrumb.clone(),
             ancestry: chunk.ancestry.clone().unwrap_or_default(),
             estimated_tokens: chunk.estimated_tokens,
@@ -277,6 +284,7 @@ pub fn render_chunks_preview(
     chunk_meta: Option<&IndexedChunkMeta>,
     is_pdf: bool,
     all_chunks: &[IndexedChunkMeta],
+    result_scores: &[(usize, f32)],
     full_file_mode: bool,
     disable_match_highlighting: bool,
 ) -> Vec<Line<'static>> {
@@ -341,6 +349,7 @@ PDF chunk (approximate)
         match_line,
         chunk_meta,
         all_chunks,
+        result_scores,
         full_file_mode,
         disable_match_highlighting,
     ));
@@ -356,6 +365,7 @@ pub fn build_chunk_lines(
     match_line: usize,
     chunk_meta: Option<&IndexedChunkMeta>,
     all_chunks: &[IndexedChunkMeta],
+    result_scores: &[(usize, f32)],
     full_file_mode: bool,
     disable_match_highlighting: bool,
 ) -> Vec<Line<'static>> {
@@ -363,6 +373,18 @@ pub fn build_chunk_lines(
     let max_line_num = lines.len();
     let line_num_width = max_line_num.to_string().len() + 1; // +1 for spacing
 
+    // Score gutter: the best score of the results starting on each line
+    let mut line_scores: HashMap<usize, f32> = HashMap::new();
+    for &(line, score) in result_scores {
+        let best = line_scores.entry(line).or_insert(score);
+        *best = best.max(score);
+    }
+    let gutter_width = if line_scores.is_empty() {
+        0
+    } else {
+        SCORE_GUTTER_WIDTH
+    };
+
     collect_chunk_display_lines(
         lines,
         context_start,
@@ -383,7 +405,7 @@ pub fn build_chunk_lines(
 
             // Add indentation
             spans.push(Span::styled(
-                " ".repeat(prefix),
+                " ".repeat(gutter_width + prefix),
                 Style::default().fg(COLOR_DARK_GRAY),
             ));
 
@@ -428,6 +450,23 @@ pub fn build_chunk_lines(
         } => {
             let mut spans = Vec::new();
 
+            if gutter_width > 0 {
+                spans.push(match line_scores.get(&line_num) {
+                    Some(&score) => Span::styled(
+                        // BM25 scores can exceed 10; cut digits rather than shift the columns
+                        format!(
+                            "{:<width$.width$}",
+                            format!("{:.3}", score),
+                            width = gutter_width - 1
+                        ) + " ",
+                        Style::default()
+                            .fg(score_to_color(score))
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    None => Span::raw(" ".repeat(gutter_width)),
+                });
+            }
+
             // Always render chunk columns with fixed width
             if columns.is_empty() {
                 spans.push(Span::styled(" ", Style::default().fg(COLOR_DARK_GRAY)));
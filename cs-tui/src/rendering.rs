@@ -1,6 +1,6 @@
 use crate::colors::*;
 use crate::state::TuiState;
-use crate::utils::score_to_color;
+use crate::utils::{format_threshold, result_source, score_to_color};
 use cs_core::SearchMode;
 use ratatui::Frame;
 use ratatui::layout::Rect;
@@ -23,11 +23,16 @@ pub fn draw_query_input(f: &mut Frame, area: Rect, state: &TuiState) {
             SearchMode::Hybrid => "[HYB]",
             SearchMode::Lexical => "[LEX]",
             SearchMode::Ast => "[AST]",
+            SearchMode::Symbol => "[SYM]",
         };
+        let threshold = state
+            .threshold
+            .map(|threshold| format!(" ≥{}", format_threshold(threshold)))
+            .unwrap_or_default();
         (
             format!(
-                "Search {} (Tab to cycle, /help for commands)",
-                mode_indicator
+                "Search {} top {}{} (Tab/⇧Tab: mode, Alt+←→: threshold, Alt+↑↓: top-k, /help)",
+                mode_indicator, state.top_k, threshold
             ),
             Style::default().fg(COLOR_YELLOW),
         )
@@ -40,36 +45,60 @@ pub fn draw_query_input(f: &mut Frame, area: Rect, state: &TuiState) {
 }
 
 pub fn draw_results_list(f: &mut Frame, area: Rect, state: &TuiState, list_state: &mut ListState) {
-    let items: Vec<ListItem> = state
-        .results
-        .iter()
-        .enumerate()
-        .map(|(idx, result)| {
-            let score_color = score_to_color(result.score);
-            let is_selected = state.selected_files.contains(&result.file);
-            let prefix = if is_selected { "✓ " } else { "  " };
-            let content = format!(
-                "{}[{:.3}] {}:{}",
+    let mut items: Vec<ListItem> = Vec::with_capacity(state.results.len());
+    let mut selected_row = None;
+    let mut current_file = None;
+    for (idx, result) in state.results.iter().enumerate() {
+        if state.group_by_file && current_file != Some(&result.file) {
+            // File heading, separated from the previous group by a blank row
+            if current_file.is_some() {
+                items.push(ListItem::new(""));
+            }
+            items.push(
+                ListItem::new(result.file.display().to_string())
+                    .style(Style::default().fg(COLOR_CYAN).add_modifier(Modifier::BOLD)),
+            );
+            current_file = Some(&result.file);
+        }
+
+        let score_color = score_to_color(result.score);
+        let is_selected = state.selected_files.contains(&result.file);
+        let prefix = if is_selected { "✓ " } else { "  " };
+        let source = result_source(result, &state.results_mode);
+        let content = if state.group_by_file {
+            format!(
+                "{}[{:.3}] {:<7} {}",
+                prefix, result.score, source, result.span.line_start
+            )
+        } else {
+            format!(
+                "{}[{:.3}] {:<7} {}:{}",
                 prefix,
                 result.score,
+                source,
                 result.file.display(),
                 result.span.line_start
-            );
-            let style = if idx == state.selected_idx {
-                Style::default()
-                    .fg(COLOR_BLACK)
-                    .bg(score_color)
-                    .add_modifier(Modifier::BOLD)
-            } else if is_selected {
-                Style::default()
-                    .fg(score_color)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(score_color)
-            };
-            ListItem::new(content).style(style)
-        })
-        .collect();
+            )
+        };
+        let style = if idx == state.selected_idx {
+            selected_row = Some(items.len());
+            Style::default()
+                .fg(COLOR_BLACK)
+                .bg(score_color)
+                .add_modifier(Modifier::BOLD)
+        } else if is_selected {
+            Style::default()
+                .fg(score_color)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(score_color)
+        };
+        items.push(ListItem::new(content).style(style));
+    }
+    if state.group_by_file {
+        // Headings shift the rows, so scroll to the selected result's row rather than its index
+        list_state.select(selected_row);
+    }
 
     let title = format!("Results ({}/{})", state.results.len(), state.results.len());
     let list = List::new(items)
@@ -110,7 +139,7 @@ pub fn draw_preview(f: &mut Frame, area: Rect, state: &TuiState) {
 }
 
 pub fn draw_status_bar(f: &mut Frame, area: Rect, state: &TuiState) {
-    let help_text = " ↑↓: Nav | Tab: Mode | ^V: View | ^Space: Select | Enter: Open | ^↑↓: History | Esc/q: Quit ";
+    let help_text = " ↑↓: Nav | Tab/⇧Tab: Mode | Alt+←→↑↓: Threshold/Top-k | ^V: View | ^G: Group | ^Space: Select | Enter/^O: Open | ^↑↓: History | Esc/q: Quit ";
 
     let mut status_spans = vec![Span::styled(
         state.status_message.clone(),
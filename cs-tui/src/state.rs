@@ -11,12 +11,16 @@ use std::time::Instant;
 pub struct TuiState {
     pub query: String,
     pub mode: SearchMode,
+    pub top_k: usize,             // results per search (Alt+Up/Down)
+    pub threshold: Option<f32>,   // minimum score, reset to the mode's default on mode change
+    pub results_mode: SearchMode, // mode that produced `results`
     pub results: Vec<SearchResult>,
     pub selected_idx: usize,
     pub preview_content: String,
     pub preview_lines: Vec<Line<'static>>, // Colored preview
     pub preview_mode: PreviewMode,
     pub full_file_mode: bool, // false = snippet (±5 lines), true = full file
+    pub group_by_file: bool,  // results listed under a heading per file
     pub scroll_offset: usize, // For scrolling in full file mode
     pub status_message: String,
     pub search_path: PathBuf,
@@ -1,4 +1,5 @@
 use cs_core::heatmap::{self, HeatmapBucket};
+use cs_core::{SearchMode, SearchResult};
 use ratatui::style::Color;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
@@ -17,6 +18,38 @@ pub fn theme_set() -> &'static ThemeSet {
 
 pub use heatmap::{calculate_token_similarity, split_into_tokens};
 
+/// Which search produced a result: the mode itself, or for hybrid results the ranked lists it
+/// was fused from
+pub fn result_source(result: &SearchResult, mode: &SearchMode) -> &'static str {
+    match mode {
+        SearchMode::Hybrid => {
+            let signals = result.signals.as_ref();
+            let keyword = signals.is_some_and(|signals| signals.lex_rank.is_some());
+            let semantic = signals.is_some_and(|signals| signals.vec_rank.is_some());
+            match (keyword, semantic) {
+                (true, true) => "REG+SEM",
+                (true, false) => "REG",
+                (false, true) => "SEM",
+                (false, false) => "HYB",
+            }
+        }
+        SearchMode::Semantic => "SEM",
+        SearchMode::Regex => "REG",
+        SearchMode::Lexical => "LEX",
+        SearchMode::Ast => "AST",
+        SearchMode::Symbol => "SYM",
+    }
+}
+
+/// Threshold for display, with more digits for the small fused hybrid scores
+pub fn format_threshold(threshold: f32) -> String {
+    if threshold < 0.1 {
+        format!("{:.3}", threshold)
+    } else {
+        format!("{:.2}", threshold)
+    }
+}
+
 pub fn score_to_color(score: f32) -> Color {
     match HeatmapBucket::from_score(score) {
         HeatmapBucket::Step8 => Color::Rgb(0, 255, 100),